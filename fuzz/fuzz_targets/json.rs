@@ -0,0 +1,15 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use przntr::parsing::json;
+
+// whatever is read from JSON has to be read again once written back, and to
+// be written the same the second time
+fuzz_target!(|input: &str| {
+    if let Ok(presentation) = json::parse(input) {
+        let written = json::to_json(&presentation).pretty();
+        let reread = json::parse(&written)
+            .unwrap_or_else(|error| panic!("{} in the written JSON:\n{}", error, written));
+        assert_eq!(written, json::to_json(&reread).pretty());
+    }
+});
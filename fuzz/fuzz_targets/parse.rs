@@ -0,0 +1,14 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+// whatever parses has to parse again once written back as source, and to
+// be written the same the second time
+fuzz_target!(|input: &str| {
+    if let Ok(presentation) = przntr::parse(input) {
+        let source = presentation.to_source();
+        let reparsed = przntr::parse(&source)
+            .unwrap_or_else(|errors| panic!("{:?} in the written source:\n{}", errors, source));
+        assert_eq!(source, reparsed.to_source());
+    }
+});
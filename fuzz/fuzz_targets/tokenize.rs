@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+// the tokenizer, the highlighter and the formatter have to get through any
+// text without panicking
+fuzz_target!(|input: &str| {
+    for _ in przntr::tokenize(input) {}
+    przntr::classify(input);
+    let _ = przntr::parsing::formatter::format(input);
+});
@@ -0,0 +1,290 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Downloaded files, named after a hash of their contents so that the same
+/// file served from several URLs is kept once
+const OBJECTS: &str = "objects";
+/// One file per downloaded URL, named after a hash of the URL, holding the
+/// name of the file it was downloaded to
+const URLS: &str = "urls";
+
+#[derive(Debug)]
+pub enum AssetError {
+    /// The asset hasn't been downloaded before and going online isn't allowed
+    Offline(String),
+    Download {
+        url: String,
+        message: String,
+    },
+    Io {
+        path: PathBuf,
+        error: io::Error,
+    },
+}
+
+impl fmt::Display for AssetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Offline(url) => write!(f, "`{}` is not downloaded and przntr is offline", url),
+            Self::Download { url, message } => {
+                write!(f, "could not download `{}`: {}", url, message)
+            }
+            Self::Io { path, error } => write!(f, "{}: {}", path.display(), error),
+        }
+    }
+}
+
+/// Finds the files of images, videos, fonts and themes named by URL,
+/// downloading them with `curl` into a cache the first time
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct AssetLoader {
+    cache_dir: PathBuf,
+    /// Caches that are only read from, like the one of an unpacked bundle
+    read_only_caches: Vec<PathBuf>,
+    offline: bool,
+}
+
+impl Default for AssetLoader {
+    /// Caches in `$XDG_CACHE_HOME/przntr/assets` or `~/.cache/przntr/assets`
+    fn default() -> Self {
+        let cache_home = std::env::var_os("XDG_CACHE_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))
+            .unwrap_or_else(std::env::temp_dir);
+
+        Self::new(cache_home.join("przntr").join("assets"))
+    }
+}
+
+impl AssetLoader {
+    pub fn new(cache_dir: PathBuf) -> Self {
+        Self {
+            cache_dir,
+            read_only_caches: vec![],
+            offline: false,
+        }
+    }
+
+    pub fn cache_dir(&self) -> &PathBuf {
+        &self.cache_dir
+    }
+
+    /// Looks assets up in the cache before the loader's own
+    pub fn add_read_only_cache(&mut self, cache_dir: PathBuf) {
+        self.read_only_caches.push(cache_dir);
+    }
+
+    /// Only assets downloaded before are used, nothing is downloaded
+    pub fn set_offline(&mut self, offline: bool) {
+        self.offline = offline;
+    }
+
+    /// The file the URL was downloaded to, downloading it unless a cache
+    /// already has it
+    pub fn load(&self, url: &str) -> Result<PathBuf, AssetError> {
+        if let Some(path) = self.cached(url) {
+            return Ok(path);
+        }
+        if self.offline {
+            return Err(AssetError::Offline(url.into()));
+        }
+
+        self.download(url)
+    }
+
+    /// The files in the cache directory the downloaded asset is found
+    /// through, relative to it: the asset itself and the entries of the URLs
+    /// it was downloaded from. None for files which aren't downloaded assets.
+    pub fn cache_files(&self, asset: &Path) -> Option<Vec<PathBuf>> {
+        let objects = fs::canonicalize(self.cache_dir.join(OBJECTS)).ok()?;
+        let object = asset.strip_prefix(&objects).ok()?.to_string_lossy();
+
+        let mut files = vec![Path::new(OBJECTS).join(object.as_ref())];
+        let urls = fs::read_dir(self.cache_dir.join(URLS)).ok()?;
+        files.extend(
+            urls.filter_map(Result::ok)
+                .filter(|entry| {
+                    fs::read_to_string(entry.path()).is_ok_and(|name| name.trim() == object)
+                })
+                .map(|entry| Path::new(URLS).join(entry.file_name())),
+        );
+
+        Some(files)
+    }
+
+    fn cached(&self, url: &str) -> Option<PathBuf> {
+        self.read_only_caches
+            .iter()
+            .chain(std::iter::once(&self.cache_dir))
+            .find_map(|cache_dir| {
+                let object =
+                    fs::read_to_string(cache_dir.join(URLS).join(hash(url.as_bytes()))).ok()?;
+                Some(cache_dir.join(OBJECTS).join(object.trim())).filter(|path| path.is_file())
+            })
+    }
+
+    fn download(&self, url: &str) -> Result<PathBuf, AssetError> {
+        let objects = self.cache_dir.join(OBJECTS);
+        let urls = self.cache_dir.join(URLS);
+        for directory in [&objects, &urls] {
+            fs::create_dir_all(directory).map_err(|error| io_error(directory, error))?;
+        }
+
+        let url_hash = hash(url.as_bytes());
+        // other instances may be downloading the same URL
+        let partial = objects.join(format!("{}.{}.partial", url_hash, std::process::id()));
+        let failed = |message: String| AssetError::Download {
+            url: url.into(),
+            message,
+        };
+        let output = Command::new("curl")
+            .args([
+                "--fail",
+                "--silent",
+                "--show-error",
+                "--location",
+                "--output",
+            ])
+            .arg(&partial)
+            .arg(url)
+            .output()
+            .map_err(|error| match error.kind() {
+                io::ErrorKind::NotFound => {
+                    failed("curl is needed to download assets and it isn't installed".into())
+                }
+                _ => failed(format!("could not run curl: {}", error)),
+            })?;
+        if !output.status.success() {
+            let _ = fs::remove_file(&partial);
+            return Err(failed(
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            ));
+        }
+
+        let contents = fs::read(&partial).map_err(|error| io_error(&partial, error))?;
+        let object = format!("{}{}", hash(&contents), extension(url));
+        let path = objects.join(&object);
+        fs::rename(&partial, &path).map_err(|error| io_error(&path, error))?;
+        write_entry(&urls.join(url_hash), &object)?;
+
+        Ok(path)
+    }
+}
+
+/// Writes the entry of a URL to a file of this instance's first and renames
+/// it over the entry, so other instances never read a half written one
+fn write_entry(entry: &Path, object: &str) -> Result<(), AssetError> {
+    let mut partial_name = entry.as_os_str().to_os_string();
+    partial_name.push(format!(".{}.partial", std::process::id()));
+    let partial = PathBuf::from(partial_name);
+
+    fs::write(&partial, object).map_err(|error| io_error(&partial, error))?;
+    fs::rename(&partial, entry).map_err(|error| {
+        let _ = fs::remove_file(&partial);
+        io_error(entry, error)
+    })
+}
+
+/// Whether the asset is named by a URL rather than a path
+pub fn is_url(reference: &str) -> bool {
+    reference.starts_with("https://") || reference.starts_with("http://")
+}
+
+/// FNV-1a as hex, which unlike the standard library's hasher stays the same
+/// across Rust versions
+pub fn hash(bytes: &[u8]) -> String {
+    let hash = bytes.iter().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+
+    format!("{:016x}", hash)
+}
+
+/// Extension of the file the URL points at, with its dot, for decoders that
+/// go by it
+fn extension(url: &str) -> String {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+
+    match file_name.rsplit_once('.') {
+        Some((_, extension))
+            if !extension.is_empty()
+                && extension.len() <= 5
+                && extension.chars().all(|c| c.is_ascii_alphanumeric()) =>
+        {
+            format!(".{}", extension.to_lowercase())
+        }
+        _ => String::new(),
+    }
+}
+
+fn io_error(path: &Path, error: io::Error) -> AssetError {
+    AssetError::Io {
+        path: path.into(),
+        error,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn keeps_the_extension_of_urls() {
+        assert_eq!(".png", extension("https://example.com/cat.PNG?size=large"));
+        assert_eq!(".przt", extension("https://example.com/themes/dark.przt"));
+        assert_eq!("", extension("https://example.com/image"));
+        assert_eq!("", extension("https://example.com/"));
+    }
+
+    #[test]
+    pub fn replaces_url_entries_whole() {
+        let directory =
+            std::env::temp_dir().join(format!("przntr-asset-entry-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let entry = directory.join(hash(b"https://example.com/cat.png"));
+        fs::write(&entry, "older.png").unwrap();
+
+        write_entry(&entry, "newer.png").unwrap();
+
+        assert_eq!("newer.png", fs::read_to_string(&entry).unwrap());
+        assert_eq!(1, fs::read_dir(&directory).unwrap().count());
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    pub fn uses_cached_downloads_when_offline() {
+        let cache_dir =
+            std::env::temp_dir().join(format!("przntr-asset-cache-{}", std::process::id()));
+        let url = "https://example.com/cat.png";
+        fs::create_dir_all(cache_dir.join(OBJECTS)).unwrap();
+        fs::create_dir_all(cache_dir.join(URLS)).unwrap();
+        let object = format!("{}.png", hash(b"meow"));
+        fs::write(cache_dir.join(OBJECTS).join(&object), b"meow").unwrap();
+        fs::write(cache_dir.join(URLS).join(hash(url.as_bytes())), &object).unwrap();
+
+        let mut loader = AssetLoader::new(cache_dir.clone());
+        loader.set_offline(true);
+
+        let path = loader.load(url).unwrap();
+        assert_eq!(cache_dir.join(OBJECTS).join(&object), path);
+        assert_eq!(
+            Some(vec![
+                Path::new(OBJECTS).join(&object),
+                Path::new(URLS).join(hash(url.as_bytes()))
+            ]),
+            loader.cache_files(&fs::canonicalize(&path).unwrap())
+        );
+        assert!(matches!(
+            loader.load("https://example.com/dog.png"),
+            Err(AssetError::Offline(_))
+        ));
+
+        fs::remove_dir_all(cache_dir).unwrap();
+    }
+}
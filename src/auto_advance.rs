@@ -0,0 +1,304 @@
+use crate::event_loop::{Error, OnLoop};
+use crate::navigation::Navigation;
+use crate::presentation::{Presentation, Slide};
+use crate::talk_timer::Schedule;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// Moves the presentation on by itself once the current build step has
+/// been up for the slide's duration, starting over after the last slide.
+/// Navigating by hand restarts the countdown.
+pub struct AutoAdvance<'a> {
+    /// The presentation as last reloaded
+    presentation: &'a RefCell<Rc<Presentation>>,
+    navigation: &'a RefCell<Navigation>,
+    /// Used for slides which don't set their own duration
+    default_duration: Option<Duration>,
+    shown: Option<(usize, u32)>,
+    shown_at: Instant,
+}
+
+impl<'a> AutoAdvance<'a> {
+    pub fn new(
+        presentation: &'a RefCell<Rc<Presentation>>,
+        navigation: &'a RefCell<Navigation>,
+        default_duration: Option<Duration>,
+    ) -> Self {
+        Self {
+            presentation,
+            navigation,
+            default_duration,
+            shown: None,
+            shown_at: Instant::now(),
+        }
+    }
+
+    /// How long the slide stays up before moving on, if it moves on at all
+    fn duration(&self, slide: usize) -> Option<Duration> {
+        self.presentation
+            .borrow()
+            .slides()
+            .get(slide)
+            .and_then(Slide::duration)
+            .or(self.default_duration)
+    }
+
+    fn update(&mut self, now: Instant) {
+        let mut navigation = self.navigation.borrow_mut();
+        let current = (navigation.current_slide(), navigation.current_step());
+
+        if self.shown != Some(current) {
+            self.shown = Some(current);
+            self.shown_at = now;
+            return;
+        }
+
+        match self.duration(current.0) {
+            Some(duration) if now - self.shown_at >= duration => {}
+            _ => return,
+        }
+
+        navigation.next();
+        if (navigation.current_slide(), navigation.current_step()) == current {
+            navigation.first();
+        }
+    }
+}
+
+impl OnLoop for AutoAdvance<'_> {
+    fn run(&mut self) -> Result<(), Error> {
+        self.update(Instant::now());
+
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        "auto advance"
+    }
+
+    /// When the current step's time is up
+    fn wake_up_in(&self, now: Instant) -> Option<Duration> {
+        let (slide, _) = self.shown?;
+
+        self.duration(slide)
+            .map(|duration| (self.shown_at + duration).saturating_duration_since(now))
+    }
+}
+
+/// Goes to the slides of the schedule when their time comes, for
+/// `--strict-schedule`. Each time only moves the talk on once, so going back
+/// to an earlier slide afterwards is left alone.
+pub struct ScheduledAdvance<'a> {
+    /// The presentation as last reloaded
+    presentation: &'a RefCell<Rc<Presentation>>,
+    /// Presentation the schedule was made of, it is made again when the
+    /// presentation is reloaded
+    scheduled: Rc<Presentation>,
+    schedule: Schedule,
+    navigation: &'a RefCell<Navigation>,
+    started_at: Instant,
+    /// Slide whose time came last
+    due: Option<usize>,
+}
+
+impl<'a> ScheduledAdvance<'a> {
+    pub fn new(
+        presentation: &'a RefCell<Rc<Presentation>>,
+        navigation: &'a RefCell<Navigation>,
+        started_at: Instant,
+    ) -> Self {
+        let scheduled = Rc::clone(&presentation.borrow());
+        let schedule = Schedule::new(&scheduled);
+
+        Self {
+            presentation,
+            scheduled,
+            schedule,
+            navigation,
+            started_at,
+            due: None,
+        }
+    }
+
+    fn update(&mut self, now: Instant) {
+        let presentation = Rc::clone(&self.presentation.borrow());
+        if !Rc::ptr_eq(&presentation, &self.scheduled) {
+            self.schedule = Schedule::new(&presentation);
+            self.scheduled = presentation;
+        }
+
+        let due = self
+            .schedule
+            .due(now.saturating_duration_since(self.started_at));
+        if due == self.due {
+            return;
+        }
+        self.due = due;
+
+        let mut navigation = self.navigation.borrow_mut();
+        if let Some(slide) = due.filter(|slide| *slide > navigation.current_slide()) {
+            navigation.go_to(slide);
+        }
+    }
+}
+
+impl OnLoop for ScheduledAdvance<'_> {
+    fn run(&mut self) -> Result<(), Error> {
+        self.update(Instant::now());
+
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        "scheduled advance"
+    }
+
+    /// When the next time of the schedule comes
+    fn wake_up_in(&self, now: Instant) -> Option<Duration> {
+        let elapsed = now.saturating_duration_since(self.started_at);
+
+        self.schedule
+            .next_time(elapsed)
+            .map(|time| time.saturating_sub(elapsed))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::presentation::{Metadata, Resolution, Style};
+
+    fn presentation(durations: &[Option<u64>]) -> RefCell<Rc<Presentation>> {
+        let slides = durations
+            .iter()
+            .map(|duration| {
+                let mut slide = Slide::new("some slide".into(), vec![]);
+                if let Some(seconds) = duration {
+                    slide.set_duration(Duration::from_secs(*seconds));
+                }
+                slide
+            })
+            .collect();
+
+        RefCell::new(Rc::new(Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            slides,
+            Style::empty(),
+        )))
+    }
+
+    #[test]
+    pub fn advances_after_slide_duration_and_loops() {
+        let navigation = RefCell::new(Navigation::new(2));
+        let presentation = presentation(&[Some(5), None]);
+        let mut auto_advance =
+            AutoAdvance::new(&presentation, &navigation, Some(Duration::from_secs(10)));
+        let start = Instant::now();
+
+        auto_advance.update(start);
+        auto_advance.update(start + Duration::from_secs(4));
+        assert_eq!(0, navigation.borrow().current_slide());
+
+        auto_advance.update(start + Duration::from_secs(5));
+        assert_eq!(1, navigation.borrow().current_slide());
+
+        auto_advance.update(start + Duration::from_secs(6));
+        auto_advance.update(start + Duration::from_secs(16));
+        assert_eq!(0, navigation.borrow().current_slide());
+    }
+
+    #[test]
+    pub fn manual_navigation_restarts_countdown() {
+        let navigation = RefCell::new(Navigation::new(3));
+        let presentation = presentation(&[None, None, None]);
+        let mut auto_advance =
+            AutoAdvance::new(&presentation, &navigation, Some(Duration::from_secs(10)));
+        let start = Instant::now();
+
+        auto_advance.update(start);
+        navigation.borrow_mut().next();
+        auto_advance.update(start + Duration::from_secs(8));
+        auto_advance.update(start + Duration::from_secs(12));
+        assert_eq!(1, navigation.borrow().current_slide());
+    }
+
+    #[test]
+    pub fn wakes_up_when_the_slide_duration_is_up() {
+        let navigation = RefCell::new(Navigation::new(2));
+        let presentation = presentation(&[Some(5), None]);
+        let mut auto_advance = AutoAdvance::new(&presentation, &navigation, None);
+        let start = Instant::now();
+
+        auto_advance.update(start);
+
+        assert_eq!(
+            Some(Duration::from_secs(3)),
+            auto_advance.wake_up_in(start + Duration::from_secs(2))
+        );
+        navigation.borrow_mut().next();
+        auto_advance.update(start + Duration::from_secs(2));
+        assert_eq!(
+            None,
+            auto_advance.wake_up_in(start + Duration::from_secs(2))
+        );
+    }
+
+    #[test]
+    pub fn times_slides_by_the_reloaded_presentation() {
+        let navigation = RefCell::new(Navigation::new(2));
+        let shared = presentation(&[Some(5), None]);
+        let mut auto_advance = AutoAdvance::new(&shared, &navigation, None);
+        let start = Instant::now();
+
+        auto_advance.update(start);
+        shared.replace(presentation(&[Some(10), None]).into_inner());
+        auto_advance.update(start + Duration::from_secs(5));
+        assert_eq!(0, navigation.borrow().current_slide());
+
+        auto_advance.update(start + Duration::from_secs(10));
+        assert_eq!(1, navigation.borrow().current_slide());
+    }
+
+    #[test]
+    pub fn goes_to_scheduled_slides_once_when_their_time_comes() {
+        let mut slides: Vec<Slide> = (0..4)
+            .map(|_| Slide::new("some slide".into(), vec![]))
+            .collect();
+        slides[2].set_scheduled_at(Duration::from_secs(60));
+        let presentation = RefCell::new(Rc::new(Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            slides,
+            Style::empty(),
+        )));
+        let navigation = RefCell::new(Navigation::new(4));
+        let start = Instant::now();
+        let mut scheduled_advance = ScheduledAdvance::new(&presentation, &navigation, start);
+
+        scheduled_advance.update(start + Duration::from_secs(59));
+        assert_eq!(0, navigation.borrow().current_slide());
+        assert_eq!(
+            Some(Duration::from_secs(1)),
+            scheduled_advance.wake_up_in(start + Duration::from_secs(59))
+        );
+
+        scheduled_advance.update(start + Duration::from_secs(60));
+        assert_eq!(2, navigation.borrow().current_slide());
+
+        navigation.borrow_mut().go_to(1);
+        scheduled_advance.update(start + Duration::from_secs(61));
+        assert_eq!(1, navigation.borrow().current_slide());
+    }
+
+    #[test]
+    pub fn stays_without_any_duration() {
+        let navigation = RefCell::new(Navigation::new(2));
+        let presentation = presentation(&[None, None]);
+        let mut auto_advance = AutoAdvance::new(&presentation, &navigation, None);
+        let start = Instant::now();
+
+        auto_advance.update(start);
+        auto_advance.update(start + Duration::from_secs(3600));
+        assert_eq!(0, navigation.borrow().current_slide());
+    }
+}
@@ -0,0 +1,348 @@
+use crate::assets::AssetLoader;
+use crate::font_resolver;
+use crate::presentation::{Font, FontSource, Presentation};
+use crate::temp_dir::TempDir;
+use crate::zip::{ZipArchive, ZipWriter};
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Component, Path, PathBuf};
+
+/// Extension of bundles, which are opened like presentation files
+pub const EXTENSION: &str = "przb";
+/// Entry naming the presentation file among the bundled ones
+const MANIFEST: &str = "przntr-bundle";
+/// Directory the presentation's files keep their places relative to each
+/// other in
+const FILES: &str = "files";
+/// Directory of the fonts found outside the presentation's files, which the
+/// unpacked presentation looks its fonts up in
+const FONTS: &str = "fonts";
+/// Directory of the assets downloaded from URLs, laid out like the asset
+/// cache so that the unpacked presentation finds them without going online
+const CACHE: &str = "cache";
+
+#[derive(Debug)]
+pub enum BundleError {
+    Io {
+        path: PathBuf,
+        error: io::Error,
+    },
+    /// A font the presentation uses couldn't be found to pack it
+    Font(String),
+    /// The file isn't a bundle przntr can open
+    Invalid(String),
+}
+
+impl fmt::Display for BundleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io { path, error } => write!(f, "{}: {}", path.display(), error),
+            Self::Font(message) | Self::Invalid(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+/// Whether the file is a bundle, going by its extension
+pub fn is_bundle(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension == EXTENSION)
+}
+
+/// Packs the presentation file with the files it includes, its images,
+/// videos and fonts into a ZIP archive. The files keep their places relative
+/// to each other, so the presentation's relative paths still lead to them.
+/// Assets downloaded from URLs are packed with the cache entries of the URLs.
+pub fn write(
+    presentation_path: &Path,
+    presentation: &Presentation,
+    assets: &AssetLoader,
+    output: &Path,
+) -> Result<(), BundleError> {
+    let mut files = vec![presentation_path.to_path_buf()];
+    files.extend(presentation.included_files().iter().cloned());
+    files.extend(presentation.media_paths());
+    let mut fonts = vec![];
+    for font in presentation.style().fonts() {
+        if let FontSource::Bundled = font.source() {
+            continue;
+        }
+        let path = font_resolver::resolve(font, presentation.metadata().font_dirs())
+            .map_err(BundleError::Font)?;
+        match font.source() {
+            FontSource::File(declared) if Path::new(declared) == path => files.push(path),
+            _ => fonts.push((font_file_name(font, &path), path)),
+        }
+    }
+
+    let mut files = files
+        .iter()
+        .map(|path| fs::canonicalize(path).map_err(|error| io_error(path, error)))
+        .collect::<Result<Vec<PathBuf>, BundleError>>()?;
+    let presentation_path = files[0].clone();
+    files.sort();
+    files.dedup();
+    let mut cached = vec![];
+    files.retain(|path| match assets.cache_files(path) {
+        Some(cache_files) => {
+            cached.extend(cache_files);
+            false
+        }
+        None => true,
+    });
+    cached.sort();
+    cached.dedup();
+    let root = common_directory(&files);
+
+    let file = File::create(output).map_err(|error| io_error(output, error))?;
+    let mut zip = ZipWriter::new(BufWriter::new(file));
+    let add = |zip: &mut ZipWriter<_>, name: &str, data: &[u8]| {
+        zip.add_file(name, data)
+            .map_err(|error| io_error(output, error))
+    };
+    add(
+        &mut zip,
+        MANIFEST,
+        entry_name(&presentation_path, &root).as_bytes(),
+    )?;
+    for path in &files {
+        let data = fs::read(path).map_err(|error| io_error(path, error))?;
+        add(
+            &mut zip,
+            &format!("{}/{}", FILES, entry_name(path, &root)),
+            &data,
+        )?;
+    }
+    for (name, path) in &fonts {
+        let data = fs::read(path).map_err(|error| io_error(path, error))?;
+        add(&mut zip, &format!("{}/{}", FONTS, name), &data)?;
+    }
+    for entry in &cached {
+        let path = assets.cache_dir().join(entry);
+        let data = fs::read(&path).map_err(|error| io_error(&path, error))?;
+        add(
+            &mut zip,
+            &format!("{}/{}", CACHE, entry_name(entry, Path::new(""))),
+            &data,
+        )?;
+    }
+
+    zip.finish()
+        .and_then(|mut output| output.flush())
+        .map_err(|error| io_error(output, error))
+}
+
+/// A bundle unpacked into the temporary directory, which is removed when
+/// it is dropped
+#[derive(Debug)]
+pub struct Unpacked {
+    directory: TempDir,
+    presentation_path: PathBuf,
+}
+
+impl Unpacked {
+    pub fn presentation_path(&self) -> &PathBuf {
+        &self.presentation_path
+    }
+
+    /// Where the fonts found outside the presentation's files went, for the
+    /// presentation to look its fonts up in
+    pub fn font_dir(&self) -> PathBuf {
+        self.directory.path().join(FONTS)
+    }
+
+    /// Where the assets downloaded from URLs went, for the asset loader to
+    /// look them up in
+    pub fn cache_dir(&self) -> PathBuf {
+        self.directory.path().join(CACHE)
+    }
+}
+
+/// Unpacks the bundle into a new directory of the temporary one, for the
+/// presentation to be loaded from like any other
+pub fn unpack(path: &Path) -> Result<Unpacked, BundleError> {
+    let data = fs::read(path).map_err(|error| io_error(path, error))?;
+    let invalid =
+        |message: String| BundleError::Invalid(format!("{}: {}", path.display(), message));
+    let archive = ZipArchive::new(data).map_err(invalid)?;
+    let presentation = archive.text(MANIFEST).map_err(invalid)?;
+
+    let directory =
+        TempDir::new("przntr-bundle").map_err(|error| io_error(&std::env::temp_dir(), error))?;
+    for name in archive.names() {
+        if name == MANIFEST || name.ends_with('/') {
+            continue;
+        }
+        let target = directory.path().join(relative_path(name).map_err(invalid)?);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|error| io_error(parent, error))?;
+        }
+        let contents = archive.file(name).map_err(invalid)?;
+        fs::write(&target, contents).map_err(|error| io_error(&target, error))?;
+    }
+
+    Ok(Unpacked {
+        presentation_path: directory
+            .path()
+            .join(FILES)
+            .join(relative_path(presentation.trim()).map_err(invalid)?),
+        directory,
+    })
+}
+
+fn io_error(path: &Path, error: io::Error) -> BundleError {
+    BundleError::Io {
+        path: path.into(),
+        error,
+    }
+}
+
+/// Name the font file is looked up by in the unpacked font directory: font
+/// files keep their name, system fonts are named after their descriptor
+fn font_file_name(font: &Font, path: &Path) -> String {
+    match font.source() {
+        FontSource::File(_) => path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        FontSource::System | FontSource::Bundled => {
+            let extension = path
+                .extension()
+                .map(|extension| extension.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            format!(
+                "{}.{}",
+                font_resolver::file_stem_for(font.descriptor()),
+                extension
+            )
+        }
+    }
+}
+
+/// Deepest directory all the files are in
+fn common_directory(files: &[PathBuf]) -> PathBuf {
+    let mut directory = files
+        .first()
+        .and_then(|file| file.parent())
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+    for file in files {
+        while !file.starts_with(&directory) && directory.pop() {}
+    }
+
+    directory
+}
+
+/// The file's path below the root with `/` between its parts, the way ZIP
+/// archives name their entries
+fn entry_name(path: &Path, root: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// The entry's name as a path, refusing names that would lead out of the
+/// directory the bundle is unpacked into
+fn relative_path(name: &str) -> Result<PathBuf, String> {
+    let path = Path::new(name);
+    if path
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)))
+    {
+        Ok(path.into())
+    } else {
+        Err(format!("`{}` points outside the bundle", name))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn finds_the_directory_all_files_are_in() {
+        assert_eq!(
+            PathBuf::from("/talks"),
+            common_directory(&[
+                "/talks/rust/deck.prz".into(),
+                "/talks/shared/logo.png".into(),
+                "/talks/rust/images/cat.png".into()
+            ])
+        );
+        assert_eq!(
+            "rust/images/cat.png",
+            entry_name(Path::new("/talks/rust/images/cat.png"), Path::new("/talks"))
+        );
+    }
+
+    #[test]
+    pub fn refuses_entries_outside_the_bundle() {
+        assert_eq!(
+            Ok(PathBuf::from("files/deck.prz")),
+            relative_path("files/deck.prz")
+        );
+        assert!(relative_path("files/../../.bashrc").is_err());
+        assert!(relative_path("/etc/passwd").is_err());
+    }
+
+    #[test]
+    pub fn unpacks_what_was_packed() {
+        let directory =
+            std::env::temp_dir().join(format!("przntr-bundle-test-{}", std::process::id()));
+        fs::create_dir_all(directory.join("talk")).unwrap();
+        fs::create_dir_all(directory.join("shared")).unwrap();
+        let presentation_path = directory.join("talk").join("deck.prz");
+        let url = "https://example.com/logo.png";
+        let source = format!(
+            "metadata {{ title \"some title\" }} slide \"s\" {{ image \"../shared/cat.png\" image \"{}\" }}",
+            url
+        );
+        fs::write(&presentation_path, &source).unwrap();
+        fs::write(directory.join("shared").join("cat.png"), b"meow").unwrap();
+        // downloaded before, the way the asset loader keeps downloads
+        let cache_dir = directory.join("cache");
+        let object = format!("{}.png", crate::assets::hash(b"logo"));
+        fs::create_dir_all(cache_dir.join("objects")).unwrap();
+        fs::create_dir_all(cache_dir.join("urls")).unwrap();
+        fs::write(cache_dir.join("objects").join(&object), b"logo").unwrap();
+        fs::write(
+            cache_dir
+                .join("urls")
+                .join(crate::assets::hash(url.as_bytes())),
+            &object,
+        )
+        .unwrap();
+        let mut assets = AssetLoader::new(cache_dir);
+        assets.set_offline(true);
+        let presentation =
+            crate::parse_file_with(&source, &presentation_path, None, assets.clone()).unwrap();
+        let output = directory.join("deck.przb");
+
+        write(&presentation_path, &presentation, &assets, &output).unwrap();
+        let unpacked = unpack(&output).unwrap();
+
+        let unpacked_source = fs::read_to_string(unpacked.presentation_path()).unwrap();
+        assert_eq!(source, unpacked_source);
+        // nothing downloaded on this side
+        let mut assets = AssetLoader::new(directory.join("empty-cache"));
+        assets.set_offline(true);
+        assets.add_read_only_cache(unpacked.cache_dir());
+        let unpacked_presentation =
+            crate::parse_file_with(&unpacked_source, unpacked.presentation_path(), None, assets)
+                .unwrap();
+        let contents: Vec<Vec<u8>> = unpacked_presentation
+            .media_paths()
+            .iter()
+            .map(|path| fs::read(path).unwrap())
+            .collect();
+        assert_eq!(vec![b"meow".to_vec(), b"logo".to_vec()], contents);
+
+        fs::remove_dir_all(directory).unwrap();
+        let unpacked_directory = unpacked.directory.path().to_path_buf();
+        drop(unpacked);
+        assert!(!unpacked_directory.exists());
+    }
+}
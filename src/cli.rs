@@ -0,0 +1,1273 @@
+use log::LevelFilter;
+use przntr::bundle;
+use przntr::config::Config;
+use przntr::presentation::Color;
+use przntr::rendering::window::WindowOptions;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::iter::Peekable;
+use std::net::{IpAddr, Ipv4Addr};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Printed for `--help` and after invalid arguments
+pub const USAGE: &str = "\
+Usage: przntr [COMMAND] [OPTIONS] <PRESENTATION>
+
+Commands:
+  present   Shows the presentation or a bundle of it, the default
+  export    Writes the slides to a file, see --format and --out
+  bundle    Packs the presentation with its images, videos and fonts into
+            one .przb file, see --out
+  check     Reports problems with the presentation
+  fmt       Rewrites the presentation in the canonical layout
+  import    Converts a PPTX or ODP file into the DSL
+  lsp       Serves editors over the Language Server Protocol
+  help      Prints this message
+
+Options:
+  --fullscreen, --windowed  Fills the screen with the presentation window or
+                            not, whatever the config says
+  --start-slide <SLIDE>     Starts at the slide, by its number or name
+  --start-section <TITLE>   Starts at the first slide of the section
+  --resume                  Starts at the slide shown last time
+  --theme <THEME>           Styles the presentation with the theme file, or
+                            the theme of the name in the configured directories
+  --format <FORMAT>         Input format (prz, md, json) or export format
+                            (pdf, png, html, handout, reveal, marp, pptx, json)
+  --out, -o <PATH>          Where export and bundle write to
+  --width, --height <PX>    Size of exported images
+  --watch                   Reloads the presentation when its files change
+  --offline                 Uses images, fonts and themes downloaded before
+                            instead of downloading them
+  --presenter               Opens a second window for the speaker
+  --include-hidden          Shows and exports hidden slides
+  --spell <LANGUAGE>        Spell checks with check, e.g. en_US
+  --auto-advance <TIME>     Moves on from slides after the time, e.g. 30s
+  --talk-length <TIME>      Planned length of the talk, e.g. 20m
+  --strict-schedule         Goes to the slides scheduled with `at` when their
+                            time comes
+  --rehearse                Records the time spent on each slide and prints
+                            it at the end, the presenter view paces later
+                            runs by it
+  --store-targets           Rehearses and writes the times into the
+                            presentation as the slides' targets
+  --pointer-color <COLOR>   Color of the pointer, by name or as hex
+  --overview-key <KEY>      Key opening the overview (o, escape)
+  --volume-keys             Goes through the slides with the volume keys
+  --control-address <IP>    Address OSC controls listen on, 127.0.0.1 unless
+                            given, e.g. 0.0.0.0 for every network
+  --display <INDEX>         Fills the display with the window
+  --window-position <X> <Y> Opens the window at the position
+  --borderless              Opens the window without its decorations
+  --always-on-top           Keeps the window above the other ones
+  --transparent-background  Draws a chroma key backdrop for the backgrounds
+  --no-vsync                Paces frames without waiting for the display
+  --verbose, --quiet        Logs more or less than warnings and errors
+  --help                    Prints this message
+";
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum Error {
+    MissingValue(String),
+    InvalidValue { option: String, value: String },
+    UnknownOption(String),
+    MissingPresentationPath,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingValue(option) => write!(f, "`{}` needs a value", option),
+            Self::InvalidValue { option, value } => {
+                write!(f, "`{}` is not a valid value for `{}`", value, option)
+            }
+            Self::UnknownOption(option) => write!(f, "unknown option `{}`", option),
+            Self::MissingPresentationPath => write!(f, "no presentation file given"),
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum ExportFormat {
+    Pdf,
+    Png,
+    Html,
+    /// Titles, pictures and notes of the slides, as a PDF or in Markdown
+    Handout,
+    /// Reveal.js HTML, for presenting from a browser
+    Reveal,
+    /// Marp Markdown, for editing the deck further in Marp
+    Marp,
+    /// PPTX, with the slides' text and images as shapes
+    Pptx,
+    /// The presentation model as JSON, for scripts to post-process
+    Json,
+}
+
+/// Language the presentation file is written in
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum InputFormat {
+    Dsl,
+    Markdown,
+    /// The presentation model as `export --format json` writes it
+    Json,
+}
+
+impl InputFormat {
+    /// Guesses the format from the file extension, the DSL unless it is
+    /// Markdown or JSON
+    pub fn for_path(path: &Path) -> Self {
+        match path.extension().and_then(std::ffi::OsStr::to_str) {
+            Some("md" | "markdown") => Self::Markdown,
+            Some("json") => Self::Json,
+            _ => Self::Dsl,
+        }
+    }
+}
+
+/// Where the talk starts, instead of the first slide or where the restored
+/// session left off
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum Start {
+    /// Index of the slide
+    Slide(usize),
+    /// Slide with the name given in the presentation
+    SlideNamed(String),
+    /// First slide of the section with the title
+    Section(String),
+}
+
+/// Key opening the slide overview, Escape no longer quits when it does
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum OverviewKey {
+    O,
+    Escape,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum Command {
+    Present,
+    Check,
+    /// Rewrites the presentation file in the canonical layout
+    Format,
+    /// Serves editors over the Language Server Protocol on stdin and
+    /// stdout, without a presentation file
+    LanguageServer,
+    /// Converts a PPTX or ODP file into the DSL, written to stdout
+    Import,
+    /// Prints how przntr is used
+    Help,
+    /// Packs the presentation and the files it uses into a bundle
+    Bundle {
+        output: PathBuf,
+    },
+    Export {
+        format: ExportFormat,
+        output: PathBuf,
+        width: Option<u32>,
+        height: Option<u32>,
+    },
+}
+
+/// Options that are either given or not
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Copy, Clone)]
+enum Flag {
+    Watch,
+    PresenterView,
+    VolumeKeys,
+    IncludeHidden,
+    Resume,
+    Offline,
+    Rehearse,
+    StoreTargets,
+    StrictSchedule,
+}
+
+impl Flag {
+    /// The flags the option turns on, none for options that aren't flags
+    fn for_option(option: &str) -> Option<&'static [Self]> {
+        match option {
+            "--watch" => Some(&[Self::Watch]),
+            "--presenter" => Some(&[Self::PresenterView]),
+            "--volume-keys" => Some(&[Self::VolumeKeys]),
+            "--include-hidden" => Some(&[Self::IncludeHidden]),
+            "--resume" => Some(&[Self::Resume]),
+            "--offline" => Some(&[Self::Offline]),
+            "--rehearse" => Some(&[Self::Rehearse]),
+            // the targets are what the rehearsal measures
+            "--store-targets" => Some(&[Self::Rehearse, Self::StoreTargets]),
+            "--strict-schedule" => Some(&[Self::StrictSchedule]),
+            _ => None,
+        }
+    }
+}
+
+/// Command named before the options, which some options still change
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+enum CommandName {
+    Present,
+    Help,
+    Export,
+    Check,
+    Format,
+    LanguageServer,
+    Import,
+    Bundle,
+}
+
+impl CommandName {
+    /// Takes the command off the arguments, presenting unless one is named
+    fn parse<I: Iterator<Item = String>>(args: &mut Peekable<I>) -> Self {
+        let name = match args.peek().map(String::as_str) {
+            Some("present") => Self::Present,
+            Some("help") => Self::Help,
+            Some("export") => Self::Export,
+            Some("check") => Self::Check,
+            Some("fmt") => Self::Format,
+            Some("lsp") => Self::LanguageServer,
+            Some("import") => Self::Import,
+            Some("bundle") => Self::Bundle,
+            _ => return Self::Present,
+        };
+        args.next();
+
+        name
+    }
+}
+
+/// What export and bundle write, gathered from the options before the
+/// command is made
+#[derive(Debug, Default)]
+struct Output {
+    format: Option<ExportFormat>,
+    path: Option<PathBuf>,
+    width: Option<u32>,
+    height: Option<u32>,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct Arguments {
+    command: Command,
+    presentation_path: PathBuf,
+    input_format: Option<InputFormat>,
+    flags: BTreeSet<Flag>,
+    overview_key: OverviewKey,
+    pointer_color: Option<Color>,
+    auto_advance: Option<Duration>,
+    talk_length: Option<Duration>,
+    theme: Option<PathBuf>,
+    spell: Option<String>,
+    window: WindowOptions,
+    log_level: LevelFilter,
+    start: Option<Start>,
+    fullscreen: Option<bool>,
+    control_address: IpAddr,
+}
+
+impl Arguments {
+    /// Parses the command line arguments, without the program name
+    pub fn parse<I: Iterator<Item = String>>(args: I) -> Result<Self, Error> {
+        let mut args = args.peekable();
+        let mut command = CommandName::parse(&mut args);
+        let mut output = Output::default();
+        let mut presentation_path: Option<PathBuf> = None;
+        let mut arguments = Self {
+            command: Command::Present,
+            presentation_path: PathBuf::new(),
+            input_format: None,
+            flags: BTreeSet::new(),
+            overview_key: OverviewKey::O,
+            pointer_color: None,
+            auto_advance: None,
+            talk_length: None,
+            theme: None,
+            spell: None,
+            window: WindowOptions::default(),
+            log_level: LevelFilter::Warn,
+            start: None,
+            fullscreen: None,
+            control_address: IpAddr::V4(Ipv4Addr::LOCALHOST),
+        };
+
+        while let Some(argument) = args.next() {
+            if let Some(flags) = Flag::for_option(&argument) {
+                arguments.flags.extend(flags);
+                continue;
+            }
+            let taken = arguments.parse_talk_option(&argument, &mut args)?
+                || arguments.parse_window_option(&argument, &mut args)?
+                || arguments.parse_output_option(
+                    &argument,
+                    &mut args,
+                    &mut command,
+                    &mut output,
+                )?;
+            if taken {
+                continue;
+            }
+
+            match argument.as_str() {
+                "--help" | "-h" => command = CommandName::Help,
+                option if option.starts_with("--") => {
+                    return Err(Error::UnknownOption(option.into()))
+                }
+                _ => presentation_path = Some(PathBuf::from(argument)),
+            }
+        }
+
+        arguments.command = Self::command_named(command, output, presentation_path.as_ref())?;
+        // the language server gets its files from the editor
+        arguments.presentation_path = match presentation_path {
+            Some(path) => path,
+            None if matches!(arguments.command, Command::LanguageServer | Command::Help) => {
+                PathBuf::new()
+            }
+            None => return Err(Error::MissingPresentationPath),
+        };
+
+        Ok(arguments)
+    }
+
+    /// Takes the options of how the talk goes and is logged, false for other
+    /// options
+    fn parse_talk_option<I: Iterator<Item = String>>(
+        &mut self,
+        option: &str,
+        args: &mut I,
+    ) -> Result<bool, Error> {
+        match option {
+            "--control-address" => {
+                let value = Self::value(option, args.next())?;
+                self.control_address = value
+                    .parse()
+                    .map_err(|_| Self::invalid_value(option, &value))?;
+            }
+            "--verbose" => self.log_level = LevelFilter::Debug,
+            "--quiet" => self.log_level = LevelFilter::Error,
+            "--overview-key" => match Self::value(option, args.next())?.as_str() {
+                "o" => self.overview_key = OverviewKey::O,
+                "escape" => self.overview_key = OverviewKey::Escape,
+                value => return Err(Self::invalid_value(option, value)),
+            },
+            "--pointer-color" => {
+                let value = Self::value(option, args.next())?;
+                self.pointer_color = Some(
+                    Color::from_hex(&value)
+                        .or_else(|| Color::from_name(&value))
+                        .ok_or_else(|| Self::invalid_value(option, &value))?,
+                );
+            }
+            "--auto-advance" => self.auto_advance = Some(Self::duration(option, args.next())?),
+            "--talk-length" => self.talk_length = Some(Self::duration(option, args.next())?),
+            "--theme" => self.theme = Some(PathBuf::from(Self::value(option, args.next())?)),
+            "--spell" => self.spell = Some(Self::value(option, args.next())?),
+            "--start-slide" => self.start = Some(Self::slide(option, args.next())?),
+            "--start-section" => {
+                self.start = Some(Start::Section(Self::value(option, args.next())?));
+            }
+            _ => return Ok(false),
+        }
+
+        Ok(true)
+    }
+
+    /// Takes the options of how the window is opened, false for other
+    /// options
+    fn parse_window_option<I: Iterator<Item = String>>(
+        &mut self,
+        option: &str,
+        args: &mut I,
+    ) -> Result<bool, Error> {
+        match option {
+            "--fullscreen" => self.fullscreen = Some(true),
+            "--windowed" => self.fullscreen = Some(false),
+            "--borderless" => self.window.set_borderless(true),
+            "--always-on-top" => self.window.set_always_on_top(true),
+            "--transparent-background" => self.window.set_transparent_background(true),
+            "--no-vsync" => self.window.set_vsync(false),
+            "--display" => self.window.set_display(Self::display(option, args.next())?),
+            "--window-position" => {
+                let x = Self::coordinate(option, args.next())?;
+                let y = Self::coordinate(option, args.next())?;
+                self.window.set_position(x, y);
+            }
+            _ => return Ok(false),
+        }
+
+        Ok(true)
+    }
+
+    /// Takes the options of what is read and written, false for other
+    /// options
+    fn parse_output_option<I: Iterator<Item = String>>(
+        &mut self,
+        option: &str,
+        args: &mut I,
+        command: &mut CommandName,
+        output: &mut Output,
+    ) -> Result<bool, Error> {
+        match option {
+            "--export-pdf" => {
+                // asking for help still only prints it
+                if *command != CommandName::Help {
+                    *command = CommandName::Export;
+                }
+                output.format = Some(ExportFormat::Pdf);
+                output.path = Some(PathBuf::from(Self::value(option, args.next())?));
+            }
+            "--format" => match Self::value(option, args.next())?.as_str() {
+                "pdf" => output.format = Some(ExportFormat::Pdf),
+                "png" => output.format = Some(ExportFormat::Png),
+                "html" => output.format = Some(ExportFormat::Html),
+                "handout" => output.format = Some(ExportFormat::Handout),
+                "reveal" => output.format = Some(ExportFormat::Reveal),
+                "marp" => output.format = Some(ExportFormat::Marp),
+                "pptx" => output.format = Some(ExportFormat::Pptx),
+                // the only format that is both read and exported
+                "json" if *command == CommandName::Export => {
+                    output.format = Some(ExportFormat::Json);
+                }
+                "json" => self.input_format = Some(InputFormat::Json),
+                "prz" => self.input_format = Some(InputFormat::Dsl),
+                "md" => self.input_format = Some(InputFormat::Markdown),
+                value => return Err(Self::invalid_value(option, value)),
+            },
+            "--out" | "-o" => output.path = Some(PathBuf::from(Self::value(option, args.next())?)),
+            "--width" => output.width = Some(Self::dimension(option, args.next())?),
+            "--height" => output.height = Some(Self::dimension(option, args.next())?),
+            _ => return Ok(false),
+        }
+
+        Ok(true)
+    }
+
+    /// Makes the command of its name out of the options it takes
+    fn command_named(
+        name: CommandName,
+        output: Output,
+        presentation_path: Option<&PathBuf>,
+    ) -> Result<Command, Error> {
+        Ok(match name {
+            CommandName::Present => Command::Present,
+            CommandName::Help => Command::Help,
+            CommandName::Export => Command::Export {
+                format: output.format.unwrap_or(ExportFormat::Pdf),
+                output: output
+                    .path
+                    .ok_or_else(|| Error::MissingValue("--out".into()))?,
+                width: output.width,
+                height: output.height,
+            },
+            CommandName::Check => Command::Check,
+            CommandName::Format => Command::Format,
+            CommandName::LanguageServer => Command::LanguageServer,
+            CommandName::Import => Command::Import,
+            // next to the presentation unless told otherwise
+            CommandName::Bundle => Command::Bundle {
+                output: match (output.path, presentation_path) {
+                    (Some(output), _) => output,
+                    (None, Some(path)) => path.with_extension(bundle::EXTENSION),
+                    (None, None) => return Err(Error::MissingPresentationPath),
+                },
+            },
+        })
+    }
+
+    pub fn command(&self) -> &Command {
+        &self.command
+    }
+
+    pub fn presentation_path(&self) -> &PathBuf {
+        &self.presentation_path
+    }
+
+    /// Whether to reload the presentation when its files change
+    pub fn watch(&self) -> bool {
+        self.flags.contains(&Flag::Watch)
+    }
+
+    /// Whether to open a second window for the speaker
+    pub fn presenter_view(&self) -> bool {
+        self.flags.contains(&Flag::PresenterView)
+    }
+
+    pub fn overview_key(&self) -> OverviewKey {
+        self.overview_key
+    }
+
+    /// Color of the pointer's dot and strokes, instead of the default red
+    pub fn pointer_color(&self) -> Option<Color> {
+        self.pointer_color
+    }
+
+    /// How long slides without their own duration stay up before moving on
+    /// by themselves
+    pub fn auto_advance(&self) -> Option<Duration> {
+        self.auto_advance
+    }
+
+    /// Planned length of the talk, the timer warns when it is running out
+    pub fn talk_length(&self) -> Option<Duration> {
+        self.talk_length
+    }
+
+    /// Theme file styling the presentation instead of the one its metadata
+    /// names
+    pub fn theme(&self) -> Option<&PathBuf> {
+        self.theme.as_ref()
+    }
+
+    /// Whether the volume keys go to the next and previous slide, for
+    /// remotes that send them
+    pub fn volume_keys(&self) -> bool {
+        self.flags.contains(&Flag::VolumeKeys)
+    }
+
+    /// Whether hidden slides are shown and exported, for drafting
+    pub fn include_hidden(&self) -> bool {
+        self.flags.contains(&Flag::IncludeHidden)
+    }
+
+    /// Language of the dictionary `check` spell checks with, e.g. `en_US`
+    pub fn spell(&self) -> Option<&String> {
+        self.spell.as_ref()
+    }
+
+    /// Most detailed messages logged, warnings and errors unless asked for
+    /// more with `--verbose` or less with `--quiet`
+    pub fn log_level(&self) -> LevelFilter {
+        self.log_level
+    }
+
+    pub fn start(&self) -> Option<&Start> {
+        self.start.as_ref()
+    }
+
+    /// Whether to start at the slide shown last time the presentation was
+    /// open, unless told where to start
+    pub fn resume(&self) -> bool {
+        self.flags.contains(&Flag::Resume)
+    }
+
+    /// Whether assets named by URL may only come from the cache, without
+    /// going online
+    pub fn offline(&self) -> bool {
+        self.flags.contains(&Flag::Offline)
+    }
+
+    /// Whether to record the time spent on each slide
+    pub fn rehearse(&self) -> bool {
+        self.flags.contains(&Flag::Rehearse)
+    }
+
+    /// Whether the rehearsed times are written into the presentation as the
+    /// slides' targets
+    pub fn store_targets(&self) -> bool {
+        self.flags.contains(&Flag::StoreTargets)
+    }
+
+    /// Whether the talk moves on to the scheduled slides by itself
+    pub fn strict_schedule(&self) -> bool {
+        self.flags.contains(&Flag::StrictSchedule)
+    }
+
+    /// Address the presentation's OSC controls listen on, only this machine
+    /// can reach them unless another one is given
+    pub fn control_address(&self) -> IpAddr {
+        self.control_address
+    }
+
+    /// How the presentation window is opened
+    pub fn window_options(&self) -> WindowOptions {
+        let mut window = self.window;
+        window.set_fullscreen(self.fullscreen.unwrap_or(false));
+
+        window
+    }
+
+    /// Takes the defaults of what the command line leaves out from the
+    /// config, flags given on the command line stay
+    pub fn apply_config(&mut self, config: &Config) {
+        self.fullscreen = self.fullscreen.or_else(|| config.fullscreen());
+        // themes given by name are looked up in the theme directories
+        if let Some(theme) = &self.theme {
+            if !theme.is_file() {
+                if let Some(found) = config.find_theme(&theme.to_string_lossy()) {
+                    self.theme = Some(found);
+                }
+            }
+        }
+    }
+
+    /// Format given with `--format`, otherwise guessed from the file extension
+    pub fn input_format(&self) -> InputFormat {
+        self.input_format
+            .unwrap_or_else(|| InputFormat::for_path(&self.presentation_path))
+    }
+
+    fn value(option: &str, value: Option<String>) -> Result<String, Error> {
+        value.ok_or_else(|| Error::MissingValue(option.into()))
+    }
+
+    fn dimension(option: &str, value: Option<String>) -> Result<u32, Error> {
+        let value = Self::value(option, value)?;
+
+        match value.parse() {
+            Ok(dimension) if dimension > 0 => Ok(dimension),
+            _ => Err(Self::invalid_value(option, &value)),
+        }
+    }
+
+    /// Parses a slide number counting from 1, anything but a number is
+    /// the slide's name
+    fn slide(option: &str, value: Option<String>) -> Result<Start, Error> {
+        let value = Self::value(option, value)?;
+
+        match value.parse::<usize>() {
+            Ok(number) if number > 0 => Ok(Start::Slide(number - 1)),
+            Ok(_) => Err(Self::invalid_value(option, &value)),
+            Err(_) => Ok(Start::SlideNamed(value)),
+        }
+    }
+
+    /// Parses the index of a display, 0 being the primary one
+    fn display(option: &str, value: Option<String>) -> Result<i32, Error> {
+        let value = Self::value(option, value)?;
+
+        match value.parse() {
+            Ok(index) if index >= 0 => Ok(index),
+            _ => Err(Self::invalid_value(option, &value)),
+        }
+    }
+
+    /// Parses one of the two numbers of a screen position, which can be
+    /// negative on screens left of or above the primary one
+    fn coordinate(option: &str, value: Option<String>) -> Result<i32, Error> {
+        let value = Self::value(option, value)?;
+
+        value
+            .parse()
+            .map_err(|_| Self::invalid_value(option, &value))
+    }
+
+    /// Parses a whole number of hours, minutes, seconds or milliseconds
+    /// (`1h`, `20m`, `10s`, `500ms`), plain numbers are seconds
+    fn duration(option: &str, value: Option<String>) -> Result<Duration, Error> {
+        let value = Self::value(option, value)?;
+        let split_at = value
+            .find(|character: char| !character.is_ascii_digit())
+            .unwrap_or_else(|| value.len());
+        let (number, unit) = value.split_at(split_at);
+        let number: u64 = number
+            .parse()
+            .map_err(|_| Self::invalid_value(option, &value))?;
+
+        match unit {
+            "h" => Ok(Duration::from_secs(number * 3600)),
+            "m" => Ok(Duration::from_secs(number * 60)),
+            "s" | "" => Ok(Duration::from_secs(number)),
+            "ms" => Ok(Duration::from_millis(number)),
+            _ => Err(Self::invalid_value(option, &value)),
+        }
+    }
+
+    fn invalid_value(option: &str, value: &str) -> Error {
+        Error::InvalidValue {
+            option: option.into(),
+            value: value.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::net::Ipv6Addr;
+
+    fn parse(args: &[&str]) -> Result<Arguments, Error> {
+        Arguments::parse(args.iter().map(ToString::to_string))
+    }
+
+    #[test]
+    pub fn presents_by_default() {
+        assert_eq!(
+            Ok(Arguments {
+                command: Command::Present,
+                presentation_path: "deck.prz".into(),
+                input_format: None,
+                flags: BTreeSet::new(),
+                overview_key: OverviewKey::O,
+                pointer_color: None,
+                auto_advance: None,
+                talk_length: None,
+                theme: None,
+                spell: None,
+                window: WindowOptions::default(),
+                log_level: LevelFilter::Warn,
+                start: None,
+                fullscreen: None,
+                control_address: IpAddr::V4(Ipv4Addr::LOCALHOST)
+            }),
+            parse(&["deck.prz"])
+        );
+    }
+
+    #[test]
+    pub fn can_parse_export_pdf_flag() {
+        assert_eq!(
+            Ok(Arguments {
+                command: Command::Export {
+                    format: ExportFormat::Pdf,
+                    output: "out.pdf".into(),
+                    width: None,
+                    height: None
+                },
+                presentation_path: "deck.prz".into(),
+                input_format: None,
+                flags: BTreeSet::new(),
+                overview_key: OverviewKey::O,
+                pointer_color: None,
+                auto_advance: None,
+                talk_length: None,
+                theme: None,
+                spell: None,
+                window: WindowOptions::default(),
+                log_level: LevelFilter::Warn,
+                start: None,
+                fullscreen: None,
+                control_address: IpAddr::V4(Ipv4Addr::LOCALHOST)
+            }),
+            parse(&["deck.prz", "--export-pdf", "out.pdf"])
+        );
+    }
+
+    #[test]
+    pub fn can_parse_export_subcommand() {
+        assert_eq!(
+            Ok(Arguments {
+                command: Command::Export {
+                    format: ExportFormat::Png,
+                    output: "dir/".into(),
+                    width: Some(1920),
+                    height: Some(1080)
+                },
+                presentation_path: "deck.prz".into(),
+                input_format: None,
+                flags: BTreeSet::new(),
+                overview_key: OverviewKey::O,
+                pointer_color: None,
+                auto_advance: None,
+                talk_length: None,
+                theme: None,
+                spell: None,
+                window: WindowOptions::default(),
+                log_level: LevelFilter::Warn,
+                start: None,
+                fullscreen: None,
+                control_address: IpAddr::V4(Ipv4Addr::LOCALHOST)
+            }),
+            parse(&[
+                "export", "--format", "png", "--out", "dir/", "--width", "1920", "--height",
+                "1080", "deck.prz"
+            ])
+        );
+    }
+
+    #[test]
+    pub fn can_parse_handout_export() {
+        assert_eq!(
+            &Command::Export {
+                format: ExportFormat::Handout,
+                output: "handout.md".into(),
+                width: None,
+                height: None,
+            },
+            parse(&[
+                "export",
+                "--format",
+                "handout",
+                "--out",
+                "handout.md",
+                "deck.prz"
+            ])
+            .unwrap()
+            .command()
+        );
+    }
+
+    #[test]
+    pub fn can_parse_html_export() {
+        assert_eq!(
+            &Command::Export {
+                format: ExportFormat::Html,
+                output: "deck.html".into(),
+                width: None,
+                height: None,
+            },
+            parse(&[
+                "export",
+                "--format",
+                "html",
+                "--out",
+                "deck.html",
+                "deck.prz"
+            ])
+            .unwrap()
+            .command()
+        );
+    }
+
+    #[test]
+    pub fn can_parse_interop_exports() {
+        let format =
+            |name: &str| match parse(&["export", "--format", name, "--out", "x", "deck.prz"])
+                .unwrap()
+                .command()
+            {
+                Command::Export { format, .. } => Some(*format),
+                _ => None,
+            };
+
+        assert_eq!(Some(ExportFormat::Reveal), format("reveal"));
+        assert_eq!(Some(ExportFormat::Marp), format("marp"));
+        assert_eq!(Some(ExportFormat::Pptx), format("pptx"));
+        assert_eq!(Some(ExportFormat::Json), format("json"));
+    }
+
+    #[test]
+    pub fn can_parse_check_subcommand() {
+        assert_eq!(
+            &Command::Check,
+            parse(&["check", "deck.prz"]).unwrap().command()
+        );
+    }
+
+    #[test]
+    pub fn can_parse_spell_check_language() {
+        let arguments = parse(&["check", "--spell", "en_US", "deck.prz"]).unwrap();
+
+        assert_eq!(Some(&"en_US".to_string()), arguments.spell());
+        assert_eq!(
+            Err(Error::MissingValue("--spell".into())),
+            parse(&["check", "deck.prz", "--spell"])
+        );
+    }
+
+    #[test]
+    pub fn can_parse_fmt_subcommand() {
+        assert_eq!(
+            &Command::Format,
+            parse(&["fmt", "deck.prz"]).unwrap().command()
+        );
+    }
+
+    #[test]
+    pub fn can_parse_bundle_subcommand() {
+        assert_eq!(
+            &Command::Bundle {
+                output: "talks/deck.przb".into()
+            },
+            parse(&["bundle", "talks/deck.prz"]).unwrap().command()
+        );
+        assert_eq!(
+            &Command::Bundle {
+                output: "out.przb".into()
+            },
+            parse(&["bundle", "deck.prz", "-o", "out.przb"])
+                .unwrap()
+                .command()
+        );
+    }
+
+    #[test]
+    pub fn can_parse_present_subcommand_with_global_flags() {
+        let arguments = parse(&[
+            "present",
+            "--fullscreen",
+            "--start-slide",
+            "12",
+            "--theme",
+            "dark.prz",
+            "deck.prz",
+        ])
+        .unwrap();
+
+        assert_eq!(&Command::Present, arguments.command());
+        assert_eq!(Some(&Start::Slide(11)), arguments.start());
+        assert_eq!(Some(&PathBuf::from("dark.prz")), arguments.theme());
+        assert!(arguments.window_options().fullscreen());
+    }
+
+    #[test]
+    pub fn can_start_at_a_named_slide_or_section() {
+        assert_eq!(
+            Some(&Start::SlideNamed("demo".into())),
+            parse(&["deck.prz", "--start-slide", "demo"])
+                .unwrap()
+                .start()
+        );
+        assert_eq!(
+            Some(&Start::Section("Live demo".into())),
+            parse(&["deck.prz", "--start-section", "Live demo"])
+                .unwrap()
+                .start()
+        );
+    }
+
+    #[test]
+    pub fn can_parse_resume_flag() {
+        assert!(parse(&["deck.prz", "--resume"]).unwrap().resume());
+        assert!(!parse(&["deck.prz"]).unwrap().resume());
+    }
+
+    #[test]
+    pub fn can_parse_strict_schedule_flag() {
+        assert!(parse(&["deck.prz", "--strict-schedule"])
+            .unwrap()
+            .strict_schedule());
+        assert!(!parse(&["deck.prz"]).unwrap().strict_schedule());
+    }
+
+    #[test]
+    pub fn can_parse_control_address() {
+        let control_address = |value: &str| {
+            parse(&["deck.prz", "--control-address", value])
+                .map(|arguments| arguments.control_address())
+        };
+
+        assert_eq!(
+            Ok(IpAddr::V4(Ipv4Addr::UNSPECIFIED)),
+            control_address("0.0.0.0")
+        );
+        assert_eq!(Ok(IpAddr::V6(Ipv6Addr::LOCALHOST)), control_address("::1"));
+        assert_eq!(
+            Err(Error::InvalidValue {
+                option: "--control-address".into(),
+                value: "everywhere".into()
+            }),
+            control_address("everywhere")
+        );
+    }
+
+    #[test]
+    pub fn storing_targets_rehearses() {
+        let arguments = parse(&["deck.prz", "--store-targets"]).unwrap();
+        assert!(arguments.rehearse());
+        assert!(arguments.store_targets());
+
+        let arguments = parse(&["deck.prz", "--rehearse"]).unwrap();
+        assert!(arguments.rehearse());
+        assert!(!arguments.store_targets());
+    }
+
+    #[test]
+    pub fn flags_go_over_the_config() {
+        let config = Config::parse("fullscreen = true", Path::new("config.toml")).unwrap();
+
+        let mut arguments = parse(&["deck.prz"]).unwrap();
+        arguments.apply_config(&config);
+        assert!(arguments.window_options().fullscreen());
+
+        let mut arguments = parse(&["deck.prz", "--windowed"]).unwrap();
+        arguments.apply_config(&config);
+        assert!(!arguments.window_options().fullscreen());
+    }
+
+    #[test]
+    pub fn fails_on_slide_numbers_below_one() {
+        assert_eq!(
+            Err(Error::InvalidValue {
+                option: "--start-slide".into(),
+                value: "0".into()
+            }),
+            parse(&["deck.prz", "--start-slide", "0"])
+        );
+    }
+
+    #[test]
+    pub fn can_ask_for_help_without_a_path() {
+        assert_eq!(&Command::Help, parse(&["help"]).unwrap().command());
+        assert_eq!(
+            &Command::Help,
+            parse(&["export", "--help"]).unwrap().command()
+        );
+        assert_eq!(
+            "`--out` needs a value",
+            Error::MissingValue("--out".into()).to_string()
+        );
+    }
+
+    #[test]
+    pub fn can_parse_import_subcommand() {
+        let arguments = parse(&["import", "deck.pptx"]).unwrap();
+
+        assert_eq!(&Command::Import, arguments.command());
+        assert_eq!(&PathBuf::from("deck.pptx"), arguments.presentation_path());
+    }
+
+    #[test]
+    pub fn can_parse_lsp_subcommand_without_a_path() {
+        assert_eq!(&Command::LanguageServer, parse(&["lsp"]).unwrap().command());
+        assert_eq!(Err(Error::MissingPresentationPath), parse(&["fmt"]));
+    }
+
+    #[test]
+    pub fn can_parse_log_level_flags() {
+        assert_eq!(
+            LevelFilter::Debug,
+            parse(&["deck.prz", "--verbose"]).unwrap().log_level()
+        );
+        assert_eq!(
+            LevelFilter::Error,
+            parse(&["deck.prz", "--quiet"]).unwrap().log_level()
+        );
+        assert_eq!(LevelFilter::Warn, parse(&["deck.prz"]).unwrap().log_level());
+    }
+
+    #[test]
+    pub fn can_parse_watch_flag() {
+        assert!(parse(&["--watch", "deck.prz"]).unwrap().watch());
+        assert!(!parse(&["deck.prz"]).unwrap().watch());
+    }
+
+    #[test]
+    pub fn can_parse_theme_option() {
+        assert_eq!(
+            Some(&PathBuf::from("corporate.przt")),
+            parse(&["--theme", "corporate.przt", "deck.prz"])
+                .unwrap()
+                .theme()
+        );
+        assert_eq!(None, parse(&["deck.prz"]).unwrap().theme());
+    }
+
+    #[test]
+    pub fn can_parse_presenter_flag() {
+        assert!(parse(&["deck.prz", "--presenter"])
+            .unwrap()
+            .presenter_view());
+        assert!(!parse(&["deck.prz"]).unwrap().presenter_view());
+    }
+
+    #[test]
+    pub fn can_parse_volume_keys_flag() {
+        assert!(parse(&["deck.prz", "--volume-keys"]).unwrap().volume_keys());
+        assert!(!parse(&["deck.prz"]).unwrap().volume_keys());
+    }
+
+    #[test]
+    pub fn can_parse_include_hidden_flag() {
+        assert!(parse(&["deck.prz", "--include-hidden"])
+            .unwrap()
+            .include_hidden());
+        assert!(!parse(&["deck.prz"]).unwrap().include_hidden());
+    }
+
+    #[test]
+    pub fn can_parse_window_options() {
+        let mut expected = WindowOptions::default();
+        expected.set_borderless(true);
+        expected.set_always_on_top(true);
+        expected.set_position(-1920, 40);
+        expected.set_transparent_background(true);
+
+        assert_eq!(
+            expected,
+            parse(&[
+                "deck.prz",
+                "--borderless",
+                "--always-on-top",
+                "--window-position",
+                "-1920",
+                "40",
+                "--transparent-background",
+            ])
+            .unwrap()
+            .window_options()
+        );
+        assert_eq!(
+            WindowOptions::default(),
+            parse(&["deck.prz"]).unwrap().window_options()
+        );
+    }
+
+    #[test]
+    pub fn can_parse_no_vsync_flag() {
+        assert!(!parse(&["deck.prz", "--no-vsync"])
+            .unwrap()
+            .window_options()
+            .vsync());
+        assert!(parse(&["deck.prz"]).unwrap().window_options().vsync());
+    }
+
+    #[test]
+    pub fn can_parse_display() {
+        assert_eq!(
+            Some(1),
+            parse(&["deck.prz", "--display", "1"])
+                .unwrap()
+                .window_options()
+                .display()
+        );
+        assert_eq!(
+            Err(Error::InvalidValue {
+                option: "--display".into(),
+                value: "-1".into()
+            }),
+            parse(&["deck.prz", "--display", "-1"])
+        );
+    }
+
+    #[test]
+    pub fn fails_on_incomplete_window_position() {
+        assert_eq!(
+            Err(Error::InvalidValue {
+                option: "--window-position".into(),
+                value: "left".into()
+            }),
+            parse(&["deck.prz", "--window-position", "left", "0"])
+        );
+        assert_eq!(
+            Err(Error::MissingValue("--window-position".into())),
+            parse(&["deck.prz", "--window-position", "10"])
+        );
+    }
+
+    #[test]
+    pub fn guesses_input_format_from_extension() {
+        assert_eq!(
+            InputFormat::Markdown,
+            parse(&["deck.md"]).unwrap().input_format()
+        );
+        assert_eq!(
+            InputFormat::Dsl,
+            parse(&["deck.prz"]).unwrap().input_format()
+        );
+        assert_eq!(
+            InputFormat::Json,
+            parse(&["deck.json"]).unwrap().input_format()
+        );
+    }
+
+    #[test]
+    pub fn input_format_can_be_forced() {
+        assert_eq!(
+            InputFormat::Markdown,
+            parse(&["--format", "md", "deck.txt"])
+                .unwrap()
+                .input_format()
+        );
+        assert_eq!(
+            InputFormat::Json,
+            parse(&["--format", "json", "deck.txt"])
+                .unwrap()
+                .input_format()
+        );
+    }
+
+    #[test]
+    pub fn fails_on_export_without_output() {
+        assert_eq!(
+            Err(Error::MissingValue("--out".into())),
+            parse(&["export", "--format", "png", "deck.prz"])
+        );
+    }
+
+    #[test]
+    pub fn fails_on_unknown_format() {
+        assert_eq!(
+            Err(Error::InvalidValue {
+                option: "--format".into(),
+                value: "gif".into()
+            }),
+            parse(&["export", "--format", "gif", "deck.prz"])
+        );
+    }
+
+    #[test]
+    pub fn fails_on_invalid_width() {
+        assert_eq!(
+            Err(Error::InvalidValue {
+                option: "--width".into(),
+                value: "0".into()
+            }),
+            parse(&["export", "--width", "0", "deck.prz"])
+        );
+    }
+
+    #[test]
+    pub fn fails_on_unknown_option() {
+        assert_eq!(
+            Err(Error::UnknownOption("--something".into())),
+            parse(&["--something", "deck.prz"])
+        );
+    }
+
+    #[test]
+    pub fn fails_on_missing_presentation_path() {
+        assert_eq!(Err(Error::MissingPresentationPath), parse(&[]));
+    }
+
+    #[test]
+    pub fn can_parse_overview_key() {
+        assert_eq!(
+            OverviewKey::Escape,
+            parse(&["deck.prz", "--overview-key", "escape"])
+                .unwrap()
+                .overview_key()
+        );
+        assert_eq!(
+            Err(Error::InvalidValue {
+                option: "--overview-key".into(),
+                value: "x".into()
+            }),
+            parse(&["deck.prz", "--overview-key", "x"])
+        );
+    }
+
+    #[test]
+    pub fn can_parse_pointer_color() {
+        assert_eq!(
+            Some(Color::new(0, 0, 0xff, 0xff)),
+            parse(&["deck.prz", "--pointer-color", "blue"])
+                .unwrap()
+                .pointer_color()
+        );
+        assert_eq!(
+            Some(Color::new(0x12, 0x34, 0x56, 0xff)),
+            parse(&["deck.prz", "--pointer-color", "#123456"])
+                .unwrap()
+                .pointer_color()
+        );
+    }
+
+    #[test]
+    pub fn can_parse_auto_advance_durations() {
+        let auto_advance = |value: &str| {
+            parse(&["deck.prz", "--auto-advance", value]).map(|arguments| arguments.auto_advance())
+        };
+
+        assert_eq!(Ok(Some(Duration::from_secs(10))), auto_advance("10s"));
+        assert_eq!(Ok(Some(Duration::from_secs(10))), auto_advance("10"));
+        assert_eq!(Ok(Some(Duration::from_secs(120))), auto_advance("2m"));
+        assert_eq!(Ok(Some(Duration::from_millis(500))), auto_advance("500ms"));
+        assert_eq!(
+            Err(Error::InvalidValue {
+                option: "--auto-advance".into(),
+                value: "soon".into()
+            }),
+            auto_advance("soon")
+        );
+    }
+
+    #[test]
+    pub fn can_parse_talk_length() {
+        assert_eq!(
+            Some(Duration::from_secs(20 * 60)),
+            parse(&["deck.prz", "--talk-length", "20m"])
+                .unwrap()
+                .talk_length()
+        );
+    }
+}
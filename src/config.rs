@@ -0,0 +1,405 @@
+use crate::control::Action;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct ConfigError {
+    path: PathBuf,
+    line: usize,
+    message: String,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.path.display(), self.line, self.message)
+    }
+}
+
+/// Defaults for the command line options, read from `config.toml` files.
+/// Only the part of TOML the settings need is understood: strings,
+/// booleans and arrays of strings, at the top level or in `[keys]`.
+#[derive(Debug, Eq, PartialEq, Clone, Default)]
+pub struct Config {
+    fullscreen: Option<bool>,
+    keys: Vec<(String, Action)>,
+    theme_dirs: Vec<PathBuf>,
+    font_dirs: Vec<PathBuf>,
+}
+
+impl Config {
+    /// Reads the system wide config files and the user's one over them,
+    /// any of them may be missing
+    pub fn load() -> Result<Self, ConfigError> {
+        let mut config = Self::default();
+        for path in config_paths() {
+            match fs::read_to_string(&path) {
+                Ok(source) => config.merge(Self::parse(&source, &path)?),
+                Err(ref error) if error.kind() == io::ErrorKind::NotFound => {}
+                Err(error) => {
+                    return Err(ConfigError {
+                        path,
+                        line: 0,
+                        message: error.to_string(),
+                    })
+                }
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// Relative directories are relative to the config file
+    pub fn parse(source: &str, path: &Path) -> Result<Self, ConfigError> {
+        let base = path.parent().unwrap_or_else(|| Path::new(""));
+        let mut config = Self::default();
+
+        let entries = entries(source).map_err(|(line, message)| ConfigError {
+            path: path.into(),
+            line,
+            message,
+        })?;
+
+        for Entry {
+            line,
+            table,
+            key,
+            value,
+        } in entries
+        {
+            let error = |message: String| ConfigError {
+                path: path.into(),
+                line,
+                message,
+            };
+
+            match (table.as_str(), key.as_str()) {
+                ("", "fullscreen") => config.fullscreen = Some(value.as_bool().map_err(error)?),
+                ("", "theme-dirs") => {
+                    config.theme_dirs = directories(base, value.as_strings().map_err(error)?);
+                }
+                ("", "font-dirs") => {
+                    config.font_dirs = directories(base, value.as_strings().map_err(error)?);
+                }
+                ("keys", action) => {
+                    let action = Action::from_name(action)
+                        .ok_or_else(|| error(format!("unknown action `{}`", action)))?;
+                    for key in value.as_strings().map_err(error)? {
+                        config.keys.push((key, action));
+                    }
+                }
+                _ => return Err(error(format!("unknown setting `{}`", key))),
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// Puts the other config's settings over this one's, its directories
+    /// are searched first
+    pub fn merge(&mut self, other: Self) {
+        self.fullscreen = other.fullscreen.or(self.fullscreen);
+        self.keys.splice(0..0, other.keys);
+        self.theme_dirs.splice(0..0, other.theme_dirs);
+        self.font_dirs.splice(0..0, other.font_dirs);
+    }
+
+    pub fn fullscreen(&self) -> Option<bool> {
+        self.fullscreen
+    }
+
+    /// Key names bound to actions, the first binding of a key wins
+    pub fn keys(&self) -> &Vec<(String, Action)> {
+        &self.keys
+    }
+
+    /// Directories `--theme` looks the theme up in when it isn't a path
+    pub fn theme_dirs(&self) -> &Vec<PathBuf> {
+        &self.theme_dirs
+    }
+
+    /// Directories fonts are looked up in, after the presentation's own
+    pub fn font_dirs(&self) -> &Vec<PathBuf> {
+        &self.font_dirs
+    }
+
+    /// Theme file with the name in one of the theme directories, the `.przt`
+    /// extension may be left out
+    pub fn find_theme(&self, name: &str) -> Option<PathBuf> {
+        self.theme_dirs
+            .iter()
+            .flat_map(|dir| vec![dir.join(name), dir.join(name).with_extension("przt")])
+            .find(|path| path.is_file())
+    }
+}
+
+/// System wide files first, from `$XDG_CONFIG_DIRS`, then the user's from
+/// `$XDG_CONFIG_HOME` or `~/.config`
+fn config_paths() -> Vec<PathBuf> {
+    let non_empty = |name| std::env::var_os(name).filter(|value| !value.is_empty());
+    let system_dirs = non_empty("XDG_CONFIG_DIRS").unwrap_or_else(|| "/etc/xdg".into());
+    let user_dir = non_empty("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| non_empty("HOME").map(|home| Path::new(&home).join(".config")));
+
+    let mut dirs: Vec<PathBuf> = std::env::split_paths(&system_dirs).collect();
+    // the first of the system directories is the most important one
+    dirs.reverse();
+    dirs.extend(user_dir);
+
+    dirs.into_iter()
+        .map(|dir| dir.join("przntr").join("config.toml"))
+        .collect()
+}
+
+/// Expands `~` to the home directory and resolves relative directories
+/// against the base
+fn directories(base: &Path, values: Vec<String>) -> Vec<PathBuf> {
+    values
+        .into_iter()
+        .map(|value| match value.strip_prefix("~/") {
+            Some(rest) => std::env::var_os("HOME")
+                .map_or_else(|| PathBuf::from(&value), |home| Path::new(&home).join(rest)),
+            None => base.join(value),
+        })
+        .collect()
+}
+
+#[derive(Debug, Eq, PartialEq)]
+enum Value {
+    String(String),
+    Bool(bool),
+    Array(Vec<String>),
+}
+
+impl Value {
+    fn as_bool(&self) -> Result<bool, String> {
+        match self {
+            Self::Bool(value) => Ok(*value),
+            _ => Err("expected `true` or `false`".into()),
+        }
+    }
+
+    /// A single string counts as an array of one
+    fn as_strings(&self) -> Result<Vec<String>, String> {
+        match self {
+            Self::String(value) => Ok(vec![value.clone()]),
+            Self::Array(values) => Ok(values.clone()),
+            Self::Bool(_) => Err("expected a string or an array of strings".into()),
+        }
+    }
+}
+
+/// A `key = value` line, under the table it is in
+struct Entry {
+    line: usize,
+    table: String,
+    key: String,
+    value: Value,
+}
+
+/// The settings, failures come with their line number
+fn entries(source: &str) -> Result<Vec<Entry>, (usize, String)> {
+    let mut entries = vec![];
+    let mut table = String::new();
+    let mut lines = source.lines().enumerate();
+
+    while let Some((index, line)) = lines.next() {
+        let line_number = index + 1;
+        let mut line = strip_comment(line).trim().to_string();
+        if line.is_empty() {
+            continue;
+        }
+        if line.starts_with('[') && line.ends_with(']') {
+            table = line[1..line.len() - 1].trim().to_string();
+            if !table.is_empty() && table != "keys" {
+                return Err((line_number, format!("unknown table `{}`", table)));
+            }
+            continue;
+        }
+
+        // arrays may go on over several lines
+        let is_array = line
+            .split_once('=')
+            .is_some_and(|(_, value)| value.trim_start().starts_with('['));
+        while is_array && !line.ends_with(']') {
+            match lines.next() {
+                Some((_, next)) => line.push_str(strip_comment(next).trim()),
+                None => return Err((line_number, "the array is not closed".into())),
+            }
+        }
+
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| (line_number, "expected `key = value`".to_string()))?;
+        let key = key.trim().trim_matches('"').to_string();
+        let value = parse_value(value.trim()).map_err(|message| (line_number, message))?;
+        entries.push(Entry {
+            line: line_number,
+            table: table.clone(),
+            key,
+            value,
+        });
+    }
+
+    Ok(entries)
+}
+
+fn parse_value(value: &str) -> Result<Value, String> {
+    match value {
+        "true" => Ok(Value::Bool(true)),
+        "false" => Ok(Value::Bool(false)),
+        _ if value.starts_with('[') && value.ends_with(']') => {
+            let mut values = vec![];
+            let mut rest = value[1..value.len() - 1].trim();
+            while !rest.is_empty() {
+                let (string, after) = parse_string(rest)?;
+                values.push(string);
+                rest = after.trim_start();
+                rest = rest.strip_prefix(',').unwrap_or(rest).trim_start();
+            }
+
+            Ok(Value::Array(values))
+        }
+        _ => match parse_string(value)? {
+            (string, "") => Ok(Value::String(string)),
+            (_, rest) => Err(format!("unexpected `{}` after the string", rest.trim())),
+        },
+    }
+}
+
+/// Reads a basic string or a literal one, returns it with the rest of the
+/// input
+fn parse_string(input: &str) -> Result<(String, &str), String> {
+    let mut characters = input.char_indices();
+    let Some((_, quote @ ('"' | '\''))) = characters.next() else {
+        return Err(format!("expected a value, found `{}`", input));
+    };
+
+    let mut string = String::new();
+    while let Some((index, character)) = characters.next() {
+        match character {
+            _ if character == quote => return Ok((string, &input[index + 1..])),
+            '\\' if quote == '"' => match characters.next().map(|(_, escaped)| escaped) {
+                Some('n') => string.push('\n'),
+                Some('t') => string.push('\t'),
+                Some(escaped @ ('"' | '\\')) => string.push(escaped),
+                _ => return Err("unknown escape sequence".into()),
+            },
+            _ => string.push(character),
+        }
+    }
+
+    Err("the string is not closed".into())
+}
+
+/// The line without a `#` comment, `#` inside strings is kept
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut escaped = false;
+    for (index, character) in line.char_indices() {
+        match (quote, character) {
+            (Some('"'), '\\') if !escaped => {
+                escaped = true;
+                continue;
+            }
+            (None, '"' | '\'') => quote = Some(character),
+            (Some(open), _) if open == character && !escaped => quote = None,
+            (None, '#') => return &line[..index],
+            _ => {}
+        }
+        escaped = false;
+    }
+
+    line
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(source: &str) -> Result<Config, ConfigError> {
+        Config::parse(source, Path::new("/home/me/.config/przntr/config.toml"))
+    }
+
+    #[test]
+    pub fn can_parse_settings() {
+        let config = parse(
+            r#"
+            # defaults for every talk
+            fullscreen = true
+            theme-dirs = ["themes", "/usr/share/przntr/themes"]
+            font-dirs = [
+                "fonts", # next to the config
+            ]
+
+            [keys]
+            next = ["N", "J"]
+            previous = "K"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(Some(true), config.fullscreen());
+        assert_eq!(
+            &vec![
+                PathBuf::from("/home/me/.config/przntr/themes"),
+                PathBuf::from("/usr/share/przntr/themes")
+            ],
+            config.theme_dirs()
+        );
+        assert_eq!(
+            &vec![PathBuf::from("/home/me/.config/przntr/fonts")],
+            config.font_dirs()
+        );
+        assert_eq!(
+            &vec![
+                ("N".to_string(), Action::Next),
+                ("J".to_string(), Action::Next),
+                ("K".to_string(), Action::Previous)
+            ],
+            config.keys()
+        );
+    }
+
+    #[test]
+    pub fn reports_the_line_of_invalid_settings() {
+        assert_eq!(
+            Err(ConfigError {
+                path: "/home/me/.config/przntr/config.toml".into(),
+                line: 3,
+                message: "expected `true` or `false`".into()
+            }),
+            parse("theme-dirs = 'themes'\n\nfullscreen = \"yes\"")
+        );
+        assert_eq!(
+            "unknown action `jump`",
+            parse("[keys]\njump = 'J'").unwrap_err().message
+        );
+        assert_eq!(
+            "the array is not closed",
+            parse("font-dirs = [\n'fonts',").unwrap_err().message
+        );
+    }
+
+    #[test]
+    pub fn keeps_hashes_and_escapes_inside_strings() {
+        assert_eq!(
+            Ok(Value::Array(vec!["#1".into(), "a\"b".into()])),
+            parse_value(strip_comment(r##"["#1", "a\"b"] # comment"##).trim())
+        );
+    }
+
+    #[test]
+    pub fn later_configs_go_over_earlier_ones() {
+        let mut config = parse("fullscreen = true\ntheme-dirs = ['/system']").unwrap();
+        config.merge(parse("fullscreen = false\ntheme-dirs = ['/user']").unwrap());
+
+        assert_eq!(Some(false), config.fullscreen());
+        assert_eq!(
+            &vec![PathBuf::from("/user"), PathBuf::from("/system")],
+            config.theme_dirs()
+        );
+    }
+}
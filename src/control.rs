@@ -0,0 +1,326 @@
+use crate::presentation::ControlInput;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::net::{IpAddr, UdpSocket};
+use std::sync::mpsc::Sender;
+use std::thread;
+
+/// What a key or a control message does outside the overview, kept apart
+/// from the keys so that remotes and stage-control setups drive the same
+/// actions
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Action {
+    Next,
+    Previous,
+    First,
+    Last,
+    TogglePointer,
+    ToggleTimer,
+    TogglePlayback,
+}
+
+impl Action {
+    /// Parses the names used in control bindings, e.g. `next` or `pointer`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "next" => Some(Self::Next),
+            "previous" => Some(Self::Previous),
+            "first" => Some(Self::First),
+            "last" => Some(Self::Last),
+            "pointer" => Some(Self::TogglePointer),
+            "timer" => Some(Self::ToggleTimer),
+            "playback" => Some(Self::TogglePlayback),
+            _ => None,
+        }
+    }
+
+    /// The name `from_name` takes
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Next => "next",
+            Self::Previous => "previous",
+            Self::First => "first",
+            Self::Last => "last",
+            Self::TogglePointer => "pointer",
+            Self::ToggleTimer => "timer",
+            Self::TogglePlayback => "playback",
+        }
+    }
+}
+
+/// Addresses used when an OSC input doesn't bind any of its own
+fn default_addresses() -> HashMap<String, Action> {
+    [
+        ("/przntr/next", Action::Next),
+        ("/przntr/previous", Action::Previous),
+        ("/przntr/first", Action::First),
+        ("/przntr/last", Action::Last),
+    ]
+    .iter()
+    .map(|&(address, action)| (address.to_string(), action))
+    .collect()
+}
+
+/// Notes used when a MIDI input doesn't bind any of its own, middle C
+/// going forward and the B below it going back
+fn default_notes() -> HashMap<u8, Action> {
+    [(60, Action::Next), (59, Action::Previous)]
+        .iter()
+        .copied()
+        .collect()
+}
+
+/// Starts listening on the input in a thread of its own, sending the
+/// actions its messages are bound to until the receiving end goes away.
+/// OSC inputs listen on the address, MIDI inputs read their device.
+pub fn listen(input: &ControlInput, address: IpAddr, sender: Sender<Action>) -> Result<(), String> {
+    match input {
+        ControlInput::Osc { port, addresses } => {
+            let socket = UdpSocket::bind((address, *port)).map_err(|e| {
+                format!(
+                    "Could not listen for OSC on {} port {}: {}",
+                    address, port, e
+                )
+            })?;
+            let addresses = if addresses.is_empty() {
+                default_addresses()
+            } else {
+                addresses.clone()
+            };
+            thread::spawn(move || listen_osc(&socket, &addresses, &sender));
+        }
+        ControlInput::Midi { device, notes } => {
+            let file = File::open(device)
+                .map_err(|e| format!("Could not open MIDI device {}: {}", device, e))?;
+            let notes = if notes.is_empty() {
+                default_notes()
+            } else {
+                notes.clone()
+            };
+            thread::spawn(move || listen_midi(file, &notes, &sender));
+        }
+    }
+
+    Ok(())
+}
+
+fn listen_osc(socket: &UdpSocket, addresses: &HashMap<String, Action>, sender: &Sender<Action>) {
+    let mut buffer = [0; 1536];
+    while let Ok(length) = socket.recv(&mut buffer) {
+        for message in decode_osc(&buffer[..length]) {
+            if !message.pressed {
+                continue;
+            }
+            if let Some(&action) = addresses.get(&message.address) {
+                if sender.send(action).is_err() {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+fn listen_midi(mut device: File, notes: &HashMap<u8, Action>, sender: &Sender<Action>) {
+    let mut parser = MidiParser::default();
+    let mut buffer = [0; 64];
+    while let Ok(length) = device.read(&mut buffer) {
+        if length == 0 {
+            return;
+        }
+        for &byte in &buffer[..length] {
+            let action = parser.feed(byte).and_then(|note| notes.get(&note));
+            if let Some(&action) = action {
+                if sender.send(action).is_err() {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// OSC message as far as the bindings care about it
+#[derive(Debug, Eq, PartialEq)]
+pub struct OscMessage {
+    pub address: String,
+    /// False when the first argument is a zero or false, which buttons on
+    /// control surfaces send when they are let go
+    pub pressed: bool,
+}
+
+/// Decodes an OSC packet into its messages, looking into bundles. Malformed
+/// parts are dropped.
+pub fn decode_osc(packet: &[u8]) -> Vec<OscMessage> {
+    let mut messages = vec![];
+    decode_osc_into(packet, &mut messages);
+
+    messages
+}
+
+/// Stops at the first malformed bundle element
+fn decode_osc_into(packet: &[u8], messages: &mut Vec<OscMessage>) -> Option<()> {
+    let (address, rest) = osc_string(packet)?;
+
+    if address == "#bundle" {
+        // skip the time tag, elements follow prefixed with their size
+        let mut elements = rest.get(8..).unwrap_or_default();
+        while let Some(size) = elements.get(..4) {
+            let size = u32::from_be_bytes([size[0], size[1], size[2], size[3]]) as usize;
+            decode_osc_into(elements.get(4..4 + size)?, messages);
+            elements = &elements[4 + size..];
+        }
+    } else if address.starts_with('/') {
+        messages.push(OscMessage {
+            pressed: first_argument_is_zero(rest) != Some(true),
+            address,
+        });
+    }
+
+    Some(())
+}
+
+/// A zero-terminated string padded to four bytes and what follows it
+fn osc_string(data: &[u8]) -> Option<(String, &[u8])> {
+    let end = data.iter().position(|&byte| byte == 0)?;
+    let string = String::from_utf8(data[..end].to_vec()).ok()?;
+    let padded = (end / 4 + 1) * 4;
+
+    Some((string, data.get(padded..).unwrap_or_default()))
+}
+
+/// False counts as a zero, true and arguments of other types don't
+fn first_argument_is_zero(data: &[u8]) -> Option<bool> {
+    let (tags, arguments) = osc_string(data)?;
+    let tag = tags.chars().nth(1);
+    // true and false are in the tag alone, without argument bytes
+    match tag {
+        Some('T') => return Some(false),
+        Some('F') => return Some(true),
+        _ => {}
+    }
+
+    let value = arguments.get(..4)?;
+    let value = [value[0], value[1], value[2], value[3]];
+
+    Some(match tag {
+        Some('i') => i32::from_be_bytes(value) == 0,
+        Some('f') => f32::from_be_bytes(value) == 0.0,
+        _ => false,
+    })
+}
+
+/// Picks note-on messages out of a raw MIDI byte stream, keeping track of
+/// running status
+#[derive(Debug, Default)]
+pub struct MidiParser {
+    status: Option<u8>,
+    data: Vec<u8>,
+}
+
+impl MidiParser {
+    /// Returns the note when the byte completes a note-on
+    pub fn feed(&mut self, byte: u8) -> Option<u8> {
+        match byte {
+            // real-time messages can come in the middle of others
+            0xf8..=0xff => None,
+            0x80..=0xf7 => {
+                self.status = if byte < 0xf0 { Some(byte) } else { None };
+                self.data.clear();
+                None
+            }
+            _ => {
+                let status = self.status?;
+                self.data.push(byte);
+                if self.data.len() < message_length(status) {
+                    return None;
+                }
+
+                let data = std::mem::take(&mut self.data);
+                // a note-on with no velocity is a note-off
+                if status & 0xf0 == 0x90 && data[1] > 0 {
+                    Some(data[0])
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+/// Data bytes that follow the status byte
+fn message_length(status: u8) -> usize {
+    match status & 0xf0 {
+        0xc0 | 0xd0 => 1,
+        _ => 2,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn decodes_osc_messages_and_bundles() {
+        let message = b"/go\0,f\0\0\x3f\x80\0\0";
+        assert_eq!(
+            vec![OscMessage {
+                address: "/go".into(),
+                pressed: true
+            }],
+            decode_osc(message)
+        );
+
+        let mut bundle = b"#bundle\0\0\0\0\0\0\0\0\x01".to_vec();
+        bundle.extend_from_slice(&[0, 0, 0, 8]);
+        bundle.extend_from_slice(b"/back\0\0\0");
+        bundle.extend_from_slice(&[0, 0, 0, 12]);
+        bundle.extend_from_slice(b"/go\0,i\0\0\0\0\0\0");
+        assert_eq!(
+            vec![
+                OscMessage {
+                    address: "/back".into(),
+                    pressed: true
+                },
+                OscMessage {
+                    address: "/go".into(),
+                    pressed: false
+                }
+            ],
+            decode_osc(&bundle)
+        );
+    }
+
+    #[test]
+    pub fn takes_false_for_a_release() {
+        assert_eq!(
+            vec![OscMessage {
+                address: "/go".into(),
+                pressed: false
+            }],
+            decode_osc(b"/go\0,F\0\0")
+        );
+        assert_eq!(
+            vec![OscMessage {
+                address: "/go".into(),
+                pressed: true
+            }],
+            decode_osc(b"/go\0,T\0\0")
+        );
+    }
+
+    #[test]
+    pub fn picks_note_ons_out_of_midi() {
+        let mut parser = MidiParser::default();
+        let notes: Vec<u8> = [
+            0x90, 60, 100, // note on
+            62, 0,    // running status note off
+            0xf8, // clock
+            61, 90, 0x80, 60, 0, 0xc0, 5,
+        ]
+        .iter()
+        .filter_map(|&byte| parser.feed(byte))
+        .collect();
+
+        assert_eq!(vec![60, 61], notes);
+    }
+}
@@ -0,0 +1,392 @@
+use crate::parsing::markup::MarkupError;
+use crate::parsing::parser::Error;
+use crate::parsing::token_stream::{SourceLocationRange, TokenizerFailureKind};
+use crate::presentation::StyleError;
+use crate::spelling::Misspelling;
+use crate::validation::ValidationError;
+
+/// Describes a parser error the way compilers do: a message, followed by the
+/// offending source line with the problematic part underlined
+pub fn render(source: &str, path: &str, error: &Error) -> String {
+    render_included(source, path, error, &[])
+}
+
+/// Renders an error found in a file reached through `include` statements,
+/// `included_from` lists where each of them is, innermost first
+fn render_included(source: &str, path: &str, error: &Error, included_from: &[String]) -> String {
+    if let Error::InIncludedFile {
+        path: included_path,
+        source: included_source,
+        location,
+        errors,
+    } = error
+    {
+        let mut chain = vec![format!(
+            "{}:{}:{}",
+            path,
+            location.start().line() + 1,
+            location.start().column()
+        )];
+        chain.extend(included_from.iter().cloned());
+
+        return errors
+            .iter()
+            .map(|error| render_included(included_source, included_path, error, &chain))
+            .collect::<Vec<String>>()
+            .join("\n");
+    }
+
+    let source_part = match location(error) {
+        Some(location) => snippet(source, path, location),
+        None => format!(" --> {}\n", path),
+    };
+    let notes = included_from
+        .iter()
+        .map(|place| format!(" = note: included from {}\n", place))
+        .collect::<Vec<String>>()
+        .concat();
+
+    format!("error: {}\n{}{}", message(error), source_part, notes)
+}
+
+/// Renders every error, separated by blank lines
+pub fn render_all(source: &str, path: &str, errors: &[Error]) -> String {
+    errors
+        .iter()
+        .map(|error| render(source, path, error))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Describes a problem found while validating an already parsed presentation
+pub fn render_validation(path: &str, error: &ValidationError) -> String {
+    format!("error: {}\n --> {}\n", error.message(), path)
+}
+
+/// Describes a word the spell checker doesn't know, pointing at it when its
+/// location is known
+pub fn render_misspelling(source: &str, path: &str, misspelling: &Misspelling) -> String {
+    let source_part = match misspelling.location() {
+        Some(location) => snippet(source, path, location),
+        None => format!(" --> {}\n", path),
+    };
+
+    format!("warning: {}\n{}", misspelling.message(), source_part)
+}
+
+/// What went wrong, without where
+pub fn message(error: &Error) -> String {
+    match error {
+        Error::UnexpectedToken {
+            actual, expected, ..
+        } => format!("unexpected {}, expected one of: {}", actual, expected),
+        Error::UnexpectedEndOfStream { expected } => {
+            format!("unexpected end of file, expected one of: {}", expected)
+        }
+        Error::TokenizerFailure(failure) => tokenizer_message(failure.kind()),
+        Error::InvalidStyleDefinition(StyleError::DuplicateFont(font)) => format!(
+            "font `{}` with weight {}{} is defined more than once",
+            font.name(),
+            font.weight(),
+            if font.italic() { " (italic)" } else { "" }
+        ),
+        Error::InvalidStyleDefinition(StyleError::DuplicateTextStyle(name)) => {
+            format!("text style `{}` is defined more than once", name)
+        }
+        Error::InvalidColor { value, .. } => format!("`{}` is not a valid color", value),
+        Error::IncludeFailed { path, reason, .. } => {
+            format!("cannot include `{}`: {}", path, reason)
+        }
+        Error::IncludeCycle { path, .. } => {
+            format!("including `{}` again would never end", path)
+        }
+        Error::ThemeFailed { path, reason, .. } => {
+            format!("cannot load theme `{}`: {}", path, reason)
+        }
+        Error::AssetFailed { reason, .. } => reason.clone(),
+        Error::SlidesInTheme => "themes can only define styles and layouts, not slides".into(),
+        Error::InIncludedFile { path, .. } => format!("errors in included file `{}`", path),
+        Error::DuplicateLayout { name, .. } => format!("layout `{}` is defined twice", name),
+        Error::UndefinedVariable { name, .. } => {
+            format!("variable `{}` is used before it is defined", name)
+        }
+        Error::InvalidMarkup { error, .. } => markup_message(error),
+        Error::InvalidTransition { value, .. } => {
+            invalid_value(value, "transition", "fade, slide or none")
+        }
+        Error::InvalidAlignment { value, .. } => {
+            invalid_value(value, "alignment", "start, center or end")
+        }
+        Error::InvalidHinting { value, .. } => {
+            invalid_value(value, "hinting", "normal, light, mono or none")
+        }
+        Error::InvalidGradientDirection { value, .. } => invalid_value(
+            value,
+            "gradient direction",
+            "horizontal, vertical, diagonal or radial",
+        ),
+        Error::InvalidImageFit { value, .. } => {
+            invalid_value(value, "image fit", "cover, contain or tile")
+        }
+        Error::InvalidControlInput { value, .. } => {
+            invalid_value(value, "control input", "osc or midi")
+        }
+        Error::InvalidControlNumber { value, .. } => format!(
+            "{} is out of range, ports go up to 65535 and notes up to 127",
+            value
+        ),
+        Error::InvalidAction { value, .. } => invalid_value(
+            value,
+            "action",
+            "next, previous, first, last, pointer, timer or playback",
+        ),
+        Error::OutOfRange {
+            property,
+            value,
+            min,
+            max,
+            ..
+        } => match max {
+            Some(max) => format!(
+                "`{}` {} is out of range, expected {} to {}",
+                property, value, min, max
+            ),
+            None => format!(
+                "`{}` {} is out of range, expected at least {}",
+                property, value, min
+            ),
+        },
+        Error::DuplicateProperty {
+            block, property, ..
+        } => format!("`{}` is given more than once in `{}`", property, block),
+        Error::MissingProperty {
+            block, property, ..
+        } => format!("missing required property `{}` in `{}`", property, block),
+        Error::InvalidAnchor { value, .. } => invalid_value(
+            value,
+            "anchor",
+            "center or a side or corner such as top or bottom-left",
+        ),
+        Error::InvalidSide { value, .. } => {
+            invalid_value(value, "side", "left, right, top or bottom")
+        }
+        Error::InvalidMotion { value, .. } => format!(
+            "`{}` is not something keyframes animate or an easing, expected x, y, scale, \
+             rotate, opacity, linear, ease-in, ease-out, ease-in-out, cubic-bezier or spring",
+            value
+        ),
+    }
+}
+
+/// The value isn't one of those the property takes
+fn invalid_value(value: &str, property: &str, expected: &str) -> String {
+    format!(
+        "`{}` is not a valid {}, expected {}",
+        value, property, expected
+    )
+}
+
+fn markup_message(error: &MarkupError) -> String {
+    match error {
+        MarkupError::Unclosed(markup) => format!("`{}` is never closed", markup),
+        MarkupError::UnexpectedClosingTag(tag) => format!("`{}` closes nothing", tag),
+        MarkupError::InvalidColor(value) => format!("`{}` is not a valid color", value),
+    }
+}
+
+fn tokenizer_message(kind: &TokenizerFailureKind) -> String {
+    match kind {
+        TokenizerFailureKind::UnexpectedCharacterInName { character, .. } => {
+            format!("unexpected character `{}` in a name", character)
+        }
+        TokenizerFailureKind::UnclosedString => "string is never closed".into(),
+        TokenizerFailureKind::UnclosedComment => "comment is never closed".into(),
+        TokenizerFailureKind::UnknownEscapeSequence(character) => {
+            format!("unknown escape sequence `\\{}`", character)
+        }
+        TokenizerFailureKind::InvalidUnicodeEscape(sequence) => format!(
+            "invalid unicode escape `{}`, expected `\\u{{XXXX}}`",
+            sequence
+        ),
+        TokenizerFailureKind::UnfinishedEscapeSequence => "unfinished escape sequence".into(),
+        TokenizerFailureKind::UnexpectedCharacter(character) => {
+            format!("unexpected character `{}`", character)
+        }
+        TokenizerFailureKind::InvalidIntegerValue(value) => {
+            format!("`{}` is not a valid integer", value)
+        }
+        TokenizerFailureKind::InvalidFloatValue(value) => {
+            format!("`{}` is not a valid number", value)
+        }
+        TokenizerFailureKind::UnknownUnit(unit) => {
+            format!("unknown unit `{}`, expected px, %, em, ms or s", unit)
+        }
+        TokenizerFailureKind::InvalidColorLiteral(value) => {
+            format!("`{}` is not a valid color literal", value)
+        }
+        TokenizerFailureKind::InvalidTime(value) => {
+            format!(
+                "`{}` is not a valid time, expected mm:ss or hh:mm:ss",
+                value
+            )
+        }
+    }
+}
+
+/// Where the error is, errors in included files are at their `include`
+pub fn location(error: &Error) -> Option<SourceLocationRange> {
+    match error {
+        Error::UnexpectedToken { location, .. }
+        | Error::InvalidColor { location, .. }
+        | Error::InvalidTransition { location, .. }
+        | Error::InvalidAnchor { location, .. }
+        | Error::InvalidSide { location, .. }
+        | Error::InvalidMotion { location, .. }
+        | Error::InvalidAlignment { location, .. }
+        | Error::InvalidHinting { location, .. }
+        | Error::InvalidGradientDirection { location, .. }
+        | Error::InvalidImageFit { location, .. }
+        | Error::InvalidControlInput { location, .. }
+        | Error::InvalidControlNumber { location, .. }
+        | Error::InvalidAction { location, .. }
+        | Error::OutOfRange { location, .. }
+        | Error::DuplicateProperty { location, .. }
+        | Error::MissingProperty { location, .. }
+        | Error::IncludeFailed { location, .. }
+        | Error::IncludeCycle { location, .. }
+        | Error::ThemeFailed { location, .. }
+        | Error::AssetFailed { location, .. }
+        | Error::DuplicateLayout { location, .. }
+        | Error::UndefinedVariable { location, .. }
+        | Error::InvalidMarkup { location, .. }
+        | Error::InIncludedFile { location, .. } => Some(*location),
+        Error::TokenizerFailure(failure) => Some(failure.location()),
+        Error::UnexpectedEndOfStream { .. }
+        | Error::InvalidStyleDefinition(_)
+        | Error::SlidesInTheme => None,
+    }
+}
+
+fn snippet(source: &str, path: &str, location: SourceLocationRange) -> String {
+    let start = location.start();
+    let end = location.end();
+    let line = source.lines().nth(start.line() as usize).unwrap_or("");
+    let line_number = (start.line() + 1).to_string();
+    let gutter = " ".repeat(line_number.len());
+
+    let first_column = start.column().max(1) as usize - 1;
+    let width = if end.line() == start.line() {
+        (end.column() as usize).saturating_sub(first_column + 1)
+    } else {
+        line.chars().count().saturating_sub(first_column)
+    };
+
+    format!(
+        "{gutter}--> {}:{}:{}\n{gutter} |\n{} | {}\n{gutter} | {}{}\n",
+        path,
+        line_number,
+        start.column(),
+        line_number,
+        line,
+        " ".repeat(first_column),
+        "^".repeat(width.max(1)),
+        gutter = gutter
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn underlines_unexpected_token() {
+        let source = "metadata { title \"some title\" }\nstyle { font { invalid \"some_path\" } }";
+        let error = crate::parse(source).unwrap_err().remove(0);
+
+        assert_eq!(
+            "error: unexpected Name(\"invalid\"), expected one of: KeywordName, KeywordPath, \
+             KeywordWeight, KeywordItalic, KeywordSize, KeywordSystem, ClosingBrace\n \
+             --> deck.prz:2:16\n  |\n2 | style { font { invalid \"some_path\" } }\n  |                \
+             ^^^^^^^\n",
+            render(source, "deck.prz", &error)
+        );
+    }
+
+    #[test]
+    pub fn names_missing_properties() {
+        let source = "metadata { title \"some title\" }\nstyle {\n    font { name sans }\n}";
+        let error = crate::parse(source).unwrap_err().remove(0);
+
+        assert!(render(source, "deck.prz", &error)
+            .starts_with("error: missing required property `path` in `font`\n --> deck.prz:3:5\n"));
+    }
+
+    #[test]
+    pub fn points_at_out_of_range_numbers() {
+        let source =
+            "metadata { title \"some title\" }\nstyle { font { name sans, system, weight -100 } }";
+        let error = crate::parse(source).unwrap_err().remove(0);
+
+        assert!(render(source, "deck.prz", &error).starts_with(
+            "error: `weight` -100 is out of range, expected 1 to 1000\n --> deck.prz:2:42\n"
+        ));
+    }
+
+    #[test]
+    pub fn describes_tokenizer_failures() {
+        let source = "metadata { title \"some title }";
+        let error = crate::parse(source).unwrap_err().remove(0);
+
+        assert!(render(source, "deck.prz", &error).starts_with("error: string is never closed\n"));
+    }
+
+    #[test]
+    pub fn renders_all_errors() {
+        let source =
+            "metadata { title \"some title\" }\nslide \"a\" { text 1 }\nslide \"b\" { list 2 }";
+        let errors = crate::parse(source).unwrap_err();
+
+        let rendered = render_all(source, "deck.prz", &errors);
+        assert!(rendered.contains("--> deck.prz:2:"));
+        assert!(rendered.contains("--> deck.prz:3:"));
+    }
+
+    #[test]
+    pub fn renders_validation_errors() {
+        assert_eq!(
+            "error: the presentation does not define any fonts\n --> deck.prz\n",
+            render_validation("deck.prz", &ValidationError::NoFonts)
+        );
+    }
+
+    #[test]
+    pub fn points_at_file_without_location() {
+        let error = crate::parse("metadata {").unwrap_err().remove(0);
+
+        assert_eq!(
+            "error: unexpected end of file, expected one of: KeywordTitle, KeywordWidth, KeywordHeight, KeywordTransition, KeywordTheme, KeywordControl, KeywordShowProgress, KeywordShowPageNumbers, KeywordShowFooter, KeywordSpellingIgnore, KeywordFontDir, ClosingBrace\n --> deck.prz\n",
+            render("metadata {", "deck.prz", &error)
+        );
+    }
+
+    #[test]
+    pub fn names_the_include_chain() {
+        let directory = std::env::temp_dir().join("przntr-diagnostics-include-test");
+        std::fs::create_dir_all(&directory).unwrap();
+        std::fs::write(directory.join("outer.prz"), "include \"inner.prz\"").unwrap();
+        std::fs::write(directory.join("inner.prz"), "slide \"a\" { text }").unwrap();
+        let source = "metadata { title \"some title\" }\ninclude \"outer.prz\"";
+        let path = directory.join("deck.prz");
+
+        let errors = crate::parse_file(source, &path).unwrap_err();
+        let rendered = render_all(source, &path.to_string_lossy(), &errors);
+
+        assert!(rendered.contains("inner.prz:1:"));
+        assert!(rendered.contains(&format!(
+            " = note: included from {}:1:9\n = note: included from {}:2:9\n",
+            directory.join("outer.prz").display(),
+            path.display()
+        )));
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,202 @@
+/// Times the bezier curve is halved to find the point at a fraction of the
+/// time, which pins it down far closer than a pixel
+const BISECTIONS: u32 = 40;
+/// Springs are followed until they are this close to their end
+const SPRING_SETTLED: f64 = 0.001;
+
+/// How quickly something moves along over its time, shared by transitions,
+/// elements coming in and keyframes so that they all move alike
+#[derive(Debug, Copy, Clone, Default)]
+pub enum Easing {
+    #[default]
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+    /// A curve from the start to the end pulled towards two control
+    /// points, written like CSS's `cubic-bezier(x1, y1, x2, y2)`
+    CubicBezier(f64, f64, f64, f64),
+    /// A spring pulling towards the end, the less damped the more it
+    /// overshoots and bounces back
+    Spring {
+        stiffness: f64,
+        damping: f64,
+    },
+}
+
+impl Easing {
+    /// What `spring` is without a stiffness and damping, bouncing a little
+    pub const SPRING: Self = Self::Spring {
+        stiffness: 100.0,
+        damping: 10.0,
+    };
+
+    /// Easings written with their name alone, a spring gets the default
+    /// stiffness and damping
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "linear" => Some(Self::Linear),
+            "ease-in" => Some(Self::EaseIn),
+            "ease-out" => Some(Self::EaseOut),
+            "ease-in-out" => Some(Self::EaseInOut),
+            "spring" => Some(Self::SPRING),
+            _ => None,
+        }
+    }
+
+    /// The name the easing is written with, before any numbers it takes
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Linear => "linear",
+            Self::EaseIn => "ease-in",
+            Self::EaseOut => "ease-out",
+            Self::EaseInOut => "ease-in-out",
+            Self::CubicBezier(..) => "cubic-bezier",
+            Self::Spring { .. } => "spring",
+        }
+    }
+
+    /// How far along the way something has moved at the fraction of its
+    /// time, from 0 at the start to 1 at the end. Springs and some curves
+    /// go past the end before they settle.
+    pub fn ease(self, fraction: f64) -> f64 {
+        if fraction <= 0.0 {
+            return 0.0;
+        }
+        if fraction >= 1.0 {
+            return 1.0;
+        }
+
+        match self {
+            Self::Linear => fraction,
+            // the curves CSS names the same
+            Self::EaseIn => cubic_bezier((0.42, 0.0, 1.0, 1.0), fraction),
+            Self::EaseOut => cubic_bezier((0.0, 0.0, 0.58, 1.0), fraction),
+            Self::EaseInOut => cubic_bezier((0.42, 0.0, 0.58, 1.0), fraction),
+            Self::CubicBezier(x1, y1, x2, y2) => cubic_bezier((x1, y1, x2, y2), fraction),
+            Self::Spring { stiffness, damping } => spring(stiffness, damping, fraction),
+        }
+    }
+
+    fn parts(self) -> (u8, [u64; 4]) {
+        match self {
+            Self::Linear => (0, [0; 4]),
+            Self::EaseIn => (1, [0; 4]),
+            Self::EaseOut => (2, [0; 4]),
+            Self::EaseInOut => (3, [0; 4]),
+            Self::CubicBezier(x1, y1, x2, y2) => {
+                (4, [x1.to_bits(), y1.to_bits(), x2.to_bits(), y2.to_bits()])
+            }
+            Self::Spring { stiffness, damping } => {
+                (5, [stiffness.to_bits(), damping.to_bits(), 0, 0])
+            }
+        }
+    }
+}
+
+impl PartialEq for Easing {
+    fn eq(&self, other: &Self) -> bool {
+        self.parts() == other.parts()
+    }
+}
+
+impl Eq for Easing {}
+
+/// Height of the curve where it is the fraction of the way across, the
+/// control points are kept within the width so it only goes forward
+fn cubic_bezier((x1, y1, x2, y2): (f64, f64, f64, f64), fraction: f64) -> f64 {
+    let (x1, x2) = (x1.clamp(0.0, 1.0), x2.clamp(0.0, 1.0));
+    let curve = |first: f64, second: f64, t: f64| {
+        3.0 * first * (1.0 - t) * (1.0 - t) * t + 3.0 * second * (1.0 - t) * t * t + t * t * t
+    };
+
+    let (mut low, mut high) = (0.0, 1.0);
+    for _ in 0..BISECTIONS {
+        let middle = (low + high) / 2.0;
+        if curve(x1, x2, middle) < fraction {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+
+    curve(y1, y2, (low + high) / 2.0)
+}
+
+/// Position of a weight on a spring let go at the start and pulled to the
+/// end, with the time stretched so that it has settled by the end
+fn spring(stiffness: f64, damping: f64, fraction: f64) -> f64 {
+    let frequency = stiffness.max(f64::EPSILON).sqrt();
+    let ratio = damping.max(f64::EPSILON) / (2.0 * frequency);
+    // how quickly the slowest part of the motion dies down
+    let decay = if ratio < 1.0 {
+        ratio * frequency
+    } else {
+        frequency * (ratio - (ratio * ratio - 1.0).sqrt())
+    };
+    let time = fraction * -SPRING_SETTLED.ln() / decay;
+
+    if ratio < 1.0 {
+        let bounce = frequency * (1.0 - ratio * ratio).sqrt();
+        1.0 - (-decay * time).exp()
+            * ((bounce * time).cos() + decay / bounce * (bounce * time).sin())
+    } else if (ratio - 1.0).abs() < f64::EPSILON {
+        1.0 - (-frequency * time).exp() * (1.0 + frequency * time)
+    } else {
+        let root = frequency * (ratio * ratio - 1.0).sqrt();
+        let (fast, slow) = (-frequency * ratio - root, -frequency * ratio + root);
+        1.0 - (fast * (slow * time).exp() - slow * (fast * time).exp()) / (fast - slow)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn starts_and_ends_in_place() {
+        let easings = vec![
+            Easing::Linear,
+            Easing::EaseIn,
+            Easing::EaseOut,
+            Easing::EaseInOut,
+            Easing::CubicBezier(0.3, -0.5, 0.7, 1.5),
+            Easing::SPRING,
+            Easing::Spring {
+                stiffness: 100.0,
+                damping: 40.0,
+            },
+        ];
+
+        for easing in easings {
+            assert!(easing.ease(0.0).abs() < 1e-9);
+            assert!((easing.ease(1.0) - 1.0).abs() < 1e-9);
+            assert!((easing.ease(0.999) - 1.0).abs() < 0.01, "{:?}", easing);
+        }
+    }
+
+    #[test]
+    pub fn eases_in_slowly_and_out_quickly() {
+        assert!(Easing::EaseIn.ease(0.25) < 0.25);
+        assert!(Easing::EaseOut.ease(0.25) > 0.25);
+        assert!((Easing::EaseInOut.ease(0.5) - 0.5).abs() < 1e-6);
+        assert!((Easing::CubicBezier(0.0, 0.0, 1.0, 1.0).ease(0.3) - 0.3).abs() < 1e-6);
+    }
+
+    #[test]
+    pub fn springs_overshoot_unless_damped() {
+        let peak = |easing: Easing| {
+            (1..100)
+                .map(|step| easing.ease(f64::from(step) / 100.0))
+                .fold(0.0, f64::max)
+        };
+
+        assert!(peak(Easing::SPRING) > 1.1);
+        assert!(
+            peak(Easing::Spring {
+                stiffness: 100.0,
+                damping: 40.0
+            }) <= 1.0
+        );
+    }
+}
@@ -1,49 +1,527 @@
-use sdl2::event::Event;
+use crate::control::Action;
+use crate::navigation::Navigation;
+use crate::playback::Playback;
+use crate::pointer::Pointer;
+use crate::rendering::display;
+use crate::rendering::error::RenderError;
+use crate::session::SessionError;
+use crate::talk_timer::TalkTimer;
+use sdl2::event::{Event, WindowEvent};
 use sdl2::keyboard::Keycode;
+use sdl2::mouse::MouseButton;
 use sdl2::Sdl;
-use std::time::Duration;
+use std::cell::RefCell;
+use std::fmt;
+use std::sync::mpsc::Receiver;
+use std::time::{Duration, Instant};
 
+/// Longest the loop sleeps waiting for events
+const MAX_WAIT: Duration = Duration::from_secs(1);
+
+/// How often control input is checked for actions
+const CONTROL_WAIT: Duration = Duration::from_millis(50);
+
+/// Wait before running a failed participant again, doubled after every
+/// further failure up to the longest wait
+const FIRST_RETRY: Duration = Duration::from_millis(100);
+const LONGEST_RETRY: Duration = Duration::from_secs(5);
+
+/// Why a participant failed to run
+#[derive(Debug)]
+pub enum Error {
+    /// Drawing into the participant's window failed
+    Render(RenderError),
+    SaveSession(SessionError),
+    /// Putting the participant's window back onto its display failed
+    MoveWindow(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Render(error) => write!(f, "{}", error),
+            Self::SaveSession(error) => write!(f, "failed to save the session: {:?}", error),
+            Self::MoveWindow(message) => write!(f, "failed to move the window: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Render(error) => Some(error),
+            Self::SaveSession(_) | Self::MoveWindow(_) => None,
+        }
+    }
+}
+
+impl From<RenderError> for Error {
+    fn from(error: RenderError) -> Self {
+        Self::Render(error)
+    }
+}
+
+/// A participant that failed, it is run again once the wait is over
+struct Backoff {
+    failures: u32,
+    retry_at: Instant,
+    message: String,
+}
+
+impl Backoff {
+    fn wait(failures: u32) -> Duration {
+        let doublings = failures.saturating_sub(1).min(16);
+
+        (FIRST_RETRY * 2_u32.pow(doublings)).min(LONGEST_RETRY)
+    }
+}
+
+/// Runs the participants after every batch of events, and otherwise only
+/// when one of them asks to, so an idle presentation uses next to no CPU
 pub struct EventLoop<'a> {
     sdl: &'a Sdl,
+    navigation: &'a RefCell<Navigation>,
     onloops: Vec<&'a mut dyn OnLoop>,
+    /// Failing participants, by the index of the participant
+    backoffs: Vec<Option<Backoff>>,
+    overview_keys: Vec<Keycode>,
+    pointer: Option<&'a RefCell<Pointer>>,
+    timer: Option<&'a RefCell<TalkTimer>>,
+    playback: Option<&'a RefCell<Playback>>,
+    volume_keys: bool,
+    key_bindings: Vec<(Keycode, Action)>,
+    control: Option<Receiver<Action>>,
 }
 
 pub trait OnLoop {
-    fn run(&mut self) -> Result<(), String>;
+    fn run(&mut self) -> Result<(), Error>;
+
+    /// What the participant is called in the log when it fails
+    fn name(&self) -> &'static str;
+
+    /// How soon the participant has to run again when no events come in,
+    /// none when it only reacts to events and to the other participants
+    fn wake_up_in(&self, _now: Instant) -> Option<Duration> {
+        None
+    }
+
+    /// Window the participant draws into, if it has one
+    fn window_id(&self) -> Option<u32> {
+        None
+    }
+
+    /// Handles an event sent to the participant's window, returns false when
+    /// the presentation should end
+    fn handle_window_event(&mut self, _event: &WindowEvent) -> bool {
+        true
+    }
+
+    /// Moves the participant's window after a display was connected or
+    /// disconnected
+    fn handle_displays_changed(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
 }
 
 impl<'a> EventLoop<'a> {
-    pub fn new(sdl: &'a Sdl, onloops: Vec<&'a mut dyn OnLoop>) -> Self {
-        Self { sdl, onloops }
+    pub fn new(
+        sdl: &'a Sdl,
+        navigation: &'a RefCell<Navigation>,
+        onloops: Vec<&'a mut dyn OnLoop>,
+    ) -> Self {
+        Self {
+            sdl,
+            navigation,
+            backoffs: onloops.iter().map(|_| None).collect(),
+            onloops,
+            overview_keys: vec![Keycode::O],
+            pointer: None,
+            timer: None,
+            playback: None,
+            volume_keys: false,
+            key_bindings: vec![],
+            control: None,
+        }
+    }
+
+    /// Pointer toggled with P and moved with the mouse
+    pub fn set_pointer(&mut self, pointer: &'a RefCell<Pointer>) {
+        self.pointer = Some(pointer);
+    }
+
+    /// Talk timer shown and hidden with T
+    pub fn set_timer(&mut self, timer: &'a RefCell<TalkTimer>) {
+        self.timer = Some(timer);
+    }
+
+    /// Videos and animated images paused and resumed with K
+    pub fn set_playback(&mut self, playback: &'a RefCell<Playback>) {
+        self.playback = Some(playback);
+    }
+
+    /// Keys opening the slide overview, they take precedence over quitting
+    /// with Escape
+    pub fn set_overview_keys(&mut self, overview_keys: Vec<Keycode>) {
+        self.overview_keys = overview_keys;
+    }
+
+    /// Whether the volume keys move between slides, for remotes that send
+    /// them for their buttons
+    pub fn set_volume_keys(&mut self, volume_keys: bool) {
+        self.volume_keys = volume_keys;
+    }
+
+    /// Keys bound to actions on top of the built-in ones, the first binding
+    /// of a key wins
+    pub fn set_key_bindings(&mut self, key_bindings: Vec<(Keycode, Action)>) {
+        self.key_bindings = key_bindings;
+    }
+
+    /// Actions sent by OSC and MIDI listeners
+    pub fn set_control(&mut self, control: Receiver<Action>) {
+        self.control = Some(control);
     }
 
-    pub fn run(&mut self) {
-        let mut event_pump = self.sdl.event_pump().unwrap();
+    pub fn run(&mut self) -> Result<(), RenderError> {
+        let mut event_pump = self.sdl.event_pump().map_err(RenderError::Sdl)?;
 
         'running: loop {
-            for event in event_pump.poll_iter() {
-                match event {
-                    Event::Quit { .. }
-                    | Event::KeyDown {
-                        keycode: Some(Keycode::Escape),
-                        ..
-                    } => break 'running,
-                    Event::KeyDown {
-                        keycode: Some(keycode),
-                        ..
-                    } => println!("Keydown: {}", keycode),
-                    _ => {}
+            let timeout = self.wait_timeout().as_millis() as u32;
+            let first = event_pump.wait_event_timeout(timeout);
+            for event in first.into_iter().chain(event_pump.poll_iter()) {
+                if !self.handle_event(&event) {
+                    break 'running;
                 }
             }
+            let actions: Vec<Action> = self.control.iter().flat_map(Receiver::try_iter).collect();
+            for action in actions {
+                self.perform(action);
+            }
+
+            self.run_participants();
+        }
+
+        Ok(())
+    }
+
+    /// Runs the participants that aren't waiting to be retried after a
+    /// failure. Failures are logged once, until they change or go away.
+    fn run_participants(&mut self) {
+        let now = Instant::now();
+
+        for (item, backoff) in self.onloops.iter_mut().zip(&mut self.backoffs) {
+            if matches!(backoff, Some(backoff) if backoff.retry_at > now) {
+                continue;
+            }
 
-            for item in &mut self.onloops {
-                if item.run().is_err() {
-                    println!("OnLoop failed!"); // todo more detailed message, actual logging
+            match item.run() {
+                Ok(()) => {
+                    if backoff.take().is_some() {
+                        log::info!("The {} works again", item.name());
+                    }
+                }
+                Err(error) => {
+                    let message = error.to_string();
+                    let failures = match backoff {
+                        Some(backoff) if backoff.message == message => backoff.failures + 1,
+                        _ => {
+                            log::error!("The {} failed: {}", item.name(), message);
+                            1
+                        }
+                    };
+                    *backoff = Some(Backoff {
+                        failures,
+                        retry_at: now + Backoff::wait(failures),
+                        message,
+                    });
                 }
             }
+        }
+    }
 
-            // todo implement the FPS limit correctly
-            ::std::thread::sleep(Duration::new(0, 1_000_000_000_u32 / 60));
+    /// How long to wait for events before a participant has to run again
+    fn wait_timeout(&self) -> Duration {
+        let now = Instant::now();
+
+        self.onloops
+            .iter()
+            .zip(&self.backoffs)
+            .filter_map(|(item, backoff)| match backoff {
+                Some(backoff) => Some(backoff.retry_at.saturating_duration_since(now)),
+                None => item.wake_up_in(now),
+            })
+            .min()
+            .map_or(MAX_WAIT, |wait| wait.min(MAX_WAIT))
+            .min(if self.control.is_some() {
+                CONTROL_WAIT
+            } else {
+                MAX_WAIT
+            })
+    }
+
+    /// Returns false when the presentation should end
+    fn handle_event(&mut self, event: &Event) -> bool {
+        match *event {
+            Event::KeyDown {
+                keycode: Some(Keycode::Escape),
+                ..
+            } if self.navigation.borrow_mut().cancel_typed_number() => {}
+            Event::KeyDown {
+                keycode: Some(keycode),
+                ..
+            } if self.navigation.borrow().overview_selection().is_some() => {
+                self.handle_overview_key(keycode);
+            }
+            Event::KeyDown {
+                keycode: Some(keycode),
+                ..
+            } if self.overview_keys.contains(&keycode) => {
+                self.navigation.borrow_mut().open_overview();
+            }
+            Event::KeyDown {
+                keycode: Some(keycode),
+                ..
+            } if self.navigation.borrow().section_selection().is_some() => {
+                self.handle_section_menu_key(keycode);
+            }
+            Event::KeyDown {
+                keycode: Some(Keycode::S),
+                ..
+            } => self.navigation.borrow_mut().open_section_menu(),
+            Event::Quit { .. }
+            | Event::KeyDown {
+                keycode: Some(Keycode::Escape),
+                ..
+            } => return false,
+            Event::Window {
+                window_id,
+                win_event,
+                ..
+            } if !self.dispatch_window_event(window_id, &win_event) => return false,
+            Event::Unknown {
+                type_: display::DISPLAY_EVENT,
+                ..
+            } => self.handle_displays_changed(),
+            Event::MouseMotion { .. }
+            | Event::MouseButtonDown { .. }
+            | Event::MouseButtonUp { .. } => self.handle_mouse_event(event),
+            Event::KeyDown {
+                keycode: Some(Keycode::Return),
+                ..
+            }
+            | Event::KeyDown {
+                keycode: Some(Keycode::KpEnter),
+                ..
+            } => self.navigation.borrow_mut().confirm_typed_number(),
+            Event::KeyDown {
+                keycode: Some(keycode),
+                ..
+            } => match (self.bound_action(keycode), digit(keycode)) {
+                (Some(action), _) => self.perform(action),
+                (None, Some(digit)) => self.navigation.borrow_mut().type_digit(digit),
+                (None, None) => log::debug!("Unhandled key {}", keycode),
+            },
+            _ => {}
+        }
+
+        true
+    }
+
+    /// Action of the key, configured bindings go over the built-in ones
+    fn bound_action(&self, keycode: Keycode) -> Option<Action> {
+        self.key_bindings
+            .iter()
+            .find(|(bound, _)| *bound == keycode)
+            .map(|&(_, action)| action)
+            .or_else(|| key_action(keycode, self.volume_keys))
+    }
+
+    fn perform(&mut self, action: Action) {
+        match action {
+            Action::Next => self.navigation.borrow_mut().next(),
+            Action::Previous => self.navigation.borrow_mut().previous(),
+            Action::First => self.navigation.borrow_mut().first(),
+            Action::Last => self.navigation.borrow_mut().last(),
+            Action::TogglePointer => self.toggle_pointer(),
+            Action::ToggleTimer => self.toggle_timer(),
+            Action::TogglePlayback => self.toggle_playback(),
+        }
+    }
+
+    /// The system cursor is hidden while the pointer is shown
+    fn toggle_pointer(&mut self) {
+        if let Some(pointer) = self.pointer {
+            let enabled = pointer.borrow_mut().toggle();
+            self.sdl.mouse().show_cursor(!enabled);
+        }
+    }
+
+    fn toggle_timer(&self) {
+        if let Some(timer) = self.timer {
+            timer.borrow_mut().toggle();
+        }
+    }
+
+    fn toggle_playback(&self) {
+        if let Some(playback) = self.playback {
+            playback.borrow_mut().toggle(Instant::now());
+        }
+    }
+
+    /// Moving the mouse moves the pointer, holding the left button draws
+    fn handle_mouse_event(&self, event: &Event) {
+        let mut pointer = match self.pointer {
+            Some(pointer) => pointer.borrow_mut(),
+            None => return,
+        };
+
+        match *event {
+            Event::MouseMotion {
+                window_id, x, y, ..
+            } if pointer.accepts(window_id) => pointer.move_to(x, y),
+            Event::MouseButtonDown {
+                window_id,
+                mouse_btn: MouseButton::Left,
+                x,
+                y,
+                ..
+            } if pointer.accepts(window_id) => pointer.start_stroke(x, y),
+            Event::MouseButtonUp {
+                window_id,
+                mouse_btn: MouseButton::Left,
+                ..
+            } if pointer.accepts(window_id) => pointer.end_stroke(),
+            _ => {}
+        }
+    }
+
+    /// Arrow keys move the selection, Enter goes to the selected slide and
+    /// Escape or the key that opened the overview goes back without moving
+    fn handle_overview_key(&mut self, keycode: Keycode) {
+        let mut navigation = self.navigation.borrow_mut();
+        let columns = navigation.overview_columns() as isize;
+
+        match keycode {
+            Keycode::Left => navigation.move_selection(-1),
+            Keycode::Right => navigation.move_selection(1),
+            Keycode::Up => navigation.move_selection(-columns),
+            Keycode::Down => navigation.move_selection(columns),
+            Keycode::Return | Keycode::KpEnter => navigation.confirm_selection(),
+            keycode if keycode == Keycode::Escape || self.overview_keys.contains(&keycode) => {
+                navigation.close_overview();
+            }
+            _ => {}
+        }
+    }
+
+    /// Up and Down move the selection, Enter jumps to the selected section
+    /// and Escape or S closes the menu without moving
+    fn handle_section_menu_key(&mut self, keycode: Keycode) {
+        let mut navigation = self.navigation.borrow_mut();
+
+        match keycode {
+            Keycode::Up => navigation.move_section_selection(-1),
+            Keycode::Down => navigation.move_section_selection(1),
+            Keycode::Return | Keycode::KpEnter => navigation.confirm_section_selection(),
+            Keycode::Escape | Keycode::S => {
+                navigation.close_section_menu();
+            }
+            _ => {}
+        }
+    }
+
+    /// With more than one window open closing a window doesn't quit, so the
+    /// window's owner decides what closing it means
+    fn dispatch_window_event(&mut self, window_id: u32, event: &WindowEvent) -> bool {
+        self.onloops
+            .iter_mut()
+            .filter(|item| item.window_id() == Some(window_id))
+            .all(|item| item.handle_window_event(event))
+    }
+
+    fn handle_displays_changed(&mut self) {
+        for item in &mut self.onloops {
+            if let Err(error) = item.handle_displays_changed() {
+                log::warn!(
+                    "The {} failed to follow a display change: {}",
+                    item.name(),
+                    error
+                );
+            }
         }
     }
 }
+
+/// The digit typed with the key, on the number row or the keypad
+fn digit(keycode: Keycode) -> Option<usize> {
+    [
+        (Keycode::Num0, Keycode::Kp0),
+        (Keycode::Num1, Keycode::Kp1),
+        (Keycode::Num2, Keycode::Kp2),
+        (Keycode::Num3, Keycode::Kp3),
+        (Keycode::Num4, Keycode::Kp4),
+        (Keycode::Num5, Keycode::Kp5),
+        (Keycode::Num6, Keycode::Kp6),
+        (Keycode::Num7, Keycode::Kp7),
+        (Keycode::Num8, Keycode::Kp8),
+        (Keycode::Num9, Keycode::Kp9),
+    ]
+    .iter()
+    .position(|&(number_row, keypad)| number_row == keycode || keypad == keycode)
+}
+
+/// The action bound to the key. Presenter remotes send Page Up and Page
+/// Down for their arrows and F5 to start the slideshow over, media keys
+/// work as well and the volume keys only when asked for.
+pub fn key_action(keycode: Keycode, volume_keys: bool) -> Option<Action> {
+    match keycode {
+        Keycode::Right | Keycode::PageDown | Keycode::Space | Keycode::AudioNext => {
+            Some(Action::Next)
+        }
+        Keycode::Left | Keycode::PageUp | Keycode::AudioPrev => Some(Action::Previous),
+        Keycode::VolumeUp if volume_keys => Some(Action::Next),
+        Keycode::VolumeDown if volume_keys => Some(Action::Previous),
+        Keycode::Home | Keycode::F5 => Some(Action::First),
+        Keycode::End => Some(Action::Last),
+        Keycode::P => Some(Action::TogglePointer),
+        Keycode::T => Some(Action::ToggleTimer),
+        Keycode::K | Keycode::AudioPlay => Some(Action::TogglePlayback),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn waits_longer_after_every_failure() {
+        assert_eq!(Duration::from_millis(100), Backoff::wait(1));
+        assert_eq!(Duration::from_millis(200), Backoff::wait(2));
+        assert_eq!(Duration::from_millis(800), Backoff::wait(4));
+        assert_eq!(Duration::from_secs(5), Backoff::wait(10));
+        assert_eq!(Duration::from_secs(5), Backoff::wait(u32::MAX));
+    }
+
+    #[test]
+    pub fn remote_keys_navigate() {
+        assert_eq!(Some(Action::Next), key_action(Keycode::PageDown, false));
+        assert_eq!(Some(Action::Previous), key_action(Keycode::PageUp, false));
+        assert_eq!(Some(Action::First), key_action(Keycode::F5, false));
+        assert_eq!(Some(Action::Next), key_action(Keycode::AudioNext, false));
+        assert_eq!(
+            Some(Action::TogglePlayback),
+            key_action(Keycode::AudioPlay, false)
+        );
+    }
+
+    #[test]
+    pub fn volume_keys_navigate_only_when_enabled() {
+        assert_eq!(None, key_action(Keycode::VolumeUp, false));
+        assert_eq!(Some(Action::Next), key_action(Keycode::VolumeUp, true));
+        assert_eq!(
+            Some(Action::Previous),
+            key_action(Keycode::VolumeDown, true)
+        );
+    }
+}
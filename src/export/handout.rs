@@ -0,0 +1,275 @@
+use crate::export::pdf::{latin_string, PdfWriter};
+use crate::presentation::{Presentation, Slide, SlideElement};
+use crate::rendering::offscreen::Offscreen;
+use sdl2::image::SaveSurface;
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::Path;
+
+/// A4 in points
+const PAGE_WIDTH: u32 = 595;
+const PAGE_HEIGHT: u32 = 842;
+const MARGIN: u32 = 50;
+const TITLE_SIZE: u32 = 18;
+const NOTES_SIZE: u32 = 11;
+const LINE_HEIGHT: u32 = 14;
+/// Characters of notes that fit the width of the page, Helvetica at the
+/// notes size is about half as wide as it is high on average
+const LINE_LENGTH: usize = ((PAGE_WIDTH - 2 * MARGIN) * 2 / NOTES_SIZE) as usize;
+
+/// Writes a document to hand out or rehearse with, each slide's title and
+/// picture followed by its notes. Paths ending in `.md` get Markdown with
+/// the pictures next to it, anything else a PDF.
+pub fn export(
+    presentation: &Presentation,
+    offscreen: &Offscreen,
+    path: &Path,
+) -> Result<(), String> {
+    match path.extension().and_then(std::ffi::OsStr::to_str) {
+        Some("md" | "markdown") => export_markdown(presentation, offscreen, path),
+        _ => export_pdf(presentation, offscreen, path),
+    }
+}
+
+fn export_pdf(
+    presentation: &Presentation,
+    offscreen: &Offscreen,
+    path: &Path,
+) -> Result<(), String> {
+    let file = File::create(path).map_err(|e| format!("{:?}", e))?;
+    let mut writer = PdfWriter::new(BufWriter::new(file)).map_err(|e| format!("{:?}", e))?;
+
+    for (index, slide) in presentation.slides().iter().enumerate() {
+        let image = offscreen.render(slide).map_err(|e| e.to_string())?;
+        let title = format!("{}. {}", index + 1, slide_title(slide));
+        let pages = pages(&title, (image.width(), image.height()), slide.notes());
+
+        for (page, contents) in pages.iter().enumerate() {
+            let image = if page == 0 { Some(&image) } else { None };
+            writer
+                .add_composed_page(PAGE_WIDTH, PAGE_HEIGHT, image, contents)
+                .map_err(|e| format!("{:?}", e))?;
+        }
+    }
+
+    writer.finish().map_err(|e| format!("{:?}", e))?;
+
+    Ok(())
+}
+
+fn export_markdown(
+    presentation: &Presentation,
+    offscreen: &Offscreen,
+    path: &Path,
+) -> Result<(), String> {
+    let stem = path
+        .file_stem()
+        .map_or_else(|| "handout".into(), |stem| stem.to_string_lossy());
+    let directory_name = format!("{}-slides", stem);
+    let directory = path.with_file_name(&directory_name);
+    fs::create_dir_all(&directory).map_err(|e| format!("{:?}", e))?;
+
+    for (index, slide) in presentation.slides().iter().enumerate() {
+        offscreen
+            .render_surface(slide)
+            .map_err(|e| e.to_string())?
+            .save(directory.join(thumbnail_name(index)))?;
+    }
+
+    fs::write(path, markdown(presentation, &directory_name)).map_err(|e| format!("{:?}", e))
+}
+
+fn thumbnail_name(index: usize) -> String {
+    format!("slide-{:03}.png", index + 1)
+}
+
+/// The handout in Markdown, with the pictures of the slides in the directory
+fn markdown(presentation: &Presentation, thumbnail_directory: &str) -> String {
+    let mut parts = vec![format!("# {}\n", presentation.metadata().title())];
+
+    for (index, slide) in presentation.slides().iter().enumerate() {
+        parts.push(format!(
+            "\n## {}. {}\n\n![Slide {}]({}/{})\n",
+            index + 1,
+            slide_title(slide),
+            index + 1,
+            thumbnail_directory,
+            thumbnail_name(index)
+        ));
+        parts.extend(slide.notes().iter().map(|note| format!("\n{}\n", note)));
+    }
+
+    parts.concat()
+}
+
+/// The slide's first heading, or its name when it has none
+fn slide_title(slide: &Slide) -> String {
+    slide
+        .elements()
+        .iter()
+        .flat_map(SlideElement::walk)
+        .find_map(|element| match element {
+            SlideElement::Title(text) => Some(text.content().clone()),
+            _ => None,
+        })
+        .unwrap_or_else(|| slide.name().clone())
+}
+
+/// Content streams of a slide's pages: the title, the picture scaled to the
+/// page width and as many lines of notes as fit, then pages with the notes
+/// that didn't
+fn pages(title: &str, (image_width, image_height): (u32, u32), notes: &[String]) -> Vec<String> {
+    let width = PAGE_WIDTH - 2 * MARGIN;
+    let height = width * image_height / image_width.max(1);
+    let title_baseline = PAGE_HEIGHT - MARGIN - TITLE_SIZE;
+    let image_bottom = title_baseline.saturating_sub(TITLE_SIZE + height);
+
+    let mut contents = format!(
+        "BT /Text {} Tf {} {} Td {} Tj ET\nq {} 0 0 {} {} {} cm /Slide Do Q\n\
+         q 0.5 w {} {} {} {} re S Q\n",
+        TITLE_SIZE,
+        MARGIN,
+        title_baseline,
+        latin_string(title),
+        width,
+        height,
+        MARGIN,
+        image_bottom,
+        MARGIN,
+        image_bottom,
+        width,
+        height
+    );
+    let mut pages = vec![];
+    let mut lines = notes
+        .iter()
+        .flat_map(|note| {
+            note.lines()
+                .flat_map(|line| wrap(line, LINE_LENGTH))
+                .chain(Some(String::new()))
+        })
+        .collect::<Vec<String>>();
+    // the blank line after the last note
+    lines.pop();
+
+    let mut top = image_bottom.saturating_sub(2 * LINE_HEIGHT);
+    let mut remaining = &lines[..];
+    loop {
+        let fitting = ((top.saturating_sub(MARGIN)) / LINE_HEIGHT + 1) as usize;
+        let (page_lines, rest) = remaining.split_at(fitting.min(remaining.len()));
+        if !page_lines.is_empty() {
+            contents.push_str(&notes_text(top, page_lines));
+        }
+        pages.push(contents);
+
+        if rest.is_empty() {
+            return pages;
+        }
+        remaining = rest;
+        contents = String::new();
+        top = PAGE_HEIGHT - MARGIN - NOTES_SIZE;
+    }
+}
+
+/// Writes the lines one below another, the first one's baseline at `top`
+fn notes_text(top: u32, lines: &[String]) -> String {
+    let lines = lines
+        .iter()
+        .map(|line| format!("{} Tj", latin_string(line)))
+        .collect::<Vec<String>>()
+        .join(" T* ");
+
+    format!(
+        "BT /Text {} Tf {} TL {} {} Td {} ET\n",
+        NOTES_SIZE, LINE_HEIGHT, MARGIN, top, lines
+    )
+}
+
+/// Breaks the text into lines of at most `length` characters between words,
+/// longer words get a line of their own
+fn wrap(text: &str, length: usize) -> Vec<String> {
+    let mut lines = vec![];
+    let mut line = String::new();
+
+    for word in text.split_whitespace() {
+        if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > length {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    lines.push(line);
+
+    lines
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::presentation::{Metadata, Resolution, Style, Text};
+
+    #[test]
+    pub fn wraps_text_between_words() {
+        assert_eq!(
+            vec!["one two", "three", "fourteen", ""],
+            [wrap("one two three", 8), wrap("fourteen", 4), wrap("", 4)].concat()
+        );
+    }
+
+    #[test]
+    pub fn puts_title_picture_and_notes_on_one_page() {
+        let pages = pages(
+            "1. Intro",
+            (1600, 900),
+            &["Say hi\nthen go".into(), "(slowly)".into()],
+        );
+
+        assert_eq!(
+            vec!["BT /Text 18 Tf 50 774 Td (1. Intro) Tj ET\n\
+                  q 495 0 0 278 50 478 cm /Slide Do Q\n\
+                  q 0.5 w 50 478 495 278 re S Q\n\
+                  BT /Text 11 Tf 14 TL 50 450 Td (Say hi) Tj T* (then go) Tj T* () Tj T* (\\(slowly\\)) Tj ET\n"],
+            pages
+        );
+    }
+
+    #[test]
+    pub fn continues_long_notes_on_more_pages() {
+        let notes = vec!["word".to_string(); 40];
+
+        let pages = pages("1. Intro", (1600, 900), &notes);
+
+        assert_eq!(2, pages.len());
+        assert!(pages[1].starts_with("BT /Text 11 Tf 14 TL 50 781 Td "));
+        // the title, then the notes with blank lines between them
+        assert_eq!(
+            notes.len() * 2,
+            pages
+                .iter()
+                .map(|page| page.matches(") Tj").count())
+                .sum::<usize>()
+        );
+    }
+
+    #[test]
+    pub fn writes_markdown_with_pictures_and_notes() {
+        let mut titled = Slide::new(
+            "intro".into(),
+            vec![SlideElement::Title(Text::new("Hello".into(), None))],
+        );
+        titled.add_note("Smile".into());
+        let presentation = Presentation::new(
+            Metadata::new("Talk".into(), Resolution::default()),
+            vec![titled, Slide::new("end".into(), vec![])],
+            Style::new(vec![]).unwrap(),
+        );
+
+        assert_eq!(
+            "# Talk\n\n## 1. Hello\n\n![Slide 1](talk-slides/slide-001.png)\n\nSmile\n\n\
+             ## 2. end\n\n![Slide 2](talk-slides/slide-002.png)\n",
+            markdown(&presentation, "talk-slides")
+        );
+    }
+}
@@ -0,0 +1,884 @@
+use crate::font_resolver;
+use crate::presentation::{
+    Align, Anchor, Color, Container, Fill, Font, GradientDirection, ImageFit, Length, List,
+    Presentation, Slide, SlideElement, Span, Style, Text, TextStyle,
+};
+use crate::rendering::layout::{self, Heading, DEFAULT_FONT_SIZE, HEADING_MARGIN_PERCENT};
+use std::fs;
+use std::path::Path;
+
+const BASE64_ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+const STYLESHEET: &str = "
+html, body { margin: 0; height: 100%; overflow: hidden; background: #000; }
+#deck { position: absolute; left: 50%; top: 50%; transform-origin: center; }
+.slide { position: absolute; inset: 0; display: none; flex-direction: column; justify-content: center; align-items: center; overflow: hidden; }
+.slide.current { display: flex; }
+.slide p, .slide h1, .slide h2 { margin: 0; font-size: inherit; white-space: pre; }
+.slide header { display: flex; flex-direction: column; align-items: center; }
+.slide .body { flex: 1; display: flex; flex-direction: column; justify-content: center; align-items: center; }
+.slide.headed { justify-content: flex-start; }
+.slide img, .slide video { display: block; }
+.list .item { display: flex; white-space: pre; }
+.list .bullet { flex: none; }
+.hidden { visibility: hidden; }
+.notes { display: none; }
+#sections { display: none; position: fixed; left: 50%; top: 50%; transform: translate(-50%, -50%); padding: 24px; background: rgba(0, 0, 0, 0.8); font: 24px sans-serif; }
+#sections.open { display: block; }
+#sections a { display: block; color: #fff; text-decoration: none; }
+";
+
+/// Same navigation as the native window: build steps are revealed one by
+/// one before moving to the next slide
+const SCRIPT: &str = "
+(function () {
+  var deck = document.getElementById('deck');
+  var slides = deck.querySelectorAll('.slide');
+  var sections = document.getElementById('sections');
+  var slide = 0;
+  var step = 0;
+
+  function stepCount(index) {
+    return Number(slides[index].dataset.steps);
+  }
+
+  function show() {
+    slides.forEach(function (element, index) {
+      element.classList.toggle('current', index === slide);
+    });
+    slides[slide].querySelectorAll('[data-step]').forEach(function (element) {
+      element.classList.toggle('hidden', Number(element.dataset.step) > step);
+    });
+  }
+
+  function goTo(index) {
+    slide = Math.max(0, Math.min(index, slides.length - 1));
+    step = 0;
+    show();
+    slides.forEach(function (element, index) {
+      element.querySelectorAll('video').forEach(function (video) {
+        video.currentTime = 0;
+        if (index === slide) {
+          video.play();
+        } else {
+          video.pause();
+        }
+      });
+    });
+  }
+
+  function togglePlayback() {
+    slides[slide].querySelectorAll('video').forEach(function (video) {
+      if (video.paused) {
+        video.play();
+      } else {
+        video.pause();
+      }
+    });
+  }
+
+  function next() {
+    if (step < stepCount(slide)) {
+      step++;
+      show();
+    } else if (slide + 1 < slides.length) {
+      goTo(slide + 1);
+    }
+  }
+
+  function previous() {
+    if (step > 0) {
+      step--;
+      show();
+    } else if (slide > 0) {
+      goTo(slide - 1);
+      step = stepCount(slide);
+      show();
+    }
+  }
+
+  function fit() {
+    var scale = Math.min(window.innerWidth / deck.offsetWidth, window.innerHeight / deck.offsetHeight);
+    deck.style.transform = 'translate(-50%, -50%) scale(' + scale + ')';
+  }
+
+  document.addEventListener('keydown', function (event) {
+    switch (event.key) {
+      case 'ArrowRight': case 'PageDown': case ' ': next(); break;
+      case 'ArrowLeft': case 'PageUp': previous(); break;
+      case 'Home': goTo(0); break;
+      case 'End': goTo(slides.length - 1); break;
+      case 'k': togglePlayback(); break;
+      case 's': if (sections) { sections.classList.toggle('open'); } break;
+      default: return;
+    }
+    event.preventDefault();
+  });
+  document.addEventListener('click', next);
+  if (sections) {
+    sections.querySelectorAll('a').forEach(function (link) {
+      link.addEventListener('click', function (event) {
+        event.preventDefault();
+        event.stopPropagation();
+        sections.classList.remove('open');
+        goTo(Number(link.dataset.slide));
+      });
+    });
+  }
+  window.addEventListener('resize', fit);
+
+  fit();
+  if (slides.length > 0) {
+    goTo(0);
+  }
+})();
+";
+
+/// Writes the presentation as a single HTML file, with its images and fonts
+/// embedded, which can be opened in any web browser
+pub fn export(presentation: &Presentation, output: &Path) -> Result<(), String> {
+    fs::write(output, render(presentation)?).map_err(|e| format!("{:?}", e))
+}
+
+/// Builds the whole document, reading every image and font it embeds
+pub fn render(presentation: &Presentation) -> Result<String, String> {
+    let metadata = presentation.metadata();
+    let resolution = metadata.resolution();
+    let slides = presentation
+        .slides()
+        .iter()
+        .map(|slide| render_slide(presentation, slide))
+        .collect::<Result<String, String>>()?;
+
+    Ok(format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}{}</style>\n</head>\n<body>\n<div id=\"deck\" style=\"width: {}px; height: {}px;{}\">\n{}</div>\n{}<script>{}</script>\n</body>\n</html>\n",
+        escape(metadata.title()),
+        STYLESHEET,
+        font_faces(presentation)?,
+        resolution.width(),
+        resolution.height(),
+        text_rendering(presentation.style()),
+        slides,
+        sections_nav(presentation),
+        SCRIPT
+    ))
+}
+
+/// Table of contents opened with S, linking to the first slide of every
+/// section
+fn sections_nav(presentation: &Presentation) -> String {
+    if presentation.sections().is_empty() {
+        return String::new();
+    }
+
+    let links = presentation
+        .sections()
+        .iter()
+        .map(|section| {
+            format!(
+                "<a href=\"#\" data-slide=\"{}\">{}</a>\n",
+                section.first_slide(),
+                escape(section.title())
+            )
+        })
+        .collect::<Vec<String>>()
+        .concat();
+
+    format!("<nav id=\"sections\">\n{}</nav>\n", links)
+}
+
+/// Turns off kerning and font smoothing when the style does, hinting is
+/// left to the browser
+fn text_rendering(style: &Style) -> String {
+    let mut css = String::new();
+    if !style.kerning() {
+        css.push_str(" font-kerning: none;");
+    }
+    if !style.antialias() {
+        css.push_str(" -webkit-font-smoothing: none; font-smooth: never;");
+    }
+
+    css
+}
+
+/// Declares every font of the presentation, ordered by their descriptors so
+/// the output is the same on every export
+fn font_faces(presentation: &Presentation) -> Result<String, String> {
+    let mut fonts = presentation.style().fonts();
+    fonts.sort_by_key(|font| (font.name(), font.weight(), font.italic()));
+
+    let font_dirs = presentation.metadata().font_dirs();
+    fonts
+        .into_iter()
+        .map(|font| font_face(font, font_dirs))
+        .collect()
+}
+
+fn font_face(font: &Font, font_dirs: &[String]) -> Result<String, String> {
+    let path = font_resolver::resolve(font, font_dirs)?;
+
+    Ok(format!(
+        "@font-face {{ font-family: \"{}\"; font-weight: {}; font-style: {}; src: url({}); }}\n",
+        escape(font.name()),
+        font.weight(),
+        if font.italic() { "italic" } else { "normal" },
+        data_uri(&path)?
+    ))
+}
+
+fn render_slide(presentation: &Presentation, slide: &Slide) -> Result<String, String> {
+    let background = slide
+        .background()
+        .or_else(|| presentation.style().background())
+        .map_or_else(|| Ok(css_color(Color::new(0, 0, 0, 0xff))), css_fill)?;
+    let (positioned, flowing): (Vec<_>, Vec<_>) = presentation
+        .arranged_elements(slide)
+        .into_iter()
+        .partition(|(element, _)| element.placement().is_positioned());
+    let (headings, body): (Vec<_>, Vec<_>) = flowing
+        .into_iter()
+        .partition(|(element, _)| Heading::of(element).is_some());
+    let render_elements = |elements: Vec<(&SlideElement, u32)>| {
+        elements
+            .into_iter()
+            .map(|(element, step)| render_element(presentation, element, step))
+            .collect::<Result<String, String>>()
+    };
+    let notes = slide
+        .notes()
+        .iter()
+        .map(|note| format!("<aside class=\"notes\">{}</aside>\n", escape(note)))
+        .collect::<Vec<String>>()
+        .concat();
+
+    // Like in the slide renderer, headings go to the top when there is
+    // something else on the slide and are centered with it otherwise
+    let (class, elements) = if headings.is_empty() || body.is_empty() {
+        ("slide", render_elements([headings, body].concat())?)
+    } else {
+        (
+            "slide headed",
+            format!(
+                "<header style=\"margin-top: {}px;\">\n{}</header>\n<div class=\"body\">\n{}</div>\n",
+                presentation.metadata().resolution().height() * HEADING_MARGIN_PERCENT / 100,
+                render_elements(headings)?,
+                render_elements(body)?
+            ),
+        )
+    };
+
+    Ok(format!(
+        "<section class=\"{}\" data-steps=\"{}\" style=\"background: {};\">\n{}{}{}</section>\n",
+        class,
+        slide.step_count(),
+        background,
+        elements,
+        render_elements(positioned)?,
+        notes
+    ))
+}
+
+fn render_element(
+    presentation: &Presentation,
+    element: &SlideElement,
+    step: u32,
+) -> Result<String, String> {
+    let placement = placement_css(presentation, element);
+
+    match element {
+        SlideElement::Text(text) => Ok(format!(
+            "<p data-step=\"{}\" style=\"{}{}\">{}</p>\n",
+            step,
+            text_css(presentation, text.style().map(String::as_str), text.size())?,
+            placement,
+            spans_html(text.spans())
+        )),
+        SlideElement::Title(text) => Ok(format!(
+            "<h1 data-step=\"{}\" style=\"{}{}\">{}</h1>\n",
+            step,
+            heading_css(presentation, text, &layout::TITLE)?,
+            placement,
+            spans_html(text.spans())
+        )),
+        SlideElement::Subtitle(text) => Ok(format!(
+            "<h2 data-step=\"{}\" style=\"{}{}\">{}</h2>\n",
+            step,
+            heading_css(presentation, text, &layout::SUBTITLE)?,
+            placement,
+            spans_html(text.spans())
+        )),
+        SlideElement::List(list) => Ok(render_list(presentation, list, step, &placement)),
+        SlideElement::Row(container) => {
+            render_container(presentation, container, "row", step, &placement)
+        }
+        SlideElement::Column(container) => {
+            render_container(presentation, container, "column", step, &placement)
+        }
+        SlideElement::Image(image) => Ok(format!(
+            "<img data-step=\"{}\" src=\"{}\" style=\"{}\">\n",
+            step,
+            data_uri(Path::new(image.path()))?,
+            placement.trim_start()
+        )),
+        SlideElement::Video(video) => Ok(format!(
+            "<video data-step=\"{}\" src=\"{}\" loop muted playsinline style=\"{}\"></video>\n",
+            step,
+            data_uri(Path::new(video.path()))?,
+            placement.trim_start()
+        )),
+    }
+}
+
+/// Lays the children out with a flexbox, like the slide renderer does
+fn render_container(
+    presentation: &Presentation,
+    container: &Container,
+    direction: &str,
+    step: u32,
+    placement: &str,
+) -> Result<String, String> {
+    let resolution = presentation.metadata().resolution();
+    let reference = if direction == "row" {
+        resolution.width()
+    } else {
+        resolution.height()
+    };
+    let align = match container.align() {
+        Align::Start => "flex-start",
+        Align::Center => "center",
+        Align::End => "flex-end",
+    };
+    let children = container
+        .elements()
+        .iter()
+        .map(|element| render_element(presentation, element, step))
+        .collect::<Result<String, String>>()?;
+    let background = match container.background() {
+        Some(fill) => format!(" background: {};", css_fill(fill)?),
+        None => String::new(),
+    };
+
+    Ok(format!(
+        "<div class=\"{}\" data-step=\"{}\" style=\"display: flex; flex-direction: {}; gap: {}px; align-items: {};{}{}\">\n{}</div>\n",
+        direction,
+        step,
+        direction,
+        container
+            .gap()
+            .resolve(reference, default_font_size(presentation)),
+        align,
+        background,
+        placement,
+        children
+    ))
+}
+
+/// Places the element the same way the slide renderer does: sized or
+/// growing boxes center their content unless it's an image or a video,
+/// which is scaled to them, or a row or column, which lays its children out
+/// itself.
+/// Positioned elements are taken out of the flow.
+fn placement_css(presentation: &Presentation, element: &SlideElement) -> String {
+    let placement = element.placement();
+    let centers = matches!(
+        element,
+        SlideElement::Text(_)
+            | SlideElement::Title(_)
+            | SlideElement::Subtitle(_)
+            | SlideElement::List(_)
+    );
+    let font_size = default_font_size(presentation);
+    let mut css = vec![];
+
+    let (width, height) = placement.size();
+    if let Some(width) = width {
+        css.push(format!(" width: {};", css_length(width, font_size)));
+    }
+    if let Some(height) = height {
+        css.push(format!(" height: {};", css_length(height, font_size)));
+    }
+    if placement.grow() > 0 {
+        css.push(format!(" flex-grow: {};", placement.grow()));
+    }
+    if (width.is_some() || height.is_some() || placement.grow() > 0) && centers {
+        css.push(
+            " display: flex; flex-direction: column; justify-content: center; align-items: center;"
+                .into(),
+        );
+    }
+
+    if placement.is_positioned() {
+        let anchor = placement.anchor().unwrap_or(Anchor::TopLeft).halves();
+        let offset = (anchor.0 * 50, anchor.1 * 50);
+        let (x, y) = placement.position().map_or_else(
+            || (format!("{}%", offset.0), format!("{}%", offset.1)),
+            |(x, y)| (css_length(x, font_size), css_length(y, font_size)),
+        );
+        css.push(format!(
+            " position: absolute; left: {}; top: {}; transform: translate(-{}%, -{}%);",
+            x, y, offset.0, offset.1
+        ));
+    }
+
+    css.concat()
+}
+
+/// Ems are taken of the default font size, like in the slide renderer
+fn css_length(length: Length, font_size: u32) -> String {
+    match length {
+        Length::Pixels(pixels) => format!("{}px", pixels),
+        Length::Percent(percent) => format!("{}%", percent),
+        Length::Em(_) => format!("{}px", length.resolve(0, font_size)),
+    }
+}
+
+/// Lists are drawn in the default font, with the items lined up after the
+/// bullets
+fn render_list(presentation: &Presentation, list: &List, step: u32, placement: &str) -> String {
+    let size = default_font_size(presentation);
+    let indent = list
+        .indent()
+        .resolve(presentation.metadata().resolution().width(), size);
+    let font = presentation
+        .style()
+        .default_font()
+        .map(|font| font_css(font, &[]))
+        .unwrap_or_default();
+    let items = list
+        .items()
+        .iter()
+        .map(|item| {
+            format!(
+                "<div class=\"item\"><span class=\"bullet\" style=\"width: {}px;\">{}</span><span>{}</span></div>\n",
+                indent,
+                escape(list.bullet()),
+                escape(item)
+            )
+        })
+        .collect::<Vec<String>>()
+        .concat();
+
+    format!(
+        "<div class=\"list\" data-step=\"{}\" style=\"font-size: {}px; color: {};{}{}\">\n{}</div>\n",
+        step,
+        size,
+        css_color(text_color(presentation)),
+        font,
+        placement,
+        items
+    )
+}
+
+/// Resolves the font the same way the slide renderer does, the element's
+/// own size wins over its text style, which wins over the font defaults
+fn text_css(
+    presentation: &Presentation,
+    style_name: Option<&str>,
+    size: Option<u32>,
+) -> Result<String, String> {
+    let style = presentation.style();
+    let text_style = match style_name {
+        Some(name) => Some(
+            style
+                .text_style(name)
+                .ok_or_else(|| format!("Unknown text style `{}`", name))?,
+        ),
+        None => None,
+    };
+    let fallback = style.default_font();
+    let font = match (text_style, fallback) {
+        (Some(text_style), Some(fallback)) => style.font(
+            text_style.font().unwrap_or_else(|| fallback.name()),
+            text_style.weight().unwrap_or_else(|| fallback.weight()),
+            text_style.italic().unwrap_or_else(|| fallback.italic()),
+        ),
+        _ => fallback,
+    };
+    let size = size
+        .or_else(|| text_style.and_then(TextStyle::size))
+        .or_else(|| font.and_then(Font::size))
+        .unwrap_or_else(|| default_font_size(presentation));
+    let color = text_style
+        .and_then(TextStyle::color)
+        .unwrap_or_else(|| text_color(presentation));
+
+    let mut css = format!("font-size: {}px; color: {};", size, css_color(color));
+    if let Some(font) = font {
+        let fallbacks = match text_style {
+            Some(text_style) => text_style.fallback_fonts().as_slice(),
+            None => &[],
+        };
+        css.push_str(&font_css(font, fallbacks));
+    }
+
+    Ok(css)
+}
+
+/// Headings without a text style of their own look like the text style
+/// named after them, or else like a bigger default font
+fn heading_css(
+    presentation: &Presentation,
+    text: &Text,
+    heading: &Heading,
+) -> Result<String, String> {
+    if let Some(name) = text.style() {
+        return text_css(presentation, Some(name), text.size());
+    }
+    if presentation
+        .style()
+        .text_style(heading.text_style)
+        .is_some()
+    {
+        return text_css(presentation, Some(heading.text_style), text.size());
+    }
+
+    let size = text
+        .size()
+        .unwrap_or(default_font_size(presentation) * heading.size_percent / 100);
+    let css = text_css(presentation, None, Some(size))?;
+
+    Ok(if heading.bold {
+        format!("{} font-weight: bold;", css)
+    } else {
+        css
+    })
+}
+
+/// Plain spans are written as they are, the others get their own element
+pub fn spans_html(spans: &[Span]) -> String {
+    spans
+        .iter()
+        .map(|span| {
+            if span.is_plain() {
+                return escape(span.content());
+            }
+
+            let mut css = vec![];
+            if span.bold() {
+                css.push("font-weight: bold;".to_string());
+            }
+            if span.italic() {
+                css.push("font-style: italic;".to_string());
+            }
+            if let Some(color) = span.color() {
+                css.push(format!("color: {};", css_color(color)));
+            }
+
+            format!(
+                "<span style=\"{}\">{}</span>",
+                css.join(" "),
+                escape(span.content())
+            )
+        })
+        .collect::<Vec<String>>()
+        .concat()
+}
+
+/// Font properties of the font, with the fallback fonts after it in the
+/// font family list
+fn font_css(font: &Font, fallbacks: &[String]) -> String {
+    let families = std::iter::once(font.name())
+        .chain(fallbacks)
+        .map(|name| format!("\"{}\"", escape(name)))
+        .collect::<Vec<String>>()
+        .join(", ");
+
+    format!(
+        " font-family: {}; font-weight: {}; font-style: {};",
+        families,
+        font.weight(),
+        if font.italic() { "italic" } else { "normal" }
+    )
+}
+
+fn default_font_size(presentation: &Presentation) -> u32 {
+    presentation
+        .style()
+        .default_font()
+        .and_then(Font::size)
+        .unwrap_or_else(|| u32::from(DEFAULT_FONT_SIZE))
+}
+
+fn text_color(presentation: &Presentation) -> Color {
+    presentation
+        .style()
+        .text_color()
+        .unwrap_or_else(|| Color::new(0xff, 0xff, 0xff, 0xff))
+}
+
+pub fn css_color(color: Color) -> String {
+    format!(
+        "#{:02x}{:02x}{:02x}{:02x}",
+        color.red(),
+        color.green(),
+        color.blue(),
+        color.alpha()
+    )
+}
+
+/// A color, a gradient going the same way as the slide renderer draws it
+/// or an embedded image fitted the same way
+pub fn css_fill(fill: &Fill) -> Result<String, String> {
+    Ok(match fill {
+        Fill::Color(color) => css_color(*color),
+        Fill::Image { path, fit } => format!(
+            "url({}) {}",
+            data_uri(Path::new(path))?,
+            match fit {
+                ImageFit::Cover => "center / cover no-repeat",
+                ImageFit::Contain => "center / contain no-repeat",
+                ImageFit::Tile => "top left repeat",
+            }
+        ),
+        Fill::Gradient {
+            from,
+            to,
+            direction: GradientDirection::Radial,
+        } => format!(
+            "radial-gradient(circle farthest-corner, {}, {})",
+            css_color(*from),
+            css_color(*to)
+        ),
+        Fill::Gradient {
+            from,
+            to,
+            direction,
+        } => format!(
+            "linear-gradient(to {}, {}, {})",
+            match direction {
+                GradientDirection::Horizontal => "right",
+                GradientDirection::Diagonal => "bottom right",
+                _ => "bottom",
+            },
+            css_color(*from),
+            css_color(*to)
+        ),
+    })
+}
+
+/// Reads the file into a `data:` URI, its type guessed from the extension
+fn data_uri(path: &Path) -> Result<String, String> {
+    let bytes =
+        fs::read(path).map_err(|e| format!("Could not read `{}`: {}", path.display(), e))?;
+    let extension = path
+        .extension()
+        .and_then(std::ffi::OsStr::to_str)
+        .map(str::to_lowercase);
+    let media_type = match extension.as_deref() {
+        Some("png") => "image/png",
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("bmp") => "image/bmp",
+        Some("webp") => "image/webp",
+        Some("svg") => "image/svg+xml",
+        Some("mp4" | "m4v") => "video/mp4",
+        Some("webm") => "video/webm",
+        Some("ogv") => "video/ogg",
+        Some("ttf") => "font/ttf",
+        Some("otf") => "font/otf",
+        Some("woff") => "font/woff",
+        Some("woff2") => "font/woff2",
+        _ => "application/octet-stream",
+    };
+
+    Ok(format!("data:{};base64,{}", media_type, base64(&bytes)))
+}
+
+fn base64(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len() / 3 * 4 + 4);
+
+    for chunk in bytes.chunks(3) {
+        let group = chunk
+            .iter()
+            .enumerate()
+            .fold(0_u32, |group, (index, byte)| {
+                group | u32::from(*byte) << (16 - 8 * index)
+            });
+
+        for index in 0..4 {
+            if index <= chunk.len() {
+                let sextet = (group >> (18 - 6 * index)) & 0x3f;
+                encoded.push(char::from(BASE64_ALPHABET[sextet as usize]));
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+
+    encoded
+}
+
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for character in text.chars() {
+        match character {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(character),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::presentation::{Metadata, Placement, Resolution, Section, Slide, Style};
+
+    #[test]
+    pub fn encodes_base64() {
+        assert_eq!("", base64(b""));
+        assert_eq!("Zg==", base64(b"f"));
+        assert_eq!("Zm8=", base64(b"fo"));
+        assert_eq!("Zm9v", base64(b"foo"));
+        assert_eq!("Zm9vYmFy", base64(b"foobar"));
+    }
+
+    #[test]
+    pub fn escapes_markup() {
+        assert_eq!(
+            "&lt;b&gt; &amp; &quot;quotes&quot;",
+            escape("<b> & \"quotes\"")
+        );
+    }
+
+    #[test]
+    pub fn writes_gradients_as_css() {
+        let from = Color::new(0x20, 0x20, 0x40, 0xff);
+        let to = Color::new(0, 0, 0, 0xff);
+
+        assert_eq!(
+            "linear-gradient(to bottom, #202040ff, #000000ff)",
+            css_fill(&Fill::Gradient {
+                from,
+                to,
+                direction: GradientDirection::Vertical
+            })
+            .unwrap()
+        );
+        assert_eq!(
+            "radial-gradient(circle farthest-corner, #202040ff, #000000ff)",
+            css_fill(&Fill::Gradient {
+                from,
+                to,
+                direction: GradientDirection::Radial
+            })
+            .unwrap()
+        );
+    }
+
+    #[test]
+    pub fn turns_off_kerning_and_font_smoothing() {
+        let mut style = Style::empty();
+        assert_eq!("", text_rendering(&style));
+
+        style.set_kerning(false);
+        style.set_antialias(false);
+        assert_eq!(
+            " font-kerning: none; -webkit-font-smoothing: none; font-smooth: never;",
+            text_rendering(&style)
+        );
+    }
+
+    #[test]
+    pub fn renders_slides_with_build_steps() {
+        let mut slide = Slide::new("some slide".into(), vec![]);
+        slide.push_element(SlideElement::Text(Text::new("always <here>".into(), None)));
+        slide.push_element_in_step(SlideElement::Text(Text::new("later".into(), None)), 1);
+        let presentation = Presentation::new(
+            Metadata::new("some title".into(), Resolution::new(800, 600)),
+            vec![slide],
+            Style::empty(),
+        );
+
+        let html = render(&presentation).unwrap();
+
+        assert!(html.contains("<title>some title</title>"));
+        assert!(html.contains("<div id=\"deck\" style=\"width: 800px; height: 600px;\">"));
+        assert!(html.contains("<section class=\"slide\" data-steps=\"1\""));
+        assert!(html.contains(
+            "<p data-step=\"0\" style=\"font-size: 24px; color: #ffffffff;\">always &lt;here&gt;</p>"
+        ));
+        assert!(html.contains("<p data-step=\"1\""));
+    }
+
+    #[test]
+    pub fn lists_sections() {
+        let mut presentation = Presentation::new(
+            Metadata::new("some title".into(), Resolution::new(800, 600)),
+            vec![
+                Slide::new("a".into(), vec![]),
+                Slide::new("b".into(), vec![]),
+            ],
+            Style::empty(),
+        );
+        assert_eq!("", sections_nav(&presentation));
+
+        presentation.add_section(Section::new("Q&A".into(), 1));
+        assert_eq!(
+            "<nav id=\"sections\">\n<a href=\"#\" data-slide=\"1\">Q&amp;A</a>\n</nav>\n",
+            sections_nav(&presentation)
+        );
+    }
+
+    #[test]
+    pub fn renders_headings_above_the_rest() {
+        let slide = Slide::new(
+            "some slide".into(),
+            vec![
+                SlideElement::Title(Text::new("Hi".into(), None)),
+                SlideElement::Text(Text::new("body".into(), None)),
+            ],
+        );
+        let presentation = Presentation::new(
+            Metadata::new("some title".into(), Resolution::new(800, 600)),
+            vec![slide],
+            Style::empty(),
+        );
+
+        let html = render(&presentation).unwrap();
+
+        assert!(html.contains("<section class=\"slide headed\""));
+        assert!(html.contains(
+            "<header style=\"margin-top: 48px;\">\n<h1 data-step=\"0\" style=\"font-size: 48px; color: #ffffffff; font-weight: bold;\">Hi</h1>\n</header>"
+        ));
+        assert!(html.contains("<div class=\"body\">\n<p data-step=\"0\""));
+    }
+
+    #[test]
+    pub fn positions_elements_like_the_renderer() {
+        let mut text = Text::new("a".into(), None);
+        let mut placement = Placement::default();
+        placement.set_position(Length::Percent(10.0), Length::Pixels(20.0));
+        placement.set_anchor(Anchor::Center);
+        placement.set_size(Some(Length::Em(2.0)), None);
+        text.set_placement(placement);
+        let presentation = Presentation::new(
+            Metadata::new("some title".into(), Resolution::new(800, 600)),
+            vec![],
+            Style::empty(),
+        );
+
+        assert_eq!(
+            " width: 48px; display: flex; flex-direction: column; justify-content: center; align-items: center; position: absolute; left: 10%; top: 20px; transform: translate(-50%, -50%);",
+            placement_css(&presentation, &SlideElement::Text(text))
+        );
+    }
+
+    #[test]
+    pub fn renders_styled_spans() {
+        let mut bold = Span::new("bold".into());
+        bold.set_bold(true);
+        bold.set_color(Color::new(0xff, 0, 0, 0xff));
+
+        assert_eq!(
+            "a &amp; <span style=\"font-weight: bold; color: #ff0000ff;\">bold</span>",
+            spans_html(&[Span::new("a & ".into()), bold])
+        );
+    }
+}
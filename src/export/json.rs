@@ -0,0 +1,11 @@
+use crate::parsing::json::to_json;
+use crate::presentation::Presentation;
+use std::fs;
+use std::path::Path;
+
+/// Writes the presentation model as indented JSON, which `--format json`
+/// reads back
+pub fn export(presentation: &Presentation, output: &Path) -> Result<(), String> {
+    fs::write(output, format!("{}\n", to_json(presentation).pretty()))
+        .map_err(|e| format!("{:?}", e))
+}
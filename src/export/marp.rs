@@ -0,0 +1,226 @@
+use crate::export::html::{css_color, css_fill};
+use crate::presentation::{Fill, Presentation, SlideElement, Span, TransitionKind};
+use std::fs;
+use std::path::Path;
+
+pub fn export(presentation: &Presentation, output: &Path) -> Result<(), String> {
+    fs::write(output, render(presentation)?).map_err(|e| format!("{:?}", e))
+}
+
+/// Writes the presentation as Marp Markdown. Rows and columns are flattened,
+/// build steps only survive on lists, as Marp's fragmented lists, and span
+/// colors are dropped.
+pub fn render(presentation: &Presentation) -> Result<String, String> {
+    let metadata = presentation.metadata();
+    let resolution = metadata.resolution();
+    let size = match (resolution.width(), resolution.height()) {
+        (width, height) if width * 9 == height * 16 => "size: 16:9\n",
+        (width, height) if width * 3 == height * 4 => "size: 4:3\n",
+        _ => "",
+    };
+    let slides = (0..presentation.slides().len())
+        .map(|index| render_slide(presentation, index))
+        .collect::<Result<Vec<String>, String>>()?;
+
+    Ok(format!(
+        "---\nmarp: true\ntitle: {}\n{}---\n\n{}",
+        metadata.title(),
+        size,
+        slides.join("\n---\n\n")
+    ))
+}
+
+fn render_slide(presentation: &Presentation, index: usize) -> Result<String, String> {
+    let slide = &presentation.slides()[index];
+    let mut blocks = vec![];
+
+    if let Some(transition) = presentation.transition_to(index) {
+        let name = match transition.kind() {
+            TransitionKind::None => "none",
+            TransitionKind::Fade => "fade",
+            TransitionKind::Slide => "slide",
+        };
+        blocks.push(format!("<!-- _transition: {} -->", name));
+    }
+    match slide
+        .background()
+        .or_else(|| presentation.style().background())
+    {
+        Some(Fill::Color(color)) => {
+            blocks.push(format!(
+                "<!-- _backgroundColor: \"{}\" -->",
+                css_color(*color)
+            ));
+        }
+        Some(Fill::Image { path, .. }) => blocks.push(format!("![bg]({})", path)),
+        Some(gradient) => blocks.push(format!(
+            "<!-- _backgroundImage: \"{}\" -->",
+            css_fill(gradient)?
+        )),
+        None => {}
+    }
+
+    blocks.extend(
+        presentation
+            .arranged_elements(slide)
+            .into_iter()
+            .flat_map(|(element, step)| {
+                element
+                    .walk()
+                    .into_iter()
+                    .filter_map(move |element| render_element(element, step))
+            }),
+    );
+    blocks.extend(
+        slide
+            .notes()
+            .iter()
+            .map(|note| format!("<!--\n{}\n-->", note)),
+    );
+
+    Ok(blocks
+        .into_iter()
+        .map(|block| format!("{}\n", block))
+        .collect::<Vec<String>>()
+        .join("\n"))
+}
+
+/// The element as a Markdown block, none for rows and columns whose
+/// children are rendered on their own
+fn render_element(element: &SlideElement, step: u32) -> Option<String> {
+    match element {
+        SlideElement::Text(text) => Some(spans_markdown(text.spans())),
+        SlideElement::Title(text) => Some(format!("# {}", spans_markdown(text.spans()))),
+        SlideElement::Subtitle(text) => Some(format!("## {}", spans_markdown(text.spans()))),
+        SlideElement::List(list) => {
+            // Marp reveals lists written with asterisks item by item
+            let marker = if step > 0 { "*" } else { "-" };
+            Some(
+                list.items()
+                    .iter()
+                    .map(|item| format!("{} {}", marker, escape(item)))
+                    .collect::<Vec<String>>()
+                    .join("\n"),
+            )
+        }
+        SlideElement::Image(image) => Some(format!("![]({})", image.path())),
+        SlideElement::Video(video) => Some(format!("[Video]({})", video.path())),
+        SlideElement::Row(_) | SlideElement::Column(_) => None,
+    }
+}
+
+/// Bold and italic spans get emphasis, with the whitespace around them kept
+/// outside of it so Markdown still sees it
+fn spans_markdown(spans: &[Span]) -> String {
+    spans
+        .iter()
+        .map(|span| {
+            let content = escape(span.content());
+            let marker = match (span.bold(), span.italic()) {
+                (true, true) => "***",
+                (true, false) => "**",
+                (false, true) => "*",
+                (false, false) => return content,
+            };
+            let trimmed = content.trim();
+            if trimmed.is_empty() {
+                return content;
+            }
+            let start = content.len() - content.trim_start().len();
+            let end = start + trimmed.len();
+
+            format!(
+                "{}{}{}{}{}",
+                &content[..start],
+                marker,
+                trimmed,
+                marker,
+                &content[end..]
+            )
+        })
+        .collect::<Vec<String>>()
+        .concat()
+}
+
+/// Escapes the characters Markdown would take as formatting
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for character in text.chars() {
+        if matches!(
+            character,
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#'
+        ) {
+            escaped.push('\\');
+        }
+        escaped.push(character);
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::presentation::{
+        Container, Image, Length, List, Metadata, Resolution, Slide, Style, Text, Transition,
+    };
+    use std::time::Duration;
+
+    #[test]
+    pub fn writes_emphasis_outside_whitespace() {
+        let mut bold = Span::new(" bold ".into());
+        bold.set_bold(true);
+        let mut italic = Span::new("it".into());
+        italic.set_italic(true);
+
+        assert_eq!(
+            "a\\*b **bold** *it*",
+            spans_markdown(&[Span::new("a*b".into()), bold, italic])
+        );
+    }
+
+    #[test]
+    pub fn renders_slides_with_directives_and_notes() {
+        let mut slide = Slide::new(
+            "intro".into(),
+            vec![
+                SlideElement::Title(Text::new("Hi".into(), None)),
+                SlideElement::Column(Container::new(vec![SlideElement::Image(Image::new(
+                    "cat.png".into(),
+                ))])),
+            ],
+        );
+        slide.push_element_in_step(
+            SlideElement::List(List::new(
+                vec!["one".into(), "two".into()],
+                "•".into(),
+                Length::Pixels(0.0),
+            )),
+            1,
+        );
+        slide.add_note("say hi".into());
+        slide.set_background(Fill::Image {
+            path: "bg.png".into(),
+            fit: crate::presentation::ImageFit::Cover,
+        });
+        let mut metadata = Metadata::new("Talk".into(), Resolution::default());
+        metadata.set_transition(Transition::new(
+            TransitionKind::Fade,
+            Duration::from_millis(300),
+        ));
+        let presentation = Presentation::new(
+            metadata,
+            vec![slide, Slide::new("end".into(), vec![])],
+            Style::new(vec![]).unwrap(),
+        );
+
+        assert_eq!(
+            "---\nmarp: true\ntitle: Talk\nsize: 4:3\n---\n\n\
+             <!-- _transition: fade -->\n\n![bg](bg.png)\n\n# Hi\n\n![](cat.png)\n\n\
+             * one\n* two\n\n<!--\nsay hi\n-->\n\
+             \n---\n\n<!-- _transition: fade -->\n",
+            render(&presentation).unwrap()
+        );
+    }
+}
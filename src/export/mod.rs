@@ -0,0 +1,8 @@
+pub mod handout;
+pub mod html;
+pub mod json;
+pub mod marp;
+pub mod pdf;
+pub mod png;
+pub mod pptx;
+pub mod reveal;
@@ -0,0 +1,469 @@
+use crate::presentation::Presentation;
+use crate::rendering::offscreen::{Offscreen, RgbImage};
+use std::fs::File;
+use std::io;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+const CATALOG_ID: usize = 1;
+const PAGES_ID: usize = 2;
+
+/// Writes a PDF document consisting of full-page images, one per slide
+pub struct PdfWriter<W: Write> {
+    output: W,
+    offset: usize,
+    object_offsets: Vec<usize>,
+    page_ids: Vec<usize>,
+    bookmarks: Vec<(String, usize)>,
+    font_id: Option<usize>,
+}
+
+impl<W: Write> PdfWriter<W> {
+    pub fn new(output: W) -> io::Result<Self> {
+        let mut writer = Self {
+            output,
+            offset: 0,
+            // the catalog and the page tree are written last, but their ids are reserved upfront
+            object_offsets: vec![0, 0],
+            page_ids: vec![],
+            bookmarks: vec![],
+            font_id: None,
+        };
+
+        writer.write(b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n")?;
+
+        Ok(writer)
+    }
+
+    pub fn add_page(&mut self, image: &RgbImage) -> io::Result<()> {
+        let image_id = self.write_image(image)?;
+        let contents = format!(
+            "q {} 0 0 {} 0 0 cm /Slide Do Q",
+            image.width(),
+            image.height()
+        );
+
+        self.write_page(
+            image.width(),
+            image.height(),
+            &format!("/XObject << /Slide {} 0 R >>", image_id),
+            &contents,
+        )
+    }
+
+    /// Adds a page of the given size in points, drawn by the content stream.
+    /// It can show the image as `/Slide` and write text in Helvetica as
+    /// `/Text`, with strings from `latin_string`.
+    pub fn add_composed_page(
+        &mut self,
+        width: u32,
+        height: u32,
+        image: Option<&RgbImage>,
+        contents: &str,
+    ) -> io::Result<()> {
+        let mut resources = vec![];
+        if let Some(image) = image {
+            let image_id = self.write_image(image)?;
+            resources.push(format!("/XObject << /Slide {} 0 R >>", image_id));
+        }
+        let font_id = match self.font_id {
+            Some(font_id) => font_id,
+            None => self.write_font()?,
+        };
+        resources.push(format!("/Font << /Text {} 0 R >>", font_id));
+
+        self.write_page(width, height, &resources.join(" "), contents)
+    }
+
+    fn write_image(&mut self, image: &RgbImage) -> io::Result<usize> {
+        let image_id = self.reserve_object();
+        self.begin_object(image_id)?;
+        self.write(
+            format!(
+                "<< /Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /DeviceRGB /BitsPerComponent 8 /Length {} >>\nstream\n",
+                image.width(),
+                image.height(),
+                image.pixels().len()
+            )
+            .as_bytes(),
+        )?;
+        self.write(image.pixels())?;
+        self.write(b"\nendstream\n")?;
+        self.end_object()?;
+
+        Ok(image_id)
+    }
+
+    /// Writes the standard Helvetica font once, the readers supply it
+    fn write_font(&mut self) -> io::Result<usize> {
+        let font_id = self.reserve_object();
+        self.begin_object(font_id)?;
+        self.write(
+            b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>\n",
+        )?;
+        self.end_object()?;
+        self.font_id = Some(font_id);
+
+        Ok(font_id)
+    }
+
+    fn write_page(
+        &mut self,
+        width: u32,
+        height: u32,
+        resources: &str,
+        contents: &str,
+    ) -> io::Result<()> {
+        let contents_id = self.reserve_object();
+        self.begin_object(contents_id)?;
+        self.write(
+            format!(
+                "<< /Length {} >>\nstream\n{}\nendstream\n",
+                contents.len(),
+                contents
+            )
+            .as_bytes(),
+        )?;
+        self.end_object()?;
+
+        let page_id = self.reserve_object();
+        self.begin_object(page_id)?;
+        self.write(
+            format!(
+                "<< /Type /Page /Parent {} 0 R /MediaBox [0 0 {} {}] /Resources << {} >> /Contents {} 0 R >>\n",
+                PAGES_ID,
+                width,
+                height,
+                resources,
+                contents_id
+            )
+            .as_bytes(),
+        )?;
+        self.end_object()?;
+        self.page_ids.push(page_id);
+
+        Ok(())
+    }
+
+    /// Adds an entry to the document outline, pointing at a page by its index
+    pub fn add_bookmark(&mut self, title: &str, page: usize) {
+        self.bookmarks.push((title.to_string(), page));
+    }
+
+    pub fn finish(mut self) -> io::Result<W> {
+        let kids = self
+            .page_ids
+            .iter()
+            .map(|id| format!("{} 0 R", id))
+            .collect::<Vec<String>>()
+            .join(" ");
+
+        self.begin_object(PAGES_ID)?;
+        self.write(
+            format!(
+                "<< /Type /Pages /Kids [{}] /Count {} >>\n",
+                kids,
+                self.page_ids.len()
+            )
+            .as_bytes(),
+        )?;
+        self.end_object()?;
+
+        let outlines = match self.write_outlines()? {
+            Some(outlines_id) => format!(" /Outlines {} 0 R /PageMode /UseOutlines", outlines_id),
+            None => String::new(),
+        };
+        self.begin_object(CATALOG_ID)?;
+        self.write(
+            format!("<< /Type /Catalog /Pages {} 0 R{} >>\n", PAGES_ID, outlines).as_bytes(),
+        )?;
+        self.end_object()?;
+
+        let xref_offset = self.offset;
+        self.write(
+            format!(
+                "xref\n0 {}\n0000000000 65535 f \n",
+                self.object_offsets.len() + 1
+            )
+            .as_bytes(),
+        )?;
+        for object_offset in self.object_offsets.clone() {
+            self.write(format!("{:010} 00000 n \n", object_offset).as_bytes())?;
+        }
+        self.write(
+            format!(
+                "trailer\n<< /Size {} /Root {} 0 R >>\nstartxref\n{}\n%%EOF\n",
+                self.object_offsets.len() + 1,
+                CATALOG_ID,
+                xref_offset
+            )
+            .as_bytes(),
+        )?;
+
+        self.output.flush()?;
+
+        Ok(self.output)
+    }
+
+    /// Writes the outline as a flat list of items, returning its id unless
+    /// there are no bookmarks pointing at existing pages
+    fn write_outlines(&mut self) -> io::Result<Option<usize>> {
+        let bookmarks: Vec<(String, usize)> = std::mem::take(&mut self.bookmarks)
+            .into_iter()
+            .filter_map(|(title, page)| Some((title, *self.page_ids.get(page)?)))
+            .collect();
+        if bookmarks.is_empty() {
+            return Ok(None);
+        }
+
+        let outlines_id = self.reserve_object();
+        let first_item_id = self.object_offsets.len() + 1;
+        for _ in &bookmarks {
+            self.reserve_object();
+        }
+        let last_item_id = first_item_id + bookmarks.len() - 1;
+
+        for (index, (title, page_id)) in bookmarks.iter().enumerate() {
+            let item_id = first_item_id + index;
+            let mut siblings = vec![];
+            if item_id > first_item_id {
+                siblings.push(format!(" /Prev {} 0 R", item_id - 1));
+            }
+            if item_id < last_item_id {
+                siblings.push(format!(" /Next {} 0 R", item_id + 1));
+            }
+
+            self.begin_object(item_id)?;
+            self.write(
+                format!(
+                    "<< /Title {} /Parent {} 0 R{} /Dest [{} 0 R /Fit] >>\n",
+                    text_string(title),
+                    outlines_id,
+                    siblings.concat(),
+                    page_id
+                )
+                .as_bytes(),
+            )?;
+            self.end_object()?;
+        }
+
+        self.begin_object(outlines_id)?;
+        self.write(
+            format!(
+                "<< /Type /Outlines /First {} 0 R /Last {} 0 R /Count {} >>\n",
+                first_item_id,
+                last_item_id,
+                bookmarks.len()
+            )
+            .as_bytes(),
+        )?;
+        self.end_object()?;
+
+        Ok(Some(outlines_id))
+    }
+
+    fn reserve_object(&mut self) -> usize {
+        self.object_offsets.push(0);
+
+        self.object_offsets.len()
+    }
+
+    fn begin_object(&mut self, id: usize) -> io::Result<()> {
+        self.object_offsets[id - 1] = self.offset;
+
+        self.write(format!("{} 0 obj\n", id).as_bytes())
+    }
+
+    fn end_object(&mut self) -> io::Result<()> {
+        self.write(b"endobj\n")
+    }
+
+    fn write(&mut self, data: &[u8]) -> io::Result<()> {
+        self.output.write_all(data)?;
+        self.offset += data.len();
+
+        Ok(())
+    }
+}
+
+/// Encodes text as a UTF-16 hex string, which PDF readers show in any script
+fn text_string(text: &str) -> String {
+    let units = text
+        .encode_utf16()
+        .map(|unit| format!("{:04X}", unit))
+        .collect::<Vec<String>>()
+        .concat();
+
+    format!("<FEFF{}>", units)
+}
+
+/// Encodes text as a literal string in the Windows code page the `/Text`
+/// font uses, characters it lacks become question marks
+pub fn latin_string(text: &str) -> String {
+    let mut string = String::from("(");
+
+    for character in text.chars() {
+        let code: u8 = match character {
+            '€' => 0x80,
+            '…' => 0x85,
+            '‘' => 0x91,
+            '’' => 0x92,
+            '“' => 0x93,
+            '”' => 0x94,
+            '•' => 0x95,
+            '–' => 0x96,
+            '—' => 0x97,
+            '\u{20}'..='\u{7e}' | '\u{a0}'..='\u{ff}' => character as u8,
+            _ => b'?',
+        };
+
+        match code {
+            b'(' | b')' | b'\\' => {
+                string.push('\\');
+                string.push(char::from(code));
+            }
+            0x20..=0x7e => string.push(char::from(code)),
+            // octal escapes, three digits
+            _ => {
+                string.push('\\');
+                string.extend(
+                    [6, 3, 0]
+                        .iter()
+                        .map(|shift| char::from(b'0' + (code >> shift & 7))),
+                );
+            }
+        }
+    }
+
+    string.push(')');
+    string
+}
+
+pub fn export(
+    presentation: &Presentation,
+    offscreen: &Offscreen,
+    path: &Path,
+) -> Result<(), String> {
+    let file = File::create(path).map_err(|e| format!("{:?}", e))?;
+    let mut writer = PdfWriter::new(BufWriter::new(file)).map_err(|e| format!("{:?}", e))?;
+
+    for slide in presentation.slides() {
+        writer
+            .add_page(&offscreen.render(slide).map_err(|e| e.to_string())?)
+            .map_err(|e| format!("{:?}", e))?;
+    }
+    for section in presentation.sections() {
+        writer.add_bookmark(section.title(), section.first_slide());
+    }
+
+    writer.finish().map_err(|e| format!("{:?}", e))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn write_document(pages: Vec<RgbImage>) -> Vec<u8> {
+        let mut writer = PdfWriter::new(vec![]).unwrap();
+        for page in pages {
+            writer.add_page(&page).unwrap();
+        }
+
+        writer.finish().unwrap()
+    }
+
+    fn text_at(document: &[u8], offset: usize) -> String {
+        String::from_utf8_lossy(&document[offset..]).into_owned()
+    }
+
+    #[test]
+    pub fn writes_header_and_trailer() {
+        let document = write_document(vec![RgbImage::new(1, 1, vec![0xff, 0, 0])]);
+        let text = text_at(&document, 0);
+
+        assert!(text.starts_with("%PDF-1.4\n"));
+        assert!(text.ends_with("%%EOF\n"));
+        assert!(text.contains("/Type /Pages /Kids [5 0 R] /Count 1"));
+    }
+
+    #[test]
+    pub fn cross_reference_table_points_at_objects() {
+        let document = write_document(vec![
+            RgbImage::new(1, 1, vec![0xff, 0, 0]),
+            RgbImage::new(2, 1, vec![0, 0xff, 0, 0, 0, 0xff]),
+        ]);
+        let text = text_at(&document, 0);
+        let xref_offset = text.lines().rev().nth(1).unwrap().parse::<usize>().unwrap();
+
+        let offsets = text_at(&document, xref_offset)
+            .lines()
+            .skip(3)
+            .take_while(|line| line.ends_with(" n "))
+            .map(|line| line[..10].parse::<usize>().unwrap())
+            .collect::<Vec<usize>>();
+
+        assert_eq!(8, offsets.len());
+        for (index, offset) in offsets.iter().enumerate() {
+            assert!(text_at(&document, *offset).starts_with(&format!("{} 0 obj\n", index + 1)));
+        }
+    }
+
+    #[test]
+    pub fn writes_bookmarks_for_existing_pages() {
+        let mut writer = PdfWriter::new(vec![]).unwrap();
+        writer
+            .add_page(&RgbImage::new(1, 1, vec![0, 0, 0]))
+            .unwrap();
+        writer
+            .add_page(&RgbImage::new(1, 1, vec![0, 0, 0]))
+            .unwrap();
+        writer.add_bookmark("Intro", 0);
+        writer.add_bookmark("Zażółć", 1);
+        writer.add_bookmark("Missing", 2);
+        let text = text_at(&writer.finish().unwrap(), 0);
+
+        assert!(text.contains("/Outlines 9 0 R /PageMode /UseOutlines"));
+        assert!(text.contains("/Type /Outlines /First 10 0 R /Last 11 0 R /Count 2"));
+        assert!(text.contains(
+            "<< /Title <FEFF0049006E00740072006F> /Parent 9 0 R /Next 11 0 R /Dest [5 0 R /Fit] >>"
+        ));
+        assert!(text.contains("<< /Title <FEFF005A0061017C00F301420107> /Parent 9 0 R /Prev 10 0 R /Dest [8 0 R /Fit] >>"));
+    }
+
+    #[test]
+    pub fn composes_pages_sharing_one_font() {
+        let mut writer = PdfWriter::new(vec![]).unwrap();
+        writer
+            .add_composed_page(100, 200, None, "BT /Text 12 Tf (a) Tj ET")
+            .unwrap();
+        writer
+            .add_composed_page(
+                100,
+                200,
+                Some(&RgbImage::new(1, 1, vec![0, 0, 0])),
+                "BT /Text 12 Tf (b) Tj ET",
+            )
+            .unwrap();
+        let text = text_at(&writer.finish().unwrap(), 0);
+
+        assert_eq!(1, text.matches("/BaseFont /Helvetica").count());
+        assert!(text.contains(
+            "/MediaBox [0 0 100 200] /Resources << /Font << /Text 3 0 R >> >> /Contents 4 0 R"
+        ));
+        assert!(text.contains(
+            "/Resources << /XObject << /Slide 6 0 R >> /Font << /Text 3 0 R >> >> /Contents 7 0 R"
+        ));
+    }
+
+    #[test]
+    pub fn encodes_latin_strings_with_escapes() {
+        assert_eq!("(\\(a\\) \\351\\224\\\\?)", latin_string("(a) é”\\😀"));
+    }
+
+    #[test]
+    pub fn encodes_text_strings_as_utf16() {
+        assert_eq!("<FEFF0041D83DDE00>", text_string("A😀"));
+    }
+}
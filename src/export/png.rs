@@ -0,0 +1,23 @@
+use crate::presentation::Presentation;
+use crate::rendering::offscreen::Offscreen;
+use sdl2::image::SaveSurface;
+use std::fs;
+use std::path::Path;
+
+/// Writes every slide as `slide-001.png`, `slide-002.png`, ... into the directory
+pub fn export(
+    presentation: &Presentation,
+    offscreen: &Offscreen,
+    directory: &Path,
+) -> Result<(), String> {
+    fs::create_dir_all(directory).map_err(|e| format!("{:?}", e))?;
+
+    for (index, slide) in presentation.slides().iter().enumerate() {
+        offscreen
+            .render_surface(slide)
+            .map_err(|e| e.to_string())?
+            .save(directory.join(format!("slide-{:03}.png", index + 1)))?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,741 @@
+use crate::export::html::escape;
+use crate::presentation::{Color, Fill, GradientDirection, ImageFit, Presentation};
+use crate::rendering::error::RenderError;
+use crate::rendering::layout::{FontKey, Renderer};
+use crate::rendering::offscreen::Offscreen;
+use crate::zip::ZipWriter;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// English Metric Units per slide pixel, with pixels being 1/96 of an inch
+/// like Office takes them to be
+const EMU_PER_PIXEL: i64 = 9525;
+/// Hundredths of a point per slide pixel
+const FONT_SIZE_PER_PIXEL: u32 = 75;
+
+const XML_HEADER: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n";
+const NAMESPACES: &str = "xmlns:a=\"http://schemas.openxmlformats.org/drawingml/2006/main\" \
+     xmlns:r=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships\" \
+     xmlns:p=\"http://schemas.openxmlformats.org/presentationml/2006/main\"";
+const RELATIONSHIP: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships/";
+const CONTENT_TYPE: &str = "application/vnd.openxmlformats-officedocument.";
+/// Properties of the group every slide's shapes are in
+const GROUP: &str =
+    "<p:nvGrpSpPr><p:cNvPr id=\"1\" name=\"\"/><p:cNvGrpSpPr/><p:nvPr/></p:nvGrpSpPr>\
+     <p:grpSpPr><a:xfrm><a:off x=\"0\" y=\"0\"/><a:ext cx=\"0\" cy=\"0\"/>\
+     <a:chOff x=\"0\" y=\"0\"/><a:chExt cx=\"0\" cy=\"0\"/></a:xfrm></p:grpSpPr>";
+
+/// Something the layout drew, in slide pixels
+#[derive(Debug, PartialEq)]
+enum Shape {
+    Fill {
+        fill: Fill,
+        position: (i32, i32),
+        size: (u32, u32),
+    },
+    Text {
+        text: String,
+        font: FontKey,
+        color: Color,
+        position: (i32, i32),
+        size: (u32, u32),
+    },
+    Image {
+        path: String,
+        position: (i32, i32),
+        size: (u32, u32),
+    },
+}
+
+/// A slide as the shapes drawn on it
+#[derive(Debug, Default, PartialEq)]
+struct Drawing {
+    background: Option<Fill>,
+    shapes: Vec<Shape>,
+}
+
+/// Keeps what the layout draws as shapes, measuring with another renderer
+struct ShapeRecorder<'m> {
+    measure: &'m mut dyn Renderer,
+    drawing: Drawing,
+}
+
+impl Renderer for ShapeRecorder<'_> {
+    fn text_size(&mut self, text: &str, font: &FontKey) -> Result<(u32, u32), RenderError> {
+        self.measure.text_size(text, font)
+    }
+
+    fn has_glyph(&mut self, character: char, font: &FontKey) -> Result<bool, RenderError> {
+        self.measure.has_glyph(character, font)
+    }
+
+    fn image_size(&mut self, path: &str) -> Result<(u32, u32), RenderError> {
+        self.measure.image_size(path)
+    }
+
+    fn clear(&mut self, fill: &Fill) -> Result<(), RenderError> {
+        self.drawing = Drawing {
+            background: Some(fill.clone()),
+            shapes: vec![],
+        };
+
+        Ok(())
+    }
+
+    fn draw_fill(
+        &mut self,
+        fill: &Fill,
+        position: (i32, i32),
+        size: (u32, u32),
+    ) -> Result<(), RenderError> {
+        self.drawing.shapes.push(Shape::Fill {
+            fill: fill.clone(),
+            position,
+            size,
+        });
+
+        Ok(())
+    }
+
+    fn draw_text(
+        &mut self,
+        text: &str,
+        font: &FontKey,
+        color: Color,
+        position: (i32, i32),
+    ) -> Result<(), RenderError> {
+        let size = self.measure.text_size(text, font)?;
+        self.drawing.shapes.push(Shape::Text {
+            text: text.into(),
+            font: font.clone(),
+            color,
+            position,
+            size,
+        });
+
+        Ok(())
+    }
+
+    fn draw_image(
+        &mut self,
+        path: &str,
+        position: (i32, i32),
+        size: (u32, u32),
+    ) -> Result<(), RenderError> {
+        self.drawing.shapes.push(Shape::Image {
+            path: path.into(),
+            position,
+            size,
+        });
+
+        Ok(())
+    }
+
+    fn video_size(&mut self, path: &str) -> Result<(u32, u32), RenderError> {
+        self.measure.video_size(path)
+    }
+
+    /// Office would need the video in a format it plays, so videos are
+    /// left out
+    fn draw_video(
+        &mut self,
+        _path: &str,
+        _position: (i32, i32),
+        _size: (u32, u32),
+    ) -> Result<(), RenderError> {
+        Ok(())
+    }
+}
+
+/// Writes the presentation as a PPTX file, every slide with all of
+/// its build steps shown. Lines of text become text boxes and images
+/// pictures where the layout puts them, in the fonts and colors they are
+/// drawn with. Videos and images in formats Office doesn't open, like
+/// SVG, are left out.
+pub fn export(
+    presentation: &Presentation,
+    offscreen: &Offscreen,
+    output: &Path,
+) -> Result<(), String> {
+    let drawings = offscreen
+        .slide_renderer()
+        .with_measuring_renderer(|layout, measure| {
+            presentation
+                .slides()
+                .iter()
+                .map(|slide| {
+                    let mut recorder = ShapeRecorder {
+                        measure: &mut *measure,
+                        drawing: Drawing::default(),
+                    };
+                    layout.render_slide(&mut recorder, slide, slide.step_count())?;
+
+                    Ok(recorder.drawing)
+                })
+                .collect::<Result<Vec<Drawing>, RenderError>>()
+        })
+        .map_err(|e| e.to_string())?;
+
+    let file = File::create(output).map_err(|e| format!("{:?}", e))?;
+    write_package(BufWriter::new(file), presentation, &drawings)
+        .and_then(|mut output| output.flush())
+        .map_err(|e| format!("{:?}", e))
+}
+
+fn write_package<W: Write>(
+    output: W,
+    presentation: &Presentation,
+    drawings: &[Drawing],
+) -> io::Result<W> {
+    let metadata = presentation.metadata();
+    let resolution = metadata.resolution();
+    let media = media_files(drawings);
+    let mut zip = ZipWriter::new(output);
+
+    zip.add_file(
+        "[Content_Types].xml",
+        content_types(drawings.len()).as_bytes(),
+    )?;
+    zip.add_file(
+        "_rels/.rels",
+        format!(
+            "{}<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\
+             <Relationship Id=\"rId1\" Type=\"{}officeDocument\" Target=\"ppt/presentation.xml\"/>\
+             <Relationship Id=\"rId2\" \
+             Type=\"http://schemas.openxmlformats.org/package/2006/relationships/metadata/core-properties\" \
+             Target=\"docProps/core.xml\"/></Relationships>",
+            XML_HEADER, RELATIONSHIP
+        )
+        .as_bytes(),
+    )?;
+    zip.add_file(
+        "docProps/core.xml",
+        format!(
+            "{}<cp:coreProperties xmlns:cp=\"http://schemas.openxmlformats.org/package/2006/metadata/core-properties\" \
+             xmlns:dc=\"http://purl.org/dc/elements/1.1/\"><dc:title>{}</dc:title></cp:coreProperties>",
+            XML_HEADER,
+            escape(metadata.title())
+        )
+        .as_bytes(),
+    )?;
+
+    let slide_ids = (0..drawings.len())
+        .map(|index| {
+            format!(
+                "<p:sldId id=\"{}\" r:id=\"rId{}\"/>",
+                256 + index,
+                index + 3
+            )
+        })
+        .collect::<Vec<String>>();
+    zip.add_file(
+        "ppt/presentation.xml",
+        format!(
+            "{}<p:presentation {}><p:sldMasterIdLst><p:sldMasterId id=\"2147483648\" r:id=\"rId1\"/></p:sldMasterIdLst>\
+             <p:sldIdLst>{}</p:sldIdLst><p:sldSz cx=\"{}\" cy=\"{}\"/><p:notesSz cx=\"6858000\" cy=\"9144000\"/></p:presentation>",
+            XML_HEADER,
+            NAMESPACES,
+            slide_ids.concat(),
+            i64::from(resolution.width()) * EMU_PER_PIXEL,
+            i64::from(resolution.height()) * EMU_PER_PIXEL
+        )
+        .as_bytes(),
+    )?;
+    let mut targets = vec![
+        ("slideMaster", "slideMasters/slideMaster1.xml".to_string()),
+        ("theme", "theme/theme1.xml".into()),
+    ];
+    targets.extend(
+        (1..=drawings.len()).map(|number| ("slide", format!("slides/slide{}.xml", number))),
+    );
+    zip.add_file(
+        "ppt/_rels/presentation.xml.rels",
+        relationships(&targets).as_bytes(),
+    )?;
+
+    write_master(&mut zip)?;
+
+    for (index, drawing) in drawings.iter().enumerate() {
+        let (slide, rels) = slide_part(drawing, &media);
+        zip.add_file(
+            &format!("ppt/slides/slide{}.xml", index + 1),
+            slide.as_bytes(),
+        )?;
+        zip.add_file(
+            &format!("ppt/slides/_rels/slide{}.xml.rels", index + 1),
+            rels.as_bytes(),
+        )?;
+    }
+    for (path, name) in &media {
+        zip.add_file(&format!("ppt/media/{}", name), &fs::read(path)?)?;
+    }
+
+    zip.finish()
+}
+
+/// The slide master, its only layout and the theme, which are empty as
+/// the slides have everything on them
+fn write_master<W: Write>(zip: &mut ZipWriter<W>) -> io::Result<()> {
+    let color =
+        |name: &str, value: &str| format!("<a:{0}><a:srgbClr val=\"{1}\"/></a:{0}>", name, value);
+    let colors = [
+        color("dk1", "000000"),
+        color("lt1", "FFFFFF"),
+        color("dk2", "44546A"),
+        color("lt2", "E7E6E6"),
+        color("accent1", "4472C4"),
+        color("accent2", "ED7D31"),
+        color("accent3", "A5A5A5"),
+        color("accent4", "FFC000"),
+        color("accent5", "5B9BD5"),
+        color("accent6", "70AD47"),
+        color("hlink", "0563C1"),
+        color("folHlink", "954F72"),
+    ];
+    let font = "<a:latin typeface=\"Calibri\"/><a:ea typeface=\"\"/><a:cs typeface=\"\"/>";
+    let fill = "<a:solidFill><a:schemeClr val=\"phClr\"/></a:solidFill>";
+    let line = format!("<a:ln w=\"6350\">{}</a:ln>", fill);
+    let effect = "<a:effectStyle><a:effectLst/></a:effectStyle>";
+
+    zip.add_file(
+        "ppt/theme/theme1.xml",
+        format!(
+            "{header}<a:theme xmlns:a=\"http://schemas.openxmlformats.org/drawingml/2006/main\" name=\"przntr\">\
+             <a:themeElements><a:clrScheme name=\"przntr\">{colors}</a:clrScheme>\
+             <a:fontScheme name=\"przntr\"><a:majorFont>{font}</a:majorFont><a:minorFont>{font}</a:minorFont></a:fontScheme>\
+             <a:fmtScheme name=\"przntr\"><a:fillStyleLst>{fills}</a:fillStyleLst><a:lnStyleLst>{lines}</a:lnStyleLst>\
+             <a:effectStyleLst>{effects}</a:effectStyleLst><a:bgFillStyleLst>{fills}</a:bgFillStyleLst></a:fmtScheme>\
+             </a:themeElements><a:objectDefaults/><a:extraClrSchemeLst/></a:theme>",
+            header = XML_HEADER,
+            colors = colors.concat(),
+            font = font,
+            fills = fill.repeat(3),
+            lines = line.repeat(3),
+            effects = effect.repeat(3)
+        )
+        .as_bytes(),
+    )?;
+    zip.add_file(
+        "ppt/slideMasters/slideMaster1.xml",
+        format!(
+            "{}<p:sldMaster {}><p:cSld><p:bg><p:bgRef idx=\"1001\"><a:schemeClr val=\"bg1\"/></p:bgRef></p:bg>\
+             <p:spTree>{}</p:spTree></p:cSld><p:clrMap bg1=\"lt1\" tx1=\"dk1\" bg2=\"lt2\" tx2=\"dk2\" accent1=\"accent1\" \
+             accent2=\"accent2\" accent3=\"accent3\" accent4=\"accent4\" accent5=\"accent5\" accent6=\"accent6\" \
+             hlink=\"hlink\" folHlink=\"folHlink\"/><p:sldLayoutIdLst><p:sldLayoutId id=\"2147483649\" r:id=\"rId1\"/>\
+             </p:sldLayoutIdLst></p:sldMaster>",
+            XML_HEADER, NAMESPACES, GROUP
+        )
+        .as_bytes(),
+    )?;
+    zip.add_file(
+        "ppt/slideMasters/_rels/slideMaster1.xml.rels",
+        relationships(&[
+            ("slideLayout", "../slideLayouts/slideLayout1.xml".into()),
+            ("theme", "../theme/theme1.xml".into()),
+        ])
+        .as_bytes(),
+    )?;
+    zip.add_file(
+        "ppt/slideLayouts/slideLayout1.xml",
+        format!(
+            "{}<p:sldLayout {} type=\"blank\" preserve=\"1\"><p:cSld name=\"Blank\"><p:spTree>{}</p:spTree></p:cSld>\
+             <p:clrMapOvr><a:masterClrMapping/></p:clrMapOvr></p:sldLayout>",
+            XML_HEADER, NAMESPACES, GROUP
+        )
+        .as_bytes(),
+    )?;
+    zip.add_file(
+        "ppt/slideLayouts/_rels/slideLayout1.xml.rels",
+        relationships(&[("slideMaster", "../slideMasters/slideMaster1.xml".into())]).as_bytes(),
+    )
+}
+
+fn content_types(slide_count: usize) -> String {
+    let part = |name: &str, kind: &str| {
+        format!(
+            "<Override PartName=\"{}\" ContentType=\"{}{}+xml\"/>",
+            name, CONTENT_TYPE, kind
+        )
+    };
+    let mut parts = vec![
+        part("/ppt/presentation.xml", "presentationml.presentation.main"),
+        part(
+            "/ppt/slideMasters/slideMaster1.xml",
+            "presentationml.slideMaster",
+        ),
+        part(
+            "/ppt/slideLayouts/slideLayout1.xml",
+            "presentationml.slideLayout",
+        ),
+        part("/ppt/theme/theme1.xml", "theme"),
+        "<Override PartName=\"/docProps/core.xml\" \
+         ContentType=\"application/vnd.openxmlformats-package.core-properties+xml\"/>"
+            .into(),
+    ];
+    parts.extend((1..=slide_count).map(|number| {
+        part(
+            &format!("/ppt/slides/slide{}.xml", number),
+            "presentationml.slide",
+        )
+    }));
+
+    format!(
+        "{}<Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\">\
+         <Default Extension=\"rels\" ContentType=\"application/vnd.openxmlformats-package.relationships+xml\"/>\
+         <Default Extension=\"xml\" ContentType=\"application/xml\"/>\
+         <Default Extension=\"png\" ContentType=\"image/png\"/>\
+         <Default Extension=\"jpeg\" ContentType=\"image/jpeg\"/>\
+         <Default Extension=\"gif\" ContentType=\"image/gif\"/>\
+         <Default Extension=\"bmp\" ContentType=\"image/bmp\"/>{}</Types>",
+        XML_HEADER,
+        parts.concat()
+    )
+}
+
+/// Relationships numbered from `rId1` on, in the order given
+fn relationships(targets: &[(&str, String)]) -> String {
+    let relationships = targets
+        .iter()
+        .enumerate()
+        .map(|(index, (kind, target))| {
+            format!(
+                "<Relationship Id=\"rId{}\" Type=\"{}{}\" Target=\"{}\"/>",
+                index + 1,
+                RELATIONSHIP,
+                kind,
+                escape(target)
+            )
+        })
+        .collect::<Vec<String>>();
+
+    format!(
+        "{}<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">{}</Relationships>",
+        XML_HEADER,
+        relationships.concat()
+    )
+}
+
+/// Extension of the image in the package, none when Office can't open it
+fn media_extension(path: &str) -> Option<&'static str> {
+    let extension = Path::new(path).extension()?.to_str()?.to_lowercase();
+
+    match extension.as_str() {
+        "png" => Some("png"),
+        "jpg" | "jpeg" => Some("jpeg"),
+        "gif" => Some("gif"),
+        "bmp" => Some("bmp"),
+        _ => None,
+    }
+}
+
+/// Paths of the images the slides show with the names they get in the
+/// package, each image once
+fn media_files(drawings: &[Drawing]) -> Vec<(String, String)> {
+    let mut media: Vec<(String, String)> = vec![];
+
+    let paths = drawings.iter().flat_map(|drawing| {
+        drawing
+            .background
+            .iter()
+            .filter_map(Fill::image_path)
+            .chain(drawing.shapes.iter().filter_map(|shape| match shape {
+                Shape::Image { path, .. }
+                | Shape::Fill {
+                    fill: Fill::Image { path, .. },
+                    ..
+                } => Some(path),
+                Shape::Fill { .. } | Shape::Text { .. } => None,
+            }))
+    });
+    for path in paths {
+        if let Some(extension) = media_extension(path) {
+            if media.iter().all(|(known, _)| known != path) {
+                let name = format!("image{}.{}", media.len() + 1, extension);
+                media.push((path.clone(), name));
+            }
+        }
+    }
+
+    media
+}
+
+/// The slide's XML and its relationships, to the layout and to the images
+/// it shows
+fn slide_part(drawing: &Drawing, media: &[(String, String)]) -> (String, String) {
+    let mut targets = vec![(
+        "slideLayout",
+        "../slideLayouts/slideLayout1.xml".to_string(),
+    )];
+    let mut image = |path: &str| {
+        let (_, name) = media.iter().find(|(known, _)| known == path)?;
+        let target = format!("../media/{}", name);
+        let index = targets
+            .iter()
+            .position(|(_, known)| *known == target)
+            .unwrap_or_else(|| {
+                targets.push(("image", target));
+                targets.len() - 1
+            });
+
+        Some(format!("rId{}", index + 1))
+    };
+
+    let background = drawing
+        .background
+        .as_ref()
+        .and_then(|fill| fill_xml(fill, &mut image))
+        .map(|fill| format!("<p:bg><p:bgPr>{}<a:effectLst/></p:bgPr></p:bg>", fill))
+        .unwrap_or_default();
+    // the group holding the shapes has the first id
+    let mut last_id = 1;
+    let shapes = drawing
+        .shapes
+        .iter()
+        .filter_map(|shape| {
+            let xml = shape_xml(shape, last_id + 1, &mut image)?;
+            last_id += 1;
+
+            Some(xml)
+        })
+        .collect::<Vec<String>>();
+
+    let slide = format!(
+        "{}<p:sld {}><p:cSld>{}<p:spTree>{}{}</p:spTree></p:cSld>\
+         <p:clrMapOvr><a:masterClrMapping/></p:clrMapOvr></p:sld>",
+        XML_HEADER,
+        NAMESPACES,
+        background,
+        GROUP,
+        shapes.concat()
+    );
+
+    (slide, relationships(&targets))
+}
+
+/// The shape's XML, none when it shows an image that isn't in the package
+fn shape_xml<F: FnMut(&str) -> Option<String>>(
+    shape: &Shape,
+    id: u32,
+    image: &mut F,
+) -> Option<String> {
+    match shape {
+        Shape::Fill {
+            fill,
+            position,
+            size,
+        } => Some(format!(
+            "<p:sp><p:nvSpPr><p:cNvPr id=\"{}\" name=\"Box\"/><p:cNvSpPr/><p:nvPr/></p:nvSpPr>\
+             <p:spPr>{}<a:prstGeom prst=\"rect\"><a:avLst/></a:prstGeom>{}</p:spPr></p:sp>",
+            id,
+            transform(*position, *size),
+            fill_xml(fill, image)?
+        )),
+        Shape::Text {
+            text,
+            font,
+            color,
+            position,
+            size,
+        } => Some(format!(
+            "<p:sp><p:nvSpPr><p:cNvPr id=\"{}\" name=\"Text\"/><p:cNvSpPr txBox=\"1\"/><p:nvPr/></p:nvSpPr>\
+             <p:spPr>{}<a:prstGeom prst=\"rect\"><a:avLst/></a:prstGeom><a:noFill/></p:spPr>\
+             <p:txBody><a:bodyPr wrap=\"none\" lIns=\"0\" tIns=\"0\" rIns=\"0\" bIns=\"0\"><a:noAutofit/></a:bodyPr>\
+             <a:lstStyle/><a:p><a:r><a:rPr lang=\"en-US\" sz=\"{}\" b=\"{}\" i=\"{}\" dirty=\"0\">\
+             <a:solidFill>{}</a:solidFill><a:latin typeface=\"{}\"/></a:rPr><a:t>{}</a:t></a:r></a:p></p:txBody></p:sp>",
+            id,
+            transform(*position, *size),
+            u32::from(font.1) * FONT_SIZE_PER_PIXEL,
+            u8::from(font.0.weight() >= 600),
+            u8::from(font.0.italic()),
+            color_xml(*color),
+            escape(font.0.name()),
+            escape(text)
+        )),
+        Shape::Image {
+            path,
+            position,
+            size,
+        } => Some(format!(
+            "<p:pic><p:nvPicPr><p:cNvPr id=\"{}\" name=\"Picture\"/><p:cNvPicPr><a:picLocks noChangeAspect=\"1\"/>\
+             </p:cNvPicPr><p:nvPr/></p:nvPicPr><p:blipFill><a:blip r:embed=\"{}\"/><a:stretch><a:fillRect/></a:stretch>\
+             </p:blipFill><p:spPr>{}<a:prstGeom prst=\"rect\"><a:avLst/></a:prstGeom></p:spPr></p:pic>",
+            id,
+            image(path)?,
+            transform(*position, *size)
+        )),
+    }
+}
+
+fn transform(position: (i32, i32), size: (u32, u32)) -> String {
+    format!(
+        "<a:xfrm><a:off x=\"{}\" y=\"{}\"/><a:ext cx=\"{}\" cy=\"{}\"/></a:xfrm>",
+        i64::from(position.0) * EMU_PER_PIXEL,
+        i64::from(position.1) * EMU_PER_PIXEL,
+        i64::from(size.0) * EMU_PER_PIXEL,
+        i64::from(size.1) * EMU_PER_PIXEL
+    )
+}
+
+/// Image fills are stretched over the area whether they cover or are
+/// contained in it, Office has nothing to crop them with
+fn fill_xml<F: FnMut(&str) -> Option<String>>(fill: &Fill, image: &mut F) -> Option<String> {
+    Some(match fill {
+        Fill::Color(color) => format!("<a:solidFill>{}</a:solidFill>", color_xml(*color)),
+        Fill::Gradient {
+            from,
+            to,
+            direction,
+        } => format!(
+            "<a:gradFill rotWithShape=\"1\"><a:gsLst><a:gs pos=\"0\">{}</a:gs><a:gs pos=\"100000\">{}</a:gs></a:gsLst>{}</a:gradFill>",
+            color_xml(*from),
+            color_xml(*to),
+            match direction {
+                GradientDirection::Horizontal => "<a:lin ang=\"0\" scaled=\"0\"/>",
+                GradientDirection::Vertical => "<a:lin ang=\"5400000\" scaled=\"0\"/>",
+                GradientDirection::Diagonal => "<a:lin ang=\"2700000\" scaled=\"0\"/>",
+                GradientDirection::Radial => {
+                    "<a:path path=\"circle\"><a:fillToRect l=\"50000\" t=\"50000\" r=\"50000\" b=\"50000\"/></a:path>"
+                }
+            }
+        ),
+        Fill::Image { path, fit } => format!(
+            "<a:blipFill><a:blip r:embed=\"{}\"/>{}</a:blipFill>",
+            image(path)?,
+            match fit {
+                ImageFit::Tile => "<a:tile tx=\"0\" ty=\"0\" sx=\"100000\" sy=\"100000\" flip=\"none\" algn=\"tl\"/>",
+                ImageFit::Cover | ImageFit::Contain => "<a:stretch><a:fillRect/></a:stretch>",
+            }
+        ),
+    })
+}
+
+fn color_xml(color: Color) -> String {
+    let alpha = if color.alpha() == 0xff {
+        String::new()
+    } else {
+        format!(
+            "<a:alpha val=\"{}\"/>",
+            u32::from(color.alpha()) * 100_000 / 0xff
+        )
+    };
+
+    format!(
+        "<a:srgbClr val=\"{:02X}{:02X}{:02X}\">{}</a:srgbClr>",
+        color.red(),
+        color.green(),
+        color.blue(),
+        alpha
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::presentation::{Font, Metadata, Resolution, Style};
+
+    fn text(content: &str, font: &Font) -> Shape {
+        Shape::Text {
+            text: content.into(),
+            font: (font.descriptor().clone(), 20),
+            color: Color::new(0xff, 0x80, 0, 0x80),
+            position: (10, 20),
+            size: (100, 24),
+        }
+    }
+
+    #[test]
+    pub fn writes_lines_as_text_boxes() {
+        let shape = shape_xml(
+            &text("a < b", &Font::system("Sans".into(), 700, false)),
+            2,
+            &mut |_: &str| None,
+        )
+        .unwrap();
+
+        assert!(shape.contains(
+            "<a:xfrm><a:off x=\"95250\" y=\"190500\"/><a:ext cx=\"952500\" cy=\"228600\"/></a:xfrm>"
+        ));
+        assert!(shape.contains("sz=\"1500\" b=\"1\" i=\"0\""));
+        assert!(shape.contains(
+            "<a:srgbClr val=\"FF8000\"><a:alpha val=\"50196\"/></a:srgbClr></a:solidFill><a:latin typeface=\"Sans\"/>"
+        ));
+        assert!(shape.contains("<a:t>a &lt; b</a:t>"));
+    }
+
+    #[test]
+    pub fn relates_slides_to_the_images_they_show() {
+        let drawing = Drawing {
+            background: Some(Fill::Image {
+                path: "bg.jpg".into(),
+                fit: ImageFit::Cover,
+            }),
+            shapes: vec![
+                Shape::Image {
+                    path: "cat.png".into(),
+                    position: (0, 0),
+                    size: (10, 10),
+                },
+                Shape::Image {
+                    path: "logo.svg".into(),
+                    position: (0, 0),
+                    size: (10, 10),
+                },
+                text("hi", &Font::system("Sans".into(), 400, true)),
+            ],
+        };
+        let drawings = [drawing];
+        let media = media_files(&drawings);
+
+        assert_eq!(
+            vec![
+                ("bg.jpg".to_string(), "image1.jpeg".to_string()),
+                ("cat.png".into(), "image2.png".into())
+            ],
+            media
+        );
+
+        let (slide, rels) = slide_part(&drawings[0], &media);
+
+        assert!(slide.contains("<p:bg><p:bgPr><a:blipFill><a:blip r:embed=\"rId2\"/>"));
+        assert!(slide.contains("<p:cNvPr id=\"2\" name=\"Picture\"/>"));
+        assert!(slide.contains("<a:blip r:embed=\"rId3\"/>"));
+        // the SVG is left out, so the text comes right after the picture
+        assert!(slide.contains("<p:cNvPr id=\"3\" name=\"Text\"/>"));
+        assert!(slide.contains("i=\"1\""));
+        assert!(rels.contains("Id=\"rId3\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/image\" Target=\"../media/image2.png\""));
+    }
+
+    #[test]
+    pub fn packages_every_part() {
+        let presentation = Presentation::new(
+            Metadata::new("Talk".into(), Resolution::new(800, 600)),
+            vec![],
+            Style::new(vec![]).unwrap(),
+        );
+
+        let package = write_package(
+            vec![],
+            &presentation,
+            &[Drawing::default(), Drawing::default()],
+        )
+        .unwrap();
+        let package = String::from_utf8_lossy(&package);
+
+        for part in &[
+            "[Content_Types].xml",
+            "_rels/.rels",
+            "docProps/core.xml",
+            "ppt/presentation.xml",
+            "ppt/theme/theme1.xml",
+            "ppt/slideMasters/slideMaster1.xml",
+            "ppt/slideLayouts/slideLayout1.xml",
+            "ppt/slides/slide2.xml",
+            "ppt/slides/_rels/slide2.xml.rels",
+        ] {
+            assert!(package.contains(part), "{} is missing", part);
+        }
+        assert!(package.contains("<p:sldSz cx=\"7620000\" cy=\"5715000\"/>"));
+        assert!(package.contains("<dc:title>Talk</dc:title>"));
+    }
+}
@@ -0,0 +1,218 @@
+use crate::export::html::{css_color, css_fill, escape, spans_html};
+use crate::presentation::{Fill, Presentation, SlideElement, TransitionKind};
+use std::fs;
+use std::path::Path;
+
+const REVEAL_URL: &str = "https://cdn.jsdelivr.net/npm/reveal.js@5";
+
+pub fn export(presentation: &Presentation, output: &Path) -> Result<(), String> {
+    fs::write(output, render(presentation)?).map_err(|e| format!("{:?}", e))
+}
+
+/// Builds a Reveal.js deck loading the library from its CDN. Build steps
+/// become fragments and images and videos are linked by the paths the
+/// presentation uses.
+pub fn render(presentation: &Presentation) -> Result<String, String> {
+    let metadata = presentation.metadata();
+    let resolution = metadata.resolution();
+    let slides = (0..presentation.slides().len())
+        .map(|index| render_slide(presentation, index))
+        .collect::<Result<String, String>>()?;
+
+    Ok(format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n\
+         <link rel=\"stylesheet\" href=\"{url}/dist/reveal.css\">\n\
+         <link rel=\"stylesheet\" href=\"{url}/dist/theme/black.css\">\n</head>\n<body>\n\
+         <div class=\"reveal\">\n<div class=\"slides\">\n{slides}</div>\n</div>\n\
+         <script src=\"{url}/dist/reveal.js\"></script>\n<script src=\"{url}/plugin/notes/notes.js\"></script>\n\
+         <script>Reveal.initialize({{ width: {width}, height: {height}, hash: true, plugins: [RevealNotes] }});</script>\n\
+         </body>\n</html>\n",
+        title = escape(metadata.title()),
+        url = REVEAL_URL,
+        slides = slides,
+        width = resolution.width(),
+        height = resolution.height()
+    ))
+}
+
+fn render_slide(presentation: &Presentation, index: usize) -> Result<String, String> {
+    let slide = &presentation.slides()[index];
+    let mut attributes = vec![];
+
+    if let Some(transition) = presentation.transition_to(index) {
+        let name = match transition.kind() {
+            TransitionKind::None => "none",
+            TransitionKind::Fade => "fade",
+            TransitionKind::Slide => "slide",
+        };
+        attributes.push(format!(
+            " data-transition=\"{}\" data-transition-speed=\"{}\"",
+            name,
+            if transition.duration().as_millis() < 500 {
+                "fast"
+            } else {
+                "default"
+            }
+        ));
+    }
+    match slide
+        .background()
+        .or_else(|| presentation.style().background())
+    {
+        Some(Fill::Color(color)) => {
+            attributes.push(format!(" data-background-color=\"{}\"", css_color(*color)));
+        }
+        Some(Fill::Image { path, .. }) => {
+            attributes.push(format!(" data-background-image=\"{}\"", escape(path)));
+        }
+        Some(gradient) => attributes.push(format!(
+            " data-background-gradient=\"{}\"",
+            css_fill(gradient)?
+        )),
+        None => {}
+    }
+
+    let elements = presentation
+        .arranged_elements(slide)
+        .into_iter()
+        .map(|(element, step)| render_element(element, step))
+        .collect::<Vec<String>>()
+        .concat();
+    let notes = slide
+        .notes()
+        .iter()
+        .map(|note| format!("<aside class=\"notes\">{}</aside>\n", escape(note)))
+        .collect::<Vec<String>>()
+        .concat();
+
+    Ok(format!(
+        "<section{}>\n{}{}</section>\n",
+        attributes.concat(),
+        elements,
+        notes
+    ))
+}
+
+/// The element as Reveal.js markup, shown as a fragment from its step on
+fn render_element(element: &SlideElement, step: u32) -> String {
+    let fragment = if step > 0 {
+        format!(" class=\"fragment\" data-fragment-index=\"{}\"", step)
+    } else {
+        String::new()
+    };
+
+    match element {
+        SlideElement::Text(text) => format!(
+            "<p{}>{}</p>\n",
+            fragment,
+            spans_html(text.spans()).replace('\n', "<br>")
+        ),
+        SlideElement::Title(text) => format!("<h1{}>{}</h1>\n", fragment, spans_html(text.spans())),
+        SlideElement::Subtitle(text) => {
+            format!("<h2{}>{}</h2>\n", fragment, spans_html(text.spans()))
+        }
+        SlideElement::List(list) => format!(
+            "<ul{}>\n{}</ul>\n",
+            fragment,
+            list.items()
+                .iter()
+                .map(|item| format!("<li>{}</li>\n", escape(item)))
+                .collect::<Vec<String>>()
+                .concat()
+        ),
+        SlideElement::Image(image) => {
+            format!("<img{} src=\"{}\">\n", fragment, escape(image.path()))
+        }
+        SlideElement::Video(video) => format!(
+            "<video{} src=\"{}\" data-autoplay loop muted playsinline></video>\n",
+            fragment,
+            escape(video.path())
+        ),
+        SlideElement::Row(container) | SlideElement::Column(container) => {
+            let stack = if let SlideElement::Row(_) = element {
+                "r-hstack"
+            } else {
+                "r-vstack"
+            };
+            // the container is the fragment, its children come with it
+            let children = container
+                .elements()
+                .iter()
+                .map(|child| render_element(child, 0))
+                .collect::<String>();
+            let class = if step > 0 {
+                format!(
+                    " class=\"{} fragment\" data-fragment-index=\"{}\"",
+                    stack, step
+                )
+            } else {
+                format!(" class=\"{}\"", stack)
+            };
+
+            format!("<div{}>\n{}</div>\n", class, children)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::presentation::{
+        Color, Container, Image, Length, List, Metadata, Resolution, Slide, Span, Style, Text,
+        Transition,
+    };
+    use std::time::Duration;
+
+    #[test]
+    pub fn renders_steps_as_fragments() {
+        let mut bold = Span::new("bold".into());
+        bold.set_bold(true);
+        let mut slide = Slide::new(
+            "intro".into(),
+            vec![SlideElement::Title(Text::new("Hi <there>".into(), None))],
+        );
+        slide.push_element_in_step(
+            SlideElement::Text(Text::with_spans(vec![Span::new("a\n".into()), bold], None)),
+            1,
+        );
+        slide.push_element_in_step(
+            SlideElement::Row(Container::new(vec![
+                SlideElement::Image(Image::new("cat.png".into())),
+                SlideElement::List(List::new(vec!["x".into()], "•".into(), Length::Pixels(0.0))),
+            ])),
+            2,
+        );
+        slide.add_note("say hi".into());
+        slide.set_background(Fill::Color(Color::new(0, 0, 0xff, 0xff)));
+
+        let mut metadata = Metadata::new("Talk".into(), Resolution::default());
+        metadata.set_transition(Transition::new(
+            TransitionKind::Fade,
+            Duration::from_millis(300),
+        ));
+        let presentation = Presentation::new(metadata, vec![slide], Style::new(vec![]).unwrap());
+
+        assert_eq!(
+            "<section data-transition=\"fade\" data-transition-speed=\"fast\" data-background-color=\"#0000ffff\">\n\
+             <h1>Hi &lt;there&gt;</h1>\n\
+             <p class=\"fragment\" data-fragment-index=\"1\">a<br><span style=\"font-weight: bold;\">bold</span></p>\n\
+             <div class=\"r-hstack fragment\" data-fragment-index=\"2\">\n\
+             <img src=\"cat.png\">\n<ul>\n<li>x</li>\n</ul>\n</div>\n\
+             <aside class=\"notes\">say hi</aside>\n</section>\n",
+            render_slide(&presentation, 0).unwrap()
+        );
+    }
+
+    #[test]
+    pub fn sizes_the_deck_like_the_presentation() {
+        let presentation = Presentation::new(
+            Metadata::new("A & B".into(), Resolution::default()),
+            vec![],
+            Style::new(vec![]).unwrap(),
+        );
+        let document = render(&presentation).unwrap();
+
+        assert!(document.contains("<title>A &amp; B</title>"));
+        assert!(document.contains("Reveal.initialize({ width: 800, height: 600,"));
+    }
+}
@@ -0,0 +1,461 @@
+use crate::presentation::{Font, FontDescriptor, FontSource};
+use crate::temp_dir::TempDir;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Finds the file backing the font, writing bundled ones out. Font files
+/// missing from their path and `system` fonts are looked up in the font
+/// directories first, then `system` fonts among the installed ones.
+pub fn resolve(font: &Font, font_dirs: &[String]) -> Result<PathBuf, String> {
+    match font.source() {
+        FontSource::File(path) => Ok(find_font_file(path, font_dirs)),
+        FontSource::System => find_named_font(font_dirs, font.descriptor())
+            .or_else(|| find_system_font(font.descriptor()))
+            .ok_or_else(|| format!("Could not find system font `{}`", font.name())),
+        FontSource::Bundled => extract_bundled_font(font.weight()),
+    }
+}
+
+/// The path when there is a file at it, otherwise a file of the same name in
+/// one of the font directories, falling back to the path
+fn find_font_file(path: &str, font_dirs: &[String]) -> PathBuf {
+    let path = PathBuf::from(path);
+    if path.is_file() {
+        return path;
+    }
+
+    path.file_name()
+        .and_then(|file_name| {
+            font_dirs
+                .iter()
+                .map(|directory| Path::new(directory).join(file_name))
+                .find(|candidate| candidate.is_file())
+        })
+        .unwrap_or(path)
+}
+
+/// Looks for a font file named after the descriptor in the directories,
+/// e.g. `DejaVuSans-BoldItalic.ttf`
+fn find_named_font<P: AsRef<Path>>(
+    directories: &[P],
+    descriptor: &FontDescriptor,
+) -> Option<PathBuf> {
+    let wanted = file_stem_for(descriptor);
+
+    directories
+        .iter()
+        .filter_map(|directory| fs::read_dir(directory).ok())
+        .flatten()
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .find(|path| {
+            path.file_stem()
+                .and_then(std::ffi::OsStr::to_str)
+                .is_some_and(|stem| normalize(stem) == wanted)
+        })
+}
+
+/// Fonts compiled into przntr with their weights and file names
+const BUNDLED_FONTS: [(u32, &str, &[u8]); 2] = [
+    (
+        400,
+        "DejaVuSans.ttf",
+        include_bytes!("../assets/fonts/DejaVuSans.ttf"),
+    ),
+    (
+        700,
+        "DejaVuSans-Bold.ttf",
+        include_bytes!("../assets/fonts/DejaVuSans-Bold.ttf"),
+    ),
+];
+
+/// Directory the bundled fonts are written to, made the first time one is
+/// needed so nothing else can have put files there
+static EXTRACTED: Mutex<Option<TempDir>> = Mutex::new(None);
+
+/// Writes the bundled font closest to the weight to a directory of this
+/// run's own, unless it already has, so it loads from a file like any other
+/// font
+fn extract_bundled_font(weight: u32) -> Result<PathBuf, String> {
+    let mut extracted = EXTRACTED
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+
+    extract_bundled_font_into(&mut extracted, weight)
+}
+
+/// Writes the bundled font into the directory, making it first when there
+/// is none yet
+fn extract_bundled_font_into(
+    extracted: &mut Option<TempDir>,
+    weight: u32,
+) -> Result<PathBuf, String> {
+    let (_, file_name, data) = BUNDLED_FONTS
+        .iter()
+        .min_by_key(|(bundled_weight, _, _)| (i64::from(*bundled_weight) - i64::from(weight)).abs())
+        .expect("there are bundled fonts");
+    let error = |e: std::io::Error| format!("Could not write the bundled font: {}", e);
+
+    let directory = match extracted.take() {
+        Some(directory) => directory,
+        None => TempDir::new("przntr-fonts").map_err(error)?,
+    };
+    let path = directory.path().join(file_name);
+    *extracted = Some(directory);
+
+    if !path.is_file() {
+        fs::write(&path, data).map_err(error)?;
+    }
+
+    Ok(path)
+}
+
+/// Removes the bundled fonts written out during the run when dropped, it is
+/// to be held for as long as presentations are shown or exported
+#[derive(Debug, Default)]
+pub struct ExtractedFonts;
+
+impl Drop for ExtractedFonts {
+    fn drop(&mut self) {
+        EXTRACTED
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .take();
+    }
+}
+
+/// Environment variable pointing at the emoji font to use
+const EMOJI_FONT_VARIABLE: &str = "PRZNTR_EMOJI_FONT";
+
+/// Where an emoji font bundled with przntr goes, relative to the executable
+const BUNDLED_EMOJI_FONT: &str = "fonts/emoji.ttf";
+
+/// Finds a color emoji font for emoji the presentation's fonts don't have:
+/// the one `PRZNTR_EMOJI_FONT` names, the bundled one or an installed one
+pub fn find_emoji_font() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os(EMOJI_FONT_VARIABLE) {
+        return Some(PathBuf::from(path));
+    }
+
+    let bundled = std::env::current_exe()
+        .ok()
+        .and_then(|executable| Some(executable.parent()?.join(BUNDLED_EMOJI_FONT)));
+    if let Some(bundled) = bundled.filter(|path| path.is_file()) {
+        return Some(bundled);
+    }
+
+    find_system_emoji_font()
+}
+
+/// Asks fontconfig for a color font, only taking it when it is an emoji
+/// font rather than whatever fontconfig falls back to
+#[cfg(all(unix, not(target_os = "macos")))]
+fn find_system_emoji_font() -> Option<PathBuf> {
+    let output = std::process::Command::new("fc-match")
+        .arg("--format=%{file}")
+        .arg("emoji:color=true")
+        .output()
+        .ok()?;
+    let path = PathBuf::from(String::from_utf8(output.stdout).ok()?);
+    let file_name = path.file_name()?.to_str()?.to_lowercase();
+
+    if output.status.success() && file_name.contains("emoji") {
+        Some(path)
+    } else {
+        None
+    }
+}
+
+/// Looks for the emoji fonts systems come with in the font directories
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+fn find_system_emoji_font() -> Option<PathBuf> {
+    const EMOJI_FONT_FILES: [&str; 3] = [
+        "Apple Color Emoji.ttc",
+        "seguiemj.ttf",
+        "NotoColorEmoji.ttf",
+    ];
+
+    system_font_directories()
+        .into_iter()
+        .flat_map(|directory| {
+            EMOJI_FONT_FILES
+                .iter()
+                .map(move |file| directory.join(file))
+        })
+        .find(|path| path.is_file())
+}
+
+/// Asks fontconfig for the best match of the descriptor, only taking it when
+/// it is of the family asked for rather than whatever fontconfig falls back to
+#[cfg(all(unix, not(target_os = "macos")))]
+fn find_system_font(descriptor: &FontDescriptor) -> Option<PathBuf> {
+    let output = std::process::Command::new("fc-match")
+        .arg("--format=%{family}\\n%{file}")
+        .arg(fontconfig_pattern(descriptor))
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let output = String::from_utf8(output.stdout).ok()?;
+    let (families, file) = output.split_once('\n')?;
+    // fontconfig lists the names of the family in every language it has
+    let matches = families
+        .split(',')
+        .any(|family| is_family(family, descriptor));
+
+    if matches && !file.is_empty() {
+        Some(PathBuf::from(file))
+    } else {
+        None
+    }
+}
+
+/// Asks DirectWrite for the installed font of the family closest to the
+/// descriptor's weight and style
+#[cfg(windows)]
+fn find_system_font(descriptor: &FontDescriptor) -> Option<PathBuf> {
+    use dwrote::{FontCollection, FontStretch, FontStyle, FontWeight};
+
+    let family = FontCollection::system()
+        .font_family_by_name(descriptor.name())
+        .ok()??;
+    let style = if descriptor.italic() {
+        FontStyle::Italic
+    } else {
+        FontStyle::Normal
+    };
+    let font = family
+        .first_matching_font(
+            FontWeight::from_u32(descriptor.weight()),
+            FontStretch::Normal,
+            style,
+        )
+        .ok()?;
+    let files = font.create_font_face().files().ok()?;
+
+    files.first()?.font_file_path().ok()
+}
+
+/// Asks Core Text for the installed font closest to the descriptor, only
+/// taking it when it is of the family asked for rather than a fallback
+#[cfg(target_os = "macos")]
+#[allow(unsafe_code)]
+fn find_system_font(descriptor: &FontDescriptor) -> Option<PathBuf> {
+    use objc2_core_foundation::{CFDictionary, CFString, CFURL};
+    use objc2_core_text::{
+        kCTFontFamilyNameAttribute, kCTFontURLAttribute, CTFontDescriptor, CTFontSymbolicTraits,
+    };
+
+    let mut traits = CTFontSymbolicTraits::empty();
+    if descriptor.weight() >= 600 {
+        traits |= CTFontSymbolicTraits::TraitBold;
+    }
+    if descriptor.italic() {
+        traits |= CTFontSymbolicTraits::TraitItalic;
+    }
+    let mask = CTFontSymbolicTraits::TraitBold | CTFontSymbolicTraits::TraitItalic;
+
+    // SAFETY: the attribute keys are constants Core Text defines, and every
+    // object handed over is a live, retained Core Foundation object
+    unsafe {
+        let name = CFString::from_str(descriptor.name());
+        let attributes = CFDictionary::<CFString, CFString>::from_slices(
+            &[kCTFontFamilyNameAttribute],
+            &[&*name],
+        );
+        let wanted = CTFontDescriptor::with_attributes(attributes.as_opaque())
+            .copy_with_symbolic_traits(traits, mask)?;
+        let found = wanted.matching_font_descriptor(None)?;
+
+        let family = found
+            .attribute(kCTFontFamilyNameAttribute)?
+            .downcast::<CFString>()
+            .ok()?;
+        if !is_family(&family.to_string(), descriptor) {
+            return None;
+        }
+
+        found
+            .attribute(kCTFontURLAttribute)?
+            .downcast::<CFURL>()
+            .ok()?
+            .to_file_path()
+    }
+}
+
+/// Looks for a font file named after the descriptor in the system font
+/// directories
+#[cfg(not(any(unix, windows)))]
+fn find_system_font(descriptor: &FontDescriptor) -> Option<PathBuf> {
+    find_named_font(&system_font_directories(), descriptor)
+}
+
+/// Whether the family a system font lookup came up with is the one asked for
+#[cfg(unix)]
+fn is_family(family: &str, descriptor: &FontDescriptor) -> bool {
+    normalize(family) == normalize(descriptor.name())
+}
+
+#[cfg(target_os = "macos")]
+fn system_font_directories() -> Vec<PathBuf> {
+    let mut directories = vec![
+        PathBuf::from("/System/Library/Fonts"),
+        PathBuf::from("/Library/Fonts"),
+    ];
+    if let Some(home) = std::env::var_os("HOME") {
+        directories.push(PathBuf::from(home).join("Library/Fonts"));
+    }
+
+    directories
+}
+
+#[cfg(windows)]
+fn system_font_directories() -> Vec<PathBuf> {
+    let windows = std::env::var_os("WINDIR").unwrap_or_else(|| "C:\\Windows".into());
+
+    vec![PathBuf::from(windows).join("Fonts")]
+}
+
+#[cfg(not(any(unix, windows)))]
+fn system_font_directories() -> Vec<PathBuf> {
+    vec![]
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn fontconfig_pattern(descriptor: &FontDescriptor) -> String {
+    format!(
+        "{}:weight={}:slant={}",
+        descriptor.name(),
+        fontconfig_weight(descriptor.weight()),
+        if descriptor.italic() { 100 } else { 0 }
+    )
+}
+
+/// Maps CSS-like weights (100-900) onto the fontconfig weight scale
+#[cfg(all(unix, not(target_os = "macos")))]
+fn fontconfig_weight(weight: u32) -> u32 {
+    match (weight + 50) / 100 {
+        0 | 1 => 0,
+        2 => 40,
+        3 => 50,
+        4 => 80,
+        5 => 100,
+        6 => 180,
+        7 => 200,
+        8 => 205,
+        _ => 210,
+    }
+}
+
+/// Expected file name without extension, e.g. `dejavusansbolditalic`
+pub fn file_stem_for(descriptor: &FontDescriptor) -> String {
+    let mut stem = normalize(descriptor.name());
+    if descriptor.weight() >= 600 {
+        stem.push_str("bold");
+    }
+    if descriptor.italic() {
+        stem.push_str("italic");
+    }
+
+    stem
+}
+
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect::<String>()
+        .replace("regular", "")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn resolves_font_files_to_their_path() {
+        let font = Font::new("some-font".into(), "/some/path".into(), 400, false);
+
+        assert_eq!(Ok(PathBuf::from("/some/path")), resolve(&font, &[]));
+    }
+
+    #[test]
+    pub fn looks_fonts_up_in_the_font_directories() {
+        let directory =
+            std::env::temp_dir().join(format!("przntr-font-dir-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let file = directory.join("Inter-Bold.ttf");
+        fs::write(&file, b"").unwrap();
+        let font_dirs = [directory.to_string_lossy().into_owned()];
+
+        let missing = Font::new(
+            "Inter".into(),
+            "elsewhere/Inter-Bold.ttf".into(),
+            700,
+            false,
+        );
+        assert_eq!(Ok(file.clone()), resolve(&missing, &font_dirs));
+        let system = Font::system("Inter".into(), 700, false);
+        assert_eq!(Ok(file), resolve(&system, &font_dirs));
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    pub fn writes_bundled_fonts_out() {
+        let path = resolve(&Font::bundled(800), &[]).unwrap();
+
+        assert_eq!(Some("DejaVuSans-Bold.ttf".as_ref()), path.file_name());
+        assert_eq!(BUNDLED_FONTS[1].2, &fs::read(path).unwrap()[..]);
+    }
+
+    #[test]
+    pub fn writes_bundled_fonts_into_one_directory_until_it_is_dropped() {
+        let mut extracted = None;
+        let bold = extract_bundled_font_into(&mut extracted, 700).unwrap();
+        let regular = extract_bundled_font_into(&mut extracted, 400).unwrap();
+
+        assert_eq!(bold.parent(), regular.parent());
+        assert_ne!(
+            bold,
+            extract_bundled_font_into(&mut None, 700).unwrap(),
+            "each run has a directory of its own"
+        );
+
+        drop(extracted);
+        assert!(!bold.exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    pub fn takes_system_fonts_of_the_family_asked_for() {
+        let font = Font::system("DejaVu Sans".into(), 700, true);
+
+        assert!(is_family("DejaVu Sans", font.descriptor()));
+        assert!(is_family("dejavusans", font.descriptor()));
+        assert!(!is_family("Noto Sans", font.descriptor()));
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    #[test]
+    pub fn maps_weights_to_fontconfig_scale() {
+        assert_eq!(0, fontconfig_weight(100));
+        assert_eq!(80, fontconfig_weight(400));
+        assert_eq!(200, fontconfig_weight(700));
+        assert_eq!(210, fontconfig_weight(950));
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    #[test]
+    pub fn builds_fontconfig_pattern() {
+        let font = Font::system("DejaVu Sans".into(), 700, true);
+
+        assert_eq!(
+            "DejaVu Sans:weight=200:slant=100",
+            fontconfig_pattern(font.descriptor())
+        );
+    }
+}
@@ -0,0 +1,277 @@
+use crate::presentation::{Color, Length, Presentation, Span};
+use crate::zip::ZipArchive;
+use roxmltree::{Document, Node};
+use std::fs;
+use std::path::Path;
+
+pub mod odp;
+pub mod pptx;
+
+/// Images taken out of a document, by the path slides refer to them with
+pub type Media = Vec<(String, Vec<u8>)>;
+
+/// Reads a PPTX or ODP presentation into
+/// the presentation model. Its images are written into a directory named
+/// after the file, next to it, and slides refer to them relative to the
+/// file's directory.
+pub fn import(path: &Path) -> Result<Presentation, String> {
+    let archive = ZipArchive::new(fs::read(path).map_err(|e| format!("{:?}", e))?)?;
+    let stem = path
+        .file_stem()
+        .map_or_else(|| "presentation".into(), |stem| stem.to_string_lossy());
+    let media_directory = format!("{}-media", stem);
+
+    let (presentation, media) = match path.extension().and_then(std::ffi::OsStr::to_str) {
+        Some("pptx") => pptx::read(&archive, &stem, &media_directory)?,
+        Some("odp") => odp::read(&archive, &stem, &media_directory)?,
+        _ => return Err("Only .pptx and .odp files can be imported".into()),
+    };
+
+    let directory = path.parent().unwrap_or_else(|| Path::new(""));
+    if !media.is_empty() {
+        fs::create_dir_all(directory.join(&media_directory)).map_err(|e| format!("{:?}", e))?;
+    }
+    for (media_path, data) in media {
+        fs::write(directory.join(media_path), data).map_err(|e| format!("{:?}", e))?;
+    }
+
+    Ok(presentation)
+}
+
+/// Builds the spans of a text run by run, runs formatted alike become one
+/// span
+#[derive(Default)]
+struct SpanBuilder {
+    spans: Vec<Span>,
+}
+
+impl SpanBuilder {
+    fn push(&mut self, text: &str, bold: bool, italic: bool, color: Option<Color>) {
+        if text.is_empty() {
+            return;
+        }
+        if let Some(last) = self.spans.last_mut() {
+            if last.bold() == bold && last.italic() == italic && last.color() == color {
+                let mut merged = Span::new(format!("{}{}", last.content(), text));
+                merged.set_bold(bold);
+                merged.set_italic(italic);
+                if let Some(color) = color {
+                    merged.set_color(color);
+                }
+                *last = merged;
+                return;
+            }
+        }
+
+        let mut span = Span::new(text.into());
+        span.set_bold(bold);
+        span.set_italic(italic);
+        if let Some(color) = color {
+            span.set_color(color);
+        }
+        self.spans.push(span);
+    }
+
+    /// Starts a new line, unless the text is still empty
+    fn line_break(&mut self) {
+        if let Some(last) = self.spans.last() {
+            let (bold, italic, color) = (last.bold(), last.italic(), last.color());
+            self.push("\n", bold, italic, color);
+        }
+    }
+
+    fn is_blank(&self) -> bool {
+        self.spans
+            .iter()
+            .all(|span| span.content().trim().is_empty())
+    }
+
+    /// The spans without the line breaks the text ends with
+    fn finish(mut self) -> Vec<Span> {
+        while let Some(last) = self.spans.last_mut() {
+            let trimmed = last.content().trim_end_matches('\n');
+            if !trimmed.is_empty() {
+                if trimmed.len() < last.content().len() {
+                    let mut span = Span::new(trimmed.into());
+                    span.set_bold(last.bold());
+                    span.set_italic(last.italic());
+                    if let Some(color) = last.color() {
+                        span.set_color(color);
+                    }
+                    *last = span;
+                }
+                break;
+            }
+            self.spans.pop();
+        }
+
+        self.spans
+    }
+}
+
+/// Rounds to whole pixels, which is what slides are laid out in
+fn pixels(value: f64) -> Length {
+    Length::Pixels(value.round())
+}
+
+/// Pixels in a length written with a unit like `2.5cm` or `18pt`, with 96
+/// pixels to the inch
+fn parse_length(value: &str) -> Option<f64> {
+    let split_at = value.find(|character: char| character.is_ascii_alphabetic())?;
+    let (number, unit) = value.split_at(split_at);
+    let number: f64 = number.parse().ok()?;
+
+    let per_unit = match unit {
+        "px" => 1.0,
+        "in" => 96.0,
+        "cm" => 96.0 / 2.54,
+        "mm" => 96.0 / 25.4,
+        "pt" => 96.0 / 72.0,
+        "pc" => 16.0,
+        _ => return None,
+    };
+
+    Some(number * per_unit)
+}
+
+/// Resolves a path relative to the directory of a file in an archive, like
+/// `../media/image1.png` from `ppt/slides/slide1.xml`
+fn resolve(base: &str, target: &str) -> String {
+    if let Some(absolute) = target.strip_prefix('/') {
+        return absolute.into();
+    }
+
+    let mut parts: Vec<&str> = base.split('/').collect();
+    // the file itself
+    parts.pop();
+    for part in target.split('/') {
+        match part {
+            ".." => {
+                parts.pop();
+            }
+            "." | "" => {}
+            _ => parts.push(part),
+        }
+    }
+
+    parts.join("/")
+}
+
+/// Name an image gets in the media directory, keeping its extension
+fn media_name(media_directory: &str, index: usize, archived: &str) -> String {
+    let extension = Path::new(archived)
+        .extension()
+        .and_then(std::ffi::OsStr::to_str)
+        .unwrap_or("png")
+        .to_lowercase();
+
+    format!("{}/image{}.{}", media_directory, index, extension)
+}
+
+/// Takes an image out of the archive once, however many slides show it
+fn take_media(
+    archive: &ZipArchive,
+    archived: &str,
+    media_directory: &str,
+    media: &mut Media,
+    taken: &mut Vec<(String, String)>,
+) -> Result<String, String> {
+    if let Some((_, path)) = taken.iter().find(|(known, _)| known == archived) {
+        return Ok(path.clone());
+    }
+
+    let path = media_name(media_directory, taken.len() + 1, archived);
+    media.push((path.clone(), archive.file(archived)?));
+    taken.push((archived.into(), path.clone()));
+
+    Ok(path)
+}
+
+/// Parses a file of the archive, naming it in the error
+fn parse<'a>(source: &'a str, path: &str) -> Result<Document<'a>, String> {
+    Document::parse(source).map_err(|error| format!("`{}` is not valid XML: {}", path, error))
+}
+
+/// The first child with the name
+fn child<'a, 'input>(
+    node: Node<'a, 'input>,
+    namespace: &str,
+    name: &str,
+) -> Option<Node<'a, 'input>> {
+    node.children()
+        .find(|child| child.has_tag_name((namespace, name)))
+}
+
+/// The children with the name
+fn children<'a, 'input>(
+    node: Node<'a, 'input>,
+    namespace: &str,
+    name: &str,
+) -> Vec<Node<'a, 'input>> {
+    node.children()
+        .filter(|child| child.has_tag_name((namespace, name)))
+        .collect()
+}
+
+/// The first node with the name within the node, the node itself included
+fn descendant<'a, 'input>(
+    node: Node<'a, 'input>,
+    namespace: &str,
+    name: &str,
+) -> Option<Node<'a, 'input>> {
+    node.descendants()
+        .find(|child| child.has_tag_name((namespace, name)))
+}
+
+/// The nodes with the name within the node, the node itself included
+fn descendants<'a, 'input>(
+    node: Node<'a, 'input>,
+    namespace: &str,
+    name: &str,
+) -> Vec<Node<'a, 'input>> {
+    node.descendants()
+        .filter(|child| child.has_tag_name((namespace, name)))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn converts_lengths_to_pixels() {
+        assert_eq!(Some(96.0), parse_length("1in"));
+        assert_eq!(Some(24.0), parse_length("18pt"));
+        assert_eq!(Some(96.0), parse_length("2.54cm"));
+        assert_eq!(None, parse_length("12"));
+    }
+
+    #[test]
+    pub fn resolves_paths_within_archives() {
+        assert_eq!(
+            "ppt/media/image1.png",
+            resolve("ppt/slides/slide1.xml", "../media/image1.png")
+        );
+        assert_eq!(
+            "ppt/slides/slide2.xml",
+            resolve("ppt/presentation.xml", "slides/slide2.xml")
+        );
+        assert_eq!("ppt/a.xml", resolve("ppt/slides/s.xml", "/ppt/a.xml"));
+    }
+
+    #[test]
+    pub fn merges_runs_formatted_alike() {
+        let mut builder = SpanBuilder::default();
+        builder.push("a", false, false, None);
+        builder.push("b", false, false, None);
+        builder.line_break();
+        builder.push("c", true, false, None);
+        builder.line_break();
+
+        let spans = builder.finish();
+
+        assert_eq!(2, spans.len());
+        assert_eq!("ab\n", spans[0].content());
+        assert_eq!("c", spans[1].content());
+    }
+}
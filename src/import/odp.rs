@@ -0,0 +1,484 @@
+use super::{
+    child, children, descendant, descendants, parse, parse_length, pixels, take_media, Media,
+    SpanBuilder,
+};
+use crate::presentation::{
+    Color, Fill, Image, Length, List, Metadata, Placement, Presentation, Resolution, Slide,
+    SlideElement, Style, Text,
+};
+use crate::zip::ZipArchive;
+use roxmltree::{Document, Node};
+use std::collections::HashMap;
+
+const DRAWING: &str = "urn:oasis:names:tc:opendocument:xmlns:drawing:1.0";
+const PRESENTATION: &str = "urn:oasis:names:tc:opendocument:xmlns:presentation:1.0";
+const OFFICE: &str = "urn:oasis:names:tc:opendocument:xmlns:office:1.0";
+const STYLE: &str = "urn:oasis:names:tc:opendocument:xmlns:style:1.0";
+const TEXT: &str = "urn:oasis:names:tc:opendocument:xmlns:text:1.0";
+const SVG: &str = "urn:oasis:names:tc:opendocument:xmlns:svg-compatible:1.0";
+const FORMATTING: &str = "urn:oasis:names:tc:opendocument:xmlns:xsl-fo-compatible:1.0";
+const LINK: &str = "http://www.w3.org/1999/xlink";
+const DUBLIN_CORE: &str = "http://purl.org/dc/elements/1.1/";
+
+/// Formatting a text style gives, unset where it leaves it to the
+/// enclosing one
+#[derive(Default, Copy, Clone)]
+struct RunStyle {
+    bold: Option<bool>,
+    italic: Option<bool>,
+    color: Option<Color>,
+    size: Option<f64>,
+}
+
+impl RunStyle {
+    /// This style, with what it leaves unset taken from the enclosing one
+    fn within(self, outer: Self) -> Self {
+        Self {
+            bold: self.bold.or(outer.bold),
+            italic: self.italic.or(outer.italic),
+            color: self.color.or(outer.color),
+            size: self.size.or(outer.size),
+        }
+    }
+}
+
+/// The state of reading the pages of a document
+struct PageReader<'a> {
+    archive: &'a ZipArchive,
+    text_styles: HashMap<String, RunStyle>,
+    page_fills: HashMap<String, Color>,
+    media_directory: &'a str,
+    media: Media,
+    taken: Vec<(String, String)>,
+}
+
+/// Reads the pages of an ODP presentation, with their text,
+/// pictures, background colors and notes. Text keeps its emphasis, color
+/// and size, and frames keep their position. Anything else, like tables,
+/// charts or animations, is left out.
+pub fn read(
+    archive: &ZipArchive,
+    fallback_title: &str,
+    media_directory: &str,
+) -> Result<(Presentation, Media), String> {
+    let source = archive.text("content.xml")?;
+    let document = parse(&source, "content.xml")?;
+
+    let resolution = archive
+        .text("styles.xml")
+        .ok()
+        .and_then(|source| {
+            let document = Document::parse(&source).ok()?;
+            let properties = descendant(document.root(), STYLE, "page-layout-properties")?;
+            let dimension = |name: &str| {
+                properties
+                    .attribute((FORMATTING, name))
+                    .and_then(parse_length)
+                    .map(|pixels| pixels.round() as u32)
+            };
+            Some(Resolution::new(
+                dimension("page-width")?,
+                dimension("page-height")?,
+            ))
+        })
+        .unwrap_or_else(|| Resolution::new(1280, 720));
+
+    let title = archive
+        .text("meta.xml")
+        .ok()
+        .and_then(|source| {
+            let document = Document::parse(&source).ok()?;
+            let title = descendant(document.root(), DUBLIN_CORE, "title")?
+                .text()?
+                .trim();
+            Some(title.to_string()).filter(|title| !title.is_empty())
+        })
+        .unwrap_or_else(|| fallback_title.into());
+
+    let mut reader = PageReader {
+        archive,
+        text_styles: HashMap::new(),
+        page_fills: HashMap::new(),
+        media_directory,
+        media: vec![],
+        taken: vec![],
+    };
+    if let Some(styles) = descendant(document.root(), OFFICE, "automatic-styles") {
+        reader.read_styles(styles);
+    }
+
+    let mut slides = vec![];
+    let pages = descendant(document.root(), OFFICE, "presentation")
+        .map(|presentation| children(presentation, DRAWING, "page"))
+        .unwrap_or_default();
+    for (index, page) in pages.into_iter().enumerate() {
+        slides.push(reader.read_page(page, index)?);
+    }
+
+    Ok((
+        Presentation::new(Metadata::new(title, resolution), slides, Style::empty()),
+        reader.media,
+    ))
+}
+
+impl PageReader<'_> {
+    /// Takes the text formatting and the page backgrounds out of the
+    /// document's automatic styles
+    fn read_styles(&mut self, styles: Node) {
+        for style in children(styles, STYLE, "style") {
+            let name = match style.attribute((STYLE, "name")) {
+                Some(name) => name.to_string(),
+                None => continue,
+            };
+
+            if let Some(properties) = child(style, STYLE, "text-properties") {
+                let attribute = |name: &str| properties.attribute((FORMATTING, name));
+                self.text_styles.insert(
+                    name.clone(),
+                    RunStyle {
+                        bold: attribute("font-weight").map(|weight| weight == "bold"),
+                        italic: attribute("font-style").map(|style| style == "italic"),
+                        color: attribute("color").and_then(Color::from_hex),
+                        size: attribute("font-size").and_then(parse_length),
+                    },
+                );
+            }
+
+            let fill = child(style, STYLE, "drawing-page-properties")
+                .filter(|properties| properties.attribute((DRAWING, "fill")) == Some("solid"))
+                .and_then(|properties| properties.attribute((DRAWING, "fill-color")))
+                .and_then(Color::from_hex);
+            if let Some(fill) = fill {
+                self.page_fills.insert(name, fill);
+            }
+        }
+    }
+
+    fn read_page(&mut self, page: Node, index: usize) -> Result<Slide, String> {
+        let name = page
+            .attribute((DRAWING, "name"))
+            .filter(|name| !name.is_empty())
+            .map_or_else(|| format!("Slide {}", index + 1), String::from);
+        let mut slide = Slide::new(name, vec![]);
+
+        if let Some(fill) = page
+            .attribute((DRAWING, "style-name"))
+            .and_then(|style| self.page_fills.get(style))
+        {
+            slide.set_background(Fill::Color(*fill));
+        }
+        for element in self.shapes(page)? {
+            slide.push_element(element);
+        }
+        if let Some(notes) = child(page, PRESENTATION, "notes") {
+            for frame in descendants(notes, DRAWING, "frame") {
+                if frame.attribute((PRESENTATION, "class")) != Some("notes") {
+                    continue;
+                }
+                let note = self.paragraphs(frame).join("\n");
+                if !note.trim().is_empty() {
+                    slide.add_note(note);
+                }
+            }
+        }
+
+        Ok(slide)
+    }
+
+    /// Elements made of the frames and shapes on the page, groups included.
+    /// Positions within groups are given on the page already.
+    fn shapes(&mut self, parent: Node) -> Result<Vec<SlideElement>, String> {
+        let mut elements = vec![];
+
+        for shape in parent.children().filter(Node::is_element) {
+            if shape.has_tag_name((DRAWING, "g")) {
+                elements.extend(self.shapes(shape)?);
+            } else if shape.has_tag_name((DRAWING, "frame")) {
+                elements.extend(self.frame(shape)?);
+            } else if shape.tag_name().namespace() == Some(DRAWING) {
+                elements.extend(self.text(shape, None));
+            }
+        }
+
+        Ok(elements)
+    }
+
+    fn frame(&mut self, frame: Node) -> Result<Option<SlideElement>, String> {
+        if let Some(image) = child(frame, DRAWING, "image") {
+            let href = match image.attribute((LINK, "href")) {
+                Some(href) if self.archive.contains(href) => href,
+                _ => return Ok(None),
+            };
+            let path = take_media(
+                self.archive,
+                href,
+                self.media_directory,
+                &mut self.media,
+                &mut self.taken,
+            )?;
+            let mut image = SlideElement::Image(Image::new(path));
+            image.set_placement(placement(frame));
+
+            return Ok(Some(image));
+        }
+
+        Ok(child(frame, DRAWING, "text-box")
+            .and_then(|text_box| self.text(text_box, frame.attribute((PRESENTATION, "class"))))
+            .map(|mut element| {
+                element.set_placement(placement(frame));
+                element
+            }))
+    }
+
+    /// The text in the node as a title, a subtitle, a list or a text,
+    /// depending on the class of the frame it is in and whether it is a
+    /// list
+    fn text(&self, node: Node, class: Option<&str>) -> Option<SlideElement> {
+        if class == Some("outline") || child(node, TEXT, "list").is_some() {
+            let items = self
+                .paragraphs(node)
+                .into_iter()
+                .filter(|item| !item.trim().is_empty())
+                .collect::<Vec<String>>();
+            if items.is_empty() {
+                return None;
+            }
+            let mut list = SlideElement::List(List::new(items, "•".into(), Length::Pixels(40.0)));
+            list.set_placement(placement(node));
+
+            return Some(list);
+        }
+
+        let mut builder = SpanBuilder::default();
+        let mut size = None;
+        for paragraph in descendants(node, TEXT, "p") {
+            builder.line_break();
+            let style = self.style_of(paragraph);
+            self.runs(paragraph, style, &mut builder, &mut size);
+        }
+        if builder.is_blank() {
+            return None;
+        }
+
+        let mut text = Text::with_spans(builder.finish(), None);
+        if let Some(size) = size {
+            text.set_size(size.round() as u32);
+        }
+        let mut element = match class {
+            Some("title") => SlideElement::Title(text),
+            Some("subtitle") => SlideElement::Subtitle(text),
+            _ => SlideElement::Text(text),
+        };
+        element.set_placement(placement(node));
+
+        Some(element)
+    }
+
+    /// Pushes the text within the node, in the formatting its spans give it.
+    /// Keeps the first font size it comes across.
+    fn runs(&self, node: Node, style: RunStyle, builder: &mut SpanBuilder, size: &mut Option<f64>) {
+        for piece in node.children() {
+            if piece.is_text() {
+                *size = size.or(style.size);
+                builder.push(
+                    piece.text().unwrap_or(""),
+                    style.bold.unwrap_or(false),
+                    style.italic.unwrap_or(false),
+                    style.color,
+                );
+                continue;
+            }
+
+            let text = match piece.tag_name().name() {
+                "line-break" => "\n".to_string(),
+                "tab" => "\t".into(),
+                "s" => " ".repeat(
+                    piece
+                        .attribute((TEXT, "c"))
+                        .and_then(|count| count.parse().ok())
+                        .unwrap_or(1),
+                ),
+                _ => {
+                    self.runs(piece, self.style_of(piece).within(style), builder, size);
+                    continue;
+                }
+            };
+            builder.push(
+                &text,
+                style.bold.unwrap_or(false),
+                style.italic.unwrap_or(false),
+                style.color,
+            );
+        }
+    }
+
+    /// The plain text of each paragraph in the node
+    fn paragraphs(&self, node: Node) -> Vec<String> {
+        descendants(node, TEXT, "p")
+            .into_iter()
+            .map(|paragraph| {
+                let mut builder = SpanBuilder::default();
+                self.runs(paragraph, RunStyle::default(), &mut builder, &mut None);
+                builder
+                    .finish()
+                    .iter()
+                    .map(|span| span.content().as_str())
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn style_of(&self, node: Node) -> RunStyle {
+        node.attribute((TEXT, "style-name"))
+            .and_then(|name| self.text_styles.get(name))
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+/// Where the node's `svg` attributes put it, nothing for nodes without
+/// them
+fn placement(node: Node) -> Placement {
+    let mut placement = Placement::default();
+    let attribute = |name: &str| {
+        node.attribute((SVG, name))
+            .and_then(parse_length)
+            .map(pixels)
+    };
+
+    if let (Some(x), Some(y)) = (attribute("x"), attribute("y")) {
+        placement.set_position(x, y);
+    }
+    if let (Some(width), Some(height)) = (attribute("width"), attribute("height")) {
+        placement.set_size(Some(width), Some(height));
+    }
+
+    placement
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::zip::ZipWriter;
+
+    fn archive(content: &str) -> ZipArchive {
+        let mut writer = ZipWriter::new(vec![]);
+        writer
+            .add_file(
+                "styles.xml",
+                format!(
+                    "<office:document-styles xmlns:office=\"{}\" xmlns:style=\"{}\" xmlns:fo=\"{}\">\
+                     <style:page-layout style:name=\"PM1\">\
+                     <style:page-layout-properties fo:page-width=\"28cm\" fo:page-height=\"15.75cm\"/>\
+                     </style:page-layout></office:document-styles>",
+                    OFFICE, STYLE, FORMATTING
+                )
+                .as_bytes(),
+            )
+            .unwrap();
+        writer.add_file("content.xml", content.as_bytes()).unwrap();
+        writer.add_file("Pictures/cat.png", b"png").unwrap();
+
+        ZipArchive::new(writer.finish().unwrap()).unwrap()
+    }
+
+    fn content(automatic_styles: &str, pages: &str) -> String {
+        format!(
+            "<office:document-content xmlns:office=\"{}\" xmlns:style=\"{}\" xmlns:fo=\"{}\" \
+             xmlns:draw=\"{}\" xmlns:presentation=\"{}\" xmlns:text=\"{}\" xmlns:svg=\"{}\" \
+             xmlns:xlink=\"{}\"><office:automatic-styles>{}</office:automatic-styles>\
+             <office:body><office:presentation>{}</office:presentation></office:body>\
+             </office:document-content>",
+            OFFICE,
+            STYLE,
+            FORMATTING,
+            DRAWING,
+            PRESENTATION,
+            TEXT,
+            SVG,
+            LINK,
+            automatic_styles,
+            pages
+        )
+    }
+
+    #[test]
+    pub fn reads_pages_with_their_text_and_pictures() {
+        let archive = archive(&content(
+            "<style:style style:name=\"dp1\" style:family=\"drawing-page\">\
+             <style:drawing-page-properties draw:fill=\"solid\" draw:fill-color=\"#102030\"/>\
+             </style:style>\
+             <style:style style:name=\"T1\" style:family=\"text\">\
+             <style:text-properties fo:font-weight=\"bold\" fo:font-size=\"30pt\"/></style:style>",
+            "<draw:page draw:name=\"Intro\" draw:style-name=\"dp1\">\
+             <draw:frame presentation:class=\"title\" svg:x=\"1in\" svg:y=\"0.5in\" \
+             svg:width=\"5in\" svg:height=\"1in\"><draw:text-box><text:p>Hello \
+             <text:span text:style-name=\"T1\">world</text:span></text:p></draw:text-box></draw:frame>\
+             <draw:frame presentation:class=\"outline\"><draw:text-box><text:list>\
+             <text:list-item><text:p>one</text:p></text:list-item>\
+             <text:list-item><text:p>two<text:s text:c=\"2\"/>three</text:p></text:list-item>\
+             </text:list></draw:text-box></draw:frame>\
+             <draw:frame svg:x=\"0cm\" svg:y=\"0cm\" svg:width=\"1in\" svg:height=\"1in\">\
+             <draw:image xlink:href=\"Pictures/cat.png\"/></draw:frame>\
+             <presentation:notes><draw:frame presentation:class=\"notes\"><draw:text-box>\
+             <text:p>Say hi</text:p></draw:text-box></draw:frame></presentation:notes>\
+             </draw:page>",
+        ));
+
+        let (presentation, media) = read(&archive, "deck", "deck-media").unwrap();
+
+        assert_eq!("deck", presentation.metadata().title());
+        assert_eq!(
+            Resolution::new(1058, 595),
+            presentation.metadata().resolution()
+        );
+        let slide = &presentation.slides()[0];
+        assert_eq!("Intro", slide.name());
+        assert_eq!(
+            Some(&Fill::Color(Color::new(0x10, 0x20, 0x30, 0xff))),
+            slide.background()
+        );
+        assert_eq!(vec!["Say hi".to_string()], *slide.notes());
+
+        match &slide.elements()[0] {
+            SlideElement::Title(title) => {
+                assert_eq!("Hello world", title.content());
+                assert!(title.spans()[1].bold());
+                assert_eq!(Some(40), title.size());
+                assert_eq!(
+                    Some((Length::Pixels(96.0), Length::Pixels(48.0))),
+                    title.placement().position()
+                );
+            }
+            other => panic!("Expected a title, got {:?}", other),
+        }
+        match &slide.elements()[1] {
+            SlideElement::List(list) => {
+                assert_eq!(&vec!["one".to_string(), "two  three".into()], list.items());
+            }
+            other => panic!("Expected a list, got {:?}", other),
+        }
+        match &slide.elements()[2] {
+            SlideElement::Image(image) => assert_eq!("deck-media/image1.png", image.path()),
+            other => panic!("Expected an image, got {:?}", other),
+        }
+        assert_eq!(
+            vec![("deck-media/image1.png".to_string(), b"png".to_vec())],
+            media
+        );
+    }
+
+    #[test]
+    pub fn skips_empty_frames() {
+        let archive = archive(&content(
+            "",
+            "<draw:page><draw:frame presentation:class=\"subtitle\"><draw:text-box>\
+             <text:p/></draw:text-box></draw:frame></draw:page>",
+        ));
+
+        let (presentation, _) = read(&archive, "deck", "deck-media").unwrap();
+
+        assert_eq!("Slide 1", presentation.slides()[0].name());
+        assert!(presentation.slides()[0].elements().is_empty());
+    }
+}
@@ -0,0 +1,673 @@
+use super::{
+    child, children, descendant, descendants, parse, pixels, resolve, take_media, Media,
+    SpanBuilder,
+};
+use crate::presentation::{
+    Color, Fill, GradientDirection, Image, ImageFit, Length, List, Metadata, Placement,
+    Presentation, Resolution, Slide, SlideElement, Span, Style, Text,
+};
+use crate::zip::ZipArchive;
+use roxmltree::{Document, Node};
+use std::collections::HashMap;
+
+const DRAWING: &str = "http://schemas.openxmlformats.org/drawingml/2006/main";
+const PRESENTATION: &str = "http://schemas.openxmlformats.org/presentationml/2006/main";
+const RELATIONSHIPS: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+const PACKAGE_RELATIONSHIPS: &str = "http://schemas.openxmlformats.org/package/2006/relationships";
+const DUBLIN_CORE: &str = "http://purl.org/dc/elements/1.1/";
+/// English Metric Units per pixel, at 96 pixels to the inch
+const EMU_PER_PIXEL: f64 = 9525.0;
+/// Hundredths of a point per pixel
+const FONT_SIZE_PER_PIXEL: f64 = 75.0;
+
+/// How a group maps the positions of its children onto the slide, as the
+/// offset and the scale along each axis
+type Transform = ((f64, f64), (f64, f64));
+
+const IDENTITY: Transform = ((0.0, 0.0), (1.0, 1.0));
+
+/// The state of reading one slide
+struct SlideReader<'a> {
+    archive: &'a ZipArchive,
+    relationships: HashMap<String, String>,
+    media_directory: &'a str,
+    media: &'a mut Media,
+    taken: &'a mut Vec<(String, String)>,
+}
+
+/// Reads the slides of a PPTX file, with their text, pictures,
+/// backgrounds and notes. Text keeps its emphasis, color and size, and
+/// shapes with their own position keep it. Anything else, like tables,
+/// charts or animations, is left out.
+pub fn read(
+    archive: &ZipArchive,
+    fallback_title: &str,
+    media_directory: &str,
+) -> Result<(Presentation, Media), String> {
+    let root = relationships(archive, "")?;
+    let main = root
+        .iter()
+        .find(|(_, target)| target.ends_with("presentation.xml") && archive.contains(target))
+        .map_or_else(
+            || "ppt/presentation.xml".into(),
+            |(_, target)| target.clone(),
+        );
+    let main_source = archive.text(&main)?;
+    let document = parse(&main_source, &main)?;
+    let main_relationships = relationships(archive, &main)?;
+
+    let size = descendant(document.root(), PRESENTATION, "sldSz");
+    let dimension = |name: &str| {
+        size.and_then(|size| size.attribute(name))
+            .and_then(|value| value.parse::<f64>().ok())
+            .map(|emu| (emu / EMU_PER_PIXEL).round() as u32)
+    };
+    let resolution = Resolution::new(
+        dimension("cx").unwrap_or(1280),
+        dimension("cy").unwrap_or(720),
+    );
+
+    let title = archive
+        .text("docProps/core.xml")
+        .ok()
+        .and_then(|source| {
+            let document = Document::parse(&source).ok()?;
+            let title = descendant(document.root(), DUBLIN_CORE, "title")?
+                .text()?
+                .trim();
+            Some(title.to_string()).filter(|title| !title.is_empty())
+        })
+        .unwrap_or_else(|| fallback_title.into());
+
+    let mut media = vec![];
+    let mut taken = vec![];
+    let mut slides = vec![];
+    let slide_ids = descendant(document.root(), PRESENTATION, "sldIdLst")
+        .map(|list| children(list, PRESENTATION, "sldId"))
+        .unwrap_or_default();
+    for (index, slide_id) in slide_ids.into_iter().enumerate() {
+        let path = slide_id
+            .attribute((RELATIONSHIPS, "id"))
+            .and_then(|id| main_relationships.get(id))
+            .ok_or_else(|| format!("Slide {} is missing from the file", index + 1))?;
+        let mut reader = SlideReader {
+            archive,
+            relationships: relationships(archive, path)?,
+            media_directory,
+            media: &mut media,
+            taken: &mut taken,
+        };
+        slides.push(reader.read(path, index)?);
+    }
+
+    Ok((
+        Presentation::new(Metadata::new(title, resolution), slides, Style::empty()),
+        media,
+    ))
+}
+
+impl SlideReader<'_> {
+    fn read(&mut self, path: &str, index: usize) -> Result<Slide, String> {
+        let source = self.archive.text(path)?;
+        let document = parse(&source, path)?;
+        let common = descendant(document.root(), PRESENTATION, "cSld")
+            .ok_or_else(|| format!("`{}` has no slide in it", path))?;
+
+        let name = common
+            .attribute("name")
+            .filter(|name| !name.is_empty())
+            .map_or_else(|| format!("Slide {}", index + 1), String::from);
+        let mut slide = Slide::new(name, vec![]);
+
+        if let Some(properties) = child(common, PRESENTATION, "bg")
+            .and_then(|background| child(background, PRESENTATION, "bgPr"))
+        {
+            if let Some(fill) = self.fill(properties)? {
+                slide.set_background(fill);
+            }
+        }
+        if let Some(tree) = child(common, PRESENTATION, "spTree") {
+            for element in self.shapes(tree, IDENTITY)? {
+                slide.push_element(element);
+            }
+        }
+        for note in self.notes()? {
+            slide.add_note(note);
+        }
+
+        Ok(slide)
+    }
+
+    /// Elements made of the shapes in the tree, groups included
+    fn shapes(&mut self, tree: Node, transform: Transform) -> Result<Vec<SlideElement>, String> {
+        let mut elements = vec![];
+
+        for shape in tree.children().filter(Node::is_element) {
+            match shape.tag_name().name() {
+                "sp" => elements.extend(text_shape(shape, transform)),
+                "pic" => elements.extend(self.picture(shape, transform)?),
+                "grpSp" => {
+                    let group = child(shape, PRESENTATION, "grpSpPr")
+                        .and_then(|properties| child(properties, DRAWING, "xfrm"))
+                        .map_or(transform, |frame| {
+                            compose(transform, group_transform(frame))
+                        });
+                    elements.extend(self.shapes(shape, group)?);
+                }
+                _ => {}
+            }
+        }
+
+        Ok(elements)
+    }
+
+    fn picture(
+        &mut self,
+        picture: Node,
+        transform: Transform,
+    ) -> Result<Option<SlideElement>, String> {
+        let target = match descendant(picture, DRAWING, "blip")
+            .and_then(|blip| blip.attribute((RELATIONSHIPS, "embed")))
+            .and_then(|id| self.relationships.get(id))
+        {
+            Some(target) => target.clone(),
+            None => return Ok(None),
+        };
+
+        let mut image = SlideElement::Image(Image::new(self.media_path(&target)?));
+        image.set_placement(placement(picture, transform));
+
+        Ok(Some(image))
+    }
+
+    /// A color, a gradient or an image, from the fill in the properties
+    fn fill(&mut self, properties: Node) -> Result<Option<Fill>, String> {
+        if let Some(solid) = child(properties, DRAWING, "solidFill") {
+            return Ok(color(solid).map(Fill::Color));
+        }
+        if let Some(gradient) = child(properties, DRAWING, "gradFill") {
+            let stops = descendants(gradient, DRAWING, "gs");
+            let from = stops.first().and_then(|stop| color(*stop));
+            let to = stops.last().and_then(|stop| color(*stop));
+            let direction =
+                match child(gradient, DRAWING, "lin").and_then(|line| line.attribute("ang")) {
+                    Some("0") => GradientDirection::Horizontal,
+                    Some("2700000") => GradientDirection::Diagonal,
+                    Some(_) => GradientDirection::Vertical,
+                    None => GradientDirection::Radial,
+                };
+
+            return Ok(from.zip(to).map(|(from, to)| Fill::Gradient {
+                from,
+                to,
+                direction,
+            }));
+        }
+        if let Some(target) = child(properties, DRAWING, "blipFill")
+            .and_then(|fill| child(fill, DRAWING, "blip"))
+            .and_then(|blip| blip.attribute((RELATIONSHIPS, "embed")))
+            .and_then(|id| self.relationships.get(id))
+            .cloned()
+        {
+            return Ok(Some(Fill::Image {
+                path: self.media_path(&target)?,
+                fit: ImageFit::Cover,
+            }));
+        }
+
+        Ok(None)
+    }
+
+    fn media_path(&mut self, archived: &str) -> Result<String, String> {
+        take_media(
+            self.archive,
+            archived,
+            self.media_directory,
+            self.media,
+            self.taken,
+        )
+    }
+
+    /// The paragraphs of the body of the slide's notes page
+    fn notes(&self) -> Result<Vec<String>, String> {
+        let mut notes = vec![];
+        if let Some(path) = self
+            .relationships
+            .values()
+            .find(|target| target.contains("notesSlide"))
+        {
+            let source = self.archive.text(path)?;
+            let document = parse(&source, path)?;
+            notes.extend(
+                descendants(document.root(), PRESENTATION, "sp")
+                    .into_iter()
+                    .filter(|shape| placeholder_type(*shape) == Some("body"))
+                    .map(|shape| paragraphs(shape).join("\n"))
+                    .filter(|note| !note.trim().is_empty()),
+            );
+        }
+
+        Ok(notes)
+    }
+}
+
+/// The text of a shape as a title, a subtitle, a list or a text, depending
+/// on the placeholder it fills and whether its paragraphs have bullets
+fn text_shape(shape: Node, transform: Transform) -> Option<SlideElement> {
+    let body = child(shape, PRESENTATION, "txBody")?;
+    let kind = placeholder_type(shape);
+    let has_bullets = descendants(body, DRAWING, "buChar").len()
+        + descendants(body, DRAWING, "buAutoNum").len()
+        > 0;
+    let is_body = matches!(kind, Some("body" | "obj" | ""));
+    let no_bullets = !descendants(body, DRAWING, "pPr").is_empty()
+        && descendants(body, DRAWING, "pPr")
+            .iter()
+            .all(|properties| child(*properties, DRAWING, "buNone").is_some());
+
+    let mut element = if has_bullets || (is_body && !no_bullets) {
+        let items = paragraphs(shape)
+            .into_iter()
+            .filter(|item| !item.trim().is_empty())
+            .collect::<Vec<String>>();
+        if items.is_empty() {
+            return None;
+        }
+        SlideElement::List(List::new(items, "•".into(), Length::Pixels(40.0)))
+    } else {
+        let (spans, size) = spans(body);
+        if spans.is_empty() {
+            return None;
+        }
+        let mut text = Text::with_spans(spans, None);
+        if let Some(size) = size {
+            text.set_size(size);
+        }
+        match kind {
+            Some("title" | "ctrTitle") => SlideElement::Title(text),
+            Some("subTitle") => SlideElement::Subtitle(text),
+            _ => SlideElement::Text(text),
+        }
+    };
+    element.set_placement(placement(shape, transform));
+
+    Some(element)
+}
+
+/// The runs of the text body as spans, and the font size of the first run
+/// that gives one
+fn spans(body: Node) -> (Vec<Span>, Option<u32>) {
+    let mut builder = SpanBuilder::default();
+    let mut size = None;
+
+    for paragraph in children(body, DRAWING, "p") {
+        builder.line_break();
+        for run in paragraph.children().filter(Node::is_element) {
+            match run.tag_name().name() {
+                "r" | "fld" => {
+                    let properties = child(run, DRAWING, "rPr");
+                    let flag = |name: &str| {
+                        matches!(
+                            properties.and_then(|properties| properties.attribute(name)),
+                            Some("1" | "true")
+                        )
+                    };
+                    size = size.or_else(|| {
+                        properties
+                            .and_then(|properties| properties.attribute("sz"))
+                            .and_then(|size| size.parse::<f64>().ok())
+                            .map(|size| (size / FONT_SIZE_PER_PIXEL).round() as u32)
+                    });
+                    let text = child(run, DRAWING, "t")
+                        .and_then(|text| text.text())
+                        .unwrap_or("");
+                    let color = properties
+                        .and_then(|properties| child(properties, DRAWING, "solidFill"))
+                        .and_then(color);
+                    builder.push(text, flag("b"), flag("i"), color);
+                }
+                "br" => builder.line_break(),
+                _ => {}
+            }
+        }
+    }
+
+    if builder.is_blank() {
+        return (vec![], None);
+    }
+
+    (builder.finish(), size)
+}
+
+/// The plain text of each paragraph in the shape
+fn paragraphs(shape: Node) -> Vec<String> {
+    descendants(shape, DRAWING, "p")
+        .into_iter()
+        .map(|paragraph| {
+            descendants(paragraph, DRAWING, "t")
+                .into_iter()
+                .filter_map(|text| text.text())
+                .collect::<String>()
+        })
+        .collect()
+}
+
+/// Type of the placeholder the shape fills, an empty one for placeholders
+/// without a type, which hold body text
+fn placeholder_type<'a>(shape: Node<'a, '_>) -> Option<&'a str> {
+    let placeholder = descendant(shape, PRESENTATION, "ph")?;
+
+    Some(placeholder.attribute("type").unwrap_or(""))
+}
+
+/// Where the shape's frame puts it, nothing for placeholders taking their
+/// position from the layout
+fn placement(shape: Node, transform: Transform) -> Placement {
+    let mut placement = Placement::default();
+    if let Some(frame) = shape
+        .children()
+        .find(|node| node.tag_name().name() == "spPr")
+        .and_then(|properties| child(properties, DRAWING, "xfrm"))
+    {
+        let ((x, y), (width, height)) = frame_extent(frame);
+        let ((offset_x, offset_y), (scale_x, scale_y)) = transform;
+
+        placement.set_position(
+            pixels((offset_x + x * scale_x) / EMU_PER_PIXEL),
+            pixels((offset_y + y * scale_y) / EMU_PER_PIXEL),
+        );
+        placement.set_size(
+            Some(pixels(width * scale_x / EMU_PER_PIXEL)),
+            Some(pixels(height * scale_y / EMU_PER_PIXEL)),
+        );
+    }
+
+    placement
+}
+
+/// The offset and the extent of a frame, in EMU
+fn frame_extent(frame: Node) -> ((f64, f64), (f64, f64)) {
+    let value = |name: &str, attribute: &str| {
+        child(frame, DRAWING, name)
+            .and_then(|node| node.attribute(attribute))
+            .and_then(|value| value.parse::<f64>().ok())
+            .unwrap_or(0.0)
+    };
+
+    (
+        (value("off", "x"), value("off", "y")),
+        (value("ext", "cx"), value("ext", "cy")),
+    )
+}
+
+/// Maps the coordinates of a group's children, which have their own
+/// offset and extent, onto the group's frame
+fn group_transform(frame: Node) -> Transform {
+    let ((x, y), (width, height)) = frame_extent(frame);
+    let value = |name: &str, attribute: &str, default: f64| {
+        child(frame, DRAWING, name)
+            .and_then(|node| node.attribute(attribute))
+            .and_then(|value| value.parse::<f64>().ok())
+            .unwrap_or(default)
+    };
+    let scale = |extent: f64, child_extent: f64| {
+        if child_extent > 0.0 {
+            extent / child_extent
+        } else {
+            1.0
+        }
+    };
+    let scale_x = scale(width, value("chExt", "cx", width));
+    let scale_y = scale(height, value("chExt", "cy", height));
+
+    (
+        (
+            x - value("chOff", "x", x) * scale_x,
+            y - value("chOff", "y", y) * scale_y,
+        ),
+        (scale_x, scale_y),
+    )
+}
+
+/// The transform of a group within a group
+fn compose(outer: Transform, inner: Transform) -> Transform {
+    let ((outer_x, outer_y), (outer_scale_x, outer_scale_y)) = outer;
+    let ((inner_x, inner_y), (inner_scale_x, inner_scale_y)) = inner;
+
+    (
+        (
+            outer_x + inner_x * outer_scale_x,
+            outer_y + inner_y * outer_scale_y,
+        ),
+        (outer_scale_x * inner_scale_x, outer_scale_y * inner_scale_y),
+    )
+}
+
+/// An RGB color given in the node, theme colors can't be told without the
+/// theme and are left out
+fn color(node: Node) -> Option<Color> {
+    let rgb = descendant(node, DRAWING, "srgbClr")?;
+    let mut color = Color::from_hex(&format!("#{}", rgb.attribute("val")?))?;
+    if let Some(alpha) = child(rgb, DRAWING, "alpha")
+        .and_then(|alpha| alpha.attribute("val"))
+        .and_then(|alpha| alpha.parse::<u32>().ok())
+    {
+        color = Color::new(
+            color.red(),
+            color.green(),
+            color.blue(),
+            (alpha.min(100_000) * 0xff / 100_000) as u8,
+        );
+    }
+
+    Some(color)
+}
+
+/// Targets of the relationships of the file, by their ids, resolved to
+/// paths within the archive. The package's own ones for an empty path.
+fn relationships(archive: &ZipArchive, path: &str) -> Result<HashMap<String, String>, String> {
+    let (directory, file) = path.rsplit_once('/').unwrap_or(("", path));
+    let relationships_path = if directory.is_empty() {
+        format!("_rels/{}.rels", file)
+    } else {
+        format!("{}/_rels/{}.rels", directory, file)
+    };
+    if !archive.contains(&relationships_path) {
+        return Ok(HashMap::new());
+    }
+
+    let source = archive.text(&relationships_path)?;
+    let document = parse(&source, &relationships_path)?;
+
+    Ok(children(
+        document.root_element(),
+        PACKAGE_RELATIONSHIPS,
+        "Relationship",
+    )
+    .into_iter()
+    .filter(|relationship| relationship.attribute("TargetMode") != Some("External"))
+    .filter_map(|relationship| {
+        Some((
+            relationship.attribute("Id")?.to_string(),
+            resolve(path, relationship.attribute("Target")?),
+        ))
+    })
+    .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::zip::ZipWriter;
+
+    const NAMESPACES: &str = "xmlns:a=\"http://schemas.openxmlformats.org/drawingml/2006/main\" \
+         xmlns:p=\"http://schemas.openxmlformats.org/presentationml/2006/main\" \
+         xmlns:r=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships\"";
+
+    fn relationships_xml(relationships: &[(&str, &str)]) -> String {
+        let relationships = relationships
+            .iter()
+            .map(|(id, target)| {
+                format!(
+                    "<Relationship Id=\"{}\" Type=\"x\" Target=\"{}\"/>",
+                    id, target
+                )
+            })
+            .collect::<Vec<String>>()
+            .concat();
+
+        format!(
+            "<Relationships xmlns=\"{}\">{}</Relationships>",
+            PACKAGE_RELATIONSHIPS, relationships
+        )
+    }
+
+    fn archive(
+        slide: &str,
+        slide_relationships: &[(&str, &str)],
+        notes: Option<&str>,
+    ) -> ZipArchive {
+        let mut writer = ZipWriter::new(vec![]);
+        let mut add = |name: &str, data: &str| writer.add_file(name, data.as_bytes()).unwrap();
+        add(
+            "_rels/.rels",
+            &relationships_xml(&[("rId1", "ppt/presentation.xml")]),
+        );
+        add(
+            "ppt/presentation.xml",
+            &format!(
+                "<p:presentation {}><p:sldIdLst><p:sldId id=\"256\" r:id=\"rId2\"/></p:sldIdLst>\
+                 <p:sldSz cx=\"9144000\" cy=\"6858000\"/></p:presentation>",
+                NAMESPACES
+            ),
+        );
+        add(
+            "ppt/_rels/presentation.xml.rels",
+            &relationships_xml(&[("rId2", "slides/slide1.xml")]),
+        );
+        add(
+            "ppt/slides/slide1.xml",
+            &format!("<p:sld {}><p:cSld>{}</p:cSld></p:sld>", NAMESPACES, slide),
+        );
+        add(
+            "ppt/slides/_rels/slide1.xml.rels",
+            &relationships_xml(slide_relationships),
+        );
+        if let Some(notes) = notes {
+            add(
+                "ppt/notesSlides/notesSlide1.xml",
+                &format!(
+                    "<p:notes {}><p:cSld><p:spTree><p:sp><p:nvSpPr><p:nvPr><p:ph type=\"body\"/></p:nvPr>\
+                     </p:nvSpPr><p:txBody><a:p><a:r><a:t>{}</a:t></a:r></a:p></p:txBody></p:sp>\
+                     </p:spTree></p:cSld></p:notes>",
+                    NAMESPACES, notes
+                ),
+            );
+        }
+        writer.add_file("ppt/media/image1.png", b"png").unwrap();
+
+        ZipArchive::new(writer.finish().unwrap()).unwrap()
+    }
+
+    #[test]
+    pub fn reads_slides_with_their_text_and_pictures() {
+        let archive = archive(
+            "<p:bg><p:bgPr><a:solidFill><a:srgbClr val=\"102030\"/></a:solidFill></p:bgPr></p:bg>\
+             <p:spTree>\
+             <p:sp><p:nvSpPr><p:nvPr><p:ph type=\"title\"/></p:nvPr></p:nvSpPr>\
+             <p:spPr><a:xfrm><a:off x=\"952500\" y=\"476250\"/><a:ext cx=\"4762500\" cy=\"952500\"/></a:xfrm></p:spPr>\
+             <p:txBody><a:p><a:r><a:t>Hello </a:t></a:r><a:r><a:rPr sz=\"3000\" b=\"1\">\
+             <a:solidFill><a:srgbClr val=\"FF0000\"/></a:solidFill></a:rPr><a:t>world</a:t></a:r></a:p></p:txBody></p:sp>\
+             <p:sp><p:nvSpPr><p:nvPr><p:ph idx=\"1\"/></p:nvPr></p:nvSpPr><p:txBody>\
+             <a:p><a:r><a:t>one</a:t></a:r></a:p><a:p><a:r><a:t>two</a:t></a:r></a:p></p:txBody></p:sp>\
+             <p:grpSp><p:grpSpPr><a:xfrm><a:off x=\"95250\" y=\"0\"/><a:ext cx=\"952500\" cy=\"952500\"/>\
+             <a:chOff x=\"0\" y=\"0\"/><a:chExt cx=\"1905000\" cy=\"1905000\"/></a:xfrm></p:grpSpPr>\
+             <p:pic><p:blipFill><a:blip r:embed=\"rId3\"/></p:blipFill><p:spPr><a:xfrm>\
+             <a:off x=\"952500\" y=\"952500\"/><a:ext cx=\"952500\" cy=\"952500\"/></a:xfrm></p:spPr></p:pic>\
+             </p:grpSp></p:spTree>",
+            &[
+                ("rId3", "../media/image1.png"),
+                ("rId4", "../notesSlides/notesSlide1.xml"),
+            ],
+            Some("Say hi"),
+        );
+
+        let (presentation, media) = read(&archive, "deck", "deck-media").unwrap();
+
+        assert_eq!("deck", presentation.metadata().title());
+        assert_eq!(
+            Resolution::new(960, 720),
+            presentation.metadata().resolution()
+        );
+        let slide = &presentation.slides()[0];
+        assert_eq!("Slide 1", slide.name());
+        assert_eq!(
+            Some(&Fill::Color(Color::new(0x10, 0x20, 0x30, 0xff))),
+            slide.background()
+        );
+        assert_eq!(vec!["Say hi".to_string()], *slide.notes());
+
+        match &slide.elements()[0] {
+            SlideElement::Title(title) => {
+                assert_eq!("Hello world", title.content());
+                assert!(title.spans()[1].bold());
+                assert_eq!(Some(Color::new(0xff, 0, 0, 0xff)), title.spans()[1].color());
+                assert_eq!(Some(40), title.size());
+                assert_eq!(
+                    Some((Length::Pixels(100.0), Length::Pixels(50.0))),
+                    title.placement().position()
+                );
+            }
+            other => panic!("Expected a title, got {:?}", other),
+        }
+        match &slide.elements()[1] {
+            SlideElement::List(list) => {
+                assert_eq!(&vec!["one".to_string(), "two".into()], list.items());
+                assert!(!list.placement().is_positioned());
+            }
+            other => panic!("Expected a list, got {:?}", other),
+        }
+        match &slide.elements()[2] {
+            SlideElement::Image(image) => {
+                assert_eq!("deck-media/image1.png", image.path());
+                assert_eq!(
+                    Some((Length::Pixels(60.0), Length::Pixels(50.0))),
+                    image.placement().position()
+                );
+                assert_eq!(
+                    (Some(Length::Pixels(50.0)), Some(Length::Pixels(50.0))),
+                    image.placement().size()
+                );
+            }
+            other => panic!("Expected an image, got {:?}", other),
+        }
+        assert_eq!(
+            vec![("deck-media/image1.png".to_string(), b"png".to_vec())],
+            media
+        );
+        let source = presentation.to_source();
+        assert_eq!(Ok(presentation), crate::parse(&source));
+    }
+
+    #[test]
+    pub fn reads_gradient_backgrounds() {
+        let archive = archive(
+            "<p:bg><p:bgPr><a:gradFill><a:gsLst><a:gs pos=\"0\"><a:srgbClr val=\"000000\"/></a:gs>\
+             <a:gs pos=\"100000\"><a:srgbClr val=\"FFFFFF\"><a:alpha val=\"50000\"/></a:srgbClr></a:gs>\
+             </a:gsLst><a:lin ang=\"0\"/></a:gradFill></p:bgPr></p:bg><p:spTree/>",
+            &[],
+            None,
+        );
+
+        let (presentation, _) = read(&archive, "deck", "deck-media").unwrap();
+
+        assert_eq!(
+            Some(&Fill::Gradient {
+                from: Color::new(0, 0, 0, 0xff),
+                to: Color::new(0xff, 0xff, 0xff, 0x7f),
+                direction: GradientDirection::Horizontal,
+            }),
+            presentation.slides()[0].background()
+        );
+    }
+}
@@ -0,0 +1,413 @@
+use std::fmt;
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// JSON value, objects keep their keys in the order they were written
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    /// Builds an object out of the pairs
+    pub fn object(pairs: Vec<(&str, Json)>) -> Self {
+        Self::Object(
+            pairs
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect(),
+        )
+    }
+
+    pub fn string(value: &str) -> Self {
+        Self::String(value.into())
+    }
+
+    pub fn number(value: u32) -> Self {
+        Self::Number(f64::from(value))
+    }
+
+    /// The member of an object, `Null` for anything else
+    pub fn get(&self, key: &str) -> &Json {
+        match self {
+            Self::Object(pairs) => pairs
+                .iter()
+                .find(|(name, _)| name == key)
+                .map_or(&Self::Null, |(_, value)| value),
+            _ => &Self::Null,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// A number that is a whole one and fits in `u32`
+    pub fn as_u32(&self) -> Option<u32> {
+        match self {
+            Self::Number(value)
+                if value.fract() == 0.0 && *value >= 0.0 && *value <= f64::from(u32::MAX) =>
+            {
+                Some(*value as u32)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Number(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> &[Json] {
+        match self {
+            Self::Array(values) => values,
+            _ => &[],
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        *self == Self::Null
+    }
+
+    /// Writes the value over several lines, indented by two spaces, for
+    /// files people read
+    pub fn pretty(&self) -> String {
+        let mut output = String::new();
+        write_pretty(self, 0, &mut output);
+
+        output
+    }
+
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let mut chars = input.chars().peekable();
+        let value = parse_value(&mut chars)?;
+        skip_whitespace(&mut chars);
+
+        match chars.next() {
+            None => Ok(value),
+            Some(character) => Err(format!("unexpected `{}` after the value", character)),
+        }
+    }
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bool(value) => write!(f, "{}", value),
+            // whole numbers are written without a fraction, as ids usually are
+            Self::Number(value) if value.fract() == 0.0 && value.abs() < 1e15 => {
+                write!(f, "{}", *value as i64)
+            }
+            Self::Number(value) if value.is_finite() => write!(f, "{}", value),
+            // JSON has no infinities or NaN
+            Self::Null | Self::Number(_) => write!(f, "null"),
+            Self::String(value) => write_string(f, value),
+            Self::Array(values) => {
+                write!(f, "[")?;
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                write!(f, "]")
+            }
+            Self::Object(pairs) => {
+                write!(f, "{{")?;
+                for (index, (key, value)) in pairs.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+fn write_pretty(value: &Json, depth: usize, output: &mut String) {
+    let indent = |depth: usize| "  ".repeat(depth);
+
+    match value {
+        Json::Array(values) if !values.is_empty() => {
+            output.push_str("[\n");
+            for (index, value) in values.iter().enumerate() {
+                if index > 0 {
+                    output.push_str(",\n");
+                }
+                output.push_str(&indent(depth + 1));
+                write_pretty(value, depth + 1, output);
+            }
+            output.push('\n');
+            output.push_str(&indent(depth));
+            output.push(']');
+        }
+        Json::Object(pairs) if !pairs.is_empty() => {
+            output.push_str("{\n");
+            for (index, (key, value)) in pairs.iter().enumerate() {
+                if index > 0 {
+                    output.push_str(",\n");
+                }
+                output.push_str(&indent(depth + 1));
+                output.push_str(&Json::String(key.clone()).to_string());
+                output.push_str(": ");
+                write_pretty(value, depth + 1, output);
+            }
+            output.push('\n');
+            output.push_str(&indent(depth));
+            output.push('}');
+        }
+        _ => output.push_str(&value.to_string()),
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter<'_>, value: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for character in value.chars() {
+        match character {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            character if (character as u32) < 0x20 => write!(f, "\\u{:04x}", character as u32)?,
+            character => write!(f, "{}", character)?,
+        }
+    }
+    write!(f, "\"")
+}
+
+fn skip_whitespace(chars: &mut Peekable<Chars<'_>>) {
+    while matches!(chars.peek(), Some(' ' | '\t' | '\n' | '\r')) {
+        chars.next();
+    }
+}
+
+fn parse_value(chars: &mut Peekable<Chars<'_>>) -> Result<Json, String> {
+    skip_whitespace(chars);
+
+    match chars.peek() {
+        Some('n') => parse_literal(chars, "null", Json::Null),
+        Some('t') => parse_literal(chars, "true", Json::Bool(true)),
+        Some('f') => parse_literal(chars, "false", Json::Bool(false)),
+        Some('"') => Ok(Json::String(parse_string(chars)?)),
+        Some('[') => parse_array(chars),
+        Some('{') => parse_object(chars),
+        Some(character) if *character == '-' || character.is_ascii_digit() => parse_number(chars),
+        Some(character) => Err(format!("unexpected `{}`", character)),
+        None => Err("unexpected end of input".into()),
+    }
+}
+
+fn parse_literal(
+    chars: &mut Peekable<Chars<'_>>,
+    literal: &str,
+    value: Json,
+) -> Result<Json, String> {
+    for expected in literal.chars() {
+        if chars.next() != Some(expected) {
+            return Err(format!("expected `{}`", literal));
+        }
+    }
+
+    Ok(value)
+}
+
+fn parse_number(chars: &mut Peekable<Chars<'_>>) -> Result<Json, String> {
+    let mut number = String::new();
+    while let Some(&character) = chars.peek() {
+        if !character.is_ascii_digit() && !matches!(character, '-' | '+' | '.' | 'e' | 'E') {
+            break;
+        }
+        number.push(character);
+        chars.next();
+    }
+
+    number
+        .parse()
+        .map(Json::Number)
+        .map_err(|_| format!("`{}` is not a valid number", number))
+}
+
+/// Parses a string whose opening quote is next
+fn parse_string(chars: &mut Peekable<Chars<'_>>) -> Result<String, String> {
+    chars.next();
+    let mut string = String::new();
+
+    loop {
+        match chars.next() {
+            Some('"') => return Ok(string),
+            Some('\\') => match chars.next() {
+                Some('"') => string.push('"'),
+                Some('\\') => string.push('\\'),
+                Some('/') => string.push('/'),
+                Some('b') => string.push('\u{8}'),
+                Some('f') => string.push('\u{c}'),
+                Some('n') => string.push('\n'),
+                Some('r') => string.push('\r'),
+                Some('t') => string.push('\t'),
+                Some('u') => string.push(parse_unicode_escape(chars)?),
+                _ => return Err("invalid escape sequence".into()),
+            },
+            Some(character) => string.push(character),
+            None => return Err("string is never closed".into()),
+        }
+    }
+}
+
+/// Parses the digits of a `\u` escape, and the second half of a surrogate
+/// pair when the first one is
+fn parse_unicode_escape(chars: &mut Peekable<Chars<'_>>) -> Result<char, String> {
+    let read_unit = |chars: &mut Peekable<Chars<'_>>| -> Result<u16, String> {
+        let digits: String = chars.take(4).collect();
+        u16::from_str_radix(&digits, 16).map_err(|_| format!("invalid escape `\\u{}`", digits))
+    };
+
+    let first = read_unit(chars)?;
+    let mut units = vec![first];
+    if (0xd800..0xdc00).contains(&first) {
+        if chars.next() != Some('\\') || chars.next() != Some('u') {
+            return Err("unpaired surrogate".into());
+        }
+        units.push(read_unit(chars)?);
+    }
+
+    String::from_utf16(&units)
+        .ok()
+        .and_then(|string| string.chars().next())
+        .ok_or_else(|| "unpaired surrogate".into())
+}
+
+fn parse_array(chars: &mut Peekable<Chars<'_>>) -> Result<Json, String> {
+    chars.next();
+    let mut values = vec![];
+
+    skip_whitespace(chars);
+    if chars.peek() == Some(&']') {
+        chars.next();
+        return Ok(Json::Array(values));
+    }
+
+    loop {
+        values.push(parse_value(chars)?);
+        skip_whitespace(chars);
+
+        match chars.next() {
+            Some(',') => {}
+            Some(']') => return Ok(Json::Array(values)),
+            _ => return Err("expected `,` or `]`".into()),
+        }
+    }
+}
+
+fn parse_object(chars: &mut Peekable<Chars<'_>>) -> Result<Json, String> {
+    chars.next();
+    let mut pairs = vec![];
+
+    skip_whitespace(chars);
+    if chars.peek() == Some(&'}') {
+        chars.next();
+        return Ok(Json::Object(pairs));
+    }
+
+    loop {
+        skip_whitespace(chars);
+        if chars.peek() != Some(&'"') {
+            return Err("expected a key".into());
+        }
+        let key = parse_string(chars)?;
+
+        skip_whitespace(chars);
+        if chars.next() != Some(':') {
+            return Err("expected `:`".into());
+        }
+        pairs.push((key, parse_value(chars)?));
+        skip_whitespace(chars);
+
+        match chars.next() {
+            Some(',') => {}
+            Some('}') => return Ok(Json::Object(pairs)),
+            _ => return Err("expected `,` or `}`".into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn parses_nested_values() {
+        let value = Json::parse(
+            r#" {"id": 1, "params": {"text": "a\"b\n\u00e9\ud83d\ude00", "list": [true, null, -2.5e1]}} "#,
+        )
+        .unwrap();
+
+        assert_eq!(Some(1), value.get("id").as_u32());
+        assert_eq!(Some("a\"b\né😀"), value.get("params").get("text").as_str());
+        assert_eq!(
+            &[Json::Bool(true), Json::Null, Json::Number(-25.0)],
+            value.get("params").get("list").as_array()
+        );
+        assert!(value.get("missing").is_null());
+    }
+
+    #[test]
+    pub fn fails_on_malformed_input() {
+        assert!(Json::parse("{\"a\" 1}").is_err());
+        assert!(Json::parse("[1, 2").is_err());
+        assert!(Json::parse("\"\\ud83d\"").is_err());
+        assert!(Json::parse("1 2").is_err());
+    }
+
+    #[test]
+    pub fn writes_compact_json() {
+        let value = Json::object(vec![
+            ("id", Json::number(3)),
+            ("text", Json::string("say \"hi\"\n")),
+            ("items", Json::Array(vec![Json::Null, Json::Number(0.5)])),
+        ]);
+
+        assert_eq!(
+            r#"{"id":3,"text":"say \"hi\"\n","items":[null,0.5]}"#,
+            value.to_string()
+        );
+    }
+
+    #[test]
+    pub fn writes_indented_json() {
+        let value = Json::object(vec![
+            (
+                "items",
+                Json::Array(vec![Json::Bool(true), Json::Array(vec![])]),
+            ),
+            ("empty", Json::Object(vec![])),
+        ]);
+
+        assert_eq!(
+            "{\n  \"items\": [\n    true,\n    []\n  ],\n  \"empty\": {}\n}",
+            value.pretty()
+        );
+        assert_eq!(Ok(value.clone()), Json::parse(&value.pretty()));
+    }
+}
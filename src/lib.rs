@@ -0,0 +1,144 @@
+#![deny(unsafe_code)]
+#![deny(clippy::all, clippy::pedantic, clippy::cargo)]
+// todo remove the allows
+#![allow(clippy::cast_sign_loss)]
+#![allow(clippy::cast_possible_wrap)]
+#![allow(clippy::cast_possible_truncation)]
+#![allow(clippy::multiple_crate_versions)]
+#![allow(clippy::must_use_candidate)]
+#![allow(clippy::missing_errors_doc)]
+#![allow(clippy::missing_panics_doc)]
+
+use crate::assets::AssetLoader;
+use crate::parsing::highlighting::{self, TokenClass};
+use crate::parsing::parser::{Error, Parser};
+use crate::parsing::token_stream::SourceLocationRange;
+use crate::parsing::tokenizer::{Tokenizer, Tokens};
+use crate::presentation::{Presentation, Theme};
+use std::path::Path;
+
+pub use crate::font_resolver::ExtractedFonts;
+
+pub mod assets;
+pub mod auto_advance;
+pub mod bundle;
+pub mod config;
+pub mod control;
+pub mod diagnostics;
+pub mod easing;
+pub mod event_loop;
+pub mod export;
+mod font_resolver;
+pub mod import;
+pub mod json;
+pub mod logging;
+pub mod lsp;
+pub mod navigation;
+pub mod notes;
+pub mod parsing;
+pub mod playback;
+pub mod pointer;
+pub mod presentation;
+pub mod rehearsal;
+pub mod rendering;
+pub mod session;
+pub mod spelling;
+pub mod talk_timer;
+mod temp_dir;
+pub mod validation;
+pub mod watch;
+pub mod zip;
+
+/// Parses a presentation written in the przntr DSL
+pub fn parse(input: &str) -> Result<Presentation, Vec<Error>> {
+    let mut tokenizer = Tokenizer::new(input);
+    let mut parser = Parser::new(&mut tokenizer);
+
+    parser.parse()
+}
+
+/// Splits input written in the przntr DSL into tokens, for tools such as
+/// formatters and syntax highlighters
+pub fn tokenize(input: &str) -> Tokens<'_> {
+    Tokenizer::new(input).into_iter()
+}
+
+/// Classifies the pieces of input written in the przntr DSL, for syntax
+/// highlighting. Keeps going after invalid input.
+pub fn classify(input: &str) -> Vec<(SourceLocationRange, TokenClass)> {
+    highlighting::classify(input)
+}
+
+/// Parses a presentation read from the given file, resolving includes
+/// relative to it
+pub fn parse_file(input: &str, path: &Path) -> Result<Presentation, Vec<Error>> {
+    let mut tokenizer = Tokenizer::new(input);
+    let mut parser = Parser::new(&mut tokenizer);
+    parser.set_source_path(path.to_path_buf());
+
+    parser.parse()
+}
+
+/// Parses a presentation read from the given file like `parse_file`, styled
+/// with the theme instead of the one its metadata names when there is one,
+/// and with assets named by URL found by the loader
+pub fn parse_file_with(
+    input: &str,
+    path: &Path,
+    theme: Option<Theme>,
+    assets: AssetLoader,
+) -> Result<Presentation, Vec<Error>> {
+    let mut tokenizer = Tokenizer::new(input);
+    let mut parser = Parser::new(&mut tokenizer);
+    parser.set_source_path(path.to_path_buf());
+    if let Some(theme) = theme {
+        parser.set_theme(theme);
+    }
+    parser.set_asset_loader(assets);
+
+    parser.parse()
+}
+
+/// Parses a theme read from the given file, resolving includes relative
+/// to it
+pub fn parse_theme_file(
+    input: &str,
+    path: &Path,
+    assets: AssetLoader,
+) -> Result<Theme, Vec<Error>> {
+    let mut tokenizer = Tokenizer::new(input);
+    let mut parser = Parser::new(&mut tokenizer);
+    parser.set_source_path(path.to_path_buf());
+    parser.set_asset_loader(assets);
+
+    parser.parse_theme()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parsing::token_stream::Token;
+
+    #[test]
+    pub fn parses_presentation() {
+        let presentation = parse("metadata { title \"some title\" }").unwrap();
+
+        assert_eq!("some title", presentation.metadata().title());
+    }
+
+    #[test]
+    pub fn tokenizes_input() {
+        let tokens: Vec<_> = tokenize("metadata { }")
+            .map(|result| result.map(|(token, _)| token))
+            .collect();
+
+        assert_eq!(
+            vec![
+                Ok(Token::KeywordMetadata),
+                Ok(Token::OpeningBrace),
+                Ok(Token::ClosingBrace)
+            ],
+            tokens
+        );
+    }
+}
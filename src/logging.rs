@@ -0,0 +1,64 @@
+use log::{Level, LevelFilter, Log, Metadata, Record, SetLoggerError};
+
+/// Writes log messages to stderr, prefixed with their level the way the
+/// command line reports errors and warnings
+pub struct StderrLogger {
+    level: LevelFilter,
+}
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!(
+                "{}",
+                format_record(record.level(), record.target(), &record.args().to_string())
+            );
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Sends the messages of the level and the more severe ones to stderr
+pub fn init(level: LevelFilter) -> Result<(), SetLoggerError> {
+    log::set_boxed_logger(Box::new(StderrLogger { level }))?;
+    log::set_max_level(level);
+
+    Ok(())
+}
+
+/// Debugging messages name the module they come from
+fn format_record(level: Level, target: &str, message: &str) -> String {
+    match level {
+        Level::Error => format!("error: {}", message),
+        Level::Warn => format!("warning: {}", message),
+        Level::Info => format!("info: {}", message),
+        Level::Debug | Level::Trace => format!(
+            "{}: {}: {}",
+            level.to_string().to_lowercase(),
+            target,
+            message
+        ),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn prefixes_messages_with_their_level() {
+        assert_eq!(
+            "warning: no MIDI device",
+            format_record(Level::Warn, "przntr::control", "no MIDI device")
+        );
+        assert_eq!(
+            "debug: przntr::event_loop: unhandled key Q",
+            format_record(Level::Debug, "przntr::event_loop", "unhandled key Q")
+        );
+    }
+}
@@ -0,0 +1,478 @@
+use crate::diagnostics;
+use crate::json::Json;
+use crate::parsing::token_stream::{SourceLocationRange, Token, TokenStream, TokenizerResult};
+use crate::parsing::tokenizer::{Tokenizer, KEYWORDS};
+use std::ops::Range;
+use std::path::Path;
+
+const ERROR_SEVERITY: u32 = 1;
+const CLASS_COMPLETION: u32 = 7;
+const KEYWORD_COMPLETION: u32 = 14;
+
+/// Short descriptions shown when hovering keywords
+const KEYWORD_DOCS: &[(&str, &str)] = &[
+    (
+        "metadata",
+        "Title, size and other settings of the whole presentation",
+    ),
+    (
+        "style",
+        "Fonts, colors and text styles, or the text style of an element",
+    ),
+    (
+        "slide",
+        "A slide, with its name and optionally the layout it uses",
+    ),
+    (
+        "section",
+        "Starts a section at the next slide, listed in the jump menu",
+    ),
+    (
+        "layout",
+        "Named arrangement of elements and placeholders slides can use",
+    ),
+    (
+        "uses",
+        "Fills the named layout's placeholders with this slide's groups",
+    ),
+    (
+        "placeholder",
+        "Place in a layout that slides fill with their own elements",
+    ),
+    ("define", "Defines a variable, used in strings as `${name}`"),
+    (
+        "include",
+        "Reads another file's styles, layouts, definitions and slides",
+    ),
+    ("text", "Paragraph of text with markup"),
+    (
+        "title",
+        "Title of the slide, or of the presentation in `metadata`",
+    ),
+    ("subtitle", "Smaller heading under the title"),
+    ("list", "Bulleted list of `item`s"),
+    ("image", "Picture read from a file"),
+    ("video", "Video read from a file"),
+    ("row", "Lays out its elements side by side"),
+    ("column", "Lays out its elements one below another"),
+    (
+        "step",
+        "Elements revealed one key press after the ones before",
+    ),
+    ("notes", "Speaker notes, shown in the presenter view"),
+    (
+        "transition",
+        "How the slide comes in: `fade`, `slide` or `none`",
+    ),
+    (
+        "duration",
+        "Seconds the slide stays up before moving on by itself",
+    ),
+    ("target", "Seconds the speaker plans to spend on the slide"),
+    (
+        "background",
+        "Color, gradient or image behind the slide or container",
+    ),
+    (
+        "hidden",
+        "Leaves the slide out of the talk unless hidden slides are shown",
+    ),
+    ("font", "Font file or system font used by text styles"),
+    (
+        "text-style",
+        "Named set of font, size and color that text can use",
+    ),
+    (
+        "font-stack",
+        "Fonts tried in order for characters the first one lacks",
+    ),
+    ("theme", "Theme file styling the presentation"),
+    ("control", "OSC or MIDI input driving the presentation"),
+    (
+        "spelling-ignore",
+        "Words the spell checker accepts, separated by spaces",
+    ),
+    ("font-dir", "Directory font files are looked up in"),
+];
+
+/// Position in a document the way editors count it, lines from 0 and
+/// characters in UTF-16 code units
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Position {
+    pub line: u32,
+    pub character: u32,
+}
+
+impl Position {
+    pub fn from_json(value: &Json) -> Option<Self> {
+        Some(Self {
+            line: value.get("line").as_u32()?,
+            character: value.get("character").as_u32()?,
+        })
+    }
+
+    pub fn to_json(self) -> Json {
+        Json::object(vec![
+            ("line", Json::number(self.line)),
+            ("character", Json::number(self.character)),
+        ])
+    }
+}
+
+/// Document open in the editor, split into tokens again after every change
+pub struct Document {
+    text: String,
+    /// Tokens with where they are in the text, input the tokenizer failed on
+    /// is left out
+    tokens: Vec<(Token, Range<usize>)>,
+}
+
+impl Document {
+    pub fn new(text: String) -> Self {
+        let tokens = tokenize(&text);
+
+        Self { text, tokens }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Replaces the range with the text, or the whole document when there is
+    /// no range
+    pub fn change(&mut self, range: Option<(Position, Position)>, text: &str) {
+        match range {
+            Some((start, end)) => {
+                let start = self.offset(start);
+                let end = self.offset(end).max(start);
+                self.text.replace_range(start..end, text);
+            }
+            None => self.text = text.into(),
+        }
+
+        self.tokens = tokenize(&self.text);
+    }
+
+    /// Problems the parser finds, includes and themes are looked up next to
+    /// the path when there is one. Errors without a location are put at the
+    /// end of the document.
+    pub fn diagnostics(&self, path: Option<&Path>) -> Json {
+        let result = match path {
+            Some(path) => crate::parse_file(&self.text, path),
+            None => crate::parse(&self.text),
+        };
+        let errors = result.err().unwrap_or_default();
+
+        Json::Array(
+            errors
+                .iter()
+                .map(|error| {
+                    Json::object(vec![
+                        (
+                            "range",
+                            match diagnostics::location(error) {
+                                Some(location) => self.error_range(location),
+                                None => self.range(self.text.len()..self.text.len()),
+                            },
+                        ),
+                        ("severity", Json::number(ERROR_SEVERITY)),
+                        ("source", Json::string("przntr")),
+                        ("message", Json::String(diagnostics::message(error))),
+                    ])
+                })
+                .collect(),
+        )
+    }
+
+    /// Text style names after `style`, layout names after `uses` and
+    /// keywords anywhere else
+    pub fn completions(&self, position: Position) -> Json {
+        let offset = self.offset(position);
+        let previous = self
+            .tokens
+            .iter()
+            .rev()
+            .find(|(_, span)| span.end < offset)
+            .map(|(token, _)| token);
+
+        let items = match previous {
+            Some(Token::KeywordStyle) => self.names(&Token::KeywordTextStyle, "text style"),
+            Some(Token::KeywordUses) => self.names(&Token::KeywordLayout, "layout"),
+            _ => KEYWORDS
+                .iter()
+                .map(|keyword| completion(keyword, KEYWORD_COMPLETION, "keyword"))
+                .collect(),
+        };
+
+        Json::Array(items)
+    }
+
+    /// Where the text style or layout named at the position is defined
+    pub fn definition(&self, position: Position) -> Option<Json> {
+        let index = self.token_at(self.offset(position))?;
+        let (keyword, name) = self.reference(index)?;
+
+        self.tokens
+            .windows(2)
+            .find(|pair| pair[0].0 == *keyword && pair[1].0 == Token::Name(name.into()))
+            .map(|pair| self.range(pair[1].1.clone()))
+    }
+
+    /// What the keyword at the position does, or what the name refers to
+    pub fn hover(&self, position: Position) -> Option<Json> {
+        let index = self.token_at(self.offset(position))?;
+        let span = self.tokens[index].1.clone();
+        let word = &self.text[span.clone()];
+
+        let contents = match self.reference(index) {
+            Some((Token::KeywordLayout, name)) => format!("layout `{}`", name),
+            Some((_, name)) => format!("text style `{}`", name),
+            None => KEYWORD_DOCS
+                .iter()
+                .find(|(keyword, _)| *keyword == word)
+                .map(|(keyword, doc)| format!("`{}`: {}", keyword, doc))?,
+        };
+
+        Some(Json::object(vec![
+            (
+                "contents",
+                Json::object(vec![
+                    ("kind", Json::string("markdown")),
+                    ("value", Json::String(contents)),
+                ]),
+            ),
+            ("range", self.range(span)),
+        ]))
+    }
+
+    /// Converts a position to a byte offset, positions past the end of a
+    /// line are at its end
+    pub fn offset(&self, position: Position) -> usize {
+        let line_start = self.line_start(position.line as usize);
+        let mut offset = line_start;
+        let mut character = 0;
+
+        for (index, current) in self.text[line_start..].char_indices() {
+            if current == '\n' || character >= position.character as usize {
+                return line_start + index;
+            }
+            character += current.len_utf16();
+            offset = line_start + index + current.len_utf8();
+        }
+
+        offset
+    }
+
+    pub fn position(&self, offset: usize) -> Position {
+        let before = &self.text[..offset];
+        let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+
+        Position {
+            line: before.matches('\n').count() as u32,
+            character: before[line_start..].encode_utf16().count() as u32,
+        }
+    }
+
+    fn range(&self, span: Range<usize>) -> Json {
+        Json::object(vec![
+            ("start", self.position(span.start).to_json()),
+            ("end", self.position(span.end).to_json()),
+        ])
+    }
+
+    /// Underlines what the diagnostics do
+    fn error_range(&self, location: SourceLocationRange) -> Json {
+        let (start, end) = (location.start(), location.end());
+        let start_offset = self.column_offset(start.line(), start.column().max(1) - 1);
+        let end_offset = if end.line() == start.line() {
+            self.column_offset(end.line(), end.column().saturating_sub(1))
+        } else {
+            self.column_offset(start.line(), u32::MAX)
+        };
+        let next_character = self.text[start_offset..]
+            .chars()
+            .next()
+            .map_or(0, char::len_utf8);
+
+        self.range(start_offset..end_offset.max(start_offset + next_character))
+    }
+
+    /// Offset of a column the way the tokenizer counts them, in characters
+    fn column_offset(&self, line: u32, column: u32) -> usize {
+        let line_start = self.line_start(line as usize);
+        let line_end = self.text[line_start..]
+            .find('\n')
+            .map_or(self.text.len(), |length| line_start + length);
+
+        self.text[line_start..line_end]
+            .char_indices()
+            .nth(column as usize)
+            .map_or(line_end, |(index, _)| line_start + index)
+    }
+
+    fn line_start(&self, line: usize) -> usize {
+        if line == 0 {
+            return 0;
+        }
+
+        self.text
+            .match_indices('\n')
+            .nth(line - 1)
+            .map_or(self.text.len(), |(index, _)| index + 1)
+    }
+
+    /// The token the offset is in or right after
+    fn token_at(&self, offset: usize) -> Option<usize> {
+        self.tokens
+            .iter()
+            .position(|(_, span)| span.start <= offset && offset <= span.end)
+    }
+
+    /// Keyword defining what the name at the index refers to, along with
+    /// the name
+    fn reference(&self, index: usize) -> Option<(&'static Token, &str)> {
+        match (&self.tokens[index.checked_sub(1)?].0, &self.tokens[index].0) {
+            (Token::KeywordStyle | Token::KeywordTextStyle, Token::Name(name)) => {
+                Some((&Token::KeywordTextStyle, name))
+            }
+            (Token::KeywordUses | Token::KeywordLayout, Token::Name(name)) => {
+                Some((&Token::KeywordLayout, name))
+            }
+            _ => None,
+        }
+    }
+
+    /// Names following the keyword, which defines them
+    fn names(&self, keyword: &Token, detail: &str) -> Vec<Json> {
+        self.tokens
+            .windows(2)
+            .filter(|pair| pair[0].0 == *keyword)
+            .filter_map(|pair| match &pair[1].0 {
+                Token::Name(name) => Some(completion(name, CLASS_COMPLETION, detail)),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+fn completion(label: &str, kind: u32, detail: &str) -> Json {
+    Json::object(vec![
+        ("label", Json::string(label)),
+        ("kind", Json::number(kind)),
+        ("detail", Json::string(detail)),
+    ])
+}
+
+fn tokenize(text: &str) -> Vec<(Token, Range<usize>)> {
+    let mut tokenizer = Tokenizer::new(text);
+    tokenizer.set_recover(true);
+    let mut tokens = vec![];
+
+    loop {
+        match tokenizer.next() {
+            TokenizerResult::Ok(token, _) => tokens.push((token, tokenizer.token_span())),
+            // the tokens after a typo still help completion and navigation
+            TokenizerResult::Err(_) => {}
+            TokenizerResult::End => return tokens,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const DECK: &str = "style {\n    text-style heading { size 64 }\n}\n\n\
+                        layout two-col {\n    placeholder left\n}\n\n\
+                        slide \"é\" uses two-col {\n    text \"hi\" style heading\n}\n";
+
+    fn position(line: u32, character: u32) -> Position {
+        Position { line, character }
+    }
+
+    fn range(line: u32, start: u32, end: u32) -> Json {
+        Json::object(vec![
+            ("start", position(line, start).to_json()),
+            ("end", position(line, end).to_json()),
+        ])
+    }
+
+    #[test]
+    pub fn converts_between_positions_and_offsets() {
+        let document = Document::new("a😀b\ncd".into());
+
+        assert_eq!(5, document.offset(position(0, 3)));
+        assert_eq!(position(0, 3), document.position(5));
+        assert_eq!(6, document.offset(position(0, 40)));
+        assert_eq!(9, document.offset(position(1, 2)));
+    }
+
+    #[test]
+    pub fn applies_ranged_changes() {
+        let mut document = Document::new("slide \"a\" {\n}\n".into());
+        document.change(Some((position(0, 7), position(0, 8))), "Intro");
+
+        assert_eq!("slide \"Intro\" {\n}\n", document.text());
+        assert_eq!(
+            Some(&Token::String("Intro".into())),
+            document.tokens.get(1).map(|(token, _)| token)
+        );
+    }
+
+    #[test]
+    pub fn finds_definitions_of_styles_and_layouts() {
+        let document = Document::new(DECK.into());
+
+        assert_eq!(Some(range(1, 15, 22)), document.definition(position(9, 24)));
+        assert_eq!(Some(range(4, 7, 14)), document.definition(position(8, 16)));
+        assert_eq!(None, document.definition(position(9, 6)));
+    }
+
+    #[test]
+    pub fn completes_names_after_style_and_uses() {
+        let document = Document::new(DECK.into());
+
+        assert_eq!(
+            Json::Array(vec![completion("heading", CLASS_COMPLETION, "text style")]),
+            document.completions(position(9, 22))
+        );
+        assert_eq!(
+            KEYWORDS.len(),
+            document.completions(position(10, 1)).as_array().len()
+        );
+    }
+
+    #[test]
+    pub fn describes_keywords_and_names() {
+        let document = Document::new(DECK.into());
+        let hover = |line, character| {
+            document
+                .hover(position(line, character))
+                .map(|hover| hover.get("contents").get("value").clone())
+        };
+
+        assert_eq!(Some(Json::string("text style `heading`")), hover(9, 25));
+        assert_eq!(
+            Some(Json::string(
+                "`uses`: Fills the named layout's placeholders with this slide's groups"
+            )),
+            hover(8, 12)
+        );
+        assert_eq!(None, hover(9, 12));
+    }
+
+    #[test]
+    pub fn reports_parser_errors_with_their_ranges() {
+        let document = Document::new(
+            "metadata { title \"t\" }\nslide \"a\" {\n    transition spin\n}\n".into(),
+        );
+        let diagnostics = document.diagnostics(None);
+        let diagnostic = &diagnostics.as_array()[0];
+
+        assert_eq!(&range(2, 15, 19), diagnostic.get("range"));
+        assert_eq!(
+            Some("`spin` is not a valid transition, expected fade, slide or none"),
+            diagnostic.get("message").as_str()
+        );
+    }
+}
@@ -0,0 +1,368 @@
+use crate::json::Json;
+use document::{Document, Position};
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+
+pub mod document;
+
+const INCREMENTAL_SYNC: u32 = 2;
+const PARSE_ERROR: i32 = -32700;
+const INVALID_REQUEST: i32 = -32600;
+const METHOD_NOT_FOUND: i32 = -32601;
+
+/// Language server for editors, keeping the open documents and answering
+/// requests about them
+#[derive(Default)]
+pub struct Server {
+    documents: HashMap<String, Document>,
+    is_shut_down: bool,
+    has_exited: bool,
+}
+
+impl Server {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether the client asked the server to exit
+    pub fn has_exited(&self) -> bool {
+        self.has_exited
+    }
+
+    /// Handles a message from the client, returning the messages to send
+    /// back
+    pub fn handle(&mut self, message: &Json) -> Vec<Json> {
+        let id = message.get("id");
+        let params = message.get("params");
+        let method = message.get("method").as_str().unwrap_or_default();
+
+        if self.is_shut_down && method != "exit" {
+            return Self::error_response(id, INVALID_REQUEST, "the server was shut down");
+        }
+
+        match method {
+            "initialize" => Self::response(id, capabilities()),
+            "shutdown" => {
+                self.is_shut_down = true;
+                Self::response(id, Json::Null)
+            }
+            "exit" => {
+                self.has_exited = true;
+                vec![]
+            }
+            "textDocument/didOpen" => {
+                let document = params.get("textDocument");
+                let uri = document.get("uri").as_str().unwrap_or_default();
+                let text = document.get("text").as_str().unwrap_or_default();
+                self.documents
+                    .insert(uri.into(), Document::new(text.into()));
+
+                self.publish_diagnostics(uri)
+            }
+            "textDocument/didChange" => {
+                let uri = params
+                    .get("textDocument")
+                    .get("uri")
+                    .as_str()
+                    .unwrap_or_default();
+                if let Some(document) = self.documents.get_mut(uri) {
+                    for change in params.get("contentChanges").as_array() {
+                        let range = change.get("range");
+                        let range = Position::from_json(range.get("start"))
+                            .zip(Position::from_json(range.get("end")));
+                        document.change(range, change.get("text").as_str().unwrap_or_default());
+                    }
+                }
+
+                self.publish_diagnostics(uri)
+            }
+            "textDocument/didClose" => {
+                let uri = params
+                    .get("textDocument")
+                    .get("uri")
+                    .as_str()
+                    .unwrap_or_default();
+                self.documents.remove(uri);
+
+                vec![diagnostics_notification(uri, Json::Array(vec![]))]
+            }
+            "textDocument/completion" => {
+                let result = self.at_position(params, Document::completions);
+                Self::response(id, result.unwrap_or(Json::Null))
+            }
+            "textDocument/definition" => {
+                let uri = params.get("textDocument").get("uri").clone();
+                let result = self
+                    .at_position(params, Document::definition)
+                    .flatten()
+                    .map(|range| Json::object(vec![("uri", uri), ("range", range)]));
+                Self::response(id, result.unwrap_or(Json::Null))
+            }
+            "textDocument/hover" => {
+                let result = self.at_position(params, Document::hover).flatten();
+                Self::response(id, result.unwrap_or(Json::Null))
+            }
+            _ if !id.is_null() => Self::error_response(id, METHOD_NOT_FOUND, "unknown method"),
+            // notifications the server doesn't care about
+            _ => vec![],
+        }
+    }
+
+    /// Calls the function with the document and position a request is about
+    fn at_position<T>(&self, params: &Json, function: fn(&Document, Position) -> T) -> Option<T> {
+        let uri = params.get("textDocument").get("uri").as_str()?;
+        let position = Position::from_json(params.get("position"))?;
+
+        Some(function(self.documents.get(uri)?, position))
+    }
+
+    fn publish_diagnostics(&self, uri: &str) -> Vec<Json> {
+        match self.documents.get(uri) {
+            Some(document) => vec![diagnostics_notification(
+                uri,
+                document.diagnostics(uri_path(uri).as_deref()),
+            )],
+            None => vec![],
+        }
+    }
+
+    fn response(id: &Json, result: Json) -> Vec<Json> {
+        vec![Json::object(vec![
+            ("jsonrpc", Json::string("2.0")),
+            ("id", id.clone()),
+            ("result", result),
+        ])]
+    }
+
+    fn error_response(id: &Json, code: i32, message: &str) -> Vec<Json> {
+        vec![Json::object(vec![
+            ("jsonrpc", Json::string("2.0")),
+            ("id", id.clone()),
+            (
+                "error",
+                Json::object(vec![
+                    ("code", Json::Number(f64::from(code))),
+                    ("message", Json::string(message)),
+                ]),
+            ),
+        ])]
+    }
+}
+
+fn capabilities() -> Json {
+    Json::object(vec![
+        (
+            "capabilities",
+            Json::object(vec![
+                ("textDocumentSync", Json::number(INCREMENTAL_SYNC)),
+                ("completionProvider", Json::object(vec![])),
+                ("definitionProvider", Json::Bool(true)),
+                ("hoverProvider", Json::Bool(true)),
+            ]),
+        ),
+        (
+            "serverInfo",
+            Json::object(vec![("name", Json::string("przntr"))]),
+        ),
+    ])
+}
+
+fn diagnostics_notification(uri: &str, diagnostics: Json) -> Json {
+    Json::object(vec![
+        ("jsonrpc", Json::string("2.0")),
+        ("method", Json::string("textDocument/publishDiagnostics")),
+        (
+            "params",
+            Json::object(vec![
+                ("uri", Json::string(uri)),
+                ("diagnostics", diagnostics),
+            ]),
+        ),
+    ])
+}
+
+/// Path of a `file://` URI, with percent escapes decoded
+fn uri_path(uri: &str) -> Option<PathBuf> {
+    let encoded = uri.strip_prefix("file://")?.as_bytes();
+    let mut bytes = vec![];
+    let mut index = 0;
+
+    while index < encoded.len() {
+        let escaped = encoded
+            .get(index + 1..index + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match escaped {
+            Some(byte) if encoded[index] == b'%' => {
+                bytes.push(byte);
+                index += 3;
+            }
+            _ => {
+                bytes.push(encoded[index]);
+                index += 1;
+            }
+        }
+    }
+
+    String::from_utf8(bytes).ok().map(PathBuf::from)
+}
+
+/// Serves the client talking over the streams until it asks to exit or
+/// closes the input
+pub fn run(mut input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+    let mut server = Server::new();
+
+    while let Some(body) = read_message(&mut input)? {
+        let replies = match Json::parse(&body) {
+            Ok(message) => server.handle(&message),
+            Err(error) => Server::error_response(&Json::Null, PARSE_ERROR, &error),
+        };
+        for reply in replies {
+            write_message(&mut output, &reply)?;
+        }
+
+        if server.has_exited() {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads the headers and the body of a message, nothing at the end of the
+/// input
+fn read_message(input: &mut impl BufRead) -> io::Result<Option<String>> {
+    let mut length = None;
+
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some(value) = header.strip_prefix("Content-Length:") {
+            length = value.trim().parse().ok();
+        }
+    }
+
+    let length = length.ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "message without a Content-Length",
+        )
+    })?;
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+
+    String::from_utf8(body)
+        .map(Some)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+}
+
+fn write_message(output: &mut impl Write, message: &Json) -> io::Result<()> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+
+    output.flush()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn framed(messages: &[&str]) -> Vec<u8> {
+        messages
+            .iter()
+            .map(|message| format!("Content-Length: {}\r\n\r\n{}", message.len(), message))
+            .collect::<Vec<String>>()
+            .concat()
+            .into_bytes()
+    }
+
+    #[test]
+    pub fn answers_over_the_streams_until_exit() {
+        let input = framed(&[
+            r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#,
+            r#"{"jsonrpc":"2.0","method":"exit"}"#,
+            r#"{"jsonrpc":"2.0","id":2,"method":"shutdown"}"#,
+        ]);
+        let mut output = vec![];
+
+        run(&input[..], &mut output).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("Content-Length: "));
+        assert!(output.contains(r#""id":1,"result":{"capabilities":"#));
+        assert!(!output.contains(r#""id":2"#));
+    }
+
+    #[test]
+    pub fn publishes_diagnostics_for_open_documents() {
+        let mut server = Server::new();
+        let open = Json::parse(
+            r#"{"jsonrpc":"2.0","method":"textDocument/didOpen","params":{"textDocument":
+            {"uri":"untitled:deck","languageId":"przntr","version":1,"text":"metadata { title \"t\" }\nslide \"a\" {\n txt\n}"}}}"#,
+        )
+        .unwrap();
+        let change = Json::parse(
+            r#"{"jsonrpc":"2.0","method":"textDocument/didChange","params":{"textDocument":
+            {"uri":"untitled:deck","version":2},"contentChanges":[{"range":{"start":{"line":2,
+            "character":1},"end":{"line":2,"character":4}},"text":"text \"b\""}]}}"#,
+        )
+        .unwrap();
+
+        let opened = server.handle(&open);
+        assert_eq!(
+            1,
+            opened[0].get("params").get("diagnostics").as_array().len()
+        );
+
+        let changed = server.handle(&change);
+        assert_eq!(
+            Some("textDocument/publishDiagnostics"),
+            changed[0].get("method").as_str()
+        );
+        assert!(changed[0]
+            .get("params")
+            .get("diagnostics")
+            .as_array()
+            .is_empty());
+    }
+
+    #[test]
+    pub fn rejects_unknown_requests_and_requests_after_shutdown() {
+        let mut server = Server::new();
+        let request = |id: u32, method: &str| {
+            Json::object(vec![
+                ("jsonrpc", Json::string("2.0")),
+                ("id", Json::number(id)),
+                ("method", Json::string(method)),
+            ])
+        };
+
+        let unknown = server.handle(&request(1, "workspace/symbol"));
+        assert_eq!(
+            &Json::Number(f64::from(METHOD_NOT_FOUND)),
+            unknown[0].get("error").get("code")
+        );
+
+        server.handle(&request(2, "shutdown"));
+        let after = server.handle(&request(3, "textDocument/hover"));
+        assert!(!after[0].get("error").is_null());
+    }
+
+    #[test]
+    pub fn decodes_file_uris() {
+        assert_eq!(
+            Some(PathBuf::from("/talks/my deck.prz")),
+            uri_path("file:///talks/my%20deck.prz")
+        );
+        assert_eq!(None, uri_path("untitled:deck"));
+    }
+}
@@ -6,35 +6,723 @@
 #![allow(clippy::cast_possible_truncation)]
 #![allow(clippy::multiple_crate_versions)]
 
-use crate::event_loop::EventLoop;
-use crate::parsing::parser::Parser;
-use parsing::tokenizer::Tokenizer;
+use crate::cli::{Arguments, Command, ExportFormat, InputFormat, OverviewKey, Start};
+use przntr::assets::{self, AssetLoader};
+use przntr::auto_advance::{AutoAdvance, ScheduledAdvance};
+use przntr::bundle::{self, Unpacked};
+use przntr::config::Config;
+use przntr::control::{self, Action};
+use przntr::event_loop::{EventLoop, OnLoop};
+use przntr::navigation::Navigation;
+use przntr::notes::ConsoleNotes;
+use przntr::parsing::parser::Error;
+use przntr::playback::Playback;
+use przntr::pointer::Pointer;
+use przntr::presentation::{Presentation, Section, Slide, Theme};
+use przntr::rehearsal::{self, Rehearsal, Timings};
+use przntr::rendering::offscreen::Offscreen;
+use przntr::rendering::presenter::PresenterView;
+use przntr::rendering::slide_renderer::SlideRenderer;
+use przntr::session::{Autosave, LastPosition, Session};
+use przntr::spelling::{self, Dictionary};
+use przntr::talk_timer::TalkTimer;
+use przntr::watch::{PendingReload, Watcher};
+use przntr::{diagnostics, export, parsing, rendering, validation};
+use sdl2::keyboard::Keycode;
+use sdl2::ttf::Sdl2TtfContext;
+use sdl2::Sdl;
+use std::cell::RefCell;
 use std::fs;
+use std::io;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::rc::Rc;
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, Instant};
 
-mod event_loop;
-mod parsing;
-mod presentation;
-mod rendering;
+mod cli;
 
 fn main() {
-    let mut args = std::env::args();
+    let mut arguments = Arguments::parse(std::env::args().skip(1)).unwrap_or_else(|error| {
+        eprintln!("error: {}\n\n{}", error, cli::USAGE);
+        process::exit(2)
+    });
+    if let Command::Help = arguments.command() {
+        print!("{}", cli::USAGE);
 
-    args.next();
+        return;
+    }
+    przntr::logging::init(arguments.log_level()).expect("Failed to set up logging");
+    let config = Config::load().unwrap_or_else(|error| {
+        eprintln!("error: {}", error);
+        process::exit(1)
+    });
+    arguments.apply_config(&config);
+    // removes the bundled fonts once the presentation is no longer shown
+    let _extracted_fonts = przntr::ExtractedFonts;
 
-    let sdl_context = sdl2::init().expect("Failed to initialize SDL2");
+    match arguments.command() {
+        Command::LanguageServer => {
+            let stdin = io::stdin();
+            przntr::lsp::run(stdin.lock(), io::stdout()).expect("Failed to talk to the editor");
+
+            return;
+        }
+        Command::Import => {
+            import(arguments.presentation_path());
+
+            return;
+        }
+        _ => {}
+    }
+
+    // bundles are unpacked and loaded like any other presentation, the
+    // session is still kept for the bundle itself
+    let unpacked = Some(arguments.presentation_path())
+        .filter(|path| bundle::is_bundle(path))
+        .map(|path| {
+            bundle::unpack(path).unwrap_or_else(|error| {
+                eprintln!("error: {}", error);
+                process::exit(1)
+            })
+        });
+    let source = Source::new(&arguments, &config, unpacked.as_ref());
+    let presentation = Rc::new(source.load().unwrap_or_else(|message| {
+        eprint!("{}", message);
+        process::exit(1)
+    }));
+
+    match arguments.command() {
+        Command::Check => {
+            check(source.path, &presentation);
+            if let Some(language) = arguments.spell() {
+                spell_check(source.path, source.input_format, &presentation, language);
+            }
+        }
+        Command::Format => {
+            if source.from_bundle {
+                eprintln!(
+                    "error: bundles can't be formatted, format the presentation they were made of"
+                );
+                process::exit(1);
+            }
+            format(source.path, source.input_format);
+        }
+        Command::Bundle { output } => {
+            bundle::write(source.path, &presentation, &source.assets, output).unwrap_or_else(
+                |error| {
+                    eprintln!("error: {}", error);
+                    process::exit(1)
+                },
+            );
+        }
+        Command::Export {
+            format,
+            output,
+            width,
+            height,
+        } => export(&presentation, *format, output, (*width, *height)),
+        Command::Present => present(&arguments, &config, &source, &presentation),
+        // answered before there is a presentation
+        Command::Help | Command::LanguageServer | Command::Import => {}
+    }
+}
+
+/// Where the presentation is loaded from and how, kept to load it again when
+/// its files change
+struct Source<'a> {
+    path: &'a Path,
+    input_format: InputFormat,
+    theme: Option<&'a PathBuf>,
+    /// Hidden slides are still checked and bundled, they are only left out
+    /// of the talk
+    include_hidden: bool,
+    font_dirs: Vec<PathBuf>,
+    assets: AssetLoader,
+    /// Whether the presentation was unpacked from a bundle, it is never
+    /// written to then
+    from_bundle: bool,
+}
+
+impl<'a> Source<'a> {
+    fn new(arguments: &'a Arguments, config: &Config, unpacked: Option<&'a Unpacked>) -> Self {
+        let (path, input_format) = match unpacked {
+            Some(unpacked) => (
+                unpacked.presentation_path(),
+                InputFormat::for_path(unpacked.presentation_path()),
+            ),
+            None => (arguments.presentation_path(), arguments.input_format()),
+        };
+        let mut font_dirs: Vec<PathBuf> = unpacked
+            .iter()
+            .map(|unpacked| unpacked.font_dir())
+            .collect();
+        font_dirs.extend(config.font_dirs().iter().cloned());
+        let mut assets = AssetLoader::default();
+        assets.set_offline(arguments.offline());
+        if let Some(unpacked) = unpacked {
+            assets.add_read_only_cache(unpacked.cache_dir());
+        }
+
+        Self {
+            path,
+            input_format,
+            theme: arguments.theme(),
+            include_hidden: arguments.include_hidden()
+                || matches!(arguments.command(), Command::Check | Command::Bundle { .. }),
+            font_dirs,
+            assets,
+            from_bundle: unpacked.is_some(),
+        }
+    }
+
+    /// Reads and parses the presentation, styled with the theme file when
+    /// one is given. The configured font directories are searched after the
+    /// presentation's own.
+    fn load(&self) -> Result<Presentation, String> {
+        let path = self.path;
+        let file = fs::read_to_string(path)
+            .map_err(|e| format!("error: could not read {}: {}\n", path.display(), e))?;
+        let theme = self
+            .theme
+            .map(|theme_path| load_theme(theme_path, &self.assets))
+            .transpose()?;
+
+        let mut presentation = match (self.input_format, theme) {
+            (InputFormat::Dsl, theme) => {
+                przntr::parse_file_with(&file, path, theme, self.assets.clone()).map_err(
+                    |errors| diagnostics::render_all(&file, &path.to_string_lossy(), &errors),
+                )?
+            }
+            (InputFormat::Markdown, theme) => {
+                let mut presentation = parsing::markdown::parse(&file);
+                if let Some(theme) = theme {
+                    presentation.apply_theme(theme);
+                }
+                presentation
+            }
+            (InputFormat::Json, theme) => {
+                let mut presentation = parsing::json::parse(&file)
+                    .map_err(|error| format!("error: {}: {}\n", path.display(), error))?;
+                if let Some(theme) = theme {
+                    presentation.apply_theme(theme);
+                }
+                presentation
+            }
+        };
+        // lowest of all, below the presentation's theme and style
+        presentation.apply_theme(Theme::built_in());
+        if !self.include_hidden {
+            presentation.remove_hidden_slides();
+        }
+        for font_dir in &self.font_dirs {
+            presentation
+                .metadata_mut()
+                .add_font_dir(font_dir.to_string_lossy().into_owned());
+        }
+
+        Ok(presentation)
+    }
+}
+
+/// Prints the presentation converted from PPTX or ODP as DSL
+fn import(path: &Path) {
+    let presentation = przntr::import::import(path).unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        process::exit(1)
+    });
+    print!("{}", presentation.to_source());
+}
+
+/// Writes the slides in the format, at the size asked for or the
+/// presentation's own
+fn export(
+    presentation: &Rc<Presentation>,
+    format: ExportFormat,
+    output: &Path,
+    (width, height): (Option<u32>, Option<u32>),
+) {
     let sdl_ttf_context = sdl2::ttf::init().expect("Failed to initialize SDL2 ttf");
-    let file = fs::read_to_string(
-        args.next()
-            .expect("Missing argument (path to the presentation)"),
+    let resolution = presentation.metadata().resolution();
+    let offscreen = || {
+        Offscreen::new(
+            SlideRenderer::new(&sdl_ttf_context, Rc::clone(presentation))
+                .expect("Failed to load the presentation fonts"),
+            width.unwrap_or_else(|| resolution.width()),
+            height.unwrap_or_else(|| resolution.height()),
+        )
+    };
+
+    match format {
+        ExportFormat::Pdf => export::pdf::export(presentation, &offscreen(), output),
+        ExportFormat::Png => export::png::export(presentation, &offscreen(), output),
+        ExportFormat::Html => export::html::export(presentation, output),
+        ExportFormat::Handout => export::handout::export(presentation, &offscreen(), output),
+        ExportFormat::Reveal => export::reveal::export(presentation, output),
+        ExportFormat::Marp => export::marp::export(presentation, output),
+        ExportFormat::Pptx => export::pptx::export(presentation, &offscreen(), output),
+        ExportFormat::Json => export::json::export(presentation, output),
+    }
+    .expect("Failed to export the presentation");
+}
+
+/// What the windows and everything else going on during the talk share
+struct Talk {
+    sdl: Sdl,
+    sdl_ttf: Sdl2TtfContext,
+    /// The presentation as last reloaded
+    presentation: RefCell<Rc<Presentation>>,
+    pending_reload: RefCell<PendingReload>,
+    navigation: RefCell<Navigation>,
+    pointer: RefCell<Pointer>,
+    timer: RefCell<TalkTimer>,
+    playback: RefCell<Playback>,
+    session_path: PathBuf,
+    restored_session: Session,
+}
+
+impl Talk {
+    /// Starts the talk where asked to, where it was left off last time or
+    /// where the restored session was
+    fn new(arguments: &Arguments, presentation: &Rc<Presentation>) -> Self {
+        let sdl_ttf = sdl2::ttf::init().expect("Failed to initialize SDL2 ttf");
+        let start = arguments
+            .start()
+            .map(|start| find_start(presentation, start))
+            .or_else(|| {
+                Session::last_position_path_for(arguments.presentation_path())
+                    .filter(|_| arguments.resume())
+                    .and_then(|path| {
+                        LastPosition::load(&path).unwrap_or_else(|error| {
+                            log::warn!("Could not read the last position: {:?}", error);
+                            None
+                        })
+                    })
+            });
+        let sdl = sdl2::init().expect("Failed to initialize SDL2");
+
+        let session_path = Session::path_for(arguments.presentation_path());
+        let restored_session = Session::load(&session_path)
+            .unwrap_or_else(|error| {
+                log::warn!(
+                    "Could not read the session file, starting afresh: {:?}",
+                    error
+                );
+                None
+            })
+            .filter(|_| ask_to_restore_session())
+            .unwrap_or_default();
+
+        let mut pointer = Pointer::new();
+        if let Some(color) = arguments.pointer_color() {
+            pointer.set_color(color);
+        }
+        // wiped like any other strokes when the talk starts on another slide
+        pointer.restore_strokes(
+            restored_session.current_slide(),
+            restored_session.strokes().clone(),
+        );
+        // a restored session's time was already spent on the talk
+        let started_at = Instant::now()
+            .checked_sub(restored_session.elapsed())
+            .unwrap_or_else(Instant::now);
+
+        Self {
+            sdl,
+            sdl_ttf,
+            presentation: RefCell::new(Rc::clone(presentation)),
+            pending_reload: RefCell::new(None),
+            navigation: RefCell::new(navigation_for(
+                presentation,
+                start.unwrap_or_else(|| restored_session.current_slide()),
+            )),
+            pointer: RefCell::new(pointer),
+            timer: RefCell::new(TalkTimer::new(started_at, arguments.talk_length())),
+            playback: RefCell::new(Playback::new(Instant::now())),
+            session_path,
+            restored_session,
+        }
+    }
+}
+
+/// Shows the presentation until the window is closed, then reports the
+/// rehearsal when there is one
+fn present(
+    arguments: &Arguments,
+    config: &Config,
+    source: &Source,
+    presentation: &Rc<Presentation>,
+) {
+    // checked before the talk rather than after it
+    if arguments.store_targets() && (source.from_bundle || source.input_format != InputFormat::Dsl)
+    {
+        eprintln!("error: targets can only be stored in presentations written in the DSL");
+        process::exit(1);
+    }
+
+    let talk = Talk::new(arguments, presentation);
+    let rehearsal = Some(Rehearsal::new(&talk.navigation, Instant::now()))
+        .filter(|_| arguments.rehearse())
+        .map(|mut rehearsal| {
+            rehearsal.resume(talk.restored_session.rehearsal().clone());
+            RefCell::new(rehearsal)
+        });
+    show(&talk, arguments, config, source, rehearsal.as_ref());
+
+    Session::discard(&talk.session_path).expect("Failed to remove the session file");
+    if let Some(rehearsal) = &rehearsal {
+        // the presentation as it was last reloaded during the talk
+        let presentation = Rc::clone(&talk.presentation.borrow());
+        let timings = rehearsal.borrow().finish(&presentation, Instant::now());
+        finish_rehearsal(
+            arguments,
+            source.path,
+            &presentation,
+            &timings,
+            Timings::path_for(arguments.presentation_path()).as_deref(),
+        );
+    }
+}
+
+/// Opens the windows and runs the talk until they are closed
+fn show<'a>(
+    talk: &'a Talk,
+    arguments: &Arguments,
+    config: &Config,
+    source: &Source,
+    rehearsal: Option<&'a RefCell<Rehearsal<'a>>>,
+) {
+    let mut watcher = Watcher::new(
+        source.path.to_path_buf(),
+        &talk.presentation.borrow(),
+        Duration::from_millis(500),
+        || source.load(),
+        &talk.pending_reload,
+    );
+    let mut r = rendering::renderer::SDL2::new(
+        &talk.sdl,
+        &talk.sdl_ttf,
+        &talk.presentation,
+        &talk.navigation,
+        &talk.pending_reload,
+        arguments.window_options(),
     )
-    .expect("Failed to read the presentation file");
+    .unwrap_or_else(|error| {
+        eprintln!("error: {}", error);
+        process::exit(1)
+    });
+
+    talk.pointer.borrow_mut().set_window_id(r.window_id());
+    r.set_pointer(&talk.pointer);
+    r.set_timer(&talk.timer);
+    r.set_playback(&talk.playback);
+
+    let mut auto_advance = AutoAdvance::new(
+        &talk.presentation,
+        &talk.navigation,
+        arguments.auto_advance(),
+    );
+    let mut notes = ConsoleNotes::new(&talk.presentation, &talk.navigation);
+    let mut presenter_view = Some(talk)
+        .filter(|_| arguments.presenter_view())
+        .map(|talk| open_presenter_view(talk, arguments));
+    // a reload may schedule slides when there were none
+    let mut scheduled_advance = Some(ScheduledAdvance::new(
+        &talk.presentation,
+        &talk.navigation,
+        Instant::now(),
+    ))
+    .filter(|_| arguments.strict_schedule());
+
+    let mut autosave = Autosave::new(
+        talk.session_path.clone(),
+        Duration::from_secs(5),
+        &talk.navigation,
+        &talk.restored_session,
+    );
+    autosave.set_pointer(&talk.pointer);
+    let mut rehearsing = rehearsal;
+    if let Some(rehearsal) = rehearsing {
+        autosave.set_rehearsal(rehearsal);
+    }
+    let mut last_position = Session::last_position_path_for(arguments.presentation_path())
+        .map(|path| LastPosition::new(path, &talk.navigation));
+
+    let mut onloops: Vec<&mut dyn OnLoop> =
+        vec![&mut r, &mut autosave, &mut notes, &mut auto_advance];
+    if let Some(last_position) = &mut last_position {
+        onloops.push(last_position);
+    }
+    if arguments.watch() {
+        onloops.push(&mut watcher);
+    }
+    if let Some(presenter_view) = &mut presenter_view {
+        onloops.push(presenter_view);
+    }
+    if let Some(rehearsal) = &mut rehearsing {
+        onloops.push(rehearsal);
+    }
+    if let Some(scheduled_advance) = &mut scheduled_advance {
+        onloops.push(scheduled_advance);
+    }
+
+    let mut ev_loop = EventLoop::new(&talk.sdl, &talk.navigation, onloops);
+    ev_loop.set_overview_keys(vec![match arguments.overview_key() {
+        OverviewKey::O => Keycode::O,
+        OverviewKey::Escape => Keycode::Escape,
+    }]);
+    ev_loop.set_volume_keys(arguments.volume_keys());
+    ev_loop.set_key_bindings(key_bindings(config));
+    ev_loop.set_pointer(&talk.pointer);
+    ev_loop.set_timer(&talk.timer);
+    ev_loop.set_playback(&talk.playback);
+    if let Some(control) =
+        listen_for_control(&talk.presentation.borrow(), arguments.control_address())
+    {
+        ev_loop.set_control(control);
+    }
+    ev_loop.run().unwrap_or_else(|error| {
+        eprintln!("error: {}", error);
+        process::exit(1)
+    });
+}
+
+/// Opens the second window for the speaker, paced by the last rehearsal
+fn open_presenter_view<'a>(talk: &'a Talk, arguments: &Arguments) -> PresenterView<'a> {
+    let mut presenter_view = PresenterView::new(
+        &talk.sdl,
+        &talk.sdl_ttf,
+        &talk.presentation,
+        &talk.navigation,
+        arguments.window_options().display(),
+    )
+    .expect("Failed to open the presenter view");
+    let rehearsed = Timings::path_for(arguments.presentation_path()).and_then(|path| {
+        Timings::load(&path).unwrap_or_else(|error| {
+            log::warn!("Could not read the last rehearsal: {:?}", error);
+            None
+        })
+    });
+    if let Some(rehearsed) = rehearsed {
+        presenter_view.set_rehearsed(rehearsed);
+    }
+
+    presenter_view
+}
+
+/// Navigation through the presentation's slides, steps and sections, at the
+/// slide
+fn navigation_for(presentation: &Presentation, slide: usize) -> Navigation {
+    let mut navigation = Navigation::new(presentation.slides().len());
+    navigation.set_step_counts(
+        presentation
+            .slides()
+            .iter()
+            .map(Slide::step_count)
+            .collect(),
+    );
+    navigation.set_section_starts(
+        presentation
+            .sections()
+            .iter()
+            .map(Section::first_slide)
+            .collect(),
+    );
+    navigation.go_to(slide);
+
+    navigation
+}
+
+/// Prints the time spent on each slide and keeps it for the presenter view
+/// of later runs, writing it into the presentation as the slides' targets
+/// when asked to
+fn finish_rehearsal(
+    arguments: &Arguments,
+    presentation_path: &Path,
+    presentation: &Presentation,
+    timings: &Timings,
+    timings_path: Option<&Path>,
+) {
+    print!("{}", timings.report(presentation));
+    if let Some(path) = timings_path {
+        if let Err(error) = timings.save(path) {
+            log::warn!("Could not save the rehearsal: {:?}", error);
+        }
+    }
+
+    if arguments.store_targets() {
+        let file = fs::read_to_string(presentation_path).expect("Failed to read the presentation");
+        let stored = rehearsal::store_targets(&file, timings).unwrap_or_else(|failure| {
+            eprint!(
+                "{}",
+                diagnostics::render(
+                    &file,
+                    &presentation_path.to_string_lossy(),
+                    &Error::TokenizerFailure(failure)
+                )
+            );
+            process::exit(1)
+        });
+        fs::write(presentation_path, stored).expect("Failed to write the targets");
+    }
+}
+
+/// Index of the slide the talk starts at, exits with a failure when the
+/// presentation has no such slide or section
+fn find_start(presentation: &Presentation, start: &Start) -> usize {
+    let found = match start {
+        Start::Slide(index) => Ok(*index),
+        Start::SlideNamed(name) => presentation
+            .slide_named(name)
+            .ok_or_else(|| format!("the presentation has no slide named `{}`", name)),
+        Start::Section(title) => presentation
+            .section_titled(title)
+            .map(Section::first_slide)
+            .ok_or_else(|| format!("the presentation has no section `{}`", title)),
+    };
+
+    found.unwrap_or_else(|message| {
+        eprintln!("error: {}", message);
+        process::exit(1)
+    })
+}
+
+/// Keys the config binds to actions, keys SDL doesn't know are reported and
+/// left out
+fn key_bindings(config: &Config) -> Vec<(Keycode, Action)> {
+    config
+        .keys()
+        .iter()
+        .filter_map(|(name, action)| {
+            let keycode = Keycode::from_name(name);
+            if keycode.is_none() {
+                log::warn!("Unknown key `{}` bound to {}", name, action.name());
+            }
+
+            Some((keycode?, *action))
+        })
+        .collect()
+}
+
+/// Prints problems the renderer would run into and exits with a failure if
+/// there are any
+fn check(path: &Path, presentation: &Presentation) {
+    let errors = validation::validate(presentation);
+
+    for error in &errors {
+        eprint!(
+            "{}",
+            diagnostics::render_validation(&path.to_string_lossy(), error)
+        );
+    }
+
+    if !errors.is_empty() {
+        process::exit(1);
+    }
+
+    println!("{}: no problems found", path.display());
+}
+
+/// Prints the words the dictionary doesn't know and exits with a failure if
+/// there are any
+fn spell_check(
+    path: &Path,
+    input_format: InputFormat,
+    presentation: &Presentation,
+    language: &str,
+) {
+    let dictionary = Dictionary::load(language).unwrap_or_else(|message| {
+        eprintln!("error: {}", message);
+        process::exit(1)
+    });
+    let file = fs::read_to_string(path).expect("Failed to read the presentation");
+    // locations are only found in the DSL
+    let source = Some(file.as_str()).filter(|_| input_format == InputFormat::Dsl);
+    let misspellings = spelling::check(presentation, &dictionary, source);
+
+    for misspelling in &misspellings {
+        eprint!(
+            "{}",
+            diagnostics::render_misspelling(&file, &path.to_string_lossy(), misspelling)
+        );
+    }
+
+    if !misspellings.is_empty() {
+        process::exit(1);
+    }
+
+    println!("{}: no misspellings found", path.display());
+}
+
+/// Rewrites the presentation in the canonical layout, the presentation was
+/// parsed before so files with errors are left alone
+fn format(path: &Path, input_format: InputFormat) {
+    if input_format != InputFormat::Dsl {
+        eprintln!("error: only presentations written in the DSL can be formatted");
+        process::exit(1);
+    }
+
+    let file = fs::read_to_string(path).expect("Failed to read the presentation");
+    let formatted = parsing::formatter::format(&file).unwrap_or_else(|failure| {
+        eprint!(
+            "{}",
+            diagnostics::render(
+                &file,
+                &path.to_string_lossy(),
+                &Error::TokenizerFailure(failure)
+            )
+        );
+        process::exit(1)
+    });
+
+    if formatted != file {
+        fs::write(path, formatted).expect("Failed to write the formatted presentation");
+    }
+}
+
+/// Reads and parses the theme file, downloading it when it is named by URL
+fn load_theme(path: &Path, assets: &AssetLoader) -> Result<Theme, String> {
+    let name = path.to_string_lossy();
+    let path = if assets::is_url(&name) {
+        assets
+            .load(&name)
+            .map_err(|error| format!("error: {}\n", error))?
+    } else {
+        path.to_path_buf()
+    };
+    let file = fs::read_to_string(&path)
+        .map_err(|e| format!("error: could not read {}: {}\n", path.display(), e))?;
+
+    przntr::parse_theme_file(&file, &path, assets.clone())
+        .map_err(|errors| diagnostics::render_all(&file, &path.to_string_lossy(), &errors))
+}
+
+/// Starts the OSC and MIDI listeners the presentation asks for, inputs
+/// that can't be opened are reported and left out
+fn listen_for_control(presentation: &Presentation, address: IpAddr) -> Option<Receiver<Action>> {
+    let controls = presentation.metadata().controls();
+    if controls.is_empty() {
+        return None;
+    }
+
+    let (sender, receiver) = mpsc::channel();
+    for input in controls {
+        if let Err(message) = control::listen(input, address, sender.clone()) {
+            log::warn!("{}", message);
+        }
+    }
+
+    Some(receiver)
+}
 
-    let mut t = Tokenizer::new(&file);
-    let mut p = Parser::new(&mut t);
+fn ask_to_restore_session() -> bool {
+    println!("przntr did not exit cleanly last time. Restore the previous session? [y/N]");
 
-    let presentation = p.parse().expect("Presentation was not parsed correctly");
-    let mut r = rendering::renderer::SDL2::new(&sdl_context, &sdl_ttf_context, &presentation);
+    let mut answer = String::new();
+    io::stdin()
+        .read_line(&mut answer)
+        .expect("Failed to read the answer");
 
-    let mut ev_loop = EventLoop::new(&sdl_context, vec![&mut r]);
-    ev_loop.run();
+    answer.trim().eq_ignore_ascii_case("y")
 }
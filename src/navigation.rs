@@ -0,0 +1,410 @@
+#[derive(Debug, Eq, PartialEq)]
+pub struct Navigation {
+    current_slide: usize,
+    current_step: u32,
+    slide_count: usize,
+    step_counts: Vec<u32>,
+    /// Slide number typed so far, counting from 1
+    typed_number: Option<usize>,
+    /// Slide highlighted in the overview, while the overview is open
+    overview_selection: Option<usize>,
+    /// First slide of every section
+    section_starts: Vec<usize>,
+    /// Section highlighted in the section menu, while the menu is open
+    section_selection: Option<usize>,
+}
+
+impl Navigation {
+    pub fn new(slide_count: usize) -> Self {
+        Self {
+            current_slide: 0,
+            current_step: 0,
+            slide_count,
+            step_counts: vec![],
+            typed_number: None,
+            overview_selection: None,
+            section_starts: vec![],
+            section_selection: None,
+        }
+    }
+
+    pub fn current_slide(&self) -> usize {
+        self.current_slide
+    }
+
+    /// Number of build steps of the current slide revealed so far
+    pub fn current_step(&self) -> u32 {
+        self.current_step
+    }
+
+    /// Reveals the next build step, or moves to the next slide once every
+    /// step of the current one is shown
+    pub fn next(&mut self) {
+        self.typed_number = None;
+        if self.current_step < self.step_count(self.current_slide) {
+            self.current_step += 1;
+        } else if self.current_slide + 1 < self.slide_count {
+            self.go_to(self.current_slide + 1);
+        }
+    }
+
+    /// Hides the last revealed build step, or moves to the previous slide
+    /// with all of its steps revealed
+    pub fn previous(&mut self) {
+        self.typed_number = None;
+        if self.current_step > 0 {
+            self.current_step -= 1;
+        } else if self.current_slide > 0 {
+            self.go_to(self.current_slide - 1);
+            self.current_step = self.step_count(self.current_slide);
+        }
+    }
+
+    /// Updates the number of slides after the presentation was reloaded,
+    /// staying on the current slide if it still exists
+    pub fn set_slide_count(&mut self, slide_count: usize) {
+        self.slide_count = slide_count;
+        self.current_slide = self.current_slide.min(self.slide_count.saturating_sub(1));
+    }
+
+    /// Sets the number of build steps of every slide, keeping as much of the
+    /// current slide revealed as still exists
+    pub fn set_step_counts(&mut self, step_counts: Vec<u32>) {
+        self.step_counts = step_counts;
+        self.current_step = self.current_step.min(self.step_count(self.current_slide));
+    }
+
+    /// Moves to the given slide, clamping the index to the last slide
+    pub fn go_to(&mut self, slide: usize) {
+        self.current_slide = slide.min(self.slide_count.saturating_sub(1));
+        self.current_step = 0;
+        self.typed_number = None;
+    }
+
+    pub fn first(&mut self) {
+        self.go_to(0);
+    }
+
+    pub fn last(&mut self) {
+        self.go_to(self.slide_count.saturating_sub(1));
+    }
+
+    /// Slide number being typed, waiting to be confirmed
+    pub fn typed_number(&self) -> Option<usize> {
+        self.typed_number
+    }
+
+    /// Appends a digit to the slide number being typed
+    pub fn type_digit(&mut self, digit: usize) {
+        self.typed_number = Some(
+            self.typed_number
+                .unwrap_or(0)
+                .saturating_mul(10)
+                .saturating_add(digit),
+        );
+    }
+
+    /// Moves to the typed slide number, if there is one
+    pub fn confirm_typed_number(&mut self) {
+        if let Some(number) = self.typed_number {
+            self.go_to(number.saturating_sub(1));
+        }
+    }
+
+    /// Forgets the typed slide number, returns false if there was none
+    pub fn cancel_typed_number(&mut self) -> bool {
+        self.typed_number.take().is_some()
+    }
+
+    /// Shows every slide at once, starting with the current one selected
+    pub fn open_overview(&mut self) {
+        self.typed_number = None;
+        self.overview_selection = Some(self.current_slide);
+    }
+
+    /// Leaves the overview without moving, returns false if it wasn't open
+    pub fn close_overview(&mut self) -> bool {
+        self.overview_selection.take().is_some()
+    }
+
+    pub fn overview_selection(&self) -> Option<usize> {
+        self.overview_selection
+    }
+
+    /// Number of slides in each row of the overview, enough to make the
+    /// grid about as tall as it is wide
+    pub fn overview_columns(&self) -> usize {
+        (1..=self.slide_count)
+            .find(|columns| columns * columns >= self.slide_count)
+            .unwrap_or(1)
+    }
+
+    /// Moves the overview selection by the given number of slides, moves
+    /// past the first or the last slide are ignored
+    pub fn move_selection(&mut self, offset: isize) {
+        if let Some(selection) = self.overview_selection {
+            let target = selection as isize + offset;
+            if target >= 0 && (target as usize) < self.slide_count {
+                self.overview_selection = Some(target as usize);
+            }
+        }
+    }
+
+    /// Leaves the overview for the selected slide
+    pub fn confirm_selection(&mut self) {
+        if let Some(selection) = self.overview_selection.take() {
+            self.go_to(selection);
+        }
+    }
+
+    /// Sets where each section of the presentation starts, closing the
+    /// section menu
+    pub fn set_section_starts(&mut self, section_starts: Vec<usize>) {
+        self.section_starts = section_starts;
+        self.section_selection = None;
+    }
+
+    /// Index of the section the current slide is in, the first one for
+    /// slides before any section
+    fn current_section(&self) -> usize {
+        self.section_starts
+            .iter()
+            .rposition(|&start| start <= self.current_slide)
+            .unwrap_or(0)
+    }
+
+    /// Lists the sections to jump to, starting with the current one
+    /// selected, there is nothing to open without sections
+    pub fn open_section_menu(&mut self) {
+        if !self.section_starts.is_empty() {
+            self.typed_number = None;
+            self.section_selection = Some(self.current_section());
+        }
+    }
+
+    /// Leaves the section menu without moving, returns false if it wasn't
+    /// open
+    pub fn close_section_menu(&mut self) -> bool {
+        self.section_selection.take().is_some()
+    }
+
+    pub fn section_selection(&self) -> Option<usize> {
+        self.section_selection
+    }
+
+    /// Moves the section menu selection, moves past the first or the last
+    /// section are ignored
+    pub fn move_section_selection(&mut self, offset: isize) {
+        if let Some(selection) = self.section_selection {
+            let target = selection as isize + offset;
+            if target >= 0 && (target as usize) < self.section_starts.len() {
+                self.section_selection = Some(target as usize);
+            }
+        }
+    }
+
+    /// Leaves the section menu for the first slide of the selected section
+    pub fn confirm_section_selection(&mut self) {
+        if let Some(selection) = self.section_selection.take() {
+            if let Some(&start) = self.section_starts.get(selection) {
+                self.go_to(start);
+            }
+        }
+    }
+
+    fn step_count(&self, slide: usize) -> u32 {
+        self.step_counts.get(slide).copied().unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn starts_at_first_slide() {
+        assert_eq!(0, Navigation::new(3).current_slide());
+    }
+
+    #[test]
+    pub fn can_go_forward_and_back() {
+        let mut navigation = Navigation::new(3);
+
+        navigation.next();
+        navigation.next();
+        assert_eq!(2, navigation.current_slide());
+
+        navigation.previous();
+        assert_eq!(1, navigation.current_slide());
+    }
+
+    #[test]
+    pub fn does_not_go_past_last_slide() {
+        let mut navigation = Navigation::new(2);
+
+        navigation.next();
+        navigation.next();
+        assert_eq!(1, navigation.current_slide());
+    }
+
+    #[test]
+    pub fn does_not_go_before_first_slide() {
+        let mut navigation = Navigation::new(2);
+
+        navigation.previous();
+        assert_eq!(0, navigation.current_slide());
+    }
+
+    #[test]
+    pub fn go_to_clamps_to_last_slide() {
+        let mut navigation = Navigation::new(4);
+
+        navigation.go_to(10);
+        assert_eq!(3, navigation.current_slide());
+    }
+
+    #[test]
+    pub fn slide_count_change_keeps_current_slide() {
+        let mut navigation = Navigation::new(5);
+        navigation.go_to(3);
+
+        navigation.set_slide_count(6);
+        assert_eq!(3, navigation.current_slide());
+
+        navigation.set_slide_count(2);
+        assert_eq!(1, navigation.current_slide());
+    }
+
+    #[test]
+    pub fn handles_presentation_without_slides() {
+        let mut navigation = Navigation::new(0);
+
+        navigation.next();
+        assert_eq!(0, navigation.current_slide());
+    }
+
+    #[test]
+    pub fn reveals_steps_before_moving_on() {
+        let mut navigation = Navigation::new(2);
+        navigation.set_step_counts(vec![2, 0]);
+
+        navigation.next();
+        navigation.next();
+        assert_eq!(
+            (0, 2),
+            (navigation.current_slide(), navigation.current_step())
+        );
+
+        navigation.next();
+        assert_eq!(
+            (1, 0),
+            (navigation.current_slide(), navigation.current_step())
+        );
+    }
+
+    #[test]
+    pub fn going_back_shows_all_steps_of_previous_slide() {
+        let mut navigation = Navigation::new(2);
+        navigation.set_step_counts(vec![2, 1]);
+        navigation.go_to(1);
+        navigation.next();
+
+        navigation.previous();
+        assert_eq!(
+            (1, 0),
+            (navigation.current_slide(), navigation.current_step())
+        );
+
+        navigation.previous();
+        assert_eq!(
+            (0, 2),
+            (navigation.current_slide(), navigation.current_step())
+        );
+    }
+
+    #[test]
+    pub fn jumps_to_typed_slide_number() {
+        let mut navigation = Navigation::new(20);
+
+        navigation.type_digit(1);
+        navigation.type_digit(2);
+        assert_eq!(Some(12), navigation.typed_number());
+
+        navigation.confirm_typed_number();
+        assert_eq!(11, navigation.current_slide());
+        assert_eq!(None, navigation.typed_number());
+    }
+
+    #[test]
+    pub fn other_navigation_discards_typed_number() {
+        let mut navigation = Navigation::new(20);
+
+        navigation.type_digit(5);
+        navigation.next();
+        navigation.confirm_typed_number();
+        assert_eq!(1, navigation.current_slide());
+
+        navigation.type_digit(5);
+        assert!(navigation.cancel_typed_number());
+        assert!(!navigation.cancel_typed_number());
+    }
+
+    #[test]
+    pub fn jumps_to_first_and_last_slide() {
+        let mut navigation = Navigation::new(5);
+
+        navigation.last();
+        assert_eq!(4, navigation.current_slide());
+
+        navigation.first();
+        assert_eq!(0, navigation.current_slide());
+    }
+
+    #[test]
+    pub fn moves_overview_selection_within_slides() {
+        let mut navigation = Navigation::new(7);
+        navigation.go_to(2);
+        navigation.open_overview();
+        assert_eq!(3, navigation.overview_columns());
+
+        navigation.move_selection(3);
+        navigation.move_selection(3);
+        assert_eq!(Some(5), navigation.overview_selection());
+
+        navigation.move_selection(-6);
+        assert_eq!(Some(5), navigation.overview_selection());
+
+        navigation.confirm_selection();
+        assert_eq!(5, navigation.current_slide());
+        assert_eq!(None, navigation.overview_selection());
+    }
+
+    #[test]
+    pub fn closing_overview_stays_on_current_slide() {
+        let mut navigation = Navigation::new(4);
+        navigation.open_overview();
+        navigation.move_selection(1);
+
+        assert!(navigation.close_overview());
+        assert!(!navigation.close_overview());
+        assert_eq!(0, navigation.current_slide());
+    }
+
+    #[test]
+    pub fn jumps_to_selected_section() {
+        let mut navigation = Navigation::new(10);
+        navigation.open_section_menu();
+        assert_eq!(None, navigation.section_selection());
+
+        navigation.set_section_starts(vec![0, 4, 7]);
+        navigation.go_to(5);
+        navigation.open_section_menu();
+        assert_eq!(Some(1), navigation.section_selection());
+
+        navigation.move_section_selection(1);
+        navigation.move_section_selection(1);
+        navigation.confirm_section_selection();
+        assert_eq!(7, navigation.current_slide());
+        assert_eq!(None, navigation.section_selection());
+    }
+}
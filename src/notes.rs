@@ -0,0 +1,88 @@
+use crate::event_loop::{Error, OnLoop};
+use crate::navigation::Navigation;
+use crate::presentation::{Presentation, Slide};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Prints the speaker notes of the current slide to the terminal every time
+/// the slide changes, so they can be read on a screen the audience can't see
+pub struct ConsoleNotes<'a> {
+    /// The presentation as last reloaded
+    presentation: &'a RefCell<Rc<Presentation>>,
+    navigation: &'a RefCell<Navigation>,
+    /// Slide whose notes were printed last and the presentation they were
+    /// read from, they are printed again after a reload
+    printed: Option<(usize, Rc<Presentation>)>,
+}
+
+impl<'a> ConsoleNotes<'a> {
+    pub fn new(
+        presentation: &'a RefCell<Rc<Presentation>>,
+        navigation: &'a RefCell<Navigation>,
+    ) -> Self {
+        Self {
+            presentation,
+            navigation,
+            printed: None,
+        }
+    }
+}
+
+impl<'a> OnLoop for ConsoleNotes<'a> {
+    fn run(&mut self) -> Result<(), Error> {
+        let current_slide = self.navigation.borrow().current_slide();
+        let presentation = Rc::clone(&self.presentation.borrow());
+        if let Some((slide, printed_from)) = &self.printed {
+            if *slide == current_slide && Rc::ptr_eq(printed_from, &presentation) {
+                return Ok(());
+            }
+        }
+
+        if let Some(slide) = presentation.slides().get(current_slide) {
+            println!("{}", format_notes(current_slide, slide));
+        }
+        self.printed = Some((current_slide, presentation));
+
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        "speaker notes"
+    }
+}
+
+fn format_notes(index: usize, slide: &Slide) -> String {
+    let mut lines = vec![format!("--- slide {}: {}", index + 1, slide.name())];
+
+    if slide.notes().is_empty() {
+        lines.push("(no notes)".into());
+    } else {
+        lines.extend(slide.notes().iter().cloned());
+    }
+
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn formats_notes_under_slide_header() {
+        let mut slide = Slide::new("intro".into(), vec![]);
+        slide.add_note("say hello".into());
+        slide.add_note("then wave".into());
+
+        assert_eq!(
+            "--- slide 2: intro\nsay hello\nthen wave",
+            format_notes(1, &slide)
+        );
+    }
+
+    #[test]
+    pub fn marks_slides_without_notes() {
+        let slide = Slide::new("intro".into(), vec![]);
+
+        assert_eq!("--- slide 1: intro\n(no notes)", format_notes(0, &slide));
+    }
+}
@@ -0,0 +1,439 @@
+use super::grammar::{self, PropertyRule};
+use super::token_stream::{Token, TokenStream, TokenizerFailure, TokenizerResult};
+use super::tokenizer::Tokenizer;
+
+const INDENT: &str = "    ";
+
+/// Top level items that are followed by a blank line
+const TOP_LEVEL_BLOCKS: &[Token] = &[
+    Token::KeywordMetadata,
+    Token::KeywordStyle,
+    Token::KeywordLayout,
+    Token::KeywordSlide,
+    Token::KeywordSection,
+];
+
+const TOP_LEVEL_ITEMS: &[Token] = &[
+    Token::KeywordMetadata,
+    Token::KeywordStyle,
+    Token::KeywordLayout,
+    Token::KeywordSlide,
+    Token::KeywordSection,
+    Token::KeywordDefine,
+    Token::KeywordInclude,
+];
+
+/// What starts a line in a block besides elements and placeholder groups
+const BLOCK_ITEMS: &[Token] = &[
+    Token::KeywordBackground,
+    Token::KeywordNotes,
+    Token::KeywordTransition,
+    Token::KeywordDuration,
+    Token::KeywordTarget,
+    Token::KeywordStep,
+    Token::KeywordPlaceholder,
+    Token::KeywordFont,
+    Token::KeywordTextStyle,
+    Token::KeywordColor,
+    Token::KeywordHinting,
+    Token::KeywordAntialias,
+    Token::KeywordKerning,
+];
+
+/// Token with the text it was written as
+struct Piece<'a> {
+    token: Token,
+    text: &'a str,
+    /// Lines of the input the text starts and ends on
+    line: usize,
+    end_line: usize,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Separator {
+    None,
+    Space,
+    Line,
+    BlankLine,
+}
+
+/// Rewrites a presentation with one item per line, indented by the blocks
+/// it is in, and the properties of `metadata` and `font` blocks in their
+/// usual order. Comments are kept, as are single blank lines between
+/// items.
+pub fn format(input: &str) -> Result<String, TokenizerFailure> {
+    let pieces = sort_properties(read_pieces(input)?);
+
+    Ok(Printer::new(&pieces).print())
+}
+
+fn read_pieces(input: &str) -> Result<Vec<Piece<'_>>, TokenizerFailure> {
+    let mut tokenizer = Tokenizer::new(input);
+    tokenizer.set_keep_comments(true);
+    let mut pieces = vec![];
+    let mut offset = 0;
+    let mut line = 0;
+
+    loop {
+        match tokenizer.next() {
+            TokenizerResult::Ok(token, _) => {
+                let span = tokenizer.token_span();
+                let text = &input[span.clone()];
+                line += input[offset..span.start].matches('\n').count();
+                offset = span.start;
+
+                pieces.push(Piece {
+                    token,
+                    text,
+                    line,
+                    end_line: line + text.matches('\n').count(),
+                });
+            }
+            TokenizerResult::Err(failure) => return Err(failure),
+            TokenizerResult::End => return Ok(pieces),
+        }
+    }
+}
+
+/// Property of a block with the comments written after its comma, on the
+/// same line
+#[derive(Default)]
+struct Property<'a> {
+    pieces: Vec<Piece<'a>>,
+    comments: Vec<Piece<'a>>,
+}
+
+/// Puts the properties of every `metadata` and `font` block in the order of
+/// their rules, comments before a property move along with it
+fn sort_properties(mut pieces: Vec<Piece<'_>>) -> Vec<Piece<'_>> {
+    let mut index = 0;
+
+    while index + 1 < pieces.len() {
+        let rules = match (&pieces[index].token, &pieces[index + 1].token) {
+            (Token::KeywordMetadata, Token::OpeningBrace) => grammar::METADATA_PROPERTIES,
+            (Token::KeywordFont, Token::OpeningBrace) => grammar::FONT_PROPERTIES,
+            _ => {
+                index += 1;
+                continue;
+            }
+        };
+        let start = index + 2;
+        let end = match closing_brace(&pieces[start..]) {
+            Some(length) => start + length,
+            None => break,
+        };
+
+        let sorted = sort_block(pieces.drain(start..end).collect(), rules);
+        index = end;
+        pieces.splice(start..start, sorted);
+    }
+
+    pieces
+}
+
+/// Index of the brace closing the block the pieces are in
+fn closing_brace(pieces: &[Piece<'_>]) -> Option<usize> {
+    let mut depth = 0;
+
+    for (index, piece) in pieces.iter().enumerate() {
+        match piece.token {
+            Token::OpeningBrace => depth += 1,
+            Token::ClosingBrace if depth == 0 => return Some(index),
+            Token::ClosingBrace => depth -= 1,
+            _ => {}
+        }
+    }
+
+    None
+}
+
+fn sort_block<'a>(pieces: Vec<Piece<'a>>, rules: &[PropertyRule]) -> Vec<Piece<'a>> {
+    let mut properties = vec![Property::default()];
+    let mut commas = vec![];
+    let mut depth = 0;
+
+    for piece in pieces {
+        match piece.token {
+            Token::OpeningBrace => depth += 1,
+            Token::ClosingBrace => depth -= 1,
+            Token::Comma if depth == 0 => {
+                commas.push(piece);
+                properties.push(Property::default());
+                continue;
+            }
+            Token::Comment(_) => {
+                let comma_line = commas.last().map(|comma: &Piece<'_>| comma.line);
+                let count = properties.len();
+                if properties[count - 1].pieces.is_empty() && comma_line == Some(piece.line) {
+                    properties[count - 2].comments.push(piece);
+                    continue;
+                }
+            }
+            _ => {}
+        }
+
+        let count = properties.len();
+        properties[count - 1].pieces.push(piece);
+    }
+
+    // comments after the last property stay last
+    properties.sort_by_key(|property| {
+        property
+            .pieces
+            .iter()
+            .find(|piece| !matches!(piece.token, Token::Comment(_)))
+            .map_or(usize::MAX, |piece| {
+                rules
+                    .iter()
+                    .position(|rule| rule.keyword == piece.token)
+                    .unwrap_or(rules.len())
+            })
+    });
+
+    let mut commas = commas.into_iter();
+    let mut sorted = vec![];
+    for property in properties {
+        sorted.extend(property.pieces);
+        sorted.extend(commas.next());
+        sorted.extend(property.comments);
+    }
+
+    sorted
+}
+
+struct Printer<'p, 'a> {
+    pieces: &'p [Piece<'a>],
+    output: String,
+    depth: usize,
+    /// Element whose properties can follow, at each depth
+    elements: Vec<Option<&'static Token>>,
+    /// Whether the last top level item is a section, which is kept together
+    /// with the slide starting it
+    in_section_header: bool,
+}
+
+impl<'p, 'a> Printer<'p, 'a> {
+    fn new(pieces: &'p [Piece<'a>]) -> Self {
+        Self {
+            pieces,
+            output: String::new(),
+            depth: 0,
+            elements: vec![None],
+            in_section_header: false,
+        }
+    }
+
+    fn print(mut self) -> String {
+        for (index, piece) in self.pieces.iter().enumerate() {
+            if piece.token == Token::ClosingBrace {
+                self.depth = self.depth.saturating_sub(1);
+                self.elements.truncate(self.depth + 1);
+            }
+
+            let starts_item = self.starts_item(index);
+            let separator = self.separator_before(index, starts_item);
+            if starts_item {
+                self.elements[self.depth] =
+                    grammar::element(&piece.token).map(|rule| &rule.keyword);
+                if self.depth == 0 {
+                    self.in_section_header = piece.token == Token::KeywordSection;
+                }
+            }
+
+            self.write_separator(separator);
+            self.output.push_str(piece.text);
+
+            if piece.token == Token::OpeningBrace {
+                self.depth += 1;
+                self.elements.push(None);
+            }
+        }
+
+        if !self.output.is_empty() {
+            self.output.push('\n');
+        }
+
+        self.output
+    }
+
+    fn separator_before(&self, index: usize, starts_item: bool) -> Separator {
+        let piece = &self.pieces[index];
+        let previous = match index.checked_sub(1) {
+            Some(previous) => &self.pieces[previous],
+            None => return Separator::None,
+        };
+        // blank lines are kept between items, not before closing braces
+        let line = if piece.line > previous.end_line + 1 && piece.token != Token::ClosingBrace {
+            Separator::BlankLine
+        } else {
+            Separator::Line
+        };
+
+        match (&previous.token, &piece.token) {
+            (Token::OpeningBrace, Token::ClosingBrace) => Separator::None,
+            (Token::Comment(comment), _) if comment.starts_with("//") => line,
+            (_, Token::Comment(_)) if piece.line == previous.end_line => Separator::Space,
+            (_, Token::ClosingBrace) | (Token::OpeningBrace, _) => Separator::Line,
+            (_, Token::Comma) => Separator::None,
+            // names after a comma continue a font stack
+            (Token::Comma, Token::Name(_)) => Separator::Space,
+            (_, Token::Comment(_)) | (Token::Comma, _) => line,
+            _ if starts_item && self.depth == 0 => {
+                // a comment on a line of its own belongs to the item
+                let is_commented = matches!(previous.token, Token::Comment(_))
+                    && index >= 2
+                    && self.pieces[index - 2].end_line < previous.line;
+                let is_section_start = self.in_section_header && piece.token == Token::KeywordSlide;
+                if TOP_LEVEL_BLOCKS.contains(&piece.token) && !is_commented && !is_section_start {
+                    Separator::BlankLine
+                } else {
+                    line
+                }
+            }
+            _ if starts_item => line,
+            _ => Separator::Space,
+        }
+    }
+
+    /// Whether the piece starts an item of the block it is in, rather than
+    /// continuing the one before it
+    fn starts_item(&self, index: usize) -> bool {
+        let token = &self.pieces[index].token;
+        let previous = index
+            .checked_sub(1)
+            .map(|previous| &self.pieces[previous].token);
+
+        if self.depth == 0 {
+            return TOP_LEVEL_ITEMS.contains(token);
+        }
+        // keywords used as values
+        if matches!(
+            (previous, token),
+            (Some(Token::KeywordTransition), Token::KeywordSlide)
+                | (Some(Token::KeywordBackground), Token::KeywordImage)
+        ) {
+            return false;
+        }
+        if let (Some(rule), Some(element)) = (
+            grammar::property(grammar::ELEMENT_PROPERTIES, token),
+            self.elements[self.depth],
+        ) {
+            if rule.target.accepts_keyword(element) {
+                return false;
+            }
+        }
+
+        grammar::element(token).is_some()
+            || BLOCK_ITEMS.contains(token)
+            || self.starts_placeholder_group(index)
+    }
+
+    fn starts_placeholder_group(&self, index: usize) -> bool {
+        let previous = index
+            .checked_sub(1)
+            .map(|previous| &self.pieces[previous].token);
+        let next = self.pieces.get(index + 1).map(|next| &next.token);
+
+        matches!(self.pieces[index].token, Token::Name(_))
+            && next == Some(&Token::OpeningBrace)
+            && !matches!(
+                previous,
+                Some(Token::KeywordLayout | Token::KeywordTextStyle | Token::KeywordUses)
+            )
+    }
+
+    fn write_separator(&mut self, separator: Separator) {
+        match separator {
+            Separator::None => {}
+            Separator::Space => self.output.push(' '),
+            Separator::Line | Separator::BlankLine => {
+                if separator == Separator::BlankLine {
+                    self.output.push('\n');
+                }
+                self.output.push('\n');
+                self.output.push_str(&INDENT.repeat(self.depth));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn indents_blocks_and_puts_items_on_their_own_lines() {
+        let input = "metadata { title \"Talk\", width 800 } slide \"a\" { title \"Hi\" size 64 \
+                     row { text \"x\" style body text \"y\" } gap 20 background #ffffff \
+                     list { item \"a\", item \"b\" } }";
+
+        assert_eq!(
+            Ok("metadata {\n    title \"Talk\",\n    width 800\n}\n\nslide \"a\" {\n    \
+                title \"Hi\" size 64\n    row {\n        text \"x\" style body\n        \
+                text \"y\"\n    } gap 20 background #ffffff\n    list {\n        item \"a\",\n        \
+                item \"b\"\n    }\n}\n"
+                .into()),
+            format(input)
+        );
+    }
+
+    #[test]
+    pub fn keeps_comments_and_single_blank_lines() {
+        let input = "// deck\nslide \"a\" { // first\n  text \"x\"\n\n\n\n  /* later */\n  \
+                     text \"y\" }\nsection \"More\"\nslide \"b\" {}";
+
+        assert_eq!(
+            Ok(
+                "// deck\nslide \"a\" { // first\n    text \"x\"\n\n    /* later */\n    \
+                text \"y\"\n}\n\nsection \"More\"\nslide \"b\" {}\n"
+                    .into()
+            ),
+            format(input)
+        );
+    }
+
+    #[test]
+    pub fn sorts_metadata_and_font_properties() {
+        let input = "metadata { width 800, // wide\n title \"Talk\" }\n\
+                     style { font { path \"a.ttf\", weight 700, name sans } }";
+
+        assert_eq!(
+            Ok(
+                "metadata {\n    title \"Talk\",\n    width 800 // wide\n}\n\nstyle {\n    \
+                font {\n        name sans,\n        path \"a.ttf\",\n        weight 700\n    \
+                }\n}\n"
+                    .into()
+            ),
+            format(input)
+        );
+    }
+
+    #[test]
+    pub fn keeps_values_and_font_stacks_on_their_line() {
+        let input = "style { background image \"bg.png\" text-style body { font-stack a, b, \
+                     size 20 } } slide \"a\" uses two-col { transition slide 300 left { text \
+                     \"x\" } }";
+
+        assert_eq!(
+            Ok(
+                "style {\n    background image \"bg.png\"\n    text-style body {\n        \
+                font-stack a, b,\n        size 20\n    }\n}\n\nslide \"a\" uses two-col {\n    \
+                transition slide 300\n    left {\n        text \"x\"\n    }\n}\n"
+                    .into()
+            ),
+            format(input)
+        );
+    }
+
+    #[test]
+    pub fn formats_its_own_output_the_same() {
+        let input = "define v \"1\" metadata { title \"T\", control osc 9000 { \"/go\" next, \
+                     \"/back\" previous } } // end\nslide \"a\" { step { text \"\"\"\n  raw\n\"\"\" } }";
+        let formatted = format(input).unwrap();
+
+        assert_eq!(Ok(formatted.clone()), format(&formatted));
+        let presentation = crate::parse(input).ok();
+        assert!(presentation.is_some());
+        assert_eq!(presentation, crate::parse(&formatted).ok());
+    }
+}
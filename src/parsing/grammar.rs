@@ -0,0 +1,452 @@
+use super::parser::Error;
+use super::properties::ValueKind;
+use super::token_stream::{SourceLocationRange, Token};
+use crate::presentation::{Container, SlideElement, Text};
+
+/// What follows an element's keyword, before its properties
+#[derive(Clone, Copy)]
+pub enum Content {
+    /// A string with markup, made into the element by the function
+    Text(fn(Text) -> SlideElement),
+    /// The path of the image file
+    Image,
+    /// The path of the video file
+    Video,
+    /// Items and how they are laid out, in braces
+    List,
+    /// Any elements in braces, containers included, made into the element by
+    /// the function
+    Container(fn(Container) -> SlideElement),
+}
+
+/// Element that can be put on slides, in layouts and in containers
+pub struct ElementRule {
+    pub keyword: Token,
+    pub content: Content,
+}
+
+/// Elements a property can follow
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Target {
+    Any,
+    Text,
+    Container,
+}
+
+impl Target {
+    pub fn accepts(self, element: &SlideElement) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Text => matches!(
+                element,
+                SlideElement::Text(_) | SlideElement::Title(_) | SlideElement::Subtitle(_)
+            ),
+            Self::Container => matches!(element, SlideElement::Row(_) | SlideElement::Column(_)),
+        }
+    }
+
+    /// Like `accepts`, for the element started by the keyword
+    pub fn accepts_keyword(self, keyword: &Token) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Text => matches!(
+                keyword,
+                Token::KeywordText | Token::KeywordTitle | Token::KeywordSubtitle
+            ),
+            Self::Container => matches!(keyword, Token::KeywordRow | Token::KeywordColumn),
+        }
+    }
+}
+
+/// How many times a property can be given in a block
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Occurs {
+    Optional,
+    Required,
+    Repeated,
+}
+
+pub struct PropertyRule {
+    pub keyword: Token,
+    /// Name the property is written with, used in error messages
+    pub name: &'static str,
+    /// Only used by element properties
+    pub target: Target,
+    pub occurs: Occurs,
+    /// Kind of value read into a property bag, none when the block parses
+    /// the value itself
+    pub value: Option<ValueKind>,
+}
+
+const fn rule(keyword: Token, name: &'static str, target: Target, occurs: Occurs) -> PropertyRule {
+    PropertyRule {
+        keyword,
+        name,
+        target,
+        occurs,
+        value: None,
+    }
+}
+
+const fn typed(
+    keyword: Token,
+    name: &'static str,
+    value: ValueKind,
+    occurs: Occurs,
+) -> PropertyRule {
+    PropertyRule {
+        keyword,
+        name,
+        target: Target::Any,
+        occurs,
+        value: Some(value),
+    }
+}
+
+/// Every element, in the order they are listed in error messages. A new
+/// element only needs a model type and an entry here.
+pub const ELEMENTS: &[ElementRule] = &[
+    ElementRule {
+        keyword: Token::KeywordText,
+        content: Content::Text(SlideElement::Text),
+    },
+    ElementRule {
+        keyword: Token::KeywordTitle,
+        content: Content::Text(SlideElement::Title),
+    },
+    ElementRule {
+        keyword: Token::KeywordSubtitle,
+        content: Content::Text(SlideElement::Subtitle),
+    },
+    ElementRule {
+        keyword: Token::KeywordList,
+        content: Content::List,
+    },
+    ElementRule {
+        keyword: Token::KeywordImage,
+        content: Content::Image,
+    },
+    ElementRule {
+        keyword: Token::KeywordVideo,
+        content: Content::Video,
+    },
+    ElementRule {
+        keyword: Token::KeywordRow,
+        content: Content::Container(SlideElement::Row),
+    },
+    ElementRule {
+        keyword: Token::KeywordColumn,
+        content: Content::Container(SlideElement::Column),
+    },
+];
+
+/// Properties following an element
+pub const ELEMENT_PROPERTIES: &[PropertyRule] = &[
+    rule(Token::KeywordStyle, "style", Target::Text, Occurs::Optional),
+    rule(
+        Token::KeywordGap,
+        "gap",
+        Target::Container,
+        Occurs::Optional,
+    ),
+    rule(
+        Token::KeywordAlign,
+        "align",
+        Target::Container,
+        Occurs::Optional,
+    ),
+    rule(
+        Token::KeywordBackground,
+        "background",
+        Target::Container,
+        Occurs::Optional,
+    ),
+    rule(Token::KeywordGrow, "grow", Target::Any, Occurs::Optional),
+    rule(Token::KeywordSize, "size", Target::Any, Occurs::Optional),
+    rule(Token::KeywordAt, "at", Target::Any, Occurs::Optional),
+    rule(
+        Token::KeywordAnchor,
+        "anchor",
+        Target::Any,
+        Occurs::Optional,
+    ),
+    rule(
+        Token::KeywordOpacity,
+        "opacity",
+        Target::Any,
+        Occurs::Optional,
+    ),
+    rule(
+        Token::KeywordFadeIn,
+        "fade-in",
+        Target::Any,
+        Occurs::Optional,
+    ),
+    rule(
+        Token::KeywordSlideInFrom,
+        "slide-in-from",
+        Target::Any,
+        Occurs::Optional,
+    ),
+    rule(
+        Token::KeywordAnimate,
+        "animate",
+        Target::Any,
+        Occurs::Optional,
+    ),
+    rule(
+        Token::KeywordRotate,
+        "rotate",
+        Target::Any,
+        Occurs::Optional,
+    ),
+    rule(Token::KeywordScale, "scale", Target::Any, Occurs::Optional),
+    rule(Token::KeywordSkew, "skew", Target::Any, Occurs::Optional),
+];
+
+/// `size` followed by a single number after a text, which sets the font size
+/// rather than the size of the text's box
+pub const FONT_SIZE: PropertyRule = rule(
+    Token::KeywordSize,
+    "font size",
+    Target::Text,
+    Occurs::Optional,
+);
+
+/// Properties of the `metadata` block
+pub const METADATA_PROPERTIES: &[PropertyRule] = &[
+    rule(Token::KeywordTitle, "title", Target::Any, Occurs::Required),
+    rule(Token::KeywordWidth, "width", Target::Any, Occurs::Optional),
+    rule(
+        Token::KeywordHeight,
+        "height",
+        Target::Any,
+        Occurs::Optional,
+    ),
+    rule(
+        Token::KeywordTransition,
+        "transition",
+        Target::Any,
+        Occurs::Optional,
+    ),
+    rule(Token::KeywordTheme, "theme", Target::Any, Occurs::Optional),
+    rule(
+        Token::KeywordControl,
+        "control",
+        Target::Any,
+        Occurs::Repeated,
+    ),
+    rule(
+        Token::KeywordShowProgress,
+        "show-progress",
+        Target::Any,
+        Occurs::Optional,
+    ),
+    rule(
+        Token::KeywordShowPageNumbers,
+        "show-page-numbers",
+        Target::Any,
+        Occurs::Optional,
+    ),
+    rule(
+        Token::KeywordShowFooter,
+        "show-footer",
+        Target::Any,
+        Occurs::Optional,
+    ),
+    rule(
+        Token::KeywordSpellingIgnore,
+        "spelling-ignore",
+        Target::Any,
+        Occurs::Repeated,
+    ),
+    rule(
+        Token::KeywordFontDir,
+        "font-dir",
+        Target::Any,
+        Occurs::Repeated,
+    ),
+];
+
+/// Properties of a `font` in a style, `path` is required unless the font is
+/// a system one
+pub const FONT_PROPERTIES: &[PropertyRule] = &[
+    typed(
+        Token::KeywordName,
+        "name",
+        ValueKind::Name,
+        Occurs::Required,
+    ),
+    typed(
+        Token::KeywordPath,
+        "path",
+        ValueKind::String,
+        Occurs::Optional,
+    ),
+    typed(
+        Token::KeywordWeight,
+        "weight",
+        ValueKind::Integer,
+        Occurs::Optional,
+    ),
+    typed(
+        Token::KeywordItalic,
+        "italic",
+        ValueKind::Flag,
+        Occurs::Optional,
+    ),
+    typed(
+        Token::KeywordSize,
+        "size",
+        ValueKind::Integer,
+        Occurs::Optional,
+    ),
+    typed(
+        Token::KeywordSystem,
+        "system",
+        ValueKind::Flag,
+        Occurs::Optional,
+    ),
+];
+
+/// What a slide holds besides elements and placeholders
+pub const SLIDE_ITEMS: &[Token] = &[
+    Token::KeywordBackground,
+    Token::KeywordNotes,
+    Token::KeywordTransition,
+    Token::KeywordDuration,
+    Token::KeywordTarget,
+    Token::KeywordStep,
+    Token::ClosingBrace,
+];
+
+/// What a layout holds besides elements
+pub const LAYOUT_ITEMS: &[Token] = &[Token::KeywordPlaceholder, Token::ClosingBrace];
+
+/// What a row or a column holds besides elements
+pub const CONTAINER_ITEMS: &[Token] = &[Token::ClosingBrace];
+
+pub fn element(keyword: &Token) -> Option<&'static ElementRule> {
+    ELEMENTS.iter().find(|rule| rule.keyword == *keyword)
+}
+
+pub fn property<'a>(rules: &'a [PropertyRule], keyword: &Token) -> Option<&'a PropertyRule> {
+    rules.iter().find(|rule| rule.keyword == *keyword)
+}
+
+/// Name of the block of properties following the element
+pub fn element_name(element: &SlideElement) -> &'static str {
+    match element {
+        SlideElement::Text(_) => "text",
+        SlideElement::Title(_) => "title",
+        SlideElement::Subtitle(_) => "subtitle",
+        SlideElement::List(_) => "list",
+        SlideElement::Image(_) => "image",
+        SlideElement::Video(_) => "video",
+        SlideElement::Row(_) => "row",
+        SlideElement::Column(_) => "column",
+    }
+}
+
+/// Lists the tokens the way errors do, e.g. `KeywordText, ClosingBrace`
+pub fn expected<'a>(tokens: impl Iterator<Item = &'a Token>) -> String {
+    tokens
+        .map(|token| format!("{:?}", token))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// Tracks the properties given in a block, to report the ones given twice
+/// or not at all
+pub struct GivenProperties {
+    block: &'static str,
+    given: Vec<&'static str>,
+}
+
+impl GivenProperties {
+    pub fn new(block: &'static str) -> Self {
+        Self {
+            block,
+            given: vec![],
+        }
+    }
+
+    pub fn add(&mut self, rule: &PropertyRule, location: SourceLocationRange) -> Result<(), Error> {
+        if rule.occurs != Occurs::Repeated && self.given.contains(&rule.name) {
+            return Err(Error::DuplicateProperty {
+                block: self.block.into(),
+                property: rule.name.into(),
+                location,
+            });
+        }
+        self.given.push(rule.name);
+
+        Ok(())
+    }
+
+    /// Fails on the first required property that wasn't given, pointing at
+    /// the block's location
+    pub fn check_required(
+        &self,
+        rules: &[PropertyRule],
+        location: SourceLocationRange,
+    ) -> Result<(), Error> {
+        match rules
+            .iter()
+            .find(|rule| rule.occurs == Occurs::Required && !self.given.contains(&rule.name))
+        {
+            Some(rule) => Err(Error::MissingProperty {
+                block: self.block.into(),
+                property: rule.name.into(),
+                location,
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parsing::token_stream::SourceLocation;
+
+    #[test]
+    pub fn finds_properties_for_their_elements() {
+        let text = SlideElement::Text(Text::with_spans(vec![], None));
+        let row = SlideElement::Row(Container::new(vec![]));
+
+        let style = property(ELEMENT_PROPERTIES, &Token::KeywordStyle).unwrap();
+        assert!(style.target.accepts(&text));
+        assert!(!style.target.accepts(&row));
+        assert!(element(&Token::KeywordRow).is_some());
+        assert!(element(&Token::KeywordStep).is_none());
+    }
+
+    #[test]
+    pub fn reports_duplicate_and_missing_properties() {
+        let location = SourceLocationRange::new_single(SourceLocation::new(0, 1));
+        let width = property(METADATA_PROPERTIES, &Token::KeywordWidth).unwrap();
+        let control = property(METADATA_PROPERTIES, &Token::KeywordControl).unwrap();
+        let mut given = GivenProperties::new("metadata");
+
+        assert_eq!(Ok(()), given.add(width, location));
+        assert_eq!(Ok(()), given.add(control, location));
+        assert_eq!(Ok(()), given.add(control, location));
+        assert_eq!(
+            Err(Error::DuplicateProperty {
+                block: "metadata".into(),
+                property: "width".into(),
+                location
+            }),
+            given.add(width, location)
+        );
+        assert_eq!(
+            Err(Error::MissingProperty {
+                block: "metadata".into(),
+                property: "title".into(),
+                location
+            }),
+            given.check_required(METADATA_PROPERTIES, location)
+        );
+    }
+}
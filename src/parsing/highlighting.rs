@@ -0,0 +1,141 @@
+use super::token_stream::{
+    SourceLocation, SourceLocationRange, Token, TokenStream, TokenizerResult,
+};
+use super::tokenizer::Tokenizer;
+use std::ops::Range;
+
+/// What a piece of the input is, for syntax highlighting
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum TokenClass {
+    /// Keywords and the `true` and `false` literals
+    Keyword,
+    Name,
+    String,
+    /// Integers, decimal numbers, lengths, times and durations
+    Number,
+    Color,
+    Comment,
+    Punctuation,
+    /// Input the tokenizer failed on
+    Invalid,
+}
+
+impl TokenClass {
+    fn of(token: &Token) -> Self {
+        match token {
+            Token::Name(_) => Self::Name,
+            Token::String(_) => Self::String,
+            Token::Integer(_)
+            | Token::Float(_)
+            | Token::Length(_)
+            | Token::Time(_)
+            | Token::Duration(_) => Self::Number,
+            Token::Color(_) => Self::Color,
+            Token::Comment(_) => Self::Comment,
+            Token::OpeningBrace | Token::ClosingBrace | Token::Comma => Self::Punctuation,
+            _ => Self::Keyword,
+        }
+    }
+}
+
+/// Splits the input into classified pieces. Ranges start at a piece's first
+/// character and end right after its last one. Unlike the parser this keeps
+/// going after tokenizer failures, marking the input skipped over as invalid.
+pub fn classify(input: &str) -> Vec<(SourceLocationRange, TokenClass)> {
+    let lines = LineStarts::new(input);
+    let mut tokenizer = Tokenizer::new(input);
+    tokenizer.set_keep_comments(true);
+    tokenizer.set_recover(true);
+    let mut classes = vec![];
+
+    loop {
+        let class = match tokenizer.next() {
+            TokenizerResult::Ok(token, _) => TokenClass::of(&token),
+            TokenizerResult::Err(_) => TokenClass::Invalid,
+            TokenizerResult::End => return classes,
+        };
+
+        classes.push((lines.range(input, tokenizer.token_span()), class));
+    }
+}
+
+/// Where each line of the input starts, to locate byte offsets
+struct LineStarts(Vec<usize>);
+
+impl LineStarts {
+    fn new(input: &str) -> Self {
+        Self(
+            std::iter::once(0)
+                .chain(input.match_indices('\n').map(|(index, _)| index + 1))
+                .collect(),
+        )
+    }
+
+    fn location(&self, input: &str, offset: usize) -> SourceLocation {
+        let line = match self.0.binary_search(&offset) {
+            Ok(line) => line,
+            Err(next_line) => next_line - 1,
+        };
+        let column = input[self.0[line]..offset].chars().count() + 1;
+
+        SourceLocation::new(line as u32, column as u32)
+    }
+
+    fn range(&self, input: &str, span: Range<usize>) -> SourceLocationRange {
+        SourceLocationRange::new(
+            self.location(input, span.start),
+            self.location(input, span.end),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn range(line: u32, start: u32, end: u32) -> SourceLocationRange {
+        SourceLocationRange::new(
+            SourceLocation::new(line, start),
+            SourceLocation::new(line, end),
+        )
+    }
+
+    #[test]
+    pub fn classifies_every_kind_of_token() {
+        assert_eq!(
+            vec![
+                (range(0, 1, 6), TokenClass::Keyword),
+                (range(0, 7, 12), TokenClass::String),
+                (range(0, 13, 17), TokenClass::Keyword),
+                (range(0, 18, 23), TokenClass::Name),
+                (range(0, 24, 25), TokenClass::Punctuation),
+                (range(0, 26, 36), TokenClass::Comment),
+                (range(1, 3, 7), TokenClass::Keyword),
+                (range(1, 8, 12), TokenClass::Number),
+                (range(1, 12, 13), TokenClass::Punctuation),
+                (range(1, 14, 21), TokenClass::Color),
+                (range(1, 22, 26), TokenClass::Keyword),
+                (range(2, 1, 2), TokenClass::Punctuation),
+            ],
+            classify("slide \"Ünï\" uses split { // comment\n  size 20px, #ff0000 true\n}")
+        );
+    }
+
+    #[test]
+    pub fn continues_after_a_failure() {
+        assert_eq!(
+            vec![
+                (range(0, 1, 5), TokenClass::Keyword),
+                (range(0, 6, 11), TokenClass::Invalid),
+                (range(0, 12, 13), TokenClass::Name),
+                (range(1, 1, 5), TokenClass::Keyword),
+                (range(1, 6, 9), TokenClass::String),
+                (
+                    SourceLocationRange::new(SourceLocation::new(2, 1), SourceLocation::new(3, 4)),
+                    TokenClass::Comment
+                ),
+            ],
+            classify("text \"a\\q\" b\ntext \"c\"\n/* a\n */")
+        );
+    }
+}
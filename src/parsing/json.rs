@@ -0,0 +1,1268 @@
+use crate::control::Action;
+use crate::easing::Easing;
+use crate::json::Json;
+use crate::parsing::markup;
+use crate::parsing::serializer::{color, length};
+use crate::presentation::{
+    Align, Anchor, Animation, Color, Container, ControlInput, Fill, Font, FontSource,
+    GradientDirection, Hinting, Image, ImageFit, Keyframe, Layout, LayoutItem, Length, List,
+    Metadata, Motion, Placement, Presentation, Resolution, Section, Side, Slide, SlideElement,
+    Span, Style, StyleError, Text, TextStyle, Transition, TransitionKind, Video,
+};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::time::Duration;
+
+/// What lists have when they don't say otherwise, as in the DSL
+const DEFAULT_BULLET: &str = "•";
+const DEFAULT_INDENT: Length = Length::Pixels(40.0);
+const DEFAULT_FONT_WEIGHT: u32 = 400;
+const DEFAULT_TRANSITION_DURATION: u64 = 300;
+const DEFAULT_ANIMATION_DURATION: u64 = 300;
+
+/// Writes the whole presentation as JSON, for scripts that generate or
+/// post-process decks. Colors are written as `#rrggbb`, lengths with their
+/// unit and fills as a color, a gradient or an image object.
+pub fn to_json(presentation: &Presentation) -> Json {
+    let mut layout_names = presentation.layout_names();
+    layout_names.sort();
+    let layouts = layout_names
+        .into_iter()
+        .filter_map(|name| {
+            presentation
+                .layout(name)
+                .map(|layout| (name.clone(), layout_json(layout)))
+        })
+        .collect();
+    let sections = presentation
+        .sections()
+        .iter()
+        .map(|section| {
+            Json::object(vec![
+                ("title", Json::string(section.title())),
+                ("firstSlide", index(section.first_slide())),
+            ])
+        })
+        .collect();
+
+    Json::object(vec![
+        ("metadata", metadata_json(presentation.metadata())),
+        ("style", style_json(presentation.style())),
+        ("layouts", Json::Object(layouts)),
+        ("sections", Json::Array(sections)),
+        (
+            "slides",
+            Json::Array(presentation.slides().iter().map(slide_json).collect()),
+        ),
+    ])
+}
+
+/// Reads a presentation written the way `to_json` writes it. Errors name
+/// the member that is wrong, like `slides[2].elements[0].type`.
+pub fn parse(input: &str) -> Result<Presentation, String> {
+    let value = Json::parse(input).map_err(|error| format!("invalid JSON: {}", error))?;
+
+    from_json(&value)
+}
+
+pub fn from_json(value: &Json) -> Result<Presentation, String> {
+    let mut presentation = Presentation::new(
+        read_metadata(value.get("metadata"), "metadata")?,
+        each(value.get("slides"), "slides", read_slide)?,
+        read_style(value.get("style"), "style")?,
+    );
+    for (name, layout) in members(value.get("layouts"), "layouts")? {
+        let path = join("layouts", name);
+        presentation.add_layout(
+            name.clone(),
+            Layout::new(each(layout, &path, read_layout_item)?),
+        );
+    }
+    for section in each(value.get("sections"), "sections", read_section)? {
+        presentation.add_section(section);
+    }
+
+    Ok(presentation)
+}
+
+fn index(value: usize) -> Json {
+    Json::number(u32::try_from(value).unwrap_or(u32::MAX))
+}
+
+fn strings(values: &[String]) -> Json {
+    Json::Array(values.iter().map(|value| Json::string(value)).collect())
+}
+
+fn metadata_json(metadata: &Metadata) -> Json {
+    let resolution = metadata.resolution();
+    let mut members = vec![
+        ("title", Json::string(metadata.title())),
+        ("width", Json::number(resolution.width())),
+        ("height", Json::number(resolution.height())),
+    ];
+    if let Some(transition) = metadata.transition() {
+        members.push(("transition", transition_json(transition)));
+    }
+    if let Some(theme) = metadata.theme() {
+        members.push(("theme", Json::string(theme)));
+    }
+    if !metadata.controls().is_empty() {
+        let controls = metadata.controls().iter().map(control_json).collect();
+        members.push(("controls", Json::Array(controls)));
+    }
+    members.extend(vec![
+        ("showProgress", Json::Bool(metadata.show_progress())),
+        ("showPageNumbers", Json::Bool(metadata.show_page_numbers())),
+        ("showFooter", Json::Bool(metadata.show_footer())),
+    ]);
+    if !metadata.spelling_ignore().is_empty() {
+        members.push(("spellingIgnore", strings(metadata.spelling_ignore())));
+    }
+    if !metadata.font_dirs().is_empty() {
+        members.push(("fontDirs", strings(metadata.font_dirs())));
+    }
+
+    Json::object(members)
+}
+
+fn transition_json(transition: Transition) -> Json {
+    let milliseconds = u32::try_from(transition.duration().as_millis()).unwrap_or(u32::MAX);
+
+    let mut members = vec![
+        ("kind", Json::string(transition.kind().name())),
+        ("duration", Json::number(milliseconds)),
+    ];
+    if transition.easing() != Easing::Linear {
+        members.push(("easing", easing_json(transition.easing())));
+    }
+
+    Json::object(members)
+}
+
+/// The name of the easing, or `{"cubicBezier": [x1, y1, x2, y2]}` and
+/// `{"spring": [stiffness, damping]}` for those taking numbers
+fn easing_json(easing: Easing) -> Json {
+    match easing {
+        Easing::CubicBezier(x1, y1, x2, y2) => Json::object(vec![(
+            "cubicBezier",
+            Json::Array(vec![
+                Json::Number(x1),
+                Json::Number(y1),
+                Json::Number(x2),
+                Json::Number(y2),
+            ]),
+        )]),
+        Easing::Spring { stiffness, damping } => Json::object(vec![(
+            "spring",
+            Json::Array(vec![Json::Number(stiffness), Json::Number(damping)]),
+        )]),
+        _ => Json::string(easing.name()),
+    }
+}
+
+/// The input with its bindings sorted, so that the JSON is always the same
+fn control_json(control: &ControlInput) -> Json {
+    match control {
+        ControlInput::Osc { port, addresses } => {
+            let mut bindings = addresses
+                .iter()
+                .map(|(address, action)| (address.clone(), Json::string(action.name())))
+                .collect::<Vec<(String, Json)>>();
+            bindings.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+            Json::object(vec![
+                ("osc", Json::number(u32::from(*port))),
+                ("bindings", Json::Object(bindings)),
+            ])
+        }
+        ControlInput::Midi { device, notes } => {
+            let mut notes = notes.iter().collect::<Vec<_>>();
+            notes.sort_by_key(|(note, _)| *note);
+            let bindings = notes
+                .into_iter()
+                .map(|(note, action)| (note.to_string(), Json::string(action.name())))
+                .collect();
+
+            Json::object(vec![
+                ("midi", Json::string(device)),
+                ("bindings", Json::Object(bindings)),
+            ])
+        }
+    }
+}
+
+/// The style with its default font first, the other fonts and the text
+/// styles sorted by name. Settings left at their default are left out.
+fn style_json(style: &Style) -> Json {
+    let default_font = style.default_font();
+    let mut fonts = style
+        .fonts()
+        .into_iter()
+        .filter(|font| Some(*font) != default_font)
+        .collect::<Vec<&Font>>();
+    fonts.sort_by_key(|font| (font.name(), font.weight(), font.italic()));
+    let fonts = default_font
+        .into_iter()
+        .chain(fonts)
+        .map(font_json)
+        .collect();
+
+    let mut members = vec![("fonts", Json::Array(fonts))];
+    if let Some(background) = style.background() {
+        members.push(("background", fill_json(background)));
+    }
+    if let Some(value) = style.text_color() {
+        members.push(("color", Json::String(color(value))));
+    }
+    if style.hinting() != Hinting::Normal {
+        members.push(("hinting", Json::string(style.hinting().name())));
+    }
+    if !style.antialias() {
+        members.push(("antialias", Json::Bool(false)));
+    }
+    if !style.kerning() {
+        members.push(("kerning", Json::Bool(false)));
+    }
+
+    let mut names = style.text_style_names();
+    names.sort();
+    let text_styles = names
+        .into_iter()
+        .filter_map(|name| {
+            style
+                .text_style(name)
+                .map(|text_style| (name.clone(), text_style_json(text_style)))
+        })
+        .collect();
+    members.push(("textStyles", Json::Object(text_styles)));
+
+    Json::object(members)
+}
+
+fn font_json(font: &Font) -> Json {
+    let mut members = vec![("name", Json::string(font.name()))];
+    match font.source() {
+        FontSource::File(path) => members.push(("path", Json::string(path))),
+        FontSource::System => members.push(("system", Json::Bool(true))),
+        FontSource::Bundled => members.push(("bundled", Json::Bool(true))),
+    }
+    members.push(("weight", Json::number(font.weight())));
+    members.push(("italic", Json::Bool(font.italic())));
+    if let Some(size) = font.size() {
+        members.push(("size", Json::number(size)));
+    }
+
+    Json::object(members)
+}
+
+fn text_style_json(text_style: &TextStyle) -> Json {
+    let mut members = vec![];
+    if let Some(font) = text_style.font() {
+        members.push(("font", Json::string(font)));
+    }
+    if !text_style.fallback_fonts().is_empty() {
+        members.push(("fallbackFonts", strings(text_style.fallback_fonts())));
+    }
+    if let Some(size) = text_style.size() {
+        members.push(("size", Json::number(size)));
+    }
+    if let Some(value) = text_style.color() {
+        members.push(("color", Json::String(color(value))));
+    }
+    if let Some(weight) = text_style.weight() {
+        members.push(("weight", Json::number(weight)));
+    }
+    if let Some(italic) = text_style.italic() {
+        members.push(("italic", Json::Bool(italic)));
+    }
+
+    Json::object(members)
+}
+
+fn layout_json(layout: &Layout) -> Json {
+    Json::Array(
+        layout
+            .items()
+            .iter()
+            .map(|item| match item {
+                LayoutItem::Element(element) => Json::object(element_members(element)),
+                LayoutItem::Placeholder(name) => Json::object(vec![
+                    ("type", Json::string("placeholder")),
+                    ("name", Json::string(name)),
+                ]),
+            })
+            .collect(),
+    )
+}
+
+/// The slide with its elements in order, each with the build step and the
+/// placeholder it is in
+fn slide_json(slide: &Slide) -> Json {
+    let mut members = vec![("name", Json::string(slide.name()))];
+    if let Some(layout) = slide.layout() {
+        members.push(("layout", Json::string(layout)));
+    }
+    members.push(("hidden", Json::Bool(slide.is_hidden())));
+    if let Some(scheduled_at) = slide.scheduled_at() {
+        let seconds = u32::try_from(scheduled_at.as_secs()).unwrap_or(u32::MAX);
+        members.push(("at", Json::number(seconds)));
+    }
+    if let Some(background) = slide.background() {
+        members.push(("background", fill_json(background)));
+    }
+    if let Some(transition) = slide.transition() {
+        members.push(("transition", transition_json(transition)));
+    }
+    if let Some(duration) = slide.duration() {
+        let seconds = u32::try_from(duration.as_secs()).unwrap_or(u32::MAX);
+        members.push(("duration", Json::number(seconds)));
+    }
+    if let Some(target) = slide.target() {
+        let seconds = u32::try_from(target.as_secs()).unwrap_or(u32::MAX);
+        members.push(("target", Json::number(seconds)));
+    }
+
+    let elements = slide
+        .elements()
+        .iter()
+        .enumerate()
+        .map(|(index, element)| {
+            let mut members = element_members(element);
+            if slide.step_of(index) > 0 {
+                members.push(("step", Json::number(slide.step_of(index))));
+            }
+            if let Some(placeholder) = slide.placeholder_of(index) {
+                members.push(("placeholder", Json::string(placeholder)));
+            }
+            Json::object(members)
+        })
+        .collect();
+    members.push(("elements", Json::Array(elements)));
+    members.push(("notes", strings(slide.notes())));
+
+    Json::object(members)
+}
+
+fn element_members(element: &SlideElement) -> Vec<(&'static str, Json)> {
+    let mut members = match element {
+        SlideElement::Text(text) => text_members("text", text),
+        SlideElement::Title(text) => text_members("title", text),
+        SlideElement::Subtitle(text) => text_members("subtitle", text),
+        SlideElement::List(list) => vec![
+            ("type", Json::string("list")),
+            ("items", strings(list.items())),
+            ("bullet", Json::string(list.bullet())),
+            ("indent", Json::String(length(list.indent()))),
+        ],
+        SlideElement::Image(image) => vec![
+            ("type", Json::string("image")),
+            ("path", Json::string(image.path())),
+        ],
+        SlideElement::Video(video) => vec![
+            ("type", Json::string("video")),
+            ("path", Json::string(video.path())),
+        ],
+        SlideElement::Row(container) => container_members("row", container),
+        SlideElement::Column(container) => container_members("column", container),
+    };
+    members.extend(placement_members(element.placement()));
+    members.extend(animation_members(element.animation()));
+
+    members
+}
+
+fn text_members(kind: &str, text: &Text) -> Vec<(&'static str, Json)> {
+    let spans = text.spans().iter().map(span_json).collect();
+    let mut members = vec![("type", Json::string(kind)), ("spans", Json::Array(spans))];
+    if let Some(style) = text.style() {
+        members.push(("style", Json::string(style)));
+    }
+    if let Some(size) = text.size() {
+        members.push(("fontSize", Json::number(size)));
+    }
+
+    members
+}
+
+fn span_json(span: &Span) -> Json {
+    let mut members = vec![("text", Json::string(span.content()))];
+    if span.bold() {
+        members.push(("bold", Json::Bool(true)));
+    }
+    if span.italic() {
+        members.push(("italic", Json::Bool(true)));
+    }
+    if let Some(value) = span.color() {
+        members.push(("color", Json::String(color(value))));
+    }
+
+    Json::object(members)
+}
+
+fn container_members(kind: &str, container: &Container) -> Vec<(&'static str, Json)> {
+    let elements = container
+        .elements()
+        .iter()
+        .map(|element| Json::object(element_members(element)))
+        .collect();
+    let mut members = vec![
+        ("type", Json::string(kind)),
+        ("elements", Json::Array(elements)),
+        ("gap", Json::String(length(container.gap()))),
+        ("align", Json::string(container.align().name())),
+    ];
+    if let Some(background) = container.background() {
+        members.push(("background", fill_json(background)));
+    }
+
+    members
+}
+
+fn placement_members(placement: &Placement) -> Vec<(&'static str, Json)> {
+    let mut members = vec![];
+    if let Some((x, y)) = placement.position() {
+        members.push((
+            "at",
+            Json::Array(vec![Json::String(length(x)), Json::String(length(y))]),
+        ));
+    }
+    if let Some(anchor) = placement.anchor() {
+        members.push(("anchor", Json::string(anchor.name())));
+    }
+    if let (Some(_), _) | (_, Some(_)) = placement.size() {
+        let extent = |extent: Option<Length>| {
+            extent.map_or(Json::Null, |extent| Json::String(length(extent)))
+        };
+        let (width, height) = placement.size();
+        members.push(("size", Json::Array(vec![extent(width), extent(height)])));
+    }
+    if placement.grow() > 0 {
+        members.push(("grow", Json::number(placement.grow())));
+    }
+
+    members
+}
+
+/// Durations are in milliseconds, like those of transitions
+fn animation_members(animation: &Animation) -> Vec<(&'static str, Json)> {
+    let milliseconds =
+        |duration: Duration| Json::number(u32::try_from(duration.as_millis()).unwrap_or(u32::MAX));
+    let mut members = vec![];
+    if animation.opacity() != 100 {
+        members.push(("opacity", Json::number(animation.opacity())));
+    }
+    if animation.rotation() != 0.0 {
+        members.push(("rotate", Json::Number(animation.rotation())));
+    }
+    if (animation.scale() - 1.0).abs() > f64::EPSILON {
+        members.push(("scale", Json::Number(animation.scale())));
+    }
+    if animation.skew() != (0.0, 0.0) {
+        let (x, y) = animation.skew();
+        members.push(("skew", Json::Array(vec![Json::Number(x), Json::Number(y)])));
+    }
+    if let Some(duration) = animation.fade_in() {
+        members.push(("fadeIn", milliseconds(duration)));
+    }
+    if let Some((side, duration)) = animation.slide_in() {
+        members.push((
+            "slideIn",
+            Json::object(vec![
+                ("from", Json::string(side.name())),
+                ("duration", milliseconds(duration)),
+            ]),
+        ));
+    }
+    if animation.easing() != Easing::Linear {
+        members.push(("easing", easing_json(animation.easing())));
+    }
+    if !animation.keyframes().is_empty() {
+        let keyframes = animation.keyframes().iter().map(|keyframe| {
+            // an array rather than members, a keyframe may repeat a motion and
+            // their order matters
+            let motions = keyframe.motions().iter().map(|motion| {
+                let value = match *motion {
+                    Motion::X(value) | Motion::Y(value) => Json::String(length(value)),
+                    Motion::Scale(value) | Motion::Rotate(value) => Json::Number(value),
+                    Motion::Opacity(value) => Json::number(value),
+                };
+                Json::object(vec![
+                    ("motion", Json::string(motion.name())),
+                    ("value", value),
+                ])
+            });
+            let mut members = vec![
+                ("at", milliseconds(keyframe.at())),
+                ("motions", Json::Array(motions.collect())),
+            ];
+            if keyframe.easing() != Easing::Linear {
+                members.push(("easing", easing_json(keyframe.easing())));
+            }
+            Json::object(members)
+        });
+        members.push(("keyframes", Json::Array(keyframes.collect())));
+    }
+
+    members
+}
+
+fn fill_json(fill: &Fill) -> Json {
+    match fill {
+        Fill::Color(value) => Json::String(color(*value)),
+        Fill::Gradient {
+            from,
+            to,
+            direction,
+        } => Json::object(vec![
+            ("from", Json::String(color(*from))),
+            ("to", Json::String(color(*to))),
+            ("direction", Json::string(direction.name())),
+        ]),
+        Fill::Image { path, fit } => Json::object(vec![
+            ("image", Json::string(path)),
+            ("fit", Json::string(fit.name())),
+        ]),
+    }
+}
+
+/// Where a member is, like `slides[0].name`
+fn join(path: &str, key: &str) -> String {
+    format!("{}.{}", path, key)
+}
+
+fn expected(path: &str, what: &str) -> String {
+    format!("`{}` should be {}", path, what)
+}
+
+/// Reads the items of an array, a missing array has none
+fn each<T>(
+    value: &Json,
+    path: &str,
+    read: impl Fn(&Json, &str) -> Result<T, String>,
+) -> Result<Vec<T>, String> {
+    match value {
+        Json::Null => Ok(vec![]),
+        Json::Array(items) => items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| read(item, &format!("{}[{}]", path, index)))
+            .collect(),
+        _ => Err(expected(path, "an array")),
+    }
+}
+
+/// The members of an object, a missing object has none
+fn members<'a>(value: &'a Json, path: &str) -> Result<&'a [(String, Json)], String> {
+    match value {
+        Json::Null => Ok(&[]),
+        Json::Object(pairs) => Ok(pairs),
+        _ => Err(expected(path, "an object")),
+    }
+}
+
+/// Reads the member of the object, none when it is missing or `null`
+fn optional<T>(
+    object: &Json,
+    path: &str,
+    key: &str,
+    read: impl Fn(&Json, &str) -> Result<T, String>,
+) -> Result<Option<T>, String> {
+    match object.get(key) {
+        Json::Null => Ok(None),
+        value => read(value, &join(path, key)).map(Some),
+    }
+}
+
+fn required<T>(
+    object: &Json,
+    path: &str,
+    key: &str,
+    read: impl Fn(&Json, &str) -> Result<T, String>,
+) -> Result<T, String> {
+    optional(object, path, key, read)?.ok_or_else(|| format!("`{}` is missing", join(path, key)))
+}
+
+/// A boolean member, false when it is missing
+fn flag(object: &Json, path: &str, key: &str) -> Result<bool, String> {
+    Ok(optional(object, path, key, read_bool)?.unwrap_or(false))
+}
+
+fn read_string(value: &Json, path: &str) -> Result<String, String> {
+    value
+        .as_str()
+        .map(String::from)
+        .ok_or_else(|| expected(path, "a string"))
+}
+
+fn read_u32(value: &Json, path: &str) -> Result<u32, String> {
+    value
+        .as_u32()
+        .ok_or_else(|| expected(path, "a whole number"))
+}
+
+fn read_f64(value: &Json, path: &str) -> Result<f64, String> {
+    value.as_f64().ok_or_else(|| expected(path, "a number"))
+}
+
+fn read_bool(value: &Json, path: &str) -> Result<bool, String> {
+    value
+        .as_bool()
+        .ok_or_else(|| expected(path, "true or false"))
+}
+
+fn read_color(value: &Json, path: &str) -> Result<Color, String> {
+    value
+        .as_str()
+        .and_then(Color::from_hex)
+        .ok_or_else(|| expected(path, "a color like `#rrggbb`"))
+}
+
+/// A length like `10px`, `50%` or `1.5em`, plain numbers are pixels
+fn read_length(value: &Json, path: &str) -> Result<Length, String> {
+    let invalid = || expected(path, "a length like `10px`, `50%` or `1.5em`");
+    if let Some(pixels) = value.as_f64() {
+        return Ok(Length::Pixels(pixels));
+    }
+
+    let text = value.as_str().ok_or_else(invalid)?;
+    let (number, unit): (&str, fn(f64) -> Length) = if let Some(number) = text.strip_suffix("px") {
+        (number, Length::Pixels)
+    } else if let Some(number) = text.strip_suffix('%') {
+        (number, Length::Percent)
+    } else if let Some(number) = text.strip_suffix("em") {
+        (number, Length::Em)
+    } else {
+        return Err(invalid());
+    };
+
+    number.trim().parse().map(unit).map_err(|_| invalid())
+}
+
+/// A name one of the model's enums takes, like an anchor or an action
+fn read_name<T>(
+    value: &Json,
+    path: &str,
+    from_name: fn(&str) -> Option<T>,
+    what: &str,
+) -> Result<T, String> {
+    value
+        .as_str()
+        .and_then(from_name)
+        .ok_or_else(|| expected(path, what))
+}
+
+fn read_metadata(value: &Json, path: &str) -> Result<Metadata, String> {
+    let mut resolution = Resolution::default();
+    if let Some(width) = optional(value, path, "width", read_u32)? {
+        resolution.set_width(width);
+    }
+    if let Some(height) = optional(value, path, "height", read_u32)? {
+        resolution.set_height(height);
+    }
+
+    let mut metadata = Metadata::new(required(value, path, "title", read_string)?, resolution);
+    if let Some(transition) = optional(value, path, "transition", read_transition)? {
+        metadata.set_transition(transition);
+    }
+    if let Some(theme) = optional(value, path, "theme", read_string)? {
+        metadata.set_theme(theme);
+    }
+    for control in each(value.get("controls"), &join(path, "controls"), read_control)? {
+        metadata.add_control(control);
+    }
+    metadata.set_show_progress(flag(value, path, "showProgress")?);
+    metadata.set_show_page_numbers(flag(value, path, "showPageNumbers")?);
+    metadata.set_show_footer(flag(value, path, "showFooter")?);
+    let spelling_ignore = join(path, "spellingIgnore");
+    for word in each(value.get("spellingIgnore"), &spelling_ignore, read_string)? {
+        metadata.add_spelling_ignore(word);
+    }
+    for font_dir in each(value.get("fontDirs"), &join(path, "fontDirs"), read_string)? {
+        metadata.add_font_dir(font_dir);
+    }
+
+    Ok(metadata)
+}
+
+/// A transition, whose duration is in milliseconds
+fn read_transition(value: &Json, path: &str) -> Result<Transition, String> {
+    let kind = required(value, path, "kind", |value, path| {
+        read_name(
+            value,
+            path,
+            TransitionKind::from_name,
+            "none, fade or slide",
+        )
+    })?;
+    let duration = match optional(value, path, "duration", read_u32)? {
+        Some(duration) => u64::from(duration),
+        None if kind == TransitionKind::None => 0,
+        None => DEFAULT_TRANSITION_DURATION,
+    };
+
+    let mut transition = Transition::new(kind, Duration::from_millis(duration));
+    if let Some(easing) = optional(value, path, "easing", read_easing)? {
+        transition.set_easing(easing);
+    }
+
+    Ok(transition)
+}
+
+/// An easing's name, `{"cubicBezier": [x1, y1, x2, y2]}` or `{"spring":
+/// [stiffness, damping]}`
+fn read_easing(value: &Json, path: &str) -> Result<Easing, String> {
+    if let Json::String(_) = value {
+        return read_name(
+            value,
+            path,
+            Easing::from_name,
+            "linear, ease-in, ease-out, ease-in-out or spring",
+        );
+    }
+
+    if !value.get("cubicBezier").is_null() {
+        let path = join(path, "cubicBezier");
+        return match value.get("cubicBezier").as_array() {
+            [x1, y1, x2, y2] => Ok(Easing::CubicBezier(
+                read_f64(x1, &path)?,
+                read_f64(y1, &path)?,
+                read_f64(x2, &path)?,
+                read_f64(y2, &path)?,
+            )),
+            _ => Err(expected(&path, "an array of four numbers")),
+        };
+    }
+
+    if value.get("spring").is_null() {
+        return Err(expected(path, "an easing"));
+    }
+    let (stiffness, damping) = required(value, path, "spring", |value, path| {
+        let (stiffness, damping) = read_pair(value, path, "an array of two numbers")?;
+        Ok((read_f64(stiffness, path)?, read_f64(damping, path)?))
+    })?;
+
+    Ok(Easing::Spring { stiffness, damping })
+}
+
+fn read_action(value: &Json, path: &str) -> Result<Action, String> {
+    read_name(value, path, Action::from_name, "an action like `next`")
+}
+
+/// `{"osc": 9000, "bindings": {...}}` or `{"midi": "/dev/...", ...}`
+fn read_control(value: &Json, path: &str) -> Result<ControlInput, String> {
+    let bindings_path = join(path, "bindings");
+    let bindings = members(value.get("bindings"), &bindings_path)?;
+
+    if let Some(device) = optional(value, path, "midi", read_string)? {
+        let mut notes = HashMap::new();
+        for (note, action) in bindings {
+            let path = join(&bindings_path, note);
+            let note = note
+                .parse()
+                .ok()
+                .filter(|note| *note <= 127)
+                .ok_or_else(|| format!("`{}` is not a note from 0 to 127", path))?;
+            notes.insert(note, read_action(action, &path)?);
+        }
+
+        return Ok(ControlInput::Midi { device, notes });
+    }
+
+    let port = required(value, path, "osc", read_u32)?;
+    let port = u16::try_from(port).map_err(|_| expected(&join(path, "osc"), "a port"))?;
+    let mut addresses = HashMap::new();
+    for (address, action) in bindings {
+        let action = read_action(action, &join(&bindings_path, address))?;
+        addresses.insert(address.clone(), action);
+    }
+
+    Ok(ControlInput::Osc { port, addresses })
+}
+
+fn read_style(value: &Json, path: &str) -> Result<Style, String> {
+    let fonts = each(value.get("fonts"), &join(path, "fonts"), read_font)?;
+    let mut style = Style::new(fonts).map_err(|error| style_error(&error, path))?;
+
+    if let Some(background) = optional(value, path, "background", read_fill)? {
+        style.set_background(background);
+    }
+    if let Some(value) = optional(value, path, "color", read_color)? {
+        style.set_text_color(value);
+    }
+    let hinting = optional(value, path, "hinting", |value, path| {
+        read_name(
+            value,
+            path,
+            Hinting::from_name,
+            "normal, light, mono or none",
+        )
+    })?;
+    if let Some(hinting) = hinting {
+        style.set_hinting(hinting);
+    }
+    if let Some(antialias) = optional(value, path, "antialias", read_bool)? {
+        style.set_antialias(antialias);
+    }
+    if let Some(kerning) = optional(value, path, "kerning", read_bool)? {
+        style.set_kerning(kerning);
+    }
+
+    let text_styles = join(path, "textStyles");
+    for (name, text_style) in members(value.get("textStyles"), &text_styles)? {
+        let text_style = read_text_style(text_style, &join(&text_styles, name))?;
+        style
+            .add_text_style(name.clone(), text_style)
+            .map_err(|error| style_error(&error, path))?;
+    }
+
+    Ok(style)
+}
+
+fn style_error(error: &StyleError, path: &str) -> String {
+    match error {
+        StyleError::DuplicateFont(font) => format!(
+            "`{}.fonts` has the font {} with weight {}{} twice",
+            path,
+            font.name(),
+            font.weight(),
+            if font.italic() { " in italic" } else { "" }
+        ),
+        StyleError::DuplicateTextStyle(name) => {
+            format!("`{}.textStyles` has `{}` twice", path, name)
+        }
+    }
+}
+
+/// A font read from `path`, installed on the system or the bundled one
+fn read_font(value: &Json, path: &str) -> Result<Font, String> {
+    let weight = optional(value, path, "weight", read_u32)?.unwrap_or(DEFAULT_FONT_WEIGHT);
+    let italic = flag(value, path, "italic")?;
+
+    let mut font = if flag(value, path, "bundled")? {
+        Font::bundled(weight)
+    } else {
+        let name = required(value, path, "name", read_string)?;
+        if flag(value, path, "system")? {
+            Font::system(name, weight, italic)
+        } else {
+            Font::new(
+                name,
+                required(value, path, "path", read_string)?,
+                weight,
+                italic,
+            )
+        }
+    };
+    if let Some(size) = optional(value, path, "size", read_u32)? {
+        font.set_size(size);
+    }
+
+    Ok(font)
+}
+
+fn read_text_style(value: &Json, path: &str) -> Result<TextStyle, String> {
+    let mut text_style = TextStyle::new(
+        optional(value, path, "font", read_string)?,
+        optional(value, path, "size", read_u32)?,
+        optional(value, path, "color", read_color)?,
+    );
+    let fallback_fonts = join(path, "fallbackFonts");
+    text_style.set_fallback_fonts(each(
+        value.get("fallbackFonts"),
+        &fallback_fonts,
+        read_string,
+    )?);
+    if let Some(weight) = optional(value, path, "weight", read_u32)? {
+        text_style.set_weight(weight);
+    }
+    if let Some(italic) = optional(value, path, "italic", read_bool)? {
+        text_style.set_italic(italic);
+    }
+
+    Ok(text_style)
+}
+
+fn read_layout_item(value: &Json, path: &str) -> Result<LayoutItem, String> {
+    if value.get("type").as_str() == Some("placeholder") {
+        return Ok(LayoutItem::Placeholder(required(
+            value,
+            path,
+            "name",
+            read_string,
+        )?));
+    }
+
+    Ok(LayoutItem::Element(Box::new(read_element(value, path)?)))
+}
+
+fn read_section(value: &Json, path: &str) -> Result<Section, String> {
+    let first_slide = required(value, path, "firstSlide", read_u32)?;
+
+    Ok(Section::new(
+        required(value, path, "title", read_string)?,
+        first_slide as usize,
+    ))
+}
+
+fn read_slide(value: &Json, path: &str) -> Result<Slide, String> {
+    let mut slide = Slide::new(required(value, path, "name", read_string)?, vec![]);
+    if let Some(layout) = optional(value, path, "layout", read_string)? {
+        slide.set_layout(layout);
+    }
+    slide.set_hidden(flag(value, path, "hidden")?);
+    if let Some(seconds) = optional(value, path, "at", read_u32)? {
+        slide.set_scheduled_at(Duration::from_secs(u64::from(seconds)));
+    }
+    if let Some(background) = optional(value, path, "background", read_fill)? {
+        slide.set_background(background);
+    }
+    if let Some(transition) = optional(value, path, "transition", read_transition)? {
+        slide.set_transition(transition);
+    }
+    if let Some(seconds) = optional(value, path, "duration", read_u32)? {
+        slide.set_duration(Duration::from_secs(u64::from(seconds)));
+    }
+    if let Some(seconds) = optional(value, path, "target", read_u32)? {
+        slide.set_target(Duration::from_secs(u64::from(seconds)));
+    }
+
+    let elements = each(
+        value.get("elements"),
+        &join(path, "elements"),
+        |value, path| {
+            Ok((
+                read_element(value, path)?,
+                optional(value, path, "step", read_u32)?.unwrap_or(0),
+                optional(value, path, "placeholder", read_string)?,
+            ))
+        },
+    )?;
+    for (element, step, placeholder) in elements {
+        slide.push_element_in_placeholder(element, step, placeholder);
+    }
+    for note in each(value.get("notes"), &join(path, "notes"), read_string)? {
+        slide.add_note(note);
+    }
+
+    Ok(slide)
+}
+
+fn read_element(value: &Json, path: &str) -> Result<SlideElement, String> {
+    let kind = required(value, path, "type", read_string)?;
+    let mut element = match kind.as_str() {
+        "text" => SlideElement::Text(read_text(value, path)?),
+        "title" => SlideElement::Title(read_text(value, path)?),
+        "subtitle" => SlideElement::Subtitle(read_text(value, path)?),
+        "list" => SlideElement::List(List::new(
+            each(value.get("items"), &join(path, "items"), read_string)?,
+            optional(value, path, "bullet", read_string)?.unwrap_or_else(|| DEFAULT_BULLET.into()),
+            optional(value, path, "indent", read_length)?.unwrap_or(DEFAULT_INDENT),
+        )),
+        "image" => SlideElement::Image(Image::new(required(value, path, "path", read_string)?)),
+        "video" => SlideElement::Video(Video::new(required(value, path, "path", read_string)?)),
+        "row" => SlideElement::Row(read_container(value, path)?),
+        "column" => SlideElement::Column(read_container(value, path)?),
+        _ => {
+            return Err(expected(
+                &join(path, "type"),
+                "text, title, subtitle, list, image, video, row or column",
+            ))
+        }
+    };
+    element.set_placement(read_placement(value, path)?);
+    element.set_animation(read_animation(value, path)?);
+
+    Ok(element)
+}
+
+/// Text given as `spans`, or as `text` written in the DSL's inline markup
+fn read_text(value: &Json, path: &str) -> Result<Text, String> {
+    let spans = optional(value, path, "spans", |value, path| {
+        each(value, path, read_span)
+    })?;
+    let spans = if let Some(spans) = spans {
+        spans
+    } else {
+        let markup = required(value, path, "text", read_string)?;
+        markup::parse(&markup)
+            .map_err(|error| format!("`{}` has invalid markup: {:?}", join(path, "text"), error))?
+    };
+
+    let mut text = Text::with_spans(spans, optional(value, path, "style", read_string)?);
+    if let Some(size) = optional(value, path, "fontSize", read_u32)? {
+        text.set_size(size);
+    }
+
+    Ok(text)
+}
+
+fn read_span(value: &Json, path: &str) -> Result<Span, String> {
+    let mut span = Span::new(required(value, path, "text", read_string)?);
+    span.set_bold(flag(value, path, "bold")?);
+    span.set_italic(flag(value, path, "italic")?);
+    if let Some(value) = optional(value, path, "color", read_color)? {
+        span.set_color(value);
+    }
+
+    Ok(span)
+}
+
+fn read_container(value: &Json, path: &str) -> Result<Container, String> {
+    let mut container = Container::new(each(
+        value.get("elements"),
+        &join(path, "elements"),
+        read_element,
+    )?);
+    if let Some(gap) = optional(value, path, "gap", read_length)? {
+        container.set_gap(gap);
+    }
+    let align = optional(value, path, "align", |value, path| {
+        read_name(value, path, Align::from_name, "start, center or end")
+    })?;
+    if let Some(align) = align {
+        container.set_align(align);
+    }
+    if let Some(background) = optional(value, path, "background", read_fill)? {
+        container.set_background(background);
+    }
+
+    Ok(container)
+}
+
+/// Two values in an array, like the `at` of a placement
+fn read_pair<'a>(value: &'a Json, path: &str, what: &str) -> Result<(&'a Json, &'a Json), String> {
+    match value.as_array() {
+        [first, second] => Ok((first, second)),
+        _ => Err(expected(path, what)),
+    }
+}
+
+fn read_placement(value: &Json, path: &str) -> Result<Placement, String> {
+    let mut placement = Placement::default();
+    let position = optional(value, path, "at", |value, path| {
+        let (x, y) = read_pair(value, path, "an array of two lengths")?;
+        Ok((read_length(x, path)?, read_length(y, path)?))
+    })?;
+    if let Some((x, y)) = position {
+        placement.set_position(x, y);
+    }
+    let anchor = optional(value, path, "anchor", |value, path| {
+        read_name(value, path, Anchor::from_name, "an anchor like `center`")
+    })?;
+    if let Some(anchor) = anchor {
+        placement.set_anchor(anchor);
+    }
+    let size = optional(value, path, "size", |value, path| {
+        let (width, height) = read_pair(value, path, "an array of two lengths or nulls")?;
+        let extent = |value: &Json| match value {
+            Json::Null => Ok(None),
+            value => read_length(value, path).map(Some),
+        };
+        Ok((extent(width)?, extent(height)?))
+    })?;
+    if let Some((width, height)) = size {
+        placement.set_size(width, height);
+    }
+    if let Some(grow) = optional(value, path, "grow", read_u32)? {
+        placement.set_grow(grow);
+    }
+
+    Ok(placement)
+}
+
+fn read_animation(value: &Json, path: &str) -> Result<Animation, String> {
+    let mut animation = Animation::default();
+    if let Some(opacity) = optional(value, path, "opacity", read_u32)? {
+        if opacity > 100 {
+            return Err(expected(&join(path, "opacity"), "a percentage up to 100"));
+        }
+        animation.set_opacity(opacity);
+    }
+    if let Some(rotation) = optional(value, path, "rotate", read_f64)? {
+        animation.set_rotation(rotation);
+    }
+    if let Some(scale) = optional(value, path, "scale", read_f64)? {
+        animation.set_scale(scale);
+    }
+    let skew = optional(value, path, "skew", |value, path| {
+        let (x, y) = read_pair(value, path, "an array of two numbers")?;
+        Ok((read_f64(x, path)?, read_f64(y, path)?))
+    })?;
+    if let Some((x, y)) = skew {
+        animation.set_skew(x, y);
+    }
+    if let Some(milliseconds) = optional(value, path, "fadeIn", read_u32)? {
+        animation.set_fade_in(Duration::from_millis(u64::from(milliseconds)));
+    }
+    let slide_in = optional(value, path, "slideIn", |value, path| {
+        let side = required(value, path, "from", |value, path| {
+            read_name(value, path, Side::from_name, "left, right, top or bottom")
+        })?;
+        let milliseconds = optional(value, path, "duration", read_u32)?
+            .map_or(DEFAULT_ANIMATION_DURATION, u64::from);
+        Ok((side, Duration::from_millis(milliseconds)))
+    })?;
+    if let Some((side, duration)) = slide_in {
+        animation.set_slide_in(side, duration);
+    }
+    if let Some(easing) = optional(value, path, "easing", read_easing)? {
+        animation.set_easing(easing);
+    }
+    animation.set_keyframes(each(
+        value.get("keyframes"),
+        &join(path, "keyframes"),
+        read_keyframe,
+    )?);
+
+    Ok(animation)
+}
+
+/// A keyframe's time in milliseconds, the motions it reaches by then and
+/// its easing
+fn read_keyframe(value: &Json, path: &str) -> Result<Keyframe, String> {
+    let at = required(value, path, "at", read_u32)?;
+    let motions = each(value.get("motions"), &join(path, "motions"), read_motion)?;
+    let easing = optional(value, path, "easing", read_easing)?;
+
+    Ok(Keyframe::new(
+        Duration::from_millis(u64::from(at)),
+        motions,
+        easing.unwrap_or_default(),
+    ))
+}
+
+/// `{"motion", "value"}`, where the value is a length for `x` and `y`, a
+/// number for `scale` and `rotate` and a percentage for `opacity`
+fn read_motion(value: &Json, path: &str) -> Result<Motion, String> {
+    let name = required(value, path, "motion", read_string)?;
+    match name.as_str() {
+        "x" => required(value, path, "value", read_length).map(Motion::X),
+        "y" => required(value, path, "value", read_length).map(Motion::Y),
+        "scale" => required(value, path, "value", read_f64).map(Motion::Scale),
+        "rotate" => required(value, path, "value", read_f64).map(Motion::Rotate),
+        "opacity" => {
+            let opacity = required(value, path, "value", read_u32)?;
+            if opacity > 100 {
+                return Err(expected(&join(path, "value"), "a percentage up to 100"));
+            }
+            Ok(Motion::Opacity(opacity))
+        }
+        _ => Err(expected(
+            &join(path, "motion"),
+            "x, y, scale, rotate or opacity",
+        )),
+    }
+}
+
+/// A color, `{"from", "to", "direction"}` for a gradient or `{"image",
+/// "fit"}` for an image
+fn read_fill(value: &Json, path: &str) -> Result<Fill, String> {
+    if let Json::String(_) = value {
+        return read_color(value, path).map(Fill::Color);
+    }
+
+    if let Some(image) = optional(value, path, "image", read_string)? {
+        let fit = optional(value, path, "fit", |value, path| {
+            read_name(value, path, ImageFit::from_name, "cover, contain or tile")
+        })?;
+        return Ok(Fill::Image {
+            path: image,
+            fit: fit.unwrap_or(ImageFit::Cover),
+        });
+    }
+
+    if value.get("from").is_null() {
+        return Err(expected(path, "a color, a gradient or an image"));
+    }
+    let direction = optional(value, path, "direction", |value, path| {
+        read_name(
+            value,
+            path,
+            GradientDirection::from_name,
+            "horizontal, vertical, diagonal or radial",
+        )
+    })?;
+
+    Ok(Fill::Gradient {
+        from: required(value, path, "from", read_color)?,
+        to: required(value, path, "to", read_color)?,
+        direction: direction.unwrap_or(GradientDirection::Vertical),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn round_trips_presentations_through_json() {
+        let source = "metadata {\n    title \"Talk\",\n    width 800,\n    height 600,\n    \
+             transition fade 250 ease-in-out,\n    control osc 9000 {\n        \"/go\" next\n    },\n    \
+             control midi \"/dev/midi\" {\n        60 previous\n    },\n    show-footer,\n    \
+             spelling-ignore \"przntr\"\n}\n\n\
+             style {\n    font {\n        name sans,\n        path \"sans.ttf\",\n        weight 400\n    }\n    \
+             font {\n        name serif,\n        weight 700,\n        italic,\n        size 30,\n        system\n    }\n    \
+             background gradient #000000 #202040 radial\n    color #20202080\n    hinting light\n    \
+             text-style body {\n        font-stack sans, serif,\n        size 24,\n        italic true\n    }\n}\n\n\
+             layout two-col {\n    title \"Header\"\n    placeholder left\n}\n\n\
+             section \"Start\"\nslide \"a\" uses two-col hidden at 00:05:30 {\n    background image \"bg.png\" tile\n    \
+             duration 5\n    target 90\n    left {\n        text \"*x* [color=#ff0000]y[/color]\" style body size 30\n    }\n    \
+             step {\n        list {\n            item \"one\",\n            bullet \"-\"\n        } at 10px 5% anchor center fade-in 200ms\n        \
+             row {\n            image \"cat.png\" grow 1 opacity 40% rotate 15 scale 1.2 skew -10 5 slide-in-from top 1s cubic-bezier 0.5 0 0.5 1.5\n            video \"dog.mp4\" animate { at 0 y 5em ease-in, at 1s y 0px rotate -45 spring 150 8 }\n        \
+             } gap 1em align start size 50% auto\n    }\n    \
+             notes \"line\\nbreak\"\n}\n";
+        let presentation = crate::parse(source).unwrap();
+        let json = to_json(&presentation);
+
+        assert_eq!(Ok(presentation), from_json(&json));
+        assert_eq!(Some("#20202080"), json.get("style").get("color").as_str());
+        assert_eq!(
+            Some("50%"),
+            json.get("slides").as_array()[0].get("elements").as_array()[2]
+                .get("size")
+                .as_array()[0]
+                .as_str()
+        );
+    }
+
+    #[test]
+    pub fn reads_text_written_as_markup() {
+        let presentation = parse(
+            r#"{"metadata": {"title": "T"}, "slides": [{"name": "a", "elements": [
+                {"type": "title", "text": "*Hi*", "at": [10, "50%"]}, {"type": "list", "items": ["x"]}]}]}"#,
+        )
+        .unwrap();
+        let mut title = Text::with_spans(markup::parse("*Hi*").unwrap(), None);
+        let mut placement = Placement::default();
+        placement.set_position(Length::Pixels(10.0), Length::Percent(50.0));
+        title.set_placement(placement);
+        let expected = Slide::new(
+            "a".into(),
+            vec![
+                SlideElement::Title(title),
+                SlideElement::List(List::new(vec!["x".into()], "•".into(), DEFAULT_INDENT)),
+            ],
+        );
+
+        assert_eq!(&expected, &presentation.slides()[0]);
+        assert_eq!(Resolution::default(), presentation.metadata().resolution());
+    }
+
+    #[test]
+    pub fn names_the_member_in_errors() {
+        assert_eq!(
+            Err("`metadata.title` is missing".into()),
+            parse(r#"{"slides": []}"#)
+        );
+        assert_eq!(
+            Err("`slides[0].elements[1].type` should be text, title, subtitle, list, image, video, row or column".into()),
+            parse(r#"{"metadata": {"title": "T"}, "slides": [{"name": "a", "elements": [
+                {"type": "image", "path": "a.png"}, {"type": "table"}]}]}"#)
+        );
+        assert_eq!(
+            Err("`style.background` should be a color like `#rrggbb`".into()),
+            parse(r##"{"metadata": {"title": "T"}, "style": {"background": "red"}}"##)
+        );
+        assert!(parse("{").unwrap_err().starts_with("invalid JSON"));
+    }
+}
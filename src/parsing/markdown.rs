@@ -0,0 +1,197 @@
+use crate::presentation::{
+    Font, Image, Length, List, Metadata, Presentation, Resolution, Slide, SlideElement, Style,
+    Text, TextStyle,
+};
+
+const HEADING_STYLE: &str = "heading";
+
+/// Builds a presentation out of a Markdown document: every heading starts a
+/// new slide, lists become bullet lists and images become image elements
+pub fn parse(input: &str) -> Presentation {
+    let mut title: Option<String> = None;
+    let mut slides: Vec<Slide> = vec![];
+    let mut current: Option<Slide> = None;
+    let mut list_items: Vec<String> = vec![];
+
+    for line in input.lines().map(str::trim) {
+        if let Some(item) = list_item(line) {
+            list_items.push(item.into());
+            continue;
+        }
+
+        if !list_items.is_empty() {
+            push_element(&mut current, list(list_items));
+            list_items = vec![];
+        }
+
+        if let Some(heading) = heading(line) {
+            slides.extend(current.take());
+            title.get_or_insert_with(|| heading.into());
+            current = Some(Slide::new(
+                heading.into(),
+                vec![SlideElement::Text(Text::new(
+                    heading.into(),
+                    Some(HEADING_STYLE.into()),
+                ))],
+            ));
+        } else if let Some(path) = image(line) {
+            push_element(&mut current, SlideElement::Image(Image::new(path.into())));
+        } else if !line.is_empty() {
+            push_element(
+                &mut current,
+                SlideElement::Text(Text::new(line.into(), None)),
+            );
+        }
+    }
+
+    if !list_items.is_empty() {
+        push_element(&mut current, list(list_items));
+    }
+    slides.extend(current);
+
+    Presentation::new(
+        Metadata::new(title.unwrap_or_default(), Resolution::default()),
+        slides,
+        default_style(),
+    )
+}
+
+/// Markdown has no way of picking fonts, so the deck uses the system sans
+/// font and makes headings larger
+fn default_style() -> Style {
+    let mut style = Style::new(vec![Font::system("sans".into(), 400, false)])
+        .expect("The default style has a single font");
+    style
+        .add_text_style(HEADING_STYLE.into(), TextStyle::new(None, Some(48), None))
+        .expect("The default style has a single text style");
+
+    style
+}
+
+fn push_element(slide: &mut Option<Slide>, element: SlideElement) {
+    slide
+        .get_or_insert_with(|| Slide::new(String::new(), vec![]))
+        .push_element(element);
+}
+
+fn list(items: Vec<String>) -> SlideElement {
+    SlideElement::List(List::new(items, "•".into(), Length::Pixels(40.0)))
+}
+
+fn heading(line: &str) -> Option<&str> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+
+    if level == 0 || level > 6 || !line[level..].starts_with(' ') {
+        return None;
+    }
+
+    Some(line[level..].trim())
+}
+
+fn list_item(line: &str) -> Option<&str> {
+    for marker in &["- ", "* ", "+ "] {
+        if let Some(item) = line.strip_prefix(marker) {
+            return Some(item.trim());
+        }
+    }
+
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    if digits > 0 && line[digits..].starts_with(". ") {
+        return Some(line[digits + 2..].trim());
+    }
+
+    None
+}
+
+/// Path of an image written as `![alt](path "title")` on its own line
+fn image(line: &str) -> Option<&str> {
+    if !line.starts_with("![") || !line.ends_with(')') {
+        return None;
+    }
+
+    let start = line.find("](")? + 2;
+    let target = &line[start..line.len() - 1];
+
+    target.split_whitespace().next()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn text(content: &str) -> SlideElement {
+        SlideElement::Text(Text::new(content.into(), None))
+    }
+
+    fn heading_text(content: &str) -> SlideElement {
+        SlideElement::Text(Text::new(content.into(), Some(HEADING_STYLE.into())))
+    }
+
+    #[test]
+    pub fn headings_become_slides() {
+        let presentation = parse("# Intro\nHello\n\n## Details\nMore");
+
+        assert_eq!("Intro", presentation.metadata().title());
+        assert_eq!(
+            &vec![
+                Slide::new("Intro".into(), vec![heading_text("Intro"), text("Hello")]),
+                Slide::new(
+                    "Details".into(),
+                    vec![heading_text("Details"), text("More")]
+                )
+            ],
+            presentation.slides()
+        );
+    }
+
+    #[test]
+    pub fn lists_become_bullets() {
+        let presentation = parse("# Agenda\n- first\n* second\n1. third\nAfter");
+
+        assert_eq!(
+            &vec![Slide::new(
+                "Agenda".into(),
+                vec![
+                    heading_text("Agenda"),
+                    SlideElement::List(List::new(
+                        vec!["first".into(), "second".into(), "third".into()],
+                        "•".into(),
+                        Length::Pixels(40.0)
+                    )),
+                    text("After")
+                ]
+            )],
+            presentation.slides()
+        );
+    }
+
+    #[test]
+    pub fn images_become_image_elements() {
+        let presentation = parse("# Cats\n![a cat](images/cat.png \"Cat\")");
+
+        assert_eq!(
+            &vec![Slide::new(
+                "Cats".into(),
+                vec![
+                    heading_text("Cats"),
+                    SlideElement::Image(Image::new("images/cat.png".into()))
+                ]
+            )],
+            presentation.slides()
+        );
+    }
+
+    #[test]
+    pub fn content_before_first_heading_gets_its_own_slide() {
+        let presentation = parse("Welcome\n#not a heading");
+
+        assert_eq!("", presentation.metadata().title());
+        assert_eq!(
+            &vec![Slide::new(
+                String::new(),
+                vec![text("Welcome"), text("#not a heading")]
+            )],
+            presentation.slides()
+        );
+    }
+}
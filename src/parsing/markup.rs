@@ -0,0 +1,212 @@
+use crate::presentation::{Color, Span};
+
+/// Problems with the inline markup of a text
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum MarkupError {
+    /// The markup is still open at the end of the text
+    Unclosed(String),
+    /// `[/color]` without a matching `[color=...]`
+    UnexpectedClosingTag(String),
+    InvalidColor(String),
+}
+
+const COLOR_OPENING: &str = "[color=";
+const COLOR_CLOSING: &str = "[/color]";
+
+/// Splits text into spans styled with `*bold*`, `_italic_` and
+/// `[color=red]...[/color]`, which can be nested.
+///
+/// Like in Markdown, `*` and `_` only start emphasis at the beginning of a
+/// word and end it at the end of one, so `2 * 3` and `snake_case` stay as
+/// they are. A backslash makes the next character literal.
+pub fn parse(input: &str) -> Result<Vec<Span>, MarkupError> {
+    let characters: Vec<char> = input.chars().collect();
+    let mut spans = vec![];
+    let mut current = String::new();
+    let mut bold = false;
+    let mut italic = false;
+    let mut colors: Vec<Color> = vec![];
+    let mut index = 0;
+
+    while index < characters.len() {
+        let character = characters[index];
+        let previous = index.checked_sub(1).map(|previous| characters[previous]);
+        let next = characters.get(index + 1).copied();
+        let rest = &characters[index..];
+
+        match character {
+            '\\' if next.is_some() => {
+                current.extend(next);
+                index += 2;
+                continue;
+            }
+            '*' | '_' => {
+                let active = if character == '*' { bold } else { italic };
+                if (active && closes(previous, next)) || (!active && opens(previous, next)) {
+                    push_span(&mut spans, &mut current, bold, italic, &colors);
+                    if character == '*' {
+                        bold = !bold;
+                    } else {
+                        italic = !italic;
+                    }
+                } else {
+                    current.push(character);
+                }
+            }
+            '[' if starts_with(rest, COLOR_OPENING) && rest.contains(&']') => {
+                let value: String = rest[COLOR_OPENING.len()..]
+                    .iter()
+                    .copied()
+                    .take_while(|character| *character != ']')
+                    .collect();
+                let color = Color::from_hex(&value)
+                    .or_else(|| Color::from_name(&value))
+                    .ok_or_else(|| MarkupError::InvalidColor(value.clone()))?;
+
+                push_span(&mut spans, &mut current, bold, italic, &colors);
+                colors.push(color);
+                index += COLOR_OPENING.len() + value.chars().count() + 1;
+                continue;
+            }
+            '[' if starts_with(rest, COLOR_CLOSING) => {
+                push_span(&mut spans, &mut current, bold, italic, &colors);
+                colors
+                    .pop()
+                    .ok_or_else(|| MarkupError::UnexpectedClosingTag(COLOR_CLOSING.into()))?;
+                index += COLOR_CLOSING.len();
+                continue;
+            }
+            _ => current.push(character),
+        }
+
+        index += 1;
+    }
+
+    push_span(&mut spans, &mut current, bold, italic, &colors);
+    if bold {
+        return Err(MarkupError::Unclosed("*".into()));
+    }
+    if italic {
+        return Err(MarkupError::Unclosed("_".into()));
+    }
+    if !colors.is_empty() {
+        return Err(MarkupError::Unclosed("[color=...]".into()));
+    }
+    if spans.is_empty() {
+        spans.push(Span::new(String::new()));
+    }
+
+    Ok(spans)
+}
+
+fn starts_with(characters: &[char], pattern: &str) -> bool {
+    characters
+        .iter()
+        .copied()
+        .take(pattern.len())
+        .eq(pattern.chars())
+}
+
+/// Missing neighbours, at either end of the text, count as whitespace
+fn opens(previous: Option<char>, next: Option<char>) -> bool {
+    !previous.unwrap_or(' ').is_alphanumeric() && !next.unwrap_or(' ').is_whitespace()
+}
+
+fn closes(previous: Option<char>, next: Option<char>) -> bool {
+    !previous.unwrap_or(' ').is_whitespace() && !next.unwrap_or(' ').is_alphanumeric()
+}
+
+fn push_span(
+    spans: &mut Vec<Span>,
+    current: &mut String,
+    bold: bool,
+    italic: bool,
+    colors: &[Color],
+) {
+    if current.is_empty() {
+        return;
+    }
+
+    let mut span = Span::new(std::mem::take(current));
+    span.set_bold(bold);
+    span.set_italic(italic);
+    if let Some(color) = colors.last() {
+        span.set_color(*color);
+    }
+    spans.push(span);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn span(content: &str, bold: bool, italic: bool, color: Option<Color>) -> Span {
+        let mut span = Span::new(content.into());
+        span.set_bold(bold);
+        span.set_italic(italic);
+        if let Some(color) = color {
+            span.set_color(color);
+        }
+
+        span
+    }
+
+    #[test]
+    pub fn plain_text_is_a_single_span() {
+        assert_eq!(
+            Ok(vec![span("just text", false, false, None)]),
+            parse("just text")
+        );
+    }
+
+    #[test]
+    pub fn parses_nested_emphasis() {
+        assert_eq!(
+            Ok(vec![
+                span("a ", false, false, None),
+                span("bold ", true, false, None),
+                span("and italic", true, true, None),
+                span(" word", false, false, None)
+            ]),
+            parse("a *bold _and italic_* word")
+        );
+    }
+
+    #[test]
+    pub fn parses_colors() {
+        let red = Color::new(0xff, 0, 0, 0xff);
+
+        assert_eq!(
+            Ok(vec![
+                span("a ", false, false, None),
+                span("red", false, false, Some(red)),
+                span(" word", false, false, None)
+            ]),
+            parse("a [color=#ff0000]red[/color] word")
+        );
+    }
+
+    #[test]
+    pub fn keeps_markers_inside_words_and_escaped_ones() {
+        assert_eq!(
+            Ok(vec![span("snake_case 2 * 3 *literal*", false, false, None)]),
+            parse("snake_case 2 * 3 \\*literal\\*")
+        );
+    }
+
+    #[test]
+    pub fn fails_on_unclosed_markup() {
+        assert_eq!(
+            Err(MarkupError::Unclosed("*".into())),
+            parse("*never closed")
+        );
+        assert_eq!(
+            Err(MarkupError::UnexpectedClosingTag("[/color]".into())),
+            parse("text[/color]")
+        );
+        assert_eq!(
+            Err(MarkupError::InvalidColor("reddish".into())),
+            parse("[color=reddish]text[/color]")
+        );
+    }
+}
@@ -1,3 +1,14 @@
+pub mod formatter;
+pub mod grammar;
+pub mod highlighting;
+pub mod json;
+pub mod markdown;
+pub mod markup;
 pub mod parser;
-mod token_stream;
+pub mod properties;
+pub mod serializer;
+pub mod token_stream;
 pub mod tokenizer;
+
+#[cfg(test)]
+mod proptests;
@@ -1,6 +1,31 @@
+use super::grammar::{self, Content, ElementRule, GivenProperties, PropertyRule, Target};
+use super::properties::{PropertyBag, Value, ValueKind};
 use super::token_stream::{Peekable, Token, TokenStream, TokenizerFailure, TokenizerResult};
+use crate::assets::{self, AssetLoader};
+use crate::control::Action;
+use crate::easing::Easing;
+use crate::parsing::markup::{self, MarkupError};
 use crate::parsing::token_stream::SourceLocationRange;
-use crate::presentation::{Font, Presentation, Slide, Style, StyleError};
+use crate::parsing::tokenizer::Tokenizer;
+use crate::presentation::{
+    Align, Anchor, Animation, Color, Container, ControlInput, Fill, Font, GradientDirection,
+    Hinting, Image, ImageFit, Keyframe, Layout, LayoutItem, Length, List, Metadata, Motion,
+    Placement, Presentation, Resolution, Section, Side, Slide, SlideElement, Style, StyleError,
+    Text, TextStyle, Theme, Transition, TransitionKind, Video,
+};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const DEFAULT_TRANSITION_DURATION: u64 = 300;
+/// Milliseconds elements take to fade or slide in when not told otherwise
+const DEFAULT_ANIMATION_DURATION: u64 = 300;
+const DEFAULT_FONT_WEIGHT: u32 = 400;
+/// Weights go from thin at 100 to black at 900, variable fonts going further
+const MIN_FONT_WEIGHT: i128 = 1;
+const MAX_FONT_WEIGHT: i128 = 1000;
 
 #[derive(Debug, Eq, PartialEq)]
 pub enum Error {
@@ -14,6 +39,117 @@ pub enum Error {
     },
     TokenizerFailure(TokenizerFailure),
     InvalidStyleDefinition(StyleError),
+    InvalidColor {
+        value: String,
+        location: SourceLocationRange,
+    },
+    InvalidTransition {
+        value: String,
+        location: SourceLocationRange,
+    },
+    InvalidAnchor {
+        value: String,
+        location: SourceLocationRange,
+    },
+    InvalidSide {
+        value: String,
+        location: SourceLocationRange,
+    },
+    InvalidMotion {
+        value: String,
+        location: SourceLocationRange,
+    },
+    InvalidAlignment {
+        value: String,
+        location: SourceLocationRange,
+    },
+    InvalidHinting {
+        value: String,
+        location: SourceLocationRange,
+    },
+    InvalidGradientDirection {
+        value: String,
+        location: SourceLocationRange,
+    },
+    InvalidImageFit {
+        value: String,
+        location: SourceLocationRange,
+    },
+    InvalidControlInput {
+        value: String,
+        location: SourceLocationRange,
+    },
+    /// Port or note number out of its range
+    InvalidControlNumber {
+        value: i128,
+        location: SourceLocationRange,
+    },
+    InvalidAction {
+        value: String,
+        location: SourceLocationRange,
+    },
+    /// Property given twice in the same block
+    DuplicateProperty {
+        block: String,
+        property: String,
+        location: SourceLocationRange,
+    },
+    /// Required property left out of a block, located at the block's keyword
+    MissingProperty {
+        block: String,
+        property: String,
+        location: SourceLocationRange,
+    },
+    /// Number outside of what its property allows
+    OutOfRange {
+        property: String,
+        value: i128,
+        min: i128,
+        /// None when only the lower bound is checked
+        max: Option<i128>,
+        location: SourceLocationRange,
+    },
+    IncludeFailed {
+        path: String,
+        reason: String,
+        location: SourceLocationRange,
+    },
+    IncludeCycle {
+        path: String,
+        location: SourceLocationRange,
+    },
+    ThemeFailed {
+        path: String,
+        reason: String,
+        location: SourceLocationRange,
+    },
+    /// Image, video or font named by a URL that couldn't be downloaded
+    AssetFailed {
+        url: String,
+        reason: String,
+        location: SourceLocationRange,
+    },
+    /// Theme files only have styles and layouts
+    SlidesInTheme,
+    DuplicateLayout {
+        name: String,
+        location: SourceLocationRange,
+    },
+    UndefinedVariable {
+        name: String,
+        location: SourceLocationRange,
+    },
+    InvalidMarkup {
+        error: MarkupError,
+        location: SourceLocationRange,
+    },
+    /// Errors found in an included file, located within that file's source
+    InIncludedFile {
+        path: String,
+        source: String,
+        location: SourceLocationRange,
+        errors: Vec<Error>,
+    },
 }
 
 impl From<StyleError> for Error {
@@ -22,13 +158,39 @@ impl From<StyleError> for Error {
     }
 }
 
+/// Element or one of the other tokens a block takes
+enum BlockItem {
+    Element(Box<SlideElement>),
+    /// Nothing following the token is consumed
+    Other(Token),
+}
+
+/// Everything a file defines besides its metadata
+#[derive(Default)]
+struct Definitions {
+    slides: Vec<Slide>,
+    sections: Vec<Section>,
+    style: Option<Style>,
+    layouts: HashMap<String, Layout>,
+}
+
 pub struct Parser<'a, T: TokenStream> {
     token_stream: Peekable<'a, T>,
+    errors: Vec<Error>,
+    source_path: Option<PathBuf>,
+    /// Files being parsed, from the outermost one, used to detect cycles
+    include_chain: Vec<PathBuf>,
+    included_files: Vec<PathBuf>,
+    /// Values from `define`, substituted for `${name}` in later strings
+    variables: HashMap<String, String>,
+    /// Theme the presentation's style and layouts go over
+    theme: Option<Theme>,
+    assets: AssetLoader,
 }
 
 macro_rules! consume {
     ($self:expr, $expected:pat) => {
-        match $self.token_stream.next() {
+        match $self.next_token() {
             TokenizerResult::Ok($expected, _) => {}
             result => {
                 return Self::handle_invalid_result(
@@ -39,7 +201,7 @@ macro_rules! consume {
         }
     };
     ($self:expr, $($expected:pat => $action:expr),+) => {
-        match $self.token_stream.next() {
+        match $self.next_token() {
             $(
                 TokenizerResult::Ok($expected, _) => $action,
             )+
@@ -84,274 +246,2961 @@ impl<'a, T: TokenStream> Parser<'a, T> {
     pub fn new(token_stream: &'a mut T) -> Self {
         Parser {
             token_stream: Peekable::new(token_stream),
+            errors: vec![],
+            source_path: None,
+            include_chain: vec![],
+            included_files: vec![],
+            variables: HashMap::new(),
+            theme: None,
+            assets: AssetLoader::default(),
         }
     }
 
-    pub fn parse(&mut self) -> Result<Presentation, Error> {
-        let mut slides: Vec<Slide> = Vec::new();
-        let mut style = None;
-        let title: String = self.parse_metadata()?;
-
-        loop {
-            peek_decide!(
-                self,
-                Token::KeywordSlide => slides.push(self.parse_slide()?),
-                Token::KeywordStyle => style = Some(self.parse_style()?)
-                ;break
-            );
+    /// Path of the file being parsed, included files are looked up relative
+    /// to it (or to the working directory when it's not set)
+    pub fn set_source_path(&mut self, path: PathBuf) {
+        if let Ok(canonical_path) = fs::canonicalize(&path) {
+            self.include_chain.push(canonical_path);
         }
-
-        Ok(Presentation::new(
-            title,
-            slides,
-            style.unwrap_or_else(Style::empty),
-        ))
+        self.source_path = Some(path);
     }
 
-    fn parse_slide(&mut self) -> Result<Slide, Error> {
-        consume!(self, Token::KeywordSlide);
-        let slide_name = consume!(self, Token::String(slide_name) => slide_name);
-        consume!(self, Token::OpeningBrace);
-        consume!(self, Token::ClosingBrace);
+    /// Styles the presentation with the theme instead of the one its
+    /// metadata names
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = Some(theme);
+    }
 
-        Ok(Slide::new(slide_name))
+    /// Loads the images, videos, fonts and themes named by URL, and those of
+    /// the included files
+    pub fn set_asset_loader(&mut self, assets: AssetLoader) {
+        self.assets = assets;
     }
 
-    fn parse_metadata(&mut self) -> Result<String, Error> {
-        consume!(self, Token::KeywordMetadata);
-        consume!(self, Token::OpeningBrace);
-        consume!(self, Token::KeywordTitle);
-        let title = consume!(self, Token::String(title) => title);
-        consume!(self, Token::ClosingBrace);
+    /// Parses the whole presentation, skipping past errors to report as many
+    /// of them as possible in one go
+    pub fn parse(&mut self) -> Result<Presentation, Vec<Error>> {
+        while let Some(TokenizerResult::Ok(Token::KeywordDefine, _)) = self.token_stream.peek() {
+            if let Err(error) = self.parse_define() {
+                self.recover(error, 0, Self::starts_metadata_or_define);
+            }
+        }
+        let metadata = match self.parse_metadata() {
+            Ok(metadata) => Some(metadata),
+            Err(error) => {
+                self.recover(error, 0, Self::starts_top_level_item);
+                None
+            }
+        };
+        let definitions = self.parse_top_level_items();
 
-        Ok(title)
-    }
+        match metadata {
+            Some(metadata) if self.errors.is_empty() => {
+                let mut presentation = Presentation::new(
+                    metadata,
+                    definitions.slides,
+                    definitions.style.unwrap_or_else(Style::empty),
+                );
+                for (name, layout) in definitions.layouts {
+                    presentation.add_layout(name, layout);
+                }
+                for section in definitions.sections {
+                    presentation.add_section(section);
+                }
+                for path in self.included_files.drain(..) {
+                    presentation.add_included_file(path);
+                }
+                if let Some(theme) = self.theme.take() {
+                    presentation.apply_theme(theme);
+                }
 
-    fn parse_style(&mut self) -> Result<Style, Error> {
-        let mut fonts: Vec<Font> = vec![];
+                Ok(presentation)
+            }
+            _ => Err(self.errors.drain(..).collect()),
+        }
+    }
 
-        consume!(self, Token::KeywordStyle);
-        consume!(self, Token::OpeningBrace);
+    /// Parses a theme file, which has the same styles, layouts, definitions
+    /// and includes as a presentation but no metadata or slides
+    pub fn parse_theme(&mut self) -> Result<Theme, Vec<Error>> {
+        let definitions = self.parse_top_level_items();
+        if !definitions.slides.is_empty() {
+            self.errors.push(Error::SlidesInTheme);
+        }
+        if !self.errors.is_empty() {
+            return Err(self.errors.drain(..).collect());
+        }
 
-        loop {
-            peek_decide!(
-                self,
-                Token::KeywordFont => fonts.push(self.parse_font()?),
-                Token::ClosingBrace => { consume!(self, Token::ClosingBrace); break }
-            );
+        let mut theme = Theme::new(definitions.style.unwrap_or_else(Style::empty));
+        for (name, layout) in definitions.layouts {
+            theme.add_layout(name, layout);
+        }
+        if let Some(path) = &self.source_path {
+            theme.add_file(path.clone());
+        }
+        for path in self.included_files.drain(..) {
+            theme.add_file(path);
         }
 
-        Ok(Style::new(fonts)?)
+        Ok(theme)
     }
 
-    fn parse_font(&mut self) -> Result<Font, Error> {
-        let mut italic = false;
-        let mut name: Option<String> = None;
-        let mut path: Option<String> = None;
-        let mut weight: Option<i128> = None;
-
-        consume!(self, Token::KeywordFont);
-        consume!(self, Token::OpeningBrace);
+    fn parse_top_level_items(&mut self) -> Definitions {
+        let mut definitions = Definitions::default();
 
         loop {
-            consume!(
-                self,
-                Token::KeywordName => name = consume!(self, Token::Name(font_name) => Some(font_name)),
-                Token::KeywordPath => path = consume!(self, Token::String(font_path) => Some(font_path)),
-                Token::KeywordWeight => weight = consume!(self, Token::Integer(font_weight) => Some(font_weight)),
-                Token::KeywordItalic => italic = true,
-                Token::ClosingBrace => break
-            );
-
-            consume!(self, Token::Comma);
+            match self.parse_top_level_item(&mut definitions) {
+                Ok(true) => {}
+                Ok(false) => break,
+                Err(error) => {
+                    self.recover(error, 0, Self::starts_top_level_item);
+                }
+            }
         }
 
-        // todo return error instead of unwrap panicking
-        Ok(Font::new(
-            name.unwrap(),
-            path.unwrap(),
-            weight.unwrap() as u32,
-            italic,
-        ))
+        definitions
     }
 
-    fn handle_invalid_result<TOk>(
-        result: &TokenizerResult,
-        expected: String,
-    ) -> Result<TOk, Error> {
-        Err(match result {
-            TokenizerResult::Ok(token, location) => Error::UnexpectedToken {
-                actual: format!("{:?}", token),
-                expected,
-                location: *location,
+    /// Returns false once there is nothing more to parse
+    fn parse_top_level_item(&mut self, definitions: &mut Definitions) -> Result<bool, Error> {
+        peek_decide!(
+            self,
+            Token::KeywordSlide => definitions.slides.push(self.parse_slide()?),
+            Token::KeywordSection => {
+                consume!(self, Token::KeywordSection);
+                let title = consume!(self, Token::String(title) => title);
+                definitions.sections.push(Section::new(title, definitions.slides.len()));
             },
-            TokenizerResult::Err(error) => Error::TokenizerFailure(error.clone()),
-            TokenizerResult::End => Error::UnexpectedEndOfStream { expected },
-        })
+            Token::KeywordStyle => definitions.style = Some(self.parse_style()?),
+            Token::KeywordLayout => {
+                let (name, layout, location) = self.parse_layout()?;
+                Self::add_layout(definitions, name, layout, location)?;
+            },
+            Token::KeywordDefine => self.parse_define()?,
+            Token::KeywordInclude => self.parse_include(definitions)?
+            ;return Ok(false)
+        );
+
+        Ok(true)
     }
-}
 
-#[cfg(test)]
-mod test {
-    use super::super::token_stream::{
-        MockTokenStream, SourceLocation, SourceLocationRange, TokenizerFailureKind,
-    };
-    use super::*;
-    use crate::parsing::tokenizer::Tokenizer;
-    use crate::presentation::Font;
+    fn starts_top_level_item(token: &Token) -> bool {
+        [
+            Token::KeywordSlide,
+            Token::KeywordSection,
+            Token::KeywordStyle,
+            Token::KeywordLayout,
+            Token::KeywordDefine,
+            Token::KeywordInclude,
+        ]
+        .contains(token)
+    }
 
-    macro_rules! parser_test_fail {
-        ($test_name:ident, $results:expr, $expected_error:expr) => {
-            #[test]
-            pub fn $test_name() {
-                let mut tokenizer = Tokenizer::new($results);
-                let mut parser = Parser::new(&mut tokenizer);
+    fn starts_metadata_or_define(token: &Token) -> bool {
+        [Token::KeywordMetadata, Token::KeywordDefine].contains(token)
+    }
 
-                let error: Error = $expected_error;
-                assert_eq!(parser.parse(), Err(error));
+    /// Parses `define <name> "value"`, a later definition of the same name
+    /// replaces the earlier one from then on
+    fn parse_define(&mut self) -> Result<(), Error> {
+        consume!(self, Token::KeywordDefine);
+        let name = consume!(self, Token::Name(name) => name);
+        let value = consume!(self, Token::String(value) => value);
+
+        self.variables.insert(name, value);
+
+        Ok(())
+    }
+
+    /// Takes the next token, with variables substituted in strings
+    fn next_token(&mut self) -> TokenizerResult {
+        match self.token_stream.next() {
+            TokenizerResult::Ok(Token::String(value), location) => {
+                let value = self.interpolate(&value, location);
+                TokenizerResult::Ok(Token::String(value), location)
             }
-        };
+            result => result,
+        }
     }
 
-    macro_rules! parser_test {
-        ($test_name:ident, $results:expr, $expected_presentation:expr) => {
-            #[test]
-            pub fn $test_name() {
-                let mut tokenizer = Tokenizer::new($results);
-                let mut parser = Parser::new(&mut tokenizer);
+    /// Replaces every `${name}` with the variable's value, undefined
+    /// variables are reported and left as they are. `$${` stands for a
+    /// plain `${`.
+    fn interpolate(&mut self, value: &str, location: SourceLocationRange) -> String {
+        let mut result = String::new();
+        let mut rest = value;
 
-                let parsed = parser.parse().unwrap();
+        while let Some(start) = rest.find("${") {
+            if rest[..start].ends_with('$') {
+                result.push_str(&rest[..start - 1]);
+                result.push_str("${");
+                rest = &rest[start + 2..];
+                continue;
+            }
+            let end = if let Some(length) = rest[start..].find('}') {
+                start + length
+            } else {
+                break;
+            };
+            let name = &rest[start + 2..end];
 
-                assert_eq!(parsed, $expected_presentation);
+            result.push_str(&rest[..start]);
+            if let Some(variable) = self.variables.get(name) {
+                result.push_str(variable);
+            } else {
+                self.errors.push(Error::UndefinedVariable {
+                    name: name.into(),
+                    location,
+                });
+                result.push_str(&rest[start..=end]);
             }
-        };
+            rest = &rest[end + 1..];
+        }
+        result.push_str(rest);
+
+        result
     }
 
-    parser_test_fail!(
-        fails_on_slide_before_metadata,
-        "slide \"some slide\" {}",
-        Error::UnexpectedToken {
-            actual: "KeywordSlide".into(),
-            expected: "KeywordMetadata".into(),
-            location: SourceLocationRange::new(
-                SourceLocation::new(0, 1),
-                SourceLocation::new(0, 6)
-            )
+    fn add_layout(
+        definitions: &mut Definitions,
+        name: String,
+        layout: Layout,
+        location: SourceLocationRange,
+    ) -> Result<(), Error> {
+        if definitions.layouts.contains_key(&name) {
+            return Err(Error::DuplicateLayout { name, location });
         }
-    );
 
-    parser_test!(
-        can_parse_metadata_block,
-        "metadata { title \"some title\" }",
-        Presentation::new("some title".into(), vec![], Style::new(vec![]).unwrap())
-    );
+        definitions.layouts.insert(name, layout);
 
-    parser_test!(
-        can_parse_slide_after_metadata,
-        "metadata { title \"some title\" } slide \"first slide\" {}",
-        Presentation::new(
-            "some title".into(),
-            vec![Slide::new("first slide".into())],
-            Style::new(vec![]).unwrap()
-        )
-    );
+        Ok(())
+    }
 
-    parser_test_fail!(
-        fails_if_block_type_is_not_slide,
-        "metadata { title \"some title\" } notslide \"some slide\" {}",
-        Error::UnexpectedToken {
-            actual: "Name(\"notslide\")".into(),
-            expected: "KeywordSlide, KeywordStyle".into(),
-            location: SourceLocationRange::new(
-                SourceLocation::new(0, 33),
-                SourceLocation::new(0, 41)
-            )
-        }
-    );
+    /// Parses `include "path"`, splicing in the slides, the layouts and the
+    /// style of the included file
+    fn parse_include(&mut self, definitions: &mut Definitions) -> Result<(), Error> {
+        consume!(self, Token::KeywordInclude);
+        let (included_path, location) = match self.next_token() {
+            TokenizerResult::Ok(Token::String(path), location) => (path, location),
+            result => return Self::handle_invalid_result(&result, "String(text)".into()),
+        };
+        let path = self.relative_path(&included_path);
+        let display_path = path.display().to_string();
+        let include_failed = |error: std::io::Error| Error::IncludeFailed {
+            path: display_path.clone(),
+            reason: error.to_string(),
+            location,
+        };
 
-    parser_test_fail!(
-        fails_on_missing_braces,
-        "metadata { title \"some title\" } slide \"some slide\"",
-        Error::UnexpectedEndOfStream {
-            expected: "OpeningBrace".into()
+        let canonical_path = fs::canonicalize(&path).map_err(include_failed)?;
+        if self.include_chain.contains(&canonical_path) {
+            return Err(Error::IncludeCycle {
+                path: display_path,
+                location,
+            });
         }
-    );
+        let source = fs::read_to_string(&path).map_err(include_failed)?;
 
-    parser_test_fail!(
-        fails_on_unexpected_token_after_slide_name,
-        "metadata { title \"some title\" } slide \"some slide\" }",
-        Error::UnexpectedToken {
-            actual: "ClosingBrace".into(),
-            expected: "OpeningBrace".into(),
-            location: SourceLocationRange::new_single(SourceLocation::new(0, 52))
+        let mut tokenizer = Tokenizer::new(&source);
+        let mut parser = Parser::new(&mut tokenizer);
+        parser.include_chain.clone_from(&self.include_chain);
+        parser.variables.clone_from(&self.variables);
+        parser.assets.clone_from(&self.assets);
+        parser.set_source_path(path.clone());
+        let included = parser.parse_top_level_items();
+
+        if !parser.errors.is_empty() {
+            return Err(Error::InIncludedFile {
+                path: display_path,
+                source: source.clone(),
+                location,
+                errors: parser.errors,
+            });
         }
-    );
 
-    parser_test_fail!(
-        fails_on_unexpected_token_after_slide_opening_brace,
-        "metadata { title \"some title\" } slide \"some slide\" {{",
-        Error::UnexpectedToken {
-            actual: "OpeningBrace".into(),
-            expected: "ClosingBrace".into(),
-            location: SourceLocationRange::new_single(SourceLocation::new(0, 53))
+        let offset = definitions.slides.len();
+        definitions.sections.extend(
+            included.sections.into_iter().map(|section| {
+                Section::new(section.title().clone(), offset + section.first_slide())
+            }),
+        );
+        definitions.slides.extend(included.slides);
+        if included.style.is_some() {
+            definitions.style = included.style;
         }
-    );
+        for (name, layout) in included.layouts {
+            Self::add_layout(definitions, name, layout, location)?;
+        }
+        self.included_files.push(path);
+        self.included_files.extend(parser.included_files);
+        self.variables = parser.variables;
 
-    parser_test!(
-        can_parse_single_font,
-        "metadata { title \"some title\" } style { font { path \"some_path\", name my-wonderful-font, weight 500,}}",
-        Presentation::new(
-            "some title".into(),
-            vec![],
-            Style::new(vec![Font::new(
-                "my-wonderful-font".into(),
-                "some_path".into(),
-                500,
-                false
-            )]).unwrap()
-        )
-    );
+        Ok(())
+    }
 
-    parser_test!(
-        can_parse_italic_font,
-        "metadata { title \"some title\" } style { font { path \"some_path\", name my-wonderful-font, weight 500, italic, } }",
-        Presentation::new(
-            "some title".into(),
-            vec![],
-            Style::new(vec![Font::new(
-                "my-wonderful-font".into(),
-                "some_path".into(),
-                500,
-                true
-            )]).unwrap()
-        )
-    );
+    /// Loads the theme named in the metadata, a file next to the
+    /// presentation with the `.przt` extension unless the name has one, or
+    /// one downloaded from a URL
+    fn load_theme(&mut self, name: &str, location: SourceLocationRange) -> Result<Theme, Error> {
+        let path = if assets::is_url(name) {
+            self.assets.load(name).map_err(|error| Error::ThemeFailed {
+                path: name.into(),
+                reason: error.to_string(),
+                location,
+            })?
+        } else {
+            let mut path = self.relative_path(name);
+            if path.extension().is_none() {
+                path.set_extension("przt");
+            }
+            path
+        };
+        let display_path = path.display().to_string();
+        let source = fs::read_to_string(&path).map_err(|error| Error::ThemeFailed {
+            path: display_path.clone(),
+            reason: error.to_string(),
+            location,
+        })?;
 
-    parser_test!(
-        slide_after_style,
-        "metadata { title \"some title\" } style { font { path \"some_path\", name my-wonderful-font, weight 500, } } slide \"some slide\" {}",
-        Presentation::new(
-            "some title".into(),
-            vec![Slide::new("some slide".into())],
-            Style::new(vec![Font::new(
-                "my-wonderful-font".into(),
-                "some_path".into(),
-                500,
-                false
-            )]).unwrap()
-        )
-    );
+        let mut tokenizer = Tokenizer::new(&source);
+        let mut parser = Parser::new(&mut tokenizer);
+        parser.variables.clone_from(&self.variables);
+        parser.assets.clone_from(&self.assets);
+        parser.set_source_path(path);
 
-    parser_test!(
-        style_with_multiple_fonts,
-        "metadata { title \"some title\" } \n\
-         style { \n\
-         font { path \"path1\", name font-1, weight 500, } \n\
-         font { path \"path2\", name font-1, weight 500, italic, } \n\
-         }",
-        Presentation::new(
-            "some title".into(),
-            vec![],
+        parser
+            .parse_theme()
+            .map_err(|errors| Error::InIncludedFile {
+                path: display_path,
+                source: source.clone(),
+                location,
+                errors,
+            })
+    }
+
+    /// The path relative to the directory of the file being parsed
+    fn relative_path(&self, path: &str) -> PathBuf {
+        self.source_path
+            .as_ref()
+            .and_then(|source_path| source_path.parent())
+            .unwrap_or_else(|| Path::new(""))
+            .join(path)
+    }
+
+    /// Images, videos and fonts are looked up next to the file using them,
+    /// not in the directory przntr was started from. Those named by URL are
+    /// downloaded.
+    fn asset_path(&self, path: String, location: SourceLocationRange) -> Result<String, Error> {
+        if assets::is_url(&path) {
+            return match self.assets.load(&path) {
+                Ok(downloaded) => Ok(downloaded.to_string_lossy().into_owned()),
+                Err(error) => Err(Error::AssetFailed {
+                    url: path,
+                    reason: error.to_string(),
+                    location,
+                }),
+            };
+        }
+        if self.source_path.is_none() || Path::new(&path).is_absolute() {
+            return Ok(path);
+        }
+
+        Ok(self.relative_path(&path).to_string_lossy().into_owned())
+    }
+
+    /// Parses the string naming an image or video and finds its file
+    fn parse_asset_path(&mut self) -> Result<String, Error> {
+        match self.next_token() {
+            TokenizerResult::Ok(Token::String(path), location) => self.asset_path(path, location),
+            result => Self::handle_invalid_result(&result, "String(path)".into()),
+        }
+    }
+
+    /// Records the error and skips tokens until one for which `is_boundary`
+    /// holds at the given brace depth, returns false if the block at that
+    /// depth was closed (or the input ended) in the meantime
+    fn recover(&mut self, error: Error, depth: u32, is_boundary: fn(&Token) -> bool) -> bool {
+        self.errors.push(error);
+
+        loop {
+            let current_depth = self.token_stream.depth();
+
+            match self.token_stream.peek() {
+                None | Some(TokenizerResult::End) => return false,
+                Some(TokenizerResult::Err(failure)) => {
+                    let error = Error::TokenizerFailure(failure.clone());
+                    if self.errors.last() != Some(&error) {
+                        self.errors.push(error);
+                    }
+                }
+                Some(TokenizerResult::Ok(token, _)) => {
+                    if current_depth < depth {
+                        return false;
+                    }
+                    if current_depth == depth && is_boundary(token) {
+                        return true;
+                    }
+                }
+            }
+
+            self.token_stream.next();
+        }
+    }
+
+    fn parse_slide(&mut self) -> Result<Slide, Error> {
+        consume!(self, Token::KeywordSlide);
+        let slide_name = consume!(self, Token::String(slide_name) => slide_name);
+        let mut slide = Slide::new(slide_name, vec![]);
+        if let Some(TokenizerResult::Ok(Token::KeywordUses, _)) = self.token_stream.peek() {
+            consume!(self, Token::KeywordUses);
+            slide.set_layout(consume!(self, Token::Name(layout) => layout));
+        }
+        if let Some(TokenizerResult::Ok(Token::KeywordHidden, _)) = self.token_stream.peek() {
+            consume!(self, Token::KeywordHidden);
+            slide.set_hidden(true);
+        }
+        if let Some(TokenizerResult::Ok(Token::KeywordAt, _)) = self.token_stream.peek() {
+            consume!(self, Token::KeywordAt);
+            slide.set_scheduled_at(consume!(self, Token::Time(time) => time));
+        }
+        consume!(self, Token::OpeningBrace);
+
+        self.parse_slide_items(&mut slide, 0, None);
+
+        Ok(slide)
+    }
+
+    /// Parses a `step { ... }` group, revealed one key press after the
+    /// groups before it
+    fn parse_step(&mut self, slide: &mut Slide, placeholder: Option<&str>) -> Result<(), Error> {
+        consume!(self, Token::OpeningBrace);
+        let step = slide.step_count() + 1;
+
+        self.parse_slide_items(slide, step, placeholder);
+
+        Ok(())
+    }
+
+    /// Parses a `<placeholder> { ... }` group filling the placeholder of the
+    /// same name in the slide's layout
+    fn parse_placeholder(&mut self, slide: &mut Slide, step: u32) -> Result<(), Error> {
+        let placeholder = consume!(self, Token::Name(placeholder) => placeholder);
+        consume!(self, Token::OpeningBrace);
+
+        self.parse_slide_items(slide, step, Some(&placeholder));
+
+        Ok(())
+    }
+
+    /// Parses the contents of a block opened right before, up to and
+    /// including its closing brace
+    fn parse_slide_items(&mut self, slide: &mut Slide, step: u32, placeholder: Option<&str>) {
+        let depth = self.token_stream.depth();
+
+        loop {
+            match self.parse_slide_item(slide, step, placeholder) {
+                Ok(true) => {}
+                Ok(false) => break,
+                Err(error) => {
+                    if !self.recover(error, depth, Self::starts_slide_item) {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns false once the closing brace of the block was consumed
+    fn parse_slide_item(
+        &mut self,
+        slide: &mut Slide,
+        step: u32,
+        placeholder: Option<&str>,
+    ) -> Result<bool, Error> {
+        if let Some(TokenizerResult::Ok(Token::Name(_), _)) = self.token_stream.peek() {
+            self.parse_placeholder(slide, step)?;
+            return Ok(true);
+        }
+
+        match self.parse_block_item(grammar::SLIDE_ITEMS)? {
+            BlockItem::Element(element) => {
+                slide.push_element_in_placeholder(*element, step, placeholder.map(String::from));
+            }
+            BlockItem::Other(Token::KeywordBackground) => slide.set_background(self.parse_fill()?),
+            BlockItem::Other(Token::KeywordNotes) => {
+                slide.add_note(consume!(self, Token::String(note) => note));
+            }
+            BlockItem::Other(Token::KeywordTransition) => {
+                slide.set_transition(self.parse_transition()?);
+            }
+            BlockItem::Other(Token::KeywordDuration) => slide.set_duration(Duration::from_secs(
+                self.parse_integer_in("duration", 0, None)?.into(),
+            )),
+            BlockItem::Other(Token::KeywordTarget) => slide.set_target(Duration::from_secs(
+                self.parse_integer_in("target", 0, None)?.into(),
+            )),
+            BlockItem::Other(Token::KeywordStep) => self.parse_step(slide, placeholder)?,
+            BlockItem::Other(_) => return Ok(false),
+        }
+
+        Ok(true)
+    }
+
+    fn starts_slide_item(token: &Token) -> bool {
+        grammar::element(token).is_some() || grammar::SLIDE_ITEMS.contains(token)
+    }
+
+    /// Takes the next token, parsing the element it starts or returning it
+    /// if it is one of `others`
+    fn parse_block_item(&mut self, others: &[Token]) -> Result<BlockItem, Error> {
+        match self.next_token() {
+            TokenizerResult::Ok(token, location) => {
+                if let Some(rule) = grammar::element(&token) {
+                    return Ok(BlockItem::Element(Box::new(self.parse_element(rule)?)));
+                }
+                if others.contains(&token) {
+                    return Ok(BlockItem::Other(token));
+                }
+
+                Self::handle_invalid_result(
+                    &TokenizerResult::Ok(token, location),
+                    Self::expected_block_items(others),
+                )
+            }
+            result => Self::handle_invalid_result(&result, Self::expected_block_items(others)),
+        }
+    }
+
+    fn expected_block_items(others: &[Token]) -> String {
+        grammar::expected(
+            grammar::ELEMENTS
+                .iter()
+                .map(|rule| &rule.keyword)
+                .chain(others),
+        )
+    }
+
+    /// Parses what follows the element's keyword, then its properties
+    fn parse_element(&mut self, rule: &ElementRule) -> Result<SlideElement, Error> {
+        match rule.content {
+            Content::Text(element) => self.parse_text(element),
+            Content::Image => self.parse_image(),
+            Content::Video => self.parse_video(),
+            Content::List => self.parse_list(),
+            Content::Container(element) => self.parse_container(element),
+        }
+    }
+
+    /// Parses `layout <name> { ... }` holding elements and placeholders
+    fn parse_layout(&mut self) -> Result<(String, Layout, SourceLocationRange), Error> {
+        let mut items = vec![];
+
+        consume!(self, Token::KeywordLayout);
+        let (name, location) = match self.next_token() {
+            TokenizerResult::Ok(Token::Name(name), location) => (name, location),
+            result => return Self::handle_invalid_result(&result, "Name".into()),
+        };
+        consume!(self, Token::OpeningBrace);
+
+        loop {
+            match self.parse_block_item(grammar::LAYOUT_ITEMS)? {
+                BlockItem::Element(element) => items.push(LayoutItem::Element(element)),
+                BlockItem::Other(Token::KeywordPlaceholder) => items.push(LayoutItem::Placeholder(
+                    consume!(self, Token::Name(placeholder) => placeholder),
+                )),
+                BlockItem::Other(_) => break,
+            }
+        }
+
+        Ok((name, Layout::new(items), location))
+    }
+
+    fn parse_image(&mut self) -> Result<SlideElement, Error> {
+        let image = Image::new(self.parse_asset_path()?);
+
+        self.parse_properties(SlideElement::Image(image))
+    }
+
+    fn parse_video(&mut self) -> Result<SlideElement, Error> {
+        let video = Video::new(self.parse_asset_path()?);
+
+        self.parse_properties(SlideElement::Video(video))
+    }
+
+    /// Parses a text into the kind of element made by `element`
+    fn parse_text(&mut self, element: fn(Text) -> SlideElement) -> Result<SlideElement, Error> {
+        let (text, location) = match self.next_token() {
+            TokenizerResult::Ok(Token::String(text), location) => (text, location),
+            result => return Self::handle_invalid_result(&result, "String(text)".into()),
+        };
+        let spans =
+            markup::parse(&text).map_err(|error| Error::InvalidMarkup { error, location })?;
+
+        self.parse_properties(element(Text::with_spans(spans, None)))
+    }
+
+    /// Parses `row { ... }` or `column { ... }` into the kind of element
+    /// made by `element`, the children are any other elements
+    fn parse_container(
+        &mut self,
+        element: fn(Container) -> SlideElement,
+    ) -> Result<SlideElement, Error> {
+        let mut elements = vec![];
+
+        consume!(self, Token::OpeningBrace);
+        while let BlockItem::Element(child) = self.parse_block_item(grammar::CONTAINER_ITEMS)? {
+            elements.push(*child);
+        }
+
+        self.parse_properties(element(Container::new(elements)))
+    }
+
+    /// Parses the properties following an element that take it, each at
+    /// most once
+    fn parse_properties(&mut self, mut element: SlideElement) -> Result<SlideElement, Error> {
+        let mut placement = *element.placement();
+        let mut animation = element.animation().clone();
+        let mut given = GivenProperties::new(grammar::element_name(&element));
+
+        while let Some((rule, location)) = self.peek_element_property(&element) {
+            self.next_token();
+            let rule = match self.token_stream.peek() {
+                Some(TokenizerResult::Ok(Token::Integer(_), _))
+                    if rule.keyword == Token::KeywordSize && Target::Text.accepts(&element) =>
+                {
+                    &grammar::FONT_SIZE
+                }
+                _ => rule,
+            };
+            given.add(rule, location)?;
+            self.parse_element_property(rule, &mut element, &mut placement, &mut animation)?;
+        }
+
+        element.set_placement(placement);
+        element.set_animation(animation);
+
+        Ok(element)
+    }
+
+    fn peek_element_property(
+        &mut self,
+        element: &SlideElement,
+    ) -> Option<(&'static PropertyRule, SourceLocationRange)> {
+        match self.token_stream.peek() {
+            Some(TokenizerResult::Ok(token, location)) => {
+                let rule = grammar::property(grammar::ELEMENT_PROPERTIES, token)?;
+                if rule.target.accepts(element) {
+                    Some((rule, *location))
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Parses the value of a property whose keyword was just consumed
+    fn parse_element_property(
+        &mut self,
+        rule: &PropertyRule,
+        element: &mut SlideElement,
+        placement: &mut Placement,
+        animation: &mut Animation,
+    ) -> Result<(), Error> {
+        match (rule.name, element) {
+            (
+                "style",
+                SlideElement::Text(text) | SlideElement::Title(text) | SlideElement::Subtitle(text),
+            ) => text.set_style(consume!(self, Token::Name(style_name) => style_name)),
+            (
+                "font size",
+                SlideElement::Text(text) | SlideElement::Title(text) | SlideElement::Subtitle(text),
+            ) => text.set_size(self.parse_integer_in("size", 1, None)?),
+            ("gap", SlideElement::Row(container) | SlideElement::Column(container)) => {
+                container.set_gap(self.parse_length("gap")?);
+            }
+            ("align", SlideElement::Row(container) | SlideElement::Column(container)) => {
+                container.set_align(self.parse_align()?);
+            }
+            ("background", SlideElement::Row(container) | SlideElement::Column(container)) => {
+                container.set_background(self.parse_fill()?);
+            }
+            ("grow", _) => {
+                placement.set_grow(self.parse_integer_in("grow", 0, None)?);
+            }
+            ("size", _) => placement.set_size(self.parse_extent()?, self.parse_extent()?),
+            ("at", _) => placement.set_position(self.parse_length("at")?, self.parse_length("at")?),
+            ("anchor", _) => placement.set_anchor(self.parse_anchor()?),
+            ("opacity", _) => animation.set_opacity(self.parse_opacity()?),
+            ("fade-in", _) => {
+                animation.set_fade_in(self.parse_animation_duration()?);
+                if let Some(easing) = self.parse_optional_easing()? {
+                    animation.set_easing(easing);
+                }
+            }
+            ("slide-in-from", _) => {
+                animation.set_slide_in(self.parse_side()?, self.parse_animation_duration()?);
+                if let Some(easing) = self.parse_optional_easing()? {
+                    animation.set_easing(easing);
+                }
+            }
+            ("animate", _) => animation.set_keyframes(self.parse_keyframes()?),
+            ("rotate", _) => animation.set_rotation(self.parse_number("rotate")?),
+            ("scale", _) => animation.set_scale(self.parse_number("scale")?),
+            ("skew", _) => {
+                let x = self.parse_number("skew")?;
+                let y = match self.token_stream.peek() {
+                    Some(TokenizerResult::Ok(Token::Integer(_) | Token::Float(_), _)) => {
+                        self.parse_number("skew")?
+                    }
+                    _ => 0.0,
+                };
+                animation.set_skew(x, y);
+            }
+            // the grammar only lets through properties the element takes
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Parses a length of an element's size, which can also be `auto`
+    fn parse_extent(&mut self) -> Result<Option<Length>, Error> {
+        if let Some(TokenizerResult::Ok(Token::Name(name), _)) = self.token_stream.peek() {
+            if name == "auto" {
+                consume!(self, Token::Name(_auto));
+                return Ok(None);
+            }
+        }
+
+        Ok(Some(self.parse_length("size")?))
+    }
+
+    fn parse_anchor(&mut self) -> Result<Anchor, Error> {
+        match self.next_token() {
+            TokenizerResult::Ok(Token::Name(value), location) => {
+                Anchor::from_name(&value).ok_or(Error::InvalidAnchor { value, location })
+            }
+            result => Self::handle_invalid_result(&result, "Name".into()),
+        }
+    }
+
+    fn parse_side(&mut self) -> Result<Side, Error> {
+        match self.next_token() {
+            TokenizerResult::Ok(Token::Name(value), location) => {
+                Side::from_name(&value).ok_or(Error::InvalidSide { value, location })
+            }
+            result => Self::handle_invalid_result(&result, "Name".into()),
+        }
+    }
+
+    /// Parses an opacity in percent, written as `50` or `50%`
+    fn parse_opacity(&mut self) -> Result<u32, Error> {
+        match self.next_token() {
+            TokenizerResult::Ok(Token::Integer(value), location) => {
+                Self::in_range("opacity", value, location, 0, Some(100))
+            }
+            TokenizerResult::Ok(Token::Length(Length::Percent(value)), location) => {
+                Self::in_range("opacity", value.round() as i128, location, 0, Some(100))
+            }
+            result => Self::handle_invalid_result(&result, "Integer, Length(percent)".into()),
+        }
+    }
+
+    /// Parses how long an element takes to come in, plain numbers are
+    /// milliseconds and leaving it out takes the default
+    fn parse_animation_duration(&mut self) -> Result<Duration, Error> {
+        Ok(match self.token_stream.peek() {
+            Some(TokenizerResult::Ok(Token::Duration(_), _)) => {
+                consume!(self, Token::Duration(duration) => duration)
+            }
+            Some(TokenizerResult::Ok(Token::Integer(_), _)) => {
+                Duration::from_millis(self.parse_integer_in("duration", 0, None)?.into())
+            }
+            _ => Duration::from_millis(DEFAULT_ANIMATION_DURATION),
+        })
+    }
+
+    /// Parses keyframes in braces, each `at` a time followed by what the
+    /// element reaches by then and optionally the easing it moves on to the
+    /// next keyframe with, e.g. `{ at 0ms x 0% ease-out, at 500ms x 40% }`
+    fn parse_keyframes(&mut self) -> Result<Vec<Keyframe>, Error> {
+        let mut keyframes = vec![];
+
+        consume!(self, Token::OpeningBrace);
+        loop {
+            consume!(
+                self,
+                Token::KeywordAt => keyframes.push(self.parse_keyframe()?),
+                Token::ClosingBrace => break
+            );
+
+            peek_decide!(
+                self,
+                Token::Comma => consume!(self, Token::Comma),
+                Token::ClosingBrace => {}
+            );
+        }
+
+        Ok(keyframes)
+    }
+
+    /// Parses the time of a keyframe whose `at` was just consumed, plain
+    /// numbers are milliseconds, then its motions and easing
+    fn parse_keyframe(&mut self) -> Result<Keyframe, Error> {
+        let at = match self.next_token() {
+            TokenizerResult::Ok(Token::Duration(at), _) => at,
+            TokenizerResult::Ok(Token::Integer(milliseconds), location) => {
+                Duration::from_millis(Self::in_range("at", milliseconds, location, 0, None)?.into())
+            }
+            result => return Self::handle_invalid_result(&result, "Duration, Integer".into()),
+        };
+        let mut motions = vec![];
+        let mut easing = Easing::default();
+
+        loop {
+            if let Some(found) = self.parse_optional_easing()? {
+                easing = found;
+                continue;
+            }
+
+            match self.token_stream.peek() {
+                Some(TokenizerResult::Ok(Token::Comma | Token::ClosingBrace, _)) => break,
+                Some(TokenizerResult::Ok(Token::KeywordOpacity, _)) => {
+                    consume!(self, Token::KeywordOpacity);
+                    motions.push(Motion::Opacity(self.parse_opacity()?));
+                }
+                _ => match self.next_token() {
+                    TokenizerResult::Ok(Token::KeywordScale, _) => {
+                        motions.push(Motion::Scale(self.parse_number("scale")?));
+                    }
+                    TokenizerResult::Ok(Token::KeywordRotate, _) => {
+                        motions.push(Motion::Rotate(self.parse_number("rotate")?));
+                    }
+                    TokenizerResult::Ok(Token::Name(value), location) => match value.as_str() {
+                        "x" => motions.push(Motion::X(self.parse_length("x")?)),
+                        "y" => motions.push(Motion::Y(self.parse_length("y")?)),
+                        _ => return Err(Error::InvalidMotion { value, location }),
+                    },
+                    result => return Self::handle_invalid_result(
+                        &result,
+                        "Name, KeywordScale, KeywordRotate, KeywordOpacity, Comma, ClosingBrace"
+                            .into(),
+                    ),
+                },
+            }
+        }
+
+        Ok(Keyframe::new(at, motions, easing))
+    }
+
+    /// Parses a whole or decimal number of the property, whole ones have to
+    /// fit an `i32`
+    fn parse_number(&mut self, property: &str) -> Result<f64, Error> {
+        match self.next_token() {
+            TokenizerResult::Ok(Token::Integer(value), location) => {
+                Self::whole_number(property, value, location)
+            }
+            TokenizerResult::Ok(Token::Float(value), _) => Ok(value.value()),
+            result => Self::handle_invalid_result(&result, "Integer, Float".into()),
+        }
+    }
+
+    /// Takes a whole number as a decimal one, as long as it fits an `i32`
+    fn whole_number(
+        property: &str,
+        value: i128,
+        location: SourceLocationRange,
+    ) -> Result<f64, Error> {
+        i32::try_from(value)
+            .map(f64::from)
+            .map_err(|_| Error::OutOfRange {
+                property: property.into(),
+                value,
+                min: i32::MIN.into(),
+                max: Some(i32::MAX.into()),
+                location,
+            })
+    }
+
+    /// Parses an easing if one comes next, e.g. `ease-out`,
+    /// `cubic-bezier 0.3 0 0.2 1` or `spring 200 15`, a spring without its
+    /// stiffness and damping gets the default ones
+    fn parse_optional_easing(&mut self) -> Result<Option<Easing>, Error> {
+        match self.token_stream.peek() {
+            Some(TokenizerResult::Ok(Token::Name(name), _))
+                if name == "cubic-bezier" || Easing::from_name(name).is_some() => {}
+            _ => return Ok(None),
+        }
+
+        let name = consume!(self, Token::Name(name) => name);
+        Ok(Some(match name.as_str() {
+            "cubic-bezier" => Easing::CubicBezier(
+                self.parse_number("cubic-bezier")?,
+                self.parse_number("cubic-bezier")?,
+                self.parse_number("cubic-bezier")?,
+                self.parse_number("cubic-bezier")?,
+            ),
+            "spring" => match self.token_stream.peek() {
+                Some(TokenizerResult::Ok(Token::Integer(_) | Token::Float(_), _)) => {
+                    Easing::Spring {
+                        stiffness: self.parse_number("spring")?,
+                        damping: self.parse_number("spring")?,
+                    }
+                }
+                _ => Easing::SPRING,
+            },
+            _ => Easing::from_name(&name).unwrap_or_default(),
+        }))
+    }
+
+    fn parse_align(&mut self) -> Result<Align, Error> {
+        match self.next_token() {
+            TokenizerResult::Ok(Token::Name(value), location) => {
+                Align::from_name(&value).ok_or(Error::InvalidAlignment { value, location })
+            }
+            result => Self::handle_invalid_result(&result, "Name".into()),
+        }
+    }
+
+    fn parse_list(&mut self) -> Result<SlideElement, Error> {
+        let mut items: Vec<String> = vec![];
+        let mut bullet = String::from("•");
+        let mut indent = Length::Pixels(40.0);
+
+        consume!(self, Token::OpeningBrace);
+
+        loop {
+            consume!(
+                self,
+                Token::KeywordItem => items.push(consume!(self, Token::String(item) => item)),
+                Token::KeywordBullet => bullet = consume!(self, Token::String(list_bullet) => list_bullet),
+                Token::KeywordIndent => indent = self.parse_length("indent")?,
+                Token::ClosingBrace => break
+            );
+
+            peek_decide!(
+                self,
+                Token::Comma => consume!(self, Token::Comma),
+                Token::ClosingBrace => {}
+            );
+        }
+
+        self.parse_properties(SlideElement::List(List::new(items, bullet, indent)))
+    }
+
+    /// Parses a length of the property with a unit, plain numbers are
+    /// taken as pixels
+    fn parse_length(&mut self, property: &str) -> Result<Length, Error> {
+        match self.next_token() {
+            TokenizerResult::Ok(Token::Length(length), _) => Ok(length),
+            TokenizerResult::Ok(Token::Integer(pixels), location) => {
+                Self::whole_number(property, pixels, location).map(Length::Pixels)
+            }
+            TokenizerResult::Ok(Token::Float(pixels), _) => Ok(Length::Pixels(pixels.value())),
+            result => Self::handle_invalid_result(&result, "Length, Integer, Float".into()),
+        }
+    }
+
+    fn parse_metadata(&mut self) -> Result<Metadata, Error> {
+        let mut title: Option<String> = None;
+        let mut resolution = Resolution::default();
+        let mut transition: Option<Transition> = None;
+        let mut theme: Option<String> = None;
+        let mut controls: Vec<ControlInput> = vec![];
+        let mut show_progress = false;
+        let mut show_page_numbers = false;
+        let mut show_footer = false;
+        let mut spelling_ignore: Vec<String> = vec![];
+        let mut font_dirs: Vec<String> = vec![];
+
+        let location = self.consume_keyword(&Token::KeywordMetadata)?;
+        consume!(self, Token::OpeningBrace);
+        let mut given = GivenProperties::new("metadata");
+
+        while let Some((rule, _)) =
+            self.parse_property_keyword(grammar::METADATA_PROPERTIES, &mut given)?
+        {
+            match rule.keyword {
+                Token::KeywordTitle => title = Some(consume!(self, Token::String(title) => title)),
+                Token::KeywordWidth => {
+                    resolution.set_width(self.parse_integer_in("width", 1, None)?);
+                }
+                Token::KeywordHeight => {
+                    resolution.set_height(self.parse_integer_in("height", 1, None)?);
+                }
+                Token::KeywordTransition => transition = Some(self.parse_transition()?),
+                Token::KeywordTheme => theme = Some(self.parse_theme_name()?),
+                Token::KeywordControl => controls.push(self.parse_control()?),
+                Token::KeywordShowProgress => show_progress = self.parse_flag()?,
+                Token::KeywordShowPageNumbers => show_page_numbers = self.parse_flag()?,
+                Token::KeywordShowFooter => show_footer = self.parse_flag()?,
+                Token::KeywordSpellingIgnore => {
+                    let words = consume!(self, Token::String(words) => words);
+                    spelling_ignore.extend(words.split_whitespace().map(String::from));
+                }
+                Token::KeywordFontDir => {
+                    let font_dir = consume!(self, Token::String(font_dir) => font_dir);
+                    font_dirs.push(self.relative_path(&font_dir).to_string_lossy().into_owned());
+                }
+                _ => {}
+            }
+
+            consume!(
+                self,
+                Token::Comma => {},
+                Token::ClosingBrace => break
+            );
+        }
+        given.check_required(grammar::METADATA_PROPERTIES, location)?;
+        let title = title.unwrap_or_default();
+
+        let mut metadata = Metadata::new(title, resolution);
+        if let Some(transition) = transition {
+            metadata.set_transition(transition);
+        }
+        if let Some(theme) = theme {
+            metadata.set_theme(theme);
+        }
+        for control in controls {
+            metadata.add_control(control);
+        }
+        metadata.set_show_progress(show_progress);
+        metadata.set_show_page_numbers(show_page_numbers);
+        metadata.set_show_footer(show_footer);
+        for word in spelling_ignore {
+            metadata.add_spelling_ignore(word);
+        }
+        for font_dir in font_dirs {
+            metadata.add_font_dir(font_dir);
+        }
+
+        Ok(metadata)
+    }
+
+    /// Parses `osc` followed by the port or `midi` followed by the device,
+    /// then optionally the bindings in braces, e.g. `{ "/go" next }` or
+    /// `{ 60 next }`
+    fn parse_control(&mut self) -> Result<ControlInput, Error> {
+        let mut control = match self.next_token() {
+            TokenizerResult::Ok(Token::Name(value), location) => match value.as_str() {
+                "osc" => ControlInput::Osc {
+                    port: self.parse_control_number(u16::MAX)?,
+                    addresses: HashMap::new(),
+                },
+                "midi" => ControlInput::Midi {
+                    device: consume!(self, Token::String(device) => device),
+                    notes: HashMap::new(),
+                },
+                _ => return Err(Error::InvalidControlInput { value, location }),
+            },
+            result => return Self::handle_invalid_result(&result, "Name".into()),
+        };
+
+        if let Some(TokenizerResult::Ok(Token::OpeningBrace, _)) = self.token_stream.peek() {
+            consume!(self, Token::OpeningBrace);
+            loop {
+                match self.token_stream.peek() {
+                    Some(TokenizerResult::Ok(Token::ClosingBrace, _)) => {
+                        consume!(self, Token::ClosingBrace);
+                        break;
+                    }
+                    Some(TokenizerResult::Ok(Token::Comma, _)) => consume!(self, Token::Comma),
+                    _ => self.parse_control_binding(&mut control)?,
+                }
+            }
+        }
+
+        Ok(control)
+    }
+
+    /// Parses the address or note and the action it is bound to
+    fn parse_control_binding(&mut self, control: &mut ControlInput) -> Result<(), Error> {
+        match control {
+            ControlInput::Osc { addresses, .. } => {
+                let address = consume!(self, Token::String(address) => address);
+                addresses.insert(address, self.parse_action()?);
+            }
+            ControlInput::Midi { notes, .. } => {
+                let note = self.parse_control_number(127)? as u8;
+                notes.insert(note, self.parse_action()?);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn parse_control_number(&mut self, max: u16) -> Result<u16, Error> {
+        match self.next_token() {
+            TokenizerResult::Ok(Token::Integer(value), location) => {
+                if value < 0 || value > i128::from(max) {
+                    return Err(Error::InvalidControlNumber { value, location });
+                }
+
+                Ok(value as u16)
+            }
+            result => Self::handle_invalid_result(&result, "Integer".into()),
+        }
+    }
+
+    fn parse_action(&mut self) -> Result<Action, Error> {
+        match self.next_token() {
+            TokenizerResult::Ok(Token::Name(value), location) => {
+                Action::from_name(&value).ok_or(Error::InvalidAction { value, location })
+            }
+            result => Self::handle_invalid_result(&result, "Name(action)".into()),
+        }
+    }
+
+    /// Parses the theme's name and loads it, unless a theme was given
+    /// upfront
+    fn parse_theme_name(&mut self) -> Result<String, Error> {
+        let (name, location) = match self.next_token() {
+            TokenizerResult::Ok(Token::String(name), location) => (name, location),
+            result => return Self::handle_invalid_result(&result, "String(theme)".into()),
+        };
+        if self.theme.is_none() {
+            self.theme = Some(self.load_theme(&name, location)?);
+        }
+
+        Ok(name)
+    }
+
+    /// Parses `fade`, `slide` or `none`, optionally followed by the duration
+    /// in milliseconds
+    fn parse_transition(&mut self) -> Result<Transition, Error> {
+        let kind = match self.next_token() {
+            TokenizerResult::Ok(Token::KeywordSlide, _) => TransitionKind::Slide,
+            TokenizerResult::Ok(Token::Name(value), location) => match value.as_str() {
+                "fade" => TransitionKind::Fade,
+                "none" => TransitionKind::None,
+                _ => return Err(Error::InvalidTransition { value, location }),
+            },
+            result => return Self::handle_invalid_result(&result, "KeywordSlide, Name".into()),
+        };
+
+        let duration = match self.token_stream.peek() {
+            Some(TokenizerResult::Ok(Token::Integer(_), _)) => {
+                self.parse_integer_in("duration", 0, None)?.into()
+            }
+            _ if kind == TransitionKind::None => 0,
+            _ => DEFAULT_TRANSITION_DURATION,
+        };
+
+        let mut transition = Transition::new(kind, Duration::from_millis(duration));
+        if let Some(easing) = self.parse_optional_easing()? {
+            transition.set_easing(easing);
+        }
+
+        Ok(transition)
+    }
+
+    fn parse_style(&mut self) -> Result<Style, Error> {
+        let mut fonts: Vec<Font> = vec![];
+        let mut background: Option<Fill> = None;
+        let mut text_color: Option<Color> = None;
+        let mut text_styles: Vec<(String, TextStyle)> = vec![];
+        let mut hinting: Option<Hinting> = None;
+        let mut antialias: Option<bool> = None;
+        let mut kerning: Option<bool> = None;
+
+        consume!(self, Token::KeywordStyle);
+        consume!(self, Token::OpeningBrace);
+
+        loop {
+            peek_decide!(
+                self,
+                Token::KeywordFont => fonts.push(self.parse_font()?),
+                Token::KeywordTextStyle => text_styles.push(self.parse_text_style()?),
+                Token::KeywordBackground => {
+                    consume!(self, Token::KeywordBackground);
+                    background = Some(self.parse_fill()?);
+                },
+                Token::KeywordColor => {
+                    consume!(self, Token::KeywordColor);
+                    text_color = Some(self.parse_color()?);
+                },
+                Token::KeywordHinting => {
+                    consume!(self, Token::KeywordHinting);
+                    hinting = Some(self.parse_hinting()?);
+                },
+                Token::KeywordAntialias => {
+                    consume!(self, Token::KeywordAntialias);
+                    antialias = Some(self.parse_flag()?);
+                },
+                Token::KeywordKerning => {
+                    consume!(self, Token::KeywordKerning);
+                    kerning = Some(self.parse_flag()?);
+                },
+                Token::ClosingBrace => { consume!(self, Token::ClosingBrace); break }
+            );
+        }
+
+        let mut style = Style::new(fonts)?;
+        if let Some(background) = background {
+            style.set_background(background);
+        }
+        if let Some(text_color) = text_color {
+            style.set_text_color(text_color);
+        }
+        if let Some(hinting) = hinting {
+            style.set_hinting(hinting);
+        }
+        if let Some(antialias) = antialias {
+            style.set_antialias(antialias);
+        }
+        if let Some(kerning) = kerning {
+            style.set_kerning(kerning);
+        }
+        for (name, text_style) in text_styles {
+            style.add_text_style(name, text_style)?;
+        }
+
+        Ok(style)
+    }
+
+    fn parse_text_style(&mut self) -> Result<(String, TextStyle), Error> {
+        let mut font: Option<String> = None;
+        let mut size: Option<u32> = None;
+        let mut color: Option<Color> = None;
+        let mut weight: Option<u32> = None;
+        let mut italic: Option<bool> = None;
+        let mut fallback_fonts: Vec<String> = vec![];
+
+        consume!(self, Token::KeywordTextStyle);
+        let name = consume!(self, Token::Name(style_name) => style_name);
+        consume!(self, Token::OpeningBrace);
+
+        loop {
+            consume!(
+                self,
+                Token::KeywordFont => font = consume!(self, Token::Name(font_name) => Some(font_name)),
+                Token::KeywordSize => size = Some(self.parse_integer_in("size", 1, None)?),
+                Token::KeywordColor => color = Some(self.parse_color()?),
+                Token::KeywordWeight => weight = Some(self.parse_integer_in("weight", MIN_FONT_WEIGHT, Some(MAX_FONT_WEIGHT))?),
+                Token::KeywordItalic => italic = Some(self.parse_flag()?),
+                Token::KeywordFontStack => {
+                    let (stack, separated) = self.parse_font_stack()?;
+                    let mut stack = stack.into_iter();
+                    font = stack.next();
+                    fallback_fonts = stack.collect();
+                    if separated {
+                        continue;
+                    }
+                },
+                Token::ClosingBrace => break
+            );
+
+            peek_decide!(
+                self,
+                Token::Comma => consume!(self, Token::Comma),
+                Token::ClosingBrace => {}
+            );
+        }
+
+        let mut text_style = TextStyle::new(font, size, color);
+        if let Some(weight) = weight {
+            text_style.set_weight(weight);
+        }
+        if let Some(italic) = italic {
+            text_style.set_italic(italic);
+        }
+        text_style.set_fallback_fonts(fallback_fonts);
+
+        Ok((name, text_style))
+    }
+
+    /// Parses the comma separated font names of a `font-stack`, the comma
+    /// after the last one separates it from the next property and is
+    /// consumed too, which the returned flag tells
+    fn parse_font_stack(&mut self) -> Result<(Vec<String>, bool), Error> {
+        let mut fonts = vec![consume!(self, Token::Name(font_name) => font_name)];
+
+        loop {
+            match self.token_stream.peek() {
+                Some(TokenizerResult::Ok(Token::Comma, _)) => consume!(self, Token::Comma),
+                _ => return Ok((fonts, false)),
+            }
+
+            match self.token_stream.peek() {
+                Some(TokenizerResult::Ok(Token::Name(_), _)) => {
+                    fonts.push(consume!(self, Token::Name(font_name) => font_name));
+                }
+                _ => return Ok((fonts, true)),
+            }
+        }
+    }
+
+    fn parse_hinting(&mut self) -> Result<Hinting, Error> {
+        match self.next_token() {
+            TokenizerResult::Ok(Token::Name(value), location) => {
+                Hinting::from_name(&value).ok_or(Error::InvalidHinting { value, location })
+            }
+            result => Self::handle_invalid_result(&result, "Name".into()),
+        }
+    }
+
+    /// Parses a plain color, `gradient <from> <to>` optionally followed by
+    /// the direction, which is vertical unless said otherwise, or an image
+    fn parse_fill(&mut self) -> Result<Fill, Error> {
+        match self.token_stream.peek() {
+            Some(TokenizerResult::Ok(Token::KeywordImage, _)) => return self.parse_image_fill(),
+            Some(TokenizerResult::Ok(Token::Name(name), _)) if name == "gradient" => {
+                consume!(self, Token::Name(_gradient));
+            }
+            _ => return Ok(Fill::Color(self.parse_color()?)),
+        }
+
+        let from = self.parse_color()?;
+        let to = self.parse_color()?;
+        let direction = match self.token_stream.peek() {
+            Some(TokenizerResult::Ok(Token::Name(_), _)) => self.parse_gradient_direction()?,
+            _ => GradientDirection::Vertical,
+        };
+
+        Ok(Fill::Gradient {
+            from,
+            to,
+            direction,
+        })
+    }
+
+    /// Parses `image "<path>"` optionally followed by how the image is
+    /// fitted, which is cover unless said otherwise
+    fn parse_image_fill(&mut self) -> Result<Fill, Error> {
+        consume!(self, Token::KeywordImage);
+        let path = self.parse_asset_path()?;
+        let fit = match self.token_stream.peek() {
+            Some(TokenizerResult::Ok(Token::Name(_), _)) => self.parse_image_fit()?,
+            _ => ImageFit::Cover,
+        };
+
+        Ok(Fill::Image { path, fit })
+    }
+
+    fn parse_image_fit(&mut self) -> Result<ImageFit, Error> {
+        match self.next_token() {
+            TokenizerResult::Ok(Token::Name(value), location) => {
+                ImageFit::from_name(&value).ok_or(Error::InvalidImageFit { value, location })
+            }
+            result => Self::handle_invalid_result(&result, "Name".into()),
+        }
+    }
+
+    fn parse_gradient_direction(&mut self) -> Result<GradientDirection, Error> {
+        match self.next_token() {
+            TokenizerResult::Ok(Token::Name(value), location) => {
+                GradientDirection::from_name(&value)
+                    .ok_or(Error::InvalidGradientDirection { value, location })
+            }
+            result => Self::handle_invalid_result(&result, "Name".into()),
+        }
+    }
+
+    /// Parses a color given as a literal (`#rrggbb`), a hex string (`"#rrggbb"`) or by name
+    fn parse_color(&mut self) -> Result<Color, Error> {
+        let (color, value, location) = match self.next_token() {
+            TokenizerResult::Ok(Token::Color(color), _) => return Ok(color),
+            TokenizerResult::Ok(Token::String(value), location) => {
+                (Color::from_hex(&value), value, location)
+            }
+            TokenizerResult::Ok(Token::Name(value), location) => {
+                (Color::from_name(&value), value, location)
+            }
+            result => return Self::handle_invalid_result(&result, "Color, String, Name".into()),
+        };
+
+        color.ok_or(Error::InvalidColor { value, location })
+    }
+
+    fn parse_font(&mut self) -> Result<Font, Error> {
+        let location = self.consume_keyword(&Token::KeywordFont)?;
+        let properties = self.parse_property_bag("font", location, grammar::FONT_PROPERTIES)?;
+
+        let name = properties.required_string("name")?;
+        let weight = match properties.integer("weight") {
+            Some((weight, location)) => Self::in_range(
+                "weight",
+                weight,
+                location,
+                MIN_FONT_WEIGHT,
+                Some(MAX_FONT_WEIGHT),
+            )?,
+            None => DEFAULT_FONT_WEIGHT,
+        };
+        let italic = properties.flag("italic").unwrap_or(false);
+        let mut font = if properties.flag("system").unwrap_or(false) {
+            Font::system(name, weight, italic)
+        } else {
+            let path = properties.required_string("path")?;
+            let location = properties
+                .get("path")
+                .map_or(location, |(_, location)| *location);
+            Font::new(name, self.asset_path(path, location)?, weight, italic)
+        };
+        if let Some((size, location)) = properties.integer("size") {
+            font.set_size(Self::in_range("size", size, location, 1, None)?);
+        }
+
+        Ok(font)
+    }
+
+    /// Parses a block of comma separated properties, all with values of the
+    /// kinds their rules say, into a bag. The block's keyword was consumed
+    /// at `location`.
+    fn parse_property_bag(
+        &mut self,
+        block: &'static str,
+        location: SourceLocationRange,
+        rules: &[PropertyRule],
+    ) -> Result<PropertyBag, Error> {
+        let mut properties = PropertyBag::new(block, location);
+        let mut given = GivenProperties::new(block);
+
+        consume!(self, Token::OpeningBrace);
+        while let Some((rule, keyword_location)) = self.parse_property_keyword(rules, &mut given)? {
+            if let Some(kind) = rule.value {
+                let (value, value_location) = self.parse_value(kind, keyword_location)?;
+                properties.insert(rule.name, value, value_location);
+            }
+
+            peek_decide!(
+                self,
+                Token::Comma => consume!(self, Token::Comma),
+                Token::ClosingBrace => {}
+            );
+        }
+        given.check_required(rules, location)?;
+
+        Ok(properties)
+    }
+
+    /// Parses a value of the kind, a flag without one is located at its
+    /// keyword
+    fn parse_value(
+        &mut self,
+        kind: ValueKind,
+        keyword_location: SourceLocationRange,
+    ) -> Result<(Value, SourceLocationRange), Error> {
+        let expected = match kind {
+            ValueKind::String => "String",
+            ValueKind::Name => "Name",
+            ValueKind::Integer => "Integer",
+            ValueKind::Flag => {
+                if !matches!(
+                    self.token_stream.peek(),
+                    Some(TokenizerResult::Ok(Token::Boolean(_), _))
+                ) {
+                    return Ok((Value::Flag(true), keyword_location));
+                }
+                "Boolean"
+            }
+        };
+
+        match (kind, self.next_token()) {
+            (ValueKind::String, TokenizerResult::Ok(Token::String(value), location)) => {
+                Ok((Value::String(value), location))
+            }
+            (ValueKind::Name, TokenizerResult::Ok(Token::Name(value), location)) => {
+                Ok((Value::Name(value), location))
+            }
+            (ValueKind::Integer, TokenizerResult::Ok(Token::Integer(value), location)) => {
+                Ok((Value::Integer(value), location))
+            }
+            (ValueKind::Flag, TokenizerResult::Ok(Token::Boolean(value), location)) => {
+                Ok((Value::Flag(value), location))
+            }
+            (_, result) => Self::handle_invalid_result(&result, expected.into()),
+        }
+    }
+
+    /// Takes the keyword of the block's next property, or its closing brace
+    /// which ends it
+    fn parse_property_keyword<'r>(
+        &mut self,
+        rules: &'r [PropertyRule],
+        given: &mut GivenProperties,
+    ) -> Result<Option<(&'r PropertyRule, SourceLocationRange)>, Error> {
+        let expected = || {
+            grammar::expected(
+                rules
+                    .iter()
+                    .map(|rule| &rule.keyword)
+                    .chain(&[Token::ClosingBrace]),
+            )
+        };
+
+        match self.next_token() {
+            TokenizerResult::Ok(Token::ClosingBrace, _) => Ok(None),
+            TokenizerResult::Ok(token, location) => match grammar::property(rules, &token) {
+                Some(rule) => {
+                    given.add(rule, location)?;
+                    Ok(Some((rule, location)))
+                }
+                None => {
+                    Self::handle_invalid_result(&TokenizerResult::Ok(token, location), expected())
+                }
+            },
+            result => Self::handle_invalid_result(&result, expected()),
+        }
+    }
+
+    /// Consumes the keyword, returning where it is
+    fn consume_keyword(&mut self, keyword: &Token) -> Result<SourceLocationRange, Error> {
+        match self.next_token() {
+            TokenizerResult::Ok(token, location) if token == *keyword => Ok(location),
+            result => Self::handle_invalid_result(&result, format!("{:?}", keyword)),
+        }
+    }
+
+    /// Parses an integer within what the property allows, see `in_range`
+    fn parse_integer_in(
+        &mut self,
+        property: &str,
+        min: i128,
+        max: Option<i128>,
+    ) -> Result<u32, Error> {
+        match self.next_token() {
+            TokenizerResult::Ok(Token::Integer(value), location) => {
+                Self::in_range(property, value, location, min, max)
+            }
+            result => Self::handle_invalid_result(&result, "Integer".into()),
+        }
+    }
+
+    /// Checks the value is at least `min` and at most `max`, if given, and
+    /// fits a `u32` either way
+    fn in_range(
+        property: &str,
+        value: i128,
+        location: SourceLocationRange,
+        min: i128,
+        max: Option<i128>,
+    ) -> Result<u32, Error> {
+        match u32::try_from(value) {
+            Ok(number) if value >= min && !matches!(max, Some(max) if value > max) => Ok(number),
+            _ => Err(Error::OutOfRange {
+                property: property.into(),
+                value,
+                min,
+                max,
+                location,
+            }),
+        }
+    }
+
+    /// Parses the optional value of a flag, a flag given without one is set
+    fn parse_flag(&mut self) -> Result<bool, Error> {
+        match self.token_stream.peek() {
+            Some(TokenizerResult::Ok(Token::Boolean(_), _)) => {
+                Ok(consume!(self, Token::Boolean(value) => value))
+            }
+            _ => Ok(true),
+        }
+    }
+
+    fn handle_invalid_result<TOk>(
+        result: &TokenizerResult,
+        expected: String,
+    ) -> Result<TOk, Error> {
+        Err(match result {
+            TokenizerResult::Ok(token, location) => Error::UnexpectedToken {
+                actual: format!("{:?}", token),
+                expected,
+                location: *location,
+            },
+            TokenizerResult::Err(error) => Error::TokenizerFailure(error.clone()),
+            TokenizerResult::End => Error::UnexpectedEndOfStream { expected },
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::token_stream::{
+        MockTokenStream, SourceLocation, SourceLocationRange, TokenizerFailureKind,
+    };
+    use super::*;
+    use crate::parsing::tokenizer::Tokenizer;
+    use crate::presentation::{Font, Placement, Span};
+
+    macro_rules! parser_test_fail {
+        ($test_name:ident, $results:expr, $expected_error:expr) => {
+            #[test]
+            pub fn $test_name() {
+                let mut tokenizer = Tokenizer::new($results);
+                let mut parser = Parser::new(&mut tokenizer);
+
+                let error: Error = $expected_error;
+                assert_eq!(parser.parse(), Err(vec![error]));
+            }
+        };
+    }
+
+    macro_rules! parser_test {
+        ($test_name:ident, $results:expr, $expected_presentation:expr) => {
+            #[test]
+            pub fn $test_name() {
+                let mut tokenizer = Tokenizer::new($results);
+                let mut parser = Parser::new(&mut tokenizer);
+
+                let parsed = parser.parse().unwrap();
+
+                assert_eq!(parsed, $expected_presentation);
+            }
+        };
+    }
+
+    parser_test_fail!(
+        fails_on_slide_before_metadata,
+        "slide \"some slide\" {}",
+        Error::UnexpectedToken {
+            actual: "KeywordSlide".into(),
+            expected: "KeywordMetadata".into(),
+            location: SourceLocationRange::new(
+                SourceLocation::new(0, 1),
+                SourceLocation::new(0, 6)
+            )
+        }
+    );
+
+    parser_test!(
+        can_parse_metadata_block,
+        "metadata { title \"some title\" }",
+        Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![],
+            Style::new(vec![]).unwrap()
+        )
+    );
+
+    parser_test!(
+        can_parse_metadata_with_resolution,
+        "metadata { title \"some title\", width 1920, height 1080, }",
+        Presentation::new(
+            Metadata::new("some title".into(), Resolution::new(1920, 1080)),
+            vec![],
+            Style::new(vec![]).unwrap()
+        )
+    );
+
+    parser_test_fail!(
+        fails_on_metadata_properties_without_separator,
+        "metadata { title \"some title\" width 1920 }",
+        Error::UnexpectedToken {
+            actual: "KeywordWidth".into(),
+            expected: "Comma, ClosingBrace".into(),
+            location: SourceLocationRange::new(
+                SourceLocation::new(0, 31),
+                SourceLocation::new(0, 36)
+            )
+        }
+    );
+
+    parser_test!(
+        can_parse_slide_after_metadata,
+        "metadata { title \"some title\" } slide \"first slide\" {}",
+        Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![Slide::new("first slide".into(), vec![])],
+            Style::new(vec![]).unwrap()
+        )
+    );
+
+    parser_test!(
+        ignores_comments,
+        "// the metadata\nmetadata { title \"some title\" /* the title */ }\n/* slides: */ slide \"first slide\" {}",
+        Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![Slide::new("first slide".into(), vec![])],
+            Style::new(vec![]).unwrap()
+        )
+    );
+
+    parser_test_fail!(
+        fails_if_block_type_is_not_slide,
+        "metadata { title \"some title\" } notslide \"some slide\" {}",
+        Error::UnexpectedToken {
+            actual: "Name(\"notslide\")".into(),
+            expected:
+                "KeywordSlide, KeywordSection, KeywordStyle, KeywordLayout, KeywordDefine, KeywordInclude"
+                    .into(),
+            location: SourceLocationRange::new(
+                SourceLocation::new(0, 33),
+                SourceLocation::new(0, 41)
+            )
+        }
+    );
+
+    parser_test_fail!(
+        fails_on_missing_braces,
+        "metadata { title \"some title\" } slide \"some slide\"",
+        Error::UnexpectedEndOfStream {
+            expected: "OpeningBrace".into()
+        }
+    );
+
+    parser_test_fail!(
+        fails_on_unexpected_token_after_slide_name,
+        "metadata { title \"some title\" } slide \"some slide\" }",
+        Error::UnexpectedToken {
+            actual: "ClosingBrace".into(),
+            expected: "OpeningBrace".into(),
+            location: SourceLocationRange::new_single(SourceLocation::new(0, 52))
+        }
+    );
+
+    parser_test_fail!(
+        fails_on_unexpected_token_after_slide_opening_brace,
+        "metadata { title \"some title\" } slide \"some slide\" {{",
+        Error::UnexpectedToken {
+            actual: "OpeningBrace".into(),
+            expected: "KeywordText, KeywordTitle, KeywordSubtitle, KeywordList, KeywordImage, KeywordVideo, KeywordRow, KeywordColumn, KeywordBackground, KeywordNotes, KeywordTransition, KeywordDuration, KeywordTarget, KeywordStep, ClosingBrace"
+                .into(),
+            location: SourceLocationRange::new_single(SourceLocation::new(0, 53))
+        }
+    );
+
+    parser_test!(
+        can_parse_transitions,
+        "metadata { title \"some title\", transition fade 500 } slide \"a\" { transition slide } slide \"b\" { transition none }",
+        Presentation::new(
+            {
+                let mut metadata = Metadata::new("some title".into(), Resolution::default());
+                metadata.set_transition(Transition::new(
+                    TransitionKind::Fade,
+                    Duration::from_millis(500)
+                ));
+                metadata
+            },
+            vec![
+                {
+                    let mut slide = Slide::new("a".into(), vec![]);
+                    slide.set_transition(Transition::new(
+                        TransitionKind::Slide,
+                        Duration::from_millis(300)
+                    ));
+                    slide
+                },
+                {
+                    let mut slide = Slide::new("b".into(), vec![]);
+                    slide.set_transition(Transition::new(
+                        TransitionKind::None,
+                        Duration::from_millis(0)
+                    ));
+                    slide
+                }
+            ],
+            Style::new(vec![]).unwrap()
+        )
+    );
+
+    parser_test!(
+        can_parse_build_steps,
+        "metadata { title \"some title\" } slide \"some slide\" { text \"a\" step { text \"b\" } step { text \"c\" image \"d.png\" } }",
+        Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![{
+                let mut slide = Slide::new(
+                    "some slide".into(),
+                    vec![SlideElement::Text(Text::new("a".into(), None))]
+                );
+                slide.push_element_in_step(SlideElement::Text(Text::new("b".into(), None)), 1);
+                slide.push_element_in_step(SlideElement::Text(Text::new("c".into(), None)), 2);
+                slide.push_element_in_step(SlideElement::Image(Image::new("d.png".into())), 2);
+                slide
+            }],
+            Style::new(vec![]).unwrap()
+        )
+    );
+
+    parser_test!(
+        can_parse_slide_with_notes,
+        "metadata { title \"some title\" } slide \"some slide\" { notes \"say hello\" text \"a\" notes \"then wave\" }",
+        Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![{
+                let mut slide = Slide::new(
+                    "some slide".into(),
+                    vec![SlideElement::Text(Text::new("a".into(), None))]
+                );
+                slide.add_note("say hello".into());
+                slide.add_note("then wave".into());
+                slide
+            }],
+            Style::new(vec![]).unwrap()
+        )
+    );
+
+    parser_test!(
+        can_parse_slide_duration,
+        "metadata { title \"some title\" } slide \"some slide\" { duration 10 }",
+        Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![{
+                let mut slide = Slide::new("some slide".into(), vec![]);
+                slide.set_duration(Duration::from_secs(10));
+                slide
+            }],
+            Style::new(vec![]).unwrap()
+        )
+    );
+
+    parser_test!(
+        can_parse_text_with_markup,
+        "metadata { title \"some title\" } slide \"some slide\" { text \"a *bold* word\" }",
+        Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![Slide::new(
+                "some slide".into(),
+                vec![SlideElement::Text(Text::with_spans(
+                    vec![
+                        Span::new("a ".into()),
+                        bold("bold"),
+                        Span::new(" word".into())
+                    ],
+                    None
+                ))]
+            )],
+            Style::new(vec![]).unwrap()
+        )
+    );
+
+    parser_test!(
+        can_parse_titles_and_subtitles,
+        "metadata { title \"some title\" } slide \"some slide\" { title \"a\" subtitle \"b\" size 30 }",
+        Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![Slide::new(
+                "some slide".into(),
+                vec![
+                    SlideElement::Title(Text::new("a".into(), None)),
+                    SlideElement::Subtitle({
+                        let mut text = Text::new("b".into(), None);
+                        text.set_size(30);
+                        text
+                    })
+                ]
+            )],
+            Style::new(vec![]).unwrap()
+        )
+    );
+
+    parser_test!(
+        can_parse_element_placement,
+        "metadata { title \"some title\" } slide \"s\" { image \"a.png\" at 10% 20px anchor center size 50% auto text \"b\" size 30 at 0 0 size 10em 2em }",
+        Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![Slide::new(
+                "s".into(),
+                vec![
+                    SlideElement::Image({
+                        let mut image = Image::new("a.png".into());
+                        let mut placement = Placement::default();
+                        placement.set_position(Length::Percent(10.0), Length::Pixels(20.0));
+                        placement.set_anchor(Anchor::Center);
+                        placement.set_size(Some(Length::Percent(50.0)), None);
+                        image.set_placement(placement);
+                        image
+                    }),
+                    SlideElement::Text({
+                        let mut text = Text::new("b".into(), None);
+                        let mut placement = Placement::default();
+                        placement.set_position(Length::Pixels(0.0), Length::Pixels(0.0));
+                        placement.set_size(Some(Length::Em(10.0)), Some(Length::Em(2.0)));
+                        text.set_size(30);
+                        text.set_placement(placement);
+                        text
+                    })
+                ]
+            )],
+            Style::new(vec![]).unwrap()
+        )
+    );
+
+    parser_test!(
+        can_parse_rows_and_columns,
+        "metadata { title \"some title\" } slide \"s\" { row { text \"a\" grow 1 column { image \"b.png\" } } gap 10 align start }",
+        Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![Slide::new(
+                "s".into(),
+                vec![SlideElement::Row({
+                    let mut text = Text::new("a".into(), None);
+                    let mut placement = Placement::default();
+                    placement.set_grow(1);
+                    text.set_placement(placement);
+                    let mut row = Container::new(vec![
+                        SlideElement::Text(text),
+                        SlideElement::Column(Container::new(vec![SlideElement::Image(
+                            Image::new("b.png".into())
+                        )])),
+                    ]);
+                    row.set_gap(Length::Pixels(10.0));
+                    row.set_align(Align::Start);
+                    row
+                })]
+            )],
+            Style::new(vec![]).unwrap()
+        )
+    );
+
+    parser_test!(
+        can_parse_containers_nested_at_any_depth,
+        "metadata { title \"some title\" } slide \"s\" { column { row { column { text \"a\" } } } }",
+        Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![Slide::new(
+                "s".into(),
+                vec![SlideElement::Column(Container::new(vec![SlideElement::Row(
+                    Container::new(vec![SlideElement::Column(Container::new(vec![
+                        SlideElement::Text(Text::new("a".into(), None))
+                    ]))])
+                )]))]
+            )],
+            Style::new(vec![]).unwrap()
+        )
+    );
+
+    parser_test_fail!(
+        fails_on_duplicate_element_property,
+        "metadata { title \"some title\" } slide \"s\" { text \"a\" size 20 at 0 0 size 30 }",
+        Error::DuplicateProperty {
+            block: "text".into(),
+            property: "font size".into(),
+            location: SourceLocationRange::new(
+                SourceLocation::new(0, 69),
+                SourceLocation::new(0, 73)
+            )
+        }
+    );
+
+    parser_test!(
+        can_parse_metadata_properties_in_any_order,
+        "metadata { width 1920, title \"some title\", height 1080 }",
+        Presentation::new(
+            Metadata::new("some title".into(), Resolution::new(1920, 1080)),
+            vec![],
+            Style::new(vec![]).unwrap()
+        )
+    );
+
+    parser_test_fail!(
+        fails_on_missing_metadata_title,
+        "metadata { width 1920 }",
+        Error::MissingProperty {
+            block: "metadata".into(),
+            property: "title".into(),
+            location: SourceLocationRange::new(
+                SourceLocation::new(0, 1),
+                SourceLocation::new(0, 9)
+            )
+        }
+    );
+
+    parser_test_fail!(
+        fails_on_duplicate_metadata_property,
+        "metadata { title \"a\", title \"b\" }",
+        Error::DuplicateProperty {
+            block: "metadata".into(),
+            property: "title".into(),
+            location: SourceLocationRange::new(
+                SourceLocation::new(0, 23),
+                SourceLocation::new(0, 28)
+            )
+        }
+    );
+
+    parser_test_fail!(
+        fails_on_invalid_anchor,
+        "metadata { title \"some title\" } slide \"s\" { image \"a.png\" anchor middle }",
+        Error::InvalidAnchor {
+            value: "middle".into(),
+            location: SourceLocationRange::new(
+                SourceLocation::new(0, 66),
+                SourceLocation::new(0, 72)
+            )
+        }
+    );
+
+    parser_test!(
+        can_parse_element_animations,
+        "metadata { title \"some title\" } slide \"s\" { \
+         text \"a\" opacity 50% fade-in 200ms \
+         image \"b.png\" slide-in-from left \
+         text \"c\" opacity 80 slide-in-from bottom 1s fade-in }",
+        Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![Slide::new("s".into(), {
+                let mut faded = Animation::default();
+                faded.set_opacity(50);
+                faded.set_fade_in(Duration::from_millis(200));
+                let mut sliding = Animation::default();
+                sliding.set_slide_in(Side::Left, Duration::from_millis(300));
+                let mut both = Animation::default();
+                both.set_opacity(80);
+                both.set_slide_in(Side::Bottom, Duration::from_secs(1));
+                both.set_fade_in(Duration::from_millis(300));
+
+                let mut elements = vec![
+                    SlideElement::Text(Text::new("a".into(), None)),
+                    SlideElement::Image(Image::new("b.png".into())),
+                    SlideElement::Text(Text::new("c".into(), None)),
+                ];
+                for (element, animation) in elements.iter_mut().zip(vec![faded, sliding, both]) {
+                    element.set_animation(animation);
+                }
+                elements
+            })],
+            Style::new(vec![]).unwrap()
+        )
+    );
+
+    parser_test!(
+        can_parse_keyframes,
+        "metadata { title \"some title\" } slide \"s\" { \
+         text \"a\" animate { at 500ms x 40% rotate 90 opacity 50, at 0 x 0% ease-out scale 1.5 } }",
+        Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![Slide::new("s".into(), {
+                let mut animation = Animation::default();
+                animation.set_keyframes(vec![
+                    Keyframe::new(
+                        Duration::from_millis(0),
+                        vec![Motion::X(Length::Percent(0.0)), Motion::Scale(1.5)],
+                        Easing::EaseOut,
+                    ),
+                    Keyframe::new(
+                        Duration::from_millis(500),
+                        vec![
+                            Motion::X(Length::Percent(40.0)),
+                            Motion::Rotate(90.0),
+                            Motion::Opacity(50),
+                        ],
+                        Easing::Linear,
+                    ),
+                ]);
+                let mut text = Text::new("a".into(), None);
+                text.set_animation(animation);
+
+                vec![SlideElement::Text(text)]
+            })],
+            Style::new(vec![]).unwrap()
+        )
+    );
+
+    parser_test!(
+        can_parse_easings,
+        "metadata { title \"some title\", transition fade 300 ease-out } slide \"s\" { \
+         image \"a.png\" fade-in 200 spring \
+         text \"a\" slide-in-from left cubic-bezier 0.3 0 0.2 1 animate { at 0 y 1em spring 200 15.5 } }",
+        Presentation::new(
+            {
+                let mut metadata = Metadata::new("some title".into(), Resolution::default());
+                let mut transition = Transition::new(TransitionKind::Fade, Duration::from_millis(300));
+                transition.set_easing(Easing::EaseOut);
+                metadata.set_transition(transition);
+                metadata
+            },
+            vec![Slide::new("s".into(), {
+                let mut image = Image::new("a.png".into());
+                let mut animation = Animation::default();
+                animation.set_fade_in(Duration::from_millis(200));
+                animation.set_easing(Easing::SPRING);
+                image.set_animation(animation);
+
+                let mut animation = Animation::default();
+                animation.set_slide_in(Side::Left, Duration::from_millis(DEFAULT_ANIMATION_DURATION));
+                animation.set_easing(Easing::CubicBezier(0.3, 0.0, 0.2, 1.0));
+                animation.set_keyframes(vec![Keyframe::new(
+                    Duration::from_millis(0),
+                    vec![Motion::Y(Length::Em(1.0))],
+                    Easing::Spring {
+                        stiffness: 200.0,
+                        damping: 15.5,
+                    },
+                )]);
+                let mut text = Text::new("a".into(), None);
+                text.set_animation(animation);
+
+                vec![SlideElement::Image(image), SlideElement::Text(text)]
+            })],
+            Style::new(vec![]).unwrap()
+        )
+    );
+
+    parser_test!(
+        can_parse_transforms,
+        "metadata { title \"some title\" } slide \"s\" { \
+         text \"a\" rotate 15 scale 1.2 skew -10 \
+         image \"a.png\" skew 5 2.5 animate { at 0 rotate 90 scale 2 } }",
+        Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![Slide::new("s".into(), {
+                let mut animation = Animation::default();
+                animation.set_rotation(15.0);
+                animation.set_scale(1.2);
+                animation.set_skew(-10.0, 0.0);
+                let mut text = Text::new("a".into(), None);
+                text.set_animation(animation);
+
+                let mut animation = Animation::default();
+                animation.set_skew(5.0, 2.5);
+                animation.set_keyframes(vec![Keyframe::new(
+                    Duration::from_millis(0),
+                    vec![Motion::Rotate(90.0), Motion::Scale(2.0)],
+                    Easing::Linear,
+                )]);
+                let mut image = Image::new("a.png".into());
+                image.set_animation(animation);
+
+                vec![SlideElement::Text(text), SlideElement::Image(image)]
+            })],
+            Style::new(vec![]).unwrap()
+        )
+    );
+
+    parser_test_fail!(
+        fails_on_unknown_motion,
+        "metadata { title \"some title\" } slide \"s\" { text \"a\" animate { at 0ms z 5 } }",
+        Error::InvalidMotion {
+            value: "z".into(),
+            location: SourceLocationRange::new(
+                SourceLocation::new(0, 71),
+                SourceLocation::new(0, 72)
+            )
+        }
+    );
+
+    parser_test_fail!(
+        fails_on_invalid_side,
+        "metadata { title \"some title\" } slide \"s\" { image \"a.png\" slide-in-from middle }",
+        Error::InvalidSide {
+            value: "middle".into(),
+            location: SourceLocationRange::new(
+                SourceLocation::new(0, 73),
+                SourceLocation::new(0, 79)
+            )
+        }
+    );
+
+    parser_test_fail!(
+        fails_on_a_length_too_long_for_its_pixels,
+        "metadata { title \"some title\" } slide \"s\" { text \"a\" at 9999999999 0 }",
+        Error::OutOfRange {
+            property: "at".into(),
+            value: 9_999_999_999,
+            min: i32::MIN.into(),
+            max: Some(i32::MAX.into()),
+            location: SourceLocationRange::new(
+                SourceLocation::new(0, 57),
+                SourceLocation::new(0, 66)
+            )
+        }
+    );
+
+    parser_test_fail!(
+        fails_on_negative_grow,
+        "metadata { title \"some title\" } slide \"s\" { image \"a.png\" grow -1 }",
+        Error::OutOfRange {
+            property: "grow".into(),
+            value: -1,
+            min: 0,
+            max: None,
+            location: SourceLocationRange::new(
+                SourceLocation::new(0, 64),
+                SourceLocation::new(0, 65)
+            )
+        }
+    );
+
+    parser_test_fail!(
+        fails_on_opacity_over_a_hundred_percent,
+        "metadata { title \"some title\" } slide \"s\" { text \"a\" opacity 150 }",
+        Error::OutOfRange {
+            property: "opacity".into(),
+            value: 150,
+            min: 0,
+            max: Some(100),
+            location: SourceLocationRange::new(
+                SourceLocation::new(0, 62),
+                SourceLocation::new(0, 64)
+            )
+        }
+    );
+
+    parser_test_fail!(
+        fails_on_unclosed_markup,
+        "metadata { title \"some title\" } slide \"s\" { text \"*a\" }",
+        Error::InvalidMarkup {
+            error: MarkupError::Unclosed("*".into()),
+            location: SourceLocationRange::new(
+                SourceLocation::new(0, 50),
+                SourceLocation::new(0, 53)
+            )
+        }
+    );
+
+    parser_test!(
+        can_parse_slide_with_image,
+        "metadata { title \"some title\" } slide \"some slide\" { image \"images/cat.png\" }",
+        Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![Slide::new(
+                "some slide".into(),
+                vec![SlideElement::Image(Image::new("images/cat.png".into()))]
+            )],
+            Style::new(vec![]).unwrap()
+        )
+    );
+
+    parser_test!(
+        can_parse_slide_with_video,
+        "metadata { title \"some title\" } slide \"some slide\" { video \"clip.mp4\" size 640px auto }",
+        Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![Slide::new(
+                "some slide".into(),
+                vec![SlideElement::Video({
+                    let mut video = Video::new("clip.mp4".into());
+                    let mut placement = Placement::default();
+                    placement.set_size(Some(Length::Pixels(640.0)), None);
+                    video.set_placement(placement);
+                    video
+                })]
+            )],
+            Style::new(vec![]).unwrap()
+        )
+    );
+
+    parser_test!(
+        can_parse_slide_with_text,
+        "metadata { title \"some title\" } slide \"some slide\" { text \"first\" text \"second\" }",
+        Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![Slide::new(
+                "some slide".into(),
+                vec![
+                    SlideElement::Text(Text::new("first".into(), None)),
+                    SlideElement::Text(Text::new("second".into(), None))
+                ]
+            )],
+            Style::new(vec![]).unwrap()
+        )
+    );
+
+    parser_test!(
+        can_parse_list,
+        "metadata { title \"some title\" } slide \"some slide\" { list { item \"first\", item \"second\" } }",
+        Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![Slide::new(
+                "some slide".into(),
+                vec![SlideElement::List(List::new(
+                    vec!["first".into(), "second".into()],
+                    "•".into(),
+                    Length::Pixels(40.0)
+                ))]
+            )],
+            Style::new(vec![]).unwrap()
+        )
+    );
+
+    parser_test!(
+        can_parse_list_with_custom_bullet_and_indent,
+        "metadata { title \"some title\" } slide \"some slide\" { list { bullet \"-\", indent 20, item \"first\", } }",
+        Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![Slide::new(
+                "some slide".into(),
+                vec![SlideElement::List(List::new(
+                    vec!["first".into()],
+                    "-".into(),
+                    Length::Pixels(20.0)
+                ))]
+            )],
+            Style::new(vec![]).unwrap()
+        )
+    );
+
+    parser_test!(
+        can_parse_list_with_relative_indent,
+        "metadata { title \"some title\" } slide \"some slide\" { list { indent 2em, item \"first\" } }",
+        Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![Slide::new(
+                "some slide".into(),
+                vec![SlideElement::List(List::new(
+                    vec!["first".into()],
+                    "•".into(),
+                    Length::Em(2.0)
+                ))]
+            )],
+            Style::new(vec![]).unwrap()
+        )
+    );
+
+    parser_test_fail!(
+        fails_on_list_items_without_separator,
+        "metadata { title \"some title\" } slide \"some slide\" { list { item \"first\" item \"second\" } }",
+        Error::UnexpectedToken {
+            actual: "KeywordItem".into(),
+            expected: "Comma, ClosingBrace".into(),
+            location: SourceLocationRange::new(
+                SourceLocation::new(0, 74),
+                SourceLocation::new(0, 78)
+            )
+        }
+    );
+
+    parser_test!(
+        can_parse_style_background,
+        "metadata { title \"some title\" } style { background \"#102030\" }",
+        Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![],
+            {
+                let mut style = Style::new(vec![]).unwrap();
+                style.set_background(Fill::Color(Color::new(0x10, 0x20, 0x30, 0xff)));
+                style
+            }
+        )
+    );
+
+    parser_test!(
+        can_parse_slide_background,
+        "metadata { title \"some title\" } slide \"some slide\" { background white text \"a\" }",
+        Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![{
+                let mut slide = Slide::new(
+                    "some slide".into(),
+                    vec![SlideElement::Text(Text::new("a".into(), None))],
+                );
+                slide.set_background(Fill::Color(Color::new(0xff, 0xff, 0xff, 0xff)));
+                slide
+            }],
+            Style::new(vec![]).unwrap()
+        )
+    );
+
+    parser_test!(
+        can_parse_gradient_backgrounds,
+        "metadata { title \"some title\" } style { background gradient #202040 #000000 } slide \"s\" { background gradient white blue radial row { text \"a\" } background gradient red black horizontal }",
+        Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![{
+                let mut row = Container::new(vec![SlideElement::Text(Text::new("a".into(), None))]);
+                row.set_background(Fill::Gradient {
+                    from: Color::new(0xff, 0, 0, 0xff),
+                    to: Color::new(0, 0, 0, 0xff),
+                    direction: GradientDirection::Horizontal,
+                });
+                let mut slide = Slide::new("s".into(), vec![SlideElement::Row(row)]);
+                slide.set_background(Fill::Gradient {
+                    from: Color::new(0xff, 0xff, 0xff, 0xff),
+                    to: Color::new(0, 0, 0xff, 0xff),
+                    direction: GradientDirection::Radial,
+                });
+                slide
+            }],
+            {
+                let mut style = Style::new(vec![]).unwrap();
+                style.set_background(Fill::Gradient {
+                    from: Color::new(0x20, 0x20, 0x40, 0xff),
+                    to: Color::new(0, 0, 0, 0xff),
+                    direction: GradientDirection::Vertical,
+                });
+                style
+            }
+        )
+    );
+
+    parser_test!(
+        can_parse_background_images,
+        "metadata { title \"some title\" } style { background image \"texture.png\" tile } slide \"s\" { background image \"photo.jpg\" image \"a.png\" }",
+        Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![{
+                let mut slide = Slide::new(
+                    "s".into(),
+                    vec![SlideElement::Image(Image::new("a.png".into()))],
+                );
+                slide.set_background(Fill::Image {
+                    path: "photo.jpg".into(),
+                    fit: ImageFit::Cover,
+                });
+                slide
+            }],
+            {
+                let mut style = Style::new(vec![]).unwrap();
+                style.set_background(Fill::Image {
+                    path: "texture.png".into(),
+                    fit: ImageFit::Tile,
+                });
+                style
+            }
+        )
+    );
+
+    parser_test_fail!(
+        fails_on_invalid_image_fit,
+        "metadata { title \"some title\" } style { background image \"a.png\" stretch }",
+        Error::InvalidImageFit {
+            value: "stretch".into(),
+            location: SourceLocationRange::new(
+                SourceLocation::new(0, 66),
+                SourceLocation::new(0, 73)
+            )
+        }
+    );
+
+    parser_test_fail!(
+        fails_on_invalid_gradient_direction,
+        "metadata { title \"some title\" } style { background gradient red black sideways }",
+        Error::InvalidGradientDirection {
+            value: "sideways".into(),
+            location: SourceLocationRange::new(
+                SourceLocation::new(0, 71),
+                SourceLocation::new(0, 79)
+            )
+        }
+    );
+
+    parser_test!(
+        can_parse_color_literals_in_style,
+        "metadata { title \"some title\" } style { background #000000 color #ffffff80 }",
+        Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![],
+            {
+                let mut style = Style::new(vec![]).unwrap();
+                style.set_background(Fill::Color(Color::new(0, 0, 0, 0xff)));
+                style.set_text_color(Color::new(0xff, 0xff, 0xff, 0x80));
+                style
+            }
+        )
+    );
+
+    parser_test!(
+        can_parse_text_rendering_options,
+        "metadata { title \"some title\" } style { hinting light antialias false kerning }",
+        Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![],
+            {
+                let mut style = Style::new(vec![]).unwrap();
+                style.set_hinting(Hinting::Light);
+                style.set_antialias(false);
+                style.set_kerning(true);
+                style
+            }
+        )
+    );
+
+    parser_test_fail!(
+        fails_on_invalid_hinting,
+        "metadata { title \"some title\" } style { hinting soft }",
+        Error::InvalidHinting {
+            value: "soft".into(),
+            location: SourceLocationRange::new(
+                SourceLocation::new(0, 49),
+                SourceLocation::new(0, 53)
+            )
+        }
+    );
+
+    parser_test!(
+        can_parse_text_styles,
+        "metadata { title \"some title\" } style { text-style heading { font my-font, size 48, color #ffffff } text-style quote { size 12, } }",
+        Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![],
+            {
+                let mut style = Style::new(vec![]).unwrap();
+                style
+                    .add_text_style(
+                        "heading".into(),
+                        TextStyle::new(
+                            Some("my-font".into()),
+                            Some(48),
+                            Some(Color::new(0xff, 0xff, 0xff, 0xff))
+                        )
+                    )
+                    .unwrap();
+                style
+                    .add_text_style("quote".into(), TextStyle::new(None, Some(12), None))
+                    .unwrap();
+                style
+            }
+        )
+    );
+
+    parser_test!(
+        can_parse_text_style_with_font_stack,
+        "metadata { title \"some title\" } style { text-style body { font-stack main, emoji, cjk, size 20 } }",
+        Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![],
+            {
+                let mut text_style = TextStyle::new(Some("main".into()), Some(20), None);
+                text_style.set_fallback_fonts(vec!["emoji".into(), "cjk".into()]);
+
+                let mut style = Style::new(vec![]).unwrap();
+                style.add_text_style("body".into(), text_style).unwrap();
+                style
+            }
+        )
+    );
+
+    parser_test!(
+        can_parse_text_style_with_weight_and_italic,
+        "metadata { title \"some title\" } style { text-style emphasis { font sans, weight 700, italic } }",
+        Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![],
+            {
+                let mut text_style = TextStyle::new(Some("sans".into()), None, None);
+                text_style.set_weight(700);
+                text_style.set_italic(true);
+
+                let mut style = Style::new(vec![]).unwrap();
+                style
+                    .add_text_style("emphasis".into(), text_style)
+                    .unwrap();
+                style
+            }
+        )
+    );
+
+    parser_test!(
+        can_parse_text_style_with_explicit_flag_value,
+        "metadata { title \"some title\" } style { text-style plain { italic false } }",
+        Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![],
+            {
+                let mut text_style = TextStyle::new(None, None, None);
+                text_style.set_italic(false);
+
+                let mut style = Style::new(vec![]).unwrap();
+                style.add_text_style("plain".into(), text_style).unwrap();
+                style
+            }
+        )
+    );
+
+    parser_test_fail!(
+        fails_on_duplicate_text_style,
+        "metadata { title \"some title\" } style { text-style heading { size 48 } text-style heading { size 12 } }",
+        Error::InvalidStyleDefinition(StyleError::DuplicateTextStyle("heading".into()))
+    );
+
+    parser_test!(
+        can_parse_text_with_style,
+        "metadata { title \"some title\" } slide \"some slide\" { text \"a\" style heading text \"b\" }",
+        Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![Slide::new(
+                "some slide".into(),
+                vec![
+                    SlideElement::Text(Text::new("a".into(), Some("heading".into()))),
+                    SlideElement::Text(Text::new("b".into(), None))
+                ]
+            )],
+            Style::new(vec![]).unwrap()
+        )
+    );
+
+    parser_test_fail!(
+        fails_on_invalid_color,
+        "metadata { title \"some title\" } style { background \"#10203\" }",
+        Error::InvalidColor {
+            value: "#10203".into(),
+            location: SourceLocationRange::new(
+                SourceLocation::new(0, 52),
+                SourceLocation::new(0, 59)
+            )
+        }
+    );
+
+    parser_test_fail!(
+        fails_on_unknown_color_name,
+        "metadata { title \"some title\" } style { background reddish }",
+        Error::InvalidColor {
+            value: "reddish".into(),
+            location: SourceLocationRange::new(
+                SourceLocation::new(0, 52),
+                SourceLocation::new(0, 59)
+            )
+        }
+    );
+
+    parser_test_fail!(
+        fails_on_unknown_transition,
+        "metadata { title \"some title\", transition spin }",
+        Error::InvalidTransition {
+            value: "spin".into(),
+            location: SourceLocationRange::new(
+                SourceLocation::new(0, 43),
+                SourceLocation::new(0, 47)
+            )
+        }
+    );
+
+    parser_test!(
+        can_parse_control_inputs,
+        "metadata { title \"some title\", control osc 53000 { \"/cue/go\" next, \"/cue/back\" previous }, control midi \"/dev/snd/midiC1D0\" }",
+        Presentation::new(
+            {
+                let mut metadata = Metadata::new("some title".into(), Resolution::default());
+                let mut addresses = HashMap::new();
+                addresses.insert("/cue/go".into(), Action::Next);
+                addresses.insert("/cue/back".into(), Action::Previous);
+                metadata.add_control(ControlInput::Osc {
+                    port: 53000,
+                    addresses,
+                });
+                metadata.add_control(ControlInput::Midi {
+                    device: "/dev/snd/midiC1D0".into(),
+                    notes: HashMap::new(),
+                });
+                metadata
+            },
+            vec![],
+            Style::new(vec![]).unwrap()
+        )
+    );
+
+    parser_test!(
+        can_parse_overlay_flags,
+        "metadata { title \"some title\", show-progress, show-page-numbers true, show-footer false }",
+        Presentation::new(
+            {
+                let mut metadata = Metadata::new("some title".into(), Resolution::default());
+                metadata.set_show_progress(true);
+                metadata.set_show_page_numbers(true);
+                metadata
+            },
+            vec![],
+            Style::new(vec![]).unwrap()
+        )
+    );
+
+    parser_test!(
+        can_parse_spelling_ignore_lists,
+        "metadata { title \"some title\", spelling-ignore \"przntr SDL\", spelling-ignore \"OSC\" }",
+        Presentation::new(
+            {
+                let mut metadata = Metadata::new("some title".into(), Resolution::default());
+                metadata.add_spelling_ignore("przntr".into());
+                metadata.add_spelling_ignore("SDL".into());
+                metadata.add_spelling_ignore("OSC".into());
+                metadata
+            },
+            vec![],
+            Style::new(vec![]).unwrap()
+        )
+    );
+
+    parser_test_fail!(
+        fails_on_unknown_control_action,
+        "metadata { title \"some title\", control midi \"dev\" { 60 jump } }",
+        Error::InvalidAction {
+            value: "jump".into(),
+            location: SourceLocationRange::new(
+                SourceLocation::new(0, 56),
+                SourceLocation::new(0, 60)
+            )
+        }
+    );
+
+    parser_test_fail!(
+        fails_on_note_out_of_range,
+        "metadata { title \"some title\", control midi \"dev\" { 128 next } }",
+        Error::InvalidControlNumber {
+            value: 128,
+            location: SourceLocationRange::new(
+                SourceLocation::new(0, 53),
+                SourceLocation::new(0, 55)
+            )
+        }
+    );
+
+    parser_test_fail!(
+        fails_on_text_without_a_string,
+        "metadata { title \"some title\" } slide \"some slide\" { text }",
+        Error::UnexpectedToken {
+            actual: "ClosingBrace".into(),
+            expected: "String(text)".into(),
+            location: SourceLocationRange::new_single(SourceLocation::new(0, 59))
+        }
+    );
+
+    parser_test!(
+        can_parse_single_font,
+        "metadata { title \"some title\" } style { font { path \"some_path\", name my-wonderful-font, weight 500,}}",
+        Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![],
+            Style::new(vec![Font::new(
+                "my-wonderful-font".into(),
+                "some_path".into(),
+                500,
+                false
+            )]).unwrap()
+        )
+    );
+
+    parser_test!(
+        can_parse_italic_font,
+        "metadata { title \"some title\" } style { font { path \"some_path\", name my-wonderful-font, weight 500, italic, } }",
+        Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![],
+            Style::new(vec![Font::new(
+                "my-wonderful-font".into(),
+                "some_path".into(),
+                500,
+                true
+            )]).unwrap()
+        )
+    );
+
+    parser_test!(
+        can_parse_font_with_explicit_flag_values,
+        "metadata { title \"some title\" } style { font { name sans, italic true, system false, path \"some_path\" } }",
+        Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![],
+            Style::new(vec![Font::new(
+                "sans".into(),
+                "some_path".into(),
+                400,
+                true
+            )]).unwrap()
+        )
+    );
+
+    parser_test!(
+        can_parse_system_font,
+        "metadata { title \"some title\" } style { font { name sans, system } }",
+        Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![],
+            Style::new(vec![Font::system("sans".into(), 400, false)]).unwrap()
+        )
+    );
+
+    parser_test_fail!(
+        fails_on_font_without_path,
+        "metadata { title \"some title\" } style { font { name sans, weight 700 } }",
+        Error::MissingProperty {
+            block: "font".into(),
+            property: "path".into(),
+            location: SourceLocationRange::new(
+                SourceLocation::new(0, 41),
+                SourceLocation::new(0, 45)
+            )
+        }
+    );
+
+    parser_test_fail!(
+        fails_on_font_with_only_a_name,
+        "metadata { title \"some title\" } style { font { name foo, } }",
+        Error::MissingProperty {
+            block: "font".into(),
+            property: "path".into(),
+            location: SourceLocationRange::new(
+                SourceLocation::new(0, 41),
+                SourceLocation::new(0, 45)
+            )
+        }
+    );
+
+    parser_test_fail!(
+        fails_on_font_without_name,
+        "metadata { title \"some title\" } style { font { path \"some_path\", weight 700 } }",
+        Error::MissingProperty {
+            block: "font".into(),
+            property: "name".into(),
+            location: SourceLocationRange::new(
+                SourceLocation::new(0, 41),
+                SourceLocation::new(0, 45)
+            )
+        }
+    );
+
+    parser_test_fail!(
+        fails_on_system_font_without_name,
+        "metadata { title \"some title\" } style { font { system, italic } }",
+        Error::MissingProperty {
+            block: "font".into(),
+            property: "name".into(),
+            location: SourceLocationRange::new(
+                SourceLocation::new(0, 41),
+                SourceLocation::new(0, 45)
+            )
+        }
+    );
+
+    parser_test_fail!(
+        reports_missing_font_name_before_path,
+        "metadata { title \"some title\" } style { font { } }",
+        Error::MissingProperty {
+            block: "font".into(),
+            property: "name".into(),
+            location: SourceLocationRange::new(
+                SourceLocation::new(0, 41),
+                SourceLocation::new(0, 45)
+            )
+        }
+    );
+
+    parser_test!(
+        defaults_font_weight_and_italic,
+        "metadata { title \"some title\" } style { font { name sans, path \"some_path\" } }",
+        Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![],
+            Style::new(vec![Font::new(
+                "sans".into(),
+                "some_path".into(),
+                400,
+                false
+            )])
+            .unwrap()
+        )
+    );
+
+    parser_test_fail!(
+        fails_on_negative_font_weight,
+        "metadata { title \"some title\" } style { font { name sans, system, weight -100 } }",
+        Error::OutOfRange {
+            property: "weight".into(),
+            value: -100,
+            min: 1,
+            max: Some(1000),
+            location: SourceLocationRange::new(
+                SourceLocation::new(0, 74),
+                SourceLocation::new(0, 77)
+            )
+        }
+    );
+
+    parser_test_fail!(
+        fails_on_too_heavy_text_style,
+        "metadata { title \"some title\" } style { text-style heading { weight 1200 } }",
+        Error::OutOfRange {
+            property: "weight".into(),
+            value: 1200,
+            min: 1,
+            max: Some(1000),
+            location: SourceLocationRange::new(
+                SourceLocation::new(0, 69),
+                SourceLocation::new(0, 72)
+            )
+        }
+    );
+
+    parser_test_fail!(
+        fails_on_empty_text_size,
+        "metadata { title \"some title\" } slide \"s\" { text \"a\" size 0 }",
+        Error::OutOfRange {
+            property: "size".into(),
+            value: 0,
+            min: 1,
+            max: None,
+            location: SourceLocationRange::new(
+                SourceLocation::new(0, 59),
+                SourceLocation::new(0, 59)
+            )
+        }
+    );
+
+    parser_test_fail!(
+        fails_on_zero_width,
+        "metadata { title \"some title\", width 0 }",
+        Error::OutOfRange {
+            property: "width".into(),
+            value: 0,
+            min: 1,
+            max: None,
+            location: SourceLocationRange::new(
+                SourceLocation::new(0, 38),
+                SourceLocation::new(0, 38)
+            )
+        }
+    );
+
+    parser_test!(
+        can_parse_font_with_size,
+        "metadata { title \"some title\" } style { font { path \"some_path\", name my-wonderful-font, weight 500, size 32, } }",
+        Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![],
+            Style::new(vec![{
+                let mut font = Font::new(
+                    "my-wonderful-font".into(),
+                    "some_path".into(),
+                    500,
+                    false
+                );
+                font.set_size(32);
+                font
+            }]).unwrap()
+        )
+    );
+
+    parser_test!(
+        can_parse_text_with_size,
+        "metadata { title \"some title\" } slide \"some slide\" { text \"a\" size 64 style heading text \"b\" style heading size 12 }",
+        Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![Slide::new(
+                "some slide".into(),
+                vec![
+                    SlideElement::Text({
+                        let mut text = Text::new("a".into(), Some("heading".into()));
+                        text.set_size(64);
+                        text
+                    }),
+                    SlideElement::Text({
+                        let mut text = Text::new("b".into(), Some("heading".into()));
+                        text.set_size(12);
+                        text
+                    })
+                ]
+            )],
+            Style::new(vec![]).unwrap()
+        )
+    );
+
+    parser_test!(
+        slide_after_style,
+        "metadata { title \"some title\" } style { font { path \"some_path\", name my-wonderful-font, weight 500, } } slide \"some slide\" {}",
+        Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![Slide::new("some slide".into(), vec![])],
+            Style::new(vec![Font::new(
+                "my-wonderful-font".into(),
+                "some_path".into(),
+                500,
+                false
+            )]).unwrap()
+        )
+    );
+
+    parser_test!(
+        style_with_multiple_fonts,
+        "metadata { title \"some title\" } \n\
+         style { \n\
+         font { path \"path1\", name font-1, weight 500, } \n\
+         font { path \"path2\", name font-1, weight 500, italic, } \n\
+         }",
+        Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![],
             Style::new(vec![
                 Font::new("font-1".into(), "path1".into(), 500, false),
                 Font::new("font-1".into(), "path2".into(), 500, true)
@@ -365,7 +3214,9 @@ mod test {
         "metadata { title \"some title\" } style { font { invalid \"some_path\" } }",
         Error::UnexpectedToken {
             actual: "Name(\"invalid\")".into(),
-            expected: "KeywordName, KeywordPath, KeywordWeight, KeywordItalic, ClosingBrace".into(),
+            expected:
+                "KeywordName, KeywordPath, KeywordWeight, KeywordItalic, KeywordSize, KeywordSystem, ClosingBrace"
+                    .into(),
             location: SourceLocationRange::new(
                 SourceLocation::new(0, 48),
                 SourceLocation::new(0, 55)
@@ -384,10 +3235,359 @@ mod test {
 
         assert_eq!(
             parser.parse(),
-            Err(Error::TokenizerFailure(TokenizerFailure::new(
+            Err(vec![Error::TokenizerFailure(TokenizerFailure::new(
                 SourceLocationRange::new_single(SourceLocation::new(0, 0)),
                 TokenizerFailureKind::UnclosedString
-            )))
+            ))])
+        );
+    }
+
+    #[test]
+    pub fn reports_errors_from_multiple_slides() {
+        let mut tokenizer = Tokenizer::new(
+            "metadata { title \"some title\" } \
+             slide \"first\" { text 12 text \"ok\" } \
+             slide \"second\" { list { item 34 } } \
+             style { font { weight } }",
+        );
+        let mut parser = Parser::new(&mut tokenizer);
+
+        let errors = parser.parse().unwrap_err();
+
+        assert_eq!(3, errors.len());
+        assert_eq!(
+            vec!["Integer(12)", "Integer(34)", "ClosingBrace"],
+            errors
+                .iter()
+                .map(|error| match error {
+                    Error::UnexpectedToken { actual, .. } => actual.as_str(),
+                    _ => "other error",
+                })
+                .collect::<Vec<&str>>()
+        );
+    }
+
+    #[test]
+    pub fn recovers_at_next_element_of_slide() {
+        let mut tokenizer = Tokenizer::new(
+            "metadata { title \"some title\" } slide \"first\" { text 1 list { item 34 } text \"ok\" } slide \"second\" { text 56 }",
+        );
+        let mut parser = Parser::new(&mut tokenizer);
+
+        assert_eq!(3, parser.parse().unwrap_err().len());
+    }
+
+    #[test]
+    pub fn parses_layouts_and_slides_using_them() {
+        let mut tokenizer = Tokenizer::new(
+            "metadata { title \"some title\" } \
+             layout two-column { text \"header\" placeholder left placeholder right } \
+             slide \"some slide\" uses two-column { right { text \"b\" } left { step { text \"a\" } } }",
+        );
+        let mut parser = Parser::new(&mut tokenizer);
+        let text = |content: &str| SlideElement::Text(Text::new(content.into(), None));
+
+        let presentation = parser.parse().unwrap();
+        let slide = &presentation.slides()[0];
+
+        assert_eq!(Some(&"two-column".to_string()), slide.layout());
+        assert_eq!(
+            vec![(&text("header"), 0), (&text("a"), 1), (&text("b"), 0)],
+            presentation.arranged_elements(slide)
+        );
+    }
+
+    parser_test!(
+        can_parse_sections,
+        "metadata { title \"some title\" } section \"Introduction\" slide \"a\" { } slide \"b\" { } section \"Demo\" slide \"c\" { }",
+        {
+            let mut presentation = Presentation::new(
+                Metadata::new("some title".into(), Resolution::default()),
+                vec![
+                    Slide::new("a".into(), vec![]),
+                    Slide::new("b".into(), vec![]),
+                    Slide::new("c".into(), vec![])
+                ],
+                Style::new(vec![]).unwrap(),
+            );
+            presentation.add_section(Section::new("Introduction".into(), 0));
+            presentation.add_section(Section::new("Demo".into(), 2));
+            presentation
+        }
+    );
+
+    parser_test!(
+        can_parse_hidden_slides,
+        "metadata { title \"some title\" } slide \"wip\" hidden { } slide \"done\" { }",
+        Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![
+                {
+                    let mut slide = Slide::new("wip".into(), vec![]);
+                    slide.set_hidden(true);
+                    slide
+                },
+                Slide::new("done".into(), vec![])
+            ],
+            Style::new(vec![]).unwrap()
+        )
+    );
+
+    parser_test!(
+        can_parse_scheduled_slides,
+        "metadata { title \"some title\" } slide \"demo\" hidden at 00:05:30 { } slide \"end\" at 12:00 { }",
+        Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![
+                {
+                    let mut slide = Slide::new("demo".into(), vec![]);
+                    slide.set_hidden(true);
+                    slide.set_scheduled_at(Duration::from_secs(330));
+                    slide
+                },
+                {
+                    let mut slide = Slide::new("end".into(), vec![]);
+                    slide.set_scheduled_at(Duration::from_secs(720));
+                    slide
+                }
+            ],
+            Style::new(vec![]).unwrap()
+        )
+    );
+
+    parser_test_fail!(
+        fails_on_duplicate_layout,
+        "metadata { title \"some title\" } layout a { } layout a { }",
+        Error::DuplicateLayout {
+            name: "a".into(),
+            location: SourceLocationRange::new(
+                SourceLocation::new(0, 53),
+                SourceLocation::new(0, 54)
+            )
+        }
+    );
+
+    parser_test!(
+        interpolates_variables_in_strings,
+        "define event \"RustConf\" \
+         metadata { title \"${event} talk\" } \
+         define year \"2024\" \
+         slide \"${event}\" { text \"See you at ${event} ${year}!\" text \"$5 {}\" \
+         text \"$${year} stays\" }",
+        Presentation::new(
+            Metadata::new("RustConf talk".into(), Resolution::default()),
+            vec![Slide::new(
+                "RustConf".into(),
+                vec![
+                    SlideElement::Text(Text::new("See you at RustConf 2024!".into(), None)),
+                    SlideElement::Text(Text::new("$5 {}".into(), None)),
+                    SlideElement::Text(Text::new("${year} stays".into(), None))
+                ]
+            )],
+            Style::new(vec![]).unwrap()
+        )
+    );
+
+    parser_test_fail!(
+        fails_on_undefined_variable,
+        "metadata { title \"${event}\" } define event \"RustConf\"",
+        Error::UndefinedVariable {
+            name: "event".into(),
+            location: SourceLocationRange::new(
+                SourceLocation::new(0, 18),
+                SourceLocation::new(0, 27)
+            )
+        }
+    );
+
+    fn bold(content: &str) -> Span {
+        let mut span = Span::new(content.into());
+        span.set_bold(true);
+        span
+    }
+
+    fn parse_in_directory(
+        directory: &Path,
+        files: &[(&str, &str)],
+    ) -> Result<Presentation, Vec<Error>> {
+        fs::create_dir_all(directory.join("sections")).unwrap();
+        for (name, contents) in files {
+            fs::write(directory.join(name), contents).unwrap();
+        }
+
+        let source = fs::read_to_string(directory.join("deck.prz")).unwrap();
+        let mut tokenizer = Tokenizer::new(&source);
+        let mut parser = Parser::new(&mut tokenizer);
+        parser.set_source_path(directory.join("deck.prz"));
+        let result = parser.parse();
+
+        fs::remove_dir_all(directory).unwrap();
+        result
+    }
+
+    #[test]
+    pub fn splices_in_included_slides() {
+        let directory = std::env::temp_dir().join("przntr-include-test");
+        let presentation = parse_in_directory(
+            &directory,
+            &[
+                (
+                    "deck.prz",
+                    "metadata { title \"some title\" } include \"sections/intro.prz\" slide \"last\" { }",
+                ),
+                ("sections/intro.prz", "slide \"first\" { } slide \"second\" { }"),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(
+            vec!["first", "second", "last"],
+            presentation
+                .slides()
+                .iter()
+                .map(|slide| slide.name().as_str())
+                .collect::<Vec<&str>>()
+        );
+        assert_eq!(
+            &vec![directory.join("sections/intro.prz")],
+            presentation.included_files()
+        );
+    }
+
+    #[test]
+    pub fn resolves_asset_paths_next_to_the_presentation() {
+        let directory = std::env::temp_dir().join("przntr-asset-path-test");
+        let presentation = parse_in_directory(
+            &directory,
+            &[(
+                "deck.prz",
+                "metadata { title \"some title\", font-dir \"fonts\", font-dir \"/usr/share/fonts\" } slide \"s\" { image \"cat.png\" }",
+            )],
+        )
+        .unwrap();
+
+        assert_eq!(
+            &vec![
+                directory.join("fonts").to_string_lossy().into_owned(),
+                "/usr/share/fonts".to_string()
+            ],
+            presentation.metadata().font_dirs()
+        );
+        let Some(SlideElement::Image(image)) = presentation.slides()[0].elements().first() else {
+            panic!("expected an image");
+        };
+        assert_eq!(&directory.join("cat.png").to_string_lossy(), image.path());
+    }
+
+    #[test]
+    pub fn reports_urls_that_are_not_downloaded_when_offline() {
+        let mut assets = AssetLoader::new(std::env::temp_dir().join("przntr-no-such-cache"));
+        assets.set_offline(true);
+        let mut tokenizer = Tokenizer::new(
+            "metadata { title \"some title\" } slide \"s\" { image \"https://example.com/cat.png\" }",
         );
+        let mut parser = Parser::new(&mut tokenizer);
+        parser.set_asset_loader(assets);
+
+        assert_eq!(
+            Err(vec![Error::AssetFailed {
+                url: "https://example.com/cat.png".into(),
+                reason: "`https://example.com/cat.png` is not downloaded and przntr is offline"
+                    .into(),
+                location: SourceLocationRange::new(
+                    SourceLocation::new(0, 51),
+                    SourceLocation::new(0, 79)
+                )
+            }]),
+            parser.parse()
+        );
+    }
+
+    #[test]
+    pub fn detects_include_cycles() {
+        let directory = std::env::temp_dir().join("przntr-include-cycle-test");
+        let errors = parse_in_directory(
+            &directory,
+            &[
+                (
+                    "deck.prz",
+                    "metadata { title \"some title\" } include \"sections/a.prz\"",
+                ),
+                ("sections/a.prz", "include \"../deck.prz\""),
+            ],
+        )
+        .unwrap_err();
+
+        assert_eq!(1, errors.len());
+        assert!(match &errors[0] {
+            Error::InIncludedFile { errors, .. } => match errors.as_slice() {
+                [Error::IncludeCycle { path, .. }] => path.ends_with("deck.prz"),
+                _ => false,
+            },
+            _ => false,
+        });
+    }
+
+    #[test]
+    pub fn reports_missing_included_file() {
+        let directory = std::env::temp_dir().join("przntr-include-missing-test");
+        let errors = parse_in_directory(
+            &directory,
+            &[(
+                "deck.prz",
+                "metadata { title \"some title\" } include \"nope.prz\" slide \"a\" { }",
+            )],
+        )
+        .unwrap_err();
+
+        assert!(match errors.as_slice() {
+            [Error::IncludeFailed { path, .. }] => path.ends_with("nope.prz"),
+            _ => false,
+        });
+    }
+
+    #[test]
+    pub fn styles_presentations_with_their_theme() {
+        let directory = std::env::temp_dir().join("przntr-theme-test");
+        let presentation = parse_in_directory(
+            &directory,
+            &[
+                (
+                    "deck.prz",
+                    "metadata { title \"some title\", theme \"corporate\" } style { color #000000 } slide \"a\" uses cover { }",
+                ),
+                (
+                    "corporate.przt",
+                    "style { background #202040 color #ffffff } layout cover { placeholder main }",
+                ),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(
+            Some(&"corporate".to_string()),
+            presentation.metadata().theme()
+        );
+        assert_eq!(
+            Some(&Fill::Color(Color::new(0x20, 0x20, 0x40, 0xff))),
+            presentation.style().background()
+        );
+        assert_eq!(
+            Some(Color::new(0, 0, 0, 0xff)),
+            presentation.style().text_color()
+        );
+        assert!(presentation.layout("cover").is_some());
+        assert_eq!(
+            &vec![directory.join("corporate.przt")],
+            presentation.included_files()
+        );
+    }
+
+    #[test]
+    pub fn rejects_slides_in_themes() {
+        let mut tokenizer = Tokenizer::new("style { } slide \"a\" { }");
+        let mut parser = Parser::new(&mut tokenizer);
+
+        assert_eq!(Err(vec![Error::SlidesInTheme]), parser.parse_theme());
     }
 }
@@ -0,0 +1,108 @@
+use super::parser::Error;
+use super::token_stream::SourceLocationRange;
+use std::collections::HashMap;
+
+/// Kind of value a property takes
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ValueKind {
+    String,
+    Name,
+    Integer,
+    /// A boolean which can be left out to mean true
+    Flag,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Value {
+    String(String),
+    Name(String),
+    Integer(i128),
+    Flag(bool),
+}
+
+/// Properties given in a block, each with where its value is
+#[derive(Debug)]
+pub struct PropertyBag {
+    block: &'static str,
+    location: SourceLocationRange,
+    values: HashMap<&'static str, (Value, SourceLocationRange)>,
+}
+
+impl PropertyBag {
+    /// Empty bag of the block whose keyword is at the location
+    pub fn new(block: &'static str, location: SourceLocationRange) -> Self {
+        Self {
+            block,
+            location,
+            values: HashMap::new(),
+        }
+    }
+
+    pub fn insert(&mut self, name: &'static str, value: Value, location: SourceLocationRange) {
+        self.values.insert(name, (value, location));
+    }
+
+    pub fn get(&self, name: &str) -> Option<&(Value, SourceLocationRange)> {
+        self.values.get(name)
+    }
+
+    /// A string or a name
+    pub fn string(&self, name: &str) -> Option<String> {
+        match self.get(name) {
+            Some((Value::String(value) | Value::Name(value), _)) => Some(value.clone()),
+            _ => None,
+        }
+    }
+
+    pub fn integer(&self, name: &str) -> Option<(i128, SourceLocationRange)> {
+        match self.get(name) {
+            Some((Value::Integer(value), location)) => Some((*value, *location)),
+            _ => None,
+        }
+    }
+
+    pub fn flag(&self, name: &str) -> Option<bool> {
+        match self.get(name) {
+            Some((Value::Flag(value), _)) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Like `string`, failing when the property wasn't given
+    pub fn required_string(&self, name: &str) -> Result<String, Error> {
+        self.string(name).ok_or_else(|| Error::MissingProperty {
+            block: self.block.into(),
+            property: name.into(),
+            location: self.location,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parsing::token_stream::SourceLocation;
+
+    #[test]
+    pub fn gets_values_of_their_kind() {
+        let location = SourceLocationRange::new_single(SourceLocation::new(2, 4));
+        let value_location = SourceLocationRange::new_single(SourceLocation::new(3, 12));
+        let mut bag = PropertyBag::new("font", location);
+        bag.insert("name", Value::Name("sans".into()), value_location);
+        bag.insert("weight", Value::Integer(700), value_location);
+        bag.insert("italic", Value::Flag(true), value_location);
+
+        assert_eq!(Some("sans".into()), bag.string("name"));
+        assert_eq!(Some((700, value_location)), bag.integer("weight"));
+        assert_eq!(None, bag.integer("name"));
+        assert_eq!(Some(true), bag.flag("italic"));
+        assert_eq!(
+            Err(Error::MissingProperty {
+                block: "font".into(),
+                property: "path".into(),
+                location
+            }),
+            bag.required_string("path")
+        );
+    }
+}
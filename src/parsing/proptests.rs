@@ -0,0 +1,639 @@
+//! Property tests: random input must never make the tokenizer or the parser
+//! panic, and random presentations must come back unchanged from their DSL
+//! source and their JSON
+
+use crate::control::Action;
+use crate::easing::Easing;
+use crate::parsing::{formatter, json, markup};
+use crate::presentation::{
+    Align, Anchor, Animation, Color, Container, ControlInput, Fill, Font, GradientDirection,
+    Hinting, Image, ImageFit, Keyframe, Layout, LayoutItem, Length, List, Metadata, Motion,
+    Placement, Presentation, Resolution, Section, Side, Slide, SlideElement, Style, Text,
+    TextStyle, Transition, TransitionKind, Video,
+};
+use proptest::collection::{hash_map, vec};
+use proptest::option;
+use proptest::prelude::*;
+use proptest::sample::select;
+use std::time::Duration;
+
+/// Pieces of source the parser gives a meaning to, glued together at random
+/// so that the input gets past the tokenizer and deep into the parser
+const FRAGMENTS: &[&str] = &[
+    "metadata",
+    "style",
+    "slide",
+    "layout",
+    "section",
+    "define",
+    "include",
+    "title",
+    "subtitle",
+    "text",
+    "list",
+    "item",
+    "bullet",
+    "indent",
+    "image",
+    "video",
+    "row",
+    "column",
+    "step",
+    "placeholder",
+    "uses",
+    "hidden",
+    "notes",
+    "font",
+    "font-stack",
+    "text-style",
+    "name",
+    "path",
+    "weight",
+    "italic",
+    "system",
+    "size",
+    "color",
+    "background",
+    "gradient",
+    "transition",
+    "fade",
+    "none",
+    "duration",
+    "target",
+    "at",
+    "anchor",
+    "center",
+    "gap",
+    "align",
+    "grow",
+    "opacity",
+    "fade-in",
+    "slide-in-from",
+    "animate",
+    "ease-in-out",
+    "ease-out",
+    "cubic-bezier",
+    "spring",
+    "rotate",
+    "scale",
+    "skew",
+    "hinting",
+    "antialias",
+    "kerning",
+    "width",
+    "height",
+    "theme",
+    "control",
+    "osc",
+    "midi",
+    "next",
+    "show-progress",
+    "spelling-ignore",
+    "font-dir",
+    "{",
+    "}",
+    ",",
+    "\"x\"",
+    "\"*a* [color=#fff]b\"",
+    "10px",
+    "50%",
+    "1.5em",
+    "#ff0000",
+    "#00000080",
+    "12",
+    "-3",
+    "1.5",
+    "05:30",
+    "300ms",
+    "2s",
+    "true",
+    "false",
+    "$x",
+    "//",
+    "/*",
+    "\"",
+    "\\",
+];
+
+fn source() -> impl Strategy<Value = String> {
+    vec(
+        prop_oneof![
+            6 => select(FRAGMENTS).prop_map(String::from),
+            1 => "[a-z][a-z0-9-]{0,6}",
+            1 => "\\PC{0,4}",
+        ],
+        0..80,
+    )
+    .prop_map(|fragments| fragments.join(" "))
+}
+
+/// Strings with quotes, backslashes and line breaks, which need escaping
+fn string() -> impl Strategy<Value = String> {
+    "(\\PC|[\"\\\\\n\t]){0,12}"
+}
+
+/// A name that is never a keyword
+fn name(prefix: &'static str) -> impl Strategy<Value = String> {
+    "[a-z0-9]{0,4}".prop_map(move |suffix| format!("{}{}", prefix, suffix))
+}
+
+fn length() -> impl Strategy<Value = Length> {
+    (0..4000_i32, 0..3_u8).prop_map(|(halves, unit)| {
+        let value = f64::from(halves) / 2.0;
+        match unit {
+            0 => Length::Pixels(value),
+            1 => Length::Percent(value),
+            _ => Length::Em(value),
+        }
+    })
+}
+
+fn color() -> impl Strategy<Value = Color> {
+    any::<(u8, u8, u8, u8)>()
+        .prop_map(|(red, green, blue, alpha)| Color::new(red, green, blue, alpha))
+}
+
+fn fill() -> impl Strategy<Value = Fill> {
+    let directions = vec![
+        GradientDirection::Horizontal,
+        GradientDirection::Vertical,
+        GradientDirection::Diagonal,
+        GradientDirection::Radial,
+    ];
+    let fits = vec![ImageFit::Cover, ImageFit::Contain, ImageFit::Tile];
+
+    prop_oneof![
+        color().prop_map(Fill::Color),
+        (color(), color(), select(directions)).prop_map(|(from, to, direction)| {
+            Fill::Gradient {
+                from,
+                to,
+                direction,
+            }
+        }),
+        (string(), select(fits)).prop_map(|(path, fit)| Fill::Image { path, fit }),
+    ]
+}
+
+fn transition() -> impl Strategy<Value = Transition> {
+    let kinds = vec![
+        TransitionKind::None,
+        TransitionKind::Fade,
+        TransitionKind::Slide,
+    ];
+
+    (select(kinds), 0..3000_u64, easing()).prop_map(|(kind, milliseconds, easing)| {
+        let mut transition = Transition::new(kind, Duration::from_millis(milliseconds));
+        transition.set_easing(easing);
+        transition
+    })
+}
+
+/// Numbers are whole quarters, which are written exactly
+fn easing() -> impl Strategy<Value = Easing> {
+    let quarter =
+        |range: std::ops::Range<i32>| range.prop_map(|quarters| f64::from(quarters) / 4.0);
+
+    prop_oneof![
+        Just(Easing::Linear),
+        Just(Easing::EaseIn),
+        Just(Easing::EaseOut),
+        Just(Easing::EaseInOut),
+        (quarter(0..5), quarter(-4..9), quarter(0..5), quarter(-4..9))
+            .prop_map(|(x1, y1, x2, y2)| Easing::CubicBezier(x1, y1, x2, y2)),
+        (quarter(4..2000), quarter(0..200))
+            .prop_map(|(stiffness, damping)| Easing::Spring { stiffness, damping }),
+    ]
+}
+
+fn control() -> impl Strategy<Value = ControlInput> {
+    let actions = vec![
+        Action::Next,
+        Action::Previous,
+        Action::First,
+        Action::Last,
+        Action::TogglePointer,
+        Action::ToggleTimer,
+        Action::TogglePlayback,
+    ];
+
+    prop_oneof![
+        (
+            1..=u16::MAX,
+            hash_map("/[a-z]{1,5}", select(actions.clone()), 0..3)
+        )
+            .prop_map(|(port, addresses)| ControlInput::Osc { port, addresses }),
+        (string(), hash_map(0..128_u8, select(actions), 0..3))
+            .prop_map(|(device, notes)| ControlInput::Midi { device, notes }),
+    ]
+}
+
+// no theme, parsing the source would load it
+prop_compose! {
+    fn metadata()(
+        title in string(),
+        (width, height) in (1..4000_u32, 1..4000_u32),
+        transition in option::of(transition()),
+        controls in vec(control(), 0..2),
+        flags in any::<(bool, bool, bool)>(),
+        spelling_ignore in vec("[a-z]{1,8}", 0..3),
+        font_dirs in vec("[a-z]{1,8}(/[a-z]{1,8})?", 0..2),
+    ) -> Metadata {
+        let mut metadata = Metadata::new(title, Resolution::new(width, height));
+        if let Some(transition) = transition {
+            metadata.set_transition(transition);
+        }
+        for control in controls {
+            metadata.add_control(control);
+        }
+        metadata.set_show_progress(flags.0);
+        metadata.set_show_page_numbers(flags.1);
+        metadata.set_show_footer(flags.2);
+        for word in spelling_ignore {
+            metadata.add_spelling_ignore(word);
+        }
+        for font_dir in font_dirs {
+            metadata.add_font_dir(font_dir);
+        }
+
+        metadata
+    }
+}
+
+fn font() -> impl Strategy<Value = Font> {
+    (
+        name("fnt"),
+        option::of(string()),
+        1..1000_u32,
+        any::<bool>(),
+        option::of(1..200_u32),
+    )
+        .prop_map(|(name, path, weight, italic, size)| {
+            let mut font = match path {
+                Some(path) => Font::new(name, path, weight, italic),
+                None => Font::system(name, weight, italic),
+            };
+            if let Some(size) = size {
+                font.set_size(size);
+            }
+            font
+        })
+}
+
+prop_compose! {
+    fn text_style()(
+        font in option::of(name("fnt")),
+        fallback_fonts in vec(name("fnt"), 0..3),
+        size in option::of(1..200_u32),
+        color in option::of(color()),
+        weight in option::of(1..1000_u32),
+        italic in option::of(any::<bool>()),
+    ) -> TextStyle {
+        let has_font = font.is_some();
+        let mut text_style = TextStyle::new(font, size, color);
+        // fallbacks are only written after a font
+        if has_font {
+            text_style.set_fallback_fonts(fallback_fonts);
+        }
+        if let Some(weight) = weight {
+            text_style.set_weight(weight);
+        }
+        if let Some(italic) = italic {
+            text_style.set_italic(italic);
+        }
+
+        text_style
+    }
+}
+
+/// Settings are only set when they aren't the default, as the source
+/// leaves those out
+fn style() -> impl Strategy<Value = Style> {
+    let hintings = vec![
+        Hinting::Normal,
+        Hinting::Light,
+        Hinting::Mono,
+        Hinting::None,
+    ];
+
+    (
+        vec(font(), 0..3),
+        option::of(fill()),
+        option::of(color()),
+        select(hintings),
+        any::<(bool, bool)>(),
+        hash_map(name("ts"), text_style(), 0..3),
+    )
+        .prop_filter_map(
+            "fonts must differ",
+            |(fonts, background, text_color, hinting, (antialias, kerning), text_styles)| {
+                let mut style = Style::new(fonts).ok()?;
+                if let Some(background) = background {
+                    style.set_background(background);
+                }
+                if let Some(text_color) = text_color {
+                    style.set_text_color(text_color);
+                }
+                if hinting != Hinting::Normal {
+                    style.set_hinting(hinting);
+                }
+                if !antialias {
+                    style.set_antialias(false);
+                }
+                if !kerning {
+                    style.set_kerning(false);
+                }
+                for (name, text_style) in text_styles {
+                    style.add_text_style(name, text_style).ok()?;
+                }
+                Some(style)
+            },
+        )
+}
+
+/// Text whose spans come from markup, as spans the markup can't express
+/// don't survive being written as source
+fn text() -> impl Strategy<Value = Text> {
+    let word = (
+        "[a-zA-Z0-9]{1,6}",
+        any::<(bool, bool)>(),
+        option::of(color()),
+    );
+    let words = vec(word, 1..4).prop_map(|words| {
+        words
+            .into_iter()
+            .map(|(word, (bold, italic), color)| {
+                let marker = match (bold, italic) {
+                    (true, true) => "*_",
+                    (true, false) => "*",
+                    (false, true) => "_",
+                    (false, false) => "",
+                };
+                let closing = marker.chars().rev().collect::<String>();
+                let word = format!("{}{}{}", marker, word, closing);
+                match color {
+                    Some(color) => format!(
+                        "[color={}]{}[/color]",
+                        crate::parsing::serializer::color(color),
+                        word
+                    ),
+                    None => word,
+                }
+            })
+            .collect::<Vec<String>>()
+            .join(" ")
+    });
+
+    (words, option::of(name("ts")), option::of(1..200_u32)).prop_map(|(words, style, size)| {
+        let mut text = Text::with_spans(markup::parse(&words).unwrap(), style);
+        if let Some(size) = size {
+            text.set_size(size);
+        }
+        text
+    })
+}
+
+fn placement() -> impl Strategy<Value = Placement> {
+    let anchors = vec![
+        Anchor::TopLeft,
+        Anchor::Top,
+        Anchor::TopRight,
+        Anchor::Left,
+        Anchor::Center,
+        Anchor::Right,
+        Anchor::BottomLeft,
+        Anchor::Bottom,
+        Anchor::BottomRight,
+    ];
+
+    (
+        option::of((length(), length())),
+        option::of(select(anchors)),
+        (option::of(length()), option::of(length())),
+        0..4_u32,
+    )
+        .prop_map(|(position, anchor, (width, height), grow)| {
+            let mut placement = Placement::default();
+            if let Some((x, y)) = position {
+                placement.set_position(x, y);
+            }
+            if let Some(anchor) = anchor {
+                placement.set_anchor(anchor);
+            }
+            placement.set_size(width, height);
+            placement.set_grow(grow);
+            placement
+        })
+}
+
+/// Durations are whole milliseconds, which is what the DSL writes
+fn animation() -> impl Strategy<Value = Animation> {
+    let sides = vec![Side::Left, Side::Right, Side::Top, Side::Bottom];
+
+    (
+        0..=100_u32,
+        (-720..720_i32, 0..20_i32, (-180..180_i32, -180..180_i32)),
+        option::of(0..5000_u64),
+        option::of((select(sides), 0..5000_u64)),
+        easing(),
+        vec(keyframe(), 0..3),
+    )
+        .prop_map(
+            |(opacity, (rotation, scale, skew), fade_in, slide_in, easing, keyframes)| {
+                let mut animation = Animation::default();
+                animation.set_opacity(opacity);
+                animation.set_rotation(f64::from(rotation) / 2.0);
+                animation.set_scale(f64::from(scale) / 2.0);
+                animation.set_skew(f64::from(skew.0) / 2.0, f64::from(skew.1) / 2.0);
+                if let Some(milliseconds) = fade_in {
+                    animation.set_fade_in(Duration::from_millis(milliseconds));
+                }
+                if let Some((side, milliseconds)) = slide_in {
+                    animation.set_slide_in(side, Duration::from_millis(milliseconds));
+                }
+                // the easing is written after coming in, so there is none without it
+                if fade_in.is_some() || slide_in.is_some() {
+                    animation.set_easing(easing);
+                }
+                animation.set_keyframes(keyframes);
+                animation
+            },
+        )
+}
+
+fn keyframe() -> impl Strategy<Value = Keyframe> {
+    let motion = prop_oneof![
+        length().prop_map(Motion::X),
+        length().prop_map(Motion::Y),
+        (0..20_i32).prop_map(|halves| Motion::Scale(f64::from(halves) / 2.0)),
+        (-720..720_i32).prop_map(|halves| Motion::Rotate(f64::from(halves) / 2.0)),
+        (0..=100_u32).prop_map(Motion::Opacity),
+    ];
+
+    (0..5000_u64, vec(motion, 0..3), easing()).prop_map(|(milliseconds, motions, easing)| {
+        Keyframe::new(Duration::from_millis(milliseconds), motions, easing)
+    })
+}
+
+fn container(element: BoxedStrategy<SlideElement>) -> impl Strategy<Value = Container> {
+    let aligns = vec![Align::Start, Align::Center, Align::End];
+
+    (
+        vec(element, 0..3),
+        length(),
+        select(aligns),
+        option::of(fill()),
+    )
+        .prop_map(|(elements, gap, align, background)| {
+            let mut container = Container::new(elements);
+            container.set_gap(gap);
+            container.set_align(align);
+            if let Some(background) = background {
+                container.set_background(background);
+            }
+            container
+        })
+}
+
+fn element() -> BoxedStrategy<SlideElement> {
+    let leaf = prop_oneof![
+        text().prop_map(SlideElement::Text),
+        text().prop_map(SlideElement::Title),
+        text().prop_map(SlideElement::Subtitle),
+        (vec(string(), 0..3), string(), length()).prop_map(|(items, bullet, indent)| {
+            SlideElement::List(List::new(items, bullet, indent))
+        }),
+        string().prop_map(|path| SlideElement::Image(Image::new(path))),
+        string().prop_map(|path| SlideElement::Video(Video::new(path))),
+    ];
+    let element = leaf.prop_recursive(2, 8, 3, |element| {
+        prop_oneof![
+            container(element.clone()).prop_map(SlideElement::Row),
+            container(element).prop_map(SlideElement::Column),
+        ]
+    });
+
+    (element, placement(), animation())
+        .prop_map(|(mut element, placement, animation)| {
+            element.set_placement(placement);
+            element.set_animation(animation);
+            element
+        })
+        .boxed()
+}
+
+prop_compose! {
+    fn slide()(
+        name in string(),
+        layout in option::of(name("ly")),
+        hidden in any::<bool>(),
+        background in option::of(fill()),
+        transition in option::of(transition()),
+        duration in option::of(0..600_u64),
+        target in option::of(0..600_u64),
+        scheduled_at in option::of(0..36_000_u64),
+        elements in vec((element(), any::<bool>(), option::of(name("ph"))), 0..5),
+        notes in vec(string(), 0..2),
+    ) -> Slide {
+        let mut slide = Slide::new(name, vec![]);
+        if let Some(layout) = layout {
+            slide.set_layout(layout);
+        }
+        slide.set_hidden(hidden);
+        if let Some(seconds) = scheduled_at {
+            slide.set_scheduled_at(Duration::from_secs(seconds));
+        }
+        if let Some(background) = background {
+            slide.set_background(background);
+        }
+        if let Some(transition) = transition {
+            slide.set_transition(transition);
+        }
+        if let Some(seconds) = duration {
+            slide.set_duration(Duration::from_secs(seconds));
+        }
+        if let Some(seconds) = target {
+            slide.set_target(Duration::from_secs(seconds));
+        }
+        // steps are counted up one `step` block at a time
+        let mut step = 0;
+        for (element, is_next_step, placeholder) in elements {
+            if is_next_step {
+                step += 1;
+            }
+            slide.push_element_in_placeholder(element, step, placeholder);
+        }
+        for note in notes {
+            slide.add_note(note);
+        }
+
+        slide
+    }
+}
+
+fn layout() -> impl Strategy<Value = Layout> {
+    let item = prop_oneof![
+        element().prop_map(|element| LayoutItem::Element(Box::new(element))),
+        name("ph").prop_map(LayoutItem::Placeholder),
+    ];
+
+    vec(item, 0..3).prop_map(Layout::new)
+}
+
+prop_compose! {
+    fn presentation()(
+        metadata in metadata(),
+        slides in vec(slide(), 0..4),
+        style in style(),
+        layouts in hash_map(name("ly"), layout(), 0..2),
+        mut sections in vec((string(), 0..5_usize), 0..3),
+    ) -> Presentation {
+        let slide_count = slides.len();
+        let mut presentation = Presentation::new(metadata, slides, style);
+        for (name, layout) in layouts {
+            presentation.add_layout(name, layout);
+        }
+        // sections come in the order of the slides they start at
+        sections.sort_by_key(|(_, first_slide)| *first_slide);
+        for (title, first_slide) in sections {
+            presentation.add_section(Section::new(title, first_slide.min(slide_count)));
+        }
+
+        presentation
+    }
+}
+
+proptest! {
+    #[test]
+    fn tokenizer_never_panics(input in any::<String>()) {
+        for _ in crate::tokenize(&input) {}
+        crate::classify(&input);
+    }
+
+    #[test]
+    fn parser_never_panics(input in source()) {
+        let _ = crate::parse(&input);
+        let _ = formatter::format(&input);
+        crate::classify(&input);
+    }
+
+    #[test]
+    fn json_and_markup_readers_never_panic(input in any::<String>()) {
+        let _ = json::parse(&input);
+        let _ = markup::parse(&input);
+    }
+
+    #[test]
+    fn presentations_round_trip_through_source(presentation in presentation()) {
+        let source = presentation.to_source();
+
+        prop_assert_eq!(Ok(presentation), crate::parse(&source), "{}", source);
+    }
+
+    #[test]
+    fn presentations_round_trip_through_json(presentation in presentation()) {
+        let written = json::to_json(&presentation).pretty();
+
+        prop_assert_eq!(Ok(presentation), json::parse(&written), "{}", written);
+    }
+}
@@ -0,0 +1,776 @@
+use crate::easing::Easing;
+use crate::presentation::{
+    Animation, Color, Container, ControlInput, Fill, Font, FontSource, Hinting, Keyframe, Layout,
+    LayoutItem, Length, List, Metadata, Motion, Placement, Presentation, Slide, SlideElement, Span,
+    Style, Text, TextStyle, Transition, TransitionKind,
+};
+use std::fmt::Write;
+use std::ops::Range;
+use std::time::Duration;
+
+const INDENT: &str = "    ";
+/// What lists are written with when they don't say otherwise
+const DEFAULT_BULLET: &str = "•";
+const DEFAULT_INDENT: Length = Length::Pixels(40.0);
+
+/// Writes the whole presentation as DSL source, laid out the way `fmt` lays
+/// it out. Parsing the source gives the presentation back, except for
+/// settings given their default value explicitly, which are left out.
+pub fn to_source(presentation: &Presentation) -> String {
+    let mut blocks = vec![metadata_source(presentation.metadata())];
+    if has_settings(presentation.style()) {
+        blocks.push(style_source(presentation.style()));
+    }
+
+    let mut layout_names = presentation.layout_names();
+    layout_names.sort();
+    blocks.extend(layout_names.into_iter().filter_map(|name| {
+        presentation
+            .layout(name)
+            .map(|layout| layout_source(name, layout))
+    }));
+
+    let sections_before = |slide: usize| {
+        presentation
+            .sections()
+            .iter()
+            .filter(|section| section.first_slide() == slide)
+            .map(|section| format!("section {}\n", string(section.title())))
+            .collect::<Vec<String>>()
+            .concat()
+    };
+    let slide_count = presentation.slides().len();
+    blocks.extend(
+        presentation
+            .slides()
+            .iter()
+            .enumerate()
+            .map(|(index, slide)| format!("{}{}", sections_before(index), slide_source(slide))),
+    );
+    // sections with no slides after them
+    blocks.extend(
+        presentation
+            .sections()
+            .iter()
+            .filter(|section| section.first_slide() >= slide_count)
+            .map(|section| format!("section {}\n", string(section.title()))),
+    );
+
+    blocks.join("\n")
+}
+
+fn metadata_source(metadata: &Metadata) -> String {
+    let resolution = metadata.resolution();
+    let mut properties = vec![
+        vec![format!("title {}", string(metadata.title()))],
+        vec![format!("width {}", resolution.width())],
+        vec![format!("height {}", resolution.height())],
+    ];
+    if let Some(value) = metadata.transition() {
+        properties.push(vec![format!("transition {}", transition(value))]);
+    }
+    if let Some(theme) = metadata.theme() {
+        properties.push(vec![format!("theme {}", string(theme))]);
+    }
+    properties.extend(metadata.controls().iter().map(control_lines));
+    let flags = [
+        ("show-progress", metadata.show_progress()),
+        ("show-page-numbers", metadata.show_page_numbers()),
+        ("show-footer", metadata.show_footer()),
+    ];
+    properties.extend(
+        flags
+            .iter()
+            .filter(|(_, is_set)| *is_set)
+            .map(|(name, _)| vec![(*name).to_string()]),
+    );
+    if !metadata.spelling_ignore().is_empty() {
+        properties.push(vec![format!(
+            "spelling-ignore {}",
+            string(&metadata.spelling_ignore().join(" "))
+        )]);
+    }
+    properties.extend(
+        metadata
+            .font_dirs()
+            .iter()
+            .map(|font_dir| vec![format!("font-dir {}", string(font_dir))]),
+    );
+
+    block("metadata {", &separated_groups(properties), "}")
+}
+
+/// `hh:mm:ss`, the hours are not wrapped into days
+fn time(time: Duration) -> String {
+    let seconds = time.as_secs();
+
+    format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+/// `300ms`, fractions of milliseconds are dropped
+fn milliseconds(duration: Duration) -> String {
+    format!("{}ms", duration.as_millis())
+}
+
+/// `fade 300 ease-out`, or `none` for transitions without a duration
+fn transition(transition: Transition) -> String {
+    let milliseconds = transition.duration().as_millis();
+    if transition.kind() == TransitionKind::None
+        && milliseconds == 0
+        && transition.easing() == Easing::Linear
+    {
+        return transition.kind().name().into();
+    }
+
+    let mut source = format!("{} {}", transition.kind().name(), milliseconds);
+    if transition.easing() != Easing::Linear {
+        write!(source, " {}", easing(transition.easing())).expect("Failed to write to a string");
+    }
+
+    source
+}
+
+/// `ease-out`, `cubic-bezier 0.3 0 0.2 1` or `spring 200 15`
+fn easing(easing: Easing) -> String {
+    match easing {
+        Easing::CubicBezier(x1, y1, x2, y2) => {
+            format!("{} {} {} {} {}", easing.name(), x1, y1, x2, y2)
+        }
+        Easing::Spring { stiffness, damping } => {
+            format!("{} {} {}", easing.name(), stiffness, damping)
+        }
+        _ => easing.name().into(),
+    }
+}
+
+/// The input with its bindings in braces, sorted so that the source is
+/// always the same
+fn control_lines(control: &ControlInput) -> Vec<String> {
+    let (input, bindings) = match control {
+        ControlInput::Osc { port, addresses } => {
+            let mut addresses = addresses.iter().collect::<Vec<_>>();
+            addresses.sort_by_key(|(address, _)| *address);
+            (
+                format!("control osc {}", port),
+                addresses
+                    .into_iter()
+                    .map(|(address, action)| format!("{} {}", string(address), action.name()))
+                    .collect::<Vec<String>>(),
+            )
+        }
+        ControlInput::Midi { device, notes } => {
+            let mut notes = notes.iter().collect::<Vec<_>>();
+            notes.sort_by_key(|(note, _)| *note);
+            (
+                format!("control midi {}", string(device)),
+                notes
+                    .into_iter()
+                    .map(|(note, action)| format!("{} {}", note, action.name()))
+                    .collect(),
+            )
+        }
+    };
+
+    if bindings.is_empty() {
+        vec![input]
+    } else {
+        nested(&format!("{} {{", input), separated(bindings))
+    }
+}
+
+/// Whether the style sets anything, a style that doesn't is left out
+fn has_settings(style: &Style) -> bool {
+    !style.fonts().is_empty()
+        || style.background().is_some()
+        || style.text_color().is_some()
+        || style.hinting() != Hinting::Normal
+        || !style.antialias()
+        || !style.kerning()
+        || !style.text_style_names().is_empty()
+}
+
+/// Writes the style as a `style` block. Its default font comes first, the
+/// other fonts and the text styles follow sorted by name.
+pub fn style_source(style: &Style) -> String {
+    let default_font = style.default_font();
+    let mut fonts = style
+        .fonts()
+        .into_iter()
+        .filter(|font| Some(*font) != default_font)
+        .collect::<Vec<&Font>>();
+    fonts.sort_by_key(|font| (font.name(), font.weight(), font.italic()));
+
+    let mut lines = default_font
+        .into_iter()
+        .chain(fonts)
+        .flat_map(font_lines)
+        .collect::<Vec<String>>();
+    if let Some(background) = style.background() {
+        lines.push(format!("background {}", fill(background)));
+    }
+    if let Some(value) = style.text_color() {
+        lines.push(format!("color {}", color(value)));
+    }
+    if style.hinting() != Hinting::Normal {
+        lines.push(format!("hinting {}", style.hinting().name()));
+    }
+    if !style.antialias() {
+        lines.push("antialias false".into());
+    }
+    if !style.kerning() {
+        lines.push("kerning false".into());
+    }
+
+    let mut names = style.text_style_names();
+    names.sort();
+    for name in names {
+        if let Some(text_style) = style.text_style(name) {
+            lines.extend(text_style_lines(name, text_style));
+        }
+    }
+
+    block("style {", &lines, "}")
+}
+
+/// The font's properties in a `font` block, the bundled font can't be
+/// declared and has none
+fn font_lines(font: &Font) -> Vec<String> {
+    let mut properties = vec![format!("name {}", font.name())];
+    match font.source() {
+        FontSource::File(path) => properties.push(format!("path {}", string(path))),
+        FontSource::System => {}
+        FontSource::Bundled => return vec![],
+    }
+    properties.push(format!("weight {}", font.weight()));
+    if font.italic() {
+        properties.push("italic".into());
+    }
+    if let Some(size) = font.size() {
+        properties.push(format!("size {}", size));
+    }
+    if let FontSource::System = font.source() {
+        properties.push("system".into());
+    }
+
+    nested("font {", separated(properties))
+}
+
+fn text_style_lines(name: &str, text_style: &TextStyle) -> Vec<String> {
+    let mut properties = vec![];
+    if let Some(font) = text_style.font() {
+        if text_style.fallback_fonts().is_empty() {
+            properties.push(format!("font {}", font));
+        } else {
+            let stack = std::iter::once(font)
+                .chain(text_style.fallback_fonts())
+                .map(String::as_str)
+                .collect::<Vec<&str>>();
+            properties.push(format!("font-stack {}", stack.join(", ")));
+        }
+    }
+    if let Some(size) = text_style.size() {
+        properties.push(format!("size {}", size));
+    }
+    if let Some(value) = text_style.color() {
+        properties.push(format!("color {}", color(value)));
+    }
+    if let Some(weight) = text_style.weight() {
+        properties.push(format!("weight {}", weight));
+    }
+    if let Some(italic) = text_style.italic() {
+        properties.push(format!("italic {}", italic));
+    }
+
+    nested(&format!("text-style {} {{", name), separated(properties))
+}
+
+fn layout_source(name: &str, layout: &Layout) -> String {
+    let lines = layout
+        .items()
+        .iter()
+        .flat_map(|item| match item {
+            LayoutItem::Element(element) => element_lines(element),
+            LayoutItem::Placeholder(placeholder) => vec![format!("placeholder {}", placeholder)],
+        })
+        .collect::<Vec<String>>();
+
+    block(&format!("layout {} {{", name), &lines, "}")
+}
+
+/// Writes the slide as a `slide` block, with its elements grouped into the
+/// steps and the placeholders they are in
+pub fn slide_source(slide: &Slide) -> String {
+    let mut opening = format!("slide {}", string(slide.name()));
+    if let Some(layout) = slide.layout() {
+        opening.push_str(" uses ");
+        opening.push_str(layout);
+    }
+    if slide.is_hidden() {
+        opening.push_str(" hidden");
+    }
+    if let Some(scheduled_at) = slide.scheduled_at() {
+        opening.push_str(" at ");
+        opening.push_str(&time(scheduled_at));
+    }
+    opening.push_str(" {");
+
+    let mut lines = vec![];
+    if let Some(background) = slide.background() {
+        lines.push(format!("background {}", fill(background)));
+    }
+    if let Some(value) = slide.transition() {
+        lines.push(format!("transition {}", transition(value)));
+    }
+    if let Some(duration) = slide.duration() {
+        lines.push(format!("duration {}", duration.as_secs()));
+    }
+    if let Some(target) = slide.target() {
+        lines.push(format!("target {}", target.as_secs()));
+    }
+    for steps in runs(0..slide.elements().len(), |index| slide.step_of(index)) {
+        let step_lines = runs(steps.clone(), |index| slide.placeholder_of(index))
+            .into_iter()
+            .flat_map(|placeholders| {
+                let element_lines = slide.elements()[placeholders.clone()]
+                    .iter()
+                    .flat_map(element_lines)
+                    .collect();
+                match slide.placeholder_of(placeholders.start) {
+                    Some(name) => nested(&format!("{} {{", name), element_lines),
+                    None => element_lines,
+                }
+            })
+            .collect::<Vec<String>>();
+
+        if slide.step_of(steps.start) == 0 {
+            lines.extend(step_lines);
+        } else {
+            lines.extend(nested("step {", step_lines));
+        }
+    }
+    lines.extend(
+        slide
+            .notes()
+            .iter()
+            .map(|note| format!("notes {}", string(note))),
+    );
+
+    block(&opening, &lines, "}")
+}
+
+/// Splits the range where the key changes
+fn runs<K: PartialEq>(range: Range<usize>, key: impl Fn(usize) -> K) -> Vec<Range<usize>> {
+    let mut runs: Vec<Range<usize>> = vec![];
+    for index in range {
+        match runs.last_mut() {
+            Some(run) if key(run.start) == key(index) => run.end = index + 1,
+            _ => runs.push(index..index + 1),
+        }
+    }
+
+    runs
+}
+
+/// The lines between the opening and the closing one, indented
+fn block(opening: &str, lines: &[String], closing: &str) -> String {
+    if lines.is_empty() {
+        return format!("{}{}\n", opening, closing);
+    }
+
+    let mut source = vec![format!("{}\n", opening)];
+    source.extend(lines.iter().map(|line| format!("{}{}\n", INDENT, line)));
+    source.push(format!("{}\n", closing));
+
+    source.concat()
+}
+
+fn element_lines(element: &SlideElement) -> Vec<String> {
+    let lines = match element {
+        SlideElement::Text(text) => vec![format!("text {}", text_source(text))],
+        SlideElement::Title(text) => vec![format!("title {}", text_source(text))],
+        SlideElement::Subtitle(text) => vec![format!("subtitle {}", text_source(text))],
+        SlideElement::List(list) => list_lines(list),
+        SlideElement::Image(image) => vec![format!("image {}", string(image.path()))],
+        SlideElement::Video(video) => vec![format!("video {}", string(video.path()))],
+        SlideElement::Row(container) => container_lines("row", container),
+        SlideElement::Column(container) => container_lines("column", container),
+    };
+
+    with_animation(
+        with_placement(lines, element.placement()),
+        element.animation(),
+    )
+}
+
+/// Appends the placement to the last line, which ends the element
+fn with_placement(mut lines: Vec<String>, placement: &Placement) -> Vec<String> {
+    let mut properties = vec![];
+    if let Some((x, y)) = placement.position() {
+        properties.push(format!(" at {} {}", length(x), length(y)));
+    }
+    if let Some(anchor) = placement.anchor() {
+        properties.push(format!(" anchor {}", anchor.name()));
+    }
+    if let (Some(_), _) | (_, Some(_)) = placement.size() {
+        let extent = |extent: Option<Length>| extent.map_or_else(|| "auto".into(), length);
+        let (width, height) = placement.size();
+        properties.push(format!(" size {} {}", extent(width), extent(height)));
+    }
+    if placement.grow() > 0 {
+        properties.push(format!(" grow {}", placement.grow()));
+    }
+
+    if let Some(last) = lines.last_mut() {
+        last.push_str(&properties.concat());
+    }
+
+    lines
+}
+
+/// Appends the animation to the last line, after the placement
+fn with_animation(mut lines: Vec<String>, animation: &Animation) -> Vec<String> {
+    let mut properties = vec![];
+    if animation.opacity() != 100 {
+        properties.push(format!(" opacity {}%", animation.opacity()));
+    }
+    if animation.rotation() != 0.0 {
+        properties.push(format!(" rotate {}", animation.rotation()));
+    }
+    if (animation.scale() - 1.0).abs() > f64::EPSILON {
+        properties.push(format!(" scale {}", animation.scale()));
+    }
+    match animation.skew() {
+        (x, y) if y != 0.0 => properties.push(format!(" skew {} {}", x, y)),
+        (x, _) if x != 0.0 => properties.push(format!(" skew {}", x)),
+        _ => {}
+    }
+    if let Some(duration) = animation.fade_in() {
+        properties.push(format!(" fade-in {}", milliseconds(duration)));
+    }
+    if let Some((side, duration)) = animation.slide_in() {
+        properties.push(format!(
+            " slide-in-from {} {}",
+            side.name(),
+            milliseconds(duration)
+        ));
+    }
+    let comes_in = animation.fade_in().is_some() || animation.slide_in().is_some();
+    if comes_in && animation.easing() != Easing::Linear {
+        properties.push(format!(" {}", easing(animation.easing())));
+    }
+    if !animation.keyframes().is_empty() {
+        let keyframes = animation
+            .keyframes()
+            .iter()
+            .map(keyframe)
+            .collect::<Vec<String>>();
+        properties.push(format!(" animate {{ {} }}", keyframes.join(", ")));
+    }
+
+    if let Some(last) = lines.last_mut() {
+        last.push_str(&properties.concat());
+    }
+
+    lines
+}
+
+fn keyframe(keyframe: &Keyframe) -> String {
+    let mut parts = vec![format!("at {}", milliseconds(keyframe.at()))];
+    for motion in keyframe.motions() {
+        parts.push(match *motion {
+            Motion::X(value) | Motion::Y(value) => format!("{} {}", motion.name(), length(value)),
+            Motion::Scale(value) | Motion::Rotate(value) => format!("{} {}", motion.name(), value),
+            Motion::Opacity(value) => format!("opacity {}%", value),
+        });
+    }
+    if keyframe.easing() != Easing::Linear {
+        parts.push(easing(keyframe.easing()));
+    }
+
+    parts.join(" ")
+}
+
+fn text_source(text: &Text) -> String {
+    let mut source = vec![string(&markup(text.spans()))];
+    if let Some(style) = text.style() {
+        source.push(format!(" style {}", style));
+    }
+    if let Some(size) = text.size() {
+        source.push(format!(" size {}", size));
+    }
+
+    source.concat()
+}
+
+fn list_lines(list: &List) -> Vec<String> {
+    let mut items = list
+        .items()
+        .iter()
+        .map(|item| format!("item {}", string(item)))
+        .collect::<Vec<String>>();
+    if list.bullet() != DEFAULT_BULLET {
+        items.push(format!("bullet {}", string(list.bullet())));
+    }
+    if list.indent() != DEFAULT_INDENT {
+        items.push(format!("indent {}", length(list.indent())));
+    }
+
+    nested("list {", separated(items))
+}
+
+fn container_lines(keyword: &str, container: &Container) -> Vec<String> {
+    let children = container
+        .elements()
+        .iter()
+        .flat_map(element_lines)
+        .collect();
+    let mut lines = nested(&format!("{} {{", keyword), children);
+
+    let default = Container::new(vec![]);
+    let mut properties = vec![];
+    if container.gap() != default.gap() {
+        properties.push(format!(" gap {}", length(container.gap())));
+    }
+    if container.align() != default.align() {
+        properties.push(format!(" align {}", container.align().name()));
+    }
+    if let Some(background) = container.background() {
+        properties.push(format!(" background {}", fill(background)));
+    }
+    if let Some(last) = lines.last_mut() {
+        last.push_str(&properties.concat());
+    }
+
+    lines
+}
+
+/// The lines with commas between them, for blocks of properties
+fn separated(lines: Vec<String>) -> Vec<String> {
+    separated_groups(lines.into_iter().map(|line| vec![line]).collect())
+}
+
+/// Like `separated`, for properties written over several lines
+fn separated_groups(groups: Vec<Vec<String>>) -> Vec<String> {
+    let count = groups.len();
+
+    groups
+        .into_iter()
+        .enumerate()
+        .flat_map(|(index, mut lines)| {
+            if index + 1 < count {
+                if let Some(last) = lines.last_mut() {
+                    last.push(',');
+                }
+            }
+            lines
+        })
+        .collect()
+}
+
+/// The opening line, the lines indented and a closing brace
+fn nested(opening: &str, lines: Vec<String>) -> Vec<String> {
+    if lines.is_empty() {
+        return vec![format!("{}}}", opening)];
+    }
+
+    let mut nested = vec![opening.to_string()];
+    nested.extend(lines.into_iter().map(|line| format!("{}{}", INDENT, line)));
+    nested.push("}".into());
+
+    nested
+}
+
+fn fill(fill: &Fill) -> String {
+    match fill {
+        Fill::Color(value) => color(*value),
+        Fill::Gradient {
+            from,
+            to,
+            direction,
+        } => format!(
+            "gradient {} {} {}",
+            color(*from),
+            color(*to),
+            direction.name()
+        ),
+        Fill::Image { path, fit } => format!("image {} {}", string(path), fit.name()),
+    }
+}
+
+/// `#rrggbb`, with the alpha only when the color isn't opaque
+pub fn color(color: Color) -> String {
+    let alpha = if color.alpha() == 0xff {
+        String::new()
+    } else {
+        format!("{:02x}", color.alpha())
+    };
+
+    format!(
+        "#{:02x}{:02x}{:02x}{}",
+        color.red(),
+        color.green(),
+        color.blue(),
+        alpha
+    )
+}
+
+/// The length with its unit, like `10px` or `50%`
+pub fn length(length: Length) -> String {
+    match length {
+        Length::Pixels(value) => format!("{}px", value),
+        Length::Percent(value) => format!("{}%", value),
+        Length::Em(value) => format!("{}em", value),
+    }
+}
+
+/// A string token, with quotes, backslashes and line breaks escaped
+fn string(text: &str) -> String {
+    let mut source = String::with_capacity(text.len() + 2);
+    source.push('"');
+    for character in text.chars() {
+        match character {
+            '"' => source.push_str("\\\""),
+            '\\' => source.push_str("\\\\"),
+            '\n' => source.push_str("\\n"),
+            '\t' => source.push_str("\\t"),
+            _ => source.push(character),
+        }
+    }
+    source.push('"');
+
+    // doubled so that it isn't read as a variable
+    source.replace("${", "$${")
+}
+
+/// The spans as inline markup. Emphasis only starts and ends between
+/// words, so spans that would need it within a word lose it.
+fn markup(spans: &[Span]) -> String {
+    let mut pieces: Vec<String> = vec![];
+
+    for (index, span) in spans.iter().enumerate() {
+        let content = escape_markup(span.content());
+        let previous = pieces.last().and_then(|piece| piece.chars().last());
+        let next = spans.get(index + 1).and_then(|next| {
+            if next.color().is_some() {
+                Some('[')
+            } else {
+                next.content().chars().next()
+            }
+        });
+        let is_alphanumeric = |character: Option<char>| matches!(character, Some(character) if character.is_alphanumeric());
+        let trimmed = content.trim();
+
+        let marker = match (span.bold(), span.italic()) {
+            _ if trimmed.is_empty() => "",
+            (true, true) => "*_",
+            (true, false) => "*",
+            (false, true) => "_",
+            (false, false) => "",
+        };
+        let leading = &content[..content.len() - content.trim_start().len()];
+        let trailing = &content[leading.len() + trimmed.len()..];
+        let at_word_boundaries = (!leading.is_empty() || !is_alphanumeric(previous))
+            && (!trailing.is_empty() || !is_alphanumeric(next));
+
+        let mut piece = if marker.is_empty() || (!at_word_boundaries && span.color().is_none()) {
+            content.clone()
+        } else {
+            let closing = marker.chars().rev().collect::<String>();
+            format!("{}{}{}{}{}", leading, marker, trimmed, closing, trailing)
+        };
+        if let Some(value) = span.color() {
+            piece = format!("[color={}]{}[/color]", color(value), piece);
+        }
+        pieces.push(piece);
+    }
+
+    pieces.concat()
+}
+
+/// Makes the characters markup gives a meaning to literal
+fn escape_markup(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        if matches!(character, '\\' | '*' | '_' | '[') {
+            escaped.push('\\');
+        }
+        escaped.push(character);
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parsing::{formatter, markup};
+
+    #[test]
+    pub fn writes_spans_as_markup_that_parses_back() {
+        let spans =
+            markup::parse("plain *bold* _it_ [color=#ff0000]red *and bold*[/color] 2 \\* 3")
+                .unwrap();
+
+        assert_eq!(
+            "plain *bold* _it_ [color=#ff0000]red [/color][color=#ff0000]*and bold*[/color] 2 \\* 3",
+            markup(&spans)
+        );
+        assert_eq!(spans, markup::parse(&markup(&spans)).unwrap());
+    }
+
+    #[test]
+    pub fn drops_emphasis_within_words() {
+        let mut bold = Span::new("bold".into());
+        bold.set_bold(true);
+
+        assert_eq!("unbold", markup(&[Span::new("un".into()), bold]));
+    }
+
+    #[test]
+    pub fn doubles_dollars_that_would_start_a_variable() {
+        assert_eq!("\"$$${price} $5\"", string("$${price} $5"));
+    }
+
+    #[test]
+    pub fn round_trips_slides_through_the_parser() {
+        let source = "metadata {\n    title \"A \\\"talk\\\"\",\n    width 1024,\n    height 768\n}\n\n\
+             slide \"intro\" {\n    background gradient #000000 #202040 radial\n    \
+             title \"*Hello*\" size 40 at 10px 5% size 50% auto slide-in-from left 300ms spring 200 15\n    \
+             list {\n        item \"one\",\n        bullet \"-\"\n    } anchor center rotate -7.5 scale 0.5 skew 10 fade-in 1500ms\n    \
+             row {\n        image \"cat.png\" grow 1 opacity 50% \
+             animate { at 0ms x -10% scale 1.5 cubic-bezier 0.25 0 0.5 1, at 400ms x 0% rotate 90 opacity 80% }\n        video \"dog.mp4\"\n    } gap 1em align start\n    \
+             notes \"line\\nbreak\"\n}\n";
+        let presentation = crate::parse(source).unwrap();
+
+        assert_eq!(source, to_source(&presentation));
+    }
+
+    #[test]
+    pub fn round_trips_styles_layouts_and_steps_through_the_parser() {
+        let source = "metadata {\n    title \"Talk\",\n    width 800,\n    height 600,\n    \
+             transition slide 250 ease-out,\n    control osc 9000 {\n        \"/back\" previous,\n        \"/go\" next\n    },\n    \
+             control midi \"/dev/midi\",\n    show-progress,\n    show-footer,\n    \
+             spelling-ignore \"przntr osc\"\n}\n\n\
+             style {\n    font {\n        name sans,\n        path \"sans.ttf\",\n        weight 400\n    }\n    \
+             font {\n        name sans,\n        path \"sans-bold.ttf\",\n        weight 700,\n        italic\n    }\n    \
+             font {\n        name serif,\n        weight 400,\n        size 30,\n        system\n    }\n    \
+             background #ffffff\n    color #202020\n    hinting light\n    kerning false\n    \
+             text-style body {\n        font-stack sans, serif,\n        size 24\n    }\n    \
+             text-style quote {\n        font serif,\n        italic true\n    }\n}\n\n\
+             layout two-col {\n    title \"Header\"\n    placeholder left\n    placeholder right\n}\n\n\
+             section \"Start\"\nslide \"a\" uses two-col hidden at 00:05:30 {\n    transition none\n    duration 5\n    target 90\n    \
+             left {\n        text \"x\" style body\n    }\n    step {\n        right {\n            \
+             text \"y\"\n        }\n        text \"z\"\n    }\n    step {\n        text \"w\"\n    }\n}\n\n\
+             section \"End\"\nslide \"b\" {}\n";
+        let presentation = crate::parse(source).unwrap();
+        let written = to_source(&presentation);
+
+        assert_eq!(source, written);
+        assert_eq!(Ok(written.clone()), formatter::format(&written));
+    }
+}
@@ -1,3 +1,5 @@
+use crate::presentation::{Color, Length};
+use std::time::Duration;
 #[cfg(test)]
 use std::vec::Drain;
 
@@ -11,6 +13,16 @@ impl SourceLocation {
     pub fn new(line: u32, column: u32) -> Self {
         Self { line, column }
     }
+
+    /// Line number, counted from 0
+    pub fn line(self) -> u32 {
+        self.line
+    }
+
+    /// Column number, counted from 1
+    pub fn column(self) -> u32 {
+        self.column
+    }
 }
 
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
@@ -28,18 +40,59 @@ impl SourceLocationRange {
     pub fn new_single(single: SourceLocation) -> Self {
         Self(single, single)
     }
+
+    pub fn start(self) -> SourceLocation {
+        self.0
+    }
+
+    pub fn end(self) -> SourceLocation {
+        self.1
+    }
+}
+
+/// A decimal number, compared bit by bit so that tokens can stay `Eq`
+#[derive(Debug, Copy, Clone)]
+pub struct Float(f64);
+
+impl Float {
+    pub fn new(value: f64) -> Self {
+        Self(value)
+    }
+
+    pub fn value(self) -> f64 {
+        self.0
+    }
+}
+
+impl PartialEq for Float {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.to_bits() == other.0.to_bits()
+    }
 }
 
+impl Eq for Float {}
+
 #[derive(Eq, PartialEq, Debug)]
 pub enum Token {
     Name(String),
     String(String),
     Integer(i128),
+    Float(Float),
+    Length(Length),
+    Boolean(bool),
+    Color(Color),
+    /// Time into the talk, written as `mm:ss` or `hh:mm:ss`
+    Time(Duration),
+    /// Length of time with a unit, `300ms` or `2s`
+    Duration(Duration),
     OpeningBrace,
     ClosingBrace,
     Comma,
+    /// Only returned when the tokenizer is asked to keep comments
+    Comment(String),
     KeywordSlide,
     KeywordTitle,
+    KeywordSubtitle,
     KeywordMetadata,
     KeywordStyle,
     KeywordFont,
@@ -47,6 +100,57 @@ pub enum Token {
     KeywordName,
     KeywordWeight,
     KeywordItalic,
+    KeywordText,
+    KeywordList,
+    KeywordItem,
+    KeywordBullet,
+    KeywordIndent,
+    KeywordWidth,
+    KeywordHeight,
+    KeywordBackground,
+    KeywordColor,
+    KeywordTextStyle,
+    KeywordSize,
+    KeywordSystem,
+    KeywordImage,
+    KeywordVideo,
+    KeywordNotes,
+    KeywordTransition,
+    KeywordStep,
+    KeywordInclude,
+    KeywordLayout,
+    KeywordUses,
+    KeywordPlaceholder,
+    KeywordDefine,
+    KeywordDuration,
+    KeywordAt,
+    KeywordAnchor,
+    KeywordRow,
+    KeywordColumn,
+    KeywordGap,
+    KeywordAlign,
+    KeywordGrow,
+    KeywordHinting,
+    KeywordAntialias,
+    KeywordKerning,
+    KeywordFontStack,
+    KeywordTheme,
+    KeywordControl,
+    KeywordShowProgress,
+    KeywordShowPageNumbers,
+    KeywordShowFooter,
+    KeywordHidden,
+    KeywordSection,
+    KeywordSpellingIgnore,
+    KeywordFontDir,
+    KeywordTarget,
+    KeywordOpacity,
+    KeywordFadeIn,
+    KeywordSlideInFrom,
+    KeywordAnimate,
+    KeywordRotate,
+    KeywordScale,
+    KeywordSkew,
 }
 
 #[derive(Debug, Eq, PartialEq)]
@@ -64,10 +168,16 @@ pub trait TokenStream {
 pub enum TokenizerFailureKind {
     UnexpectedCharacterInName { index: usize, character: char },
     UnclosedString,
+    UnclosedComment,
     UnknownEscapeSequence(char),
+    InvalidUnicodeEscape(String),
     UnfinishedEscapeSequence,
     UnexpectedCharacter(char),
     InvalidIntegerValue(String),
+    InvalidFloatValue(String),
+    UnknownUnit(String),
+    InvalidColorLiteral(String),
+    InvalidTime(String),
 }
 
 #[derive(Debug, Eq, PartialEq, Clone)]
@@ -80,11 +190,20 @@ impl TokenizerFailure {
     pub fn new(location: SourceLocationRange, kind: TokenizerFailureKind) -> Self {
         Self { location, kind }
     }
+
+    pub fn kind(&self) -> &TokenizerFailureKind {
+        &self.kind
+    }
+
+    pub fn location(&self) -> SourceLocationRange {
+        self.location
+    }
 }
 
 pub struct Peekable<'a, T: TokenStream> {
     token_stream: &'a mut T,
     peeked: Option<TokenizerResult>,
+    depth: u32,
 }
 
 impl<'a, T: TokenStream> Peekable<'a, T> {
@@ -92,26 +211,41 @@ impl<'a, T: TokenStream> Peekable<'a, T> {
         Peekable {
             token_stream,
             peeked: None,
+            depth: 0,
         }
     }
 
     pub fn peek(&mut self) -> Option<&TokenizerResult> {
-        self.peeked = Some(self.next());
+        if self.peeked.is_none() {
+            self.peeked = Some(self.token_stream.next());
+        }
 
         self.peeked.as_ref()
     }
+
+    /// Number of braces opened, but not yet closed, by the tokens consumed
+    /// so far
+    pub fn depth(&self) -> u32 {
+        self.depth
+    }
 }
 
 impl<'a, T: TokenStream> TokenStream for Peekable<'a, T> {
     fn next(&mut self) -> TokenizerResult {
-        match self.peeked.take() {
-            Some(p) => {
-                self.peeked = None;
+        let result = match self.peeked.take() {
+            Some(p) => p,
+            None => self.token_stream.next(),
+        };
 
-                p
+        match result {
+            TokenizerResult::Ok(Token::OpeningBrace, _) => self.depth += 1,
+            TokenizerResult::Ok(Token::ClosingBrace, _) => {
+                self.depth = self.depth.saturating_sub(1);
             }
-            None => self.token_stream.next(),
+            _ => {}
         }
+
+        result
     }
 }
 
@@ -228,4 +362,27 @@ mod test {
             peekable_stream.next()
         );
     }
+
+    #[test]
+    pub fn tracks_depth_of_consumed_braces() {
+        let location = SourceLocationRange::new_single(SourceLocation::new(1, 1));
+        let mut tokens = vec![
+            TokenizerResult::Ok(Token::OpeningBrace, location),
+            TokenizerResult::Ok(Token::OpeningBrace, location),
+            TokenizerResult::Ok(Token::ClosingBrace, location),
+        ];
+
+        let mut stream = MockTokenStream::new(&mut tokens);
+        let mut peekable_stream = Peekable::new(&mut stream);
+
+        peekable_stream.next();
+        peekable_stream.peek();
+        assert_eq!(1, peekable_stream.depth());
+
+        peekable_stream.next();
+        assert_eq!(2, peekable_stream.depth());
+
+        peekable_stream.next();
+        assert_eq!(1, peekable_stream.depth());
+    }
 }
@@ -1,9 +1,81 @@
 use crate::parsing::token_stream::{
-    SourceLocation, SourceLocationRange, Token, TokenStream, TokenizerFailure,
+    Float, SourceLocation, SourceLocationRange, Token, TokenStream, TokenizerFailure,
     TokenizerFailureKind, TokenizerResult,
 };
+use crate::presentation::{Color, Length};
 use std::iter::Peekable;
+use std::ops::Range;
 use std::str::CharIndices;
+use std::time::Duration;
+use unicode_xid::UnicodeXID;
+
+/// Opens and closes strings which are taken verbatim, without escapes
+const RAW_STRING_QUOTES: &str = "\"\"\"";
+
+/// Every keyword of the language, in the order they were added
+pub const KEYWORDS: &[&str] = &[
+    "slide",
+    "title",
+    "subtitle",
+    "metadata",
+    "style",
+    "font",
+    "name",
+    "path",
+    "weight",
+    "italic",
+    "text",
+    "list",
+    "item",
+    "bullet",
+    "indent",
+    "width",
+    "height",
+    "background",
+    "color",
+    "text-style",
+    "size",
+    "system",
+    "image",
+    "video",
+    "notes",
+    "transition",
+    "step",
+    "include",
+    "layout",
+    "uses",
+    "placeholder",
+    "define",
+    "duration",
+    "at",
+    "anchor",
+    "row",
+    "column",
+    "gap",
+    "align",
+    "grow",
+    "hinting",
+    "antialias",
+    "kerning",
+    "font-stack",
+    "theme",
+    "control",
+    "hidden",
+    "section",
+    "show-progress",
+    "show-page-numbers",
+    "show-footer",
+    "spelling-ignore",
+    "font-dir",
+    "target",
+    "opacity",
+    "fade-in",
+    "slide-in-from",
+    "animate",
+    "rotate",
+    "scale",
+    "skew",
+];
 
 #[derive(Eq, PartialEq, Debug)]
 enum TokenizerState {
@@ -16,10 +88,6 @@ enum TokenizerState {
         start_index: usize,
         start_location: SourceLocation,
     },
-    ReadingNumber {
-        start_index: usize,
-        start_location: SourceLocation,
-    },
 }
 
 pub struct Tokenizer<'a> {
@@ -28,6 +96,10 @@ pub struct Tokenizer<'a> {
     is_failed: bool,
     line: u32,
     column: u32,
+    /// Where the token being read starts in the input
+    token_start: usize,
+    keep_comments: bool,
+    recover: bool,
 }
 
 impl<'a> Tokenizer<'a> {
@@ -38,14 +110,40 @@ impl<'a> Tokenizer<'a> {
             is_failed: false,
             line: 0,
             column: 0,
+            token_start: 0,
+            keep_comments: false,
+            recover: false,
         }
     }
 
+    /// Returns comments as tokens instead of skipping them, for tools that
+    /// write the input back. The parser doesn't expect them.
+    pub fn set_keep_comments(&mut self, keep_comments: bool) {
+        self.keep_comments = keep_comments;
+    }
+
+    /// Keeps tokenizing after a failure instead of ending the stream. The
+    /// failure is still returned, then reading resumes at the next whitespace,
+    /// after the broken string or on the line after an unclosed one.
+    pub fn set_recover(&mut self, recover: bool) {
+        self.recover = recover;
+    }
+
+    /// Where the token returned last is in the input, as it was written
+    pub fn token_span(&mut self) -> Range<usize> {
+        let length = self.data.len();
+        let end = self.peek().map_or(length, |&(index, _)| index);
+        let text = self.data[self.token_start..end].trim_end();
+
+        self.token_start..self.token_start + text.len()
+    }
+
     fn handle_name_or_keyword(&self, name: &str, start: SourceLocation) -> TokenizerResult {
         TokenizerResult::Ok(
             match name {
                 "slide" => Token::KeywordSlide,
                 "title" => Token::KeywordTitle,
+                "subtitle" => Token::KeywordSubtitle,
                 "metadata" => Token::KeywordMetadata,
                 "style" => Token::KeywordStyle,
                 "font" => Token::KeywordFont,
@@ -53,6 +151,59 @@ impl<'a> Tokenizer<'a> {
                 "path" => Token::KeywordPath,
                 "weight" => Token::KeywordWeight,
                 "italic" => Token::KeywordItalic,
+                "text" => Token::KeywordText,
+                "list" => Token::KeywordList,
+                "item" => Token::KeywordItem,
+                "bullet" => Token::KeywordBullet,
+                "indent" => Token::KeywordIndent,
+                "width" => Token::KeywordWidth,
+                "height" => Token::KeywordHeight,
+                "background" => Token::KeywordBackground,
+                "color" => Token::KeywordColor,
+                "text-style" => Token::KeywordTextStyle,
+                "size" => Token::KeywordSize,
+                "system" => Token::KeywordSystem,
+                "image" => Token::KeywordImage,
+                "video" => Token::KeywordVideo,
+                "notes" => Token::KeywordNotes,
+                "transition" => Token::KeywordTransition,
+                "step" => Token::KeywordStep,
+                "include" => Token::KeywordInclude,
+                "layout" => Token::KeywordLayout,
+                "uses" => Token::KeywordUses,
+                "placeholder" => Token::KeywordPlaceholder,
+                "define" => Token::KeywordDefine,
+                "duration" => Token::KeywordDuration,
+                "at" => Token::KeywordAt,
+                "anchor" => Token::KeywordAnchor,
+                "row" => Token::KeywordRow,
+                "column" => Token::KeywordColumn,
+                "gap" => Token::KeywordGap,
+                "align" => Token::KeywordAlign,
+                "grow" => Token::KeywordGrow,
+                "hinting" => Token::KeywordHinting,
+                "antialias" => Token::KeywordAntialias,
+                "kerning" => Token::KeywordKerning,
+                "font-stack" => Token::KeywordFontStack,
+                "theme" => Token::KeywordTheme,
+                "control" => Token::KeywordControl,
+                "hidden" => Token::KeywordHidden,
+                "section" => Token::KeywordSection,
+                "show-progress" => Token::KeywordShowProgress,
+                "show-page-numbers" => Token::KeywordShowPageNumbers,
+                "show-footer" => Token::KeywordShowFooter,
+                "spelling-ignore" => Token::KeywordSpellingIgnore,
+                "font-dir" => Token::KeywordFontDir,
+                "target" => Token::KeywordTarget,
+                "opacity" => Token::KeywordOpacity,
+                "fade-in" => Token::KeywordFadeIn,
+                "slide-in-from" => Token::KeywordSlideInFrom,
+                "animate" => Token::KeywordAnimate,
+                "rotate" => Token::KeywordRotate,
+                "scale" => Token::KeywordScale,
+                "skew" => Token::KeywordSkew,
+                "true" => Token::Boolean(true),
+                "false" => Token::Boolean(false),
                 _ => Token::Name(name.into()),
             },
             SourceLocationRange::new(start, self.current_location()),
@@ -75,8 +226,70 @@ impl<'a> Tokenizer<'a> {
         }
     }
 
-    fn is_name_character(&self, character: char) -> bool {
-        character.is_ascii_alphanumeric() || character == '_' || character == '-'
+    fn handle_float(&self, float: &str, start: SourceLocation) -> TokenizerResult {
+        let location = SourceLocationRange::new(start, self.current_location());
+
+        if let Some(parsed) = parse_decimal(float) {
+            TokenizerResult::Ok(Token::Float(Float::new(parsed)), location)
+        } else {
+            TokenizerResult::Err(TokenizerFailure::new(
+                location,
+                TokenizerFailureKind::InvalidFloatValue(float.into()),
+            ))
+        }
+    }
+
+    fn handle_length(&self, number: &str, unit: &str, start: SourceLocation) -> TokenizerResult {
+        let location = SourceLocationRange::new(start, self.current_location());
+        let length: Option<fn(f64) -> Length> = match unit {
+            "px" => Some(Length::Pixels),
+            "%" => Some(Length::Percent),
+            "em" => Some(Length::Em),
+            _ => None,
+        };
+
+        match (parse_decimal(number), length) {
+            (Some(value), Some(length)) => {
+                TokenizerResult::Ok(Token::Length(length(value)), location)
+            }
+            (None, _) => TokenizerResult::Err(TokenizerFailure::new(
+                location,
+                TokenizerFailureKind::InvalidFloatValue(number.into()),
+            )),
+            (_, None) => TokenizerResult::Err(TokenizerFailure::new(
+                location,
+                TokenizerFailureKind::UnknownUnit(unit.into()),
+            )),
+        }
+    }
+
+    /// Durations can't be negative, `1.5s` is as good as `1500ms`
+    fn handle_duration(&self, number: &str, unit: &str, start: SourceLocation) -> TokenizerResult {
+        let location = SourceLocationRange::new(start, self.current_location());
+        let nanoseconds_per_unit = if unit == "ms" { 1e6 } else { 1e9 };
+        let duration = parse_decimal(number)
+            .map(|value| (value * nanoseconds_per_unit).round())
+            // as many nanoseconds as a u64 holds
+            .filter(|nanoseconds| (0.0..2_f64.powi(64)).contains(nanoseconds))
+            .map(|nanoseconds| Duration::from_nanos(nanoseconds as u64));
+
+        match duration {
+            Some(duration) => TokenizerResult::Ok(Token::Duration(duration), location),
+            None => TokenizerResult::Err(TokenizerFailure::new(
+                location,
+                TokenizerFailureKind::InvalidFloatValue(number.into()),
+            )),
+        }
+    }
+
+    /// Names follow the Unicode identifier rules, plus dashes inside them.
+    /// Keywords are all ASCII, so they are matched as before.
+    fn is_name_start(character: char) -> bool {
+        character.is_xid_start()
+    }
+
+    fn is_name_character(character: char) -> bool {
+        character.is_xid_continue() || character == '-'
     }
 
     fn read_next(&mut self) -> Option<(usize, char)> {
@@ -103,22 +316,267 @@ impl<'a> Tokenizer<'a> {
         }
     }
 
+    /// Reads a color literal whose opening `#` is at `start_index`
+    fn read_color(&mut self, start_index: usize) -> TokenizerResult {
+        let start_location = self.current_location();
+        let mut end_index = start_index + 1;
+
+        while let Some(&(index, character)) = self.peek() {
+            if !character.is_ascii_alphanumeric() {
+                break;
+            }
+
+            self.read_next();
+            end_index = index + 1;
+        }
+
+        let literal = &self.data[start_index..end_index];
+        let location = SourceLocationRange::new(start_location, self.current_location());
+
+        if let Some(color) = Color::from_hex(literal) {
+            TokenizerResult::Ok(Token::Color(color), location)
+        } else {
+            self.is_failed = true;
+
+            TokenizerResult::Err(TokenizerFailure::new(
+                location,
+                TokenizerFailureKind::InvalidColorLiteral(literal.into()),
+            ))
+        }
+    }
+
+    /// Reads an integer, a decimal number, or a length or a duration with a
+    /// unit suffix, whose first character (a digit or a minus sign) is at `start_index`
+    fn read_number(&mut self, start_index: usize) -> TokenizerResult {
+        let start_location = self.current_location();
+        let mut end_index = start_index + 1;
+
+        while let Some(&(index, character)) = self.peek() {
+            if !character.is_ascii_digit() && character != '.' {
+                break;
+            }
+
+            self.read_next();
+            end_index = index + 1;
+        }
+
+        if self.check_next(':') {
+            return self.read_time(start_index, end_index, start_location);
+        }
+
+        let mut unit_end_index = end_index;
+        while let Some(&(index, character)) = self.peek() {
+            if !character.is_ascii_alphabetic() && character != '%' {
+                break;
+            }
+
+            self.read_next();
+            unit_end_index = index + 1;
+        }
+
+        let literal = &self.data[start_index..end_index];
+        let unit = &self.data[end_index..unit_end_index];
+
+        if unit == "ms" || unit == "s" {
+            self.handle_duration(literal, unit, start_location)
+        } else if !unit.is_empty() {
+            self.handle_length(literal, unit, start_location)
+        } else if literal.contains('.') {
+            self.handle_float(literal, start_location)
+        } else {
+            self.handle_integer(literal, start_location)
+        }
+    }
+
+    /// Reads the rest of a time whose first number was read up to
+    /// `end_index`
+    fn read_time(
+        &mut self,
+        start_index: usize,
+        mut end_index: usize,
+        start_location: SourceLocation,
+    ) -> TokenizerResult {
+        while let Some(&(index, character)) = self.peek() {
+            if !character.is_ascii_digit() && character != ':' {
+                break;
+            }
+
+            self.read_next();
+            end_index = index + 1;
+        }
+
+        let literal = &self.data[start_index..end_index];
+        let location = SourceLocationRange::new(start_location, self.current_location());
+
+        match parse_time(literal) {
+            Some(time) => TokenizerResult::Ok(Token::Time(time), location),
+            None => TokenizerResult::Err(TokenizerFailure::new(
+                location,
+                TokenizerFailureKind::InvalidTime(literal.into()),
+            )),
+        }
+    }
+
+    /// Reads a string whose opening triple quotes start at `start_index`, a
+    /// newline right after the opening quotes is not part of the string
+    fn read_raw_string(&mut self, start_index: usize) -> TokenizerResult {
+        let start_location = self.current_location();
+        self.read_next();
+        self.read_next();
+
+        while let Some((index, _)) = self.read_next() {
+            if self.data[index..].starts_with(RAW_STRING_QUOTES) {
+                self.read_next();
+                self.read_next();
+
+                let content = &self.data[start_index + RAW_STRING_QUOTES.len()..index];
+                return TokenizerResult::Ok(
+                    Token::String(content.strip_prefix('\n').unwrap_or(content).into()),
+                    SourceLocationRange::new(start_location, self.current_location()),
+                );
+            }
+        }
+
+        self.is_failed = true;
+
+        TokenizerResult::Err(TokenizerFailure::new(
+            SourceLocationRange::new(start_location, self.current_location()),
+            TokenizerFailureKind::UnclosedString,
+        ))
+    }
+
+    /// Reads the `{XXXX}` part of a `\\u{XXXX}` escape, returning the text
+    /// read so far if it is not a valid code point
+    fn read_unicode_escape(&mut self) -> Result<(), String> {
+        let mut sequence = String::from("\\u");
+
+        if !self.check_next('{') {
+            return Err(sequence);
+        }
+        self.read_next();
+        sequence.push('{');
+
+        while let Some(&(_, character)) = self.peek() {
+            if character == '}' {
+                self.read_next();
+                sequence.push('}');
+
+                let hex = &sequence[3..sequence.len() - 1];
+                return match u32::from_str_radix(hex, 16).ok().and_then(char::from_u32) {
+                    Some(_) if hex.len() <= 6 => Ok(()),
+                    _ => Err(sequence),
+                };
+            }
+            if !character.is_ascii_hexdigit() {
+                return Err(sequence);
+            }
+
+            self.read_next();
+            sequence.push(character);
+        }
+
+        Err(sequence)
+    }
+
+    /// The comment read last as a token, when comments are kept
+    fn comment(&mut self, start_location: SourceLocation) -> Option<TokenizerResult> {
+        if !self.keep_comments {
+            return None;
+        }
+
+        let text = &self.data[self.token_span()];
+        Some(TokenizerResult::Ok(
+            Token::Comment(text.into()),
+            SourceLocationRange::new(start_location, self.current_location()),
+        ))
+    }
+
+    fn skip_line_comment(&mut self) {
+        while let Some((_, character)) = self.read_next() {
+            if character == '\n' {
+                break;
+            }
+        }
+    }
+
+    /// Skips a comment whose opening `/` has already been read
+    fn skip_block_comment(&mut self) -> Result<(), TokenizerFailure> {
+        let start_location = self.current_location();
+        self.read_next();
+
+        while let Some((_, character)) = self.read_next() {
+            if character == '*' && self.check_next('/') {
+                self.read_next();
+
+                return Ok(());
+            }
+        }
+
+        Err(TokenizerFailure::new(
+            SourceLocationRange::new(start_location, self.current_location()),
+            TokenizerFailureKind::UnclosedComment,
+        ))
+    }
+
     fn current_location(&self) -> SourceLocation {
         SourceLocation::new(self.line, self.column)
     }
-}
 
-impl<'a> TokenStream for Tokenizer<'a> {
-    fn next(&mut self) -> TokenizerResult {
-        if self.is_failed {
-            return TokenizerResult::End;
+    /// Skips the input a failure left unreadable, so that tokenizing can go on
+    fn resynchronize(&mut self, failure: &TokenizerFailure) {
+        match failure.kind() {
+            TokenizerFailureKind::UnexpectedCharacterInName { .. } => {
+                while matches!(self.peek(), Some((_, character)) if !character.is_whitespace()) {
+                    self.read_next();
+                }
+            }
+            TokenizerFailureKind::UnknownEscapeSequence(_)
+            | TokenizerFailureKind::InvalidUnicodeEscape(_) => {
+                while let Some((_, character)) = self.read_next() {
+                    match character {
+                        '\\' => {
+                            self.read_next();
+                        }
+                        '"' | '\n' => break,
+                        _ => {}
+                    }
+                }
+            }
+            TokenizerFailureKind::UnclosedString | TokenizerFailureKind::UnclosedComment => {
+                let line_end = self.data[self.token_start..]
+                    .find('\n')
+                    .map_or(self.data.len(), |length| self.token_start + length + 1);
+                self.resume_at(line_end);
+            }
+            _ => {}
+        }
+
+        self.is_failed = false;
+    }
+
+    /// Moves reading back to a byte offset of the input
+    fn resume_at(&mut self, offset: usize) {
+        let read = &self.data[..offset];
+        let line_start = read.rfind('\n').map_or(0, |index| index + 1);
+
+        self.iter = self.data.char_indices().peekable();
+        while matches!(self.iter.peek(), Some(&(index, _)) if index < offset) {
+            self.iter.next();
         }
+        self.line = read.matches('\n').count() as u32;
+        self.column = read[line_start..].chars().count() as u32;
+    }
 
+    fn read_token(&mut self) -> TokenizerResult {
         let mut state = TokenizerState::None;
 
         while let Some((index, character)) = self.read_next() {
+            if state == TokenizerState::None {
+                self.token_start = index;
+            }
+
             match state {
-                TokenizerState::None if character.is_ascii_alphabetic() => {
+                TokenizerState::None if Self::is_name_start(character) => {
                     state = TokenizerState::ReadingName {
                         start_index: index,
                         start_location: self.current_location(),
@@ -126,7 +584,7 @@ impl<'a> TokenStream for Tokenizer<'a> {
 
                     if self.check_next(',') {
                         return self.handle_name_or_keyword(
-                            &self.data[index..=index],
+                            &self.data[index..index + character.len_utf8()],
                             self.current_location(),
                         );
                     }
@@ -135,30 +593,15 @@ impl<'a> TokenStream for Tokenizer<'a> {
                     start_index,
                     start_location,
                 } => {
-                    let is_next_character_a_comma = self.check_next(',');
-
-                    if self.is_name_character(character) && !is_next_character_a_comma {
-                        continue;
-                    }
-
-                    if character.is_ascii_whitespace() || is_next_character_a_comma {
-                        let actual_index = if is_next_character_a_comma { 1 } else { 0 } + index;
-
-                        return self.handle_name_or_keyword(
-                            &self.data[start_index..actual_index],
-                            start_location,
-                        );
-                    } else {
-                        self.is_failed = true;
-
-                        println!("Failure! {:?}", state);
-
-                        return TokenizerResult::Err(TokenizerFailure::new(
-                            SourceLocationRange::new(start_location, self.current_location()),
-                            TokenizerFailureKind::UnexpectedCharacterInName { index, character },
-                        ));
+                    if let Some(result) =
+                        self.read_name_character(index, character, start_index, start_location)
+                    {
+                        return result;
                     }
                 }
+                TokenizerState::None if self.data[index..].starts_with(RAW_STRING_QUOTES) => {
+                    return self.read_raw_string(index);
+                }
                 TokenizerState::None if character == '"' => {
                     state = TokenizerState::ReadingString {
                         start_index: index,
@@ -166,25 +609,8 @@ impl<'a> TokenStream for Tokenizer<'a> {
                     }
                 }
                 TokenizerState::ReadingString { start_location, .. } if character == '\\' => {
-                    match self.iter.peek() {
-                        Some((_, '\"')) => {
-                            self.read_next();
-                        }
-                        Some((_, character)) => {
-                            self.is_failed = true;
-                            let failure_kind =
-                                TokenizerFailureKind::UnknownEscapeSequence(*character);
-                            return TokenizerResult::Err(TokenizerFailure::new(
-                                SourceLocationRange::new(start_location, self.current_location()),
-                                failure_kind,
-                            ));
-                        }
-                        _ => {
-                            return TokenizerResult::Err(TokenizerFailure::new(
-                                SourceLocationRange::new(start_location, self.current_location()),
-                                TokenizerFailureKind::UnfinishedEscapeSequence,
-                            ));
-                        }
+                    if let Err(failure) = self.read_escape_sequence(start_location) {
+                        return TokenizerResult::Err(failure);
                     }
                 }
                 TokenizerState::ReadingString {
@@ -192,68 +618,39 @@ impl<'a> TokenStream for Tokenizer<'a> {
                     start_location,
                 } if character == '"' => {
                     return TokenizerResult::Ok(
-                        Token::String(
-                            self.data[start_index + 1..index]
-                                .to_owned()
-                                .replace("\\\"", "\""),
-                        ),
+                        Token::String(unescape(&self.data[start_index + 1..index])),
                         SourceLocationRange::new(start_location, self.current_location()),
                     );
                 }
                 TokenizerState::ReadingString { .. } => {}
                 TokenizerState::None if character.is_ascii_digit() || character == '-' => {
-                    state = TokenizerState::ReadingNumber {
-                        start_index: index,
-                        start_location: self.current_location(),
-                    }
+                    return self.read_number(index);
                 }
-                TokenizerState::ReadingNumber {
-                    start_index,
-                    start_location,
-                } => match self.peek() {
-                    None => {
-                        return self
-                            .handle_integer(&self.data[start_index..=index], start_location);
-                    }
-                    Some((_, next_character)) => {
-                        if !next_character.is_ascii_digit() {
-                            return self
-                                .handle_integer(&self.data[start_index..=index], start_location);
-                        }
+                TokenizerState::None if character == '#' => {
+                    return self.read_color(index);
+                }
+                TokenizerState::None if character == '/' && self.check_next('/') => {
+                    let start_location = self.current_location();
+                    self.skip_line_comment();
+
+                    if let Some(comment) = self.comment(start_location) {
+                        return comment;
                     }
-                },
-                TokenizerState::None => {
-                    if character.is_ascii_whitespace() {
-                        continue;
+                }
+                TokenizerState::None if character == '/' && self.check_next('*') => {
+                    let start_location = self.current_location();
+                    if let Err(failure) = self.skip_block_comment() {
+                        self.is_failed = true;
+
+                        return TokenizerResult::Err(failure);
                     }
 
-                    match character {
-                        '{' => {
-                            return TokenizerResult::Ok(
-                                Token::OpeningBrace,
-                                SourceLocationRange::new_single(self.current_location()),
-                            );
-                        }
-                        '}' => {
-                            return TokenizerResult::Ok(
-                                Token::ClosingBrace,
-                                SourceLocationRange::new_single(self.current_location()),
-                            );
-                        }
-                        ',' => {
-                            return TokenizerResult::Ok(
-                                Token::Comma,
-                                SourceLocationRange::new_single(self.current_location()),
-                            )
-                        }
-                        c => {
-                            return TokenizerResult::Err(TokenizerFailure::new(
-                                SourceLocationRange::new_single(self.current_location()),
-                                TokenizerFailureKind::UnexpectedCharacter(c),
-                            ));
-                        }
+                    if let Some(comment) = self.comment(start_location) {
+                        return comment;
                     }
                 }
+                TokenizerState::None if character.is_ascii_whitespace() => {}
+                TokenizerState::None => return self.read_punctuation(character),
             }
         }
 
@@ -269,14 +666,228 @@ impl<'a> TokenStream for Tokenizer<'a> {
                     TokenizerFailureKind::UnclosedString,
                 ))
             }
-            TokenizerState::ReadingNumber {
-                start_index,
-                start_location,
-            } => self.handle_integer(&self.data[start_index..], start_location),
+        }
+    }
+
+    /// Ends the name at whitespace or before a comma, None while the name
+    /// goes on
+    fn read_name_character(
+        &mut self,
+        index: usize,
+        character: char,
+        start_index: usize,
+        start_location: SourceLocation,
+    ) -> Option<TokenizerResult> {
+        let is_next_character_a_comma = self.check_next(',');
+
+        if Self::is_name_character(character) && !is_next_character_a_comma {
+            return None;
+        }
+
+        if character.is_ascii_whitespace() || is_next_character_a_comma {
+            let actual_index = if is_next_character_a_comma {
+                index + character.len_utf8()
+            } else {
+                index
+            };
+
+            return Some(
+                self.handle_name_or_keyword(&self.data[start_index..actual_index], start_location),
+            );
+        }
+
+        self.is_failed = true;
+
+        Some(TokenizerResult::Err(TokenizerFailure::new(
+            SourceLocationRange::new(start_location, self.current_location()),
+            TokenizerFailureKind::UnexpectedCharacterInName { index, character },
+        )))
+    }
+
+    /// Skips over the escape sequence after a backslash in a string, it is
+    /// unescaped once the whole string is read
+    fn read_escape_sequence(
+        &mut self,
+        start_location: SourceLocation,
+    ) -> Result<(), TokenizerFailure> {
+        let failure = |tokenizer: &Self, kind| {
+            TokenizerFailure::new(
+                SourceLocationRange::new(start_location, tokenizer.current_location()),
+                kind,
+            )
+        };
+
+        match self.iter.peek() {
+            Some((_, '\"' | 'n' | 't' | '\\')) => {
+                self.read_next();
+            }
+            Some((_, 'u')) => {
+                self.read_next();
+
+                if let Err(sequence) = self.read_unicode_escape() {
+                    self.is_failed = true;
+                    return Err(failure(
+                        self,
+                        TokenizerFailureKind::InvalidUnicodeEscape(sequence),
+                    ));
+                }
+            }
+            Some((_, character)) => {
+                self.is_failed = true;
+                let failure_kind = TokenizerFailureKind::UnknownEscapeSequence(*character);
+                return Err(failure(self, failure_kind));
+            }
+            _ => {
+                return Err(failure(
+                    self,
+                    TokenizerFailureKind::UnfinishedEscapeSequence,
+                ))
+            }
+        }
+
+        Ok(())
+    }
+
+    fn read_punctuation(&self, character: char) -> TokenizerResult {
+        let location = SourceLocationRange::new_single(self.current_location());
+
+        match character {
+            '{' => TokenizerResult::Ok(Token::OpeningBrace, location),
+            '}' => TokenizerResult::Ok(Token::ClosingBrace, location),
+            ',' => TokenizerResult::Ok(Token::Comma, location),
+            c => TokenizerResult::Err(TokenizerFailure::new(
+                location,
+                TokenizerFailureKind::UnexpectedCharacter(c),
+            )),
         }
     }
 }
 
+impl<'a> TokenStream for Tokenizer<'a> {
+    fn next(&mut self) -> TokenizerResult {
+        if self.is_failed {
+            return TokenizerResult::End;
+        }
+
+        let result = self.read_token();
+        if let TokenizerResult::Err(failure) = &result {
+            if self.recover {
+                self.resynchronize(failure);
+            }
+        }
+
+        result
+    }
+}
+
+/// Tokens of the input with their locations, for tools that only need the
+/// token stream. Comments are skipped and iteration stops after the first
+/// failure.
+pub struct Tokens<'a> {
+    tokenizer: Tokenizer<'a>,
+    is_failed: bool,
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = Result<(Token, SourceLocationRange), TokenizerFailure>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.is_failed {
+            return None;
+        }
+
+        match TokenStream::next(&mut self.tokenizer) {
+            TokenizerResult::Ok(token, location) => Some(Ok((token, location))),
+            TokenizerResult::Err(failure) => {
+                // some failures leave the tokenizer going, but what follows
+                // them can't be trusted
+                self.is_failed = true;
+                Some(Err(failure))
+            }
+            TokenizerResult::End => None,
+        }
+    }
+}
+
+impl<'a> IntoIterator for Tokenizer<'a> {
+    type Item = Result<(Token, SourceLocationRange), TokenizerFailure>;
+    type IntoIter = Tokens<'a>;
+
+    fn into_iter(self) -> Tokens<'a> {
+        Tokens {
+            tokenizer: self,
+            is_failed: false,
+        }
+    }
+}
+
+/// Replaces escape sequences in a string already checked by the tokenizer
+fn unescape(raw: &str) -> String {
+    let mut result = String::with_capacity(raw.len());
+    let mut characters = raw.chars();
+
+    while let Some(character) = characters.next() {
+        if character != '\\' {
+            result.push(character);
+            continue;
+        }
+
+        match characters.next() {
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some('u') => {
+                let hex: String = characters
+                    .by_ref()
+                    .skip(1)
+                    .take_while(|character| *character != '}')
+                    .collect();
+                result.extend(u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32));
+            }
+            Some(escaped) => result.push(escaped),
+            None => {}
+        }
+    }
+
+    result
+}
+
+/// Accepts `mm:ss` and `hh:mm:ss`, the minutes and seconds after the first
+/// number are below 60
+fn parse_time(time: &str) -> Option<Duration> {
+    let parts = time
+        .split(':')
+        .map(|part| {
+            Some(part)
+                .filter(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()))
+                .and_then(|part| part.parse::<u64>().ok())
+        })
+        .collect::<Option<Vec<u64>>>()?;
+    if !(2..=3).contains(&parts.len()) || parts[1..].iter().any(|part| *part >= 60) {
+        return None;
+    }
+
+    Some(Duration::from_secs(
+        parts.iter().fold(0, |seconds, part| seconds * 60 + part),
+    ))
+}
+
+/// Accepts an integer or digits on both sides of a single decimal point
+fn parse_decimal(number: &str) -> Option<f64> {
+    let digits = number.strip_prefix('-').unwrap_or(number);
+    let is_well_formed = match digits.split_once('.') {
+        Some((whole, fraction)) => {
+            !whole.is_empty() && !fraction.is_empty() && !fraction.contains('.')
+        }
+        None => !digits.is_empty(),
+    };
+
+    if is_well_formed {
+        number.parse().ok()
+    } else {
+        None
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -382,6 +993,56 @@ mod tests {
         Token::String("test\"some\"words".into())
     );
 
+    tokenizer_test!(
+        can_read_a_raw_string,
+        "\"\"\"\nHe said \"hi\"\\n\nand left\"\"\" next",
+        Token::String("He said \"hi\"\\n\nand left".into()),
+        Token::Name("next".into())
+    );
+    tokenizer_test!(
+        can_read_an_empty_string,
+        "\"\"",
+        Token::String(String::new())
+    );
+    tokenizer_fail_test!(
+        fails_on_unclosed_raw_string,
+        "\"\"\"abc\"\"",
+        TokenizerFailure::new(
+            SourceLocationRange::new(SourceLocation::new(0, 1), SourceLocation::new(0, 9)),
+            TokenizerFailureKind::UnclosedString
+        )
+    );
+    tokenizer_test!(
+        can_read_newline_and_tab_escapes,
+        "\"first\\n\\tsecond\"",
+        Token::String("first\n\tsecond".into())
+    );
+    tokenizer_test!(
+        can_read_escaped_backslash,
+        "\"a\\\\\"",
+        Token::String("a\\".into())
+    );
+    tokenizer_test!(
+        can_read_unicode_escape,
+        "\"\\u{48}i \\u{1F600}\"",
+        Token::String("Hi \u{1F600}".into())
+    );
+    tokenizer_fail_test!(
+        fails_on_invalid_unicode_escape,
+        "\"\\u{D800}\"",
+        TokenizerFailure::new(
+            SourceLocationRange::new(SourceLocation::new(0, 1), SourceLocation::new(0, 9)),
+            TokenizerFailureKind::InvalidUnicodeEscape("\\u{D800}".into())
+        )
+    );
+    tokenizer_fail_test!(
+        fails_on_unicode_escape_without_braces,
+        "\"\\u48\"",
+        TokenizerFailure::new(
+            SourceLocationRange::new(SourceLocation::new(0, 1), SourceLocation::new(0, 3)),
+            TokenizerFailureKind::InvalidUnicodeEscape("\\u".into())
+        )
+    );
     tokenizer_fail_test!(
         fails_on_unknown_escape_sequence,
         "\"\\a",
@@ -427,6 +1088,36 @@ mod tests {
         "na_me",
         Token::Name("na_me".into())
     );
+    tokenizer_test!(
+        allows_letters_outside_ascii_in_names,
+        "Überschrift заголовок 見出し",
+        Token::Name("Überschrift".into()),
+        Token::Name("заголовок".into()),
+        Token::Name("見出し".into())
+    );
+    tokenizer_test!(
+        allows_combining_characters_in_names,
+        "cafe\u{301} नमस्ते",
+        Token::Name("cafe\u{301}".into()),
+        Token::Name("नमस्ते".into())
+    );
+    tokenizer_test!(
+        only_matches_keywords_written_in_ascii,
+        "ｓｌｉｄｅ slide",
+        Token::Name("ｓｌｉｄｅ".into()),
+        Token::KeywordSlide
+    );
+    tokenizer_fail_test!(
+        fails_on_symbols_in_names,
+        "ab🆒",
+        TokenizerFailure::new(
+            SourceLocationRange::new(SourceLocation::new(0, 1), SourceLocation::new(0, 3)),
+            TokenizerFailureKind::UnexpectedCharacterInName {
+                index: 2,
+                character: '🆒'
+            }
+        )
+    );
 
     tokenizer_test!(allows_hyphen_in_name, "na-me", Token::Name("na-me".into()));
     tokenizer_test!(
@@ -437,12 +1128,126 @@ mod tests {
 
     tokenizer_test!(handles_slide_as_keyword, "slide", Token::KeywordSlide);
     tokenizer_test!(handles_title_as_keyword, "title", Token::KeywordTitle);
+    tokenizer_test!(handles_at_as_keyword, "at", Token::KeywordAt);
+    tokenizer_test!(handles_anchor_as_keyword, "anchor", Token::KeywordAnchor);
+    tokenizer_test!(handles_row_as_keyword, "row", Token::KeywordRow);
+    tokenizer_test!(handles_column_as_keyword, "column", Token::KeywordColumn);
+    tokenizer_test!(handles_gap_as_keyword, "gap", Token::KeywordGap);
+    tokenizer_test!(handles_align_as_keyword, "align", Token::KeywordAlign);
+    tokenizer_test!(handles_grow_as_keyword, "grow", Token::KeywordGrow);
+    tokenizer_test!(
+        handles_subtitle_as_keyword,
+        "subtitle",
+        Token::KeywordSubtitle
+    );
     tokenizer_test!(handles_style_as_keyword, "style", Token::KeywordStyle);
     tokenizer_test!(handles_font_as_keyword, "font", Token::KeywordFont);
     tokenizer_test!(handles_path_as_keyword, "path", Token::KeywordPath);
     tokenizer_test!(handles_name_as_keyword, "name", Token::KeywordName);
     tokenizer_test!(handles_weight_as_keyword, "weight", Token::KeywordWeight);
     tokenizer_test!(handles_italic_as_keyword, "italic", Token::KeywordItalic);
+    tokenizer_test!(handles_text_as_keyword, "text", Token::KeywordText);
+    tokenizer_test!(handles_list_as_keyword, "list", Token::KeywordList);
+    tokenizer_test!(handles_item_as_keyword, "item", Token::KeywordItem);
+    tokenizer_test!(handles_bullet_as_keyword, "bullet", Token::KeywordBullet);
+    tokenizer_test!(handles_indent_as_keyword, "indent", Token::KeywordIndent);
+    tokenizer_test!(handles_width_as_keyword, "width", Token::KeywordWidth);
+    tokenizer_test!(handles_height_as_keyword, "height", Token::KeywordHeight);
+    tokenizer_test!(handles_color_as_keyword, "color", Token::KeywordColor);
+    tokenizer_test!(
+        handles_text_style_as_keyword,
+        "text-style",
+        Token::KeywordTextStyle
+    );
+    tokenizer_test!(handles_size_as_keyword, "size", Token::KeywordSize);
+    tokenizer_test!(handles_system_as_keyword, "system", Token::KeywordSystem);
+    tokenizer_test!(handles_image_as_keyword, "image", Token::KeywordImage);
+    tokenizer_test!(handles_video_as_keyword, "video", Token::KeywordVideo);
+    tokenizer_test!(handles_hinting_as_keyword, "hinting", Token::KeywordHinting);
+    tokenizer_test!(
+        handles_antialias_as_keyword,
+        "antialias",
+        Token::KeywordAntialias
+    );
+    tokenizer_test!(handles_kerning_as_keyword, "kerning", Token::KeywordKerning);
+    tokenizer_test!(
+        handles_font_stack_as_keyword,
+        "font-stack",
+        Token::KeywordFontStack
+    );
+    tokenizer_test!(handles_notes_as_keyword, "notes", Token::KeywordNotes);
+    tokenizer_test!(
+        handles_booleans,
+        "true false",
+        Token::Boolean(true),
+        Token::Boolean(false)
+    );
+    tokenizer_test!(handles_include_as_keyword, "include", Token::KeywordInclude);
+    tokenizer_test!(handles_define_as_keyword, "define", Token::KeywordDefine);
+    tokenizer_test!(
+        handles_duration_as_keyword,
+        "duration",
+        Token::KeywordDuration
+    );
+    tokenizer_test!(
+        handles_layout_keywords,
+        "layout uses placeholder",
+        Token::KeywordLayout,
+        Token::KeywordUses,
+        Token::KeywordPlaceholder
+    );
+    tokenizer_test!(handles_step_as_keyword, "step", Token::KeywordStep);
+    tokenizer_test!(
+        handles_transition_as_keyword,
+        "transition",
+        Token::KeywordTransition
+    );
+    tokenizer_test!(handles_theme_as_keyword, "theme", Token::KeywordTheme);
+    tokenizer_test!(handles_control_as_keyword, "control", Token::KeywordControl);
+    tokenizer_test!(handles_hidden_as_keyword, "hidden", Token::KeywordHidden);
+    tokenizer_test!(handles_section_as_keyword, "section", Token::KeywordSection);
+    tokenizer_test!(
+        handles_show_progress_as_keyword,
+        "show-progress",
+        Token::KeywordShowProgress
+    );
+    tokenizer_test!(
+        handles_show_page_numbers_as_keyword,
+        "show-page-numbers",
+        Token::KeywordShowPageNumbers
+    );
+    tokenizer_test!(
+        handles_show_footer_as_keyword,
+        "show-footer",
+        Token::KeywordShowFooter
+    );
+    tokenizer_test!(
+        handles_spelling_ignore_as_keyword,
+        "spelling-ignore",
+        Token::KeywordSpellingIgnore
+    );
+    tokenizer_test!(
+        handles_font_dir_as_keyword,
+        "font-dir",
+        Token::KeywordFontDir
+    );
+    tokenizer_test!(handles_target_as_keyword, "target", Token::KeywordTarget);
+    tokenizer_test!(handles_opacity_as_keyword, "opacity", Token::KeywordOpacity);
+    tokenizer_test!(handles_fade_in_as_keyword, "fade-in", Token::KeywordFadeIn);
+    tokenizer_test!(
+        handles_slide_in_from_as_keyword,
+        "slide-in-from",
+        Token::KeywordSlideInFrom
+    );
+    tokenizer_test!(handles_animate_as_keyword, "animate", Token::KeywordAnimate);
+    tokenizer_test!(handles_rotate_as_keyword, "rotate", Token::KeywordRotate);
+    tokenizer_test!(handles_scale_as_keyword, "scale", Token::KeywordScale);
+    tokenizer_test!(handles_skew_as_keyword, "skew", Token::KeywordSkew);
+    tokenizer_test!(
+        handles_background_as_keyword,
+        "background",
+        Token::KeywordBackground
+    );
     tokenizer_test!(
         handles_metadata_as_keyword,
         "metadata",
@@ -511,6 +1316,78 @@ mod tests {
         Token::Integer(123456789)
     );
     tokenizer_test!(can_handle_negative_integers, "-123", Token::Integer(-123));
+    tokenizer_test!(
+        can_handle_single_digit_integer_followed_by_a_name,
+        "1 a",
+        Token::Integer(1),
+        Token::Name("a".into())
+    );
+    tokenizer_test!(can_handle_floats, "1.5", Token::Float(Float::new(1.5)));
+    tokenizer_test!(
+        can_handle_negative_floats,
+        "-0.25",
+        Token::Float(Float::new(-0.25))
+    );
+    tokenizer_test!(
+        can_handle_float_followed_by_a_comma,
+        "0.8,",
+        Token::Float(Float::new(0.8)),
+        Token::Comma
+    );
+    tokenizer_test!(
+        can_handle_non_ascii_names_followed_by_a_comma,
+        "żółw, ą,",
+        Token::Name("żółw".into()),
+        Token::Comma,
+        Token::Name("ą".into()),
+        Token::Comma
+    );
+    tokenizer_fail_test!(
+        fails_on_float_with_multiple_decimal_points,
+        "1.2.3",
+        TokenizerFailure::new(
+            SourceLocationRange::new(SourceLocation::new(0, 1), SourceLocation::new(0, 5)),
+            TokenizerFailureKind::InvalidFloatValue("1.2.3".into())
+        )
+    );
+    tokenizer_test!(
+        can_handle_lengths,
+        "10px 50% 1.5em",
+        Token::Length(Length::Pixels(10.0)),
+        Token::Length(Length::Percent(50.0)),
+        Token::Length(Length::Em(1.5))
+    );
+    tokenizer_fail_test!(
+        fails_on_unknown_unit,
+        "12pt",
+        TokenizerFailure::new(
+            SourceLocationRange::new(SourceLocation::new(0, 1), SourceLocation::new(0, 4)),
+            TokenizerFailureKind::UnknownUnit("pt".into())
+        )
+    );
+    tokenizer_test!(
+        can_handle_durations,
+        "300ms 2s 1.5s",
+        Token::Duration(Duration::from_millis(300)),
+        Token::Duration(Duration::from_secs(2)),
+        Token::Duration(Duration::from_millis(1500))
+    );
+    tokenizer_fail_test!(
+        fails_on_negative_duration,
+        "-5ms",
+        TokenizerFailure::new(
+            SourceLocationRange::new(SourceLocation::new(0, 1), SourceLocation::new(0, 4)),
+            TokenizerFailureKind::InvalidFloatValue("-5".into())
+        )
+    );
+    tokenizer_fail_test!(
+        fails_on_float_without_fraction,
+        "1.",
+        TokenizerFailure::new(
+            SourceLocationRange::new(SourceLocation::new(0, 1), SourceLocation::new(0, 2)),
+            TokenizerFailureKind::InvalidFloatValue("1.".into())
+        )
+    );
 
     tokenizer_test!(
         can_handle_name_followed_by_integer,
@@ -533,10 +1410,215 @@ mod tests {
         Token::Comma
     );
 
+    tokenizer_test!(
+        can_read_a_color_literal,
+        "#12abFF",
+        Token::Color(Color::new(0x12, 0xab, 0xff, 0xff))
+    );
+
+    tokenizer_test!(
+        can_read_a_color_literal_with_alpha,
+        "#12abff80",
+        Token::Color(Color::new(0x12, 0xab, 0xff, 0x80))
+    );
+
+    tokenizer_test!(
+        can_handle_color_followed_by_a_comma,
+        "#000000,#ffffff",
+        Token::Color(Color::new(0, 0, 0, 0xff)),
+        Token::Comma,
+        Token::Color(Color::new(0xff, 0xff, 0xff, 0xff))
+    );
+
+    tokenizer_fail_test!(
+        fails_on_invalid_color_literal,
+        "#12abfg",
+        TokenizerFailure::new(
+            SourceLocationRange::new(SourceLocation::new(0, 1), SourceLocation::new(0, 7)),
+            TokenizerFailureKind::InvalidColorLiteral("#12abfg".into())
+        )
+    );
+
+    tokenizer_fail_test!(
+        fails_on_color_literal_of_invalid_length,
+        "#1234",
+        TokenizerFailure::new(
+            SourceLocationRange::new(SourceLocation::new(0, 1), SourceLocation::new(0, 5)),
+            TokenizerFailureKind::InvalidColorLiteral("#1234".into())
+        )
+    );
+
+    tokenizer_test!(
+        can_tokenize_times,
+        "05:30 01:02:03",
+        Token::Time(Duration::from_secs(330)),
+        Token::Time(Duration::from_secs(3723))
+    );
+
+    tokenizer_fail_test!(
+        fails_on_invalid_time,
+        "00:75",
+        TokenizerFailure::new(
+            SourceLocationRange::new(SourceLocation::new(0, 1), SourceLocation::new(0, 5)),
+            TokenizerFailureKind::InvalidTime("00:75".into())
+        )
+    );
+
+    tokenizer_test!(
+        skips_line_comments,
+        "aaa // some comment\nbbb",
+        Token::Name("aaa".into()),
+        Token::Name("bbb".into())
+    );
+
+    tokenizer_test!(
+        skips_line_comment_at_the_end,
+        "aaa // some comment",
+        Token::Name("aaa".into())
+    );
+
+    tokenizer_test!(
+        skips_block_comments,
+        "aaa /* some \n comment */ bbb",
+        Token::Name("aaa".into()),
+        Token::Name("bbb".into())
+    );
+
+    tokenizer_test!(
+        block_comment_can_contain_stars_and_slashes,
+        "{/* * / ** // */}",
+        Token::OpeningBrace,
+        Token::ClosingBrace
+    );
+
+    tokenizer_fail_test!(
+        fails_on_unclosed_block_comment,
+        "/* aaa",
+        TokenizerFailure::new(
+            SourceLocationRange::new(SourceLocation::new(0, 1), SourceLocation::new(0, 7)),
+            TokenizerFailureKind::UnclosedComment
+        )
+    );
+
+    tokenizer_fail_test!(
+        fails_on_single_slash,
+        "/",
+        TokenizerFailure::new(
+            SourceLocationRange::new_single(SourceLocation::new(0, 1)),
+            TokenizerFailureKind::UnexpectedCharacter('/')
+        )
+    );
+
+    tokenizer_fail_test!(
+        keeps_track_of_location_across_comments,
+        "/* a\nbb */ // c\n  🆒",
+        TokenizerFailure::new(
+            SourceLocationRange::new_single(SourceLocation::new(2, 3)),
+            TokenizerFailureKind::UnexpectedCharacter('🆒')
+        )
+    );
+
     tokenizer_test!(
         can_handle_integer_followed_by_a_comma,
         "1234,",
         Token::Integer(1234),
         Token::Comma
     );
+
+    #[test]
+    pub fn iterates_over_tokens_until_a_failure() {
+        let tokens: Vec<_> = Tokenizer::new("slide /* comment */ \"a\" // comment\n 🆒 b")
+            .into_iter()
+            .collect();
+
+        assert_eq!(
+            vec![
+                Ok((
+                    Token::KeywordSlide,
+                    SourceLocationRange::new(SourceLocation::new(0, 1), SourceLocation::new(0, 6))
+                )),
+                Ok((
+                    Token::String("a".into()),
+                    SourceLocationRange::new(
+                        SourceLocation::new(0, 21),
+                        SourceLocation::new(0, 23)
+                    )
+                )),
+                Err(TokenizerFailure::new(
+                    SourceLocationRange::new_single(SourceLocation::new(1, 2)),
+                    TokenizerFailureKind::UnexpectedCharacter('🆒')
+                )),
+            ],
+            tokens
+        );
+    }
+
+    #[test]
+    pub fn keeps_comments_with_their_text_when_asked() {
+        let input = "title /* a */ \"b\" // c\nname,";
+        let mut tokenizer = Tokenizer::new(input);
+        tokenizer.set_keep_comments(true);
+        let mut tokens = vec![];
+
+        while let TokenizerResult::Ok(token, _) = tokenizer.next() {
+            tokens.push((token, &input[tokenizer.token_span()]));
+        }
+
+        assert_eq!(
+            vec![
+                (Token::KeywordTitle, "title"),
+                (Token::Comment("/* a */".into()), "/* a */"),
+                (Token::String("b".into()), "\"b\""),
+                (Token::Comment("// c".into()), "// c"),
+                (Token::KeywordName, "name"),
+                (Token::Comma, ","),
+            ],
+            tokens
+        );
+    }
+
+    #[test]
+    pub fn resynchronizes_after_failures_when_recovering() {
+        let input = "name\"x title\n\"a\\q\" b\n\"open,\n{ /* never";
+        let mut tokenizer = Tokenizer::new(input);
+        tokenizer.set_recover(true);
+        let mut results = vec![];
+
+        loop {
+            match tokenizer.next() {
+                TokenizerResult::Ok(token, _) => results.push(Ok(token)),
+                TokenizerResult::Err(failure) => results.push(Err(failure.kind().clone())),
+                TokenizerResult::End => break,
+            }
+        }
+
+        assert_eq!(
+            vec![
+                Err(TokenizerFailureKind::UnexpectedCharacterInName {
+                    index: 4,
+                    character: '"'
+                }),
+                Ok(Token::KeywordTitle),
+                Err(TokenizerFailureKind::UnknownEscapeSequence('q')),
+                Ok(Token::Name("b".into())),
+                Err(TokenizerFailureKind::UnclosedString),
+                Ok(Token::OpeningBrace),
+                Err(TokenizerFailureKind::UnclosedComment),
+            ],
+            results
+        );
+    }
+
+    #[test]
+    pub fn lists_every_keyword() {
+        for keyword in KEYWORDS {
+            let mut tokenizer = Tokenizer::new(keyword);
+
+            match tokenizer.next() {
+                TokenizerResult::Ok(Token::Name(name), _) => panic!("`{}` is not a keyword", name),
+                TokenizerResult::Ok(..) => {}
+                result => panic!("Unexpected result: {:?}", result),
+            }
+        }
+    }
 }
@@ -0,0 +1,81 @@
+use std::time::{Duration, Instant};
+
+/// Clock the videos and animated images of the current slide play by, it
+/// starts over on every slide and stands still while paused
+pub struct Playback {
+    started_at: Instant,
+    paused_at: Option<Instant>,
+}
+
+impl Playback {
+    pub fn new(now: Instant) -> Self {
+        Self {
+            started_at: now,
+            paused_at: None,
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    /// Pauses a playing clock and resumes a paused one where it stopped
+    pub fn toggle(&mut self, now: Instant) {
+        match self.paused_at.take() {
+            Some(paused_at) => self.started_at += now.saturating_duration_since(paused_at),
+            None => self.paused_at = Some(now),
+        }
+    }
+
+    /// Goes back to the beginning, a paused clock stays paused
+    pub fn restart(&mut self, now: Instant) {
+        self.started_at = now;
+        if self.paused_at.is_some() {
+            self.paused_at = Some(now);
+        }
+    }
+
+    /// Time played since the beginning, not counting the pauses
+    pub fn position(&self, now: Instant) -> Duration {
+        self.paused_at
+            .unwrap_or(now)
+            .saturating_duration_since(self.started_at)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn stands_still_while_paused() {
+        let start = Instant::now();
+        let mut playback = Playback::new(start);
+
+        playback.toggle(start + Duration::from_secs(2));
+        assert!(playback.is_paused());
+        assert_eq!(
+            Duration::from_secs(2),
+            playback.position(start + Duration::from_secs(5))
+        );
+
+        playback.toggle(start + Duration::from_secs(5));
+        assert_eq!(
+            Duration::from_secs(3),
+            playback.position(start + Duration::from_secs(6))
+        );
+    }
+
+    #[test]
+    pub fn restarts_from_the_beginning() {
+        let start = Instant::now();
+        let mut playback = Playback::new(start);
+
+        playback.restart(start + Duration::from_secs(4));
+
+        assert_eq!(
+            Duration::from_secs(1),
+            playback.position(start + Duration::from_secs(5))
+        );
+    }
+}
@@ -0,0 +1,162 @@
+use crate::presentation::Color;
+
+const DEFAULT_RADIUS: u32 = 8;
+
+/// A highlight dot following the mouse in place of the cursor, with freehand
+/// strokes drawn while the left button is held, they last until the slide
+/// changes
+pub struct Pointer {
+    enabled: bool,
+    window_id: Option<u32>,
+    position: Option<(i32, i32)>,
+    strokes: Vec<Vec<(i32, i32)>>,
+    drawing: bool,
+    slide: usize,
+    color: Color,
+    radius: u32,
+}
+
+impl Pointer {
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            window_id: None,
+            position: None,
+            strokes: vec![],
+            drawing: false,
+            slide: 0,
+            color: Color::new(0xff, 0x20, 0x20, 0xff),
+            radius: DEFAULT_RADIUS,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Turns the pointer on or off, returns whether it is on now
+    pub fn toggle(&mut self) -> bool {
+        self.enabled = !self.enabled;
+        self.drawing = false;
+
+        self.enabled
+    }
+
+    /// Window the pointer is shown in, mouse events from other windows are
+    /// ignored
+    pub fn set_window_id(&mut self, window_id: Option<u32>) {
+        self.window_id = window_id;
+    }
+
+    pub fn accepts(&self, window_id: u32) -> bool {
+        self.enabled && (self.window_id.is_none() || self.window_id == Some(window_id))
+    }
+
+    pub fn color(&self) -> Color {
+        self.color
+    }
+
+    pub fn set_color(&mut self, color: Color) {
+        self.color = color;
+    }
+
+    pub fn radius(&self) -> u32 {
+        self.radius
+    }
+
+    /// Position in the slide coordinate system
+    pub fn position(&self) -> Option<(i32, i32)> {
+        self.position
+    }
+
+    pub fn strokes(&self) -> &Vec<Vec<(i32, i32)>> {
+        &self.strokes
+    }
+
+    pub fn move_to(&mut self, x: i32, y: i32) {
+        self.position = Some((x, y));
+
+        if self.drawing {
+            if let Some(stroke) = self.strokes.last_mut() {
+                stroke.push((x, y));
+            }
+        }
+    }
+
+    pub fn start_stroke(&mut self, x: i32, y: i32) {
+        self.position = Some((x, y));
+        self.drawing = true;
+        self.strokes.push(vec![(x, y)]);
+    }
+
+    pub fn end_stroke(&mut self) {
+        self.drawing = false;
+    }
+
+    /// Puts back strokes drawn on the slide in an earlier run
+    pub fn restore_strokes(&mut self, slide: usize, strokes: Vec<Vec<(i32, i32)>>) {
+        self.slide = slide;
+        self.strokes = strokes;
+        self.drawing = false;
+    }
+
+    /// Wipes the strokes when the given slide is not the one they were
+    /// drawn on
+    pub fn set_slide(&mut self, slide: usize) {
+        if self.slide != slide {
+            self.slide = slide;
+            self.strokes.clear();
+            self.drawing = false;
+        }
+    }
+}
+
+impl Default for Pointer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn draws_strokes_while_button_is_held() {
+        let mut pointer = Pointer::new();
+        pointer.toggle();
+
+        pointer.move_to(1, 1);
+        pointer.start_stroke(2, 2);
+        pointer.move_to(3, 3);
+        pointer.end_stroke();
+        pointer.move_to(4, 4);
+
+        assert_eq!(&vec![vec![(2, 2), (3, 3)]], pointer.strokes());
+        assert_eq!(Some((4, 4)), pointer.position());
+    }
+
+    #[test]
+    pub fn clears_strokes_on_slide_change() {
+        let mut pointer = Pointer::new();
+        pointer.toggle();
+        pointer.start_stroke(2, 2);
+
+        pointer.set_slide(0);
+        assert_eq!(1, pointer.strokes().len());
+
+        pointer.set_slide(1);
+        assert!(pointer.strokes().is_empty());
+    }
+
+    #[test]
+    pub fn ignores_other_windows() {
+        let mut pointer = Pointer::new();
+        pointer.set_window_id(Some(1));
+        assert!(!pointer.accepts(1));
+
+        pointer.toggle();
+        assert!(pointer.accepts(1));
+        assert!(!pointer.accepts(2));
+    }
+}
@@ -1,19 +1,1338 @@
+use crate::control::Action;
+use crate::easing::Easing;
+use crate::parsing::serializer;
+use std::cmp::Reverse;
 use std::collections::HashMap;
 use std::hash::Hash;
+use std::path::PathBuf;
+use std::time::Duration;
 
 #[derive(Debug, Eq, PartialEq)]
 pub enum StyleError {
     DuplicateFont(FontDescriptor),
+    DuplicateTextStyle(String),
+}
+
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
+pub struct Color {
+    red: u8,
+    green: u8,
+    blue: u8,
+    alpha: u8,
+}
+
+impl Color {
+    pub fn new(red: u8, green: u8, blue: u8, alpha: u8) -> Self {
+        Self {
+            red,
+            green,
+            blue,
+            alpha,
+        }
+    }
+
+    /// Parses `#rrggbb` or `#rrggbbaa`
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        if !hex.starts_with('#') || !hex.is_ascii() {
+            return None;
+        }
+
+        let digits = &hex[1..];
+        let component = |index: usize| u8::from_str_radix(&digits[index..index + 2], 16).ok();
+
+        match digits.len() {
+            6 => Some(Self::new(component(0)?, component(2)?, component(4)?, 0xff)),
+            8 => Some(Self::new(
+                component(0)?,
+                component(2)?,
+                component(4)?,
+                component(6)?,
+            )),
+            _ => None,
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        let (red, green, blue) = match name {
+            "black" => (0x00, 0x00, 0x00),
+            "white" => (0xff, 0xff, 0xff),
+            "gray" | "grey" => (0x80, 0x80, 0x80),
+            "red" => (0xff, 0x00, 0x00),
+            "green" => (0x00, 0x80, 0x00),
+            "blue" => (0x00, 0x00, 0xff),
+            "yellow" => (0xff, 0xff, 0x00),
+            "cyan" => (0x00, 0xff, 0xff),
+            "magenta" => (0xff, 0x00, 0xff),
+            "orange" => (0xff, 0xa5, 0x00),
+            "purple" => (0x80, 0x00, 0x80),
+            "pink" => (0xff, 0xc0, 0xcb),
+            _ => return None,
+        };
+
+        Some(Self::new(red, green, blue, 0xff))
+    }
+
+    pub fn red(self) -> u8 {
+        self.red
+    }
+
+    pub fn green(self) -> u8 {
+        self.green
+    }
+
+    pub fn blue(self) -> u8 {
+        self.blue
+    }
+
+    pub fn alpha(self) -> u8 {
+        self.alpha
+    }
+}
+
+/// Which way a gradient blends from its first color to its second, radial
+/// ones go from the middle out
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
+pub enum GradientDirection {
+    Horizontal,
+    Vertical,
+    Diagonal,
+    Radial,
+}
+
+impl GradientDirection {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "horizontal" => Some(Self::Horizontal),
+            "vertical" => Some(Self::Vertical),
+            "diagonal" => Some(Self::Diagonal),
+            "radial" => Some(Self::Radial),
+            _ => None,
+        }
+    }
+
+    /// The name `from_name` takes
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Horizontal => "horizontal",
+            Self::Vertical => "vertical",
+            Self::Diagonal => "diagonal",
+            Self::Radial => "radial",
+        }
+    }
+}
+
+/// How an image is scaled to the area it fills
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
+pub enum ImageFit {
+    /// Scaled to cover the whole area, cropping what sticks out
+    Cover,
+    /// Scaled to fit inside the area, leaving the rest of it empty
+    Contain,
+    /// Repeated at its own size from the top left corner
+    Tile,
+}
+
+impl ImageFit {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "cover" => Some(Self::Cover),
+            "contain" => Some(Self::Contain),
+            "tile" => Some(Self::Tile),
+            _ => None,
+        }
+    }
+
+    /// The name `from_name` takes
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Cover => "cover",
+            Self::Contain => "contain",
+            Self::Tile => "tile",
+        }
+    }
+}
+
+/// What a slide's background or an element's box is painted with
+#[derive(Debug, Eq, PartialEq, Hash, Clone)]
+pub enum Fill {
+    Color(Color),
+    Gradient {
+        from: Color,
+        to: Color,
+        direction: GradientDirection,
+    },
+    Image {
+        path: String,
+        fit: ImageFit,
+    },
+}
+
+impl Fill {
+    /// Path of the image the fill shows, if it is an image
+    pub fn image_path(&self) -> Option<&String> {
+        match self {
+            Self::Image { path, .. } => Some(path),
+            Self::Color(_) | Self::Gradient { .. } => None,
+        }
+    }
+}
+
+/// A distance, either absolute or relative to the slide or the font size
+#[derive(Debug, Copy, Clone)]
+pub enum Length {
+    Pixels(f64),
+    Percent(f64),
+    Em(f64),
+}
+
+impl Length {
+    /// Turns the length into pixels, percentages are taken of `reference`
+    pub fn resolve(self, reference: u32, font_size: u32) -> u32 {
+        self.offset(reference, font_size).max(0.0).round() as u32
+    }
+
+    /// Turns the length into pixels like `resolve`, keeping fractions and
+    /// negative values for how far something is moved
+    pub fn offset(self, reference: u32, font_size: u32) -> f64 {
+        match self {
+            Self::Pixels(value) => value,
+            Self::Percent(value) => value * f64::from(reference) / 100.0,
+            Self::Em(value) => value * f64::from(font_size),
+        }
+    }
+
+    fn parts(self) -> (u8, u64) {
+        match self {
+            Self::Pixels(value) => (0, value.to_bits()),
+            Self::Percent(value) => (1, value.to_bits()),
+            Self::Em(value) => (2, value.to_bits()),
+        }
+    }
+}
+
+impl PartialEq for Length {
+    fn eq(&self, other: &Self) -> bool {
+        self.parts() == other.parts()
+    }
+}
+
+impl Eq for Length {}
+
+/// Point of an element's box that its position refers to
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Anchor {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "top-left" => Some(Self::TopLeft),
+            "top" => Some(Self::Top),
+            "top-right" => Some(Self::TopRight),
+            "left" => Some(Self::Left),
+            "center" => Some(Self::Center),
+            "right" => Some(Self::Right),
+            "bottom-left" => Some(Self::BottomLeft),
+            "bottom" => Some(Self::Bottom),
+            "bottom-right" => Some(Self::BottomRight),
+            _ => None,
+        }
+    }
+
+    /// The name `from_name` takes
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::TopLeft => "top-left",
+            Self::Top => "top",
+            Self::TopRight => "top-right",
+            Self::Left => "left",
+            Self::Center => "center",
+            Self::Right => "right",
+            Self::BottomLeft => "bottom-left",
+            Self::Bottom => "bottom",
+            Self::BottomRight => "bottom-right",
+        }
+    }
+
+    /// How far the anchor is from the top left corner, in halves of the
+    /// box's width and height
+    pub fn halves(self) -> (u32, u32) {
+        match self {
+            Self::TopLeft => (0, 0),
+            Self::Top => (1, 0),
+            Self::TopRight => (2, 0),
+            Self::Left => (0, 1),
+            Self::Center => (1, 1),
+            Self::Right => (2, 1),
+            Self::BottomLeft => (0, 2),
+            Self::Bottom => (1, 2),
+            Self::BottomRight => (2, 2),
+        }
+    }
+}
+
+/// Layout constraints of an element. Elements with a position or an anchor
+/// are taken out of the slide's flow and placed on their own, a size
+/// changes the element's box either way
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Default)]
+pub struct Placement {
+    position: Option<(Length, Length)>,
+    anchor: Option<Anchor>,
+    size: (Option<Length>, Option<Length>),
+    grow: u32,
+}
+
+impl Placement {
+    /// Where the anchor goes, percentages are taken of the slide size
+    pub fn position(&self) -> Option<(Length, Length)> {
+        self.position
+    }
+
+    pub fn set_position(&mut self, x: Length, y: Length) {
+        self.position = Some((x, y));
+    }
+
+    pub fn anchor(&self) -> Option<Anchor> {
+        self.anchor
+    }
+
+    pub fn set_anchor(&mut self, anchor: Anchor) {
+        self.anchor = Some(anchor);
+    }
+
+    /// Width and height of the element's box, `None` ones are `auto`
+    pub fn size(&self) -> (Option<Length>, Option<Length>) {
+        self.size
+    }
+
+    pub fn set_size(&mut self, width: Option<Length>, height: Option<Length>) {
+        self.size = (width, height);
+    }
+
+    pub fn is_positioned(&self) -> bool {
+        self.position.is_some() || self.anchor.is_some()
+    }
+
+    /// Share of a row's or column's leftover space the element takes
+    pub fn grow(&self) -> u32 {
+        self.grow
+    }
+
+    pub fn set_grow(&mut self, grow: u32) {
+        self.grow = grow;
+    }
+}
+
+/// Edge of the slide an element comes in from
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Side {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+impl Side {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "left" => Some(Self::Left),
+            "right" => Some(Self::Right),
+            "top" => Some(Self::Top),
+            "bottom" => Some(Self::Bottom),
+            _ => None,
+        }
+    }
+
+    /// The name `from_name` takes
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Left => "left",
+            Self::Right => "right",
+            Self::Top => "top",
+            Self::Bottom => "bottom",
+        }
+    }
+}
+
+/// A value a keyframe gives one of the ways an element is drawn
+#[derive(Debug, Copy, Clone)]
+pub enum Motion {
+    /// How far the element is moved right of its place, percentages are
+    /// taken of the slide width
+    X(Length),
+    /// How far the element is moved down from its place, percentages are
+    /// taken of the slide height
+    Y(Length),
+    /// Times the element's size, around its center
+    Scale(f64),
+    /// Degrees the element is turned clockwise around its center
+    Rotate(f64),
+    /// Percent of the element's own opacity that shows
+    Opacity(u32),
+}
+
+impl Motion {
+    /// The name the motion is written with
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::X(_) => "x",
+            Self::Y(_) => "y",
+            Self::Scale(_) => "scale",
+            Self::Rotate(_) => "rotate",
+            Self::Opacity(_) => "opacity",
+        }
+    }
+
+    fn parts(self) -> (u8, (u8, u64)) {
+        match self {
+            Self::X(length) => (0, length.parts()),
+            Self::Y(length) => (1, length.parts()),
+            Self::Scale(value) => (2, (0, value.to_bits())),
+            Self::Rotate(value) => (3, (0, value.to_bits())),
+            Self::Opacity(value) => (4, (0, u64::from(value))),
+        }
+    }
+}
+
+impl PartialEq for Motion {
+    fn eq(&self, other: &Self) -> bool {
+        self.parts() == other.parts()
+    }
+}
+
+impl Eq for Motion {}
+
+/// The values an animation reaches at a time after the element's build step
+/// was revealed, and the easing it moves on to the next keyframe with
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Keyframe {
+    at: Duration,
+    motions: Vec<Motion>,
+    easing: Easing,
+}
+
+impl Keyframe {
+    pub fn new(at: Duration, motions: Vec<Motion>, easing: Easing) -> Self {
+        Self {
+            at,
+            motions,
+            easing,
+        }
+    }
+
+    pub fn at(&self) -> Duration {
+        self.at
+    }
+
+    pub fn motions(&self) -> &Vec<Motion> {
+        &self.motions
+    }
+
+    pub fn easing(&self) -> Easing {
+        self.easing
+    }
+}
+
+/// How an element is drawn over time: how opaque it is, how it is turned,
+/// scaled and skewed, how it comes in once its build step is revealed and
+/// the keyframes it moves through
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Animation {
+    opacity: u32,
+    transformation: Transformation,
+    fade_in: Option<Duration>,
+    slide_in: Option<(Side, Duration)>,
+    easing: Easing,
+    keyframes: Vec<Keyframe>,
+}
+
+impl Default for Animation {
+    fn default() -> Self {
+        Self {
+            opacity: 100,
+            transformation: Transformation::default(),
+            fade_in: None,
+            slide_in: None,
+            easing: Easing::default(),
+            keyframes: vec![],
+        }
+    }
+}
+
+impl Animation {
+    /// Percent of the element that shows, 100 for an opaque one
+    pub fn opacity(&self) -> u32 {
+        self.opacity
+    }
+
+    pub fn set_opacity(&mut self, opacity: u32) {
+        self.opacity = opacity;
+    }
+
+    /// Degrees the element is turned clockwise around its center, keyframes
+    /// turn it further
+    pub fn rotation(&self) -> f64 {
+        self.transformation.rotation
+    }
+
+    pub fn set_rotation(&mut self, degrees: f64) {
+        self.transformation.rotation = degrees;
+    }
+
+    /// Times the element's size around its center, keyframes scale it
+    /// further
+    pub fn scale(&self) -> f64 {
+        self.transformation.scale
+    }
+
+    pub fn set_scale(&mut self, scale: f64) {
+        self.transformation.scale = scale;
+    }
+
+    /// Degrees the element's vertical edges and its horizontal ones are
+    /// slanted by around its center, like CSS's `skew(x, y)`
+    pub fn skew(&self) -> (f64, f64) {
+        self.transformation.skew
+    }
+
+    pub fn set_skew(&mut self, x: f64, y: f64) {
+        self.transformation.skew = (x, y);
+    }
+
+    /// How long the element takes to fade in from transparent
+    pub fn fade_in(&self) -> Option<Duration> {
+        self.fade_in
+    }
+
+    pub fn set_fade_in(&mut self, duration: Duration) {
+        self.fade_in = Some(duration);
+    }
+
+    /// Edge the element slides in from and how long it takes to get to its
+    /// place
+    pub fn slide_in(&self) -> Option<(Side, Duration)> {
+        self.slide_in
+    }
+
+    pub fn set_slide_in(&mut self, side: Side, duration: Duration) {
+        self.slide_in = Some((side, duration));
+    }
+
+    /// How the element fades and slides in
+    pub fn easing(&self) -> Easing {
+        self.easing
+    }
+
+    pub fn set_easing(&mut self, easing: Easing) {
+        self.easing = easing;
+    }
+
+    /// Keyframes in the order of their times
+    pub fn keyframes(&self) -> &Vec<Keyframe> {
+        &self.keyframes
+    }
+
+    /// Sorts the keyframes by their times, ones at the same time keep their
+    /// order
+    pub fn set_keyframes(&mut self, mut keyframes: Vec<Keyframe>) {
+        keyframes.sort_by_key(Keyframe::at);
+        self.keyframes = keyframes;
+    }
+
+    /// How long until the element has come in and reached its last
+    /// keyframe
+    pub fn length(&self) -> Duration {
+        let fade_in = self.fade_in.unwrap_or_default();
+        let slide_in = self
+            .slide_in
+            .map_or(Duration::default(), |(_, duration)| duration);
+        let keyframes = self
+            .keyframes
+            .last()
+            .map_or(Duration::default(), Keyframe::at);
+
+        fade_in.max(slide_in).max(keyframes)
+    }
+}
+
+/// How an element is turned, scaled and skewed around its center
+#[derive(Debug, Copy, Clone)]
+struct Transformation {
+    rotation: f64,
+    scale: f64,
+    skew: (f64, f64),
+}
+
+impl Default for Transformation {
+    fn default() -> Self {
+        Self {
+            rotation: 0.0,
+            scale: 1.0,
+            skew: (0.0, 0.0),
+        }
+    }
+}
+
+impl Transformation {
+    fn parts(self) -> [u64; 4] {
+        [
+            self.rotation.to_bits(),
+            self.scale.to_bits(),
+            self.skew.0.to_bits(),
+            self.skew.1.to_bits(),
+        ]
+    }
+}
+
+impl PartialEq for Transformation {
+    fn eq(&self, other: &Self) -> bool {
+        self.parts() == other.parts()
+    }
+}
+
+impl Eq for Transformation {}
+
+/// Where the children of a row or column go across it
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Align {
+    Start,
+    Center,
+    End,
+}
+
+impl Align {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "start" => Some(Self::Start),
+            "center" => Some(Self::Center),
+            "end" => Some(Self::End),
+            _ => None,
+        }
+    }
+
+    /// The name `from_name` takes
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Start => "start",
+            Self::Center => "center",
+            Self::End => "end",
+        }
+    }
+}
+
+/// How strongly glyph outlines are snapped to the pixel grid, stronger
+/// hinting is crisper at small sizes but distorts the letter shapes more
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Hinting {
+    Normal,
+    Light,
+    Mono,
+    None,
+}
+
+impl Hinting {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "normal" => Some(Self::Normal),
+            "light" => Some(Self::Light),
+            "mono" => Some(Self::Mono),
+            "none" => Some(Self::None),
+            _ => None,
+        }
+    }
+
+    /// The name `from_name` takes
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Normal => "normal",
+            Self::Light => "light",
+            Self::Mono => "mono",
+            Self::None => "none",
+        }
+    }
+}
+
+/// Elements laid out one after another, along a row or down a column
+#[derive(Debug, Eq, PartialEq)]
+pub struct Container {
+    elements: Vec<SlideElement>,
+    gap: Length,
+    align: Align,
+    background: Option<Fill>,
+    placement: Placement,
+    animation: Animation,
+}
+
+impl Container {
+    pub fn new(elements: Vec<SlideElement>) -> Self {
+        Self {
+            elements,
+            gap: Length::Pixels(0.0),
+            align: Align::Center,
+            background: None,
+            placement: Placement::default(),
+            animation: Animation::default(),
+        }
+    }
+
+    pub fn elements(&self) -> &Vec<SlideElement> {
+        &self.elements
+    }
+
+    /// Space between neighbouring children
+    pub fn gap(&self) -> Length {
+        self.gap
+    }
+
+    pub fn set_gap(&mut self, gap: Length) {
+        self.gap = gap;
+    }
+
+    pub fn align(&self) -> Align {
+        self.align
+    }
+
+    pub fn set_align(&mut self, align: Align) {
+        self.align = align;
+    }
+
+    /// Fill drawn behind the children, over the whole box of the container
+    pub fn background(&self) -> Option<&Fill> {
+        self.background.as_ref()
+    }
+
+    pub fn set_background(&mut self, background: Fill) {
+        self.background = Some(background);
+    }
+
+    pub fn placement(&self) -> &Placement {
+        &self.placement
+    }
+
+    pub fn set_placement(&mut self, placement: Placement) {
+        self.placement = placement;
+    }
+
+    pub fn animation(&self) -> &Animation {
+        &self.animation
+    }
+
+    pub fn set_animation(&mut self, animation: Animation) {
+        self.animation = animation;
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct List {
+    items: Vec<String>,
+    bullet: String,
+    indent: Length,
+    placement: Placement,
+    animation: Animation,
+}
+
+impl List {
+    pub fn new(items: Vec<String>, bullet: String, indent: Length) -> Self {
+        Self {
+            items,
+            bullet,
+            indent,
+            placement: Placement::default(),
+            animation: Animation::default(),
+        }
+    }
+
+    pub fn items(&self) -> &Vec<String> {
+        &self.items
+    }
+
+    pub fn bullet(&self) -> &String {
+        &self.bullet
+    }
+
+    pub fn indent(&self) -> Length {
+        self.indent
+    }
+
+    pub fn placement(&self) -> &Placement {
+        &self.placement
+    }
+
+    pub fn set_placement(&mut self, placement: Placement) {
+        self.placement = placement;
+    }
+
+    pub fn animation(&self) -> &Animation {
+        &self.animation
+    }
+
+    pub fn set_animation(&mut self, animation: Animation) {
+        self.animation = animation;
+    }
+}
+
+/// Part of a text drawn in a single font variant and color
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Span {
+    content: String,
+    bold: bool,
+    italic: bool,
+    color: Option<Color>,
+}
+
+impl Span {
+    pub fn new(content: String) -> Self {
+        Self {
+            content,
+            bold: false,
+            italic: false,
+            color: None,
+        }
+    }
+
+    pub fn content(&self) -> &String {
+        &self.content
+    }
+
+    pub fn bold(&self) -> bool {
+        self.bold
+    }
+
+    pub fn set_bold(&mut self, bold: bool) {
+        self.bold = bold;
+    }
+
+    pub fn italic(&self) -> bool {
+        self.italic
+    }
+
+    pub fn set_italic(&mut self, italic: bool) {
+        self.italic = italic;
+    }
+
+    /// Color overriding the one of the text
+    pub fn color(&self) -> Option<Color> {
+        self.color
+    }
+
+    pub fn set_color(&mut self, color: Color) {
+        self.color = Some(color);
+    }
+
+    /// Whether the span looks like the rest of the text
+    pub fn is_plain(&self) -> bool {
+        !self.bold && !self.italic && self.color.is_none()
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct Text {
+    content: String,
+    spans: Vec<Span>,
+    style: Option<String>,
+    size: Option<u32>,
+    placement: Placement,
+    animation: Animation,
+}
+
+impl Text {
+    pub fn new(content: String, style: Option<String>) -> Self {
+        Self::with_spans(vec![Span::new(content)], style)
+    }
+
+    /// Text made of differently styled parts
+    pub fn with_spans(spans: Vec<Span>, style: Option<String>) -> Self {
+        Self {
+            content: spans.iter().map(|span| span.content.as_str()).collect(),
+            spans,
+            style,
+            size: None,
+            placement: Placement::default(),
+            animation: Animation::default(),
+        }
+    }
+
+    /// The whole text, without any styling
+    pub fn content(&self) -> &String {
+        &self.content
+    }
+
+    pub fn spans(&self) -> &Vec<Span> {
+        &self.spans
+    }
+
+    /// Name of the text style from `Style::text_style`
+    pub fn style(&self) -> Option<&String> {
+        self.style.as_ref()
+    }
+
+    pub fn set_style(&mut self, style: String) {
+        self.style = Some(style);
+    }
+
+    /// Font size overriding the one from the text style
+    pub fn size(&self) -> Option<u32> {
+        self.size
+    }
+
+    pub fn set_size(&mut self, size: u32) {
+        self.size = Some(size);
+    }
+
+    pub fn placement(&self) -> &Placement {
+        &self.placement
+    }
+
+    pub fn set_placement(&mut self, placement: Placement) {
+        self.placement = placement;
+    }
+
+    pub fn animation(&self) -> &Animation {
+        &self.animation
+    }
+
+    pub fn set_animation(&mut self, animation: Animation) {
+        self.animation = animation;
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct Image {
+    path: String,
+    placement: Placement,
+    animation: Animation,
+}
+
+impl Image {
+    pub fn new(path: String) -> Self {
+        Self {
+            path,
+            placement: Placement::default(),
+            animation: Animation::default(),
+        }
+    }
+
+    pub fn path(&self) -> &String {
+        &self.path
+    }
+
+    pub fn placement(&self) -> &Placement {
+        &self.placement
+    }
+
+    pub fn set_placement(&mut self, placement: Placement) {
+        self.placement = placement;
+    }
+
+    pub fn animation(&self) -> &Animation {
+        &self.animation
+    }
+
+    pub fn set_animation(&mut self, animation: Animation) {
+        self.animation = animation;
+    }
+}
+
+/// A video file played inline on the slide
+#[derive(Debug, Eq, PartialEq)]
+pub struct Video {
+    path: String,
+    placement: Placement,
+    animation: Animation,
+}
+
+impl Video {
+    pub fn new(path: String) -> Self {
+        Self {
+            path,
+            placement: Placement::default(),
+            animation: Animation::default(),
+        }
+    }
+
+    pub fn path(&self) -> &String {
+        &self.path
+    }
+
+    pub fn placement(&self) -> &Placement {
+        &self.placement
+    }
+
+    pub fn set_placement(&mut self, placement: Placement) {
+        self.placement = placement;
+    }
+
+    pub fn animation(&self) -> &Animation {
+        &self.animation
+    }
+
+    pub fn set_animation(&mut self, animation: Animation) {
+        self.animation = animation;
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum SlideElement {
+    Text(Text),
+    /// Heading laid out at the top of the slide, unlike the metadata title
+    Title(Text),
+    /// Heading laid out under the title
+    Subtitle(Text),
+    List(List),
+    Image(Image),
+    Video(Video),
+    Row(Container),
+    Column(Container),
+}
+
+impl SlideElement {
+    pub fn placement(&self) -> &Placement {
+        match self {
+            Self::Text(text) | Self::Title(text) | Self::Subtitle(text) => text.placement(),
+            Self::List(list) => list.placement(),
+            Self::Image(image) => image.placement(),
+            Self::Video(video) => video.placement(),
+            Self::Row(container) | Self::Column(container) => container.placement(),
+        }
+    }
+
+    pub fn set_placement(&mut self, placement: Placement) {
+        match self {
+            Self::Text(text) | Self::Title(text) | Self::Subtitle(text) => {
+                text.set_placement(placement);
+            }
+            Self::List(list) => list.set_placement(placement),
+            Self::Image(image) => image.set_placement(placement),
+            Self::Video(video) => video.set_placement(placement),
+            Self::Row(container) | Self::Column(container) => container.set_placement(placement),
+        }
+    }
+
+    pub fn animation(&self) -> &Animation {
+        match self {
+            Self::Text(text) | Self::Title(text) | Self::Subtitle(text) => text.animation(),
+            Self::List(list) => list.animation(),
+            Self::Image(image) => image.animation(),
+            Self::Video(video) => video.animation(),
+            Self::Row(container) | Self::Column(container) => container.animation(),
+        }
+    }
+
+    pub fn set_animation(&mut self, animation: Animation) {
+        match self {
+            Self::Text(text) | Self::Title(text) | Self::Subtitle(text) => {
+                text.set_animation(animation);
+            }
+            Self::List(list) => list.set_animation(animation),
+            Self::Image(image) => image.set_animation(animation),
+            Self::Video(video) => video.set_animation(animation),
+            Self::Row(container) | Self::Column(container) => container.set_animation(animation),
+        }
+    }
+
+    /// The element followed by every element nested in it, depth first
+    pub fn walk(&self) -> Vec<&Self> {
+        let mut elements = vec![self];
+        if let Self::Row(container) | Self::Column(container) = self {
+            elements.extend(container.elements().iter().flat_map(Self::walk));
+        }
+
+        elements
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum TransitionKind {
+    None,
+    Fade,
+    Slide,
+}
+
+impl TransitionKind {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "none" => Some(Self::None),
+            "fade" => Some(Self::Fade),
+            "slide" => Some(Self::Slide),
+            _ => None,
+        }
+    }
+
+    /// The name transitions are written with
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Fade => "fade",
+            Self::Slide => "slide",
+        }
+    }
+}
+
+/// How the previous slide gives way to the next one
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct Transition {
+    kind: TransitionKind,
+    duration: Duration,
+    easing: Easing,
+}
+
+impl Transition {
+    pub fn new(kind: TransitionKind, duration: Duration) -> Self {
+        Self {
+            kind,
+            duration,
+            easing: Easing::default(),
+        }
+    }
+
+    pub fn kind(&self) -> TransitionKind {
+        self.kind
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    pub fn easing(&self) -> Easing {
+        self.easing
+    }
+
+    pub fn set_easing(&mut self, easing: Easing) {
+        self.easing = easing;
+    }
 }
 
 #[derive(Debug, Eq, PartialEq)]
 pub struct Slide {
     name: String,
+    elements: Vec<SlideElement>,
+    element_steps: Vec<u32>,
+    element_placeholders: Vec<Option<String>>,
+    background: Option<Fill>,
+    layout: Option<String>,
+    notes: Vec<String>,
+    transition: Option<Transition>,
+    duration: Option<Duration>,
+    target: Option<Duration>,
+    scheduled_at: Option<Duration>,
+    hidden: bool,
 }
 
 impl Slide {
-    pub fn new(name: String) -> Self {
-        Self { name }
+    pub fn new(name: String, elements: Vec<SlideElement>) -> Self {
+        Self {
+            name,
+            element_steps: vec![0; elements.len()],
+            element_placeholders: vec![None; elements.len()],
+            elements,
+            background: None,
+            layout: None,
+            notes: vec![],
+            transition: None,
+            duration: None,
+            target: None,
+            scheduled_at: None,
+            hidden: false,
+        }
+    }
+
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    pub fn elements(&self) -> &Vec<SlideElement> {
+        &self.elements
+    }
+
+    pub fn push_element(&mut self, element: SlideElement) {
+        self.push_element_in_step(element, 0);
+    }
+
+    /// Adds an element revealed at the given build step, step 0 is shown as
+    /// soon as the slide comes up
+    pub fn push_element_in_step(&mut self, element: SlideElement, step: u32) {
+        self.push_element_in_placeholder(element, step, None);
+    }
+
+    /// Adds an element that takes the place of the named placeholder of the
+    /// slide's layout
+    pub fn push_element_in_placeholder(
+        &mut self,
+        element: SlideElement,
+        step: u32,
+        placeholder: Option<String>,
+    ) {
+        self.elements.push(element);
+        self.element_steps.push(step);
+        self.element_placeholders.push(placeholder);
+    }
+
+    /// Build step at which the element with the given index is revealed
+    pub fn step_of(&self, element: usize) -> u32 {
+        self.element_steps.get(element).copied().unwrap_or(0)
+    }
+
+    /// Layout placeholder filled by the element with the given index
+    pub fn placeholder_of(&self, element: usize) -> Option<&String> {
+        self.element_placeholders
+            .get(element)
+            .and_then(Option::as_ref)
+    }
+
+    /// Number of key presses needed to reveal every element
+    pub fn step_count(&self) -> u32 {
+        self.element_steps.iter().copied().max().unwrap_or(0)
+    }
+
+    pub fn background(&self) -> Option<&Fill> {
+        self.background.as_ref()
+    }
+
+    pub fn set_background(&mut self, background: Fill) {
+        self.background = Some(background);
+    }
+
+    /// Name of the layout from `Presentation::layout`
+    pub fn layout(&self) -> Option<&String> {
+        self.layout.as_ref()
+    }
+
+    pub fn set_layout(&mut self, layout: String) {
+        self.layout = Some(layout);
+    }
+
+    /// Speaker notes, never shown to the audience
+    pub fn notes(&self) -> &Vec<String> {
+        &self.notes
+    }
+
+    pub fn add_note(&mut self, note: String) {
+        self.notes.push(note);
+    }
+
+    /// Transition used when entering this slide, overrides the one given in
+    /// the metadata
+    pub fn transition(&self) -> Option<Transition> {
+        self.transition
+    }
+
+    pub fn set_transition(&mut self, transition: Transition) {
+        self.transition = Some(transition);
+    }
+
+    /// How long each build step stays up before the presentation advances
+    /// on its own
+    pub fn duration(&self) -> Option<Duration> {
+        self.duration
+    }
+
+    pub fn set_duration(&mut self, duration: Duration) {
+        self.duration = Some(duration);
+    }
+
+    /// How long the speaker plans to spend on the slide, see `--rehearse`
+    pub fn target(&self) -> Option<Duration> {
+        self.target
+    }
+
+    pub fn set_target(&mut self, target: Duration) {
+        self.target = Some(target);
+    }
+
+    /// Time into the talk the slide is planned to come up at
+    pub fn scheduled_at(&self) -> Option<Duration> {
+        self.scheduled_at
+    }
+
+    pub fn set_scheduled_at(&mut self, scheduled_at: Duration) {
+        self.scheduled_at = Some(scheduled_at);
+    }
+
+    /// Whether the slide is left out unless drafting
+    pub fn is_hidden(&self) -> bool {
+        self.hidden
+    }
+
+    pub fn set_hidden(&mut self, hidden: bool) {
+        self.hidden = hidden;
+    }
+
+    /// The slide written as a `slide` block of DSL source
+    pub fn to_source(&self) -> String {
+        serializer::slide_source(self)
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum LayoutItem {
+    Element(Box<SlideElement>),
+    /// Filled with the elements a slide puts under this name
+    Placeholder(String),
+}
+
+/// A slide element with its build step and the placeholder it fills
+type PlacedElement<'s> = (&'s SlideElement, u32, Option<&'s String>);
+
+/// Arrangement of elements shared by the slides that use it
+#[derive(Debug, Eq, PartialEq)]
+pub struct Layout {
+    items: Vec<LayoutItem>,
+}
+
+impl Layout {
+    pub fn new(items: Vec<LayoutItem>) -> Self {
+        Self { items }
+    }
+
+    pub fn items(&self) -> &Vec<LayoutItem> {
+        &self.items
+    }
+
+    pub fn placeholders(&self) -> Vec<&String> {
+        self.items
+            .iter()
+            .filter_map(|item| match item {
+                LayoutItem::Placeholder(name) => Some(name),
+                LayoutItem::Element(_) => None,
+            })
+            .collect()
+    }
+
+    fn arrange<'s>(&'s self, slide_elements: &[PlacedElement<'s>]) -> Vec<(&'s SlideElement, u32)> {
+        let placeholders = self.placeholders();
+        let mut elements = vec![];
+
+        for item in &self.items {
+            match item {
+                LayoutItem::Element(element) => elements.push((&**element, 0)),
+                LayoutItem::Placeholder(name) => elements.extend(
+                    slide_elements
+                        .iter()
+                        .filter(|(_, _, placeholder)| *placeholder == Some(name))
+                        .map(|(element, step, _)| (*element, *step)),
+                ),
+            }
+        }
+        elements.extend(
+            slide_elements
+                .iter()
+                .filter(|(_, _, placeholder)| match placeholder {
+                    Some(name) => !placeholders.contains(name),
+                    None => true,
+                })
+                .map(|(element, step, _)| (*element, *step)),
+        );
+
+        elements
     }
 }
 
@@ -24,36 +1343,178 @@ pub struct FontDescriptor {
     italic: bool,
 }
 
-#[derive(Debug, Eq, PartialEq)]
-pub struct Font {
-    path: String,
-    descriptor: FontDescriptor,
-}
+impl FontDescriptor {
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    pub fn weight(&self) -> u32 {
+        self.weight
+    }
+
+    pub fn italic(&self) -> bool {
+        self.italic
+    }
+}
+
+/// Where the font file comes from
+#[derive(Debug, Eq, PartialEq)]
+pub enum FontSource {
+    File(String),
+    /// Installed font found by its descriptor when the presentation is loaded
+    System,
+    /// Font compiled into przntr, used when the presentation declares none
+    Bundled,
+}
+
+/// Family name of the bundled font, for text styles to refer to
+pub const BUNDLED_FONT_NAME: &str = "przntr-sans";
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct Font {
+    source: FontSource,
+    descriptor: FontDescriptor,
+    size: Option<u32>,
+}
+
+impl Font {
+    pub fn new(name: String, path: String, weight: u32, italic: bool) -> Self {
+        Self::with_source(FontSource::File(path), name, weight, italic)
+    }
+
+    pub fn system(name: String, weight: u32, italic: bool) -> Self {
+        Self::with_source(FontSource::System, name, weight, italic)
+    }
+
+    /// The bundled font closest to the weight, it only comes upright
+    pub fn bundled(weight: u32) -> Self {
+        Self::with_source(
+            FontSource::Bundled,
+            BUNDLED_FONT_NAME.to_string(),
+            weight,
+            false,
+        )
+    }
+
+    fn with_source(source: FontSource, name: String, weight: u32, italic: bool) -> Self {
+        Self {
+            source,
+            descriptor: FontDescriptor {
+                name,
+                weight,
+                italic,
+            },
+            size: None,
+        }
+    }
+
+    pub fn name(&self) -> &String {
+        &self.descriptor.name
+    }
+
+    pub fn weight(&self) -> u32 {
+        self.descriptor.weight
+    }
+
+    pub fn italic(&self) -> bool {
+        self.descriptor.italic
+    }
+
+    pub fn descriptor(&self) -> &FontDescriptor {
+        &self.descriptor
+    }
+
+    pub fn source(&self) -> &FontSource {
+        &self.source
+    }
+
+    /// Size used when neither the text style nor the element sets one
+    pub fn size(&self) -> Option<u32> {
+        self.size
+    }
+
+    pub fn set_size(&mut self, size: u32) {
+        self.size = Some(size);
+    }
+}
+
+/// Named set of text properties that slide elements can refer to, each
+/// unset property falls back to the deck defaults
+#[derive(Debug, Eq, PartialEq, Default)]
+pub struct TextStyle {
+    font: Option<String>,
+    size: Option<u32>,
+    color: Option<Color>,
+    weight: Option<u32>,
+    italic: Option<bool>,
+    fallback_fonts: Vec<String>,
+}
+
+impl TextStyle {
+    pub fn new(font: Option<String>, size: Option<u32>, color: Option<Color>) -> Self {
+        Self {
+            font,
+            size,
+            color,
+            weight: None,
+            italic: None,
+            fallback_fonts: vec![],
+        }
+    }
+
+    pub fn font(&self) -> Option<&String> {
+        self.font.as_ref()
+    }
+
+    /// Fonts tried in order for characters the main font has no glyphs for
+    pub fn fallback_fonts(&self) -> &Vec<String> {
+        &self.fallback_fonts
+    }
+
+    pub fn set_fallback_fonts(&mut self, fallback_fonts: Vec<String>) {
+        self.fallback_fonts = fallback_fonts;
+    }
+
+    pub fn weight(&self) -> Option<u32> {
+        self.weight
+    }
+
+    pub fn set_weight(&mut self, weight: u32) {
+        self.weight = Some(weight);
+    }
+
+    pub fn italic(&self) -> Option<bool> {
+        self.italic
+    }
+
+    pub fn set_italic(&mut self, italic: bool) {
+        self.italic = Some(italic);
+    }
 
-impl Font {
-    pub fn new(name: String, path: String, weight: u32, italic: bool) -> Self {
-        Self {
-            path,
-            descriptor: FontDescriptor {
-                name,
-                weight,
-                italic,
-            },
-        }
+    pub fn size(&self) -> Option<u32> {
+        self.size
     }
 
-    pub fn path(&self) -> &String {
-        &self.path
+    pub fn color(&self) -> Option<Color> {
+        self.color
     }
 }
 
 #[derive(Debug, Eq, PartialEq)]
 pub struct Style {
     fonts: HashMap<FontDescriptor, Font>,
+    background: Option<Fill>,
+    text_color: Option<Color>,
+    text_styles: HashMap<String, TextStyle>,
+    default_font: Option<FontDescriptor>,
+    hinting: Option<Hinting>,
+    antialias: Option<bool>,
+    kerning: Option<bool>,
 }
 
 impl Style {
     pub fn new(fonts_input: Vec<Font>) -> Result<Self, StyleError> {
+        let default_font = fonts_input.first().map(|font| font.descriptor.clone());
         let mut fonts = HashMap::new();
         for font in fonts_input {
             if let Some(font) = fonts.insert(font.descriptor.clone(), font) {
@@ -61,45 +1522,659 @@ impl Style {
             }
         }
 
-        Ok(Self { fonts })
+        Ok(Self {
+            fonts,
+            background: None,
+            text_color: None,
+            text_styles: HashMap::new(),
+            default_font,
+            hinting: None,
+            antialias: None,
+            kerning: None,
+        })
     }
 
     pub fn empty() -> Self {
         Self {
             fonts: HashMap::new(),
+            background: None,
+            text_color: None,
+            text_styles: HashMap::new(),
+            default_font: None,
+            hinting: None,
+            antialias: None,
+            kerning: None,
         }
     }
 
     pub fn fonts(&self) -> Vec<&Font> {
         self.fonts.values().collect()
     }
+
+    /// The font declared first, used for text without an explicit font
+    pub fn default_font(&self) -> Option<&Font> {
+        self.default_font
+            .as_ref()
+            .and_then(|descriptor| self.fonts.get(descriptor))
+    }
+
+    /// Finds the font of the given family, preferring the requested slant and
+    /// then the closest weight, the heavier one when two are equally close
+    pub fn font(&self, name: &str, weight: u32, italic: bool) -> Option<&Font> {
+        self.fonts
+            .values()
+            .filter(|font| font.name() == name)
+            .min_by_key(|font| {
+                (
+                    font.italic() != italic,
+                    (i64::from(font.weight()) - i64::from(weight)).abs(),
+                    Reverse(font.weight()),
+                )
+            })
+    }
+
+    pub fn background(&self) -> Option<&Fill> {
+        self.background.as_ref()
+    }
+
+    pub fn set_background(&mut self, background: Fill) {
+        self.background = Some(background);
+    }
+
+    pub fn text_color(&self) -> Option<Color> {
+        self.text_color
+    }
+
+    pub fn set_text_color(&mut self, text_color: Color) {
+        self.text_color = Some(text_color);
+    }
+
+    pub fn hinting(&self) -> Hinting {
+        self.hinting.unwrap_or(Hinting::Normal)
+    }
+
+    pub fn set_hinting(&mut self, hinting: Hinting) {
+        self.hinting = Some(hinting);
+    }
+
+    /// Whether text edges are smoothed, aliased text is drawn in the solid
+    /// text color only
+    pub fn antialias(&self) -> bool {
+        self.antialias.unwrap_or(true)
+    }
+
+    pub fn set_antialias(&mut self, antialias: bool) {
+        self.antialias = Some(antialias);
+    }
+
+    /// Whether the spacing between pairs of letters is adjusted by the
+    /// font's kerning table
+    pub fn kerning(&self) -> bool {
+        self.kerning.unwrap_or(true)
+    }
+
+    pub fn set_kerning(&mut self, kerning: bool) {
+        self.kerning = Some(kerning);
+    }
+
+    pub fn text_style(&self, name: &str) -> Option<&TextStyle> {
+        self.text_styles.get(name)
+    }
+
+    pub fn text_style_names(&self) -> Vec<&String> {
+        self.text_styles.keys().collect()
+    }
+
+    pub fn add_text_style(
+        &mut self,
+        name: String,
+        text_style: TextStyle,
+    ) -> Result<(), StyleError> {
+        if self.text_styles.contains_key(&name) {
+            return Err(StyleError::DuplicateTextStyle(name));
+        }
+
+        self.text_styles.insert(name, text_style);
+
+        Ok(())
+    }
+
+    /// Puts everything the other style sets over this one: its fonts and
+    /// text styles replace the ones of the same name, and its default font
+    /// and settings win where it has them
+    pub fn merge(&mut self, overrides: Self) {
+        self.fonts.extend(overrides.fonts);
+        self.text_styles.extend(overrides.text_styles);
+        self.default_font = overrides.default_font.or_else(|| self.default_font.take());
+        self.background = overrides.background.or_else(|| self.background.take());
+        self.text_color = overrides.text_color.or(self.text_color);
+        self.hinting = overrides.hinting.or(self.hinting);
+        self.antialias = overrides.antialias.or(self.antialias);
+        self.kerning = overrides.kerning.or(self.kerning);
+    }
+
+    /// The style written as a `style` block of DSL source
+    pub fn to_source(&self) -> String {
+        serializer::style_source(self)
+    }
+}
+
+/// Style and layouts kept in a file of their own, for presentations to
+/// share. A presentation's own style and layouts go over its theme's.
+#[derive(Debug, Eq, PartialEq)]
+pub struct Theme {
+    style: Style,
+    layouts: HashMap<String, Layout>,
+    files: Vec<PathBuf>,
+}
+
+impl Theme {
+    pub fn new(style: Style) -> Self {
+        Self {
+            style,
+            layouts: HashMap::new(),
+            files: vec![],
+        }
+    }
+
+    /// Theme every presentation is styled with underneath its own theme,
+    /// giving decks that declare no fonts the bundled ones
+    pub fn built_in() -> Self {
+        let style = Style::new(vec![Font::bundled(400), Font::bundled(700)])
+            .expect("the bundled fonts have different weights");
+
+        Self::new(style)
+    }
+
+    pub fn style(&self) -> &Style {
+        &self.style
+    }
+
+    pub fn layout(&self, name: &str) -> Option<&Layout> {
+        self.layouts.get(name)
+    }
+
+    pub fn add_layout(&mut self, name: String, layout: Layout) {
+        self.layouts.insert(name, layout);
+    }
+
+    /// The theme file and the files it includes
+    pub fn files(&self) -> &Vec<PathBuf> {
+        &self.files
+    }
+
+    pub fn add_file(&mut self, path: PathBuf) {
+        self.files.push(path);
+    }
+}
+
+/// Named part of the talk, from its first slide up to the next section
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Section {
+    title: String,
+    first_slide: usize,
+}
+
+impl Section {
+    pub fn new(title: String, first_slide: usize) -> Self {
+        Self { title, first_slide }
+    }
+
+    pub fn title(&self) -> &String {
+        &self.title
+    }
+
+    pub fn first_slide(&self) -> usize {
+        self.first_slide
+    }
+}
+
+/// Size of the slide coordinate system, independent of the window size
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct Resolution {
+    width: u32,
+    height: u32,
+}
+
+impl Resolution {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self { width, height }
+    }
+
+    pub fn width(self) -> u32 {
+        self.width
+    }
+
+    pub fn height(self) -> u32 {
+        self.height
+    }
+
+    pub fn set_width(&mut self, width: u32) {
+        self.width = width;
+    }
+
+    pub fn set_height(&mut self, height: u32) {
+        self.height = height;
+    }
+}
+
+impl Default for Resolution {
+    fn default() -> Self {
+        Self::new(800, 600)
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct Metadata {
+    title: String,
+    resolution: Resolution,
+    transition: Option<Transition>,
+    theme: Option<String>,
+    controls: Vec<ControlInput>,
+    show_progress: bool,
+    show_page_numbers: bool,
+    show_footer: bool,
+    spelling_ignore: Vec<String>,
+    font_dirs: Vec<String>,
+}
+
+impl Metadata {
+    pub fn new(title: String, resolution: Resolution) -> Self {
+        Self {
+            title,
+            resolution,
+            transition: None,
+            theme: None,
+            controls: vec![],
+            show_progress: false,
+            show_page_numbers: false,
+            show_footer: false,
+            spelling_ignore: vec![],
+            font_dirs: vec![],
+        }
+    }
+
+    pub fn title(&self) -> &String {
+        &self.title
+    }
+
+    pub fn resolution(&self) -> Resolution {
+        self.resolution
+    }
+
+    /// Transition used by slides which don't specify their own
+    pub fn transition(&self) -> Option<Transition> {
+        self.transition
+    }
+
+    pub fn set_transition(&mut self, transition: Transition) {
+        self.transition = Some(transition);
+    }
+
+    /// Name of the theme file the presentation is styled with
+    pub fn theme(&self) -> Option<&String> {
+        self.theme.as_ref()
+    }
+
+    pub fn set_theme(&mut self, theme: String) {
+        self.theme = Some(theme);
+    }
+
+    /// OSC and MIDI inputs the presentation is driven from
+    pub fn controls(&self) -> &Vec<ControlInput> {
+        &self.controls
+    }
+
+    pub fn add_control(&mut self, control: ControlInput) {
+        self.controls.push(control);
+    }
+
+    /// Whether a thin bar along the bottom shows how far into the deck the
+    /// current slide is
+    pub fn show_progress(&self) -> bool {
+        self.show_progress
+    }
+
+    pub fn set_show_progress(&mut self, show_progress: bool) {
+        self.show_progress = show_progress;
+    }
+
+    /// Whether the slide number and count are shown in the bottom right
+    /// corner, e.g. `7 / 42`
+    pub fn show_page_numbers(&self) -> bool {
+        self.show_page_numbers
+    }
+
+    pub fn set_show_page_numbers(&mut self, show_page_numbers: bool) {
+        self.show_page_numbers = show_page_numbers;
+    }
+
+    /// Whether the deck's title is shown in the bottom left corner
+    pub fn show_footer(&self) -> bool {
+        self.show_footer
+    }
+
+    pub fn set_show_footer(&mut self, show_footer: bool) {
+        self.show_footer = show_footer;
+    }
+
+    /// Words the spell checker accepts, such as product names and jargon
+    pub fn spelling_ignore(&self) -> &Vec<String> {
+        &self.spelling_ignore
+    }
+
+    pub fn add_spelling_ignore(&mut self, word: String) {
+        self.spelling_ignore.push(word);
+    }
+
+    /// Directories font files are looked up in when they aren't found at
+    /// their path, and system fonts by their name before the installed ones
+    pub fn font_dirs(&self) -> &Vec<String> {
+        &self.font_dirs
+    }
+
+    pub fn add_font_dir(&mut self, font_dir: String) {
+        self.font_dirs.push(font_dir);
+    }
+}
+
+/// Stage-control input with the messages it binds to actions, the
+/// default bindings are used when it binds none
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum ControlInput {
+    /// OSC messages received over UDP, bound by address
+    Osc {
+        port: u16,
+        addresses: HashMap<String, Action>,
+    },
+    /// Notes read from a raw MIDI device such as `/dev/snd/midiC1D0`
+    Midi {
+        device: String,
+        notes: HashMap<u8, Action>,
+    },
 }
 
 #[derive(Debug, Eq, PartialEq)]
 pub struct Presentation {
-    name: String,
+    metadata: Metadata,
     slides: Vec<Slide>,
     style: Style,
+    layouts: HashMap<String, Layout>,
+    included_files: Vec<PathBuf>,
+    sections: Vec<Section>,
 }
 
 impl Presentation {
-    pub fn new(name: String, slides: Vec<Slide>, style: Style) -> Self {
+    pub fn new(metadata: Metadata, slides: Vec<Slide>, style: Style) -> Self {
         Self {
-            name,
+            metadata,
             slides,
             style,
+            layouts: HashMap::new(),
+            included_files: vec![],
+            sections: vec![],
+        }
+    }
+
+    /// Parts of the talk in the order they come in
+    pub fn sections(&self) -> &Vec<Section> {
+        &self.sections
+    }
+
+    pub fn add_section(&mut self, section: Section) {
+        self.sections.push(section);
+    }
+
+    /// Index of the section the slide belongs to, none for slides before
+    /// the first section
+    pub fn section_of(&self, slide: usize) -> Option<usize> {
+        self.sections
+            .iter()
+            .rposition(|section| section.first_slide() <= slide)
+    }
+
+    /// Index of the first slide with the name
+    pub fn slide_named(&self, name: &str) -> Option<usize> {
+        self.slides.iter().position(|slide| slide.name() == name)
+    }
+
+    /// First section with the title
+    pub fn section_titled(&self, title: &str) -> Option<&Section> {
+        self.sections
+            .iter()
+            .find(|section| section.title() == title)
+    }
+
+    pub fn layout(&self, name: &str) -> Option<&Layout> {
+        self.layouts.get(name)
+    }
+
+    pub fn layout_names(&self) -> Vec<&String> {
+        self.layouts.keys().collect()
+    }
+
+    /// Replaces any earlier layout of the same name
+    pub fn add_layout(&mut self, name: String, layout: Layout) {
+        self.layouts.insert(name, layout);
+    }
+
+    /// Elements of the slide in the order they are laid out, along with the
+    /// build step revealing them: the layout's own elements are always shown,
+    /// its placeholders are replaced with the slide's elements put under
+    /// their name, and elements not put in any of them come last
+    pub fn arranged_elements<'s>(&'s self, slide: &'s Slide) -> Vec<(&'s SlideElement, u32)> {
+        let own_elements = slide
+            .elements()
+            .iter()
+            .enumerate()
+            .map(|(index, element)| (element, slide.step_of(index), slide.placeholder_of(index)))
+            .collect::<Vec<PlacedElement>>();
+
+        match slide.layout().and_then(|name| self.layout(name)) {
+            Some(layout) => layout.arrange(&own_elements),
+            None => own_elements
+                .into_iter()
+                .map(|(element, step, _)| (element, step))
+                .collect(),
+        }
+    }
+
+    /// Puts the presentation's own style and layouts over the theme's, the
+    /// theme's files count as included ones
+    pub fn apply_theme(&mut self, theme: Theme) {
+        let mut style = theme.style;
+        style.merge(std::mem::replace(&mut self.style, Style::empty()));
+        self.style = style;
+
+        for (name, layout) in theme.layouts {
+            self.layouts.entry(name).or_insert(layout);
+        }
+        self.included_files.extend(theme.files);
+    }
+
+    /// Files pulled in with `include` or from the theme, besides the
+    /// presentation file itself
+    pub fn included_files(&self) -> &Vec<PathBuf> {
+        &self.included_files
+    }
+
+    pub fn add_included_file(&mut self, path: PathBuf) {
+        self.included_files.push(path);
+    }
+
+    /// Images, background images and videos shown on the slides
+    pub fn media_paths(&self) -> Vec<PathBuf> {
+        let mut paths = vec![];
+        let mut backgrounds = vec![self.style.background()];
+        for slide in &self.slides {
+            backgrounds.push(slide.background());
+            for (element, _) in self.arranged_elements(slide) {
+                for element in element.walk() {
+                    match element {
+                        SlideElement::Image(image) => paths.push(PathBuf::from(image.path())),
+                        SlideElement::Video(video) => paths.push(PathBuf::from(video.path())),
+                        SlideElement::Row(container) | SlideElement::Column(container) => {
+                            backgrounds.push(container.background());
+                        }
+                        _ => {}
+                    }
+                }
+            }
         }
+        paths.extend(
+            backgrounds
+                .into_iter()
+                .filter_map(|fill| fill?.image_path())
+                .map(PathBuf::from),
+        );
+
+        paths
+    }
+
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    pub fn metadata_mut(&mut self) -> &mut Metadata {
+        &mut self.metadata
+    }
+
+    pub fn slides(&self) -> &Vec<Slide> {
+        &self.slides
+    }
+
+    /// Leaves the hidden slides out, so that navigation, exports and the
+    /// slide count all skip them
+    pub fn remove_hidden_slides(&mut self) {
+        let slides = &self.slides;
+        let shown_before = |index: usize| {
+            slides[..index.min(slides.len())]
+                .iter()
+                .filter(|slide| !slide.is_hidden())
+                .count()
+        };
+        let starts: Vec<usize> = self
+            .sections
+            .iter()
+            .map(|section| shown_before(section.first_slide))
+            .collect();
+        let slide_count = shown_before(slides.len());
+
+        // sections left without any slides go as well
+        let sections = std::mem::take(&mut self.sections);
+        self.sections = sections
+            .into_iter()
+            .zip(&starts)
+            .enumerate()
+            .filter(|(index, (_, &start))| {
+                start < starts.get(index + 1).copied().unwrap_or(slide_count)
+            })
+            .map(|(_, (section, &start))| Section::new(section.title, start))
+            .collect();
+        self.slides.retain(|slide| !slide.is_hidden());
+    }
+
+    /// Transition to play when the given slide comes up
+    pub fn transition_to(&self, slide: usize) -> Option<Transition> {
+        self.slides
+            .get(slide)
+            .and_then(Slide::transition)
+            .or_else(|| self.metadata.transition())
     }
 
     pub fn style(&self) -> &Style {
         &self.style
     }
+
+    /// The presentation written as DSL source, which parses back into it
+    pub fn to_source(&self) -> String {
+        serializer::to_source(self)
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
 
+    #[test]
+    pub fn color_from_hex() {
+        assert_eq!(
+            Some(Color::new(0x12, 0xab, 0xff, 0xff)),
+            Color::from_hex("#12abFF")
+        );
+    }
+
+    #[test]
+    pub fn color_from_hex_with_alpha() {
+        assert_eq!(
+            Some(Color::new(0x12, 0xab, 0xff, 0x80)),
+            Color::from_hex("#12abff80")
+        );
+    }
+
+    #[test]
+    pub fn color_from_invalid_hex() {
+        assert_eq!(None, Color::from_hex("12abff"));
+        assert_eq!(None, Color::from_hex("#12abf"));
+        assert_eq!(None, Color::from_hex("#12abfg"));
+        assert_eq!(None, Color::from_hex("#12ab🆒"));
+    }
+
+    #[test]
+    pub fn color_from_name() {
+        assert_eq!(Some(Color::new(0, 0, 0xff, 0xff)), Color::from_name("blue"));
+        assert_eq!(None, Color::from_name("not-a-color"));
+    }
+
+    #[test]
+    pub fn style_conflicting_text_styles() {
+        let mut style = Style::empty();
+
+        style
+            .add_text_style("heading".into(), TextStyle::default())
+            .unwrap();
+        assert_eq!(
+            Err(StyleError::DuplicateTextStyle("heading".into())),
+            style.add_text_style("heading".into(), TextStyle::default())
+        );
+    }
+
+    #[test]
+    pub fn style_default_font_is_declared_first() {
+        let style = Style::new(vec![
+            Font::new("b".into(), "/b".into(), 400, false),
+            Font::new("a".into(), "/a".into(), 400, false),
+        ])
+        .unwrap();
+
+        assert_eq!("b", style.default_font().unwrap().name());
+    }
+
+    #[test]
+    pub fn style_font_lookup_falls_back_to_nearest_weight() {
+        let style = Style::new(vec![
+            Font::new("sans".into(), "/light".into(), 300, false),
+            Font::new("sans".into(), "/bold".into(), 700, false),
+            Font::new("sans".into(), "/bold-italic".into(), 700, true),
+        ])
+        .unwrap();
+        let file = |path: &str| FontSource::File(path.into());
+
+        assert_eq!(
+            &file("/light"),
+            style.font("sans", 400, false).unwrap().source()
+        );
+        assert_eq!(
+            &file("/bold"),
+            style.font("sans", 500, false).unwrap().source()
+        );
+        assert_eq!(
+            &file("/bold-italic"),
+            style.font("sans", 300, true).unwrap().source()
+        );
+        assert_eq!(None, style.font("serif", 400, false));
+    }
+
     #[test]
     pub fn style_conflicting_fonts() {
         Style::new(vec![
@@ -108,4 +2183,185 @@ mod test {
         ])
         .expect_err("Expected error from identical font definitions");
     }
+
+    #[test]
+    pub fn slide_transition_overrides_metadata() {
+        let fade = Transition::new(TransitionKind::Fade, Duration::from_millis(300));
+        let none = Transition::new(TransitionKind::None, Duration::from_millis(0));
+        let mut metadata = Metadata::new("some title".into(), Resolution::default());
+        metadata.set_transition(fade);
+        let mut second = Slide::new("second".into(), vec![]);
+        second.set_transition(none);
+        let presentation = Presentation::new(
+            metadata,
+            vec![Slide::new("first".into(), vec![]), second],
+            Style::empty(),
+        );
+
+        assert_eq!(Some(fade), presentation.transition_to(0));
+        assert_eq!(Some(none), presentation.transition_to(1));
+    }
+
+    #[test]
+    pub fn arranges_slide_elements_in_layout() {
+        let text = |content: &str| SlideElement::Text(Text::new(content.into(), None));
+        let mut presentation = Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![],
+            Style::empty(),
+        );
+        presentation.add_layout(
+            "two-column".into(),
+            Layout::new(vec![
+                LayoutItem::Element(Box::new(text("header"))),
+                LayoutItem::Placeholder("left".into()),
+                LayoutItem::Placeholder("right".into()),
+            ]),
+        );
+        let mut slide = Slide::new("some slide".into(), vec![]);
+        slide.set_layout("two-column".into());
+        slide.push_element_in_placeholder(text("b"), 1, Some("right".into()));
+        slide.push_element(text("c"));
+        slide.push_element_in_placeholder(text("a"), 0, Some("left".into()));
+        slide.push_element_in_placeholder(text("d"), 0, Some("unknown".into()));
+
+        assert_eq!(
+            vec![
+                (&text("header"), 0),
+                (&text("a"), 0),
+                (&text("b"), 1),
+                (&text("c"), 0),
+                (&text("d"), 0),
+            ],
+            presentation.arranged_elements(&slide)
+        );
+    }
+
+    #[test]
+    pub fn resolves_lengths() {
+        assert_eq!(40, Length::Pixels(40.0).resolve(800, 24));
+        assert_eq!(200, Length::Percent(25.0).resolve(800, 24));
+        assert_eq!(36, Length::Em(1.5).resolve(800, 24));
+    }
+
+    #[test]
+    pub fn removes_hidden_slides() {
+        let mut wip = Slide::new("wip".into(), vec![]);
+        wip.set_hidden(true);
+        let mut presentation = Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![
+                Slide::new("a".into(), vec![]),
+                wip,
+                Slide::new("b".into(), vec![]),
+            ],
+            Style::empty(),
+        );
+        presentation.add_section(Section::new("intro".into(), 0));
+        presentation.add_section(Section::new("drafts".into(), 1));
+        presentation.add_section(Section::new("outro".into(), 2));
+        presentation.remove_hidden_slides();
+
+        let names: Vec<&String> = presentation.slides().iter().map(Slide::name).collect();
+        assert_eq!(vec!["a", "b"], names);
+        assert_eq!(
+            &vec![
+                Section::new("intro".into(), 0),
+                Section::new("outro".into(), 1)
+            ],
+            presentation.sections()
+        );
+        assert_eq!(Some(1), presentation.section_of(1));
+    }
+
+    #[test]
+    pub fn finds_slides_by_name_and_sections_by_title() {
+        let mut presentation = Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![
+                Slide::new("intro".into(), vec![]),
+                Slide::new("demo".into(), vec![]),
+            ],
+            Style::empty(),
+        );
+        presentation.add_section(Section::new("Live demo".into(), 1));
+
+        assert_eq!(Some(1), presentation.slide_named("demo"));
+        assert_eq!(None, presentation.slide_named("outro"));
+        assert_eq!(
+            Some(&Section::new("Live demo".into(), 1)),
+            presentation.section_titled("Live demo")
+        );
+        assert_eq!(None, presentation.section_titled("Questions"));
+    }
+
+    #[test]
+    pub fn presentation_style_goes_over_the_theme() {
+        let mut theme_style = Style::new(vec![
+            Font::new("Corporate".into(), "corporate.ttf".into(), 400, false),
+            Font::new("Mono".into(), "mono.ttf".into(), 400, false),
+        ])
+        .unwrap();
+        theme_style.set_background(Fill::Color(Color::new(0x20, 0x20, 0x40, 0xff)));
+        theme_style.set_text_color(Color::new(0xff, 0xff, 0xff, 0xff));
+        theme_style.set_kerning(false);
+        let mut theme = Theme::new(theme_style);
+        theme.add_layout("title".into(), Layout::new(vec![]));
+        theme.add_file(PathBuf::from("corporate.przt"));
+
+        let mut style = Style::new(vec![Font::new(
+            "Mono".into(),
+            "other-mono.ttf".into(),
+            400,
+            false,
+        )])
+        .unwrap();
+        style.set_text_color(Color::new(0, 0, 0, 0xff));
+        let mut presentation = Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![],
+            style,
+        );
+        presentation.apply_theme(theme);
+
+        let style = presentation.style();
+        assert_eq!("Mono", style.default_font().unwrap().name());
+        assert_eq!(
+            &FontSource::File("other-mono.ttf".into()),
+            style.font("Mono", 400, false).unwrap().source()
+        );
+        assert!(style.font("Corporate", 400, false).is_some());
+        assert_eq!(
+            Some(&Fill::Color(Color::new(0x20, 0x20, 0x40, 0xff))),
+            style.background()
+        );
+        assert_eq!(Some(Color::new(0, 0, 0, 0xff)), style.text_color());
+        assert!(!style.kerning());
+        assert!(presentation.layout("title").is_some());
+        assert_eq!(
+            &vec![PathBuf::from("corporate.przt")],
+            presentation.included_files()
+        );
+    }
+
+    #[test]
+    pub fn built_in_theme_only_fills_in_missing_fonts() {
+        let mut bare = Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![],
+            Style::empty(),
+        );
+        bare.apply_theme(Theme::built_in());
+        let font = bare.style().default_font().unwrap();
+        assert_eq!(&FontSource::Bundled, font.source());
+        assert_eq!(400, font.weight());
+
+        let mut styled = Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![],
+            Style::new(vec![Font::system("Inter".into(), 400, false)]).unwrap(),
+        );
+        styled.apply_theme(Theme::built_in());
+        assert_eq!("Inter", styled.style().default_font().unwrap().name());
+    }
 }
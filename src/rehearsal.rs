@@ -0,0 +1,521 @@
+use crate::event_loop::{Error, OnLoop};
+use crate::navigation::Navigation;
+use crate::parsing::token_stream::{Token, TokenStream, TokenizerFailure, TokenizerResult};
+use crate::parsing::tokenizer::Tokenizer;
+use crate::presentation::{Presentation, Slide};
+use crate::session::{Session, SessionError};
+use crate::talk_timer::{format_minutes, Pace};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Time spent on each slide in one run, in the order of the slides
+#[derive(Debug, Eq, PartialEq, Clone, Default)]
+pub struct Timings {
+    slides: Vec<(String, Duration)>,
+}
+
+impl Timings {
+    pub fn new(slides: Vec<(String, Duration)>) -> Self {
+        Self { slides }
+    }
+
+    /// Path of the file keeping the presentation's last rehearsal, in the
+    /// state directory like its last position
+    pub fn path_for(presentation_path: &Path) -> Option<PathBuf> {
+        Session::state_path_for(presentation_path, "rehearsal")
+    }
+
+    /// Names of the slides with the time spent on them
+    pub fn slides(&self) -> &Vec<(String, Duration)> {
+        &self.slides
+    }
+
+    /// Time spent on a slide of the name, slides sharing a name are told
+    /// apart by how many of them came before it
+    pub fn of(&self, name: &str, occurrence: usize) -> Option<Duration> {
+        self.slides
+            .iter()
+            .filter(|(slide, _)| slide == name)
+            .nth(occurrence)
+            .map(|(_, spent)| *spent)
+    }
+
+    pub fn load(path: &Path) -> Result<Option<Self>, SessionError> {
+        match fs::read_to_string(path) {
+            Ok(contents) => Ok(Some(Self::parse(&contents)?)),
+            Err(ref error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), SessionError> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.serialize())?;
+
+        Ok(())
+    }
+
+    /// The time spent on each slide next to its target and how far off it
+    /// was, with the totals below
+    pub fn report(&self, presentation: &Presentation) -> String {
+        let targets: Vec<Option<Duration>> =
+            presentation.slides().iter().map(Slide::target).collect();
+        let width = self
+            .slides
+            .iter()
+            .map(|(name, _)| name.chars().count())
+            .chain(std::iter::once("total".len()))
+            .max()
+            .unwrap_or_default();
+        let line = |name: &str, spent: Duration, target: Option<Duration>| {
+            let target = target.map_or_else(
+                || format!("{:>6}", "-"),
+                |target| {
+                    format!(
+                        "{:>6}  {}",
+                        format_minutes(target),
+                        difference(spent, target)
+                    )
+                },
+            );
+            format!(
+                "{:<width$}  {:>6}  {}\n",
+                name,
+                format_minutes(spent),
+                target
+            )
+        };
+
+        let mut report = format!("{:<width$}  {:>6}  {:>6}\n", "slide", "spent", "target");
+        for (index, (name, spent)) in self.slides.iter().enumerate() {
+            report.push_str(&line(name, *spent, targets.get(index).copied().flatten()));
+        }
+        let total_target =
+            Some(targets.iter().flatten().sum()).filter(|_| targets.iter().any(Option::is_some));
+        report.push_str(&line(
+            "total",
+            self.slides.iter().map(|(_, spent)| *spent).sum(),
+            total_target,
+        ));
+
+        report
+    }
+
+    fn serialize(&self) -> String {
+        let mut serialized = String::new();
+        for (name, spent) in &self.slides {
+            serialized.push_str(&spent.as_millis().to_string());
+            serialized.push(' ');
+            serialized.push_str(name);
+            serialized.push('\n');
+        }
+
+        serialized
+    }
+
+    fn parse(contents: &str) -> Result<Self, SessionError> {
+        let slides = contents
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let (milliseconds, name) = line
+                    .split_once(' ')
+                    .ok_or_else(|| SessionError::InvalidLine(line.into()))?;
+                let milliseconds = milliseconds
+                    .parse()
+                    .map_err(|_| SessionError::InvalidLine(line.into()))?;
+
+                Ok((name.to_string(), Duration::from_millis(milliseconds)))
+            })
+            .collect::<Result<_, SessionError>>()?;
+
+        Ok(Self { slides })
+    }
+}
+
+/// `+01:05` over the target, `-00:10` under it
+fn difference(spent: Duration, target: Duration) -> String {
+    if spent >= target {
+        format!("+{}", format_minutes(spent.saturating_sub(target)))
+    } else {
+        format!("-{}", format_minutes(target.saturating_sub(spent)))
+    }
+}
+
+/// Time planned for each slide: its `target`, or the time spent on it in
+/// the last rehearsal when it has none
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Plan {
+    slides: Vec<Option<Duration>>,
+}
+
+impl Plan {
+    pub fn new(presentation: &Presentation, rehearsed: Option<&Timings>) -> Self {
+        let mut occurrences: HashMap<&String, usize> = HashMap::new();
+        let slides = presentation
+            .slides()
+            .iter()
+            .map(|slide| {
+                let occurrence = occurrences.entry(slide.name()).or_default();
+                let planned = slide.target().or_else(|| {
+                    rehearsed.and_then(|timings| timings.of(slide.name(), *occurrence))
+                });
+                *occurrence += 1;
+
+                planned
+            })
+            .collect();
+
+        Self { slides }
+    }
+
+    /// Whether no slide has a time planned
+    pub fn is_empty(&self) -> bool {
+        self.slides.iter().all(Option::is_none)
+    }
+
+    pub fn of(&self, slide: usize) -> Option<Duration> {
+        self.slides.get(slide).copied().flatten()
+    }
+
+    /// Ahead while the slide is up before its planned start, behind once it
+    /// stays up past its planned end. Slides without a plan take no time.
+    pub fn pace(&self, slide: usize, elapsed: Duration) -> Pace {
+        let start: Duration = self.slides.iter().take(slide).flatten().sum();
+        let end = start + self.of(slide).unwrap_or_default();
+
+        if elapsed < start {
+            Pace::Ahead(start.saturating_sub(elapsed))
+        } else if elapsed > end {
+            Pace::Behind(elapsed.saturating_sub(end))
+        } else {
+            Pace::OnTime
+        }
+    }
+}
+
+/// Adds up the time each slide is shown, for `--rehearse`
+pub struct Rehearsal<'a> {
+    navigation: &'a RefCell<Navigation>,
+    /// Time spent on each slide by its index, without the time since the
+    /// current slide came up
+    spent: Vec<Duration>,
+    current_slide: usize,
+    shown_at: Instant,
+}
+
+impl<'a> Rehearsal<'a> {
+    pub fn new(navigation: &'a RefCell<Navigation>, now: Instant) -> Self {
+        let current_slide = navigation.borrow().current_slide();
+
+        Self {
+            navigation,
+            spent: vec![],
+            current_slide,
+            shown_at: now,
+        }
+    }
+
+    /// Carries on a rehearsal saved in a session, with the time spent on
+    /// each slide by its index
+    pub fn resume(&mut self, spent: Vec<Duration>) {
+        self.spent = spent;
+    }
+
+    /// Time spent on each slide by its index until now, for the session
+    pub fn spent(&self, now: Instant) -> Vec<Duration> {
+        let mut spent = self.spent.clone();
+        if spent.len() <= self.current_slide {
+            spent.resize(self.current_slide + 1, Duration::default());
+        }
+        spent[self.current_slide] += now.saturating_duration_since(self.shown_at);
+
+        spent
+    }
+
+    /// Time spent on each of the presentation's slides until now
+    pub fn finish(&self, presentation: &Presentation, now: Instant) -> Timings {
+        let spent = self.spent(now);
+
+        Timings::new(
+            presentation
+                .slides()
+                .iter()
+                .enumerate()
+                .map(|(index, slide)| {
+                    let spent = spent.get(index).copied().unwrap_or_default();
+                    (slide.name().clone(), spent)
+                })
+                .collect(),
+        )
+    }
+
+    fn record(&mut self, current_slide: usize, now: Instant) {
+        if current_slide != self.current_slide {
+            self.spent = self.spent(now);
+            self.shown_at = now;
+            self.current_slide = current_slide;
+        }
+    }
+}
+
+/// The rehearsal is shared with the session autosave, which saves its
+/// timings
+impl OnLoop for &RefCell<Rehearsal<'_>> {
+    fn run(&mut self) -> Result<(), Error> {
+        let mut rehearsal = self.borrow_mut();
+        let current_slide = rehearsal.navigation.borrow().current_slide();
+        rehearsal.record(current_slide, Instant::now());
+
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        "rehearsal"
+    }
+}
+
+/// Writes the rehearsed times into the presentation's source as the
+/// `target` of its slides, rounded to whole seconds. Targets written before
+/// are replaced, the rest of the source is left as it was written.
+pub fn store_targets(source: &str, timings: &Timings) -> Result<String, TokenizerFailure> {
+    let mut tokenizer = Tokenizer::new(source);
+    let mut tokens = vec![];
+    loop {
+        match tokenizer.next() {
+            TokenizerResult::Ok(token, _) => tokens.push((token, tokenizer.token_span())),
+            TokenizerResult::Err(failure) => return Err(failure),
+            TokenizerResult::End => break,
+        }
+    }
+
+    let mut occurrences: HashMap<&String, usize> = HashMap::new();
+    let mut edits: Vec<(Range<usize>, String)> = vec![];
+    let mut depth = 0;
+    let mut index = 0;
+    while index < tokens.len() {
+        match (&tokens[index].0, tokens.get(index + 1)) {
+            (Token::KeywordSlide, Some((Token::String(name), _))) if depth == 0 => {
+                let occurrence = occurrences.entry(name).or_default();
+                let spent = timings.of(name, *occurrence);
+                *occurrence += 1;
+                let Some(end) = slide_end(&tokens, index) else {
+                    break;
+                };
+                edits.extend(
+                    spent.and_then(|spent| target_edit(source, &tokens[index..=end], spent)),
+                );
+                index = end + 1;
+                continue;
+            }
+            (Token::OpeningBrace, _) => depth += 1,
+            (Token::ClosingBrace, _) => depth -= 1,
+            _ => {}
+        }
+        index += 1;
+    }
+
+    let mut written = String::with_capacity(source.len());
+    let mut copied = 0;
+    for (range, text) in edits {
+        written.push_str(&source[copied..range.start]);
+        written.push_str(&text);
+        copied = range.end;
+    }
+    written.push_str(&source[copied..]);
+
+    Ok(written)
+}
+
+/// Index of the brace closing the slide block starting at the index
+fn slide_end(tokens: &[(Token, Range<usize>)], start: usize) -> Option<usize> {
+    let mut depth = 0;
+    for (index, (token, _)) in tokens.iter().enumerate().skip(start) {
+        match token {
+            Token::OpeningBrace => depth += 1,
+            Token::ClosingBrace if depth == 1 => return Some(index),
+            Token::ClosingBrace => depth -= 1,
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Replaces the number of the slide's `target`, or puts a `target` line
+/// first in the slide block when it has none
+fn target_edit(
+    source: &str,
+    slide: &[(Token, Range<usize>)],
+    spent: Duration,
+) -> Option<(Range<usize>, String)> {
+    let seconds = (spent + Duration::from_millis(500)).as_secs();
+    let mut depth = 0;
+    for pair in slide.windows(2) {
+        match pair {
+            [(Token::OpeningBrace, _), _] => depth += 1,
+            [(Token::ClosingBrace, _), _] => depth -= 1,
+            [(Token::KeywordTarget, _), (Token::Integer(_), range)] if depth == 1 => {
+                return Some((range.clone(), seconds.to_string()));
+            }
+            _ => {}
+        }
+    }
+
+    let line_start = source[..slide[0].1.start]
+        .rfind('\n')
+        .map_or(0, |at| at + 1);
+    let indent: String = source[line_start..]
+        .chars()
+        .take_while(|character| *character == ' ' || *character == '\t')
+        .collect();
+    let opening = slide
+        .iter()
+        .position(|(token, _)| *token == Token::OpeningBrace)?;
+    let at = slide[opening].1.end;
+    let mut text = format!("\n{}    target {}", indent, seconds);
+    // empty blocks get their closing brace on a line of its own
+    if opening + 2 == slide.len() {
+        text.push('\n');
+        text.push_str(&indent);
+        return Some((at..slide[opening + 1].1.start, text));
+    }
+
+    Some((at..at, text))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::presentation::{Metadata, Resolution, Style};
+
+    fn slide(name: &str, target: Option<u64>) -> Slide {
+        let mut slide = Slide::new(name.into(), vec![]);
+        if let Some(seconds) = target {
+            slide.set_target(Duration::from_secs(seconds));
+        }
+        slide
+    }
+
+    fn presentation(slides: Vec<Slide>) -> Presentation {
+        Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            slides,
+            Style::empty(),
+        )
+    }
+
+    #[test]
+    pub fn adds_up_the_time_spent_on_each_slide() {
+        let navigation = RefCell::new(Navigation::new(3));
+        let start = Instant::now();
+        let mut rehearsal = Rehearsal::new(&navigation, start);
+
+        rehearsal.record(0, start + Duration::from_secs(10));
+        rehearsal.record(1, start + Duration::from_secs(30));
+        rehearsal.record(0, start + Duration::from_secs(40));
+        rehearsal.record(1, start + Duration::from_secs(45));
+        let presentation = presentation(vec![
+            slide("intro", None),
+            slide("demo", None),
+            slide("end", None),
+        ]);
+
+        assert_eq!(
+            Timings::new(vec![
+                ("intro".into(), Duration::from_secs(35)),
+                ("demo".into(), Duration::from_secs(25)),
+                ("end".into(), Duration::default())
+            ]),
+            rehearsal.finish(&presentation, start + Duration::from_secs(60))
+        );
+    }
+
+    #[test]
+    pub fn can_parse_serialized_timings() {
+        let timings = Timings::new(vec![
+            ("Opening words".into(), Duration::from_millis(72_400)),
+            ("demo".into(), Duration::from_secs(185)),
+        ]);
+
+        assert_eq!(Ok(timings.clone()), Timings::parse(&timings.serialize()));
+        assert_eq!(
+            Err(SessionError::InvalidLine("soon demo".into())),
+            Timings::parse("soon demo")
+        );
+    }
+
+    #[test]
+    pub fn reports_time_spent_against_targets() {
+        let presentation = presentation(vec![slide("intro", Some(60)), slide("demo", None)]);
+        let timings = Timings::new(vec![
+            ("intro".into(), Duration::from_secs(72)),
+            ("demo".into(), Duration::from_secs(185)),
+        ]);
+
+        assert_eq!(
+            "slide   spent  target\n\
+             intro   01:12   01:00  +00:12\n\
+             demo    03:05       -\n\
+             total   04:17   01:00  +03:17\n",
+            timings.report(&presentation)
+        );
+    }
+
+    #[test]
+    pub fn paces_the_talk_by_targets_and_the_last_rehearsal() {
+        let presentation = presentation(vec![
+            slide("intro", Some(60)),
+            slide("demo", None),
+            slide("demo", None),
+        ]);
+        let rehearsed = Timings::new(vec![
+            ("intro".into(), Duration::from_secs(90)),
+            ("demo".into(), Duration::from_secs(120)),
+            ("demo".into(), Duration::from_secs(30)),
+        ]);
+        let plan = Plan::new(&presentation, Some(&rehearsed));
+
+        assert_eq!(Some(Duration::from_secs(60)), plan.of(0));
+        assert_eq!(Some(Duration::from_secs(30)), plan.of(2));
+        assert_eq!(
+            Pace::Ahead(Duration::from_secs(10)),
+            plan.pace(1, Duration::from_secs(50))
+        );
+        assert_eq!(Pace::OnTime, plan.pace(1, Duration::from_secs(100)));
+        assert_eq!(
+            Pace::Behind(Duration::from_secs(15)),
+            plan.pace(2, Duration::from_secs(225))
+        );
+        assert!(Plan::new(&presentation, None).of(1).is_none());
+    }
+
+    #[test]
+    pub fn stores_targets_in_the_source() {
+        let source = "metadata { title \"t\" }\n\n\
+                      slide \"intro\" {\n    text \"hi\"\n}\n\n\
+                      slide \"demo\" {\n    target 60 // too short\n    row {\n        text \"x\"\n    }\n}\n\n\
+                      slide \"intro\" {}\n";
+        let timings = Timings::new(vec![
+            ("intro".into(), Duration::from_millis(72_400)),
+            ("demo".into(), Duration::from_millis(185_600)),
+            ("intro".into(), Duration::from_secs(9)),
+        ]);
+
+        assert_eq!(
+            Ok("metadata { title \"t\" }\n\n\
+                slide \"intro\" {\n    target 72\n    text \"hi\"\n}\n\n\
+                slide \"demo\" {\n    target 186 // too short\n    row {\n        text \"x\"\n    }\n}\n\n\
+                slide \"intro\" {\n    target 9\n}\n"
+                .to_string()),
+            store_targets(source, &timings)
+        );
+    }
+}
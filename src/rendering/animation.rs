@@ -0,0 +1,311 @@
+use crate::easing::Easing;
+use crate::presentation::{Animation, Keyframe, Motion, Presentation, Side, Slide};
+use std::time::Duration;
+
+/// How an element is drawn at some point of its animation
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct Pose {
+    /// From 0 for a transparent element to 1 for an opaque one
+    pub opacity: f64,
+    /// How far the element is from its place, in slide pixels
+    pub offset: (i32, i32),
+    /// Times the element's size, around its center
+    pub scale: f64,
+    /// Degrees the element is turned clockwise around its center
+    pub rotation: f64,
+    /// Degrees the element's vertical and horizontal edges are slanted by
+    /// around its center, before it is scaled and turned
+    pub skew: (f64, f64),
+}
+
+impl Pose {
+    /// Whether the element is drawn as it would be without an animation
+    pub fn is_still(&self) -> bool {
+        self.opacity >= 1.0
+            && self.offset == (0, 0)
+            && (self.scale - 1.0).abs() < f64::EPSILON
+            && self.rotation.abs() < f64::EPSILON
+            && self.skew.0.abs() < f64::EPSILON
+            && self.skew.1.abs() < f64::EPSILON
+    }
+}
+
+/// How the element is drawn `elapsed` after its build step was revealed, or
+/// once it has come in and reached its last keyframe when there is no time.
+/// Elements sliding in start a whole slide away from their place, so they
+/// come in from off the slide. Lengths in keyframes are resolved against
+/// the slide and the font size, their scale and rotation add to the
+/// element's own.
+pub fn pose(
+    animation: &Animation,
+    elapsed: Option<Duration>,
+    slide: (u32, u32),
+    font_size: u32,
+) -> Pose {
+    let opacity = f64::from(animation.opacity()) / 100.0;
+    let faded = animation.fade_in().map_or(1.0, |duration| {
+        progress(elapsed, duration, animation.easing())
+    });
+    let (slide_x, slide_y) = animation.slide_in().map_or((0.0, 0.0), |(side, duration)| {
+        let left = 1.0 - progress(elapsed, duration, animation.easing());
+        let (width, height) = (f64::from(slide.0) * left, f64::from(slide.1) * left);
+        match side {
+            Side::Left => (-width, 0.0),
+            Side::Right => (width, 0.0),
+            Side::Top => (0.0, -height),
+            Side::Bottom => (0.0, height),
+        }
+    });
+
+    let keyframes = animation.keyframes();
+    let x = interpolate(keyframes, elapsed, |motion| match motion {
+        Motion::X(length) => Some(length.offset(slide.0, font_size)),
+        _ => None,
+    });
+    let y = interpolate(keyframes, elapsed, |motion| match motion {
+        Motion::Y(length) => Some(length.offset(slide.1, font_size)),
+        _ => None,
+    });
+    let scale = interpolate(keyframes, elapsed, |motion| match motion {
+        Motion::Scale(scale) => Some(scale),
+        _ => None,
+    });
+    let rotation = interpolate(keyframes, elapsed, |motion| match motion {
+        Motion::Rotate(degrees) => Some(degrees),
+        _ => None,
+    });
+    let shown = interpolate(keyframes, elapsed, |motion| match motion {
+        Motion::Opacity(percent) => Some(f64::from(percent) / 100.0),
+        _ => None,
+    });
+
+    let (x, y) = (x.unwrap_or(0.0), y.unwrap_or(0.0));
+
+    Pose {
+        opacity: opacity * faded * shown.unwrap_or(1.0),
+        offset: ((slide_x + x).round() as i32, (slide_y + y).round() as i32),
+        scale: animation.scale() * scale.unwrap_or(1.0),
+        rotation: animation.rotation() + rotation.unwrap_or(0.0),
+        skew: animation.skew(),
+    }
+}
+
+/// Whether any element revealed at the build step is still coming in or
+/// moving through its keyframes `elapsed` after the step was revealed
+pub fn is_coming_in(
+    presentation: &Presentation,
+    slide: &Slide,
+    step: u32,
+    elapsed: Duration,
+) -> bool {
+    presentation
+        .arranged_elements(slide)
+        .into_iter()
+        .filter(|(_, element_step)| *element_step == step)
+        .flat_map(|(element, _)| element.walk())
+        .any(|element| element.animation().length() > elapsed)
+}
+
+/// The value the keyframes giving it reach `elapsed` into the animation,
+/// eased from the keyframe before on to the one after. It stays at the
+/// first keyframe's value before that and at the last one's after, none
+/// of the keyframes giving it leaves it out.
+fn interpolate(
+    keyframes: &[Keyframe],
+    elapsed: Option<Duration>,
+    value: impl Fn(Motion) -> Option<f64>,
+) -> Option<f64> {
+    let values: Vec<(Duration, f64, Easing)> = keyframes
+        .iter()
+        .filter_map(|keyframe| {
+            let found = keyframe
+                .motions()
+                .iter()
+                .rev()
+                .find_map(|motion| value(*motion))?;
+            Some((keyframe.at(), found, keyframe.easing()))
+        })
+        .collect();
+    let (last_at, last, _) = *values.last()?;
+    let elapsed = match elapsed {
+        Some(elapsed) if elapsed < last_at => elapsed,
+        _ => return Some(last),
+    };
+
+    let next = values.iter().position(|(at, _, _)| *at > elapsed)?;
+    if next == 0 {
+        return Some(values[0].1);
+    }
+    let (from_at, from, easing) = values[next - 1];
+    let (to_at, to, _) = values[next];
+    let fraction = (elapsed - from_at).as_secs_f64() / (to_at - from_at).as_secs_f64();
+
+    Some(from + (to - from) * easing.ease(fraction))
+}
+
+/// From 0 when the animation starts to 1 once it is over, eased
+fn progress(elapsed: Option<Duration>, duration: Duration, easing: Easing) -> f64 {
+    match elapsed {
+        Some(elapsed) if elapsed < duration => {
+            easing.ease(elapsed.as_secs_f64() / duration.as_secs_f64())
+        }
+        _ => 1.0,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::presentation::{Image, Length, Metadata, Resolution, SlideElement, Style};
+
+    #[test]
+    pub fn fades_and_slides_elements_in_over_time() {
+        let mut animation = Animation::default();
+        animation.set_opacity(80);
+        animation.set_fade_in(Duration::from_millis(400));
+        animation.set_slide_in(Side::Left, Duration::from_millis(200));
+        let slide = (1000, 500);
+
+        assert_eq!(
+            Pose {
+                opacity: 0.0,
+                offset: (-1000, 0),
+                scale: 1.0,
+                rotation: 0.0,
+                skew: (0.0, 0.0),
+            },
+            pose(&animation, Some(Duration::from_millis(0)), slide, 20)
+        );
+        assert_eq!(
+            Pose {
+                opacity: 0.4,
+                offset: (0, 0),
+                scale: 1.0,
+                rotation: 0.0,
+                skew: (0.0, 0.0),
+            },
+            pose(&animation, Some(Duration::from_millis(200)), slide, 20)
+        );
+        assert_eq!(
+            Pose {
+                opacity: 0.8,
+                offset: (0, 0),
+                scale: 1.0,
+                rotation: 0.0,
+                skew: (0.0, 0.0),
+            },
+            pose(&animation, None, slide, 20)
+        );
+        assert!(pose(
+            &Animation::default(),
+            Some(Duration::from_millis(0)),
+            slide,
+            20
+        )
+        .is_still());
+    }
+
+    #[test]
+    pub fn moves_through_keyframes() {
+        let mut animation = Animation::default();
+        animation.set_keyframes(vec![
+            Keyframe::new(
+                Duration::from_millis(100),
+                vec![Motion::X(Length::Percent(0.0)), Motion::Rotate(0.0)],
+                Easing::Linear,
+            ),
+            Keyframe::new(
+                Duration::from_millis(300),
+                vec![Motion::X(Length::Percent(40.0))],
+                Easing::EaseIn,
+            ),
+            Keyframe::new(
+                Duration::from_millis(500),
+                vec![Motion::Rotate(90.0), Motion::Opacity(50)],
+                Easing::Linear,
+            ),
+        ]);
+        let at = |milliseconds| {
+            pose(
+                &animation,
+                Some(Duration::from_millis(milliseconds)),
+                (1000, 500),
+                20,
+            )
+        };
+
+        assert_eq!((0, 0), at(0).offset);
+        assert_eq!((200, 0), at(200).offset);
+        assert!((at(200).opacity - 0.5).abs() < 1e-9);
+        assert!((at(300).rotation - 45.0).abs() < 1e-9);
+        assert_eq!((400, 0), at(400).offset);
+        assert_eq!(
+            Pose {
+                opacity: 0.5,
+                offset: (400, 0),
+                scale: 1.0,
+                rotation: 90.0,
+                skew: (0.0, 0.0),
+            },
+            pose(&animation, None, (1000, 500), 20)
+        );
+    }
+
+    #[test]
+    pub fn turns_and_scales_keyframes_on_top_of_the_element() {
+        let mut animation = Animation::default();
+        animation.set_rotation(15.0);
+        animation.set_scale(2.0);
+        animation.set_skew(10.0, 0.0);
+        animation.set_keyframes(vec![Keyframe::new(
+            Duration::from_millis(0),
+            vec![Motion::Rotate(30.0), Motion::Scale(0.5)],
+            Easing::Linear,
+        )]);
+
+        assert_eq!(
+            Pose {
+                opacity: 1.0,
+                offset: (0, 0),
+                scale: 1.0,
+                rotation: 45.0,
+                skew: (10.0, 0.0),
+            },
+            pose(&animation, None, (1000, 500), 20)
+        );
+    }
+
+    #[test]
+    pub fn only_elements_of_the_revealed_step_come_in() {
+        let mut image = Image::new("a.png".into());
+        let mut animation = Animation::default();
+        animation.set_fade_in(Duration::from_millis(300));
+        image.set_animation(animation);
+        let mut slide = Slide::new("some slide".into(), vec![]);
+        slide.push_element_in_step(SlideElement::Image(image), 1);
+        let presentation = Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![],
+            Style::empty(),
+        );
+
+        assert!(!is_coming_in(
+            &presentation,
+            &slide,
+            0,
+            Duration::from_millis(0)
+        ));
+        assert!(is_coming_in(
+            &presentation,
+            &slide,
+            1,
+            Duration::from_millis(299)
+        ));
+        assert!(!is_coming_in(
+            &presentation,
+            &slide,
+            1,
+            Duration::from_millis(300)
+        ));
+    }
+}
@@ -0,0 +1,90 @@
+use crate::rendering::error::RenderError;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Values built on first use and kept until the cache is cleared, for
+/// things too slow to build on every frame
+pub struct Cache<K, V> {
+    values: RefCell<HashMap<K, V>>,
+}
+
+impl<K: Eq + Hash, V> Default for Cache<K, V> {
+    fn default() -> Self {
+        Self {
+            values: RefCell::new(HashMap::new()),
+        }
+    }
+}
+
+impl<K: Eq + Hash, V> Cache<K, V> {
+    /// Calls `use_value` with the value of the key, built with `build` when
+    /// the cache doesn't have it yet
+    pub fn with<R>(
+        &self,
+        key: K,
+        build: impl FnOnce() -> Result<V, RenderError>,
+        use_value: impl FnOnce(&V) -> R,
+    ) -> Result<R, RenderError> {
+        if let Some(value) = self.values.borrow().get(&key) {
+            return Ok(use_value(value));
+        }
+
+        let value = build()?;
+        let result = use_value(&value);
+        self.values.borrow_mut().insert(key, value);
+
+        Ok(result)
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&self) {
+        self.values.borrow_mut().clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    pub fn builds_each_value_once() {
+        let cache = Cache::default();
+        let builds = Cell::new(0);
+        let build = || {
+            builds.set(builds.get() + 1);
+            Ok("value".to_string())
+        };
+
+        assert_eq!(5, cache.with("key", build, String::len).unwrap());
+        assert_eq!(5, cache.with("key", build, String::len).unwrap());
+        assert_eq!(1, builds.get());
+
+        cache.clear();
+        assert!(cache.is_empty());
+        assert_eq!(5, cache.with("key", build, String::len).unwrap());
+        assert_eq!(2, builds.get());
+    }
+
+    #[test]
+    pub fn keeps_nothing_when_building_fails() {
+        let cache: Cache<&str, String> = Cache::default();
+
+        let built = cache.with(
+            "key",
+            || Err(RenderError::Sdl("broken".into())),
+            String::len,
+        );
+
+        assert_eq!("broken", built.unwrap_err().to_string());
+        assert_eq!(0, cache.len());
+    }
+}
@@ -0,0 +1,8 @@
+use crate::presentation;
+use sdl2::pixels::Color;
+
+impl From<presentation::Color> for Color {
+    fn from(color: presentation::Color) -> Self {
+        Self::RGBA(color.red(), color.green(), color.blue(), color.alpha())
+    }
+}
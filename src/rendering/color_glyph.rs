@@ -0,0 +1,360 @@
+use resvg::tiny_skia::{
+    BlendMode, Color, FillRule, GradientStop, LinearGradient, Mask, Paint, Path, PathBuilder,
+    Pixmap, PixmapPaint, Point, RadialGradient, Rect, Shader, SpreadMode, Transform,
+};
+use rustybuzz::ttf_parser::colr::{self, ClipBox, CompositeMode, GradientExtend, Painter};
+use rustybuzz::ttf_parser::{self, GlyphId, NormalizedCoordinate, RasterImageFormat, RgbaColor};
+
+/// Draws the glyph's bitmap, scaled from the strike closest to the size,
+/// with its origin at the position. Only PNG strikes are supported, which
+/// is what color emoji fonts use, glyphs without one are left to the caller.
+pub fn draw_raster(
+    pixmap: &mut Pixmap,
+    face: &ttf_parser::Face,
+    glyph: GlyphId,
+    size: u16,
+    (x, y): (f32, f32),
+) -> Option<()> {
+    let image = face
+        .glyph_raster_image(glyph, size)
+        .filter(|image| image.format == RasterImageFormat::PNG)?;
+    let bitmap = Pixmap::decode_png(image.data).ok()?;
+
+    // strikes are drawn at their own size, offset from the origin in
+    // pixels going up
+    let scale = f32::from(size) / f32::from(image.pixels_per_em.max(1));
+    let top = f32::from(image.y) + f32::from(image.height);
+    let transform = Transform::from_row(
+        scale,
+        0.0,
+        0.0,
+        scale,
+        x + f32::from(image.x) * scale,
+        y - top * scale,
+    );
+    pixmap.draw_pixmap(
+        0,
+        0,
+        bitmap.as_ref(),
+        &PixmapPaint::default(),
+        transform,
+        None,
+    );
+
+    Some(())
+}
+
+/// Draws the glyph's `COLR` layers, `transform` mapping font units onto the
+/// pixmap. Text color is used for the layers drawn in the foreground color.
+/// Glyphs without layers are left to the caller.
+pub fn draw_layers(
+    pixmap: &mut Pixmap,
+    face: &ttf_parser::Face,
+    glyph: GlyphId,
+    transform: Transform,
+    foreground: RgbaColor,
+) -> Option<()> {
+    if !face.is_color_glyph(glyph) {
+        return None;
+    }
+
+    let mut painter = LayerPainter {
+        face,
+        coordinates: face.variation_coordinates(),
+        layers: vec![(new_layer(pixmap), BlendMode::SourceOver)],
+        clips: vec![],
+        transforms: vec![transform],
+        outline: None,
+    };
+    face.paint_color_glyph(glyph, 0, foreground, &mut painter)?;
+
+    if let Some((layer, _)) = painter.layers.pop() {
+        pixmap.draw_pixmap(
+            0,
+            0,
+            layer.as_ref(),
+            &PixmapPaint::default(),
+            Transform::identity(),
+            None,
+        );
+    }
+
+    Some(())
+}
+
+fn new_layer(pixmap: &Pixmap) -> Pixmap {
+    Pixmap::new(pixmap.width(), pixmap.height()).expect("the pixmap has a valid size")
+}
+
+/// Paints color glyph layers onto pixmaps with tiny-skia. Gradients are
+/// approximated: linear ones ignore their rotation point, radial ones start
+/// from a point and sweep ones are filled with their first color.
+struct LayerPainter<'f, 'a> {
+    face: &'f ttf_parser::Face<'a>,
+    coordinates: &'f [NormalizedCoordinate],
+    /// Layers being composed, each drawn onto the one below with its mode
+    layers: Vec<(Pixmap, BlendMode)>,
+    clips: Vec<Mask>,
+    transforms: Vec<Transform>,
+    /// Last outlined glyph with the transform it was outlined with
+    outline: Option<(Path, Transform)>,
+}
+
+impl LayerPainter<'_, '_> {
+    fn transform(&self) -> Transform {
+        self.transforms
+            .last()
+            .copied()
+            .unwrap_or_else(Transform::identity)
+    }
+
+    fn layer_size(&self) -> (u32, u32) {
+        self.layers
+            .last()
+            .map_or((1, 1), |(layer, _)| (layer.width(), layer.height()))
+    }
+
+    /// Mask of the layer's size letting nothing through
+    fn empty_mask(&self) -> Mask {
+        let (width, height) = self.layer_size();
+        Mask::new(width, height).expect("the layer has a valid size")
+    }
+
+    /// Narrows the current clip down to the path
+    fn push_clip_path(&mut self, path: &Path, transform: Transform) {
+        let mut mask = self.clips.last().cloned().unwrap_or_else(|| {
+            let mut mask = self.empty_mask();
+            mask.invert();
+            mask
+        });
+        mask.intersect_path(path, FillRule::Winding, true, transform);
+        self.clips.push(mask);
+    }
+
+    fn shader(&self, paint: colr::Paint) -> Shader<'static> {
+        let transform = self.transform();
+        match paint {
+            colr::Paint::Solid(color) => Shader::SolidColor(color_of(color)),
+            colr::Paint::LinearGradient(gradient) => {
+                let stops = color_stops(gradient.stops(0, self.coordinates));
+                LinearGradient::new(
+                    Point::from_xy(gradient.x0, gradient.y0),
+                    Point::from_xy(gradient.x1, gradient.y1),
+                    gradient_stops(&stops),
+                    spread_mode(gradient.extend),
+                    transform,
+                )
+                .unwrap_or_else(|| first_color(&stops))
+            }
+            colr::Paint::RadialGradient(gradient) => {
+                let stops = color_stops(gradient.stops(0, self.coordinates));
+                RadialGradient::new(
+                    Point::from_xy(gradient.x0, gradient.y0),
+                    Point::from_xy(gradient.x1, gradient.y1),
+                    gradient.r1,
+                    gradient_stops(&stops),
+                    spread_mode(gradient.extend),
+                    transform,
+                )
+                .unwrap_or_else(|| first_color(&stops))
+            }
+            colr::Paint::SweepGradient(gradient) => {
+                first_color(&color_stops(gradient.stops(0, self.coordinates)))
+            }
+        }
+    }
+}
+
+impl<'a> Painter<'a> for LayerPainter<'_, 'a> {
+    fn outline_glyph(&mut self, glyph: GlyphId) {
+        let mut outline = Outline(PathBuilder::new());
+        self.outline = self
+            .face
+            .outline_glyph(glyph, &mut outline)
+            .and_then(|_| outline.0.finish())
+            .map(|path| (path, self.transform()));
+    }
+
+    fn paint(&mut self, paint: colr::Paint<'a>) {
+        let shader = self.shader(paint);
+        let (width, height) = self.layer_size();
+        let (path, transform) = match self.outline.clone() {
+            Some(outline) => outline,
+            None => {
+                match Rect::from_xywh(0.0, 0.0, f64::from(width) as f32, f64::from(height) as f32) {
+                    Some(rect) => (PathBuilder::from_rect(rect), Transform::identity()),
+                    None => return,
+                }
+            }
+        };
+        let paint = Paint {
+            shader,
+            ..Paint::default()
+        };
+
+        let clip = self.clips.last();
+        if let Some((layer, _)) = self.layers.last_mut() {
+            layer.fill_path(&path, &paint, FillRule::Winding, transform, clip);
+        }
+    }
+
+    fn push_clip(&mut self) {
+        match self.outline.clone() {
+            Some((path, transform)) => self.push_clip_path(&path, transform),
+            None => self.clips.push(self.empty_mask()),
+        }
+    }
+
+    fn push_clip_box(&mut self, clip_box: ClipBox) {
+        let rect = Rect::from_ltrb(
+            clip_box.x_min,
+            clip_box.y_min,
+            clip_box.x_max,
+            clip_box.y_max,
+        );
+        match rect {
+            Some(rect) => self.push_clip_path(&PathBuilder::from_rect(rect), self.transform()),
+            None => self.clips.push(self.empty_mask()),
+        }
+    }
+
+    fn pop_clip(&mut self) {
+        self.clips.pop();
+    }
+
+    fn push_layer(&mut self, mode: CompositeMode) {
+        let (width, height) = self.layer_size();
+        if let Some(layer) = Pixmap::new(width, height) {
+            self.layers.push((layer, blend_mode(mode)));
+        }
+    }
+
+    fn pop_layer(&mut self) {
+        if self.layers.len() < 2 {
+            return;
+        }
+
+        if let Some((layer, mode)) = self.layers.pop() {
+            if let Some((below, _)) = self.layers.last_mut() {
+                below.draw_pixmap(
+                    0,
+                    0,
+                    layer.as_ref(),
+                    &PixmapPaint {
+                        blend_mode: mode,
+                        ..PixmapPaint::default()
+                    },
+                    Transform::identity(),
+                    None,
+                );
+            }
+        }
+    }
+
+    fn push_transform(&mut self, transform: ttf_parser::Transform) {
+        let transform = self.transform().pre_concat(Transform::from_row(
+            transform.a,
+            transform.b,
+            transform.c,
+            transform.d,
+            transform.e,
+            transform.f,
+        ));
+        self.transforms.push(transform);
+    }
+
+    fn pop_transform(&mut self) {
+        if self.transforms.len() > 1 {
+            self.transforms.pop();
+        }
+    }
+}
+
+fn color_of(color: RgbaColor) -> Color {
+    Color::from_rgba8(color.red, color.green, color.blue, color.alpha)
+}
+
+/// Offsets and colors of the gradient's stops
+fn color_stops(stops: impl Iterator<Item = colr::ColorStop>) -> Vec<(f32, Color)> {
+    stops
+        .map(|stop| (stop.stop_offset, color_of(stop.color)))
+        .collect()
+}
+
+fn gradient_stops(stops: &[(f32, Color)]) -> Vec<GradientStop> {
+    stops
+        .iter()
+        .map(|(offset, color)| GradientStop::new(*offset, *color))
+        .collect()
+}
+
+/// Fills with the gradient's first color, for gradients tiny-skia can't draw
+fn first_color(stops: &[(f32, Color)]) -> Shader<'static> {
+    Shader::SolidColor(stops.first().map_or(Color::BLACK, |(_, color)| *color))
+}
+
+fn spread_mode(extend: GradientExtend) -> SpreadMode {
+    match extend {
+        GradientExtend::Pad => SpreadMode::Pad,
+        GradientExtend::Repeat => SpreadMode::Repeat,
+        GradientExtend::Reflect => SpreadMode::Reflect,
+    }
+}
+
+fn blend_mode(mode: CompositeMode) -> BlendMode {
+    match mode {
+        CompositeMode::Clear => BlendMode::Clear,
+        CompositeMode::Source => BlendMode::Source,
+        CompositeMode::Destination => BlendMode::Destination,
+        CompositeMode::SourceOver => BlendMode::SourceOver,
+        CompositeMode::DestinationOver => BlendMode::DestinationOver,
+        CompositeMode::SourceIn => BlendMode::SourceIn,
+        CompositeMode::DestinationIn => BlendMode::DestinationIn,
+        CompositeMode::SourceOut => BlendMode::SourceOut,
+        CompositeMode::DestinationOut => BlendMode::DestinationOut,
+        CompositeMode::SourceAtop => BlendMode::SourceAtop,
+        CompositeMode::DestinationAtop => BlendMode::DestinationAtop,
+        CompositeMode::Xor => BlendMode::Xor,
+        CompositeMode::Plus => BlendMode::Plus,
+        CompositeMode::Screen => BlendMode::Screen,
+        CompositeMode::Overlay => BlendMode::Overlay,
+        CompositeMode::Darken => BlendMode::Darken,
+        CompositeMode::Lighten => BlendMode::Lighten,
+        CompositeMode::ColorDodge => BlendMode::ColorDodge,
+        CompositeMode::ColorBurn => BlendMode::ColorBurn,
+        CompositeMode::HardLight => BlendMode::HardLight,
+        CompositeMode::SoftLight => BlendMode::SoftLight,
+        CompositeMode::Difference => BlendMode::Difference,
+        CompositeMode::Exclusion => BlendMode::Exclusion,
+        CompositeMode::Multiply => BlendMode::Multiply,
+        CompositeMode::Hue => BlendMode::Hue,
+        CompositeMode::Saturation => BlendMode::Saturation,
+        CompositeMode::Color => BlendMode::Color,
+        CompositeMode::Luminosity => BlendMode::Luminosity,
+    }
+}
+
+/// Collects a glyph outline into a path
+pub struct Outline(pub PathBuilder);
+
+impl ttf_parser::OutlineBuilder for Outline {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.0.move_to(x, y);
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.0.line_to(x, y);
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        self.0.quad_to(x1, y1, x, y);
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        self.0.cubic_to(x1, y1, x2, y2, x, y);
+    }
+
+    fn close(&mut self) {
+        self.0.close();
+    }
+}
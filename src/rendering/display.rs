@@ -0,0 +1,150 @@
+use crate::presentation::Resolution;
+use sdl2::rect::Rect;
+use sdl2::render::WindowCanvas;
+use sdl2::VideoSubsystem;
+
+/// DPI user interface sizes are given for
+const STANDARD_DPI: f64 = 96.0;
+
+/// `SDL_DISPLAYEVENT`, sent when a display is connected or disconnected.
+/// The SDL bindings don't know it and report it as an unknown event.
+pub const DISPLAY_EVENT: u32 = 0x150;
+
+/// The connected displays by index, with their names and bounds
+pub fn describe(video: &VideoSubsystem) -> Result<String, String> {
+    (0..video.num_video_displays()?)
+        .map(|index| {
+            let bounds = video.display_bounds(index)?;
+            Ok(format!(
+                "\n  {}: {} ({}x{} at {}, {})",
+                index,
+                video.display_name(index)?,
+                bounds.width(),
+                bounds.height(),
+                bounds.x(),
+                bounds.y()
+            ))
+        })
+        .collect()
+}
+
+/// Bounds of the display, the error lists the connected ones when there is
+/// no such display
+pub fn bounds(video: &VideoSubsystem, index: i32) -> Result<Rect, String> {
+    if let Ok(bounds) = video.display_bounds(index) {
+        Ok(bounds)
+    } else {
+        Err(format!(
+            "There is no display {}, the displays are:{}",
+            index,
+            describe(video)?
+        ))
+    }
+}
+
+/// A display other than the one taken, for the presenter view
+pub fn other_display(video: &VideoSubsystem, taken: i32) -> Option<i32> {
+    video
+        .num_video_displays()
+        .ok()
+        .and_then(|count| pick_other(count, taken))
+}
+
+/// Top left corner of a window of the size centered on the display
+pub fn centered_on(bounds: Rect, size: (u32, u32)) -> (i32, i32) {
+    center((bounds.x(), bounds.y()), bounds.size(), size)
+}
+
+/// Drawable pixels per window coordinate, above 1 on HiDPI displays where
+/// the system scales windows up
+pub fn pixel_density(window_canvas: &WindowCanvas) -> f64 {
+    let window = window_canvas.window();
+    let (width, _) = window.size();
+    let (drawable_width, _) = window.drawable_size();
+
+    if width == 0 || drawable_width == 0 {
+        1.0
+    } else {
+        f64::from(drawable_width) / f64::from(width)
+    }
+}
+
+/// How many drawable pixels user interface text and margins take up per
+/// pixel on a standard display. On systems which scale windows up this is
+/// the pixel density, elsewhere it comes from the display's DPI.
+pub fn interface_scale(window_canvas: &WindowCanvas) -> f64 {
+    let density = pixel_density(window_canvas);
+    if density > 1.0 {
+        return density;
+    }
+
+    let window = window_canvas.window();
+    window
+        .display_index()
+        .and_then(|index| window.subsystem().display_dpi(index))
+        .map_or(1.0, |(diagonal, _, _)| {
+            (f64::from(diagonal) / STANDARD_DPI).max(1.0)
+        })
+}
+
+/// Refresh rate of the display the window is on, in Hz
+pub fn refresh_rate(window_canvas: &WindowCanvas) -> Option<u32> {
+    let window = window_canvas.window();
+    let index = window.display_index().ok()?;
+    let mode = window.subsystem().current_display_mode(index).ok()?;
+
+    Some(mode.refresh_rate.max(0) as u32)
+}
+
+/// Drawable pixels per slide pixel, the slide is scaled to fit the window
+/// and letter-boxed
+pub fn slide_scale(window_canvas: &WindowCanvas, resolution: Resolution) -> f64 {
+    fit_scale(window_canvas.window().drawable_size(), resolution)
+}
+
+fn pick_other(count: i32, taken: i32) -> Option<i32> {
+    (0..count).find(|&index| index != taken)
+}
+
+fn center((x, y): (i32, i32), (width, height): (u32, u32), size: (u32, u32)) -> (i32, i32) {
+    (
+        x + (width as i32 - size.0 as i32) / 2,
+        y + (height as i32 - size.1 as i32) / 2,
+    )
+}
+
+fn fit_scale((width, height): (u32, u32), resolution: Resolution) -> f64 {
+    (f64::from(width) / f64::from(resolution.width().max(1)))
+        .min(f64::from(height) / f64::from(resolution.height().max(1)))
+        .max(0.01)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn fits_the_slide_into_the_window() {
+        let resolution = Resolution::new(800, 600);
+
+        assert!((fit_scale((1600, 1200), resolution) - 2.0).abs() < f64::EPSILON);
+        assert!((fit_scale((1600, 600), resolution) - 1.0).abs() < f64::EPSILON);
+        assert!((fit_scale((400, 900), resolution) - 0.5).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    pub fn picks_another_display_when_there_is_one() {
+        assert_eq!(Some(0), pick_other(2, 1));
+        assert_eq!(Some(1), pick_other(3, 0));
+        assert_eq!(None, pick_other(1, 0));
+    }
+
+    #[test]
+    pub fn centers_windows_on_displays() {
+        assert_eq!(
+            (1920 + 320, 180),
+            center((1920, 0), (1920, 1080), (1280, 720))
+        );
+        assert_eq!((-1600, 0), center((-1920, 0), (1280, 720), (640, 720)));
+    }
+}
@@ -0,0 +1,93 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+/// Why a slide or part of the user interface could not be drawn
+#[derive(Debug)]
+pub enum RenderError {
+    /// The presentation doesn't define any fonts to draw text with
+    NoFonts,
+    UnknownFont(String),
+    UnknownTextStyle(String),
+    /// A font file couldn't be parsed to shape text with
+    InvalidFont,
+    /// A font couldn't be found or opened
+    FontLoad {
+        font: String,
+        message: String,
+    },
+    /// An image, video or font file couldn't be read
+    Io {
+        path: String,
+        error: io::Error,
+    },
+    /// A file was read, but its contents couldn't be decoded
+    Decode {
+        path: String,
+        message: String,
+    },
+    /// A helper program like `ffmpeg` couldn't be started
+    Command {
+        program: String,
+        error: io::Error,
+    },
+    /// A surface couldn't be created or drawn onto, like when it would be
+    /// larger than the rasterizer allows
+    Surface(String),
+    /// A surface couldn't be uploaded to the graphics card
+    Texture(String),
+    /// The window was asked for on a display there isn't, the message
+    /// lists the displays there are
+    NoDisplay(String),
+    /// Any other error reported by SDL
+    Sdl(String),
+}
+
+impl fmt::Display for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoFonts => write!(f, "The presentation does not define any fonts"),
+            Self::UnknownFont(name) => write!(f, "Unknown font `{}`", name),
+            Self::InvalidFont => write!(f, "Could not parse the font for shaping"),
+            Self::UnknownTextStyle(name) => write!(f, "Unknown text style `{}`", name),
+            Self::FontLoad { font, message } => {
+                write!(f, "Could not load the font `{}`: {}", font, message)
+            }
+            Self::Io { path, error } => write!(f, "Could not read `{}`: {}", path, error),
+            Self::Decode { path, message } => {
+                write!(f, "Could not decode `{}`: {}", path, message)
+            }
+            Self::Command { program, error } => write!(f, "Could not run {}: {}", program, error),
+            Self::Surface(message)
+            | Self::Texture(message)
+            | Self::NoDisplay(message)
+            | Self::Sdl(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl Error for RenderError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io { error, .. } | Self::Command { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn keeps_the_underlying_error_as_the_source() {
+        let error = RenderError::Io {
+            path: "cat.png".into(),
+            error: io::Error::from(io::ErrorKind::NotFound),
+        };
+
+        assert!(error.source().is_some());
+        assert!(error.to_string().starts_with("Could not read `cat.png`: "));
+        assert!(RenderError::NoFonts.source().is_none());
+    }
+}
@@ -0,0 +1,118 @@
+use std::time::{Duration, Instant};
+
+/// Refresh rate assumed when the display doesn't report one
+const DEFAULT_REFRESH_RATE: u32 = 60;
+
+/// Decides when the next frame is drawn while something moves. With vsync
+/// presenting a frame waits for the display, so frames are drawn right
+/// away, otherwise they are spaced by the display's refresh interval.
+pub struct FramePacer {
+    interval: Duration,
+    vsync: bool,
+    last_tick: Option<Instant>,
+    last_frame: Option<Instant>,
+}
+
+impl FramePacer {
+    pub fn new(refresh_rate: Option<u32>, vsync: bool) -> Self {
+        let mut pacer = Self {
+            interval: Duration::from_secs(0),
+            vsync,
+            last_tick: None,
+            last_frame: None,
+        };
+        pacer.set_refresh_rate(refresh_rate);
+
+        pacer
+    }
+
+    /// Refresh rate of the display the window is on, in Hz
+    pub fn set_refresh_rate(&mut self, refresh_rate: Option<u32>) {
+        let refresh_rate = refresh_rate
+            .filter(|&rate| rate > 0)
+            .unwrap_or(DEFAULT_REFRESH_RATE);
+        self.interval = Duration::from_secs(1) / refresh_rate;
+    }
+
+    /// Time passed since the last tick, for advancing animations
+    pub fn tick(&mut self, now: Instant) -> Duration {
+        let delta = self.last_tick.map_or(Duration::from_secs(0), |last_tick| {
+            now.saturating_duration_since(last_tick)
+        });
+        self.last_tick = Some(now);
+
+        delta
+    }
+
+    pub fn frame_presented(&mut self, now: Instant) {
+        self.last_frame = Some(now);
+    }
+
+    /// How long until the next frame is due
+    pub fn wait(&self, now: Instant) -> Duration {
+        if self.vsync {
+            return Duration::from_secs(0);
+        }
+
+        self.last_frame
+            .map_or(Duration::from_secs(0), |last_frame| {
+                (last_frame + self.interval).saturating_duration_since(now)
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn spaces_frames_by_the_refresh_interval() {
+        let start = Instant::now();
+        let mut pacer = FramePacer::new(Some(100), false);
+        assert_eq!(Duration::from_secs(0), pacer.wait(start));
+
+        pacer.frame_presented(start);
+
+        assert_eq!(Duration::from_millis(10), pacer.wait(start));
+        assert_eq!(
+            Duration::from_millis(6),
+            pacer.wait(start + Duration::from_millis(4))
+        );
+        assert_eq!(
+            Duration::from_secs(0),
+            pacer.wait(start + Duration::from_millis(25))
+        );
+    }
+
+    #[test]
+    pub fn leaves_waiting_to_vsync() {
+        let start = Instant::now();
+        let mut pacer = FramePacer::new(Some(100), true);
+
+        pacer.frame_presented(start);
+
+        assert_eq!(Duration::from_secs(0), pacer.wait(start));
+    }
+
+    #[test]
+    pub fn assumes_60_hz_when_the_rate_is_unknown() {
+        let start = Instant::now();
+        let mut pacer = FramePacer::new(Some(0), false);
+
+        pacer.frame_presented(start);
+
+        assert_eq!(Duration::from_secs(1) / 60, pacer.wait(start));
+    }
+
+    #[test]
+    pub fn measures_time_between_ticks() {
+        let start = Instant::now();
+        let mut pacer = FramePacer::new(None, true);
+
+        assert_eq!(Duration::from_secs(0), pacer.tick(start));
+        assert_eq!(
+            Duration::from_millis(16),
+            pacer.tick(start + Duration::from_millis(16))
+        );
+    }
+}
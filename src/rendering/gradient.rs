@@ -0,0 +1,97 @@
+use crate::presentation::{Color, GradientDirection};
+use crate::rendering::error::RenderError;
+use resvg::tiny_skia::{
+    self, GradientStop, LinearGradient, Paint, Pixmap, Point, RadialGradient, Rect, Shader,
+    SpreadMode, Transform,
+};
+
+/// Draws the gradient over the whole of a pixmap of the size. Linear ones
+/// go from one edge or corner to the opposite one, radial ones from the
+/// middle out to the corners.
+pub fn rasterize(
+    from: Color,
+    to: Color,
+    direction: GradientDirection,
+    (width, height): (u32, u32),
+) -> Result<Pixmap, RenderError> {
+    let mut pixmap = Pixmap::new(width.max(1), height.max(1)).ok_or_else(|| {
+        RenderError::Surface(format!(
+            "Cannot rasterize a gradient at {}x{}",
+            width, height
+        ))
+    })?;
+    let width = f64::from(pixmap.width()) as f32;
+    let height = f64::from(pixmap.height()) as f32;
+    let stops = vec![
+        GradientStop::new(0.0, color_of(from)),
+        GradientStop::new(1.0, color_of(to)),
+    ];
+
+    let (start, end) = match direction {
+        GradientDirection::Horizontal => ((0.0, height / 2.0), (width, height / 2.0)),
+        GradientDirection::Vertical => ((width / 2.0, 0.0), (width / 2.0, height)),
+        GradientDirection::Diagonal | GradientDirection::Radial => ((0.0, 0.0), (width, height)),
+    };
+    let shader = if direction == GradientDirection::Radial {
+        let middle = Point::from_xy(width / 2.0, height / 2.0);
+        RadialGradient::new(
+            middle,
+            middle,
+            width.hypot(height) / 2.0,
+            stops,
+            SpreadMode::Pad,
+            Transform::identity(),
+        )
+    } else {
+        LinearGradient::new(
+            Point::from_xy(start.0, start.1),
+            Point::from_xy(end.0, end.1),
+            stops,
+            SpreadMode::Pad,
+            Transform::identity(),
+        )
+    };
+
+    let paint = Paint {
+        shader: shader.unwrap_or(Shader::SolidColor(color_of(from))),
+        ..Paint::default()
+    };
+    let rect = Rect::from_xywh(0.0, 0.0, width, height).ok_or_else(|| {
+        RenderError::Surface(format!(
+            "Cannot rasterize a gradient at {}x{}",
+            width, height
+        ))
+    })?;
+    pixmap.fill_rect(rect, &paint, Transform::identity(), None);
+
+    Ok(pixmap)
+}
+
+fn color_of(color: Color) -> tiny_skia::Color {
+    tiny_skia::Color::from_rgba8(color.red(), color.green(), color.blue(), color.alpha())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn red_at(pixmap: &Pixmap, x: u32, y: u32) -> u8 {
+        pixmap.pixel(x, y).unwrap().demultiply().red()
+    }
+
+    #[test]
+    pub fn blends_from_the_first_color_to_the_second() {
+        let white = Color::new(0xff, 0xff, 0xff, 0xff);
+        let black = Color::new(0, 0, 0, 0xff);
+
+        let vertical = rasterize(white, black, GradientDirection::Vertical, (10, 100)).unwrap();
+        assert!(red_at(&vertical, 5, 0) > 0xf0);
+        assert!((0x70..0x90).contains(&red_at(&vertical, 5, 50)));
+        assert!(red_at(&vertical, 5, 99) < 0x10);
+        assert_eq!(red_at(&vertical, 0, 20), red_at(&vertical, 9, 20));
+
+        let radial = rasterize(white, black, GradientDirection::Radial, (100, 100)).unwrap();
+        assert!(red_at(&radial, 50, 50) > 0xf0);
+        assert!(red_at(&radial, 0, 0) < 0x10);
+    }
+}
@@ -0,0 +1,1642 @@
+use crate::presentation::{
+    Align, Anchor, Color, Container, Fill, Font, FontDescriptor, ImageFit, List, Placement,
+    Presentation, Slide, SlideElement, Span, Style, Text, TextStyle,
+};
+use crate::rendering::animation::{self, Pose};
+use crate::rendering::error::RenderError;
+use crate::rendering::shaping::is_emoji;
+use std::rc::Rc;
+use std::time::Duration;
+
+/// Size of fonts which don't set one
+pub const DEFAULT_FONT_SIZE: u16 = 24;
+
+/// Weight bold spans are drawn with at least
+const BOLD_WEIGHT: u32 = 700;
+
+/// How far the axes of a transform may be from square and equally long for
+/// it to still count as only scaling and turning
+const SIMILARITY_TOLERANCE: f64 = 1e-9;
+
+/// Name of the color emoji font emoji fall back on when the text's fonts
+/// don't have them, backends find it when a slide needs it. Names in
+/// presentations can't have spaces, so it can't clash with any of them.
+pub const EMOJI_FONT: &str = "color emoji";
+
+/// A font file at a point size, backends load each one once
+pub type FontKey = (FontDescriptor, u16);
+
+/// Space above the titles of slides with other content, relative to the
+/// slide height
+pub const HEADING_MARGIN_PERCENT: u32 = 8;
+
+/// How titles and subtitles look when they don't pick a text style: like
+/// the text style named after them, or else like a bigger default font
+pub struct Heading {
+    pub text_style: &'static str,
+    pub size_percent: u32,
+    pub bold: bool,
+}
+
+pub const TITLE: Heading = Heading {
+    text_style: "heading",
+    size_percent: 200,
+    bold: true,
+};
+
+pub const SUBTITLE: Heading = Heading {
+    text_style: "subheading",
+    size_percent: 150,
+    bold: false,
+};
+
+impl Heading {
+    pub fn of(element: &SlideElement) -> Option<&'static Self> {
+        match element {
+            SlideElement::Title(_) => Some(&TITLE),
+            SlideElement::Subtitle(_) => Some(&SUBTITLE),
+            _ => None,
+        }
+    }
+}
+
+/// Measures and draws the pieces of a slide, in the slide coordinate system,
+/// while `SlideLayout` decides where they go
+pub trait Renderer {
+    fn text_size(&mut self, text: &str, font: &FontKey) -> Result<(u32, u32), RenderError>;
+
+    /// Whether the font has a glyph for the character
+    fn has_glyph(&mut self, character: char, font: &FontKey) -> Result<bool, RenderError>;
+
+    fn image_size(&mut self, path: &str) -> Result<(u32, u32), RenderError>;
+
+    /// Fills the whole slide with the fill
+    fn clear(&mut self, fill: &Fill) -> Result<(), RenderError>;
+
+    /// Fills the rectangle with its top left corner at the position
+    fn draw_fill(
+        &mut self,
+        fill: &Fill,
+        position: (i32, i32),
+        size: (u32, u32),
+    ) -> Result<(), RenderError>;
+
+    /// Draws a single line with its top left corner at the position
+    fn draw_text(
+        &mut self,
+        text: &str,
+        font: &FontKey,
+        color: Color,
+        position: (i32, i32),
+    ) -> Result<(), RenderError>;
+
+    /// Draws the image scaled to the size with its top left corner at the
+    /// position
+    fn draw_image(
+        &mut self,
+        path: &str,
+        position: (i32, i32),
+        size: (u32, u32),
+    ) -> Result<(), RenderError>;
+
+    fn video_size(&mut self, path: &str) -> Result<(u32, u32), RenderError>;
+
+    /// Draws the video's current frame scaled to the size with its top left
+    /// corner at the position
+    fn draw_video(
+        &mut self,
+        path: &str,
+        position: (i32, i32),
+        size: (u32, u32),
+    ) -> Result<(), RenderError>;
+
+    /// Blends whatever is drawn next with that opacity, from 0 for
+    /// transparent to 1 for opaque, renderers that can't blend draw it
+    /// opaque
+    fn set_opacity(&mut self, _opacity: f64) {}
+
+    /// Transforms whatever is drawn next, renderers that can't draw it as
+    /// it is
+    fn set_transform(&mut self, _transform: Transform) {}
+}
+
+/// An affine transform of slide positions, which parts are drawn through
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct Transform {
+    /// Where a step right goes
+    x_axis: (f64, f64),
+    /// Where a step down goes
+    y_axis: (f64, f64),
+    /// Where the top left corner of the slide goes
+    translation: (f64, f64),
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self {
+            x_axis: (1.0, 0.0),
+            y_axis: (0.0, 1.0),
+            translation: (0.0, 0.0),
+        }
+    }
+}
+
+impl Transform {
+    /// Skews by the degrees like CSS's `skew(x, y)`, scales and then turns
+    /// clockwise by the degrees, all around the center
+    pub fn around(center: (f64, f64), scale: f64, rotation: f64, skew: (f64, f64)) -> Self {
+        let (sin, cos) = rotation.to_radians().sin_cos();
+        let turn = |(x, y): (f64, f64)| (scale * (x * cos - y * sin), scale * (x * sin + y * cos));
+        let linear = Self {
+            x_axis: turn((1.0, skew.1.to_radians().tan())),
+            y_axis: turn((skew.0.to_radians().tan(), 1.0)),
+            translation: (0.0, 0.0),
+        };
+        let moved = linear.apply(center);
+
+        Self {
+            translation: (center.0 - moved.0, center.1 - moved.1),
+            ..linear
+        }
+    }
+
+    /// This transform followed by the outer one
+    #[must_use]
+    pub fn within(self, outer: Transform) -> Self {
+        Self {
+            x_axis: outer.turn(self.x_axis),
+            y_axis: outer.turn(self.y_axis),
+            translation: outer.apply(self.translation),
+        }
+    }
+
+    pub fn apply(&self, point: (f64, f64)) -> (f64, f64) {
+        let (x, y) = self.turn(point);
+        (self.translation.0 + x, self.translation.1 + y)
+    }
+
+    /// Where steps right and down go, and where the top left corner of the
+    /// slide goes
+    pub fn axes(&self) -> ((f64, f64), (f64, f64), (f64, f64)) {
+        (self.x_axis, self.y_axis, self.translation)
+    }
+
+    /// The scale and the degrees clockwise of a transform that only scales
+    /// evenly and turns, which is all that some renderers can draw
+    pub fn similarity(&self) -> Option<(f64, f64)> {
+        let ((a, b), (c, d)) = (self.x_axis, self.y_axis);
+        if (c + b).abs() > SIMILARITY_TOLERANCE || (d - a).abs() > SIMILARITY_TOLERANCE {
+            return None;
+        }
+
+        Some((a.hypot(b), b.atan2(a).to_degrees()))
+    }
+
+    pub fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Where the transform takes a step by the vector, without moving it
+    fn turn(&self, (x, y): (f64, f64)) -> (f64, f64) {
+        (
+            self.x_axis.0 * x + self.y_axis.0 * y,
+            self.x_axis.1 * x + self.y_axis.1 * y,
+        )
+    }
+}
+
+enum Part<'s> {
+    Text {
+        text: &'s str,
+        font: FontKey,
+        color: Color,
+    },
+    Image {
+        path: &'s str,
+        size: (u32, u32),
+    },
+    Video {
+        path: &'s str,
+        size: (u32, u32),
+    },
+    Fill {
+        fill: &'s Fill,
+        size: (u32, u32),
+    },
+    /// Parts of an element drawn in its pose, positioned relative to this
+    /// part and scaled and turned around the middle of its size
+    Animated {
+        pose: Pose,
+        size: (u32, u32),
+        parts: Vec<((i32, i32), Part<'s>)>,
+    },
+}
+
+/// Measured pieces of a single slide element, positioned relative to the
+/// element's top left corner
+struct Block<'s> {
+    width: u32,
+    height: u32,
+    parts: Vec<((i32, i32), Part<'s>)>,
+    /// Drawn behind the parts over the whole block, whatever size it ends
+    /// up being
+    fill: Option<&'s Fill>,
+}
+
+impl<'s> Block<'s> {
+    /// The parts, preceded by the fill when the block has one
+    fn into_parts(self) -> Vec<((i32, i32), Part<'s>)> {
+        let size = (self.width, self.height);
+        let fill = self.fill.map(|fill| ((0, 0), Part::Fill { fill, size }));
+
+        fill.into_iter().chain(self.parts).collect()
+    }
+
+    /// The same block drawn in the pose, it keeps taking up its space
+    /// wherever the pose moves it
+    fn posed(self, pose: Pose) -> Self {
+        if pose.is_still() {
+            return self;
+        }
+
+        let (width, height) = (self.width, self.height);
+        let parts = self.into_parts();
+        Self {
+            width,
+            height,
+            parts: vec![(
+                pose.offset,
+                Part::Animated {
+                    pose,
+                    size: (width, height),
+                    parts,
+                },
+            )],
+            fill: None,
+        }
+    }
+}
+
+/// Lays slides out independently of what they are drawn with
+pub struct SlideLayout {
+    presentation: Rc<Presentation>,
+    default_font: FontKey,
+    emoji_font: FontDescriptor,
+    background: Fill,
+    text_color: Color,
+}
+
+impl SlideLayout {
+    pub fn new(presentation: Rc<Presentation>) -> Result<Self, RenderError> {
+        let style = presentation.style();
+        let first_font = style.default_font().ok_or(RenderError::NoFonts)?;
+        let default_font = (
+            first_font.descriptor().clone(),
+            first_font
+                .size()
+                .map_or(DEFAULT_FONT_SIZE, |size| size as u16),
+        );
+        let background = style
+            .background()
+            .cloned()
+            .unwrap_or(Fill::Color(Color::new(0, 0, 0, 0xff)));
+        let text_color = style
+            .text_color()
+            .unwrap_or_else(|| Color::new(0xff, 0xff, 0xff, 0xff));
+
+        Ok(Self {
+            presentation,
+            default_font,
+            emoji_font: Font::system(EMOJI_FONT.into(), 400, false)
+                .descriptor()
+                .clone(),
+            background,
+            text_color,
+        })
+    }
+
+    pub fn presentation(&self) -> &Rc<Presentation> {
+        &self.presentation
+    }
+
+    /// Font of text that doesn't pick one, and of user interface text
+    pub fn default_font(&self) -> &FontKey {
+        &self.default_font
+    }
+
+    /// The color emoji font at the size
+    pub fn emoji_font(&self, size: u16) -> FontKey {
+        (self.emoji_font.clone(), size)
+    }
+
+    pub fn is_emoji_font(&self, font: &FontKey) -> bool {
+        font.0 == self.emoji_font
+    }
+
+    /// Every font any slide of the presentation is drawn with
+    pub fn font_keys(&self) -> Result<Vec<FontKey>, RenderError> {
+        let mut font_keys = vec![self.default_font.clone()];
+
+        for slide in self.presentation.slides() {
+            let elements = self.presentation.arranged_elements(slide);
+            for element in elements.iter().flat_map(|(element, _)| element.walk()) {
+                if let SlideElement::Text(text)
+                | SlideElement::Title(text)
+                | SlideElement::Subtitle(text) = element
+                {
+                    let heading = Heading::of(element);
+                    let (font, _) = self.text_look(text, heading)?;
+                    let fallbacks = self.fallback_fonts(text, heading, &font)?;
+                    for span in text.spans() {
+                        font_keys.push(self.span_font(&font, span));
+                        for fallback in &fallbacks {
+                            font_keys.push(self.span_font(fallback, span));
+                        }
+                        if span.content().chars().any(is_emoji) {
+                            font_keys.push(self.emoji_font(font.1));
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(font_keys)
+    }
+
+    /// Picks the font file and size of a text element, the element's own
+    /// size wins over its text style, which wins over the font defaults
+    pub fn text_font(&self, text: &Text) -> Result<FontKey, RenderError> {
+        let style = self.presentation.style();
+
+        match text_style(style, text)? {
+            Some(text_style) => styled_font_key(style, text_style, text.size()),
+            None => Ok((
+                self.default_font.0.clone(),
+                text.size().map_or(self.default_font.1, |size| size as u16),
+            )),
+        }
+    }
+
+    /// Variant of the text's font for a bold or italic span, the text's own
+    /// font when the presentation doesn't have a closer one
+    pub fn span_font(&self, font: &FontKey, span: &Span) -> FontKey {
+        self.font_variant(font, span.bold(), span.italic())
+    }
+
+    fn font_variant(&self, font: &FontKey, bold: bool, italic: bool) -> FontKey {
+        if !bold && !italic {
+            return font.clone();
+        }
+
+        let weight = if bold {
+            font.0.weight().max(BOLD_WEIGHT)
+        } else {
+            font.0.weight()
+        };
+        let variant =
+            self.presentation
+                .style()
+                .font(font.0.name(), weight, font.0.italic() || italic);
+
+        match variant {
+            Some(variant) => (variant.descriptor().clone(), font.1),
+            None => font.clone(),
+        }
+    }
+
+    /// Fonts of the text's style to fall back on for characters its main
+    /// font lacks, in the size, weight and slant of the main font
+    pub fn fallback_fonts(
+        &self,
+        text: &Text,
+        heading: Option<&Heading>,
+        font: &FontKey,
+    ) -> Result<Vec<FontKey>, RenderError> {
+        let style = self.presentation.style();
+        let text_style = match heading.filter(|_| text.style().is_none()) {
+            Some(heading) => style.text_style(heading.text_style),
+            None => text_style(style, text)?,
+        };
+        let names = match text_style {
+            Some(text_style) => text_style.fallback_fonts(),
+            None => return Ok(vec![]),
+        };
+
+        names
+            .iter()
+            .map(|name| {
+                style
+                    .font(name, font.0.weight(), font.0.italic())
+                    .map(|fallback| (fallback.descriptor().clone(), font.1))
+                    .ok_or_else(|| RenderError::UnknownFont(name.clone()))
+            })
+            .collect()
+    }
+
+    pub fn text_color(&self, text: &Text) -> Result<Color, RenderError> {
+        Ok(text_style(self.presentation.style(), text)?
+            .and_then(TextStyle::color)
+            .unwrap_or(self.text_color))
+    }
+
+    /// Font and color of a text element or heading
+    pub fn text_look(
+        &self,
+        text: &Text,
+        heading: Option<&Heading>,
+    ) -> Result<(FontKey, Color), RenderError> {
+        if let Some(heading) = heading.filter(|_| text.style().is_none()) {
+            self.heading_look(text, heading)
+        } else {
+            Ok((self.text_font(text)?, self.text_color(text)?))
+        }
+    }
+
+    fn heading_look(
+        &self,
+        text: &Text,
+        heading: &Heading,
+    ) -> Result<(FontKey, Color), RenderError> {
+        let style = self.presentation.style();
+
+        if let Some(text_style) = style.text_style(heading.text_style) {
+            return Ok((
+                styled_font_key(style, text_style, text.size())?,
+                text_style.color().unwrap_or(self.text_color),
+            ));
+        }
+
+        let (descriptor, size) = self.font_variant(&self.default_font, heading.bold, false);
+        let size = text
+            .size()
+            .unwrap_or(u32::from(size) * heading.size_percent / 100);
+
+        Ok(((descriptor, size as u16), self.text_color))
+    }
+
+    pub fn slide_background<'s>(&'s self, slide: &'s Slide) -> &'s Fill {
+        slide.background().unwrap_or(&self.background)
+    }
+
+    /// Draws the slide with only the elements revealed up to the given
+    /// build step, the hidden ones still take up their space. Every element
+    /// is drawn as it is once it has come in.
+    pub fn render_slide<R: Renderer>(
+        &self,
+        renderer: &mut R,
+        slide: &Slide,
+        step: u32,
+    ) -> Result<(), RenderError> {
+        self.render_revealing(renderer, slide, step, None)
+    }
+
+    /// Draws the slide like `render_slide`, with the elements of the build
+    /// step `revealed` into coming in when it is given.
+    ///
+    /// Titles and subtitles go to the top of the slide and the rest is
+    /// centered below them, slides with nothing but headings have them
+    /// centered instead. Positioned elements are left out of that and drawn
+    /// on top, where their placement puts them.
+    pub fn render_revealing<R: Renderer>(
+        &self,
+        renderer: &mut R,
+        slide: &Slide,
+        step: u32,
+        revealed: Option<Duration>,
+    ) -> Result<(), RenderError> {
+        let mut headings = vec![];
+        let mut body = vec![];
+        let mut positioned = vec![];
+        for (element, element_step) in self.presentation.arranged_elements(slide) {
+            let elapsed = revealed.filter(|_| element_step == step);
+            let mut block = self.element_block(renderer, element, elapsed)?;
+            let position = self.place_block(&mut block, element.placement());
+            let mut block = block.posed(self.pose(element, elapsed));
+            if element_step > step {
+                block.parts.clear();
+                block.fill = None;
+            }
+
+            match position {
+                Some(position) => positioned.push((block, position)),
+                None if Heading::of(element).is_some() => headings.push(block),
+                None => body.push(block),
+            }
+        }
+
+        renderer.clear(self.slide_background(slide))?;
+
+        let height = self.presentation.metadata().resolution().height();
+        if headings.is_empty() || body.is_empty() {
+            headings.append(&mut body);
+            self.draw_blocks(renderer, headings, (0, height))?;
+        } else {
+            let top = height * HEADING_MARGIN_PERCENT / 100;
+            let bottom = top + headings.iter().map(|block| block.height).sum::<u32>();
+            self.draw_blocks(renderer, headings, (top, bottom))?;
+            self.draw_blocks(renderer, body, (bottom, height))?;
+        }
+
+        for (block, position) in positioned {
+            draw_block(renderer, block, position)?;
+        }
+
+        Ok(())
+    }
+
+    /// Fits the block into the box of the placement, images and videos are
+    /// scaled to it while anything else is centered in it. Returns where the box's
+    /// top left corner goes when the element is taken out of the flow.
+    fn place_block(&self, block: &mut Block, placement: &Placement) -> Option<(i32, i32)> {
+        let resolution = self.presentation.metadata().resolution();
+        let slide = (resolution.width(), resolution.height());
+        let font_size = u32::from(self.default_font.1);
+        let natural = (block.width, block.height);
+        let scales = matches!(
+            block.parts.as_slice(),
+            [(_, Part::Image { .. } | Part::Video { .. })]
+        );
+
+        let size = match placement.size() {
+            (None, None) => natural,
+            (Some(width), Some(height)) => (
+                width.resolve(slide.0, font_size),
+                height.resolve(slide.1, font_size),
+            ),
+            (Some(width), None) => {
+                let width = width.resolve(slide.0, font_size);
+                (
+                    width,
+                    if scales {
+                        scale(natural.1, width, natural.0)
+                    } else {
+                        natural.1
+                    },
+                )
+            }
+            (None, Some(height)) => {
+                let height = height.resolve(slide.1, font_size);
+                (
+                    if scales {
+                        scale(natural.0, height, natural.1)
+                    } else {
+                        natural.0
+                    },
+                    height,
+                )
+            }
+        };
+
+        if let [(
+            _,
+            Part::Image {
+                size: media_size, ..
+            }
+            | Part::Video {
+                size: media_size, ..
+            },
+        )] = block.parts.as_mut_slice()
+        {
+            *media_size = size;
+            block.width = size.0;
+            block.height = size.1;
+        } else {
+            resize(block, size);
+        }
+
+        if !placement.is_positioned() {
+            return None;
+        }
+
+        let anchor = placement.anchor().unwrap_or(Anchor::TopLeft).halves();
+        let (x, y) = placement.position().map_or(
+            (slide.0 * anchor.0 / 2, slide.1 * anchor.1 / 2),
+            |(x, y)| (x.resolve(slide.0, font_size), y.resolve(slide.1, font_size)),
+        );
+
+        Some((
+            x as i32 - (size.0 * anchor.0 / 2) as i32,
+            y as i32 - (size.1 * anchor.1 / 2) as i32,
+        ))
+    }
+
+    /// Draws the text in the default font, one line under another, on a
+    /// black background
+    pub fn render_lines<R: Renderer>(
+        &self,
+        renderer: &mut R,
+        text: &str,
+        color: Color,
+    ) -> Result<(), RenderError> {
+        let blocks = text
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| text_block(renderer, vec![(line, self.default_font.clone(), color)]))
+            .collect::<Result<Vec<Block>, RenderError>>()?;
+
+        renderer.clear(&Fill::Color(Color::new(0, 0, 0, 0xff)))?;
+
+        let height = self.presentation.metadata().resolution().height();
+        self.draw_blocks(renderer, blocks, (0, height))
+    }
+
+    /// Stacks the blocks vertically in the middle of the slide's band
+    /// between the top and bottom edges
+    fn draw_blocks<R: Renderer>(
+        &self,
+        renderer: &mut R,
+        blocks: Vec<Block>,
+        (top, bottom): (u32, u32),
+    ) -> Result<(), RenderError> {
+        let resolution = self.presentation.metadata().resolution();
+        let center = (
+            (resolution.width() / 2) as i32,
+            (top + (bottom - top) / 2) as i32,
+        );
+        let total_height: u32 = blocks.iter().map(|block| block.height).sum();
+        let mut top = center.1 - (total_height / 2) as i32;
+
+        for block in blocks {
+            let left = center.0 - (block.width / 2) as i32;
+            let height = block.height as i32;
+
+            draw_block(renderer, block, (left, top))?;
+            top += height;
+        }
+
+        Ok(())
+    }
+
+    /// How the element is drawn `elapsed` into coming in
+    fn pose(&self, element: &SlideElement, elapsed: Option<Duration>) -> Pose {
+        let resolution = self.presentation.metadata().resolution();
+
+        animation::pose(
+            element.animation(),
+            elapsed,
+            (resolution.width(), resolution.height()),
+            u32::from(self.default_font.1),
+        )
+    }
+
+    /// Measures the element, the children of containers are posed
+    /// `elapsed` into coming in along with them
+    fn element_block<'s, R: Renderer>(
+        &self,
+        renderer: &mut R,
+        element: &'s SlideElement,
+        elapsed: Option<Duration>,
+    ) -> Result<Block<'s>, RenderError> {
+        match element {
+            SlideElement::Text(text) | SlideElement::Title(text) | SlideElement::Subtitle(text) => {
+                let heading = Heading::of(element);
+                let (font, color) = self.text_look(text, heading)?;
+                let fallbacks = self.fallback_fonts(text, heading, &font)?;
+                let mut pieces = vec![];
+                for span in text.spans() {
+                    let span_fallbacks: Vec<FontKey> = fallbacks
+                        .iter()
+                        .map(|fallback| self.span_font(fallback, span))
+                        .collect();
+                    let runs = font_runs(
+                        renderer,
+                        span.content(),
+                        self.span_font(&font, span),
+                        &span_fallbacks,
+                        &self.emoji_font(font.1),
+                    )?;
+                    for (run, run_font) in runs {
+                        pieces.push((run, run_font, span.color().unwrap_or(color)));
+                    }
+                }
+
+                text_block(renderer, pieces)
+            }
+            SlideElement::List(list) => self.list_block(renderer, list),
+            SlideElement::Row(container) => {
+                self.container_block(renderer, container, true, elapsed)
+            }
+            SlideElement::Column(container) => {
+                self.container_block(renderer, container, false, elapsed)
+            }
+            SlideElement::Image(image) => {
+                let (width, height) = renderer.image_size(image.path())?;
+
+                Ok(Block {
+                    width,
+                    height,
+                    parts: vec![(
+                        (0, 0),
+                        Part::Image {
+                            path: image.path(),
+                            size: (width, height),
+                        },
+                    )],
+                    fill: None,
+                })
+            }
+            SlideElement::Video(video) => {
+                let (width, height) = renderer.video_size(video.path())?;
+
+                Ok(Block {
+                    width,
+                    height,
+                    parts: vec![(
+                        (0, 0),
+                        Part::Video {
+                            path: video.path(),
+                            size: (width, height),
+                        },
+                    )],
+                    fill: None,
+                })
+            }
+        }
+    }
+
+    /// Lines the children up one after another, along a row when
+    /// `horizontal` or else down a column. A container with a size along
+    /// that direction spreads the space its children leave among the ones
+    /// that grow. Positions of the children are ignored.
+    fn container_block<'s, R: Renderer>(
+        &self,
+        renderer: &mut R,
+        container: &'s Container,
+        horizontal: bool,
+        elapsed: Option<Duration>,
+    ) -> Result<Block<'s>, RenderError> {
+        let resolution = self.presentation.metadata().resolution();
+        let font_size = u32::from(self.default_font.1);
+        let (length, reference) = if horizontal {
+            (container.placement().size().0, resolution.width())
+        } else {
+            (container.placement().size().1, resolution.height())
+        };
+        let along = |block: &Block| {
+            if horizontal {
+                block.width
+            } else {
+                block.height
+            }
+        };
+        let across = |block: &Block| {
+            if horizontal {
+                block.height
+            } else {
+                block.width
+            }
+        };
+
+        let mut children = vec![];
+        for element in container.elements() {
+            let mut block = self.element_block(renderer, element, elapsed)?;
+            self.place_block(&mut block, element.placement());
+            let block = block.posed(self.pose(element, elapsed));
+            children.push((block, element.placement().grow()));
+        }
+
+        let gap = container.gap().resolve(reference, font_size);
+        let gaps = gap * (children.len() as u32).saturating_sub(1);
+        let natural = children.iter().map(|(block, _)| along(block)).sum::<u32>() + gaps;
+        let free = length.map_or(0, |length| {
+            length.resolve(reference, font_size).saturating_sub(natural)
+        });
+        let total_grow: u32 = children.iter().map(|(_, grow)| grow).sum();
+        for (block, grow) in &mut children {
+            if *grow > 0 {
+                let share = scale(free, *grow, total_grow);
+                let size = if horizontal {
+                    (block.width + share, block.height)
+                } else {
+                    (block.width, block.height + share)
+                };
+                resize(block, size);
+            }
+        }
+
+        let thickness = children
+            .iter()
+            .map(|(block, _)| across(block))
+            .max()
+            .unwrap_or(0);
+        let mut block = Block {
+            width: 0,
+            height: 0,
+            parts: vec![],
+            fill: container.background(),
+        };
+        let mut offset = 0;
+        for (child, _) in children {
+            let room = thickness - across(&child);
+            let side = match container.align() {
+                Align::Start => 0,
+                Align::Center => room / 2,
+                Align::End => room,
+            };
+            let (x, y) = if horizontal {
+                (offset, side)
+            } else {
+                (side, offset)
+            };
+
+            offset += along(&child) + gap;
+            block.parts.extend(
+                child
+                    .into_parts()
+                    .into_iter()
+                    .map(|((left, top), part)| ((left + x as i32, top + y as i32), part)),
+            );
+        }
+
+        let length = offset.saturating_sub(gap);
+        if horizontal {
+            block.width = length;
+            block.height = thickness;
+        } else {
+            block.width = thickness;
+            block.height = length;
+        }
+
+        Ok(block)
+    }
+
+    /// Lists are drawn in the default font, with the items lined up after
+    /// the bullets
+    fn list_block<'s, R: Renderer>(
+        &self,
+        renderer: &mut R,
+        list: &'s List,
+    ) -> Result<Block<'s>, RenderError> {
+        let mut block = Block {
+            width: 0,
+            height: 0,
+            parts: vec![],
+            fill: None,
+        };
+
+        let indent = list.indent().resolve(
+            self.presentation.metadata().resolution().width(),
+            u32::from(self.default_font.1),
+        );
+
+        for item in list.items() {
+            let bullet = renderer.text_size(list.bullet(), &self.default_font)?;
+            let text = renderer.text_size(item, &self.default_font)?;
+            let top = block.height as i32;
+
+            block.width = block.width.max(indent + text.0);
+            block.height += bullet.1.max(text.1);
+            block.parts.push((
+                (0, top),
+                Part::Text {
+                    text: list.bullet(),
+                    font: self.default_font.clone(),
+                    color: self.text_color,
+                },
+            ));
+            block.parts.push((
+                (indent as i32, top),
+                Part::Text {
+                    text: item,
+                    font: self.default_font.clone(),
+                    color: self.text_color,
+                },
+            ));
+        }
+
+        Ok(block)
+    }
+}
+
+/// Draws the block's parts relative to its top left corner at the position
+fn draw_block<R: Renderer>(
+    renderer: &mut R,
+    block: Block,
+    position: (i32, i32),
+) -> Result<(), RenderError> {
+    draw_parts(
+        renderer,
+        block.into_parts(),
+        position,
+        (1.0, Transform::default()),
+    )
+}
+
+/// Draws the parts relative to the position with the opacity and the
+/// transform, animated ones in their pose on top of those
+fn draw_parts<R: Renderer>(
+    renderer: &mut R,
+    parts: Vec<((i32, i32), Part)>,
+    (left, top): (i32, i32),
+    (opacity, transform): (f64, Transform),
+) -> Result<(), RenderError> {
+    for ((x, y), part) in parts {
+        let position = (left + x, top + y);
+        renderer.set_opacity(opacity);
+        renderer.set_transform(transform);
+        match part {
+            Part::Text { text, font, color } => renderer.draw_text(text, &font, color, position)?,
+            Part::Image { path, size } => renderer.draw_image(path, position, size)?,
+            Part::Video { path, size } => renderer.draw_video(path, position, size)?,
+            Part::Fill { fill, size } => renderer.draw_fill(fill, position, size)?,
+            Part::Animated { pose, size, parts } => {
+                let center = (
+                    f64::from(position.0) + f64::from(size.0) / 2.0,
+                    f64::from(position.1) + f64::from(size.1) / 2.0,
+                );
+                let posed = Transform::around(center, pose.scale, pose.rotation, pose.skew);
+                draw_parts(
+                    renderer,
+                    parts,
+                    position,
+                    (opacity * pose.opacity, posed.within(transform)),
+                )?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Changes the block's size, keeping its parts centered in it
+fn resize(block: &mut Block, size: (u32, u32)) {
+    let shift = (
+        (size.0 as i32 - block.width as i32) / 2,
+        (size.1 as i32 - block.height as i32) / 2,
+    );
+    for ((x, y), _) in &mut block.parts {
+        *x += shift.0;
+        *y += shift.1;
+    }
+
+    block.width = size.0;
+    block.height = size.1;
+}
+
+/// Where copies of an image `image` pixels big go to fill an area `area`
+/// pixels big the way `fit` says, relative to the area's top left corner.
+/// A covering image sticks out of the area on two opposite sides.
+pub fn fit_image(
+    fit: ImageFit,
+    image: (u32, u32),
+    area: (u32, u32),
+) -> Vec<((i32, i32), (u32, u32))> {
+    if image.0 == 0 || image.1 == 0 {
+        return vec![];
+    }
+
+    if fit == ImageFit::Tile {
+        let mut tiles = vec![];
+        for y in (0..area.1).step_by(image.1 as usize) {
+            for x in (0..area.0).step_by(image.0 as usize) {
+                tiles.push(((x as i32, y as i32), image));
+            }
+        }
+        return tiles;
+    }
+
+    // an area wider than the image is covered by matching its width and
+    // contains the image when matching its height
+    let wider = u64::from(area.0) * u64::from(image.1) > u64::from(area.1) * u64::from(image.0);
+    let size = if wider == (fit == ImageFit::Cover) {
+        (area.0, scale(image.1, area.0, image.0))
+    } else {
+        (scale(image.0, area.1, image.1), area.1)
+    };
+
+    vec![(
+        (
+            (area.0 as i32 - size.0 as i32) / 2,
+            (area.1 as i32 - size.1 as i32) / 2,
+        ),
+        size,
+    )]
+}
+
+/// `value * numerator / denominator`, which keeps images' aspect ratio
+fn scale(value: u32, numerator: u32, denominator: u32) -> u32 {
+    if denominator == 0 {
+        return value;
+    }
+
+    (u64::from(value) * u64::from(numerator) / u64::from(denominator)) as u32
+}
+
+/// Lines up the pieces of text one after another on a single line
+fn text_block<'s, R: Renderer>(
+    renderer: &mut R,
+    pieces: Vec<(&'s str, FontKey, Color)>,
+) -> Result<Block<'s>, RenderError> {
+    let mut block = Block {
+        width: 0,
+        height: 0,
+        parts: vec![],
+        fill: None,
+    };
+
+    for (text, font, color) in pieces {
+        let (width, height) = renderer.text_size(text, &font)?;
+
+        block
+            .parts
+            .push(((block.width as i32, 0), Part::Text { text, font, color }));
+        block.width += width;
+        block.height = block.height.max(height);
+    }
+
+    Ok(block)
+}
+
+/// Splits the text into runs, each drawn in the first font of the chain
+/// that has glyphs for its characters, the emoji font being the last one
+/// for emoji. Characters none of the fonts have stay in the main font,
+/// which shows them as boxes.
+fn font_runs<'s, R: Renderer>(
+    renderer: &mut R,
+    text: &'s str,
+    font: FontKey,
+    fallbacks: &[FontKey],
+    emoji_font: &FontKey,
+) -> Result<Vec<(&'s str, FontKey)>, RenderError> {
+    if fallbacks.is_empty() && !text.chars().any(is_emoji) {
+        return Ok(vec![(text, font)]);
+    }
+
+    let mut runs = vec![];
+    let mut start = 0;
+    let mut current = font.clone();
+    for (index, character) in text.char_indices() {
+        if follows_previous(character) {
+            continue;
+        }
+
+        let emoji_fallback = Some(emoji_font).filter(|_| is_emoji(character));
+        let mut chosen = font.clone();
+        for candidate in std::iter::once(&font)
+            .chain(fallbacks)
+            .chain(emoji_fallback)
+        {
+            if renderer.has_glyph(character, candidate)? {
+                chosen = candidate.clone();
+                break;
+            }
+        }
+
+        if chosen != current {
+            if index > start {
+                runs.push((&text[start..index], current));
+            }
+            start = index;
+            current = chosen;
+        }
+    }
+    runs.push((&text[start..], current));
+
+    Ok(runs)
+}
+
+/// Whether the character is drawn in the font of the one before it, as
+/// spaces, combining marks, joiners and emoji modifiers are
+fn follows_previous(character: char) -> bool {
+    let code = u32::from(character);
+
+    character.is_whitespace()
+        || (0x0300..0x0370).contains(&code)
+        || (0x200c..0x200e).contains(&code)
+        || (0xfe00..0xfe10).contains(&code)
+        || (0x1f3fb..0x1f400).contains(&code)
+}
+
+fn text_style<'s>(style: &'s Style, text: &Text) -> Result<Option<&'s TextStyle>, RenderError> {
+    match text.style() {
+        Some(name) => {
+            Ok(Some(style.text_style(name).ok_or_else(|| {
+                RenderError::UnknownTextStyle(name.clone())
+            })?))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Resolves the text style's font descriptor, taking whatever the style
+/// leaves out from the default font
+fn styled_font_key(
+    style: &Style,
+    text_style: &TextStyle,
+    size: Option<u32>,
+) -> Result<FontKey, RenderError> {
+    let fallback = style.default_font().ok_or(RenderError::NoFonts)?;
+    let name = text_style.font().unwrap_or_else(|| fallback.name());
+    let font = style
+        .font(
+            name,
+            text_style.weight().unwrap_or_else(|| fallback.weight()),
+            text_style.italic().unwrap_or_else(|| fallback.italic()),
+        )
+        .ok_or_else(|| RenderError::UnknownFont(name.clone()))?;
+    let size = size
+        .or_else(|| text_style.size())
+        .or_else(|| font.size())
+        .map_or(DEFAULT_FONT_SIZE, |size| size as u16);
+
+    Ok((font.descriptor().clone(), size))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::presentation::{Animation, Font, Image, Length, Metadata, Resolution, Side, Video};
+
+    /// Text is 10 pixels wide per character and as tall as its font size,
+    /// images are 100 by 50 pixels and videos 160 by 90
+    #[derive(Default)]
+    struct RecordingRenderer {
+        drawn: Vec<(String, (i32, i32))>,
+    }
+
+    impl Renderer for RecordingRenderer {
+        fn text_size(&mut self, text: &str, font: &FontKey) -> Result<(u32, u32), RenderError> {
+            Ok((10 * text.chars().count() as u32, u32::from(font.1)))
+        }
+
+        /// Only emoji fonts have glyphs outside the basic multilingual
+        /// plane
+        fn has_glyph(&mut self, character: char, font: &FontKey) -> Result<bool, RenderError> {
+            Ok(font.0.name().to_lowercase().contains("emoji") || u32::from(character) <= 0xffff)
+        }
+
+        fn image_size(&mut self, _path: &str) -> Result<(u32, u32), RenderError> {
+            Ok((100, 50))
+        }
+
+        fn clear(&mut self, _fill: &Fill) -> Result<(), RenderError> {
+            self.drawn.clear();
+            Ok(())
+        }
+
+        fn draw_fill(
+            &mut self,
+            _fill: &Fill,
+            position: (i32, i32),
+            size: (u32, u32),
+        ) -> Result<(), RenderError> {
+            self.drawn
+                .push((format!("fill {}x{}", size.0, size.1), position));
+            Ok(())
+        }
+
+        fn draw_text(
+            &mut self,
+            text: &str,
+            _font: &FontKey,
+            _color: Color,
+            position: (i32, i32),
+        ) -> Result<(), RenderError> {
+            self.drawn.push((text.into(), position));
+            Ok(())
+        }
+
+        fn draw_image(
+            &mut self,
+            path: &str,
+            position: (i32, i32),
+            size: (u32, u32),
+        ) -> Result<(), RenderError> {
+            self.drawn
+                .push((format!("{} {}x{}", path, size.0, size.1), position));
+            Ok(())
+        }
+
+        fn video_size(&mut self, _path: &str) -> Result<(u32, u32), RenderError> {
+            Ok((160, 90))
+        }
+
+        fn draw_video(
+            &mut self,
+            path: &str,
+            position: (i32, i32),
+            size: (u32, u32),
+        ) -> Result<(), RenderError> {
+            self.draw_image(path, position, size)
+        }
+    }
+
+    fn layout(slide: Slide) -> (SlideLayout, Slide) {
+        let style = Style::new(vec![
+            Font::new("Sans".into(), "sans.ttf".into(), 400, false),
+            Font::new("Sans".into(), "sans-bold.ttf".into(), 700, false),
+        ])
+        .unwrap();
+        let presentation = Presentation::new(
+            Metadata::new("some title".into(), Resolution::new(800, 600)),
+            vec![],
+            style,
+        );
+
+        (SlideLayout::new(Rc::new(presentation)).unwrap(), slide)
+    }
+
+    #[test]
+    pub fn stacks_elements_in_the_middle() {
+        let mut slide = Slide::new("some slide".into(), vec![]);
+        slide.push_element(SlideElement::Text(Text::new("title".into(), None)));
+        slide.push_element(SlideElement::Image(Image::new("image.png".into())));
+        let (layout, slide) = layout(slide);
+        let mut renderer = RecordingRenderer::default();
+
+        layout.render_slide(&mut renderer, &slide, 0).unwrap();
+
+        assert_eq!(
+            vec![
+                ("title".to_string(), (375, 263)),
+                ("image.png 100x50".to_string(), (350, 287))
+            ],
+            renderer.drawn
+        );
+    }
+
+    #[test]
+    pub fn hidden_steps_keep_their_space() {
+        let mut slide = Slide::new("some slide".into(), vec![]);
+        slide.push_element(SlideElement::Text(Text::new("first".into(), None)));
+        slide.push_element_in_step(SlideElement::Text(Text::new("later".into(), None)), 1);
+        let (layout, slide) = layout(slide);
+        let mut renderer = RecordingRenderer::default();
+
+        layout.render_slide(&mut renderer, &slide, 0).unwrap();
+
+        assert_eq!(vec![("first".to_string(), (375, 276))], renderer.drawn);
+    }
+
+    #[test]
+    pub fn slides_revealed_elements_in() {
+        let mut image = Image::new("image.png".into());
+        let mut animation = Animation::default();
+        animation.set_slide_in(Side::Left, Duration::from_millis(300));
+        image.set_animation(animation);
+        let mut slide = Slide::new("some slide".into(), vec![]);
+        slide.push_element_in_step(SlideElement::Image(image), 1);
+        let (layout, slide) = layout(slide);
+        let mut renderer = RecordingRenderer::default();
+
+        layout
+            .render_revealing(&mut renderer, &slide, 1, Some(Duration::from_millis(150)))
+            .unwrap();
+        assert_eq!(
+            vec![("image.png 100x50".to_string(), (-50, 275))],
+            renderer.drawn
+        );
+
+        layout.render_slide(&mut renderer, &slide, 1).unwrap();
+        assert_eq!(
+            vec![("image.png 100x50".to_string(), (350, 275))],
+            renderer.drawn
+        );
+    }
+
+    #[test]
+    pub fn scales_and_turns_around_the_center() {
+        let round = |(x, y): (f64, f64)| (x.round(), y.round());
+        let turned = Transform::around((10.0, 10.0), 2.0, 90.0, (0.0, 0.0));
+
+        assert_eq!((10.0, 10.0), round(turned.apply((10.0, 10.0))));
+        assert_eq!((10.0, 30.0), round(turned.apply((20.0, 10.0))));
+        assert_eq!(
+            (-10.0, 10.0),
+            round(
+                turned
+                    .within(Transform::around((10.0, 10.0), 1.0, 90.0, (0.0, 0.0)))
+                    .apply((20.0, 10.0))
+            )
+        );
+        let (scale, rotation) = turned.similarity().unwrap();
+        assert!((scale - 2.0).abs() < 1e-9);
+        assert!((rotation - 90.0).abs() < 1e-9);
+    }
+
+    #[test]
+    pub fn skews_around_the_center() {
+        let round = |(x, y): (f64, f64)| (x.round(), y.round());
+        let skewed = Transform::around((10.0, 10.0), 1.0, 0.0, (45.0, 0.0));
+
+        assert_eq!((10.0, 10.0), round(skewed.apply((10.0, 10.0))));
+        assert_eq!((20.0, 20.0), round(skewed.apply((10.0, 20.0))));
+        assert_eq!((0.0, 0.0), round(skewed.apply((10.0, 0.0))));
+        assert_eq!(None, skewed.similarity());
+    }
+
+    #[test]
+    pub fn lines_up_list_items_after_bullets() {
+        let slide = Slide::new(
+            "some slide".into(),
+            vec![SlideElement::List(List::new(
+                vec!["one".into(), "three".into()],
+                "-".into(),
+                Length::Pixels(20.0),
+            ))],
+        );
+        let (layout, slide) = layout(slide);
+        let mut renderer = RecordingRenderer::default();
+
+        layout.render_slide(&mut renderer, &slide, 0).unwrap();
+
+        assert_eq!(
+            vec![
+                ("-".to_string(), (365, 276)),
+                ("one".to_string(), (385, 276)),
+                ("-".to_string(), (365, 300)),
+                ("three".to_string(), (385, 300))
+            ],
+            renderer.drawn
+        );
+    }
+
+    #[test]
+    pub fn lines_up_spans_in_their_font_variants() {
+        let mut bold = Span::new("bold".into());
+        bold.set_bold(true);
+        let slide = Slide::new(
+            "some slide".into(),
+            vec![SlideElement::Text(Text::with_spans(
+                vec![Span::new("a ".into()), bold.clone()],
+                None,
+            ))],
+        );
+        let (layout, slide) = layout(slide);
+        let mut renderer = RecordingRenderer::default();
+
+        layout.render_slide(&mut renderer, &slide, 0).unwrap();
+
+        assert_eq!(
+            vec![
+                ("a ".to_string(), (370, 288)),
+                ("bold".to_string(), (390, 288))
+            ],
+            renderer.drawn
+        );
+        assert_eq!(
+            700,
+            layout.span_font(layout.default_font(), &bold).0.weight()
+        );
+    }
+
+    #[test]
+    pub fn falls_back_to_fonts_having_the_glyphs() {
+        let mut text_style = TextStyle::new(Some("Sans".into()), None, None);
+        text_style.set_fallback_fonts(vec!["Emoji".into()]);
+        let mut style = Style::new(vec![
+            Font::new("Sans".into(), "sans.ttf".into(), 400, false),
+            Font::new("Emoji".into(), "emoji.ttf".into(), 400, false),
+        ])
+        .unwrap();
+        style.add_text_style("body".into(), text_style).unwrap();
+        let mut slide = Slide::new("some slide".into(), vec![]);
+        slide.push_element(SlideElement::Text(Text::new(
+            "hi \u{1f600} there".into(),
+            Some("body".into()),
+        )));
+        let presentation = Presentation::new(
+            Metadata::new("some title".into(), Resolution::new(800, 600)),
+            vec![slide],
+            style,
+        );
+        let layout = SlideLayout::new(Rc::new(presentation)).unwrap();
+        let mut renderer = RecordingRenderer::default();
+
+        layout
+            .render_slide(&mut renderer, &layout.presentation().slides()[0], 0)
+            .unwrap();
+
+        assert_eq!(
+            vec![
+                ("hi ".to_string(), (350, 288)),
+                ("\u{1f600} ".to_string(), (380, 288)),
+                ("there".to_string(), (400, 288))
+            ],
+            renderer.drawn
+        );
+    }
+
+    #[test]
+    pub fn draws_emoji_from_the_emoji_font() {
+        let mut slide = Slide::new("some slide".into(), vec![]);
+        slide.push_element(SlideElement::Text(Text::new(
+            "Ship it \u{1f680}".into(),
+            None,
+        )));
+        let (layout, slide) = layout(slide);
+        let mut renderer = RecordingRenderer::default();
+
+        layout.render_slide(&mut renderer, &slide, 0).unwrap();
+
+        assert_eq!(
+            vec![
+                ("Ship it ".to_string(), (355, 288)),
+                ("\u{1f680}".to_string(), (435, 288))
+            ],
+            renderer.drawn
+        );
+    }
+
+    #[test]
+    pub fn puts_headings_at_the_top() {
+        let mut slide = Slide::new("some slide".into(), vec![]);
+        slide.push_element(SlideElement::Text(Text::new("body".into(), None)));
+        slide.push_element(SlideElement::Title(Text::new("Hi".into(), None)));
+        let (layout, slide) = layout(slide);
+        let mut renderer = RecordingRenderer::default();
+
+        layout.render_slide(&mut renderer, &slide, 0).unwrap();
+
+        assert_eq!(
+            vec![
+                ("Hi".to_string(), (390, 48)),
+                ("body".to_string(), (380, 336))
+            ],
+            renderer.drawn
+        );
+        let (font, _) = layout
+            .text_look(&Text::new("Hi".into(), None), Some(&TITLE))
+            .unwrap();
+        assert_eq!((700, 48), (font.0.weight(), font.1));
+    }
+
+    #[test]
+    pub fn centers_slides_with_only_headings() {
+        let slide = Slide::new(
+            "some slide".into(),
+            vec![
+                SlideElement::Title(Text::new("Hi".into(), None)),
+                SlideElement::Subtitle(Text::new("there".into(), None)),
+            ],
+        );
+        let (layout, slide) = layout(slide);
+        let mut renderer = RecordingRenderer::default();
+
+        layout.render_slide(&mut renderer, &slide, 0).unwrap();
+
+        assert_eq!(
+            vec![
+                ("Hi".to_string(), (390, 258)),
+                ("there".to_string(), (375, 306))
+            ],
+            renderer.drawn
+        );
+    }
+
+    #[test]
+    pub fn places_positioned_elements_by_their_anchor() {
+        let mut image = Image::new("image.png".into());
+        let mut placement = Placement::default();
+        placement.set_position(Length::Percent(50.0), Length::Pixels(100.0));
+        placement.set_anchor(Anchor::Top);
+        placement.set_size(Some(Length::Pixels(200.0)), None);
+        image.set_placement(placement);
+        let mut corner = Text::new("corner".into(), None);
+        let mut placement = Placement::default();
+        placement.set_anchor(Anchor::BottomRight);
+        corner.set_placement(placement);
+        let slide = Slide::new(
+            "some slide".into(),
+            vec![
+                SlideElement::Image(image),
+                SlideElement::Text(corner),
+                SlideElement::Text(Text::new("flow".into(), None)),
+            ],
+        );
+        let (layout, slide) = layout(slide);
+        let mut renderer = RecordingRenderer::default();
+
+        layout.render_slide(&mut renderer, &slide, 0).unwrap();
+
+        assert_eq!(
+            vec![
+                ("flow".to_string(), (380, 288)),
+                ("image.png 200x100".to_string(), (300, 100)),
+                ("corner".to_string(), (740, 576))
+            ],
+            renderer.drawn
+        );
+    }
+
+    #[test]
+    pub fn centers_content_in_sized_boxes() {
+        let mut text = Text::new("boxed".into(), None);
+        let mut placement = Placement::default();
+        placement.set_position(Length::Pixels(0.0), Length::Pixels(0.0));
+        placement.set_size(Some(Length::Pixels(150.0)), Some(Length::Em(2.0)));
+        text.set_placement(placement);
+        let (layout, slide) = layout(Slide::new(
+            "some slide".into(),
+            vec![SlideElement::Text(text)],
+        ));
+        let mut renderer = RecordingRenderer::default();
+
+        layout.render_slide(&mut renderer, &slide, 0).unwrap();
+
+        assert_eq!(vec![("boxed".to_string(), (50, 12))], renderer.drawn);
+    }
+
+    #[test]
+    pub fn scales_videos_keeping_their_aspect_ratio() {
+        let mut video = Video::new("clip.mp4".into());
+        let mut placement = Placement::default();
+        placement.set_position(Length::Pixels(0.0), Length::Pixels(0.0));
+        placement.set_size(Some(Length::Pixels(320.0)), None);
+        video.set_placement(placement);
+        let (layout, slide) = layout(Slide::new(
+            "some slide".into(),
+            vec![SlideElement::Video(video)],
+        ));
+        let mut renderer = RecordingRenderer::default();
+
+        layout.render_slide(&mut renderer, &slide, 0).unwrap();
+
+        assert_eq!(
+            vec![("clip.mp4 320x180".to_string(), (0, 0))],
+            renderer.drawn
+        );
+    }
+
+    #[test]
+    pub fn spreads_free_space_of_rows_among_growing_children() {
+        let mut text = Text::new("ab".into(), None);
+        let mut placement = Placement::default();
+        placement.set_grow(1);
+        text.set_placement(placement);
+        let mut row = Container::new(vec![
+            SlideElement::Text(text),
+            SlideElement::Image(Image::new("image.png".into())),
+        ]);
+        let mut placement = Placement::default();
+        placement.set_size(Some(Length::Pixels(300.0)), None);
+        row.set_placement(placement);
+        row.set_gap(Length::Pixels(10.0));
+        row.set_align(Align::End);
+        let (layout, slide) = layout(Slide::new(
+            "some slide".into(),
+            vec![SlideElement::Row(row)],
+        ));
+        let mut renderer = RecordingRenderer::default();
+
+        layout.render_slide(&mut renderer, &slide, 0).unwrap();
+
+        assert_eq!(
+            vec![
+                ("ab".to_string(), (335, 301)),
+                ("image.png 100x50".to_string(), (450, 275))
+            ],
+            renderer.drawn
+        );
+    }
+
+    #[test]
+    pub fn fills_the_whole_box_of_containers_behind_their_children() {
+        let mut row = Container::new(vec![
+            SlideElement::Text(Text::new("ab".into(), None)),
+            SlideElement::Image(Image::new("image.png".into())),
+        ]);
+        let mut placement = Placement::default();
+        placement.set_size(Some(Length::Pixels(300.0)), None);
+        row.set_placement(placement);
+        row.set_gap(Length::Pixels(10.0));
+        row.set_background(Fill::Color(Color::new(0x20, 0x20, 0x40, 0xff)));
+        let (layout, slide) = layout(Slide::new(
+            "some slide".into(),
+            vec![SlideElement::Row(row)],
+        ));
+        let mut renderer = RecordingRenderer::default();
+
+        layout.render_slide(&mut renderer, &slide, 0).unwrap();
+
+        assert_eq!(
+            vec![
+                ("fill 300x50".to_string(), (250, 275)),
+                ("ab".to_string(), (335, 288)),
+                ("image.png 100x50".to_string(), (365, 275))
+            ],
+            renderer.drawn
+        );
+    }
+
+    #[test]
+    pub fn fits_background_images_to_the_area() {
+        assert_eq!(
+            vec![((-200, 0), (1200, 600))],
+            fit_image(ImageFit::Cover, (100, 50), (800, 600))
+        );
+        assert_eq!(
+            vec![((0, 100), (800, 400))],
+            fit_image(ImageFit::Contain, (100, 50), (800, 600))
+        );
+        assert_eq!(
+            vec![
+                ((0, 0), (100, 50)),
+                ((100, 0), (100, 50)),
+                ((0, 50), (100, 50)),
+                ((100, 50), (100, 50))
+            ],
+            fit_image(ImageFit::Tile, (100, 50), (150, 60))
+        );
+    }
+}
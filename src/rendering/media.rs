@@ -0,0 +1,445 @@
+use crate::rendering::error::RenderError;
+use gif::{ColorOutput, DecodeOptions, DisposalMethod};
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::surface::Surface;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+use std::process::{Child, ChildStdout, Command, Stdio};
+use std::time::Duration;
+
+/// How long GIF frames without a delay of their own are shown, like web
+/// browsers do
+const DEFAULT_FRAME_DELAY: Duration = Duration::from_millis(100);
+
+/// Whether the image may have more than one frame, and is then played
+/// instead of drawn as it is
+pub fn is_animated(path: &str) -> bool {
+    let extension = Path::new(path)
+        .extension()
+        .and_then(OsStr::to_str)
+        .map(str::to_lowercase);
+
+    extension.as_deref() == Some("gif")
+}
+
+/// Index of the frame shown at the position of an animation looping over
+/// frames shown for the given delays
+fn frame_index(delays: &[Duration], position: Duration) -> usize {
+    let total: Duration = delays.iter().sum();
+    if total == Duration::from_millis(0) {
+        return 0;
+    }
+
+    let mut left = Duration::from_nanos((position.as_nanos() % total.as_nanos()) as u64);
+    for (index, delay) in delays.iter().enumerate() {
+        if left < *delay {
+            return index;
+        }
+        left -= *delay;
+    }
+
+    delays.len() - 1
+}
+
+/// Every frame of a GIF drawn over the ones before it, as RGBA pixels,
+/// followed by how long it is shown
+type Frames = Vec<(Vec<u8>, Duration)>;
+
+fn decode_gif<R: Read>(reader: R) -> Result<((u32, u32), Frames), String> {
+    let mut options = DecodeOptions::new();
+    options.set_color_output(ColorOutput::RGBA);
+    let mut decoder = options.read_info(reader).map_err(|e| e.to_string())?;
+    let (width, height) = (usize::from(decoder.width()), usize::from(decoder.height()));
+
+    let mut canvas = vec![0; width * height * 4];
+    let mut frames = vec![];
+    while let Some(frame) = decoder.read_next_frame().map_err(|e| e.to_string())? {
+        let previous = canvas.clone();
+        let (left, top) = (usize::from(frame.left), usize::from(frame.top));
+        let visible_width = usize::from(frame.width).min(width.saturating_sub(left));
+        let rows = (0..usize::from(frame.height)).take_while(|row| top + row < height);
+        let offsets: Vec<(usize, usize)> = rows
+            .map(|row| {
+                (
+                    row * usize::from(frame.width) * 4,
+                    ((top + row) * width + left) * 4,
+                )
+            })
+            .collect();
+
+        for (source, target) in &offsets {
+            for column in 0..visible_width {
+                let pixel = &frame.buffer[source + column * 4..source + column * 4 + 4];
+                if pixel[3] != 0 {
+                    canvas[target + column * 4..target + column * 4 + 4].copy_from_slice(pixel);
+                }
+            }
+        }
+
+        let delay = match frame.delay {
+            0 => DEFAULT_FRAME_DELAY,
+            delay => Duration::from_millis(u64::from(delay) * 10),
+        };
+        frames.push((canvas.clone(), delay));
+
+        match frame.dispose {
+            DisposalMethod::Background => {
+                for (_, target) in &offsets {
+                    canvas[*target..target + visible_width * 4]
+                        .iter_mut()
+                        .for_each(|byte| *byte = 0);
+                }
+            }
+            DisposalMethod::Previous => canvas = previous,
+            DisposalMethod::Any | DisposalMethod::Keep => {}
+        }
+    }
+
+    Ok(((width as u32, height as u32), frames))
+}
+
+/// Copies RGBA pixels, row after row, into the surface
+fn fill_surface(surface: &mut Surface, pixels: &[u8]) {
+    let pitch = surface.pitch() as usize;
+    let row = surface.width() as usize * 4;
+    surface.with_lock_mut(|target| {
+        for (index, line) in pixels.chunks(row).enumerate() {
+            target[index * pitch..index * pitch + row].copy_from_slice(line);
+        }
+    });
+}
+
+fn rgba_surface(
+    (width, height): (u32, u32),
+    pixels: &[u8],
+) -> Result<Surface<'static>, RenderError> {
+    let mut surface = Surface::new(width.max(1), height.max(1), PixelFormatEnum::ABGR8888)
+        .map_err(RenderError::Surface)?;
+    if width > 0 && height > 0 {
+        fill_surface(&mut surface, pixels);
+    }
+
+    Ok(surface)
+}
+
+/// Frames of an animated image, played in a loop
+pub struct Animation {
+    size: (u32, u32),
+    frames: Vec<Surface<'static>>,
+    delays: Vec<Duration>,
+}
+
+impl Animation {
+    pub fn load(path: &str) -> Result<Self, RenderError> {
+        let file = File::open(path).map_err(|error| RenderError::Io {
+            path: path.to_string(),
+            error,
+        })?;
+        let (size, frames) =
+            decode_gif(BufReader::new(file)).map_err(|message| RenderError::Decode {
+                path: path.to_string(),
+                message,
+            })?;
+        if frames.is_empty() {
+            return Err(RenderError::Decode {
+                path: path.to_string(),
+                message: "it has no frames".into(),
+            });
+        }
+
+        let delays = frames.iter().map(|(_, delay)| *delay).collect();
+        let frames = frames
+            .iter()
+            .map(|(pixels, _)| rgba_surface(size, pixels))
+            .collect::<Result<_, RenderError>>()?;
+
+        Ok(Self {
+            size,
+            frames,
+            delays,
+        })
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    pub fn frame_at(&self, position: Duration) -> &Surface<'static> {
+        &self.frames[frame_index(&self.delays, position)]
+    }
+}
+
+/// Reads the size and frame rate of the first video stream out of
+/// `ffprobe`'s `width,height,avg_frame_rate` output
+fn parse_stream_info(output: &str) -> Option<((u32, u32), f64)> {
+    let mut fields = output.trim().split(',');
+    let width = fields.next()?.parse().ok()?;
+    let height = fields.next()?.parse().ok()?;
+    let mut rate = fields.next()?.split('/');
+    let numerator: f64 = rate.next()?.parse().ok()?;
+    let denominator: f64 = rate.next().map_or(Some(1.0), |value| value.parse().ok())?;
+
+    if denominator > 0.0 && numerator > 0.0 {
+        Some(((width, height), numerator / denominator))
+    } else {
+        None
+    }
+}
+
+/// A video decoded by an `ffmpeg` process into raw frames, which are read
+/// as the playback reaches them. Going back starts the decoding over.
+pub struct VideoStream {
+    path: String,
+    size: (u32, u32),
+    frame_rate: f64,
+    decoder: Option<(Child, ChildStdout)>,
+    /// Index of the frame the decoder gives next
+    next_frame: u64,
+    pixels: Vec<u8>,
+    frame: Surface<'static>,
+}
+
+impl VideoStream {
+    pub fn open(path: &str) -> Result<Self, RenderError> {
+        let output = Command::new("ffprobe")
+            .args([
+                "-v",
+                "error",
+                "-select_streams",
+                "v:0",
+                "-show_entries",
+                "stream=width,height,avg_frame_rate",
+                "-of",
+                "csv=p=0",
+                path,
+            ])
+            .output()
+            .map_err(|error| RenderError::Command {
+                program: "ffprobe".into(),
+                error,
+            })?;
+        let (size, frame_rate) = parse_stream_info(&String::from_utf8_lossy(&output.stdout))
+            .ok_or_else(|| RenderError::Decode {
+                path: path.to_string(),
+                message: "it has no video stream".into(),
+            })?;
+
+        Ok(Self {
+            path: path.to_string(),
+            size,
+            frame_rate,
+            decoder: None,
+            next_frame: 0,
+            pixels: vec![0; size.0 as usize * size.1 as usize * 4],
+            frame: rgba_surface(size, &[])?,
+        })
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    /// The frame shown at the position, the last one once the video ended
+    pub fn frame_at(&mut self, position: Duration) -> Result<&Surface<'static>, RenderError> {
+        let wanted = (position.as_secs_f64() * self.frame_rate) as u64;
+        if self.decoder.is_none() || wanted + 1 < self.next_frame {
+            self.start_decoder()?;
+        }
+
+        let mut updated = false;
+        while self.next_frame <= wanted {
+            let read = match &mut self.decoder {
+                Some((_, output)) => output.read_exact(&mut self.pixels).is_ok(),
+                None => false,
+            };
+            if !read {
+                break;
+            }
+
+            self.next_frame += 1;
+            updated = true;
+        }
+        if updated {
+            fill_surface(&mut self.frame, &self.pixels);
+        }
+
+        Ok(&self.frame)
+    }
+
+    fn start_decoder(&mut self) -> Result<(), RenderError> {
+        self.stop_decoder();
+
+        let mut child = Command::new("ffmpeg")
+            .args(["-v", "error", "-i", &self.path])
+            .args(["-f", "rawvideo", "-pix_fmt", "rgba", "-"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|error| RenderError::Command {
+                program: "ffmpeg".into(),
+                error,
+            })?;
+        let output = child.stdout.take().ok_or_else(|| RenderError::Decode {
+            path: self.path.clone(),
+            message: "ffmpeg gave no output".into(),
+        })?;
+        self.decoder = Some((child, output));
+        self.next_frame = 0;
+
+        Ok(())
+    }
+
+    fn stop_decoder(&mut self) {
+        if let Some((mut child, _)) = self.decoder.take() {
+            // the process may have exited on its own already
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+impl Drop for VideoStream {
+    fn drop(&mut self) {
+        self.stop_decoder();
+    }
+}
+
+/// Animated images and videos opened once, each keeps the frame it is at
+/// between the frames drawn
+#[derive(Default)]
+pub struct MediaCache {
+    animations: RefCell<HashMap<String, Animation>>,
+    videos: RefCell<HashMap<String, VideoStream>>,
+}
+
+impl MediaCache {
+    pub fn animation_size(&self, path: &str) -> Result<(u32, u32), RenderError> {
+        self.load_animation(path)?;
+
+        Ok(self.animations.borrow()[path].size())
+    }
+
+    /// Calls `draw` with the frame of the animated image shown at the
+    /// position
+    pub fn with_animation_frame<R>(
+        &self,
+        path: &str,
+        position: Duration,
+        draw: impl FnOnce(&Surface<'static>) -> R,
+    ) -> Result<R, RenderError> {
+        self.load_animation(path)?;
+
+        Ok(draw(self.animations.borrow()[path].frame_at(position)))
+    }
+
+    pub fn video_size(&self, path: &str) -> Result<(u32, u32), RenderError> {
+        self.open_video(path)?;
+
+        Ok(self.videos.borrow()[path].size())
+    }
+
+    /// Calls `draw` with the frame of the video shown at the position
+    pub fn with_video_frame<R>(
+        &self,
+        path: &str,
+        position: Duration,
+        draw: impl FnOnce(&Surface<'static>) -> R,
+    ) -> Result<R, RenderError> {
+        self.open_video(path)?;
+
+        let mut videos = self.videos.borrow_mut();
+        let video = videos
+            .get_mut(path)
+            .ok_or_else(|| RenderError::Sdl(format!("`{}` is not open", path)))?;
+
+        Ok(draw(video.frame_at(position)?))
+    }
+
+    fn load_animation(&self, path: &str) -> Result<(), RenderError> {
+        if !self.animations.borrow().contains_key(path) {
+            let animation = Animation::load(path)?;
+            self.animations
+                .borrow_mut()
+                .insert(path.to_string(), animation);
+        }
+
+        Ok(())
+    }
+
+    fn open_video(&self, path: &str) -> Result<(), RenderError> {
+        if !self.videos.borrow().contains_key(path) {
+            let video = VideoStream::open(path)?;
+            self.videos.borrow_mut().insert(path.to_string(), video);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use gif::{Encoder, Frame};
+
+    #[test]
+    pub fn recognizes_animated_images_by_extension() {
+        assert!(is_animated("demo.gif"));
+        assert!(is_animated("DEMO.GIF"));
+        assert!(!is_animated("photo.png"));
+    }
+
+    #[test]
+    pub fn loops_over_the_frames() {
+        let delays = [Duration::from_millis(100), Duration::from_millis(300)];
+
+        assert_eq!(0, frame_index(&delays, Duration::from_millis(50)));
+        assert_eq!(1, frame_index(&delays, Duration::from_millis(100)));
+        assert_eq!(1, frame_index(&delays, Duration::from_millis(399)));
+        assert_eq!(0, frame_index(&delays, Duration::from_millis(420)));
+        assert_eq!(0, frame_index(&[], Duration::from_millis(420)));
+    }
+
+    #[test]
+    pub fn draws_gif_frames_over_the_previous_ones() {
+        let mut data = vec![];
+        {
+            let mut encoder = Encoder::new(&mut data, 2, 1, &[0, 0, 0, 0xff, 0, 0]).unwrap();
+            let mut first = Frame::from_indexed_pixels(2, 1, [0, 0], None);
+            first.delay = 5;
+            encoder.write_frame(&first).unwrap();
+            let mut second = Frame::from_indexed_pixels(1, 1, [1], None);
+            second.left = 1;
+            encoder.write_frame(&second).unwrap();
+        }
+
+        let (size, frames) = decode_gif(data.as_slice()).unwrap();
+
+        assert_eq!((2, 1), size);
+        assert_eq!(
+            vec![
+                (
+                    vec![0, 0, 0, 0xff, 0, 0, 0, 0xff],
+                    Duration::from_millis(50)
+                ),
+                (vec![0, 0, 0, 0xff, 0xff, 0, 0, 0xff], DEFAULT_FRAME_DELAY),
+            ],
+            frames
+        );
+    }
+
+    #[test]
+    pub fn reads_ffprobe_stream_info() {
+        assert_eq!(
+            Some(((1920, 1080), 30.0)),
+            parse_stream_info("1920,1080,30/1\n")
+        );
+        assert_eq!(Some(((640, 360), 25.0)), parse_stream_info("640,360,25"));
+        assert_eq!(None, parse_stream_info("640,360,0/0"));
+        assert_eq!(None, parse_stream_info(""));
+    }
+}
@@ -1 +1,21 @@
+pub mod animation;
+pub mod cache;
+mod color;
+mod color_glyph;
+pub mod display;
+pub mod error;
+pub mod frame_pacing;
+pub mod gradient;
+pub mod layout;
+pub mod media;
+pub mod offscreen;
+pub mod overlay;
+pub mod overview;
+pub mod presenter;
 pub mod renderer;
+pub mod shaping;
+pub mod slide_renderer;
+pub mod svg;
+pub mod transition;
+pub mod warp;
+pub mod window;
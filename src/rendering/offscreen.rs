@@ -0,0 +1,78 @@
+use crate::presentation::Slide;
+use crate::rendering::error::RenderError;
+use crate::rendering::slide_renderer::SlideRenderer;
+use sdl2::surface::Surface;
+
+/// Pixels of a rendered slide, three bytes (red, green, blue) per pixel,
+/// row by row
+pub struct RgbImage {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl RgbImage {
+    pub fn new(width: u32, height: u32, pixels: Vec<u8>) -> Self {
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn pixels(&self) -> &Vec<u8> {
+        &self.pixels
+    }
+}
+
+/// Renders slides into memory instead of a window
+pub struct Offscreen<'a> {
+    slide_renderer: SlideRenderer<'a>,
+    width: u32,
+    height: u32,
+}
+
+impl<'a> Offscreen<'a> {
+    pub fn new(slide_renderer: SlideRenderer<'a>, width: u32, height: u32) -> Self {
+        Self {
+            slide_renderer,
+            width,
+            height,
+        }
+    }
+
+    pub fn slide_renderer(&self) -> &SlideRenderer<'a> {
+        &self.slide_renderer
+    }
+
+    pub fn slide_renderer_mut(&mut self) -> &mut SlideRenderer<'a> {
+        &mut self.slide_renderer
+    }
+
+    pub fn render_surface(&self, slide: &Slide) -> Result<Surface<'static>, RenderError> {
+        self.slide_renderer
+            .render_surface(slide, slide.step_count(), self.width, self.height)
+    }
+
+    pub fn render(&self, slide: &Slide) -> Result<RgbImage, RenderError> {
+        let surface = self.render_surface(slide)?;
+        let row_length = (self.width * 3) as usize;
+        let pitch = surface.pitch() as usize;
+        let pixels = surface.with_lock(|data| {
+            data.chunks(pitch)
+                .take(self.height as usize)
+                .flat_map(|row| row[..row_length].iter().copied())
+                .collect()
+        });
+
+        Ok(RgbImage::new(self.width, self.height, pixels))
+    }
+}
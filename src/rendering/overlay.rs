@@ -0,0 +1,323 @@
+use crate::pointer::Pointer;
+use crate::presentation::{Color, Resolution};
+use crate::rendering::error::RenderError;
+use crate::rendering::slide_renderer::SlideRenderer;
+use crate::talk_timer::{TalkTimer, TimeWarning};
+use sdl2::rect::{Point, Rect};
+use sdl2::render::{BlendMode, Canvas, RenderTarget, TextureCreator};
+use std::time::Instant;
+
+/// Offsets the strokes are drawn at, to make them a few pixels thick
+const STROKE_OFFSETS: [(i32, i32); 5] = [(0, 0), (-1, 0), (1, 0), (0, -1), (0, 1)];
+
+/// Draws the pointer's strokes and its dot over the slide, using the slide
+/// coordinate system
+pub fn draw_pointer<T: RenderTarget>(
+    canvas: &mut Canvas<T>,
+    pointer: &Pointer,
+) -> Result<(), RenderError> {
+    if !pointer.is_enabled() {
+        return Ok(());
+    }
+
+    canvas.set_draw_color(pointer.color());
+    for stroke in pointer.strokes() {
+        for segment in stroke.windows(2) {
+            for (dx, dy) in &STROKE_OFFSETS {
+                canvas
+                    .draw_line(
+                        Point::new(segment[0].0 + dx, segment[0].1 + dy),
+                        Point::new(segment[1].0 + dx, segment[1].1 + dy),
+                    )
+                    .map_err(RenderError::Sdl)?;
+            }
+        }
+    }
+
+    if let Some((x, y)) = pointer.position() {
+        for (dy, half_width) in disc_rows(pointer.radius()) {
+            canvas
+                .draw_line(
+                    Point::new(x - half_width, y + dy),
+                    Point::new(x + half_width, y + dy),
+                )
+                .map_err(RenderError::Sdl)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Space between the timer and the slide's edges, and around its text
+const TIMER_MARGIN: u32 = 12;
+
+/// Draws the talk timer in the bottom right corner of the slide, in yellow
+/// when time is running out and in red once it is up
+pub fn draw_timer<T: RenderTarget, C>(
+    canvas: &mut Canvas<T>,
+    textures: &TextureCreator<C>,
+    slide_renderer: &SlideRenderer,
+    resolution: Resolution,
+    timer: &TalkTimer,
+) -> Result<(), RenderError> {
+    if !timer.is_visible() {
+        return Ok(());
+    }
+
+    let now = Instant::now();
+    let color = match timer.warning(now) {
+        TimeWarning::None => Color::new(0xff, 0xff, 0xff, 0xff),
+        TimeWarning::Soon => Color::new(0xff, 0xd0, 0x20, 0xff),
+        TimeWarning::Over => Color::new(0xff, 0x40, 0x40, 0xff),
+    };
+    let surface = slide_renderer.render_label(&timer.label(now), color)?;
+    let (text_width, text_height) = slide_renderer.label_size(&surface);
+    let (x, y, width, height) = timer_box(resolution, (text_width, text_height));
+
+    canvas.set_blend_mode(BlendMode::Blend);
+    canvas.set_draw_color(Color::new(0, 0, 0, 0xa0));
+    canvas
+        .fill_rect(Rect::new(x, y, width, height))
+        .map_err(RenderError::Sdl)?;
+
+    let texture = textures
+        .create_texture_from_surface(surface)
+        .map_err(|e| RenderError::Texture(format!("{:?}", e)))?;
+    canvas
+        .copy(
+            &texture,
+            None,
+            Rect::new(
+                x + TIMER_MARGIN as i32,
+                y + TIMER_MARGIN as i32,
+                text_width,
+                text_height,
+            ),
+        )
+        .map_err(RenderError::Sdl)
+}
+
+/// Height of the progress bar
+const PROGRESS_HEIGHT: u32 = 4;
+
+/// Space between the footer's texts and the slide's edges
+const FOOTER_MARGIN: u32 = 16;
+
+/// Draws a bar along the bottom of the slide, as long as the part of the
+/// deck shown so far, the current slide included
+pub fn draw_progress<T: RenderTarget>(
+    canvas: &mut Canvas<T>,
+    resolution: Resolution,
+    color: Color,
+    (slide, slide_count): (usize, usize),
+) -> Result<(), RenderError> {
+    let width = progress_width(resolution.width(), slide, slide_count);
+    if width == 0 {
+        return Ok(());
+    }
+
+    canvas.set_draw_color(color);
+    canvas
+        .fill_rect(Rect::new(
+            0,
+            resolution.height().saturating_sub(PROGRESS_HEIGHT) as i32,
+            width,
+            PROGRESS_HEIGHT,
+        ))
+        .map_err(RenderError::Sdl)
+}
+
+fn progress_width(full_width: u32, slide: usize, slide_count: usize) -> u32 {
+    if slide_count == 0 {
+        return 0;
+    }
+
+    (u64::from(full_width) * (slide.min(slide_count - 1) as u64 + 1) / slide_count as u64) as u32
+}
+
+/// Slide number and count as shown in the footer, e.g. `7 / 42`
+pub fn page_number_label(slide: usize, slide_count: usize) -> String {
+    format!("{} / {}", slide + 1, slide_count)
+}
+
+/// Which bottom corner of the slide a footer text goes in
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum FooterSide {
+    Left,
+    Right,
+}
+
+/// Draws the text in one of the bottom corners of the slide, above the
+/// progress bar
+pub fn draw_footer_text<T: RenderTarget, C>(
+    canvas: &mut Canvas<T>,
+    textures: &TextureCreator<C>,
+    slide_renderer: &SlideRenderer,
+    resolution: Resolution,
+    (text, color): (&str, Color),
+    side: FooterSide,
+) -> Result<(), RenderError> {
+    let surface = slide_renderer.render_label(text, color)?;
+    let text_size = slide_renderer.label_size(&surface);
+    let (x, y) = footer_position(resolution, text_size, side);
+
+    let texture = textures
+        .create_texture_from_surface(surface)
+        .map_err(|e| RenderError::Texture(format!("{:?}", e)))?;
+    canvas
+        .copy(&texture, None, Rect::new(x, y, text_size.0, text_size.1))
+        .map_err(RenderError::Sdl)
+}
+
+fn footer_position(resolution: Resolution, text: (u32, u32), side: FooterSide) -> (i32, i32) {
+    let x = match side {
+        FooterSide::Left => FOOTER_MARGIN,
+        FooterSide::Right => resolution.width().saturating_sub(text.0 + FOOTER_MARGIN),
+    };
+    let y = resolution
+        .height()
+        .saturating_sub(text.1 + FOOTER_MARGIN + PROGRESS_HEIGHT);
+
+    (x as i32, y as i32)
+}
+
+/// Space around the section menu's titles
+const MENU_PADDING: u32 = 24;
+
+/// Draws the titles of the sections in a box in the middle of the slide,
+/// the selected one highlighted
+pub fn draw_section_menu<T: RenderTarget, C>(
+    canvas: &mut Canvas<T>,
+    textures: &TextureCreator<C>,
+    slide_renderer: &SlideRenderer,
+    resolution: Resolution,
+    titles: &[&str],
+    selected: usize,
+) -> Result<(), RenderError> {
+    let labels = titles
+        .iter()
+        .map(|title| slide_renderer.render_label(title, Color::new(0xff, 0xff, 0xff, 0xff)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let sizes: Vec<(u32, u32)> = labels
+        .iter()
+        .map(|label| slide_renderer.label_size(label))
+        .collect();
+    let (x, y, width, height) = menu_box(resolution, &sizes);
+
+    canvas.set_blend_mode(BlendMode::Blend);
+    canvas.set_draw_color(Color::new(0, 0, 0, 0xd0));
+    canvas
+        .fill_rect(Rect::new(x, y, width, height))
+        .map_err(RenderError::Sdl)?;
+
+    let mut top = y + MENU_PADDING as i32;
+    for (index, (label, (text_width, text_height))) in labels.into_iter().zip(sizes).enumerate() {
+        if index == selected {
+            canvas.set_draw_color(Color::new(0xff, 0xff, 0xff, 0x40));
+            canvas
+                .fill_rect(Rect::new(x, top, width, text_height))
+                .map_err(RenderError::Sdl)?;
+        }
+        let texture = textures
+            .create_texture_from_surface(label)
+            .map_err(|e| RenderError::Texture(format!("{:?}", e)))?;
+        canvas
+            .copy(
+                &texture,
+                None,
+                Rect::new(x + MENU_PADDING as i32, top, text_width, text_height),
+            )
+            .map_err(RenderError::Sdl)?;
+        top += text_height as i32;
+    }
+
+    Ok(())
+}
+
+/// Position and size of the menu's box, centered on the slide, for rows of
+/// the given sizes
+fn menu_box(resolution: Resolution, rows: &[(u32, u32)]) -> (i32, i32, u32, u32) {
+    let width = rows.iter().map(|row| row.0).max().unwrap_or(0) + 2 * MENU_PADDING;
+    let height = rows.iter().map(|row| row.1).sum::<u32>() + 2 * MENU_PADDING;
+
+    (
+        (resolution.width().saturating_sub(width) / 2) as i32,
+        (resolution.height().saturating_sub(height) / 2) as i32,
+        width,
+        height,
+    )
+}
+
+/// Position and size of the box behind the timer's text
+fn timer_box(resolution: Resolution, text: (u32, u32)) -> (i32, i32, u32, u32) {
+    let width = text.0 + 2 * TIMER_MARGIN;
+    let height = text.1 + 2 * TIMER_MARGIN;
+
+    (
+        resolution.width().saturating_sub(width + TIMER_MARGIN) as i32,
+        resolution.height().saturating_sub(height + TIMER_MARGIN) as i32,
+        width,
+        height,
+    )
+}
+
+/// Horizontal lines making up a filled circle, as the offset from the
+/// center row and half of the line's length
+fn disc_rows(radius: u32) -> Vec<(i32, i32)> {
+    let radius = radius as i32;
+
+    (-radius..=radius)
+        .map(|dy| {
+            let half_width = f64::from(radius * radius - dy * dy).sqrt().round() as i32;
+            (dy, half_width)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn disc_is_widest_in_the_middle() {
+        assert_eq!(vec![(-2, 0), (-1, 2), (0, 2), (1, 2), (2, 0)], disc_rows(2));
+    }
+
+    #[test]
+    pub fn progress_includes_the_current_slide() {
+        assert_eq!(200, progress_width(800, 0, 4));
+        assert_eq!(800, progress_width(800, 3, 4));
+        assert_eq!(0, progress_width(800, 0, 0));
+        assert_eq!("7 / 42", page_number_label(6, 42));
+    }
+
+    #[test]
+    pub fn footer_texts_sit_in_bottom_corners() {
+        let resolution = Resolution::new(800, 600);
+
+        assert_eq!(
+            (16, 556),
+            footer_position(resolution, (200, 24), FooterSide::Left)
+        );
+        assert_eq!(
+            (724, 556),
+            footer_position(resolution, (60, 24), FooterSide::Right)
+        );
+    }
+
+    #[test]
+    pub fn section_menu_is_centered() {
+        assert_eq!(
+            (276, 228, 248, 144),
+            menu_box(Resolution::new(800, 600), &[(200, 48), (120, 48)])
+        );
+    }
+
+    #[test]
+    pub fn timer_sits_in_bottom_right_corner() {
+        assert_eq!(
+            (676, 540, 112, 48),
+            timer_box(Resolution::new(800, 600), (88, 24))
+        );
+    }
+}
@@ -0,0 +1,122 @@
+use crate::presentation::{Presentation, Resolution};
+use crate::rendering::error::RenderError;
+use crate::rendering::slide_renderer::SlideRenderer;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{Canvas, RenderTarget, TextureCreator};
+use sdl2::surface::Surface;
+
+/// Grid of slide thumbnails to pick a slide from, the thumbnails are
+/// rendered once and kept until the presentation changes
+#[derive(Default)]
+pub struct Overview {
+    thumbnails: Vec<Surface<'static>>,
+}
+
+impl Overview {
+    pub fn new() -> Self {
+        Self { thumbnails: vec![] }
+    }
+
+    /// Drops the thumbnails, so they are rendered again next time
+    pub fn invalidate(&mut self) {
+        self.thumbnails.clear();
+    }
+
+    /// Draws the grid onto a canvas using the slide coordinate system, with
+    /// the selected slide highlighted
+    pub fn render<T: RenderTarget, C>(
+        &mut self,
+        canvas: &mut Canvas<T>,
+        textures: &TextureCreator<C>,
+        slide_renderer: &SlideRenderer,
+        presentation: &Presentation,
+        selected: usize,
+        columns: usize,
+    ) -> Result<(), RenderError> {
+        let slides = presentation.slides();
+        let resolution = presentation.metadata().resolution();
+        let rows = (0..slides.len()).step_by(columns).count();
+
+        if self.thumbnails.len() != slides.len() {
+            let (_, _, width, height) = cell(0, columns, rows, resolution);
+            let scale = slide_renderer.scale();
+            let (width, height) = (
+                (f64::from(width) * scale).round() as u32,
+                (f64::from(height) * scale).round() as u32,
+            );
+            self.thumbnails = slides
+                .iter()
+                .map(|slide| {
+                    slide_renderer.render_surface(slide, slide.step_count(), width, height)
+                })
+                .collect::<Result<Vec<Surface>, RenderError>>()?;
+        }
+
+        canvas.set_draw_color(Color::RGB(0x20, 0x20, 0x20));
+        canvas.fill_rect(None).map_err(RenderError::Sdl)?;
+
+        for (index, thumbnail) in self.thumbnails.iter().enumerate() {
+            let (x, y, width, height) = cell(index, columns, rows, resolution);
+
+            if index == selected {
+                let border = (width / 40).max(2);
+                canvas.set_draw_color(Color::RGB(0xff, 0xc0, 0x40));
+                canvas
+                    .fill_rect(Rect::new(
+                        x - border as i32,
+                        y - border as i32,
+                        width + 2 * border,
+                        height + 2 * border,
+                    ))
+                    .map_err(RenderError::Sdl)?;
+            }
+
+            let texture = textures
+                .create_texture_from_surface(thumbnail)
+                .map_err(|e| RenderError::Texture(format!("{:?}", e)))?;
+            canvas
+                .copy(&texture, None, Rect::new(x, y, width, height))
+                .map_err(RenderError::Sdl)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Position and size of the thumbnail with the given index, the rows are
+/// centered vertically when there are fewer of them than columns
+fn cell(index: usize, columns: usize, rows: usize, resolution: Resolution) -> (i32, i32, u32, u32) {
+    let columns = columns.max(1) as u32;
+    let width = resolution.width() / columns;
+    let height = resolution.height() / columns;
+    let top = resolution.height().saturating_sub(height * rows as u32) / 2;
+    let padding = (width / 20, height / 20);
+
+    (
+        ((index as u32 % columns) * width + padding.0) as i32,
+        (top + (index as u32 / columns) * height + padding.1) as i32,
+        width - 2 * padding.0,
+        height - 2 * padding.1,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn lays_out_thumbnails_in_rows() {
+        let resolution = Resolution::new(800, 600);
+
+        assert_eq!((10, 7, 180, 136), cell(0, 4, 4, resolution));
+        assert_eq!((210, 157, 180, 136), cell(5, 4, 4, resolution));
+    }
+
+    #[test]
+    pub fn centers_incomplete_grid_vertically() {
+        let resolution = Resolution::new(900, 900);
+
+        assert_eq!((15, 165, 270, 270), cell(0, 3, 2, resolution));
+    }
+}
@@ -0,0 +1,438 @@
+use crate::event_loop::{Error, OnLoop};
+use crate::navigation::Navigation;
+use crate::presentation;
+use crate::presentation::{Presentation, Resolution, Slide};
+use crate::rehearsal::{Plan, Timings};
+use crate::rendering::display;
+use crate::rendering::error::RenderError;
+use crate::rendering::offscreen::Offscreen;
+use crate::rendering::slide_renderer::SlideRenderer;
+use crate::talk_timer::{format_minutes, Schedule};
+use sdl2::event::WindowEvent;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::WindowCanvas;
+use sdl2::ttf::Sdl2TtfContext;
+use sdl2::video::WindowPos;
+use sdl2::Sdl;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+const WINDOW_WIDTH: u32 = 1280;
+const WINDOW_HEIGHT: u32 = 720;
+const MARGIN: i32 = 20;
+
+/// A second window for the speaker, showing the current and the next slide,
+/// the speaker notes, the time elapsed since the start, the clock, the pace
+/// against the plan and the schedule and the sections of the talk
+pub struct PresenterView<'a> {
+    sdl_ttf: &'a Sdl2TtfContext,
+    window_canvas: WindowCanvas,
+    offscreen: Offscreen<'a>,
+    presentation: Rc<Presentation>,
+    /// Where a reloaded presentation comes from
+    shared_presentation: &'a RefCell<Rc<Presentation>>,
+    navigation: &'a RefCell<Navigation>,
+    started_at: Instant,
+    visible: bool,
+    /// Slide, window size and the elapsed and clock seconds shown in the
+    /// last frame, none when the window has to be redrawn regardless
+    drawn: Option<(usize, (u32, u32), u64, u64)>,
+    /// Display the presentation fills, the view is moved off it
+    presentation_display: Option<i32>,
+    /// Last rehearsal, the plan is made from it and the slides' targets
+    rehearsed: Option<Timings>,
+    plan: Option<Plan>,
+    schedule: Option<Schedule>,
+}
+
+impl<'a> PresenterView<'a> {
+    pub fn new(
+        sdl: &'a Sdl,
+        sdl_ttf: &'a Sdl2TtfContext,
+        shared_presentation: &'a RefCell<Rc<Presentation>>,
+        navigation: &'a RefCell<Navigation>,
+        presentation_display: Option<i32>,
+    ) -> Result<Self, RenderError> {
+        let presentation = Rc::clone(&shared_presentation.borrow());
+        let window_canvas = sdl
+            .video()
+            .map_err(RenderError::Sdl)?
+            .window(
+                &format!("{} - presenter view", presentation.metadata().title()),
+                WINDOW_WIDTH,
+                WINDOW_HEIGHT,
+            )
+            .resizable()
+            .allow_highdpi()
+            .build()
+            .map_err(|e| RenderError::Sdl(format!("{:?}", e)))?
+            .into_canvas()
+            .build()
+            .map_err(|e| RenderError::Sdl(format!("{:?}", e)))?;
+        let offscreen = offscreen(sdl_ttf, &window_canvas, Rc::clone(&presentation))?;
+
+        let mut presenter_view = Self {
+            sdl_ttf,
+            window_canvas,
+            offscreen,
+            presentation,
+            shared_presentation,
+            navigation,
+            started_at: Instant::now(),
+            visible: true,
+            drawn: None,
+            presentation_display,
+            rehearsed: None,
+            plan: None,
+            schedule: None,
+        };
+        presenter_view.plan_talk();
+        presenter_view.move_off_presentation();
+
+        Ok(presenter_view)
+    }
+
+    /// Shows whether the talk is ahead of or behind the plan made from the
+    /// rehearsal, and the time planned for the current slide
+    pub fn set_rehearsed(&mut self, rehearsed: Timings) {
+        self.rehearsed = Some(rehearsed);
+        self.plan_talk();
+    }
+
+    /// Makes the plan and the schedule of the presentation, the schedule
+    /// counts down to its next slide and shows whether the talk is ahead of
+    /// or behind it
+    fn plan_talk(&mut self) {
+        self.plan = Some(Plan::new(&self.presentation, self.rehearsed.as_ref()))
+            .filter(|plan| !plan.is_empty());
+        self.schedule =
+            Some(Schedule::new(&self.presentation)).filter(|schedule| !schedule.is_empty());
+    }
+
+    /// Centers the window on a display the presentation doesn't fill, if
+    /// there is one
+    fn move_off_presentation(&mut self) {
+        let window = self.window_canvas.window();
+        let display = self
+            .presentation_display
+            .and_then(|taken| display::other_display(window.subsystem(), taken))
+            .filter(|&index| window.display_index() != Ok(index));
+        let bounds = display.and_then(|index| window.subsystem().display_bounds(index).ok());
+
+        if let Some(bounds) = bounds {
+            let (x, y) = display::centered_on(bounds, window.size());
+            self.window_canvas
+                .window_mut()
+                .set_position(WindowPos::Positioned(x), WindowPos::Positioned(y));
+        }
+    }
+
+    /// Catches up with a presentation reloaded since the last frame
+    fn follow_reload(&mut self) -> Result<(), RenderError> {
+        let presentation = Rc::clone(&self.shared_presentation.borrow());
+        if Rc::ptr_eq(&presentation, &self.presentation) {
+            return Ok(());
+        }
+
+        self.offscreen = offscreen(self.sdl_ttf, &self.window_canvas, Rc::clone(&presentation))?;
+        self.window_canvas
+            .window_mut()
+            .set_title(&format!(
+                "{} - presenter view",
+                presentation.metadata().title()
+            ))
+            .map_err(|e| RenderError::Sdl(format!("{:?}", e)))?;
+        self.presentation = presentation;
+        self.plan_talk();
+        self.drawn = None;
+
+        Ok(())
+    }
+
+    fn draw_slide(&mut self, slide: &Slide, area: Rect) -> Result<(), RenderError> {
+        let surface = self.offscreen.render_surface(slide)?;
+        let texture_creator = self.window_canvas.texture_creator();
+        let texture = texture_creator
+            .create_texture_from_surface(surface)
+            .map_err(|e| RenderError::Texture(format!("{:?}", e)))?;
+
+        self.window_canvas
+            .copy(
+                &texture,
+                None,
+                fit(area, self.presentation.metadata().resolution()),
+            )
+            .map_err(RenderError::Sdl)
+    }
+
+    /// Draws the line with its top left corner at the given point and returns
+    /// its height
+    fn draw_label(&mut self, text: &str, x: i32, y: i32) -> Result<u32, RenderError> {
+        if text.is_empty() {
+            return Ok(0);
+        }
+
+        let surface = self
+            .offscreen
+            .slide_renderer()
+            .render_label(text, presentation::Color::new(0xff, 0xff, 0xff, 0xff))?;
+        let (width, height) = surface.size();
+        let texture_creator = self.window_canvas.texture_creator();
+        let texture = texture_creator
+            .create_texture_from_surface(surface)
+            .map_err(|e| RenderError::Texture(format!("{:?}", e)))?;
+
+        self.window_canvas
+            .copy(&texture, None, Rect::new(x, y, width, height))
+            .map_err(RenderError::Sdl)?;
+
+        Ok(height)
+    }
+}
+
+impl<'a> OnLoop for PresenterView<'a> {
+    fn run(&mut self) -> Result<(), Error> {
+        if !self.visible {
+            return Ok(());
+        }
+
+        self.follow_reload()?;
+        let (width, height) = self.window_canvas.output_size().map_err(RenderError::Sdl)?;
+        let margin = (f64::from(MARGIN) * self.offscreen.slide_renderer().scale()).round() as i32;
+        let current_slide = self.navigation.borrow().current_slide();
+        let elapsed = self.started_at.elapsed();
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let state = (
+            current_slide,
+            (width, height),
+            elapsed.as_secs(),
+            now.as_secs(),
+        );
+        if self.drawn == Some(state) {
+            return Ok(());
+        }
+        self.drawn = Some(state);
+
+        let slides_bottom = (height * 3 / 5) as i32;
+        let side_left = (width * 3 / 5) as i32;
+        let presentation = Rc::clone(&self.presentation);
+
+        self.window_canvas
+            .set_draw_color(Color::RGB(0x20, 0x20, 0x20));
+        self.window_canvas.clear();
+
+        if let Some(slide) = presentation.slides().get(current_slide) {
+            self.draw_slide(
+                slide,
+                Rect::new(
+                    margin,
+                    margin,
+                    (side_left - 2 * margin).max(1) as u32,
+                    (slides_bottom - margin).max(1) as u32,
+                ),
+            )?;
+        }
+
+        let side_width = (width as i32 - side_left - margin).max(1) as u32;
+        let preview_height = ((slides_bottom - margin) / 2).max(1) as u32;
+        let mut top = margin;
+        if let Some(slide) = presentation.slides().get(current_slide + 1) {
+            self.draw_slide(slide, Rect::new(side_left, top, side_width, preview_height))?;
+        }
+        top += preview_height as i32 + margin;
+
+        let slide_counter = format!(
+            "slide {} of {}",
+            current_slide + 1,
+            presentation.slides().len()
+        );
+        top += self.draw_label(&slide_counter, side_left, top)? as i32;
+        let pace = self.plan.as_ref().map(|plan| {
+            let planned = plan.of(current_slide).map_or_else(String::new, |planned| {
+                format!(", {} planned for this slide", format_minutes(planned))
+            });
+            format!("{}{}", plan.pace(current_slide, elapsed).label(), planned)
+        });
+        let schedule = self.schedule.as_ref().map(|schedule| {
+            let countdown = schedule
+                .countdown(current_slide, elapsed)
+                .map_or_else(String::new, |countdown| format!(", {}", countdown));
+            format!(
+                "schedule {}{}",
+                schedule.pace(current_slide, elapsed).label(),
+                countdown
+            )
+        });
+        let elapsed = format!("elapsed {}", format_duration(elapsed));
+        top += self.draw_label(&elapsed, side_left, top)? as i32;
+        for line in pace.iter().chain(&schedule) {
+            top += self.draw_label(line, side_left, top)? as i32;
+        }
+        top += self.draw_label(&format!("{} UTC", format_clock(now)), side_left, top)? as i32;
+
+        // sections below, the current one marked
+        top += margin;
+        let current_section = presentation.section_of(current_slide);
+        for (index, section) in presentation.sections().iter().enumerate() {
+            let marker = if current_section == Some(index) {
+                "> "
+            } else {
+                "  "
+            };
+            top +=
+                self.draw_label(&format!("{}{}", marker, section.title()), side_left, top)? as i32;
+        }
+
+        let mut top = slides_bottom + margin;
+        if let Some(slide) = presentation.slides().get(current_slide) {
+            for note in slide.notes() {
+                for line in note.lines() {
+                    top += self.draw_label(line, margin, top)? as i32;
+                }
+            }
+        }
+
+        self.window_canvas.present();
+
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        "presenter view"
+    }
+
+    /// When the elapsed time or the clock shows the next second
+    fn wake_up_in(&self, now: Instant) -> Option<Duration> {
+        if !self.visible {
+            return None;
+        }
+
+        let elapsed = now.saturating_duration_since(self.started_at);
+        let clock = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+
+        Some(until_next_second(elapsed).min(until_next_second(clock)))
+    }
+
+    fn window_id(&self) -> Option<u32> {
+        Some(self.window_canvas.window().id())
+    }
+
+    fn handle_displays_changed(&mut self) -> Result<(), Error> {
+        self.drawn = None;
+        self.move_off_presentation();
+
+        Ok(())
+    }
+
+    /// Closing the presenter view only hides it, the presentation goes on
+    fn handle_window_event(&mut self, event: &WindowEvent) -> bool {
+        self.drawn = None;
+        if let WindowEvent::SizeChanged(..) = event {
+            let scale = display::interface_scale(&self.window_canvas);
+            if let Err(error) = self.offscreen.slide_renderer_mut().set_scale(scale) {
+                log::warn!("Failed to rescale the presenter view: {}", error);
+            }
+        }
+        if *event == WindowEvent::Close {
+            self.visible = false;
+            self.window_canvas.window_mut().hide();
+        }
+
+        true
+    }
+}
+
+/// Renders the presentation's slides at their resolution, scaled for the
+/// window's display
+fn offscreen<'a>(
+    sdl_ttf: &'a Sdl2TtfContext,
+    window_canvas: &WindowCanvas,
+    presentation: Rc<Presentation>,
+) -> Result<Offscreen<'a>, RenderError> {
+    let resolution = presentation.metadata().resolution();
+    let mut slide_renderer = SlideRenderer::new(sdl_ttf, presentation)?;
+    slide_renderer.set_scale(display::interface_scale(window_canvas))?;
+
+    Ok(Offscreen::new(
+        slide_renderer,
+        resolution.width(),
+        resolution.height(),
+    ))
+}
+
+fn until_next_second(time: Duration) -> Duration {
+    Duration::from_secs(time.as_secs() + 1).saturating_sub(time)
+}
+
+/// The largest rectangle with the slide's aspect ratio that fits in the area,
+/// centered in it
+fn fit(area: Rect, resolution: Resolution) -> Rect {
+    let (width, height) = fit_size(
+        (area.width(), area.height()),
+        (resolution.width(), resolution.height()),
+    );
+
+    Rect::new(
+        area.x() + ((area.width() - width) / 2) as i32,
+        area.y() + ((area.height() - height) / 2) as i32,
+        width,
+        height,
+    )
+}
+
+fn fit_size(area: (u32, u32), resolution: (u32, u32)) -> (u32, u32) {
+    let (area_width, area_height) = (u64::from(area.0), u64::from(area.1));
+    let (width, height) = (
+        u64::from(resolution.0.max(1)),
+        u64::from(resolution.1.max(1)),
+    );
+
+    if area_width * height <= area_height * width {
+        (area.0, (area_width * height / width) as u32)
+    } else {
+        ((area_height * width / height) as u32, area.1)
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+
+    format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+/// Time of day for the given time since the Unix epoch
+fn format_clock(since_epoch: Duration) -> String {
+    format_duration(Duration::from_secs(since_epoch.as_secs() % 86400))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn fits_slide_in_area() {
+        assert_eq!((400, 225), fit_size((400, 400), (1920, 1080)));
+        assert_eq!((300, 225), fit_size((400, 225), (800, 600)));
+    }
+
+    #[test]
+    pub fn formats_elapsed_time() {
+        assert_eq!("01:02:03", format_duration(Duration::from_secs(3723)));
+    }
+
+    #[test]
+    pub fn formats_clock() {
+        assert_eq!("00:00:05", format_clock(Duration::from_secs(86400 * 3 + 5)));
+    }
+}
@@ -1,75 +1,619 @@
-use crate::event_loop::OnLoop;
-use crate::presentation::Presentation;
+use crate::event_loop::{Error, OnLoop};
+use crate::navigation::Navigation;
+use crate::playback::Playback;
+use crate::pointer::Pointer;
+use crate::presentation::{self, Presentation, Section, Slide, SlideElement, TransitionKind};
+use crate::rendering::animation;
+use crate::rendering::display;
+use crate::rendering::error::RenderError;
+use crate::rendering::frame_pacing::FramePacer;
+use crate::rendering::media;
+use crate::rendering::overlay::{self, FooterSide};
+use crate::rendering::overview::Overview;
+use crate::rendering::slide_renderer::SlideRenderer;
+use crate::rendering::transition::ActiveTransition;
+use crate::rendering::window::WindowOptions;
+use crate::talk_timer::{TalkTimer, TimeWarning};
+use crate::watch::PendingReload;
+use sdl2::event::WindowEvent;
 use sdl2::pixels::Color;
-use sdl2::rect::Point;
-use sdl2::render::{Texture, WindowCanvas};
-use sdl2::surface::Surface;
-use sdl2::ttf::{Font, Sdl2TtfContext};
+use sdl2::render::{TextureCreator, WindowCanvas};
+use sdl2::ttf::Sdl2TtfContext;
+use sdl2::video::{FullscreenType, WindowContext, WindowPos};
 use sdl2::Sdl;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// Everything the window shows apart from the moving parts, a frame is only
+/// drawn when it differs from the last one drawn
+#[derive(PartialEq)]
+struct FrameState {
+    slide: (usize, u32),
+    overview: Option<(usize, usize)>,
+    /// Pointer position and the number of points drawn with it
+    pointer: Option<(Option<(i32, i32)>, usize)>,
+    timer: Option<(String, TimeWarning)>,
+    section_menu: Option<usize>,
+}
 
 pub struct SDL2<'a> {
-    font: Font<'a, 'a>,
+    sdl_ttf: &'a Sdl2TtfContext,
+    slide_renderer: SlideRenderer<'a>,
     window_canvas: WindowCanvas,
+    /// Creates the textures of whatever is copied onto the window
+    textures: TextureCreator<WindowContext>,
+    presentation: Rc<Presentation>,
+    /// Hands a reloaded presentation to the other participants
+    shared_presentation: &'a RefCell<Rc<Presentation>>,
+    navigation: &'a RefCell<Navigation>,
+    pending_reload: &'a RefCell<PendingReload>,
+    error: Option<String>,
+    /// Slide and build step drawn in the last frame
+    shown: Option<(usize, u32)>,
+    /// When the shown build step was revealed, its elements come in from
+    /// then on
+    revealed_at: Instant,
+    transition: Option<ActiveTransition>,
+    overview: Overview,
+    pointer: Option<&'a RefCell<Pointer>>,
+    timer: Option<&'a RefCell<TalkTimer>>,
+    playback: Option<&'a RefCell<Playback>>,
+    /// State of the last frame drawn, none when the window has to be
+    /// redrawn regardless
+    drawn: Option<FrameState>,
+    /// Color drawn instead of the slides' backgrounds and around them
+    backdrop: Option<presentation::Color>,
+    /// Display the window was opened fullscreen on
+    display: Option<i32>,
+    pacer: FramePacer,
 }
 
 impl<'a> SDL2<'a> {
-    pub fn new(sdl: &'a Sdl, sdl_ttf: &'a Sdl2TtfContext, presentation: &'a Presentation) -> Self {
-        let mut window_canvas = sdl
-            .video()
-            .unwrap()
-            .window("some presentation", 800, 600)
-            .position_centered()
+    pub fn new(
+        sdl: &'a Sdl,
+        sdl_ttf: &'a Sdl2TtfContext,
+        shared_presentation: &'a RefCell<Rc<Presentation>>,
+        navigation: &'a RefCell<Navigation>,
+        pending_reload: &'a RefCell<PendingReload>,
+        window_options: WindowOptions,
+    ) -> Result<Self, RenderError> {
+        let presentation = Rc::clone(&shared_presentation.borrow());
+        let resolution = presentation.metadata().resolution();
+        let video = sdl.video().map_err(RenderError::Sdl)?;
+        let mut window = video.window(
+            presentation.metadata().title(),
+            resolution.width(),
+            resolution.height(),
+        );
+        window.resizable().allow_highdpi();
+        window_options
+            .apply(&video, &mut window)
+            .map_err(RenderError::NoDisplay)?;
+        let mut canvas = window
             .build()
-            .unwrap()
-            .into_canvas()
+            .map_err(|e| RenderError::Sdl(format!("{:?}", e)))?
+            .into_canvas();
+        if window_options.vsync() {
+            canvas = canvas.present_vsync();
+        }
+        let mut window_canvas = canvas
             .build()
-            .unwrap();
+            .map_err(|e| RenderError::Sdl(format!("{:?}", e)))?;
+        let pacer = FramePacer::new(
+            display::refresh_rate(&window_canvas),
+            window_options.vsync(),
+        );
+        let backdrop = window_options.backdrop();
+        let mut slide_renderer = SlideRenderer::new(sdl_ttf, Rc::clone(&presentation))?;
+        if let Some(backdrop) = backdrop {
+            slide_renderer.set_backdrop(backdrop);
+        }
 
-        window_canvas.set_draw_color(Color::RGB(0, 0, 0));
+        slide_renderer.prepare_canvas(&mut window_canvas)?;
+        slide_renderer.set_scale(display::slide_scale(
+            &window_canvas,
+            presentation.metadata().resolution(),
+        ))?;
+        window_canvas.set_draw_color(letterbox_color(backdrop));
         window_canvas.clear();
         window_canvas.present();
 
-        Self {
-            font: sdl_ttf
-                .load_font(presentation.style().fonts().first().unwrap().path(), 24)
-                .unwrap(),
+        Ok(Self {
+            sdl_ttf,
+            slide_renderer,
+            textures: window_canvas.texture_creator(),
             window_canvas,
+            presentation,
+            shared_presentation,
+            navigation,
+            pending_reload,
+            error: None,
+            shown: None,
+            revealed_at: Instant::now(),
+            transition: None,
+            overview: Overview::new(),
+            pointer: None,
+            timer: None,
+            playback: None,
+            drawn: None,
+            backdrop,
+            display: window_options.display(),
+            pacer,
+        })
+    }
+
+    /// Pointer drawn over the current slide
+    pub fn set_pointer(&mut self, pointer: &'a RefCell<Pointer>) {
+        self.pointer = Some(pointer);
+    }
+
+    /// Talk timer drawn over everything else
+    pub fn set_timer(&mut self, timer: &'a RefCell<TalkTimer>) {
+        self.timer = Some(timer);
+    }
+
+    /// Clock the current slide's videos and animated images play by, it is
+    /// restarted whenever another slide is shown
+    pub fn set_playback(&mut self, playback: &'a RefCell<Playback>) {
+        self.playback = Some(playback);
+    }
+
+    /// Swaps in a reloaded presentation and shares it with the other
+    /// participants, keeping the old one if the new one cannot be rendered
+    fn reload(&mut self, presentation: Presentation) -> Result<(), RenderError> {
+        let presentation = Rc::new(presentation);
+        let mut slide_renderer = SlideRenderer::new(self.sdl_ttf, Rc::clone(&presentation))?;
+        if let Some(backdrop) = self.backdrop {
+            slide_renderer.set_backdrop(backdrop);
         }
+
+        slide_renderer.prepare_canvas(&mut self.window_canvas)?;
+        slide_renderer.set_scale(display::slide_scale(
+            &self.window_canvas,
+            presentation.metadata().resolution(),
+        ))?;
+        {
+            let mut navigation = self.navigation.borrow_mut();
+            navigation.set_slide_count(presentation.slides().len());
+            navigation.set_step_counts(
+                presentation
+                    .slides()
+                    .iter()
+                    .map(Slide::step_count)
+                    .collect(),
+            );
+            navigation.set_section_starts(
+                presentation
+                    .sections()
+                    .iter()
+                    .map(Section::first_slide)
+                    .collect(),
+            );
+        }
+        self.slide_renderer = slide_renderer;
+        *self.shared_presentation.borrow_mut() = Rc::clone(&presentation);
+        self.presentation = presentation;
+        self.transition = None;
+        self.overview.invalidate();
+        self.drawn = None;
+
+        Ok(())
     }
 
-    fn window_center(&self) -> Point {
-        Point::new(
-            (self.window_canvas.window().size().0 / 2) as i32,
-            (self.window_canvas.window().size().1 / 2) as i32,
-        )
+    fn frame_state(&self, now: Instant) -> FrameState {
+        let navigation = self.navigation.borrow();
+        let pointer = self
+            .pointer
+            .map(|pointer| pointer.borrow())
+            .filter(|pointer| pointer.is_enabled());
+        let timer = self
+            .timer
+            .map(|timer| timer.borrow())
+            .filter(|timer| timer.is_visible());
+
+        FrameState {
+            slide: (navigation.current_slide(), navigation.current_step()),
+            overview: navigation
+                .overview_selection()
+                .map(|selected| (selected, navigation.overview_columns())),
+            pointer: pointer.map(|pointer| {
+                (
+                    pointer.position(),
+                    pointer.strokes().iter().map(Vec::len).sum(),
+                )
+            }),
+            timer: timer.map(|timer| (timer.label(now), timer.warning(now))),
+            section_menu: navigation.section_selection(),
+        }
     }
 
-    fn render_text(&self, text: &str) -> Result<Surface, String> {
-        Ok(self
-            .font
-            .render(text)
-            .blended(Color::RGB(0xff, 0x18, 0x85))
-            .map_err(|e| return format!("{:?}", e))?)
+    /// Whether the frames change by themselves, during a transition, while
+    /// elements of the current build step come in or while videos or
+    /// animated images on the current slide play
+    fn is_moving(&self) -> bool {
+        if self.transition.is_some() {
+            return true;
+        }
+
+        let navigation = self.navigation.borrow();
+        if self.error.is_some() || navigation.overview_selection().is_some() {
+            return false;
+        }
+        let Some(slide) = self.presentation.slides().get(navigation.current_slide()) else {
+            return false;
+        };
+        if animation::is_coming_in(
+            &self.presentation,
+            slide,
+            navigation.current_step(),
+            self.revealed_at.elapsed(),
+        ) {
+            return true;
+        }
+
+        let playing = match self.playback {
+            Some(playback) => !playback.borrow().is_paused(),
+            None => false,
+        };
+        playing && plays_media(&self.presentation, slide)
     }
-}
 
-impl<'a> OnLoop for SDL2<'a> {
-    fn run(&mut self) -> Result<(), String> {
+    /// Lays the slides out again for the window's new size
+    fn fit_to_window(&mut self) {
+        let scale = display::slide_scale(
+            &self.window_canvas,
+            self.presentation.metadata().resolution(),
+        );
+        if let Err(error) = self.slide_renderer.set_scale(scale) {
+            self.error = Some(error.to_string());
+        }
+        self.slide_renderer.invalidate();
+        self.overview.invalidate();
+        self.drawn = None;
+    }
+
+    /// Starts the transition into the current slide when the slide changed
+    /// since the last frame, and lets the elements of a newly revealed
+    /// build step come in
+    fn follow_navigation(&mut self) -> Result<(), RenderError> {
+        let current = {
+            let navigation = self.navigation.borrow();
+            (navigation.current_slide(), navigation.current_step())
+        };
+
+        match self.shown.replace(current) {
+            Some(previous) if previous.0 != current.0 => {
+                self.slide_renderer.invalidate();
+                self.transition = self.start_transition(previous, current)?;
+                self.revealed_at = Instant::now();
+                if let Some(playback) = self.playback {
+                    playback.borrow_mut().restart(Instant::now());
+                }
+            }
+            Some(previous) if previous.1 >= current.1 => {}
+            _ => self.revealed_at = Instant::now(),
+        }
+
+        Ok(())
+    }
+
+    /// Progress bar, slide number and title footer, as the metadata asks
+    fn draw_deck_overlays(&mut self) -> Result<(), RenderError> {
+        let metadata = self.presentation.metadata();
+        let resolution = metadata.resolution();
+        let color = self
+            .presentation
+            .style()
+            .text_color()
+            .unwrap_or_else(|| presentation::Color::new(0xff, 0xff, 0xff, 0xff));
+        let slide = self.navigation.borrow().current_slide();
+        let slide_count = self.presentation.slides().len();
+
+        if metadata.show_progress() {
+            overlay::draw_progress(
+                &mut self.window_canvas,
+                resolution,
+                color,
+                (slide, slide_count),
+            )?;
+        }
+        if metadata.show_footer() {
+            overlay::draw_footer_text(
+                &mut self.window_canvas,
+                &self.textures,
+                &self.slide_renderer,
+                resolution,
+                (metadata.title(), color),
+                FooterSide::Left,
+            )?;
+        }
+        if metadata.show_page_numbers() && slide_count > 0 {
+            overlay::draw_footer_text(
+                &mut self.window_canvas,
+                &self.textures,
+                &self.slide_renderer,
+                resolution,
+                (&overlay::page_number_label(slide, slide_count), color),
+                FooterSide::Right,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn draw_frame(&mut self) -> Result<(), RenderError> {
+        self.window_canvas
+            .set_draw_color(letterbox_color(self.backdrop));
         self.window_canvas.clear();
 
-        let txt = self.render_text("test")?;
+        let overview = {
+            let navigation = self.navigation.borrow();
+            navigation
+                .overview_selection()
+                .map(|selected| (selected, navigation.overview_columns()))
+        };
+
+        if let Some(error) = &self.error {
+            self.slide_renderer
+                .render_error(&mut self.window_canvas, &self.textures, error)?;
+        } else if let Some((selected, columns)) = overview {
+            self.overview.render(
+                &mut self.window_canvas,
+                &self.textures,
+                &self.slide_renderer,
+                &self.presentation,
+                selected,
+                columns,
+            )?;
+        } else if let Some(transition) = &self.transition {
+            transition.render(
+                &mut self.window_canvas,
+                &self.textures,
+                self.presentation.metadata().resolution(),
+            )?;
+        } else {
+            let navigation = self.navigation.borrow();
+            let position = self.playback.map_or(Duration::from_millis(0), |playback| {
+                playback.borrow().position(Instant::now())
+            });
+            if let Some(slide) = self.presentation.slides().get(navigation.current_slide()) {
+                self.slide_renderer.render_playing(
+                    &mut self.window_canvas,
+                    &self.textures,
+                    slide,
+                    navigation.current_step(),
+                    position,
+                    Some(self.revealed_at.elapsed()),
+                )?;
+            }
+            if let Some(pointer) = self.pointer {
+                let mut pointer = pointer.borrow_mut();
+                pointer.set_slide(navigation.current_slide());
+                overlay::draw_pointer(&mut self.window_canvas, &pointer)?;
+            }
+        }
+
+        if self.error.is_none() && overview.is_none() {
+            self.draw_deck_overlays()?;
+        }
+        let section_menu = self.navigation.borrow().section_selection();
+        if let (None, Some(selected)) = (&self.error, section_menu) {
+            let titles: Vec<&str> = self
+                .presentation
+                .sections()
+                .iter()
+                .map(|section| section.title().as_str())
+                .collect();
+            overlay::draw_section_menu(
+                &mut self.window_canvas,
+                &self.textures,
+                &self.slide_renderer,
+                self.presentation.metadata().resolution(),
+                &titles,
+                selected,
+            )?;
+        }
+
+        if let Some(timer) = self.timer {
+            overlay::draw_timer(
+                &mut self.window_canvas,
+                &self.textures,
+                &self.slide_renderer,
+                self.presentation.metadata().resolution(),
+                &timer.borrow(),
+            )?;
+        }
+
+        self.window_canvas.present();
 
-        let txt_rect = txt.rect();
-        let mut dst_txt_rect = txt_rect;
-        dst_txt_rect.center_on(self.window_center());
-        let texture_creator = self.window_canvas.texture_creator();
-        let texture: Texture = texture_creator
-            .create_texture_from_surface(txt)
-            .map_err(|e| return format!("{:?}", e))?;
+        Ok(())
+    }
 
-        self.window_canvas.copy(&texture, txt_rect, dst_txt_rect)?;
+    /// Replaces what was drawn of a failed frame with the error, so that a
+    /// failure doesn't leave a stale or half drawn window behind
+    fn show_failure(&mut self, message: &str) {
+        self.window_canvas.set_draw_color(Color::RGB(0, 0, 0));
+        self.window_canvas.clear();
+        let shown =
+            self.slide_renderer
+                .render_error(&mut self.window_canvas, &self.textures, message);
         self.window_canvas.present();
 
+        if let Err(error) = shown {
+            log::debug!("Failed to show the error: {}", error);
+        }
+    }
+
+    /// Both ends of the transition are given as a slide index and the build
+    /// step to draw it at
+    fn start_transition(
+        &self,
+        from: (usize, u32),
+        to: (usize, u32),
+    ) -> Result<Option<ActiveTransition>, RenderError> {
+        let transition = match self.presentation.transition_to(to.0) {
+            Some(transition)
+                if transition.kind() != TransitionKind::None
+                    && transition.duration() > Duration::from_millis(0) =>
+            {
+                transition
+            }
+            _ => return Ok(None),
+        };
+        let slides = self.presentation.slides();
+        let resolution = self.presentation.metadata().resolution();
+        let scale = self.slide_renderer.scale();
+        let (width, height) = (
+            (f64::from(resolution.width()) * scale).round() as u32,
+            (f64::from(resolution.height()) * scale).round() as u32,
+        );
+
+        match (slides.get(from.0), slides.get(to.0)) {
+            (Some(from_slide), Some(to_slide)) => Ok(Some(ActiveTransition::new(
+                transition,
+                self.slide_renderer
+                    .render_surface(from_slide, from.1, width, height)?,
+                self.slide_renderer
+                    .render_arriving_surface(to_slide, to.1, width, height)?,
+                to.0 > from.0,
+            ))),
+            _ => Ok(None),
+        }
+    }
+}
+
+impl<'a> OnLoop for SDL2<'a> {
+    fn run(&mut self) -> Result<(), Error> {
+        let pending_reload = self.pending_reload.borrow_mut().take();
+        if let Some(reload) = pending_reload {
+            self.error = reload
+                .and_then(|presentation| {
+                    self.reload(presentation).map_err(|error| error.to_string())
+                })
+                .err();
+            self.drawn = None;
+        }
+
+        let delta = self.pacer.tick(Instant::now());
+        if let Some(transition) = &mut self.transition {
+            transition.advance(delta);
+        }
+        self.follow_navigation()?;
+        if let Some(transition) = &self.transition {
+            if transition.is_finished() {
+                self.transition = None;
+                self.revealed_at = Instant::now();
+                self.drawn = None;
+            }
+        }
+
+        let state = self.frame_state(Instant::now());
+        if !self.is_moving() && self.drawn.as_ref() == Some(&state) {
+            return Ok(());
+        }
+        self.drawn = Some(state);
+
+        if let Err(error) = self.draw_frame() {
+            // drawn again when the event loop retries
+            self.drawn = None;
+            self.show_failure(&error.to_string());
+            return Err(Error::Render(error));
+        }
+        self.pacer.frame_presented(Instant::now());
+
         Ok(())
     }
+
+    fn name(&self) -> &'static str {
+        "presentation window"
+    }
+
+    /// Right away when something changed since the last frame or a reload
+    /// is waiting, the next frame while something moves and when the
+    /// timer's label changes otherwise
+    fn wake_up_in(&self, now: Instant) -> Option<Duration> {
+        if self.is_moving() {
+            return Some(self.pacer.wait(now));
+        }
+        if self.pending_reload.borrow().is_some()
+            || self.drawn.as_ref() != Some(&self.frame_state(now))
+        {
+            return Some(Duration::from_millis(0));
+        }
+
+        self.timer
+            .map(|timer| timer.borrow())
+            .filter(|timer| timer.is_visible())
+            .map(|timer| {
+                let elapsed = timer.elapsed(now);
+                Duration::from_secs(elapsed.as_secs() + 1).saturating_sub(elapsed)
+            })
+    }
+
+    fn window_id(&self) -> Option<u32> {
+        Some(self.window_canvas.window().id())
+    }
+
+    fn handle_window_event(&mut self, event: &WindowEvent) -> bool {
+        match event {
+            WindowEvent::Resized(..) | WindowEvent::SizeChanged(..) => self.fit_to_window(),
+            WindowEvent::Exposed | WindowEvent::Restored => self.drawn = None,
+            WindowEvent::Moved(..) => self
+                .pacer
+                .set_refresh_rate(display::refresh_rate(&self.window_canvas)),
+            _ => {}
+        }
+
+        *event != WindowEvent::Close
+    }
+
+    /// Goes back onto the chosen display when it is connected again
+    fn handle_displays_changed(&mut self) -> Result<(), Error> {
+        self.drawn = None;
+        self.pacer
+            .set_refresh_rate(display::refresh_rate(&self.window_canvas));
+        let index = match self.display {
+            Some(index) if self.window_canvas.window().display_index() != Ok(index) => index,
+            _ => return Ok(()),
+        };
+        let bounds = self
+            .window_canvas
+            .window()
+            .subsystem()
+            .display_bounds(index)
+            .map_err(Error::MoveWindow)?;
+
+        let window = self.window_canvas.window_mut();
+        window
+            .set_fullscreen(FullscreenType::Off)
+            .map_err(Error::MoveWindow)?;
+        window.set_position(
+            WindowPos::Positioned(bounds.x()),
+            WindowPos::Positioned(bounds.y()),
+        );
+        window
+            .set_fullscreen(FullscreenType::Desktop)
+            .map_err(Error::MoveWindow)?;
+
+        Ok(())
+    }
+}
+
+/// Color of the bars around slides that don't fill the window
+fn letterbox_color(backdrop: Option<presentation::Color>) -> Color {
+    backdrop.map_or(Color::RGB(0, 0, 0), Color::from)
+}
+
+/// Whether the slide shows videos or animated images
+fn plays_media(presentation: &Presentation, slide: &Slide) -> bool {
+    presentation
+        .arranged_elements(slide)
+        .iter()
+        .flat_map(|(element, _)| element.walk())
+        .any(|element| match element {
+            SlideElement::Video(_) => true,
+            SlideElement::Image(image) => media::is_animated(image.path()),
+            _ => false,
+        })
 }
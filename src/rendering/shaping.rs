@@ -0,0 +1,219 @@
+use crate::presentation::Color;
+use crate::rendering::color_glyph::{self, Outline};
+use crate::rendering::error::RenderError;
+use resvg::tiny_skia::{FillRule, Paint, PathBuilder, Pixmap, Transform};
+use rustybuzz::ttf_parser::{GlyphId, RgbaColor};
+use rustybuzz::{Direction, Face, UnicodeBuffer};
+use unicode_bidi::BidiInfo;
+
+/// Whether the character is an emoji or a pictograph, which are drawn from
+/// a color emoji font when the text's fonts don't have them
+pub fn is_emoji(character: char) -> bool {
+    let code = u32::from(character);
+
+    (0x2300..0x2400).contains(&code)
+        || (0x2600..0x27c0).contains(&code)
+        || (0x2b00..0x2c00).contains(&code)
+        || (0x1f000..0x1fb00).contains(&code)
+}
+
+/// Whether the text has right-to-left, joining or combining characters,
+/// which `SDL_ttf` draws one by one and gets wrong, or emoji, which it
+/// can't draw in color. Such text goes through the shaper instead.
+pub fn needs_shaping(text: &str) -> bool {
+    text.chars().any(|character| {
+        let code = u32::from(character);
+        // combining diacritical marks
+        (0x0300..0x0370).contains(&code)
+            // Hebrew, Arabic and the other scripts up to Myanmar, the Indic ones included
+            || (0x0590..0x10a0).contains(&code)
+            || (0x1780..0x1800).contains(&code)
+            // zero width joiners and bidi controls
+            || (0x200c..0x2010).contains(&code)
+            || (0x202a..0x202f).contains(&code)
+            // presentation forms and variation selectors
+            || (0xfb1d..0xfe10).contains(&code)
+            || (0xfe70..0xff00).contains(&code)
+            || is_emoji(character)
+    })
+}
+
+/// Part of a line going in one direction, the runs of a line are ordered
+/// the way they are shown from left to right
+#[derive(Debug, Eq, PartialEq)]
+pub struct Run<'t> {
+    pub text: &'t str,
+    pub right_to_left: bool,
+}
+
+/// Splits the line into runs with the Unicode bidirectional algorithm
+pub fn visual_runs(text: &str) -> Vec<Run<'_>> {
+    let bidi = BidiInfo::new(text, None);
+    let mut runs = vec![];
+    for paragraph in &bidi.paragraphs {
+        let (levels, ranges) = bidi.visual_runs(paragraph, paragraph.range.clone());
+        for range in ranges {
+            runs.push(Run {
+                right_to_left: levels[range.start].is_rtl(),
+                text: &text[range],
+            });
+        }
+    }
+
+    runs
+}
+
+/// A glyph placed on the line, in font units from the start of the line
+/// on the baseline
+struct Glyph {
+    id: GlyphId,
+    x: i32,
+    y: i32,
+}
+
+/// Glyphs of the line in visual order and the width of the line, in font
+/// units
+fn shape(face: &Face, text: &str) -> (Vec<Glyph>, i32) {
+    let mut glyphs = vec![];
+    let mut pen = 0;
+    for run in visual_runs(text) {
+        let mut buffer = UnicodeBuffer::new();
+        buffer.push_str(run.text);
+        buffer.set_direction(if run.right_to_left {
+            Direction::RightToLeft
+        } else {
+            Direction::LeftToRight
+        });
+
+        let output = rustybuzz::shape(face, &[], buffer);
+        for (info, position) in output.glyph_infos().iter().zip(output.glyph_positions()) {
+            glyphs.push(Glyph {
+                id: GlyphId(info.glyph_id as u16),
+                x: pen + position.x_offset,
+                y: position.y_offset,
+            });
+            pen += position.x_advance;
+        }
+    }
+
+    (glyphs, pen)
+}
+
+fn parse_face(data: &[u8]) -> Result<Face<'_>, RenderError> {
+    Face::from_slice(data, 0).ok_or(RenderError::InvalidFont)
+}
+
+/// Pixels per font unit for a font drawn `size` pixels per em
+fn units_scale(face: &Face, size: u16) -> f64 {
+    f64::from(size) / f64::from(face.units_per_em())
+}
+
+fn line_size(face: &Face, size: u16, advance: i32) -> (u32, u32) {
+    let scale = units_scale(face, size);
+    let height = i32::from(face.ascender()) - i32::from(face.descender());
+
+    (
+        (f64::from(advance) * scale).ceil().max(0.0) as u32,
+        (f64::from(height) * scale).ceil().max(0.0) as u32,
+    )
+}
+
+/// Size of the shaped line in pixels, the font data being the contents of
+/// a font file drawn `size` pixels per em
+pub fn measure(data: &[u8], size: u16, text: &str) -> Result<(u32, u32), RenderError> {
+    let face = parse_face(data)?;
+    let (_, advance) = shape(&face, text);
+
+    Ok(line_size(&face, size, advance))
+}
+
+/// Whether the font has a glyph for the character
+pub fn has_glyph(data: &[u8], character: char) -> Result<bool, RenderError> {
+    Ok(parse_face(data)?.glyph_index(character).is_some())
+}
+
+/// Shapes the line and draws its glyphs, color glyphs as they are and the
+/// outlines of the others filled in the color
+pub fn rasterize(
+    data: &[u8],
+    size: u16,
+    text: &str,
+    color: Color,
+    antialias: bool,
+) -> Result<Pixmap, RenderError> {
+    let face = parse_face(data)?;
+    let (glyphs, advance) = shape(&face, text);
+    let (width, height) = line_size(&face, size, advance);
+    let mut pixmap = Pixmap::new(width.max(1), height.max(1)).ok_or_else(|| {
+        RenderError::Surface(format!("Cannot rasterize text at {}x{}", width, height))
+    })?;
+
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(color.red(), color.green(), color.blue(), color.alpha());
+    paint.anti_alias = antialias;
+
+    let scale = units_scale(&face, size);
+    let baseline = f64::from(face.ascender()) * scale;
+    let foreground = RgbaColor::new(color.red(), color.green(), color.blue(), color.alpha());
+    for glyph in glyphs {
+        let origin = (
+            (f64::from(glyph.x) * scale) as f32,
+            (baseline - f64::from(glyph.y) * scale) as f32,
+        );
+        // font units go up from the baseline, pixels go down
+        let transform =
+            Transform::from_row(scale as f32, 0.0, 0.0, -scale as f32, origin.0, origin.1);
+
+        if color_glyph::draw_raster(&mut pixmap, &face, glyph.id, size, origin).is_some()
+            || color_glyph::draw_layers(&mut pixmap, &face, glyph.id, transform, foreground)
+                .is_some()
+        {
+            continue;
+        }
+
+        let mut outline = Outline(PathBuilder::new());
+        if face.outline_glyph(glyph.id, &mut outline).is_none() {
+            continue;
+        }
+        if let Some(path) = outline.0.finish() {
+            pixmap.fill_path(&path, &paint, FillRule::Winding, transform, None);
+        }
+    }
+
+    Ok(pixmap)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn shapes_only_scripts_sdl_ttf_gets_wrong() {
+        assert!(!needs_shaping("Zażółć gęślą jaźń, Привет"));
+        assert!(needs_shaping("مرحبا"));
+        assert!(needs_shaping("नमस्ते"));
+        assert!(needs_shaping("e\u{301}"));
+        assert!(needs_shaping("Ship it \u{1f680}"));
+    }
+
+    #[test]
+    pub fn orders_runs_from_left_to_right() {
+        assert_eq!(
+            vec![
+                Run {
+                    text: "say ",
+                    right_to_left: false
+                },
+                Run {
+                    text: "שלום",
+                    right_to_left: true
+                },
+                Run {
+                    text: " twice",
+                    right_to_left: false
+                },
+            ],
+            visual_runs("say שלום twice")
+        );
+    }
+}
@@ -0,0 +1,797 @@
+use crate::font_resolver;
+use crate::presentation;
+use crate::presentation::{Fill, FontDescriptor, Hinting, Presentation, Slide};
+use crate::rendering::cache::Cache;
+use crate::rendering::error::RenderError;
+use crate::rendering::gradient;
+use crate::rendering::layout::{self, FontKey, Renderer, SlideLayout, Transform};
+use crate::rendering::media::{self, MediaCache};
+use crate::rendering::shaping;
+use crate::rendering::svg::{self, SvgCache};
+use crate::rendering::warp;
+use sdl2::image::LoadSurface;
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, Canvas, RenderTarget, Texture, TextureCreator};
+use sdl2::surface::Surface;
+use sdl2::ttf::{self, Font, Sdl2TtfContext};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::time::Duration;
+
+/// A line of text rendered in a font and a color
+type TextKey = (String, FontKey, presentation::Color);
+
+/// Draws slides onto any SDL canvas, be it a window or an offscreen surface,
+/// with the fonts of the presentation loaded upfront
+pub struct SlideRenderer<'a> {
+    sdl_ttf: &'a Sdl2TtfContext,
+    font_paths: HashMap<FontDescriptor, PathBuf>,
+    fonts: HashMap<FontKey, Font<'a, 'a>>,
+    /// Contents of the font files, read when text first needs shaping
+    font_files: Cache<FontDescriptor, Vec<u8>>,
+    /// Canvas pixels per slide pixel the fonts are loaded for
+    scale: f64,
+    layout: SlideLayout,
+    svgs: SvgCache,
+    media: MediaCache,
+    texts: Cache<TextKey, Surface<'static>>,
+    images: Cache<String, Surface<'static>>,
+    /// Gradients and images filling backgrounds, by the size they fill
+    fills: Cache<(Fill, (u32, u32)), Surface<'static>>,
+    /// Color drawn instead of the slides' backgrounds
+    backdrop: Option<presentation::Color>,
+}
+
+impl<'a> SlideRenderer<'a> {
+    pub fn new(
+        sdl_ttf: &'a Sdl2TtfContext,
+        presentation: Rc<Presentation>,
+    ) -> Result<Self, RenderError> {
+        let layout = SlideLayout::new(presentation)?;
+        let style = layout.presentation().style();
+
+        let mut font_paths = HashMap::new();
+        for key in layout.font_keys()? {
+            if layout.is_emoji_font(&key) {
+                // without an emoji font emoji are drawn as boxes instead
+                if let Some(path) = font_resolver::find_emoji_font() {
+                    font_paths.insert(key.0.clone(), path);
+                }
+            } else if !font_paths.contains_key(&key.0) {
+                let font = style
+                    .font(key.0.name(), key.0.weight(), key.0.italic())
+                    .ok_or_else(|| RenderError::UnknownFont(key.0.name().clone()))?;
+                let path =
+                    font_resolver::resolve(font, layout.presentation().metadata().font_dirs())
+                        .map_err(|message| RenderError::FontLoad {
+                            font: key.0.name().clone(),
+                            message,
+                        })?;
+                font_paths.insert(key.0.clone(), path);
+            }
+        }
+
+        let mut renderer = Self {
+            sdl_ttf,
+            font_paths,
+            fonts: HashMap::new(),
+            font_files: Cache::default(),
+            scale: 1.0,
+            layout,
+            svgs: SvgCache::default(),
+            media: MediaCache::default(),
+            texts: Cache::default(),
+            images: Cache::default(),
+            fills: Cache::default(),
+            backdrop: None,
+        };
+        renderer.load_fonts()?;
+
+        Ok(renderer)
+    }
+
+    /// Rasterizes text for a canvas showing every slide pixel as `scale`
+    /// canvas pixels, so it stays sharp however big the window is. The
+    /// layout is still done in slide pixels.
+    pub fn set_scale(&mut self, scale: f64) -> Result<(), RenderError> {
+        if (scale - self.scale).abs() < f64::EPSILON {
+            return Ok(());
+        }
+
+        self.scale = scale;
+        self.invalidate();
+        self.load_fonts()
+    }
+
+    /// Draws the color instead of the slides' backgrounds, for windows
+    /// captured with a chroma key
+    pub fn set_backdrop(&mut self, backdrop: presentation::Color) {
+        self.backdrop = Some(backdrop);
+    }
+
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    fn load_fonts(&mut self) -> Result<(), RenderError> {
+        let style = self.layout.presentation().style();
+        let hinting = match style.hinting() {
+            Hinting::Normal => ttf::Hinting::Normal,
+            Hinting::Light => ttf::Hinting::Light,
+            Hinting::Mono => ttf::Hinting::Mono,
+            Hinting::None => ttf::Hinting::None,
+        };
+        let kerning = style.kerning();
+
+        let mut fonts = HashMap::new();
+        for key in self.layout.font_keys()? {
+            // emoji are always shaped, straight from the font file
+            if fonts.contains_key(&key) || self.layout.is_emoji_font(&key) {
+                continue;
+            }
+
+            let size = scaled_font_size(key.1, self.scale);
+            let mut font = self
+                .sdl_ttf
+                .load_font(&self.font_paths[&key.0], size)
+                .map_err(|message| RenderError::FontLoad {
+                    font: key.0.name().clone(),
+                    message,
+                })?;
+            font.set_hinting(hinting.clone());
+            font.set_kerning(kerning);
+            fonts.insert(key, font);
+        }
+        self.fonts = fonts;
+
+        Ok(())
+    }
+
+    /// Makes the canvas use the slide coordinate system, scaled to fit the
+    /// canvas and letter-boxed when the aspect ratios differ
+    pub fn prepare_canvas<T: RenderTarget>(
+        &self,
+        canvas: &mut Canvas<T>,
+    ) -> Result<(), RenderError> {
+        let resolution = self.layout.presentation().metadata().resolution();
+
+        canvas
+            .set_logical_size(resolution.width(), resolution.height())
+            .map_err(|e| RenderError::Sdl(format!("{:?}", e)))
+    }
+
+    pub fn render<T: RenderTarget, C>(
+        &self,
+        canvas: &mut Canvas<T>,
+        textures: &TextureCreator<C>,
+        slide: &Slide,
+    ) -> Result<(), RenderError> {
+        self.render_step(canvas, textures, slide, slide.step_count())
+    }
+
+    /// Renders the slide with only the elements revealed up to the given
+    /// build step, the hidden ones still take up their space. Videos and
+    /// animated images show their first frame.
+    pub fn render_step<T: RenderTarget, C>(
+        &self,
+        canvas: &mut Canvas<T>,
+        textures: &TextureCreator<C>,
+        slide: &Slide,
+        step: u32,
+    ) -> Result<(), RenderError> {
+        self.render_playing(
+            canvas,
+            textures,
+            slide,
+            step,
+            Duration::from_millis(0),
+            None,
+        )
+    }
+
+    /// Renders the slide at the given build step with its videos and
+    /// animated images showing the frames of the playback position, and
+    /// the elements of the step `revealed` into coming in when it is given
+    pub fn render_playing<T: RenderTarget, C>(
+        &self,
+        canvas: &mut Canvas<T>,
+        textures: &TextureCreator<C>,
+        slide: &Slide,
+        step: u32,
+        position: Duration,
+        revealed: Option<Duration>,
+    ) -> Result<(), RenderError> {
+        let mut renderer = self.canvas_renderer(canvas, textures);
+        renderer.position = position;
+
+        self.layout
+            .render_revealing(&mut renderer, slide, step, revealed)
+    }
+
+    /// Renders the slide at the given build step into memory, scaled to the
+    /// given size
+    pub fn render_surface(
+        &self,
+        slide: &Slide,
+        step: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<Surface<'static>, RenderError> {
+        let surface =
+            Surface::new(width, height, PixelFormatEnum::RGB24).map_err(RenderError::Surface)?;
+        let mut canvas = surface.into_canvas().map_err(RenderError::Surface)?;
+        let textures = canvas.texture_creator();
+
+        self.prepare_canvas(&mut canvas)?;
+        self.render_step(&mut canvas, &textures, slide, step)?;
+
+        Ok(canvas.into_surface())
+    }
+
+    /// Renders the slide like `render_surface`, with the elements of the
+    /// build step as they are just before they start coming in, for
+    /// transitions into a step that animates them
+    pub fn render_arriving_surface(
+        &self,
+        slide: &Slide,
+        step: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<Surface<'static>, RenderError> {
+        let surface =
+            Surface::new(width, height, PixelFormatEnum::RGB24).map_err(RenderError::Surface)?;
+        let mut canvas = surface.into_canvas().map_err(RenderError::Surface)?;
+        let textures = canvas.texture_creator();
+
+        self.prepare_canvas(&mut canvas)?;
+        self.render_playing(
+            &mut canvas,
+            &textures,
+            slide,
+            step,
+            Duration::from_millis(0),
+            Some(Duration::from_millis(0)),
+        )?;
+
+        Ok(canvas.into_surface())
+    }
+
+    /// Hands the layout a renderer measuring text and images like the canvas
+    /// does, for exports that draw slides as something else than pixels
+    pub fn with_measuring_renderer<T, F>(&self, lay_out: F) -> Result<T, RenderError>
+    where
+        F: FnOnce(&SlideLayout, &mut dyn Renderer) -> Result<T, RenderError>,
+    {
+        let mut canvas = Surface::new(1, 1, PixelFormatEnum::RGB24)
+            .map_err(RenderError::Surface)?
+            .into_canvas()
+            .map_err(RenderError::Surface)?;
+        let textures = canvas.texture_creator();
+        self.prepare_canvas(&mut canvas)?;
+
+        lay_out(
+            &self.layout,
+            &mut self.canvas_renderer(&mut canvas, &textures),
+        )
+    }
+
+    /// Shows the message, one line under another, in place of a slide
+    pub fn render_error<T: RenderTarget, C>(
+        &self,
+        canvas: &mut Canvas<T>,
+        textures: &TextureCreator<C>,
+        message: &str,
+    ) -> Result<(), RenderError> {
+        self.layout.render_lines(
+            &mut self.canvas_renderer(canvas, textures),
+            message,
+            presentation::Color::new(0xff, 0x40, 0x40, 0xff),
+        )
+    }
+
+    /// Renders a single line in the default font, for user interface text
+    /// that is not part of any slide. It is rasterized at the scale, see
+    /// `label_size` for the size it takes up on the slide.
+    pub fn render_label(
+        &self,
+        text: &str,
+        color: presentation::Color,
+    ) -> Result<Surface<'static>, RenderError> {
+        render_text(
+            &self.fonts[self.layout.default_font()],
+            text,
+            color,
+            self.layout.presentation().style().antialias(),
+        )
+    }
+
+    /// Size of a rendered label in slide pixels
+    pub fn label_size(&self, label: &Surface) -> (u32, u32) {
+        unscale(label.size(), self.scale)
+    }
+
+    /// Drops the rendered texts, images and fills, and the rasterized
+    /// vector images, for when other slides are shown or they are drawn at
+    /// another scale
+    pub fn invalidate(&self) {
+        self.texts.clear();
+        self.images.clear();
+        self.fills.clear();
+        self.svgs.clear_rasterized();
+    }
+
+    fn canvas_renderer<'c, T: RenderTarget, C>(
+        &'c self,
+        canvas: &'c mut Canvas<T>,
+        textures: &'c TextureCreator<C>,
+    ) -> CanvasRenderer<'c, 'a, T, C> {
+        CanvasRenderer {
+            canvas,
+            textures,
+            fonts: &self.fonts,
+            font_paths: &self.font_paths,
+            font_files: &self.font_files,
+            svgs: &self.svgs,
+            media: &self.media,
+            texts: &self.texts,
+            images: &self.images,
+            fills: &self.fills,
+            text_scale: self.scale,
+            antialias: self.layout.presentation().style().antialias(),
+            position: Duration::from_millis(0),
+            backdrop: self.backdrop,
+            paint: Paint::default(),
+        }
+    }
+}
+
+/// Draws onto an SDL canvas with already loaded fonts, texts and images are
+/// rendered once and taken from the slide renderer's caches afterwards
+struct CanvasRenderer<'c, 'f, T: RenderTarget, C> {
+    canvas: &'c mut Canvas<T>,
+    /// Creates the textures of what is copied onto the canvas
+    textures: &'c TextureCreator<C>,
+    fonts: &'c HashMap<FontKey, Font<'f, 'f>>,
+    font_paths: &'c HashMap<FontDescriptor, PathBuf>,
+    font_files: &'c Cache<FontDescriptor, Vec<u8>>,
+    svgs: &'c SvgCache,
+    media: &'c MediaCache,
+    texts: &'c Cache<TextKey, Surface<'static>>,
+    images: &'c Cache<String, Surface<'static>>,
+    fills: &'c Cache<(Fill, (u32, u32)), Surface<'static>>,
+    /// Canvas pixels per slide pixel the fonts are loaded for
+    text_scale: f64,
+    antialias: bool,
+    /// Playback position the frames of videos and animated images are
+    /// picked by
+    position: Duration,
+    backdrop: Option<presentation::Color>,
+    paint: Paint,
+}
+
+/// How whatever a canvas renderer draws next is blended and placed
+#[derive(Copy, Clone)]
+struct Paint {
+    /// Opacity the drawing is blended with, 255 for opaque
+    alpha: u8,
+    transform: Transform,
+}
+
+impl Default for Paint {
+    fn default() -> Self {
+        Self {
+            alpha: 0xff,
+            transform: Transform::default(),
+        }
+    }
+}
+
+impl<T: RenderTarget, C> CanvasRenderer<'_, '_, T, C> {
+    /// Canvas pixels per slide pixel, for rasterizing vector images as
+    /// sharp as the window shows them
+    fn pixel_scale(&self) -> f64 {
+        let (width, height) = self.canvas.logical_size();
+        match self.canvas.output_size() {
+            Ok((output_width, output_height)) if width > 0 && height > 0 => {
+                (f64::from(output_width) / f64::from(width))
+                    .min(f64::from(output_height) / f64::from(height))
+            }
+            _ => 1.0,
+        }
+    }
+}
+
+impl<T: RenderTarget, C> Renderer for CanvasRenderer<'_, '_, T, C> {
+    fn text_size(&mut self, text: &str, font: &FontKey) -> Result<(u32, u32), RenderError> {
+        if shaping::needs_shaping(text) || !self.fonts.contains_key(font) {
+            let pixels = scaled_font_size(font.1, self.text_scale);
+            let size = with_font_file(self.font_paths, self.font_files, font, |data| {
+                shaping::measure(data, pixels, text)
+            })??;
+
+            return Ok(unscale(size, self.text_scale));
+        }
+
+        let size = self.fonts[font]
+            .size_of(text)
+            .map_err(|e| RenderError::Sdl(format!("{:?}", e)))?;
+
+        Ok(unscale(size, self.text_scale))
+    }
+
+    fn has_glyph(&mut self, character: char, font: &FontKey) -> Result<bool, RenderError> {
+        if let Some(loaded) = self.fonts.get(font) {
+            return Ok(loaded.find_glyph(character).is_some());
+        }
+        if !self.font_paths.contains_key(&font.0) {
+            return Ok(false);
+        }
+
+        with_font_file(self.font_paths, self.font_files, font, |data| {
+            shaping::has_glyph(data, character)
+        })?
+    }
+
+    fn image_size(&mut self, path: &str) -> Result<(u32, u32), RenderError> {
+        if svg::is_svg(path) {
+            return self.svgs.size(path);
+        }
+        if media::is_animated(path) {
+            return self.media.animation_size(path);
+        }
+
+        with_image(self.images, path, |image| image.size())
+    }
+
+    fn clear(&mut self, fill: &Fill) -> Result<(), RenderError> {
+        let backdrop = self.backdrop.map(Fill::Color);
+        let fill = backdrop.as_ref().unwrap_or(fill);
+        self.canvas.set_draw_color(Color::RGB(0, 0, 0));
+        self.canvas.clear();
+        if let Fill::Color(color) = fill {
+            self.canvas.set_draw_color(*color);
+            return self.canvas.fill_rect(None).map_err(RenderError::Sdl);
+        }
+
+        let size = match self.canvas.logical_size() {
+            (0, _) | (_, 0) => self.canvas.output_size().map_err(RenderError::Sdl)?,
+            size => size,
+        };
+        self.draw_fill(fill, (0, 0), size)
+    }
+
+    /// Gradients and images are rasterized as sharp as the canvas shows
+    /// them, once for every size they are drawn at
+    fn draw_fill(
+        &mut self,
+        fill: &Fill,
+        position: (i32, i32),
+        size: (u32, u32),
+    ) -> Result<(), RenderError> {
+        // rectangles can't be transformed, transformed colors are drawn like
+        // images
+        let transformed = !self.paint.transform.is_identity();
+        if let (Fill::Color(color), false) = (fill, transformed) {
+            let mut color = Color::from(*color);
+            if self.paint.alpha < 0xff {
+                color.a = fade(color.a, self.paint.alpha);
+                self.canvas.set_blend_mode(BlendMode::Blend);
+            }
+            self.canvas.set_draw_color(color);
+            let filled = self
+                .canvas
+                .fill_rect(Rect::new(position.0, position.1, size.0, size.1))
+                .map_err(RenderError::Sdl);
+            self.canvas.set_blend_mode(BlendMode::None);
+            return filled;
+        }
+
+        let scale = self.pixel_scale();
+        let pixels = scale_size(size, scale);
+        let images = self.images;
+        let svgs = self.svgs;
+        let paint = self.paint;
+        let canvas = &mut *self.canvas;
+        let textures = self.textures;
+        self.fills.with(
+            (fill.clone(), pixels),
+            || rasterize_fill(fill, pixels, scale, images, svgs),
+            |surface| copy((canvas, textures), surface, position, size, paint),
+        )?
+    }
+
+    fn draw_text(
+        &mut self,
+        text: &str,
+        font: &FontKey,
+        color: presentation::Color,
+        position: (i32, i32),
+    ) -> Result<(), RenderError> {
+        let fonts = self.fonts;
+        let scale = self.text_scale;
+        let antialias = self.antialias;
+        let font_paths = self.font_paths;
+        let font_files = self.font_files;
+        let build = || {
+            if let Some(loaded) = fonts.get(font).filter(|_| !shaping::needs_shaping(text)) {
+                return render_text(loaded, text, color, antialias);
+            }
+
+            let pixels = scaled_font_size(font.1, scale);
+            let pixmap = with_font_file(font_paths, font_files, font, |data| {
+                shaping::rasterize(data, pixels, text, color, antialias)
+            })??;
+            svg::pixmap_surface(&pixmap)
+        };
+        let paint = self.paint;
+        let canvas = &mut *self.canvas;
+        let textures = self.textures;
+        self.texts
+            .with((text.to_string(), font.clone(), color), build, |surface| {
+                copy(
+                    (canvas, textures),
+                    surface,
+                    position,
+                    unscale(surface.size(), scale),
+                    paint,
+                )
+            })?
+    }
+
+    fn draw_image(
+        &mut self,
+        path: &str,
+        position: (i32, i32),
+        size: (u32, u32),
+    ) -> Result<(), RenderError> {
+        let paint = self.paint;
+        if svg::is_svg(path) {
+            let pixels = scale_size(size, self.pixel_scale());
+            let canvas = &mut *self.canvas;
+            let textures = self.textures;
+            return self.svgs.with_rasterized(path, pixels, |surface| {
+                copy((canvas, textures), surface, position, size, paint)
+            })?;
+        }
+
+        if media::is_animated(path) {
+            let canvas = &mut *self.canvas;
+            let textures = self.textures;
+            return self
+                .media
+                .with_animation_frame(path, self.position, |surface| {
+                    copy((canvas, textures), surface, position, size, paint)
+                })?;
+        }
+
+        let canvas = &mut *self.canvas;
+
+        let textures = self.textures;
+        with_image(self.images, path, |surface| {
+            copy((canvas, textures), surface, position, size, paint)
+        })?
+    }
+
+    fn video_size(&mut self, path: &str) -> Result<(u32, u32), RenderError> {
+        self.media.video_size(path)
+    }
+
+    fn draw_video(
+        &mut self,
+        path: &str,
+        position: (i32, i32),
+        size: (u32, u32),
+    ) -> Result<(), RenderError> {
+        let paint = self.paint;
+        let canvas = &mut *self.canvas;
+        let textures = self.textures;
+        self.media
+            .with_video_frame(path, self.position, |surface| {
+                copy((canvas, textures), surface, position, size, paint)
+            })?
+    }
+
+    fn set_opacity(&mut self, opacity: f64) {
+        self.paint.alpha = (opacity.clamp(0.0, 1.0) * 255.0).round() as u8;
+    }
+
+    fn set_transform(&mut self, transform: Transform) {
+        self.paint.transform = transform;
+    }
+}
+
+/// Calls `use_file` with the contents of the font's file, read from disk
+/// on first use
+fn with_font_file<R>(
+    font_paths: &HashMap<FontDescriptor, PathBuf>,
+    font_files: &Cache<FontDescriptor, Vec<u8>>,
+    font: &FontKey,
+    use_file: impl FnOnce(&[u8]) -> R,
+) -> Result<R, RenderError> {
+    let path = &font_paths[&font.0];
+    font_files.with(
+        font.0.clone(),
+        || {
+            fs::read(path).map_err(|error| RenderError::Io {
+                path: path.display().to_string(),
+                error,
+            })
+        },
+        |data| use_file(data),
+    )
+}
+
+/// Calls `use_image` with the bitmap image, read from disk on first use
+fn with_image<R>(
+    images: &Cache<String, Surface<'static>>,
+    path: &str,
+    use_image: impl FnOnce(&Surface<'static>) -> R,
+) -> Result<R, RenderError> {
+    images.with(
+        path.to_string(),
+        || {
+            Surface::from_file(path).map_err(|message| RenderError::Decode {
+                path: path.to_string(),
+                message,
+            })
+        },
+        use_image,
+    )
+}
+
+/// Draws the fill over a surface of the size, images are scaled from their
+/// own size times `scale` before they are fitted
+fn rasterize_fill(
+    fill: &Fill,
+    size: (u32, u32),
+    scale: f64,
+    images: &Cache<String, Surface<'static>>,
+    svgs: &SvgCache,
+) -> Result<Surface<'static>, RenderError> {
+    let (path, fit) = match fill {
+        Fill::Gradient {
+            from,
+            to,
+            direction,
+        } => return svg::pixmap_surface(&gradient::rasterize(*from, *to, *direction, size)?),
+        Fill::Color(color) => {
+            let mut surface = Surface::new(size.0.max(1), size.1.max(1), PixelFormatEnum::ABGR8888)
+                .map_err(RenderError::Surface)?;
+            surface
+                .fill_rect(None, (*color).into())
+                .map_err(RenderError::Surface)?;
+            return Ok(surface);
+        }
+        Fill::Image { path, fit } => (path, *fit),
+    };
+
+    let mut surface = Surface::new(size.0.max(1), size.1.max(1), PixelFormatEnum::ABGR8888)
+        .map_err(RenderError::Surface)?;
+    let natural = if svg::is_svg(path) {
+        svgs.size(path)?
+    } else {
+        with_image(images, path, |image| image.size())?
+    };
+    let tiles = layout::fit_image(fit, scale_size(natural, scale), size);
+    let tile_size = match tiles.first() {
+        Some((_, tile_size)) => *tile_size,
+        None => return Ok(surface),
+    };
+    let mut blit = |image: &Surface<'static>| {
+        for ((x, y), (width, height)) in &tiles {
+            image
+                .blit_scaled(None, &mut surface, Rect::new(*x, *y, *width, *height))
+                .map_err(RenderError::Surface)?;
+        }
+        Ok::<(), RenderError>(())
+    };
+
+    if svg::is_svg(path) {
+        svgs.with_rasterized(path, tile_size, &mut blit)??;
+    } else {
+        with_image(images, path, &mut blit)??;
+    }
+
+    Ok(surface)
+}
+
+/// Size in canvas pixels of `size` slide pixels
+fn scale_size((width, height): (u32, u32), scale: f64) -> (u32, u32) {
+    (
+        (f64::from(width) * scale).round() as u32,
+        (f64::from(height) * scale).round() as u32,
+    )
+}
+
+/// Size in canvas pixels of a font `size` slide pixels big
+fn scaled_font_size(size: u16, scale: f64) -> u16 {
+    (f64::from(size) * scale).round().max(1.0) as u16
+}
+
+/// Turns a size in canvas pixels into slide pixels
+fn unscale((width, height): (u32, u32), scale: f64) -> (u32, u32) {
+    (
+        (f64::from(width) / scale).round() as u32,
+        (f64::from(height) / scale).round() as u32,
+    )
+}
+
+/// Scales the alpha of a color by the opacity it is drawn with
+fn fade(alpha: u8, opacity: u8) -> u8 {
+    (u16::from(alpha) * u16::from(opacity) / 0xff) as u8
+}
+
+/// Renders the text with smoothed edges blended into transparency, or
+/// with hard edges when antialiasing is off
+fn render_text(
+    font: &Font,
+    text: &str,
+    color: presentation::Color,
+    antialias: bool,
+) -> Result<Surface<'static>, RenderError> {
+    let rendering = font.render(text);
+    if antialias {
+        rendering.blended(color)
+    } else {
+        rendering.solid(color)
+    }
+    .map_err(|e| RenderError::Surface(format!("{:?}", e)))
+}
+
+/// Draws the surface scaled to the size with its top left corner at the
+/// position, blended and transformed with the paint. SDL only turns the
+/// copies it scales evenly, anything else is warped in software first.
+fn copy<T: RenderTarget, C>(
+    (canvas, textures): (&mut Canvas<T>, &TextureCreator<C>),
+    surface: &Surface,
+    position: (i32, i32),
+    size: (u32, u32),
+    paint: Paint,
+) -> Result<(), RenderError> {
+    let Some((scale, rotation)) = paint.transform.similarity() else {
+        let (warped, position, size) = warp::warp(surface, position, size, paint.transform)?;
+        let paint = Paint {
+            transform: Transform::default(),
+            ..paint
+        };
+        return copy((canvas, textures), &warped, position, size, paint);
+    };
+
+    let mut texture: Texture = textures
+        .create_texture_from_surface(surface)
+        .map_err(|e| RenderError::Texture(format!("{:?}", e)))?;
+    if paint.alpha < 0xff {
+        texture.set_blend_mode(BlendMode::Blend);
+        texture.set_alpha_mod(paint.alpha);
+    }
+
+    let transform = paint.transform;
+    if transform.is_identity() {
+        return canvas
+            .copy(
+                &texture,
+                None,
+                Rect::new(position.0, position.1, size.0, size.1),
+            )
+            .map_err(RenderError::Sdl);
+    }
+
+    let (width, height) = (f64::from(size.0) * scale, f64::from(size.1) * scale);
+    let center = transform.apply((
+        f64::from(position.0) + f64::from(size.0) / 2.0,
+        f64::from(position.1) + f64::from(size.1) / 2.0,
+    ));
+    let target = Rect::new(
+        (center.0 - width / 2.0).round() as i32,
+        (center.1 - height / 2.0).round() as i32,
+        width.round().max(0.0) as u32,
+        height.round().max(0.0) as u32,
+    );
+
+    canvas
+        .copy_ex(&texture, None, target, rotation, None, false, false)
+        .map_err(RenderError::Sdl)
+}
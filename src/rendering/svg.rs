@@ -0,0 +1,197 @@
+use crate::rendering::error::RenderError;
+use resvg::tiny_skia::{ColorU8, Pixmap, Transform};
+use resvg::usvg::{Options, Tree};
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::surface::Surface;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::Path;
+
+/// Whether the image is a vector one, which is rasterized at the size it is
+/// drawn at instead of being loaded as it is
+pub fn is_svg(path: &str) -> bool {
+    let extension = Path::new(path)
+        .extension()
+        .and_then(OsStr::to_str)
+        .map(str::to_lowercase);
+
+    extension.as_deref() == Some("svg")
+}
+
+/// A parsed vector image
+pub struct Svg {
+    tree: Tree,
+}
+
+impl Svg {
+    /// Parses the file, with the system fonts available to its texts and
+    /// the images it links to looked up next to it
+    pub fn load(path: &str) -> Result<Self, RenderError> {
+        let data = fs::read(path).map_err(|error| RenderError::Io {
+            path: path.to_string(),
+            error,
+        })?;
+        let mut options = Options {
+            resources_dir: Path::new(path).parent().map(Path::to_path_buf),
+            ..Options::default()
+        };
+        options.fontdb_mut().load_system_fonts();
+
+        let tree = Tree::from_data(&data, &options).map_err(|e| RenderError::Decode {
+            path: path.to_string(),
+            message: e.to_string(),
+        })?;
+
+        Ok(Self { tree })
+    }
+
+    /// Size the image is drawn at unless its placement says otherwise
+    pub fn size(&self) -> (u32, u32) {
+        let size = self.tree.size();
+
+        (size.width().ceil() as u32, size.height().ceil() as u32)
+    }
+
+    /// Draws the image stretched to the size
+    pub fn rasterize(&self, (width, height): (u32, u32)) -> Result<Surface<'static>, RenderError> {
+        let mut pixmap = Pixmap::new(width.max(1), height.max(1)).ok_or_else(|| {
+            RenderError::Surface(format!("Cannot rasterize an image at {}x{}", width, height))
+        })?;
+        let size = self.tree.size();
+        let transform = Transform::from_scale(
+            (f64::from(pixmap.width()) / f64::from(size.width())) as f32,
+            (f64::from(pixmap.height()) / f64::from(size.height())) as f32,
+        );
+        resvg::render(&self.tree, transform, &mut pixmap.as_mut());
+
+        pixmap_surface(&pixmap)
+    }
+}
+
+/// Copies the pixels into a surface, undoing their premultiplied alpha
+pub fn pixmap_surface(pixmap: &Pixmap) -> Result<Surface<'static>, RenderError> {
+    let mut surface = Surface::new(pixmap.width(), pixmap.height(), PixelFormatEnum::ABGR8888)
+        .map_err(RenderError::Surface)?;
+    let pitch = surface.pitch() as usize;
+    let row = pixmap.width() as usize;
+    surface.with_lock_mut(|pixels| {
+        for (index, pixel) in pixmap.pixels().iter().enumerate() {
+            let color = pixel.demultiply();
+            let offset = index / row * pitch + index % row * 4;
+            pixels[offset..offset + 4].copy_from_slice(&[
+                color.red(),
+                color.green(),
+                color.blue(),
+                color.alpha(),
+            ]);
+        }
+    });
+
+    Ok(surface)
+}
+
+/// Copies the pixels of the surface, in whatever format, into a pixmap,
+/// premultiplying their alpha
+pub fn surface_pixmap(surface: &Surface) -> Result<Pixmap, RenderError> {
+    // pixel formats can only be had safely from a surface that has one
+    let format = Surface::new(1, 1, PixelFormatEnum::ABGR8888).map_err(RenderError::Surface)?;
+    let surface = surface
+        .convert(&format.pixel_format())
+        .map_err(RenderError::Surface)?;
+    let (width, height) = surface.size();
+    let mut pixmap = Pixmap::new(width.max(1), height.max(1)).ok_or_else(|| {
+        RenderError::Surface(format!("Cannot copy a surface of {}x{}", width, height))
+    })?;
+    let pitch = surface.pitch() as usize;
+    let row = pixmap.width() as usize;
+    surface.with_lock(|pixels| {
+        for (index, pixel) in pixmap.pixels_mut().iter_mut().enumerate() {
+            let offset = index / row * pitch + index % row * 4;
+            if let Some([red, green, blue, alpha]) = pixels.get(offset..offset + 4) {
+                *pixel = ColorU8::from_rgba(*red, *green, *blue, *alpha).premultiply();
+            }
+        }
+    });
+
+    Ok(pixmap)
+}
+
+/// Rasterized images by their path and size
+type Rasterized = HashMap<(String, (u32, u32)), Surface<'static>>;
+
+/// Vector images parsed once, and rasterized once for every size they are
+/// drawn at
+#[derive(Default)]
+pub struct SvgCache {
+    images: RefCell<HashMap<String, Svg>>,
+    rasterized: RefCell<Rasterized>,
+}
+
+impl SvgCache {
+    pub fn size(&self, path: &str) -> Result<(u32, u32), RenderError> {
+        self.load(path)?;
+
+        Ok(self.images.borrow()[path].size())
+    }
+
+    /// Calls `draw` with the image rasterized at the size
+    pub fn with_rasterized<R>(
+        &self,
+        path: &str,
+        size: (u32, u32),
+        draw: impl FnOnce(&Surface<'static>) -> R,
+    ) -> Result<R, RenderError> {
+        let key = (path.to_string(), size);
+        if !self.rasterized.borrow().contains_key(&key) {
+            self.load(path)?;
+            let surface = self.images.borrow()[path].rasterize(size)?;
+            self.rasterized.borrow_mut().insert(key.clone(), surface);
+        }
+
+        Ok(draw(&self.rasterized.borrow()[&key]))
+    }
+
+    /// Forgets the rasterized images, the parsed ones are kept
+    pub fn clear_rasterized(&self) {
+        self.rasterized.borrow_mut().clear();
+    }
+
+    fn load(&self, path: &str) -> Result<(), RenderError> {
+        if !self.images.borrow().contains_key(path) {
+            let svg = Svg::load(path)?;
+            self.images.borrow_mut().insert(path.to_string(), svg);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn recognizes_svg_files_by_extension() {
+        assert!(is_svg("diagrams/flow.svg"));
+        assert!(is_svg("LOGO.SVG"));
+        assert!(!is_svg("photo.png"));
+        assert!(!is_svg("svg"));
+    }
+
+    #[test]
+    pub fn takes_the_size_from_the_document() {
+        let path = std::env::temp_dir().join(format!("przntr-svg-test-{}.svg", std::process::id()));
+        fs::write(
+            &path,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"120\" height=\"80.5\"><rect width=\"10\" height=\"10\"/></svg>",
+        )
+        .unwrap();
+
+        let svg = Svg::load(path.to_str().unwrap()).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!((120, 81), svg.size());
+    }
+}
@@ -0,0 +1,152 @@
+use crate::presentation::{Resolution, Transition, TransitionKind};
+use crate::rendering::error::RenderError;
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, Canvas, RenderTarget, TextureCreator};
+use sdl2::surface::Surface;
+use std::time::Duration;
+
+/// A transition in progress, keeping rendered copies of the outgoing and the
+/// incoming slide so they are not laid out again on every frame
+pub struct ActiveTransition {
+    transition: Transition,
+    from: Surface<'static>,
+    to: Surface<'static>,
+    forward: bool,
+    elapsed: Duration,
+}
+
+impl ActiveTransition {
+    pub fn new(
+        transition: Transition,
+        from: Surface<'static>,
+        to: Surface<'static>,
+        forward: bool,
+    ) -> Self {
+        Self {
+            transition,
+            from,
+            to,
+            forward,
+            elapsed: Duration::from_secs(0),
+        }
+    }
+
+    /// Moves the transition on by the time since the last frame
+    pub fn advance(&mut self, delta: Duration) {
+        self.elapsed += delta;
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.progress() >= 1.0
+    }
+
+    fn progress(&self) -> f64 {
+        progress(self.elapsed, self.transition.duration())
+    }
+
+    /// How far along the slides have moved, which can go past the end with
+    /// springy easings
+    fn eased_progress(&self) -> f64 {
+        self.transition.easing().ease(self.progress())
+    }
+
+    /// Draws the current frame onto a canvas using the slide coordinate system
+    pub fn render<T: RenderTarget, C>(
+        &self,
+        canvas: &mut Canvas<T>,
+        textures: &TextureCreator<C>,
+        resolution: Resolution,
+    ) -> Result<(), RenderError> {
+        let from = textures
+            .create_texture_from_surface(&self.from)
+            .map_err(|e| RenderError::Texture(format!("{:?}", e)))?;
+        let mut to = textures
+            .create_texture_from_surface(&self.to)
+            .map_err(|e| RenderError::Texture(format!("{:?}", e)))?;
+        let progress = self.eased_progress();
+
+        let copied = match self.transition.kind() {
+            TransitionKind::Fade => {
+                to.set_blend_mode(BlendMode::Blend);
+                to.set_alpha_mod(fade_alpha(progress));
+                canvas.copy(&from, None, None).map_err(RenderError::Sdl)?;
+                canvas.copy(&to, None, None)
+            }
+            TransitionKind::Slide => {
+                let (from_x, to_x) = slide_offsets(progress, resolution.width(), self.forward);
+                canvas
+                    .copy(
+                        &from,
+                        None,
+                        Rect::new(from_x, 0, resolution.width(), resolution.height()),
+                    )
+                    .map_err(RenderError::Sdl)?;
+                canvas.copy(
+                    &to,
+                    None,
+                    Rect::new(to_x, 0, resolution.width(), resolution.height()),
+                )
+            }
+            TransitionKind::None => canvas.copy(&to, None, None),
+        };
+
+        copied.map_err(RenderError::Sdl)
+    }
+}
+
+/// How far along the transition is, from 0 to 1
+fn progress(elapsed: Duration, duration: Duration) -> f64 {
+    if duration.as_millis() == 0 {
+        return 1.0;
+    }
+
+    (elapsed.as_secs_f64() / duration.as_secs_f64()).min(1.0)
+}
+
+/// Easings going past the end leave the incoming slide opaque
+fn fade_alpha(progress: f64) -> u8 {
+    (progress.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+/// Horizontal positions of the outgoing and the incoming slide, moving left
+/// when going forward and right when going back
+fn slide_offsets(progress: f64, width: u32, forward: bool) -> (i32, i32) {
+    let shift = (progress * f64::from(width)).round() as i32;
+    let width = width as i32;
+
+    if forward {
+        (-shift, width - shift)
+    } else {
+        (shift, shift - width)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn progress_is_clamped() {
+        assert!(
+            (progress(Duration::from_millis(150), Duration::from_millis(300)) - 0.5).abs() < 1e-9
+        );
+        assert!(
+            (progress(Duration::from_millis(900), Duration::from_millis(300)) - 1.0).abs() < 1e-9
+        );
+        assert!((progress(Duration::from_millis(10), Duration::from_millis(0)) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    pub fn fades_in_incoming_slide() {
+        assert_eq!(0, fade_alpha(0.0));
+        assert_eq!(128, fade_alpha(0.5));
+        assert_eq!(255, fade_alpha(1.0));
+    }
+
+    #[test]
+    pub fn slides_in_from_the_side_of_navigation() {
+        assert_eq!((-400, 400), slide_offsets(0.5, 800, true));
+        assert_eq!((400, -400), slide_offsets(0.5, 800, false));
+        assert_eq!((0, 800), slide_offsets(0.0, 800, true));
+    }
+}
@@ -0,0 +1,74 @@
+use crate::rendering::error::RenderError;
+use crate::rendering::layout::Transform;
+use crate::rendering::svg;
+use resvg::tiny_skia::{self, FilterQuality, Pixmap, PixmapPaint};
+use sdl2::surface::Surface;
+
+/// The warped surface with the position and size it covers
+pub type Warped = (Surface<'static>, (i32, i32), (u32, u32));
+
+/// Draws the surface scaled to the size, with its top left corner at the
+/// position, through the transform onto a new surface, for backends that
+/// can only turn what they copy. The new surface has as many pixels per
+/// slide pixel as the old one and covers the position and size it is
+/// returned with.
+pub fn warp(
+    surface: &Surface,
+    (left, top): (i32, i32),
+    size: (u32, u32),
+    transform: Transform,
+) -> Result<Warped, RenderError> {
+    let source = svg::surface_pixmap(surface)?;
+    let (left, top) = (f64::from(left), f64::from(top));
+    let (width, height) = (f64::from(size.0.max(1)), f64::from(size.1.max(1)));
+    let corners: Vec<(f64, f64)> = [
+        (left, top),
+        (left + width, top),
+        (left, top + height),
+        (left + width, top + height),
+    ]
+    .iter()
+    .map(|corner| transform.apply(*corner))
+    .collect();
+    let (min_x, min_y) = corners.iter().fold((f64::MAX, f64::MAX), |min, corner| {
+        (min.0.min(corner.0), min.1.min(corner.1))
+    });
+    let (max_x, max_y) = corners.iter().fold((f64::MIN, f64::MIN), |max, corner| {
+        (max.0.max(corner.0), max.1.max(corner.1))
+    });
+    let (min_x, min_y, max_x, max_y) = (min_x.floor(), min_y.floor(), max_x.ceil(), max_y.ceil());
+
+    // surface pixels per slide pixel, and slide pixels per surface pixel
+    let density = (f64::from(source.width()) / width).max(f64::from(source.height()) / height);
+    let step = (
+        width / f64::from(source.width()),
+        height / f64::from(source.height()),
+    );
+    let mut pixmap = Pixmap::new(
+        ((max_x - min_x) * density).ceil().max(1.0) as u32,
+        ((max_y - min_y) * density).ceil().max(1.0) as u32,
+    )
+    .ok_or_else(|| RenderError::Surface("Cannot warp a surface this big".into()))?;
+
+    let (x_axis, y_axis, _) = transform.axes();
+    let corner = transform.apply((left, top));
+    let matrix = tiny_skia::Transform::from_row(
+        (density * step.0 * x_axis.0) as f32,
+        (density * step.0 * x_axis.1) as f32,
+        (density * step.1 * y_axis.0) as f32,
+        (density * step.1 * y_axis.1) as f32,
+        (density * (corner.0 - min_x)) as f32,
+        (density * (corner.1 - min_y)) as f32,
+    );
+    let paint = PixmapPaint {
+        quality: FilterQuality::Bilinear,
+        ..PixmapPaint::default()
+    };
+    pixmap.draw_pixmap(0, 0, source.as_ref(), &paint, matrix, None);
+
+    Ok((
+        svg::pixmap_surface(&pixmap)?,
+        (min_x as i32, min_y as i32),
+        ((max_x - min_x) as u32, (max_y - min_y) as u32),
+    ))
+}
@@ -0,0 +1,163 @@
+use crate::presentation::Color;
+use crate::rendering::display;
+use sdl2::video::WindowBuilder;
+use sdl2::VideoSubsystem;
+
+/// `SDL_WINDOW_FULLSCREEN_DESKTOP`
+const FULLSCREEN_DESKTOP: u32 = 0x0000_1001;
+/// `SDL_WINDOW_BORDERLESS`
+const BORDERLESS: u32 = 0x0000_0010;
+/// `SDL_WINDOW_ALWAYS_ON_TOP`, which the builder has no method for
+const ALWAYS_ON_TOP: u32 = 0x0000_8000;
+
+/// How the presentation window is opened, for capturing it cleanly with
+/// streaming software or projecting it on unusual setups
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct WindowOptions {
+    /// Window flags added to the ones the window is built with
+    flags: u32,
+    position: Option<(i32, i32)>,
+    display: Option<i32>,
+    transparent_background: bool,
+    vsync: bool,
+}
+
+impl Default for WindowOptions {
+    fn default() -> Self {
+        Self {
+            flags: 0,
+            position: None,
+            display: None,
+            transparent_background: false,
+            vsync: true,
+        }
+    }
+}
+
+impl WindowOptions {
+    /// Fills the display the window opens on, at the display's resolution
+    pub fn set_fullscreen(&mut self, fullscreen: bool) {
+        self.set_flag(FULLSCREEN_DESKTOP, fullscreen);
+    }
+
+    pub fn fullscreen(&self) -> bool {
+        self.flags & FULLSCREEN_DESKTOP == FULLSCREEN_DESKTOP
+    }
+
+    pub fn set_borderless(&mut self, borderless: bool) {
+        self.set_flag(BORDERLESS, borderless);
+    }
+
+    pub fn set_always_on_top(&mut self, always_on_top: bool) {
+        self.set_flag(ALWAYS_ON_TOP, always_on_top);
+    }
+
+    /// Where the window's top left corner goes, it is centered otherwise
+    pub fn set_position(&mut self, x: i32, y: i32) {
+        self.position = Some((x, y));
+    }
+
+    /// Display the window fills, by its index among the connected ones. It
+    /// takes precedence over the position.
+    pub fn set_display(&mut self, index: i32) {
+        self.display = Some(index);
+    }
+
+    pub fn display(&self) -> Option<i32> {
+        self.display
+    }
+
+    /// Draws the slides without their backgrounds, see `backdrop`
+    pub fn set_transparent_background(&mut self, transparent_background: bool) {
+        self.transparent_background = transparent_background;
+    }
+
+    /// Whether presenting a frame waits for the display to refresh, frames
+    /// are paced by the display's refresh rate otherwise
+    pub fn set_vsync(&mut self, vsync: bool) {
+        self.vsync = vsync;
+    }
+
+    pub fn vsync(&self) -> bool {
+        self.vsync
+    }
+
+    /// Window flags the options add to the ones the window is built with
+    pub fn flags(&self) -> u32 {
+        self.flags
+    }
+
+    fn set_flag(&mut self, flag: u32, set: bool) {
+        if set {
+            self.flags |= flag;
+        } else {
+            self.flags &= !flag;
+        }
+    }
+
+    /// Color drawn in place of the slides' backgrounds. SDL windows can't
+    /// be see-through, so a transparent background is pure green, which
+    /// chroma key filters like the one in OBS take out.
+    pub fn backdrop(&self) -> Option<Color> {
+        if self.transparent_background {
+            Some(Color::new(0, 0xff, 0, 0xff))
+        } else {
+            None
+        }
+    }
+
+    /// Fails when the display asked for isn't there, listing the ones that
+    /// are
+    pub fn apply(&self, video: &VideoSubsystem, builder: &mut WindowBuilder) -> Result<(), String> {
+        let flags = builder.window_flags() | self.flags();
+        builder.set_window_flags(flags);
+
+        if let Some(index) = self.display {
+            let bounds = display::bounds(video, index)?;
+            builder
+                .position(bounds.x(), bounds.y())
+                .fullscreen_desktop();
+        } else if let Some((x, y)) = self.position {
+            builder.position(x, y);
+        } else {
+            builder.position_centered();
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn adds_flags_for_the_options_set() {
+        let mut options = WindowOptions::default();
+        assert_eq!(0, options.flags());
+
+        options.set_borderless(true);
+        options.set_always_on_top(true);
+
+        assert_eq!(BORDERLESS | ALWAYS_ON_TOP, options.flags());
+
+        options.set_borderless(false);
+
+        assert_eq!(ALWAYS_ON_TOP, options.flags());
+
+        options.set_fullscreen(true);
+
+        assert!(options.fullscreen());
+        assert_eq!(ALWAYS_ON_TOP | FULLSCREEN_DESKTOP, options.flags());
+    }
+
+    #[test]
+    pub fn draws_a_chroma_key_backdrop_for_transparent_backgrounds() {
+        let mut options = WindowOptions::default();
+        assert_eq!(None, options.backdrop());
+
+        options.set_transparent_background(true);
+
+        assert_eq!(Some(Color::new(0, 0xff, 0, 0xff)), options.backdrop());
+    }
+}
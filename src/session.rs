@@ -0,0 +1,419 @@
+use crate::assets;
+use crate::event_loop::{Error, OnLoop};
+use crate::navigation::Navigation;
+use crate::pointer::Pointer;
+use crate::rehearsal::Rehearsal;
+use std::cell::RefCell;
+use std::fs;
+use std::io;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum SessionError {
+    Io(String),
+    InvalidLine(String),
+    InvalidValue { key: String, value: String },
+}
+
+impl From<io::Error> for SessionError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(format!("{:?}", error))
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Default)]
+pub struct Session {
+    current_slide: usize,
+    elapsed: Duration,
+    /// Pointer strokes drawn on the current slide
+    strokes: Vec<Vec<(i32, i32)>>,
+    /// Time rehearsed on each slide by its index, when rehearsing
+    rehearsal: Vec<Duration>,
+}
+
+impl Session {
+    pub fn new(current_slide: usize, elapsed: Duration) -> Self {
+        Self {
+            current_slide,
+            elapsed,
+            strokes: vec![],
+            rehearsal: vec![],
+        }
+    }
+
+    /// Path of the session file kept next to the presentation file
+    pub fn path_for(presentation_path: &Path) -> PathBuf {
+        let mut file_name = presentation_path
+            .file_name()
+            .map(std::ffi::OsStr::to_os_string)
+            .unwrap_or_default();
+        file_name.push(".session");
+
+        presentation_path.with_file_name(file_name)
+    }
+
+    /// Path of the file keeping the presentation's last shown slide. Unlike
+    /// the session file it outlives a clean exit.
+    pub fn last_position_path_for(presentation_path: &Path) -> Option<PathBuf> {
+        Self::state_path_for(presentation_path, "position")
+    }
+
+    /// Path of a file kept about the presentation in `$XDG_STATE_HOME/przntr`
+    /// or `~/.local/state/przntr`, told apart from the other ones kept about
+    /// it by the extension
+    pub fn state_path_for(presentation_path: &Path, extension: &str) -> Option<PathBuf> {
+        let state_dir = std::env::var_os("XDG_STATE_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| {
+                std::env::var_os("HOME").map(|home| Path::new(&home).join(".local/state"))
+            })?;
+        let presentation_path =
+            fs::canonicalize(presentation_path).unwrap_or_else(|_| presentation_path.into());
+
+        Some(state_path_in(&state_dir, &presentation_path, extension))
+    }
+
+    pub fn load(path: &Path) -> Result<Option<Self>, SessionError> {
+        match fs::read_to_string(path) {
+            Ok(contents) => Ok(Some(Self::parse(&contents)?)),
+            Err(ref error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// Writes the session to a temporary file first and renames it over the
+    /// target, so a crash mid-write never leaves a truncated session behind
+    pub fn save(&self, path: &Path) -> Result<(), SessionError> {
+        let mut temporary_name = path.as_os_str().to_os_string();
+        temporary_name.push(".tmp");
+        let temporary_path = PathBuf::from(temporary_name);
+
+        let mut file = fs::File::create(&temporary_path)?;
+        file.write_all(self.serialize().as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&temporary_path, path)?;
+
+        Ok(())
+    }
+
+    pub fn discard(path: &Path) -> Result<(), SessionError> {
+        match fs::remove_file(path) {
+            Err(ref error) if error.kind() != io::ErrorKind::NotFound => {
+                Err(SessionError::Io(format!("{:?}", error)))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn current_slide(&self) -> usize {
+        self.current_slide
+    }
+
+    /// Time presented before the session was saved
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    pub fn strokes(&self) -> &Vec<Vec<(i32, i32)>> {
+        &self.strokes
+    }
+
+    pub fn set_strokes(&mut self, strokes: Vec<Vec<(i32, i32)>>) {
+        self.strokes = strokes;
+    }
+
+    pub fn rehearsal(&self) -> &Vec<Duration> {
+        &self.rehearsal
+    }
+
+    pub fn set_rehearsal(&mut self, rehearsal: Vec<Duration>) {
+        self.rehearsal = rehearsal;
+    }
+
+    /// A stroke is a line of its points like `stroke 10,20 12,24`, the
+    /// rehearsal a line of milliseconds in the order of the slides
+    fn serialize(&self) -> String {
+        let mut lines = vec![
+            format!("current-slide {}", self.current_slide),
+            format!("elapsed-ms {}", self.elapsed.as_millis()),
+        ];
+        for stroke in &self.strokes {
+            let points: Vec<String> = stroke.iter().map(|(x, y)| format!("{},{}", x, y)).collect();
+            lines.push(format!("stroke {}", points.join(" ")));
+        }
+        if !self.rehearsal.is_empty() {
+            let spent: Vec<String> = self
+                .rehearsal
+                .iter()
+                .map(|spent| spent.as_millis().to_string())
+                .collect();
+            lines.push(format!("rehearsal-ms {}", spent.join(" ")));
+        }
+
+        lines.push(String::new());
+        lines.join("\n")
+    }
+
+    fn parse(contents: &str) -> Result<Self, SessionError> {
+        let mut session = Self::default();
+
+        for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+            let mut parts = line.splitn(2, ' ');
+            let key = parts.next().unwrap_or_default();
+            let value = parts
+                .next()
+                .ok_or_else(|| SessionError::InvalidLine(line.into()))?;
+            let invalid_value = || SessionError::InvalidValue {
+                key: key.into(),
+                value: value.into(),
+            };
+
+            match key {
+                "current-slide" => {
+                    session.current_slide = value.parse().map_err(|_| invalid_value())?;
+                }
+                "elapsed-ms" => {
+                    session.elapsed =
+                        Duration::from_millis(value.parse().map_err(|_| invalid_value())?);
+                }
+                "stroke" => {
+                    let stroke = value
+                        .split_whitespace()
+                        .map(|point| {
+                            let (x, y) = point.split_once(',')?;
+                            Some((x.parse().ok()?, y.parse().ok()?))
+                        })
+                        .collect::<Option<_>>()
+                        .ok_or_else(invalid_value)?;
+                    session.strokes.push(stroke);
+                }
+                "rehearsal-ms" => {
+                    session.rehearsal = value
+                        .split_whitespace()
+                        .map(|spent| spent.parse().map(Duration::from_millis))
+                        .collect::<Result<_, _>>()
+                        .map_err(|_| invalid_value())?;
+                }
+                _ => return Err(SessionError::InvalidLine(line.into())),
+            }
+        }
+
+        Ok(session)
+    }
+}
+
+/// Files are keyed by a hash of the presentation's path, so that files of
+/// the same name in different directories don't share one
+fn state_path_in(state_dir: &Path, presentation_path: &Path, extension: &str) -> PathBuf {
+    let hash = assets::hash(presentation_path.to_string_lossy().as_bytes());
+
+    state_dir
+        .join("przntr")
+        .join(format!("{}.{}", hash, extension))
+}
+
+/// Remembers the slide shown last whenever it changes, for `--resume`
+pub struct LastPosition<'a> {
+    path: PathBuf,
+    navigation: &'a RefCell<Navigation>,
+    saved: Option<usize>,
+}
+
+impl<'a> LastPosition<'a> {
+    pub fn new(path: PathBuf, navigation: &'a RefCell<Navigation>) -> Self {
+        Self {
+            path,
+            navigation,
+            saved: None,
+        }
+    }
+
+    /// Slide shown last in an earlier run, none when there wasn't one
+    pub fn load(path: &Path) -> Result<Option<usize>, SessionError> {
+        Ok(Session::load(path)?.map(|session| session.current_slide))
+    }
+}
+
+impl<'a> OnLoop for LastPosition<'a> {
+    fn run(&mut self) -> Result<(), Error> {
+        let current_slide = self.navigation.borrow().current_slide();
+        if self.saved == Some(current_slide) {
+            return Ok(());
+        }
+
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).map_err(|error| Error::SaveSession(error.into()))?;
+        }
+        Session::new(current_slide, Duration::default())
+            .save(&self.path)
+            .map_err(Error::SaveSession)?;
+        self.saved = Some(current_slide);
+
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        "last position"
+    }
+}
+
+pub struct Autosave<'a> {
+    path: PathBuf,
+    interval: Duration,
+    navigation: &'a RefCell<Navigation>,
+    pointer: Option<&'a RefCell<Pointer>>,
+    rehearsal: Option<&'a RefCell<Rehearsal<'a>>>,
+    started_at: Instant,
+    last_saved_at: Instant,
+    elapsed_before_start: Duration,
+}
+
+impl<'a> Autosave<'a> {
+    pub fn new(
+        path: PathBuf,
+        interval: Duration,
+        navigation: &'a RefCell<Navigation>,
+        restored: &Session,
+    ) -> Self {
+        let now = Instant::now();
+
+        Self {
+            path,
+            interval,
+            navigation,
+            pointer: None,
+            rehearsal: None,
+            started_at: now,
+            last_saved_at: now,
+            elapsed_before_start: restored.elapsed,
+        }
+    }
+
+    /// Pointer whose strokes are saved with the session
+    pub fn set_pointer(&mut self, pointer: &'a RefCell<Pointer>) {
+        self.pointer = Some(pointer);
+    }
+
+    /// Rehearsal whose timings are saved with the session
+    pub fn set_rehearsal(&mut self, rehearsal: &'a RefCell<Rehearsal<'a>>) {
+        self.rehearsal = Some(rehearsal);
+    }
+
+    fn current_session(&self) -> Session {
+        let mut session = Session::new(
+            self.navigation.borrow().current_slide(),
+            self.elapsed_before_start + self.started_at.elapsed(),
+        );
+        if let Some(pointer) = self.pointer {
+            session.set_strokes(pointer.borrow().strokes().clone());
+        }
+        if let Some(rehearsal) = self.rehearsal {
+            session.set_rehearsal(rehearsal.borrow().spent(Instant::now()));
+        }
+
+        session
+    }
+}
+
+impl<'a> OnLoop for Autosave<'a> {
+    fn run(&mut self) -> Result<(), Error> {
+        if self.last_saved_at.elapsed() < self.interval {
+            return Ok(());
+        }
+
+        self.last_saved_at = Instant::now();
+        self.current_session()
+            .save(&self.path)
+            .map_err(Error::SaveSession)
+    }
+
+    fn name(&self) -> &'static str {
+        "session autosave"
+    }
+
+    fn wake_up_in(&self, now: Instant) -> Option<Duration> {
+        Some((self.last_saved_at + self.interval).saturating_duration_since(now))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn session_path_is_next_to_the_presentation() {
+        assert_eq!(
+            PathBuf::from("/some/dir/talk.prz.session"),
+            Session::path_for(Path::new("/some/dir/talk.prz"))
+        );
+    }
+
+    #[test]
+    pub fn last_positions_are_kept_per_presentation_path() {
+        let state_dir = Path::new("/home/me/.local/state");
+        let talk = state_path_in(state_dir, Path::new("/talks/a/talk.prz"), "position");
+
+        assert!(talk.starts_with("/home/me/.local/state/przntr"));
+        assert_eq!(
+            talk,
+            state_path_in(state_dir, Path::new("/talks/a/talk.prz"), "position")
+        );
+        assert_ne!(
+            talk,
+            state_path_in(state_dir, Path::new("/talks/b/talk.prz"), "position")
+        );
+    }
+
+    #[test]
+    pub fn can_parse_serialized_session() {
+        let mut session = Session::new(12, Duration::from_millis(345_678));
+        session.set_strokes(vec![vec![(10, 20), (-3, 24)], vec![(5, 5)]]);
+        session.set_rehearsal(vec![Duration::from_millis(1_500), Duration::default()]);
+
+        assert_eq!(Ok(session.clone()), Session::parse(&session.serialize()));
+    }
+
+    #[test]
+    pub fn fails_on_unknown_key() {
+        assert_eq!(
+            Err(SessionError::InvalidLine("something 12".into())),
+            Session::parse("something 12")
+        );
+    }
+
+    #[test]
+    pub fn fails_on_invalid_value() {
+        assert_eq!(
+            Err(SessionError::InvalidValue {
+                key: "current-slide".into(),
+                value: "abc".into()
+            }),
+            Session::parse("current-slide abc")
+        );
+        assert_eq!(
+            Err(SessionError::InvalidValue {
+                key: "stroke".into(),
+                value: "10,20 30".into()
+            }),
+            Session::parse("stroke 10,20 30")
+        );
+    }
+
+    #[test]
+    pub fn save_and_load_round_trip() {
+        let path = std::env::temp_dir().join(format!(
+            "przntr-session-test-{}.session",
+            std::process::id()
+        ));
+        let session = Session::new(3, Duration::from_secs(90));
+
+        session.save(&path).unwrap();
+        assert_eq!(Ok(Some(session)), Session::load(&path));
+
+        Session::discard(&path).unwrap();
+        assert_eq!(Ok(None), Session::load(&path));
+    }
+}
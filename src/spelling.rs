@@ -0,0 +1,610 @@
+use crate::parsing::token_stream::{
+    SourceLocation, SourceLocationRange, Token, TokenStream, TokenizerResult,
+};
+use crate::parsing::tokenizer::Tokenizer;
+use crate::presentation::{Presentation, Slide, SlideElement};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Where hunspell dictionaries are usually installed, searched after the
+/// directories listed in `DICPATH`
+const DICTIONARY_DIRECTORIES: &[&str] = &[
+    "/usr/share/hunspell",
+    "/usr/share/myspell",
+    "/usr/share/myspell/dicts",
+    "/usr/local/share/hunspell",
+    "/Library/Spelling",
+];
+
+const APOSTROPHES: &[char] = &['\'', '’'];
+
+/// How the flags of words and affixes are written, set by `FLAG` in the
+/// affix file
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum FlagType {
+    /// A single character each, the default
+    Character,
+    /// Two characters each
+    Long,
+    /// Numbers separated by commas
+    Numeric,
+}
+
+impl FlagType {
+    fn split(self, flags: &str) -> Vec<String> {
+        match self {
+            Self::Character => flags.chars().map(String::from).collect(),
+            Self::Long => flags
+                .chars()
+                .collect::<Vec<char>>()
+                .chunks(2)
+                .map(|pair| pair.iter().collect())
+                .collect(),
+            Self::Numeric => flags.split(',').map(String::from).collect(),
+        }
+    }
+}
+
+/// One character of an affix condition
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum Condition {
+    Any,
+    OneOf(Vec<char>),
+    NoneOf(Vec<char>),
+}
+
+impl Condition {
+    fn parse(pattern: &str) -> Vec<Self> {
+        let mut conditions = vec![];
+        let mut characters = pattern.chars();
+
+        while let Some(character) = characters.next() {
+            conditions.push(match character {
+                '.' => Self::Any,
+                '[' => {
+                    let set: String = characters.by_ref().take_while(|&c| c != ']').collect();
+                    match set.strip_prefix('^') {
+                        Some(excluded) => Self::NoneOf(excluded.chars().collect()),
+                        None => Self::OneOf(set.chars().collect()),
+                    }
+                }
+                character => Self::OneOf(vec![character]),
+            });
+        }
+
+        conditions
+    }
+
+    fn matches(&self, character: char) -> bool {
+        match self {
+            Self::Any => true,
+            Self::OneOf(characters) => characters.contains(&character),
+            Self::NoneOf(characters) => !characters.contains(&character),
+        }
+    }
+}
+
+/// A `PFX` or `SFX` rule turning a word into another form
+#[derive(Debug, Clone)]
+struct Affix {
+    is_prefix: bool,
+    cross_product: bool,
+    strip: String,
+    add: String,
+    condition: Vec<Condition>,
+}
+
+impl Affix {
+    fn apply(&self, word: &str) -> Option<String> {
+        let characters: Vec<char> = word.chars().collect();
+        if characters.len() < self.condition.len() {
+            return None;
+        }
+
+        if self.is_prefix {
+            let matches = self
+                .condition
+                .iter()
+                .zip(&characters)
+                .all(|(condition, &character)| condition.matches(character));
+            let stem = word.strip_prefix(self.strip.as_str())?;
+
+            matches.then(|| format!("{}{}", self.add, stem))
+        } else {
+            let ending = &characters[characters.len() - self.condition.len()..];
+            let matches = self
+                .condition
+                .iter()
+                .zip(ending)
+                .all(|(condition, &character)| condition.matches(character));
+            let stem = word.strip_suffix(self.strip.as_str())?;
+
+            matches.then(|| format!("{}{}", stem, self.add))
+        }
+    }
+}
+
+/// Words of a hunspell dictionary, with every form its prefix and suffix
+/// rules allow. Compounding and the other rarer affix file features are not
+/// supported.
+#[derive(Debug, Default)]
+pub struct Dictionary {
+    words: HashSet<String>,
+}
+
+impl Dictionary {
+    /// Finds the dictionary of a language such as `en_US` in the usual
+    /// places
+    pub fn load(language: &str) -> Result<Self, String> {
+        let directories = std::env::var_os("DICPATH")
+            .map(|paths| std::env::split_paths(&paths).collect::<Vec<PathBuf>>())
+            .unwrap_or_default();
+        let path = directories
+            .into_iter()
+            .chain(DICTIONARY_DIRECTORIES.iter().map(PathBuf::from))
+            .map(|directory| directory.join(format!("{}.dic", language)))
+            .find(|path| path.is_file())
+            .ok_or_else(|| format!("no hunspell dictionary found for `{}`", language))?;
+
+        Self::from_file(&path)
+    }
+
+    /// Reads a `.dic` file and the `.aff` file next to it
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let read = |path: &Path| {
+            fs::read(path)
+                .map(decode)
+                .map_err(|e| format!("could not read {}: {}", path.display(), e))
+        };
+
+        Ok(Self::parse(
+            &read(&path.with_extension("aff"))?,
+            &read(path)?,
+        ))
+    }
+
+    /// Builds the dictionary from the contents of an affix file and a word
+    /// list
+    pub fn parse(affix_file: &str, word_list: &str) -> Self {
+        let (flag_type, affixes) = parse_affixes(affix_file);
+        let mut words = HashSet::new();
+
+        // the first line is the number of words
+        for line in word_list.lines().skip(1) {
+            let entry = line.split('\t').next().unwrap_or_default().trim();
+            let (word, flags) = match entry.split_once('/') {
+                Some((word, flags)) => (word, flag_type.split(flags)),
+                None => (entry, vec![]),
+            };
+            if word.is_empty() {
+                continue;
+            }
+
+            let rules: Vec<&Affix> = flags
+                .iter()
+                .filter_map(|flag| affixes.get(flag))
+                .flatten()
+                .collect();
+            for suffix in rules.iter().filter(|affix| !affix.is_prefix) {
+                if let Some(form) = suffix.apply(word) {
+                    for prefix in rules.iter().filter(|affix| affix.is_prefix) {
+                        if suffix.cross_product && prefix.cross_product {
+                            words.extend(prefix.apply(&form));
+                        }
+                    }
+                    words.insert(form);
+                }
+            }
+            for prefix in rules.iter().filter(|affix| affix.is_prefix) {
+                words.extend(prefix.apply(word));
+            }
+            words.insert(word.to_string());
+        }
+
+        Self { words }
+    }
+
+    /// Whether the word is spelled right. Words may start with a capital
+    /// letter or be all capitals even when the dictionary has them in lower
+    /// case.
+    pub fn contains(&self, word: &str) -> bool {
+        let word = word.replace('’', "'");
+        if self.words.contains(&word) {
+            return true;
+        }
+
+        let lower = word.to_lowercase();
+        let mut characters = word.chars();
+        let is_capitalized = matches!(characters.next(), Some(first) if first.is_uppercase())
+            && characters.all(|character| !character.is_uppercase());
+        let is_upper_case = word.chars().all(|character| !character.is_lowercase());
+
+        ((is_capitalized || is_upper_case) && self.words.contains(&lower))
+            || (is_upper_case && self.words.contains(&capitalize(&lower)))
+    }
+}
+
+/// Reads the flag type and the affix rules by their flag
+fn parse_affixes(affix_file: &str) -> (FlagType, HashMap<String, Vec<Affix>>) {
+    let mut flag_type = FlagType::Character;
+    let mut cross_products = HashMap::new();
+    let mut affixes: HashMap<String, Vec<Affix>> = HashMap::new();
+
+    for line in affix_file.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+
+        match fields.as_slice() {
+            ["FLAG", "long", ..] => flag_type = FlagType::Long,
+            ["FLAG", "num", ..] => flag_type = FlagType::Numeric,
+            [kind @ ("PFX" | "SFX"), flag, cross_product @ ("Y" | "N"), count]
+                if count.parse::<u32>().is_ok() =>
+            {
+                cross_products.insert((*kind, *flag), *cross_product == "Y");
+            }
+            [kind @ ("PFX" | "SFX"), flag, strip, add, rest @ ..] => {
+                let add = add.split('/').next().unwrap_or_default();
+                affixes.entry((*flag).into()).or_default().push(Affix {
+                    is_prefix: *kind == "PFX",
+                    cross_product: cross_products
+                        .get(&(*kind, *flag))
+                        .copied()
+                        .unwrap_or_default(),
+                    strip: if *strip == "0" { "" } else { strip }.into(),
+                    add: if add == "0" { "" } else { add }.into(),
+                    condition: Condition::parse(rest.first().unwrap_or(&".")),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    (flag_type, affixes)
+}
+
+/// Text of a dictionary file, older ones are in Latin-1 rather than UTF-8
+fn decode(bytes: Vec<u8>) -> String {
+    String::from_utf8(bytes)
+        .unwrap_or_else(|error| error.into_bytes().into_iter().map(char::from).collect())
+}
+
+fn capitalize(word: &str) -> String {
+    let mut characters = word.chars();
+
+    characters
+        .next()
+        .map(|first| first.to_uppercase().chain(characters).collect())
+        .unwrap_or_default()
+}
+
+/// The words of a text with their byte offsets, apostrophes are kept inside
+/// words and numbers are left out
+fn words(text: &str) -> Vec<(usize, &str)> {
+    let mut words = vec![];
+    let mut start = None;
+
+    for (index, character) in text.char_indices().chain(Some((text.len(), ' '))) {
+        let is_word_character = character.is_alphanumeric() || APOSTROPHES.contains(&character);
+
+        match start {
+            None if is_word_character => start = Some(index),
+            Some(word_start) if !is_word_character => {
+                start = None;
+
+                let word = &text[word_start..index];
+                let trimmed = word.trim_start_matches(APOSTROPHES);
+                let offset = word_start + word.len() - trimmed.len();
+                let trimmed = trimmed.trim_end_matches(APOSTROPHES);
+                if !trimmed.is_empty() && !trimmed.chars().any(char::is_numeric) {
+                    words.push((offset, trimmed));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    words
+}
+
+/// A word the dictionary doesn't know
+#[derive(Debug, Eq, PartialEq)]
+pub struct Misspelling {
+    word: String,
+    slide: Option<String>,
+    location: Option<SourceLocationRange>,
+}
+
+impl Misspelling {
+    pub fn word(&self) -> &String {
+        &self.word
+    }
+
+    /// Name of the slide the word is on, none for the presentation's title
+    pub fn slide(&self) -> Option<&String> {
+        self.slide.as_ref()
+    }
+
+    /// Where the word is written, when the source is known
+    pub fn location(&self) -> Option<SourceLocationRange> {
+        self.location
+    }
+
+    pub fn message(&self) -> String {
+        match &self.slide {
+            Some(slide) => format!("`{}` on slide `{}` may be misspelled", self.word, slide),
+            None => format!("`{}` may be misspelled", self.word),
+        }
+    }
+}
+
+/// Spell checks the presentation's title and the texts, list items and
+/// notes of every slide. `source` is the DSL the presentation was parsed
+/// from, used to point at the words.
+pub fn check(
+    presentation: &Presentation,
+    dictionary: &Dictionary,
+    source: Option<&str>,
+) -> Vec<Misspelling> {
+    let ignored: HashSet<String> = presentation
+        .metadata()
+        .spelling_ignore()
+        .iter()
+        .map(|word| word.to_lowercase())
+        .collect();
+    let strings = source.map(SourceStrings::new);
+    let mut misspellings = vec![];
+
+    let title = presentation.metadata().title().as_str();
+    let texts = std::iter::once((None, vec![title])).chain(
+        presentation
+            .slides()
+            .iter()
+            .map(|slide| (Some(slide.name()), slide_texts(slide))),
+    );
+    for (slide, texts) in texts {
+        let mut occurrences: HashMap<&str, usize> = HashMap::new();
+
+        for (_, word) in texts.into_iter().flat_map(words) {
+            let occurrence = occurrences.entry(word).or_default();
+            *occurrence += 1;
+
+            if dictionary.contains(word) || ignored.contains(&word.to_lowercase()) {
+                continue;
+            }
+            misspellings.push(Misspelling {
+                word: word.into(),
+                slide: slide.cloned(),
+                location: strings
+                    .as_ref()
+                    .and_then(|strings| strings.locate(slide, word, *occurrence)),
+            });
+        }
+    }
+
+    misspellings
+}
+
+/// Everything on a slide that is shown or read out, in order
+fn slide_texts(slide: &Slide) -> Vec<&str> {
+    slide
+        .elements()
+        .iter()
+        .flat_map(SlideElement::walk)
+        .flat_map(|element| match element {
+            SlideElement::Text(text) | SlideElement::Title(text) | SlideElement::Subtitle(text) => {
+                vec![text.content().as_str()]
+            }
+            SlideElement::List(list) => list.items().iter().map(String::as_str).collect(),
+            _ => vec![],
+        })
+        .chain(slide.notes().iter().map(String::as_str))
+        .collect()
+}
+
+/// Values of string tokens with where they are written
+type LocatedStrings = Vec<(String, SourceLocationRange)>;
+
+/// The strings of a DSL source with where they are, grouped by the slide
+/// they are in, the ones before the first slide have no slide
+struct SourceStrings<'a> {
+    source: &'a str,
+    slides: Vec<(Option<String>, LocatedStrings)>,
+}
+
+impl<'a> SourceStrings<'a> {
+    fn new(source: &'a str) -> Self {
+        let mut tokenizer = Tokenizer::new(source);
+        tokenizer.set_recover(true);
+        let mut slides = vec![(None, vec![])];
+        let mut is_slide_name = false;
+
+        loop {
+            is_slide_name = match tokenizer.next() {
+                TokenizerResult::Ok(Token::String(string), _) if is_slide_name => {
+                    slides.push((Some(string), vec![]));
+                    false
+                }
+                TokenizerResult::Ok(Token::String(string), location) => {
+                    if let Some((_, strings)) = slides.last_mut() {
+                        strings.push((string, location));
+                    }
+                    false
+                }
+                TokenizerResult::Ok(Token::KeywordSlide, _) => true,
+                TokenizerResult::End => break,
+                _ => false,
+            };
+        }
+
+        Self { source, slides }
+    }
+
+    /// Where the given occurrence of the word on the slide is, counting from
+    /// one
+    fn locate(
+        &self,
+        slide: Option<&String>,
+        word: &str,
+        occurrence: usize,
+    ) -> Option<SourceLocationRange> {
+        let (_, strings) = self
+            .slides
+            .iter()
+            .find(|(name, _)| name.as_ref() == slide)?;
+        let mut remaining = occurrence;
+
+        for (string, location) in strings {
+            let count = words(string).iter().filter(|(_, w)| *w == word).count();
+            if count < remaining {
+                remaining -= count;
+                continue;
+            }
+
+            return Some(self.narrow(*location, word, remaining).unwrap_or(*location));
+        }
+
+        None
+    }
+
+    /// Location of the word in the written string, which can differ from the
+    /// string's value where it has escapes
+    fn narrow(
+        &self,
+        location: SourceLocationRange,
+        word: &str,
+        occurrence: usize,
+    ) -> Option<SourceLocationRange> {
+        let (start, end) = (location.start(), location.end());
+        let lines = self
+            .source
+            .lines()
+            .enumerate()
+            .skip(start.line() as usize)
+            .take((end.line() - start.line()) as usize + 1);
+
+        lines
+            .flat_map(|(number, line)| {
+                let first_column = if number == start.line() as usize {
+                    start.column() as usize
+                } else {
+                    0
+                };
+                let offset = line
+                    .char_indices()
+                    .nth(first_column)
+                    .map_or(line.len(), |(offset, _)| offset);
+
+                words(&line[offset..])
+                    .into_iter()
+                    .map(move |(word_offset, word)| {
+                        let column = line[..offset + word_offset].chars().count() as u32 + 1;
+                        (number as u32, column, word)
+                    })
+            })
+            .filter(|(_, _, written)| *written == word)
+            .nth(occurrence - 1)
+            .map(|(line, column, word)| {
+                SourceLocationRange::new(
+                    SourceLocation::new(line, column),
+                    SourceLocation::new(line, column + word.chars().count() as u32),
+                )
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const AFFIXES: &str = "SET UTF-8\n\
+                           PFX U Y 1\n\
+                           PFX U 0 un .\n\
+                           SFX D Y 3\n\
+                           SFX D 0 d e\n\
+                           SFX D y ied [^aeiou]y\n\
+                           SFX D 0 ed [^ey]\n";
+    const WORDS: &str = "5\nfinish/DU\ntry/D\nlike/DU\nParis\nthe\n";
+
+    #[test]
+    pub fn applies_prefixes_and_suffixes() {
+        let dictionary = Dictionary::parse(AFFIXES, WORDS);
+
+        for word in &[
+            "finish",
+            "finished",
+            "unfinished",
+            "tried",
+            "liked",
+            "unlike",
+        ] {
+            assert!(
+                dictionary.contains(word),
+                "{} is not in the dictionary",
+                word
+            );
+        }
+        assert!(!dictionary.contains("tryed"));
+        assert!(!dictionary.contains("untried"));
+    }
+
+    #[test]
+    pub fn accepts_capitalized_words_but_not_lower_case_names() {
+        let dictionary = Dictionary::parse(AFFIXES, WORDS);
+
+        assert!(dictionary.contains("The"));
+        assert!(dictionary.contains("FINISHED"));
+        assert!(dictionary.contains("PARIS"));
+        assert!(!dictionary.contains("paris"));
+        assert!(!dictionary.contains("tHe"));
+    }
+
+    #[test]
+    pub fn splits_words_around_punctuation_and_numbers() {
+        assert_eq!(
+            vec![(0, "don't"), (7, "stop"), (19, "it")],
+            words("don't 'stop', 3rd *it*")
+        );
+    }
+
+    #[test]
+    pub fn reports_unknown_words_where_they_are_written() {
+        let source = "metadata { title \"The finnish\", spelling-ignore \"przntr\" }\n\
+                      slide \"intro\" {\n  text \"przntr tried teh *tryed* teh\"\n  notes \"Liked\"\n}";
+        let presentation = crate::parse(source).unwrap();
+        let dictionary = Dictionary::parse(AFFIXES, WORDS);
+
+        let misspellings = check(&presentation, &dictionary, Some(source));
+
+        let location = |line, start, end| {
+            Some(SourceLocationRange::new(
+                SourceLocation::new(line, start),
+                SourceLocation::new(line, end),
+            ))
+        };
+        assert_eq!(
+            vec![
+                Misspelling {
+                    word: "finnish".into(),
+                    slide: None,
+                    location: location(0, 23, 30),
+                },
+                Misspelling {
+                    word: "teh".into(),
+                    slide: Some("intro".into()),
+                    location: location(2, 22, 25),
+                },
+                Misspelling {
+                    word: "tryed".into(),
+                    slide: Some("intro".into()),
+                    location: location(2, 27, 32),
+                },
+                Misspelling {
+                    word: "teh".into(),
+                    slide: Some("intro".into()),
+                    location: location(2, 34, 37),
+                },
+            ],
+            misspellings
+        );
+    }
+}
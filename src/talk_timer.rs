@@ -0,0 +1,288 @@
+use crate::presentation::{Presentation, Slide};
+use std::time::{Duration, Instant};
+
+/// How close the talk is to running out of time
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum TimeWarning {
+    None,
+    /// A fifth of the talk length or less is left
+    Soon,
+    /// A twentieth of the talk length or less is left, or the time is up
+    Over,
+}
+
+/// Time spent presenting since the start, against the planned talk length
+pub struct TalkTimer {
+    started_at: Instant,
+    length: Option<Duration>,
+    visible: bool,
+}
+
+impl TalkTimer {
+    /// The timer is shown from the start when there is a talk length
+    pub fn new(started_at: Instant, length: Option<Duration>) -> Self {
+        Self {
+            started_at,
+            length,
+            visible: length.is_some(),
+        }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    pub fn elapsed(&self, now: Instant) -> Duration {
+        now.saturating_duration_since(self.started_at)
+    }
+
+    /// Time left of the talk length, none past its end
+    pub fn remaining(&self, now: Instant) -> Option<Duration> {
+        self.length
+            .and_then(|length| length.checked_sub(self.elapsed(now)))
+    }
+
+    pub fn warning(&self, now: Instant) -> TimeWarning {
+        match (self.length, self.remaining(now)) {
+            (None, _) => TimeWarning::None,
+            (Some(length), Some(remaining)) if remaining > length / 5 => TimeWarning::None,
+            (Some(length), Some(remaining)) if remaining > length / 20 => TimeWarning::Soon,
+            (Some(_), _) => TimeWarning::Over,
+        }
+    }
+
+    /// Elapsed time, followed by the time left or the overrun when there is
+    /// a talk length
+    pub fn label(&self, now: Instant) -> String {
+        let elapsed = self.elapsed(now);
+
+        match (self.length, self.remaining(now)) {
+            (None, _) => format_minutes(elapsed),
+            (Some(_), Some(remaining)) => format!(
+                "{} / {} left",
+                format_minutes(elapsed),
+                format_minutes(remaining)
+            ),
+            (Some(length), None) => format!(
+                "{} / {} over",
+                format_minutes(elapsed),
+                format_minutes(elapsed.saturating_sub(length))
+            ),
+        }
+    }
+}
+
+/// How the talk keeps to the plan
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Pace {
+    /// The slide came up before the plan says it would
+    Ahead(Duration),
+    OnTime,
+    /// The slide is still up after the plan says it would be done
+    Behind(Duration),
+}
+
+impl Pace {
+    pub fn label(self) -> String {
+        match self {
+            Self::Ahead(duration) => format!("{} ahead", format_minutes(duration)),
+            Self::OnTime => "on time".into(),
+            Self::Behind(duration) => format!("{} behind", format_minutes(duration)),
+        }
+    }
+}
+
+/// Times into the talk the slides are planned to come up at, from their
+/// `at`
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Schedule {
+    slides: Vec<Option<Duration>>,
+}
+
+impl Schedule {
+    pub fn new(presentation: &Presentation) -> Self {
+        Self {
+            slides: presentation
+                .slides()
+                .iter()
+                .map(Slide::scheduled_at)
+                .collect(),
+        }
+    }
+
+    /// Whether no slide has a time
+    pub fn is_empty(&self) -> bool {
+        self.slides.iter().all(Option::is_none)
+    }
+
+    /// First slide after the current one that has a time, with the time
+    pub fn next(&self, current_slide: usize) -> Option<(usize, Duration)> {
+        self.slides
+            .iter()
+            .enumerate()
+            .skip(current_slide + 1)
+            .find_map(|(index, time)| time.map(|time| (index, time)))
+    }
+
+    /// Last slide whose time has come
+    pub fn due(&self, elapsed: Duration) -> Option<usize> {
+        self.slides
+            .iter()
+            .rposition(|time| time.is_some_and(|time| time <= elapsed))
+    }
+
+    /// Soonest time still to come
+    pub fn next_time(&self, elapsed: Duration) -> Option<Duration> {
+        self.slides
+            .iter()
+            .flatten()
+            .copied()
+            .filter(|time| *time > elapsed)
+            .min()
+    }
+
+    /// Ahead while the current slide, or the last slide before it that has
+    /// a time, is up before its time, behind once the time of the next
+    /// slide that has one has passed
+    pub fn pace(&self, current_slide: usize, elapsed: Duration) -> Pace {
+        let started = self
+            .slides
+            .iter()
+            .take(current_slide + 1)
+            .rev()
+            .find_map(|time| *time);
+
+        match (started, self.next(current_slide)) {
+            (Some(time), _) if elapsed < time => Pace::Ahead(time.saturating_sub(elapsed)),
+            (_, Some((_, time))) if elapsed > time => Pace::Behind(elapsed.saturating_sub(time)),
+            _ => Pace::OnTime,
+        }
+    }
+
+    /// Counts down to the next slide that has a time, by its number
+    pub fn countdown(&self, current_slide: usize, elapsed: Duration) -> Option<String> {
+        let (slide, time) = self.next(current_slide)?;
+
+        Some(match time.checked_sub(elapsed) {
+            Some(left) => format!(
+                "slide {} at {} in {}",
+                slide + 1,
+                format_minutes(time),
+                format_minutes(left)
+            ),
+            None => format!("slide {} was due at {}", slide + 1, format_minutes(time)),
+        })
+    }
+}
+
+/// Whole minutes and seconds, the minutes are not wrapped into hours
+pub fn format_minutes(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+
+    format!("{:02}:{:02}", seconds / 60, seconds % 60)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::presentation::{Metadata, Resolution, Style};
+
+    #[test]
+    pub fn shows_elapsed_and_remaining_time() {
+        let start = Instant::now();
+        let timer = TalkTimer::new(start, Some(Duration::from_secs(20 * 60)));
+
+        assert_eq!(
+            "05:12 / 14:48 left",
+            timer.label(start + Duration::from_secs(312))
+        );
+        assert_eq!(
+            "21:05 / 01:05 over",
+            timer.label(start + Duration::from_secs(1265))
+        );
+    }
+
+    #[test]
+    pub fn warns_near_the_end() {
+        let start = Instant::now();
+        let timer = TalkTimer::new(start, Some(Duration::from_secs(100)));
+
+        assert_eq!(
+            TimeWarning::None,
+            timer.warning(start + Duration::from_secs(79))
+        );
+        assert_eq!(
+            TimeWarning::Soon,
+            timer.warning(start + Duration::from_secs(80))
+        );
+        assert_eq!(
+            TimeWarning::Over,
+            timer.warning(start + Duration::from_secs(95))
+        );
+        assert_eq!(
+            TimeWarning::Over,
+            timer.warning(start + Duration::from_secs(200))
+        );
+    }
+
+    #[test]
+    pub fn keeps_to_the_schedule_of_the_slides() {
+        let slides = [None, Some(60), None, Some(300)]
+            .iter()
+            .map(|time| {
+                let mut slide = Slide::new("some slide".into(), vec![]);
+                if let Some(seconds) = time {
+                    slide.set_scheduled_at(Duration::from_secs(*seconds));
+                }
+                slide
+            })
+            .collect();
+        let schedule = Schedule::new(&Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            slides,
+            Style::empty(),
+        ));
+
+        assert_eq!(Some((3, Duration::from_secs(300))), schedule.next(1));
+        assert_eq!(Some(1), schedule.due(Duration::from_secs(90)));
+        assert_eq!(
+            Some(Duration::from_secs(300)),
+            schedule.next_time(Duration::from_secs(90))
+        );
+        assert_eq!(
+            Pace::Ahead(Duration::from_secs(20)),
+            schedule.pace(1, Duration::from_secs(40))
+        );
+        assert_eq!(Pace::OnTime, schedule.pace(2, Duration::from_secs(200)));
+        assert_eq!(
+            Pace::Behind(Duration::from_secs(12)),
+            schedule.pace(2, Duration::from_secs(312))
+        );
+        assert_eq!(
+            Some("slide 4 at 05:00 in 00:42".into()),
+            schedule.countdown(1, Duration::from_secs(258))
+        );
+        assert_eq!(
+            Some("slide 4 was due at 05:00".into()),
+            schedule.countdown(2, Duration::from_secs(312))
+        );
+        assert_eq!(None, schedule.countdown(3, Duration::from_secs(312)));
+    }
+
+    #[test]
+    pub fn only_counts_up_without_talk_length() {
+        let start = Instant::now();
+        let timer = TalkTimer::new(start, None);
+
+        assert!(!timer.is_visible());
+        assert_eq!("61:01", timer.label(start + Duration::from_secs(3661)));
+        assert_eq!(
+            TimeWarning::None,
+            timer.warning(start + Duration::from_secs(3661))
+        );
+    }
+}
@@ -0,0 +1,112 @@
+use std::fs::{self, DirBuilder};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// How many names are tried before giving up, each one is only taken by
+/// another directory when something guesses it
+const ATTEMPTS: u32 = 100;
+
+static CREATED: AtomicU32 = AtomicU32::new(0);
+
+/// A directory of the temporary one that nothing else had, readable only by
+/// the user. It is removed with everything in it when dropped.
+#[derive(Debug)]
+pub struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    /// Creates the directory, named after the prefix
+    pub fn new(prefix: &str) -> io::Result<Self> {
+        let parent = std::env::temp_dir();
+        let mut builder = DirBuilder::new();
+        restrict(&mut builder);
+
+        for _ in 0..ATTEMPTS {
+            let path = parent.join(unique_name(prefix));
+            // fails on anything already there, links included
+            match builder.create(&path) {
+                Ok(()) => return Ok(Self { path }),
+                Err(error) if error.kind() == io::ErrorKind::AlreadyExists => {}
+                Err(error) => return Err(error),
+            }
+        }
+
+        Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("no free name for a `{}` directory", prefix),
+        ))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        if let Err(error) = fs::remove_dir_all(&self.path) {
+            log::warn!("Could not remove {}: {}", self.path.display(), error);
+        }
+    }
+}
+
+fn unique_name(prefix: &str) -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since| since.subsec_nanos())
+        .unwrap_or_default();
+
+    format!(
+        "{}-{}-{}-{:08x}",
+        prefix,
+        process::id(),
+        CREATED.fetch_add(1, Ordering::Relaxed),
+        nanos
+    )
+}
+
+#[cfg(unix)]
+fn restrict(builder: &mut DirBuilder) {
+    use std::os::unix::fs::DirBuilderExt;
+
+    builder.mode(0o700);
+}
+
+/// The temporary directory is the user's own on other systems
+#[cfg(not(unix))]
+fn restrict(_builder: &mut DirBuilder) {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn creates_a_new_directory_each_time_and_removes_it_when_dropped() {
+        let first = TempDir::new("przntr-test").unwrap();
+        let second = TempDir::new("przntr-test").unwrap();
+        assert_ne!(first.path(), second.path());
+        assert!(first.path().is_dir());
+
+        let path = first.path().to_path_buf();
+        fs::write(path.join("file"), "contents").unwrap();
+        drop(first);
+
+        assert!(!path.exists());
+        assert!(second.path().is_dir());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    pub fn is_only_readable_by_the_user() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let directory = TempDir::new("przntr-test").unwrap();
+
+        let mode = fs::metadata(directory.path()).unwrap().permissions().mode();
+        assert_eq!(0o700, mode & 0o777);
+    }
+}
@@ -0,0 +1,471 @@
+use crate::font_resolver;
+use crate::presentation::{Fill, Layout, Presentation, Slide, SlideElement, Style};
+use crate::talk_timer::format_minutes;
+use std::fs::File;
+use std::time::Duration;
+
+/// Problems that the parser cannot see, because they depend on other files
+/// or on definitions elsewhere in the presentation
+#[derive(Debug, Eq, PartialEq)]
+pub enum ValidationError {
+    NoFonts,
+    FontNotFound {
+        name: String,
+        reason: String,
+    },
+    UnknownFont {
+        text_style: String,
+        font: String,
+    },
+    UnknownTextStyle {
+        slide: String,
+        text_style: String,
+    },
+    UnreadableImage {
+        slide: String,
+        path: String,
+        reason: String,
+    },
+    UnreadableVideo {
+        slide: String,
+        path: String,
+        reason: String,
+    },
+    /// Background image of the whole presentation, the ones of slides are
+    /// reported as their images
+    UnreadableBackground {
+        path: String,
+        reason: String,
+    },
+    UnknownLayout {
+        slide: String,
+        layout: String,
+    },
+    /// Elements put in a placeholder the slide's layout doesn't have, they
+    /// are shown after the layout instead
+    UnknownPlaceholder {
+        slide: String,
+        placeholder: String,
+    },
+    /// A slide scheduled at an earlier time than a slide before it
+    ScheduleOutOfOrder {
+        slide: String,
+        scheduled_at: Duration,
+        previous_slide: String,
+        previous_scheduled_at: Duration,
+    },
+}
+
+impl ValidationError {
+    pub fn message(&self) -> String {
+        match self {
+            Self::NoFonts => "the presentation does not define any fonts".into(),
+            Self::FontNotFound { name, reason } => {
+                format!("font `{}` cannot be loaded: {}", name, reason)
+            }
+            Self::UnknownFont { text_style, font } => {
+                format!("text style `{}` uses unknown font `{}`", text_style, font)
+            }
+            Self::UnknownTextStyle { slide, text_style } => {
+                format!("slide `{}` uses unknown text style `{}`", slide, text_style)
+            }
+            Self::UnreadableImage {
+                slide,
+                path,
+                reason,
+            } => format!(
+                "slide `{}` uses image `{}` which cannot be read: {}",
+                slide, path, reason
+            ),
+            Self::UnreadableVideo {
+                slide,
+                path,
+                reason,
+            } => format!(
+                "slide `{}` uses video `{}` which cannot be read: {}",
+                slide, path, reason
+            ),
+            Self::UnreadableBackground { path, reason } => {
+                format!("background image `{}` cannot be read: {}", path, reason)
+            }
+            Self::UnknownLayout { slide, layout } => {
+                format!("slide `{}` uses unknown layout `{}`", slide, layout)
+            }
+            Self::UnknownPlaceholder { slide, placeholder } => format!(
+                "slide `{}` fills placeholder `{}` which its layout does not have",
+                slide, placeholder
+            ),
+            Self::ScheduleOutOfOrder {
+                slide,
+                scheduled_at,
+                previous_slide,
+                previous_scheduled_at,
+            } => format!(
+                "slide `{}` is scheduled at {}, before slide `{}` at {}",
+                slide,
+                format_minutes(*scheduled_at),
+                previous_slide,
+                format_minutes(*previous_scheduled_at)
+            ),
+        }
+    }
+}
+
+/// Checks everything the renderer would trip over, without opening a window
+pub fn validate(presentation: &Presentation) -> Vec<ValidationError> {
+    let style = presentation.style();
+    let mut errors = validate_fonts(presentation);
+
+    if let Some((path, reason)) = unreadable_background(style.background()) {
+        errors.push(ValidationError::UnreadableBackground { path, reason });
+    }
+
+    // the slide with the latest time so far
+    let mut scheduled: Option<(&Slide, Duration)> = None;
+    for slide in presentation.slides() {
+        if let Some(scheduled_at) = slide.scheduled_at() {
+            match scheduled {
+                Some((previous, previous_scheduled_at)) if previous_scheduled_at > scheduled_at => {
+                    errors.push(ValidationError::ScheduleOutOfOrder {
+                        slide: slide.name().clone(),
+                        scheduled_at,
+                        previous_slide: previous.name().clone(),
+                        previous_scheduled_at,
+                    });
+                }
+                _ => scheduled = Some((slide, scheduled_at)),
+            }
+        }
+
+        if let Some((path, reason)) = unreadable_background(slide.background()) {
+            errors.push(ValidationError::UnreadableImage {
+                slide: slide.name().clone(),
+                path,
+                reason,
+            });
+        }
+
+        let layout = slide.layout().and_then(|name| presentation.layout(name));
+        if let Some(name) = slide.layout() {
+            if layout.is_none() {
+                errors.push(ValidationError::UnknownLayout {
+                    slide: slide.name().clone(),
+                    layout: name.clone(),
+                });
+            }
+        }
+
+        let placeholders = layout.map_or_else(Vec::new, Layout::placeholders);
+        let mut unknown_placeholders = vec![];
+        for index in 0..slide.elements().len() {
+            if let Some(placeholder) = slide.placeholder_of(index) {
+                if !placeholders.contains(&placeholder)
+                    && !unknown_placeholders.contains(&placeholder)
+                {
+                    unknown_placeholders.push(placeholder);
+                }
+            }
+        }
+        for placeholder in unknown_placeholders {
+            errors.push(ValidationError::UnknownPlaceholder {
+                slide: slide.name().clone(),
+                placeholder: placeholder.clone(),
+            });
+        }
+
+        let elements = presentation.arranged_elements(slide);
+        errors.extend(
+            elements
+                .iter()
+                .flat_map(|(element, _)| element.walk())
+                .filter_map(|element| validate_element(style, slide, element)),
+        );
+    }
+
+    errors
+}
+
+/// Fonts that can't be loaded and text styles naming fonts there aren't
+fn validate_fonts(presentation: &Presentation) -> Vec<ValidationError> {
+    let style = presentation.style();
+    let mut errors = vec![];
+
+    if style.default_font().is_none() {
+        errors.push(ValidationError::NoFonts);
+    }
+
+    let mut fonts = style.fonts();
+    fonts.sort_by_key(|font| (font.name().clone(), font.weight(), font.italic()));
+    for font in fonts {
+        match font_resolver::resolve(font, presentation.metadata().font_dirs()) {
+            Ok(path) => {
+                if let Err(error) = File::open(&path) {
+                    errors.push(ValidationError::FontNotFound {
+                        name: font.name().clone(),
+                        reason: format!("{}: {}", path.display(), error),
+                    });
+                }
+            }
+            Err(reason) => errors.push(ValidationError::FontNotFound {
+                name: font.name().clone(),
+                reason,
+            }),
+        }
+    }
+
+    let mut text_styles = style.text_style_names();
+    text_styles.sort();
+    for name in text_styles {
+        if let Some(font) = style
+            .text_style(name)
+            .and_then(|text_style| text_style.font())
+        {
+            if style
+                .fonts()
+                .iter()
+                .all(|candidate| candidate.name() != font)
+            {
+                errors.push(ValidationError::UnknownFont {
+                    text_style: name.clone(),
+                    font: font.clone(),
+                });
+            }
+        }
+    }
+
+    errors
+}
+
+/// Path of the background image and why it can't be read, if it can't
+fn unreadable_background(background: Option<&Fill>) -> Option<(String, String)> {
+    let path = background?.image_path()?;
+
+    File::open(path)
+        .err()
+        .map(|error| (path.clone(), error.to_string()))
+}
+
+/// Checks the text style a text uses, or that the file an image, a video or
+/// a row's or column's background shows can be read
+fn validate_element(
+    style: &Style,
+    slide: &Slide,
+    element: &SlideElement,
+) -> Option<ValidationError> {
+    match element {
+        SlideElement::Text(text) | SlideElement::Title(text) | SlideElement::Subtitle(text) => {
+            let text_style = text.style()?;
+
+            style
+                .text_style(text_style)
+                .is_none()
+                .then(|| ValidationError::UnknownTextStyle {
+                    slide: slide.name().clone(),
+                    text_style: text_style.clone(),
+                })
+        }
+        SlideElement::Image(image) => {
+            File::open(image.path())
+                .err()
+                .map(|error| ValidationError::UnreadableImage {
+                    slide: slide.name().clone(),
+                    path: image.path().clone(),
+                    reason: error.to_string(),
+                })
+        }
+        SlideElement::Video(video) => {
+            File::open(video.path())
+                .err()
+                .map(|error| ValidationError::UnreadableVideo {
+                    slide: slide.name().clone(),
+                    path: video.path().clone(),
+                    reason: error.to_string(),
+                })
+        }
+        SlideElement::Row(container) | SlideElement::Column(container) => {
+            unreadable_background(container.background()).map(|(path, reason)| {
+                ValidationError::UnreadableImage {
+                    slide: slide.name().clone(),
+                    path,
+                    reason,
+                }
+            })
+        }
+        SlideElement::List(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::presentation::{
+        Font, Image, ImageFit, LayoutItem, Metadata, Resolution, Slide, Style, Text, TextStyle,
+    };
+
+    #[test]
+    pub fn reports_missing_files_and_definitions() {
+        let mut style = Style::new(vec![Font::new(
+            "some-font".into(),
+            "/does/not/exist.ttf".into(),
+            400,
+            false,
+        )])
+        .unwrap();
+        style
+            .add_text_style(
+                "heading".into(),
+                TextStyle::new(Some("other-font".into()), None, None),
+            )
+            .unwrap();
+        let presentation = Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![Slide::new(
+                "some slide".into(),
+                vec![
+                    SlideElement::Text(Text::new("a".into(), Some("quote".into()))),
+                    SlideElement::Image(Image::new("/does/not/exist.png".into())),
+                ],
+            )],
+            style,
+        );
+
+        let errors = validate(&presentation);
+
+        assert_eq!(4, errors.len());
+        assert!(match &errors[0] {
+            ValidationError::FontNotFound { name, .. } => name == "some-font",
+            _ => false,
+        });
+        assert_eq!(
+            ValidationError::UnknownFont {
+                text_style: "heading".into(),
+                font: "other-font".into()
+            },
+            errors[1]
+        );
+        assert_eq!(
+            ValidationError::UnknownTextStyle {
+                slide: "some slide".into(),
+                text_style: "quote".into()
+            },
+            errors[2]
+        );
+        assert!(match &errors[3] {
+            ValidationError::UnreadableImage { path, .. } => path == "/does/not/exist.png",
+            _ => false,
+        });
+    }
+
+    #[test]
+    pub fn reports_unknown_layouts_and_placeholders() {
+        let text = || SlideElement::Text(Text::new("a".into(), None));
+        let mut first = Slide::new("first".into(), vec![]);
+        first.set_layout("three-column".into());
+        let mut second = Slide::new("second".into(), vec![]);
+        second.set_layout("two-column".into());
+        second.push_element_in_placeholder(text(), 0, Some("left".into()));
+        second.push_element_in_placeholder(text(), 0, Some("middle".into()));
+        second.push_element_in_placeholder(text(), 0, Some("middle".into()));
+        let mut presentation = Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![first, second],
+            Style::empty(),
+        );
+        presentation.add_layout(
+            "two-column".into(),
+            Layout::new(vec![LayoutItem::Placeholder("left".into())]),
+        );
+
+        assert_eq!(
+            vec![
+                ValidationError::NoFonts,
+                ValidationError::UnknownLayout {
+                    slide: "first".into(),
+                    layout: "three-column".into()
+                },
+                ValidationError::UnknownPlaceholder {
+                    slide: "second".into(),
+                    placeholder: "middle".into()
+                },
+            ],
+            validate(&presentation)
+        );
+    }
+
+    #[test]
+    pub fn reports_slides_scheduled_before_earlier_slides() {
+        let scheduled = |name: &str, seconds: u64| {
+            let mut slide = Slide::new(name.into(), vec![]);
+            slide.set_scheduled_at(Duration::from_secs(seconds));
+            slide
+        };
+        let presentation = Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![
+                scheduled("intro", 0),
+                scheduled("demo", 300),
+                scheduled("questions", 240),
+            ],
+            Style::empty(),
+        );
+
+        assert_eq!(
+            vec![
+                ValidationError::NoFonts,
+                ValidationError::ScheduleOutOfOrder {
+                    slide: "questions".into(),
+                    scheduled_at: Duration::from_secs(240),
+                    previous_slide: "demo".into(),
+                    previous_scheduled_at: Duration::from_secs(300)
+                },
+            ],
+            validate(&presentation)
+        );
+        assert_eq!(
+            "slide `questions` is scheduled at 04:00, before slide `demo` at 05:00",
+            validate(&presentation)[1].message()
+        );
+    }
+
+    #[test]
+    pub fn reports_unreadable_background_images() {
+        let missing = |path: &str| Fill::Image {
+            path: path.into(),
+            fit: ImageFit::Cover,
+        };
+        let mut slide = Slide::new("some slide".into(), vec![]);
+        slide.set_background(missing("/does/not/exist.png"));
+        let mut style = Style::empty();
+        style.set_background(missing("/does/not/exist.jpg"));
+        let presentation = Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![slide],
+            style,
+        );
+
+        let errors = validate(&presentation);
+
+        assert_eq!(3, errors.len());
+        assert!(match &errors[1] {
+            ValidationError::UnreadableBackground { path, .. } => path == "/does/not/exist.jpg",
+            _ => false,
+        });
+        assert!(match &errors[2] {
+            ValidationError::UnreadableImage { slide, path, .. } => {
+                slide == "some slide" && path == "/does/not/exist.png"
+            }
+            _ => false,
+        });
+    }
+
+    #[test]
+    pub fn requires_a_font() {
+        let presentation = Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![],
+            Style::empty(),
+        );
+
+        assert_eq!(vec![ValidationError::NoFonts], validate(&presentation));
+    }
+}
@@ -0,0 +1,185 @@
+use crate::event_loop::{Error, OnLoop};
+use crate::presentation::{FontSource, Presentation};
+use std::cell::RefCell;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Outcome of the latest reload, waiting to be picked up by the renderer
+pub type PendingReload = Option<Result<Presentation, String>>;
+
+/// Polls the presentation file and everything it references, reloading the
+/// presentation whenever one of them changes
+pub struct Watcher<'a, F: Fn() -> Result<Presentation, String>> {
+    presentation_path: PathBuf,
+    files: Vec<(PathBuf, Option<SystemTime>)>,
+    interval: Duration,
+    last_checked_at: Instant,
+    load: F,
+    pending_reload: &'a RefCell<PendingReload>,
+}
+
+impl<'a, F: Fn() -> Result<Presentation, String>> Watcher<'a, F> {
+    pub fn new(
+        presentation_path: PathBuf,
+        presentation: &Presentation,
+        interval: Duration,
+        load: F,
+        pending_reload: &'a RefCell<PendingReload>,
+    ) -> Self {
+        let mut watcher = Self {
+            presentation_path,
+            files: vec![],
+            interval,
+            last_checked_at: Instant::now(),
+            load,
+            pending_reload,
+        };
+        watcher.watch(presentation);
+
+        watcher
+    }
+
+    fn watch(&mut self, presentation: &Presentation) {
+        self.files = watched_paths(&self.presentation_path, presentation)
+            .into_iter()
+            .map(|path| {
+                let modified = modified_at(&path);
+                (path, modified)
+            })
+            .collect();
+    }
+
+    fn has_changed(&self) -> bool {
+        self.files
+            .iter()
+            .any(|(path, modified)| modified_at(path) != *modified)
+    }
+}
+
+impl<'a, F: Fn() -> Result<Presentation, String>> OnLoop for Watcher<'a, F> {
+    fn run(&mut self) -> Result<(), Error> {
+        if self.last_checked_at.elapsed() < self.interval {
+            return Ok(());
+        }
+
+        self.last_checked_at = Instant::now();
+        if !self.has_changed() {
+            return Ok(());
+        }
+
+        log::info!("Reloading {}", self.presentation_path.display());
+        let reload = (self.load)();
+        match &reload {
+            Ok(presentation) => self.watch(presentation),
+            // keep watching the old files, but don't report the same error again
+            Err(error) => {
+                log::debug!("The reloaded presentation has errors: {}", error);
+                for (path, modified) in &mut self.files {
+                    *modified = modified_at(path);
+                }
+            }
+        }
+        *self.pending_reload.borrow_mut() = Some(reload);
+
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        "file watcher"
+    }
+
+    fn wake_up_in(&self, now: Instant) -> Option<Duration> {
+        Some((self.last_checked_at + self.interval).saturating_duration_since(now))
+    }
+}
+
+/// The presentation file, the files it includes, its font files, images,
+/// background images and videos
+fn watched_paths(presentation_path: &Path, presentation: &Presentation) -> Vec<PathBuf> {
+    let mut paths = vec![presentation_path.to_path_buf()];
+    paths.extend(presentation.included_files().iter().cloned());
+
+    for font in presentation.style().fonts() {
+        if let FontSource::File(path) = font.source() {
+            paths.push(PathBuf::from(path));
+        }
+    }
+    paths.extend(presentation.media_paths());
+
+    paths
+}
+
+fn modified_at(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::presentation::{
+        Fill, Font, Image, ImageFit, Metadata, Resolution, Slide, SlideElement, Style,
+    };
+
+    #[test]
+    pub fn watches_fonts_and_images() {
+        let mut slide = Slide::new(
+            "some slide".into(),
+            vec![SlideElement::Image(Image::new("cat.png".into()))],
+        );
+        slide.set_background(Fill::Image {
+            path: "texture.png".into(),
+            fit: ImageFit::Tile,
+        });
+        let presentation = Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![slide],
+            Style::new(vec![
+                Font::new("some-font".into(), "font.ttf".into(), 400, false),
+                Font::system("sans".into(), 400, false),
+            ])
+            .unwrap(),
+        );
+
+        assert_eq!(
+            vec![
+                PathBuf::from("deck.prz"),
+                PathBuf::from("font.ttf"),
+                PathBuf::from("cat.png"),
+                PathBuf::from("texture.png")
+            ],
+            watched_paths(Path::new("deck.prz"), &presentation)
+        );
+    }
+
+    #[test]
+    pub fn reloads_when_a_file_changes() {
+        let path = std::env::temp_dir().join("przntr-watch-test.prz");
+        fs::write(&path, "first").unwrap();
+
+        let presentation = Presentation::new(
+            Metadata::new("some title".into(), Resolution::default()),
+            vec![],
+            Style::empty(),
+        );
+        let pending_reload = RefCell::new(None);
+        let mut watcher = Watcher::new(
+            path.clone(),
+            &presentation,
+            Duration::from_secs(0),
+            || Err("reloaded".into()),
+            &pending_reload,
+        );
+
+        watcher.run().unwrap();
+        assert_eq!(None, *pending_reload.borrow());
+
+        watcher.files[0].1 = None;
+        watcher.run().unwrap();
+        assert_eq!(Some(Err("reloaded".into())), *pending_reload.borrow());
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,357 @@
+use std::convert::TryFrom;
+use std::io;
+use std::io::Write;
+
+/// Names are UTF-8
+const UTF8_FLAG: u16 = 0x0800;
+/// 1980-01-01, the earliest date the format has
+const DOS_DATE: u16 = 0x21;
+
+struct Entry {
+    name: String,
+    crc: u32,
+    size: u32,
+    offset: u32,
+}
+
+/// Writes a ZIP archive with its files stored uncompressed, which is all
+/// Office documents need
+pub struct ZipWriter<W: Write> {
+    output: W,
+    offset: u32,
+    entries: Vec<Entry>,
+}
+
+impl<W: Write> ZipWriter<W> {
+    pub fn new(output: W) -> Self {
+        Self {
+            output,
+            offset: 0,
+            entries: vec![],
+        }
+    }
+
+    pub fn add_file(&mut self, name: &str, data: &[u8]) -> io::Result<()> {
+        let entry = Entry {
+            name: name.into(),
+            crc: crc32(data),
+            size: size(data.len())?,
+            offset: self.offset,
+        };
+
+        let header = [
+            &0x0403_4b50_u32.to_le_bytes()[..],
+            &20_u16.to_le_bytes(),
+            &UTF8_FLAG.to_le_bytes(),
+            // stored
+            &0_u16.to_le_bytes(),
+            &0_u16.to_le_bytes(),
+            &DOS_DATE.to_le_bytes(),
+            &entry.crc.to_le_bytes(),
+            &entry.size.to_le_bytes(),
+            &entry.size.to_le_bytes(),
+            &size(name.len())?.to_le_bytes()[..2],
+            &0_u16.to_le_bytes(),
+            name.as_bytes(),
+        ]
+        .concat();
+        self.write(&header)?;
+        self.write(data)?;
+        self.entries.push(entry);
+
+        Ok(())
+    }
+
+    /// Writes the central directory and hands back the output
+    pub fn finish(mut self) -> io::Result<W> {
+        let directory_offset = self.offset;
+        let directory = self
+            .entries
+            .iter()
+            .map(|entry| {
+                Ok([
+                    &0x0201_4b50_u32.to_le_bytes()[..],
+                    &20_u16.to_le_bytes(),
+                    &20_u16.to_le_bytes(),
+                    &UTF8_FLAG.to_le_bytes(),
+                    &0_u16.to_le_bytes(),
+                    &0_u16.to_le_bytes(),
+                    &DOS_DATE.to_le_bytes(),
+                    &entry.crc.to_le_bytes(),
+                    &entry.size.to_le_bytes(),
+                    &entry.size.to_le_bytes(),
+                    &size(entry.name.len())?.to_le_bytes()[..2],
+                    // extra field, comment, disk and attributes
+                    &[0; 12],
+                    &entry.offset.to_le_bytes(),
+                    entry.name.as_bytes(),
+                ]
+                .concat())
+            })
+            .collect::<io::Result<Vec<Vec<u8>>>>()?
+            .concat();
+        self.write(&directory)?;
+
+        let count = u16::try_from(self.entries.len())
+            .map_err(|_| io::Error::other("Too many files for a ZIP archive"))?;
+        let end = [
+            &0x0605_4b50_u32.to_le_bytes()[..],
+            &[0; 4],
+            &count.to_le_bytes(),
+            &count.to_le_bytes(),
+            &size(directory.len())?.to_le_bytes(),
+            &directory_offset.to_le_bytes(),
+            &0_u16.to_le_bytes(),
+        ]
+        .concat();
+        self.write(&end)?;
+
+        Ok(self.output)
+    }
+
+    fn write(&mut self, data: &[u8]) -> io::Result<()> {
+        self.output.write_all(data)?;
+        self.offset = self
+            .offset
+            .checked_add(size(data.len())?)
+            .ok_or_else(|| io::Error::other("The ZIP archive is too big"))?;
+
+        Ok(())
+    }
+}
+
+fn size(length: usize) -> io::Result<u32> {
+    u32::try_from(length).map_err(|_| io::Error::other("The ZIP archive is too big"))
+}
+
+/// A file listed in an archive's central directory
+struct ArchivedFile {
+    name: String,
+    method: u16,
+    crc: u32,
+    compressed_size: u32,
+    /// Size the file claims to have once inflated, it is never inflated
+    /// past it
+    size: u32,
+    offset: u32,
+}
+
+/// A ZIP archive read into memory, its files inflated when they are asked
+/// for and checked against their sizes and CRC-32s. Only stored and deflated
+/// files can be read, which is what Office documents use.
+pub struct ZipArchive {
+    data: Vec<u8>,
+    files: Vec<ArchivedFile>,
+}
+
+impl ZipArchive {
+    pub fn new(data: Vec<u8>) -> Result<Self, String> {
+        let invalid = || "Not a ZIP archive".to_string();
+        // the end record is at least 22 bytes, followed by a comment of up
+        // to 65535 bytes
+        let end = (0..=data.len().checked_sub(22).ok_or_else(invalid)?)
+            .rev()
+            .take(22 + 0xffff)
+            .find(|offset| read_u32(&data, *offset) == Some(0x0605_4b50))
+            .ok_or_else(invalid)?;
+        let count = read_u16(&data, end + 10).ok_or_else(invalid)?;
+        let mut offset = read_u32(&data, end + 16).ok_or_else(invalid)? as usize;
+
+        let mut files = vec![];
+        for _ in 0..count {
+            if read_u32(&data, offset) != Some(0x0201_4b50) {
+                return Err(invalid());
+            }
+            let field = |at: usize| read_u16(&data, offset + at).map(usize::from);
+            let name_length = field(28).ok_or_else(invalid)?;
+            let skipped =
+                name_length + field(30).ok_or_else(invalid)? + field(32).ok_or_else(invalid)?;
+            let name = data
+                .get(offset + 46..offset + 46 + name_length)
+                .ok_or_else(invalid)?;
+
+            files.push(ArchivedFile {
+                name: String::from_utf8_lossy(name).into_owned(),
+                method: read_u16(&data, offset + 10).ok_or_else(invalid)?,
+                crc: read_u32(&data, offset + 16).ok_or_else(invalid)?,
+                compressed_size: read_u32(&data, offset + 20).ok_or_else(invalid)?,
+                size: read_u32(&data, offset + 24).ok_or_else(invalid)?,
+                offset: read_u32(&data, offset + 42).ok_or_else(invalid)?,
+            });
+            offset += 46 + skipped;
+        }
+
+        Ok(Self { data, files })
+    }
+
+    pub fn names(&self) -> Vec<&String> {
+        self.files.iter().map(|file| &file.name).collect()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.files.iter().any(|file| file.name == name)
+    }
+
+    pub fn file(&self, name: &str) -> Result<Vec<u8>, String> {
+        let file = self
+            .files
+            .iter()
+            .find(|file| file.name == name)
+            .ok_or_else(|| format!("`{}` is missing from the archive", name))?;
+        let invalid = || format!("`{}` is damaged", name);
+
+        let header = file.offset as usize;
+        let name_length = read_u16(&self.data, header + 26).ok_or_else(invalid)?;
+        let extra_length = read_u16(&self.data, header + 28).ok_or_else(invalid)?;
+        let start = header + 30 + usize::from(name_length) + usize::from(extra_length);
+        let contents = self
+            .data
+            .get(start..start + file.compressed_size as usize)
+            .ok_or_else(invalid)?;
+
+        let size = file.size as usize;
+        let contents = match file.method {
+            0 => contents.to_vec(),
+            // a file that inflates past its size is damaged or a zip bomb
+            8 => miniz_oxide::inflate::decompress_to_vec_with_limit(contents, size)
+                .map_err(|_| invalid())?,
+            method => {
+                return Err(format!(
+                    "`{}` is compressed in a way that can't be read ({})",
+                    name, method
+                ))
+            }
+        };
+        if contents.len() != size || crc32(&contents) != file.crc {
+            return Err(invalid());
+        }
+
+        Ok(contents)
+    }
+
+    /// The file as text, for the XML files documents are made of
+    pub fn text(&self, name: &str) -> Result<String, String> {
+        String::from_utf8(self.file(name)?).map_err(|_| format!("`{}` is not UTF-8", name))
+    }
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    let bytes = data.get(offset..offset + 2)?;
+
+    Some(u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?;
+
+    Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// The CRC-32 the format checks files with, a bit at a time
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0_u32;
+
+    for byte in data {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+
+    !crc
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn computes_crc32() {
+        assert_eq!(0, crc32(b""));
+        assert_eq!(0xcbf4_3926, crc32(b"123456789"));
+    }
+
+    #[test]
+    pub fn reads_back_written_files() {
+        let mut writer = ZipWriter::new(vec![]);
+        writer.add_file("a.txt", b"hi").unwrap();
+        writer.add_file("dir/b.xml", b"<b/>").unwrap();
+        let archive = ZipArchive::new(writer.finish().unwrap()).unwrap();
+
+        assert_eq!(vec!["a.txt", "dir/b.xml"], archive.names());
+        assert_eq!(Ok("<b/>".to_string()), archive.text("dir/b.xml"));
+        assert!(archive.file("c.txt").is_err());
+    }
+
+    #[test]
+    pub fn inflates_deflated_files() {
+        // "hello hello hello" deflated
+        let deflated = miniz_oxide::deflate::compress_to_vec(b"hello hello hello", 6);
+        let mut archive = ZipArchive::new({
+            let mut writer = ZipWriter::new(vec![]);
+            writer.add_file("a.txt", &deflated).unwrap();
+            writer.finish().unwrap()
+        })
+        .unwrap();
+        archive.files[0].method = 8;
+        archive.files[0].crc = crc32(b"hello hello hello");
+        archive.files[0].size = 17;
+
+        assert_eq!(Ok(b"hello hello hello".to_vec()), archive.file("a.txt"));
+        assert!(ZipArchive::new(b"not a zip".to_vec()).is_err());
+    }
+
+    #[test]
+    pub fn refuses_files_larger_than_they_claim() {
+        let deflated = miniz_oxide::deflate::compress_to_vec(&[0; 4096], 6);
+        let mut archive = ZipArchive::new({
+            let mut writer = ZipWriter::new(vec![]);
+            writer.add_file("bomb.bin", &deflated).unwrap();
+            writer.finish().unwrap()
+        })
+        .unwrap();
+        archive.files[0].method = 8;
+        archive.files[0].crc = crc32(&[0; 4096]);
+        archive.files[0].size = 1024;
+
+        assert_eq!(
+            Err("`bomb.bin` is damaged".into()),
+            archive.file("bomb.bin")
+        );
+    }
+
+    #[test]
+    pub fn refuses_files_that_fail_their_crc() {
+        let mut writer = ZipWriter::new(vec![]);
+        writer.add_file("a.txt", b"hi").unwrap();
+        let mut data = writer.finish().unwrap();
+        // the data right after the local header and the name
+        data[35] = b'H';
+        let archive = ZipArchive::new(data).unwrap();
+
+        assert_eq!(Err("`a.txt` is damaged".into()), archive.file("a.txt"));
+    }
+
+    #[test]
+    pub fn writes_stored_files_and_the_central_directory() {
+        let mut writer = ZipWriter::new(vec![]);
+        writer.add_file("a.txt", b"hi").unwrap();
+        let archive = writer.finish().unwrap();
+
+        // the local header, the name and the data
+        assert_eq!(b"PK\x03\x04", &archive[..4]);
+        assert_eq!(b"a.txthi", &archive[30..37]);
+        // the central directory points back at the local header
+        assert_eq!(b"PK\x01\x02", &archive[37..41]);
+        assert_eq!(0_u32.to_le_bytes(), archive[79..83]);
+        // the end record counts one file and finds the directory after it
+        let end = &archive[archive.len() - 22..];
+        assert_eq!(b"PK\x05\x06", &end[..4]);
+        assert_eq!(1_u16.to_le_bytes(), end[10..12]);
+        assert_eq!(37_u32.to_le_bytes(), end[16..20]);
+    }
+}